@@ -1,10 +1,11 @@
+use std::fmt::Write as _;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use assistant_slash_command::{
-    ArgumentCompletion, SlashCommand, SlashCommandOutput, SlashCommandOutputSection,
-    SlashCommandResult,
+    AfterCompletion, ArgumentCompletion, SlashCommand, SlashCommandOutput,
+    SlashCommandOutputSection, SlashCommandResult,
 };
 use gpui::{App, Entity, Task, WeakEntity};
 use language::{BufferSnapshot, CodeLabel, LspAdapterDelegate};
@@ -17,6 +18,7 @@ use super::create_label_for_command;
 pub struct TerminalSlashCommand;
 
 const LINE_COUNT_ARG: &str = "--line-count";
+const LAST_FAILED_ARG: &str = "--last-failed";
 
 const DEFAULT_CONTEXT_LINES: usize = 50;
 
@@ -26,7 +28,7 @@ impl SlashCommand for TerminalSlashCommand {
     }
 
     fn label(&self, cx: &App) -> CodeLabel {
-        create_label_for_command("terminal", &[LINE_COUNT_ARG], cx)
+        create_label_for_command("terminal", &[LINE_COUNT_ARG, LAST_FAILED_ARG], cx)
     }
 
     fn description(&self) -> String {
@@ -51,13 +53,22 @@ impl SlashCommand for TerminalSlashCommand {
 
     fn complete_argument(
         self: Arc<Self>,
-        _arguments: &[String],
+        arguments: &[String],
         _cancel: Arc<AtomicBool>,
         _workspace: Option<WeakEntity<Workspace>>,
         _window: &mut Window,
         _cx: &mut App,
     ) -> Task<Result<Vec<ArgumentCompletion>>> {
-        Task::ready(Ok(Vec::new()))
+        let query = arguments.last().map(String::as_str).unwrap_or_default();
+        if !LAST_FAILED_ARG.starts_with(query) {
+            return Task::ready(Ok(Vec::new()));
+        }
+        Task::ready(Ok(vec![ArgumentCompletion {
+            label: LAST_FAILED_ARG.into(),
+            new_text: LAST_FAILED_ARG.to_string(),
+            after_completion: AfterCompletion::Run,
+            replace_previous_arguments: true,
+        }]))
     }
 
     fn run(
@@ -78,6 +89,10 @@ impl SlashCommand for TerminalSlashCommand {
             return Task::ready(Err(anyhow::anyhow!("no active terminal")));
         };
 
+        if arguments.iter().any(|argument| argument == LAST_FAILED_ARG) {
+            return Task::ready(last_failed_command_output(&active_terminal, cx));
+        }
+
         let line_count = arguments
             .get(0)
             .and_then(|s| s.parse::<usize>().ok())
@@ -108,6 +123,45 @@ impl SlashCommand for TerminalSlashCommand {
     }
 }
 
+/// Inserts the last failed command, with its exit code and output, as reported by the terminal shell integration.
+fn last_failed_command_output(
+    terminal_view: &Entity<TerminalView>,
+    cx: &App,
+) -> SlashCommandResult {
+    let terminal = terminal_view.read(cx).entity().read(cx);
+    let failed_command = terminal
+        .command_blocks()
+        .iter()
+        .rev()
+        .find(|block| block.is_failed())
+        .context("no failed command in the active terminal, or its shell integration is off")?;
+    let output = terminal
+        .command_output(failed_command)
+        .context("the output of the failed command is no longer in the terminal history")?;
+
+    let mut text = String::new();
+    if let Some(command) = &failed_command.command {
+        writeln!(text, "Terminal command: `{command}`").ok();
+    }
+    if let Some(exit_code) = failed_command.exit_code {
+        writeln!(text, "Failed with exit code {exit_code}, output:").ok();
+    }
+    text.push_str(&output);
+    let range = 0..text.len();
+
+    Ok(SlashCommandOutput {
+        text,
+        sections: vec![SlashCommandOutputSection {
+            range,
+            icon: IconName::Terminal,
+            label: "Failed Command".into(),
+            metadata: None,
+        }],
+        run_commands_in_text: false,
+    }
+    .to_event_stream())
+}
+
 fn resolve_active_terminal(
     workspace: &Entity<Workspace>,
     cx: &mut App,
@@ -0,0 +1,977 @@
+mod call_hierarchy_panel_settings;
+
+use std::{ops::Range, sync::Arc};
+
+use anyhow::Context as _;
+use call_hierarchy_panel_settings::{CallHierarchyPanelDockPosition, CallHierarchyPanelSettings};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    actions, px, uniform_list, Action, App, AsyncWindowContext, Context, Entity, EventEmitter,
+    FocusHandle, Focusable, KeyContext, Pixels, Render, ScrollStrategy, SharedString, Task,
+    UniformListScrollHandle, WeakEntity, Window,
+};
+use language::{Buffer, OffsetRangeExt as _};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrev};
+use project::{CallHierarchyCall, CallHierarchyItem, Fs, Location, Project};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+actions!(
+    call_hierarchy_panel,
+    [
+        CollapseSelectedEntry,
+        ExpandSelectedEntry,
+        ShowIncomingCalls,
+        ShowOutgoingCalls,
+        ShowSubtypes,
+        ShowSupertypes,
+        ToggleDirection,
+        ToggleFocus,
+    ]
+);
+
+const CALL_HIERARCHY_PANEL_KEY: &str = "CallHierarchyPanel";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HierarchyKind {
+    /// Shows the callers of each symbol.
+    IncomingCalls,
+    /// Shows the callees of each symbol.
+    OutgoingCalls,
+    /// Shows the types that each type extends or implements.
+    Supertypes,
+    /// Shows the types that extend or implement each type.
+    Subtypes,
+}
+
+impl HierarchyKind {
+    fn is_type_hierarchy(self) -> bool {
+        matches!(self, Self::Supertypes | Self::Subtypes)
+    }
+
+    fn reversed(self) -> Self {
+        match self {
+            Self::IncomingCalls => Self::OutgoingCalls,
+            Self::OutgoingCalls => Self::IncomingCalls,
+            Self::Supertypes => Self::Subtypes,
+            Self::Subtypes => Self::Supertypes,
+        }
+    }
+}
+
+pub struct CallHierarchyPanel {
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    pending_serialization: Task<Option<()>>,
+    kind: HierarchyKind,
+    nodes: Vec<HierarchyNode>,
+    roots: Vec<usize>,
+    visible_entries: Vec<usize>,
+    selected_node: Option<usize>,
+    prepare_task: Option<Task<()>>,
+    error: Option<SharedString>,
+}
+
+struct HierarchyNode {
+    item: CallHierarchyItem,
+    /// Where the parent node calls this node, or where this node calls the parent,
+    /// depending on the direction. Always empty in type hierarchies.
+    call_sites: Vec<Location>,
+    parent: Option<usize>,
+    depth: usize,
+    expanded: bool,
+    children: Children,
+}
+
+enum Children {
+    Unloaded,
+    Loading(#[allow(dead_code)] Task<()>),
+    Loaded(Vec<usize>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedCallHierarchyPanel {
+    width: Option<Pixels>,
+}
+
+pub fn init(cx: &mut App) {
+    CallHierarchyPanelSettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<CallHierarchyPanel>(window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowIncomingCalls, window, cx| {
+            show_hierarchy(workspace, HierarchyKind::IncomingCalls, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowOutgoingCalls, window, cx| {
+            show_hierarchy(workspace, HierarchyKind::OutgoingCalls, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowSupertypes, window, cx| {
+            show_hierarchy(workspace, HierarchyKind::Supertypes, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowSubtypes, window, cx| {
+            show_hierarchy(workspace, HierarchyKind::Subtypes, window, cx);
+        });
+    })
+    .detach();
+}
+
+/// Shows the hierarchy of the symbol under the newest cursor of the active editor.
+fn show_hierarchy(
+    workspace: &mut Workspace,
+    kind: HierarchyKind,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+        let head = editor.selections.newest_anchor().head();
+        editor.buffer().read(cx).text_anchor_for_position(head, cx)
+    }) else {
+        return;
+    };
+    if let Some(panel) = workspace.focus_panel::<CallHierarchyPanel>(window, cx) {
+        panel.update(cx, |panel, cx| {
+            panel.show_hierarchy(buffer, position, kind, window, cx)
+        });
+    }
+}
+
+/// Loads the children of a node: its calls, or its related types without any call sites.
+async fn load_children(
+    project: Entity<Project>,
+    item: CallHierarchyItem,
+    kind: HierarchyKind,
+    cx: &mut AsyncWindowContext,
+) -> anyhow::Result<Vec<CallHierarchyCall>> {
+    let types = match kind {
+        HierarchyKind::IncomingCalls => {
+            return project
+                .update(cx, |project, cx| project.incoming_calls(item, cx))?
+                .await;
+        }
+        HierarchyKind::OutgoingCalls => {
+            return project
+                .update(cx, |project, cx| project.outgoing_calls(item, cx))?
+                .await;
+        }
+        HierarchyKind::Supertypes => project.update(cx, |project, cx| project.supertypes(item, cx)),
+        HierarchyKind::Subtypes => project.update(cx, |project, cx| project.subtypes(item, cx)),
+    }?
+    .await?;
+    Ok(types
+        .into_iter()
+        .map(|item| CallHierarchyCall {
+            item,
+            call_sites: Vec::new(),
+        })
+        .collect())
+}
+
+impl CallHierarchyPanel {
+    pub async fn load(
+        workspace: WeakEntity<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<Entity<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(CALL_HIERARCHY_PANEL_KEY) })
+            .await
+            .context("loading call hierarchy panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedCallHierarchyPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update_in(&mut cx, |workspace, window, cx| {
+            let panel = Self::new(workspace, window, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(
+        workspace: &mut Workspace,
+        _window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace = cx.entity().downgrade();
+        cx.new(|cx| Self {
+            fs,
+            width: None,
+            project,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            pending_serialization: Task::ready(None),
+            kind: HierarchyKind::IncomingCalls,
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            visible_entries: Vec::new(),
+            selected_node: None,
+            prepare_task: None,
+            error: None,
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut Context<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        CALL_HIERARCHY_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedCallHierarchyPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Replaces the panel's contents with the call or type hierarchy of the symbol at the given
+    /// position.
+    pub fn show_hierarchy(
+        &mut self,
+        buffer: Entity<Buffer>,
+        position: language::Anchor,
+        kind: HierarchyKind,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let prepare = self.project.update(cx, |project, cx| {
+            if kind.is_type_hierarchy() {
+                project.prepare_type_hierarchy(&buffer, position, cx)
+            } else {
+                project.prepare_call_hierarchy(&buffer, position, cx)
+            }
+        });
+        self.kind = kind;
+        self.error = None;
+        self.prepare_task = Some(cx.spawn_in(window, |panel, mut cx| async move {
+            let result = prepare.await;
+            panel
+                .update_in(&mut cx, |panel, window, cx| {
+                    panel.prepare_task = None;
+                    match result {
+                        Ok(items) if items.is_empty() => {
+                            let message = if kind.is_type_hierarchy() {
+                                "No type hierarchy found at the cursor"
+                            } else {
+                                "No call hierarchy found at the cursor"
+                            };
+                            panel.error = Some(message.into());
+                            panel.set_roots(Vec::new(), window, cx);
+                        }
+                        Ok(items) => panel.set_roots(items, window, cx),
+                        Err(error) => {
+                            panel.error = Some(format!("{error:#}").into());
+                            panel.set_roots(Vec::new(), window, cx);
+                        }
+                    }
+                })
+                .ok();
+        }));
+        cx.notify();
+    }
+
+    fn set_roots(
+        &mut self,
+        items: Vec<CallHierarchyItem>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.nodes.clear();
+        self.roots.clear();
+        self.selected_node = None;
+        for item in items {
+            let ix = self.push_node(item, Vec::new(), None);
+            self.roots.push(ix);
+        }
+        for ix in self.roots.clone() {
+            self.expand(ix, window, cx);
+        }
+        self.selected_node = self.roots.first().copied();
+        self.update_visible_entries(cx);
+    }
+
+    /// Switches between incoming and outgoing calls, or between supertypes and subtypes,
+    /// keeping the current roots.
+    fn set_kind(&mut self, kind: HierarchyKind, window: &mut Window, cx: &mut Context<Self>) {
+        if self.kind == kind {
+            return;
+        }
+        self.kind = kind;
+        let roots = self
+            .roots
+            .iter()
+            .map(|ix| self.nodes[*ix].item.clone())
+            .collect();
+        self.set_roots(roots, window, cx);
+    }
+
+    fn push_node(
+        &mut self,
+        item: CallHierarchyItem,
+        call_sites: Vec<Location>,
+        parent: Option<usize>,
+    ) -> usize {
+        let depth = parent.map_or(0, |parent| self.nodes[parent].depth + 1);
+        self.nodes.push(HierarchyNode {
+            item,
+            call_sites,
+            parent,
+            depth,
+            expanded: false,
+            children: Children::Unloaded,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn expand(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(node) = self.nodes.get_mut(ix) else {
+            return;
+        };
+        node.expanded = true;
+        if let Children::Unloaded = node.children {
+            let item = node.item.clone();
+            let kind = self.kind;
+            let project = self.project.clone();
+            self.nodes[ix].children =
+                Children::Loading(cx.spawn_in(window, |panel, mut cx| async move {
+                    let calls = load_children(project, item, kind, &mut cx).await;
+                    panel
+                        .update(&mut cx, |panel, cx| {
+                            panel.set_children(ix, calls.log_err().unwrap_or_default(), cx)
+                        })
+                        .ok();
+                }));
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn collapse(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if let Some(node) = self.nodes.get_mut(ix) {
+            node.expanded = false;
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn toggle_expanded(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if self.nodes[ix].expanded {
+            self.collapse(ix, cx);
+        } else {
+            self.expand(ix, window, cx);
+        }
+    }
+
+    fn set_children(&mut self, ix: usize, calls: Vec<CallHierarchyCall>, cx: &mut Context<Self>) {
+        let children = calls
+            .into_iter()
+            .map(|call| self.push_node(call.item, call.call_sites, Some(ix)))
+            .collect();
+        self.nodes[ix].children = Children::Loaded(children);
+        self.update_visible_entries(cx);
+    }
+
+    fn update_visible_entries(&mut self, cx: &mut Context<Self>) {
+        let mut visible_entries = Vec::new();
+        let mut stack = self.roots.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(ix) = stack.pop() {
+            visible_entries.push(ix);
+            let node = &self.nodes[ix];
+            if let (true, Children::Loaded(children)) = (node.expanded, &node.children) {
+                stack.extend(children.iter().rev().copied());
+            }
+        }
+        self.visible_entries = visible_entries;
+        cx.notify();
+    }
+
+    fn selected_entry_ix(&self) -> Option<usize> {
+        let selected = self.selected_node?;
+        self.visible_entries.iter().position(|ix| *ix == selected)
+    }
+
+    fn select_entry_at(&mut self, entry_ix: usize, cx: &mut Context<Self>) {
+        if let Some(ix) = self.visible_entries.get(entry_ix) {
+            self.selected_node = Some(*ix);
+            self.scroll_handle
+                .scroll_to_item(entry_ix, ScrollStrategy::Center);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let entry_ix = self.selected_entry_ix().map_or(0, |ix| ix + 1);
+        self.select_entry_at(entry_ix, cx);
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, _: &mut Window, cx: &mut Context<Self>) {
+        let entry_ix = self
+            .selected_entry_ix()
+            .map_or(0, |ix| ix.saturating_sub(1));
+        self.select_entry_at(entry_ix, cx);
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_entry_at(0, cx);
+    }
+
+    fn select_last(&mut self, _: &SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_entry_at(self.visible_entries.len().saturating_sub(1), cx);
+    }
+
+    fn expand_selected_entry(
+        &mut self,
+        _: &ExpandSelectedEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(ix) = self.selected_node {
+            self.expand(ix, window, cx);
+        }
+    }
+
+    fn collapse_selected_entry(
+        &mut self,
+        _: &CollapseSelectedEntry,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.selected_node else {
+            return;
+        };
+        if self.nodes[ix].expanded {
+            self.collapse(ix, cx);
+        } else if let Some(parent) = self.nodes[ix].parent {
+            self.selected_node = Some(parent);
+            if let Some(entry_ix) = self.selected_entry_ix() {
+                self.select_entry_at(entry_ix, cx);
+            }
+        }
+    }
+
+    fn toggle_direction(
+        &mut self,
+        _: &ToggleDirection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.set_kind(self.kind.reversed(), window, cx);
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_node {
+            self.open_node(ix, true, window, cx);
+        }
+    }
+
+    /// Opens the call site of an incoming call, or the definition of anything else.
+    fn open_node(&mut self, ix: usize, focus: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(node) = self.nodes.get(ix) else {
+            return;
+        };
+        let location = match self.kind {
+            HierarchyKind::IncomingCalls => node.call_sites.first().cloned(),
+            _ => None,
+        }
+        .unwrap_or_else(|| Location {
+            buffer: node.item.range.buffer.clone(),
+            range: node.item.selection_range.clone(),
+        });
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            let pane = workspace.active_pane().clone();
+            let editor = workspace.open_project_item::<Editor>(
+                pane,
+                location.buffer.clone(),
+                true,
+                focus,
+                window,
+                cx,
+            );
+            editor.update(cx, |editor, cx| {
+                let range = location.range.to_offset(location.buffer.read(cx));
+                editor.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                    s.select_ranges([range])
+                });
+            });
+        });
+    }
+
+    fn render_entry(&self, ix: usize, indent_size: f32, cx: &Context<Self>) -> impl IntoElement {
+        let node = &self.nodes[ix];
+        let toggle = match &node.children {
+            Children::Loaded(children) if children.is_empty() => None,
+            _ => Some(node.expanded),
+        };
+        let loading = node.expanded && matches!(node.children, Children::Loading(_));
+        let call_count = node.call_sites.len();
+
+        ListItem::new(ix)
+            .indent_level(node.depth)
+            .indent_step_size(px(indent_size))
+            .spacing(ListItemSpacing::Sparse)
+            .toggle(toggle)
+            .toggle_state(self.selected_node == Some(ix))
+            .on_toggle(cx.listener(move |panel, _, window, cx| {
+                panel.toggle_expanded(ix, window, cx);
+            }))
+            .on_click(
+                cx.listener(move |panel, event: &gpui::ClickEvent, window, cx| {
+                    panel.selected_node = Some(ix);
+                    panel.open_node(ix, event.down.click_count > 1, window, cx);
+                    cx.notify();
+                }),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(node.item.name.clone()))
+                    .when_some(node.item.detail.clone(), |this, detail| {
+                        this.child(
+                            Label::new(detail)
+                                .color(Color::Muted)
+                                .size(LabelSize::Small)
+                                .truncate(),
+                        )
+                    }),
+            )
+            .end_slot(
+                h_flex()
+                    .gap_1()
+                    .when(call_count > 1, |this| {
+                        this.child(
+                            Label::new(format!("×{call_count}"))
+                                .color(Color::Muted)
+                                .size(LabelSize::Small),
+                        )
+                    })
+                    .when(loading, |this| {
+                        this.child(
+                            Label::new("Loading…")
+                                .color(Color::Muted)
+                                .size(LabelSize::Small),
+                        )
+                    }),
+            )
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let kind_button =
+            |id: &'static str, label: &'static str, kind: HierarchyKind, cx: &mut Context<Self>| {
+                Button::new(id, label)
+                    .label_size(LabelSize::Small)
+                    .toggle_state(self.kind == kind)
+                    .tooltip(move |window, cx| {
+                        Tooltip::for_action("Toggle Direction", &ToggleDirection, window, cx)
+                    })
+                    .on_click(cx.listener(move |panel, _, window, cx| {
+                        panel.set_kind(kind, window, cx);
+                    }))
+            };
+
+        let (first, second) = if self.kind.is_type_hierarchy() {
+            (
+                kind_button("supertypes", "Supertypes", HierarchyKind::Supertypes, cx),
+                kind_button("subtypes", "Subtypes", HierarchyKind::Subtypes, cx),
+            )
+        } else {
+            (
+                kind_button(
+                    "incoming-calls",
+                    "Incoming Calls",
+                    HierarchyKind::IncomingCalls,
+                    cx,
+                ),
+                kind_button(
+                    "outgoing-calls",
+                    "Outgoing Calls",
+                    HierarchyKind::OutgoingCalls,
+                    cx,
+                ),
+            )
+        };
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(first)
+            .child(second)
+    }
+
+    fn render_empty_state(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let message: SharedString = if self.prepare_task.is_some() {
+            "Loading hierarchy…".into()
+        } else if let Some(error) = &self.error {
+            error.clone()
+        } else {
+            "Show the calls or the related types of a symbol to explore its hierarchy.".into()
+        };
+        v_flex()
+            .id("empty-call-hierarchy-panel")
+            .size_full()
+            .p_4()
+            .gap_2()
+            .items_center()
+            .justify_center()
+            .child(Label::new(message).color(Color::Muted))
+            .when(self.prepare_task.is_none(), |this| {
+                this.child(
+                    Button::new("show-incoming-calls", "Show Incoming Calls")
+                        .key_binding(KeyBinding::for_action_in(
+                            &ShowIncomingCalls,
+                            &self.focus_handle,
+                            window,
+                        ))
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(ShowIncomingCalls.boxed_clone(), cx)
+                        }),
+                )
+            })
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("CallHierarchyPanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+}
+
+impl Panel for CallHierarchyPanel {
+    fn persistent_name() -> &'static str {
+        "Call Hierarchy Panel"
+    }
+
+    fn position(&self, _: &Window, cx: &App) -> DockPosition {
+        match CallHierarchyPanelSettings::get_global(cx).dock {
+            CallHierarchyPanelDockPosition::Left => DockPosition::Left,
+            CallHierarchyPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        settings::update_settings_file::<CallHierarchyPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => {
+                        CallHierarchyPanelDockPosition::Left
+                    }
+                    DockPosition::Right => CallHierarchyPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, _: &Window, cx: &App) -> Pixels {
+        self.width
+            .unwrap_or_else(|| CallHierarchyPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, _: &Window, cx: &App) -> Option<IconName> {
+        CallHierarchyPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Route)
+    }
+
+    fn icon_tooltip(&self, _window: &Window, _: &App) -> Option<&'static str> {
+        Some("Call Hierarchy Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        9
+    }
+}
+
+impl Focusable for CallHierarchyPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for CallHierarchyPanel {}
+
+impl Render for CallHierarchyPanel {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let indent_size = CallHierarchyPanelSettings::get_global(cx).indent_size;
+
+        v_flex()
+            .id("call-hierarchy-panel")
+            .size_full()
+            .overflow_hidden()
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::toggle_direction))
+            .child(self.render_header(cx))
+            .map(|this| {
+                if self.visible_entries.is_empty() {
+                    this.child(self.render_empty_state(window, cx))
+                } else {
+                    this.child(
+                        uniform_list(
+                            cx.entity(),
+                            "call-hierarchy-entries",
+                            self.visible_entries.len(),
+                            move |panel, range: Range<usize>, _window, cx| {
+                                panel.visible_entries[range]
+                                    .iter()
+                                    .map(|ix| panel.render_entry(*ix, indent_size, cx))
+                                    .collect()
+                            },
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures::StreamExt as _;
+    use gpui::{TestAppContext, VisualTestContext};
+    use language::{tree_sitter_rust, FakeLspAdapter, Language, LanguageConfig, LanguageMatcher};
+    use pretty_assertions::assert_eq;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_lazy_expansion(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "lib.rs": "fn a() { b() }\nfn b() { c() }\nfn c() {}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(rust_lang());
+        let mut fake_servers = language_registry.register_fake_lsp(
+            "Rust",
+            FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                    ..lsp::ServerCapabilities::default()
+                },
+                ..FakeLspAdapter::default()
+            },
+        );
+        let (buffer, _handle) = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer_with_lsp("/dir/lib.rs", cx)
+            })
+            .await
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+
+        fn lsp_item(name: &str, line: u32) -> lsp::CallHierarchyItem {
+            lsp::CallHierarchyItem {
+                name: name.to_string(),
+                kind: lsp::SymbolKind::FUNCTION,
+                tags: None,
+                detail: None,
+                uri: lsp::Url::from_file_path("/dir/lib.rs").unwrap(),
+                range: lsp::Range::new(lsp::Position::new(line, 0), lsp::Position::new(line, 9)),
+                selection_range: lsp::Range::new(
+                    lsp::Position::new(line, 3),
+                    lsp::Position::new(line, 4),
+                ),
+                data: None,
+            }
+        }
+        fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(|_, _| async move {
+            Ok(Some(vec![lsp_item("c", 2)]))
+        });
+        let queried_items = Arc::new(Mutex::new(Vec::new()));
+        fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>({
+            let queried_items = queried_items.clone();
+            move |params, _| {
+                queried_items.lock().unwrap().push(params.item.name.clone());
+                let caller = match params.item.name.as_str() {
+                    "c" => Some(lsp_item("b", 1)),
+                    "b" => Some(lsp_item("a", 0)),
+                    _ => None,
+                };
+                async move {
+                    Ok(Some(
+                        caller
+                            .into_iter()
+                            .map(|from| lsp::CallHierarchyIncomingCall {
+                                from,
+                                from_ranges: Vec::new(),
+                            })
+                            .collect(),
+                    ))
+                }
+            }
+        });
+
+        let workspace =
+            cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let panel = workspace
+            .update(cx, |_, window, cx| {
+                cx.spawn_in(window, CallHierarchyPanel::load)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let position = buffer.read_with(cx, |buffer, _| {
+            buffer.anchor_before(language::Point::new(2, 3))
+        });
+        panel.update_in(cx, |panel, window, cx| {
+            panel.show_hierarchy(buffer, position, HierarchyKind::IncomingCalls, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            panel.read_with(cx, |panel, _| visible_entries(panel)),
+            vec!["c  <==== selected", "  b"],
+        );
+        assert_eq!(
+            *queried_items.lock().unwrap(),
+            vec!["c"],
+            "Only the roots should be expanded, and their children loaded, right away"
+        );
+
+        panel.update_in(cx, |panel, window, cx| {
+            panel.select_next(&SelectNext, window, cx);
+            panel.expand_selected_entry(&ExpandSelectedEntry, window, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            panel.read_with(cx, |panel, _| visible_entries(panel)),
+            vec!["c", "  b  <==== selected", "    a"],
+        );
+        assert_eq!(*queried_items.lock().unwrap(), vec!["c", "b"]);
+
+        panel.update_in(cx, |panel, window, cx| {
+            panel.collapse_selected_entry(&CollapseSelectedEntry, window, cx);
+        });
+        assert_eq!(
+            panel.read_with(cx, |panel, _| visible_entries(panel)),
+            vec!["c", "  b  <==== selected"],
+        );
+        panel.update_in(cx, |panel, window, cx| {
+            panel.expand_selected_entry(&ExpandSelectedEntry, window, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            panel.read_with(cx, |panel, _| visible_entries(panel)),
+            vec!["c", "  b  <==== selected", "    a"],
+        );
+        assert_eq!(
+            *queried_items.lock().unwrap(),
+            vec!["c", "b"],
+            "Expanding a node again should reuse its loaded children"
+        );
+    }
+
+    fn visible_entries(panel: &CallHierarchyPanel) -> Vec<String> {
+        panel
+            .visible_entries
+            .iter()
+            .map(|ix| {
+                let node = &panel.nodes[*ix];
+                let mut entry = format!("{}{}", "  ".repeat(node.depth), node.item.name);
+                if panel.selected_node == Some(*ix) {
+                    entry.push_str("  <==== selected");
+                }
+                entry
+            })
+            .collect()
+    }
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        ))
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+
+            theme::init(theme::LoadThemes::JustBase, cx);
+
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            super::init(cx);
+        });
+    }
+}
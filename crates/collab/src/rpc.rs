@@ -298,6 +298,16 @@ impl Server {
             .add_request_handler(forward_read_only_project_request::<proto::GetHover>)
             .add_request_handler(forward_read_only_project_request::<proto::GetDefinition>)
             .add_request_handler(forward_read_only_project_request::<proto::GetTypeDefinition>)
+            .add_request_handler(forward_read_only_project_request::<proto::PrepareCallHierarchy>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetIncomingCalls>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetOutgoingCalls>)
+            .add_request_handler(forward_read_only_project_request::<proto::PrepareTypeHierarchy>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSupertypes>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSubtypes>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSemanticTokens>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetCodeLens>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetFoldingRanges>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSelectionRanges>)
             .add_request_handler(forward_read_only_project_request::<proto::GetReferences>)
             .add_request_handler(forward_find_search_candidates_request)
             .add_request_handler(forward_read_only_project_request::<proto::GetDocumentHighlights>)
@@ -324,6 +334,7 @@ impl Server {
             )
             .add_request_handler(forward_mutating_project_request::<proto::GetCodeActions>)
             .add_request_handler(forward_mutating_project_request::<proto::ApplyCodeAction>)
+            .add_request_handler(forward_mutating_project_request::<proto::ApplyCodeLens>)
             .add_request_handler(forward_mutating_project_request::<proto::PrepareRename>)
             .add_request_handler(forward_mutating_project_request::<proto::PerformRename>)
             .add_request_handler(forward_mutating_project_request::<proto::ReloadBuffers>)
@@ -394,6 +405,23 @@ impl Server {
             .add_request_handler(forward_mutating_project_request::<proto::Stage>)
             .add_request_handler(forward_mutating_project_request::<proto::Unstage>)
             .add_request_handler(forward_mutating_project_request::<proto::Commit>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetRemotes>)
+            .add_request_handler(forward_mutating_project_request::<proto::Fetch>)
+            .add_request_handler(forward_mutating_project_request::<proto::Pull>)
+            .add_request_handler(forward_mutating_project_request::<proto::Push>)
+            .add_request_handler(forward_askpass_request)
+            .add_message_handler(forward_git_remote_progress)
+            .add_request_handler(forward_mutating_project_request::<proto::Stash>)
+            .add_request_handler(forward_mutating_project_request::<proto::StashApply>)
+            .add_request_handler(forward_mutating_project_request::<proto::StashPop>)
+            .add_request_handler(forward_mutating_project_request::<proto::StashDrop>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetStashEntries>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetStashDiff>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetCommitLog>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetCommitDiff>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetChangedPathsSince>)
+            .add_request_handler(forward_mutating_project_request::<proto::StageLines>)
+            .add_request_handler(forward_mutating_project_request::<proto::UnstageLines>)
             .add_message_handler(broadcast_project_message_from_host::<proto::AdvertiseContexts>)
             .add_message_handler(update_context)
             .add_request_handler({
@@ -2185,6 +2213,51 @@ async fn create_buffer_for_peer(
     Ok(())
 }
 
+/// Forward a credential prompt of a git remote operation from the host to the guest
+/// that started the operation.
+async fn forward_askpass_request(
+    request: proto::AskPassRequest,
+    response: Response<proto::AskPassRequest>,
+    session: Session,
+) -> Result<()> {
+    session
+        .db()
+        .await
+        .check_user_is_project_host(
+            ProjectId::from_proto(request.project_id),
+            session.connection_id,
+        )
+        .await?;
+    let peer_id = request.peer_id.ok_or_else(|| anyhow!("invalid peer id"))?;
+    let payload = session
+        .peer
+        .forward_request(session.connection_id, peer_id.into(), request)
+        .await?;
+    response.send(payload)?;
+    Ok(())
+}
+
+/// Forward the progress of a git remote operation from the host to the guest that
+/// started the operation.
+async fn forward_git_remote_progress(
+    request: proto::GitRemoteProgress,
+    session: Session,
+) -> Result<()> {
+    session
+        .db()
+        .await
+        .check_user_is_project_host(
+            ProjectId::from_proto(request.project_id),
+            session.connection_id,
+        )
+        .await?;
+    let peer_id = request.peer_id.ok_or_else(|| anyhow!("invalid peer id"))?;
+    session
+        .peer
+        .forward_send(session.connection_id, peer_id.into(), request)?;
+    Ok(())
+}
+
 /// Notify other participants that a buffer has been updated. This is
 /// allowed for guests as long as the update is limited to selections.
 async fn update_buffer(
@@ -15,6 +15,8 @@ use fs::{FakeFs, Fs as _, RemoveOptions};
 use futures::{channel::mpsc, StreamExt as _};
 use prompt_library::PromptBuilder;
 
+use git::askpass::NoopRemoteDelegate;
+use git::repository::PushOptions;
 use git::status::{FileStatus, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode};
 use gpui::{
     px, size, App, BackgroundExecutor, Entity, Modifiers, MouseButton, MouseDownEvent,
@@ -2864,6 +2866,116 @@ async fn test_git_branch_name(
     });
 }
 
+#[gpui::test]
+async fn test_git_remote_operations(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "a",
+            }),
+        )
+        .await;
+    let dot_git = Path::new("/dir/.git");
+    client_a.fs().set_branch_name(dot_git, Some("main"));
+
+    let (project_a, _) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let repository_b =
+        project_b.read_with(cx_b, |project, cx| project.active_repository(cx).unwrap());
+
+    let error = repository_b
+        .fetch(Arc::new(NoopRemoteDelegate))
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(
+        format!("{error:#}").contains("no remote repository specified"),
+        "unexpected error: {error:#}"
+    );
+
+    client_a.fs().with_git_state(dot_git, false, |state| {
+        state.remotes = vec!["origin".to_string()];
+    });
+    repository_b
+        .fetch(Arc::new(NoopRemoteDelegate))
+        .await
+        .unwrap()
+        .unwrap();
+
+    let error = repository_b
+        .push(
+            "main".into(),
+            "upstream".into(),
+            Some(PushOptions::ForceSetUpstream),
+            Arc::new(NoopRemoteDelegate),
+        )
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(
+        format!("{error:#}").contains("no such remote: upstream"),
+        "unexpected error: {error:#}"
+    );
+
+    repository_b
+        .push(
+            "main".into(),
+            "origin".into(),
+            Some(PushOptions::ForceSetUpstream),
+            Arc::new(NoopRemoteDelegate),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    client_a.fs().with_git_state(dot_git, false, |state| {
+        assert_eq!(
+            state.upstreams["main"].ref_name.as_ref(),
+            "refs/remotes/origin/main"
+        );
+    });
+
+    let error = repository_b
+        .pull(
+            "main".into(),
+            "upstream".into(),
+            Arc::new(NoopRemoteDelegate),
+        )
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(
+        format!("{error:#}").contains("no such remote: upstream"),
+        "unexpected error: {error:#}"
+    );
+
+    repository_b
+        .pull("main".into(), "origin".into(), Arc::new(NoopRemoteDelegate))
+        .await
+        .unwrap()
+        .unwrap();
+}
+
 #[gpui::test]
 async fn test_git_status_sync(
     executor: BackgroundExecutor,
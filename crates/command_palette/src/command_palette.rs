@@ -7,7 +7,7 @@ use std::{
 use client::parse_zed_link;
 use collections::HashMap;
 use command_palette_hooks::{
-    CommandInterceptResult, CommandPaletteFilter, CommandPaletteInterceptor,
+    CommandInterceptResult, CommandPaletteCommands, CommandPaletteFilter, CommandPaletteInterceptor,
 };
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
@@ -91,7 +91,7 @@ impl CommandPalette {
     ) -> Self {
         let filter = CommandPaletteFilter::try_global(cx);
 
-        let commands = window
+        let mut commands = window
             .available_actions(cx)
             .into_iter()
             .filter_map(|action| {
@@ -104,7 +104,13 @@ impl CommandPalette {
                     action,
                 })
             })
-            .collect();
+            .collect::<Vec<_>>();
+        if let Some(extra_commands) = CommandPaletteCommands::try_global(cx) {
+            commands.extend(extra_commands.commands().map(|(name, action)| Command {
+                name: name.to_string(),
+                action: action.boxed_clone(),
+            }));
+        }
 
         let delegate =
             CommandPaletteDelegate::new(cx.entity().downgrade(), commands, previous_focus_handle);
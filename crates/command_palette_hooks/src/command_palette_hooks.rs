@@ -4,14 +4,15 @@
 
 use std::any::TypeId;
 
-use collections::HashSet;
+use collections::{BTreeMap, HashSet};
 use derive_more::{Deref, DerefMut};
-use gpui::{Action, App, BorrowAppContext, Global};
+use gpui::{Action, App, BorrowAppContext, Global, SharedString};
 
 /// Initializes the command palette hooks.
 pub fn init(cx: &mut App) {
     cx.set_global(GlobalCommandPaletteFilter::default());
     cx.set_global(GlobalCommandPaletteInterceptor::default());
+    cx.set_global(GlobalCommandPaletteCommands::default());
 }
 
 /// A filter for the command palette.
@@ -150,3 +151,60 @@ impl CommandPaletteInterceptor {
         self.0 = Some(handler);
     }
 }
+
+/// Additional commands to show in the command palette.
+///
+/// These are shown alongside the actions that are available in the current
+/// context, and are used for commands that aren't backed by a distinct
+/// [`Action`] type, such as the commands provided by extensions.
+#[derive(Default)]
+pub struct CommandPaletteCommands {
+    commands: BTreeMap<SharedString, (SharedString, Box<dyn Action>)>,
+}
+
+#[derive(Deref, DerefMut, Default)]
+struct GlobalCommandPaletteCommands(CommandPaletteCommands);
+
+impl Global for GlobalCommandPaletteCommands {}
+
+impl CommandPaletteCommands {
+    /// Returns the global [`CommandPaletteCommands`], if one is set.
+    pub fn try_global(cx: &App) -> Option<&CommandPaletteCommands> {
+        cx.try_global::<GlobalCommandPaletteCommands>()
+            .map(|commands| &commands.0)
+    }
+
+    /// Updates the global [`CommandPaletteCommands`] using the given closure.
+    pub fn update_global<F>(cx: &mut App, update: F)
+    where
+        F: FnOnce(&mut Self, &mut App),
+    {
+        if cx.has_global::<GlobalCommandPaletteCommands>() {
+            cx.update_global(|this: &mut GlobalCommandPaletteCommands, cx| update(&mut this.0, cx))
+        }
+    }
+
+    /// Adds a command with the given name that dispatches the given [`Action`].
+    ///
+    /// This will replace the previous command with the same ID, if it exists.
+    pub fn add(
+        &mut self,
+        id: impl Into<SharedString>,
+        name: impl Into<SharedString>,
+        action: Box<dyn Action>,
+    ) {
+        self.commands.insert(id.into(), (name.into(), action));
+    }
+
+    /// Removes the command with the given ID.
+    pub fn remove(&mut self, id: &str) {
+        self.commands.remove(id);
+    }
+
+    /// Returns the names and actions of all the commands.
+    pub fn commands(&self) -> impl Iterator<Item = (&SharedString, &dyn Action)> {
+        self.commands
+            .values()
+            .map(|(name, action)| (name, action.as_ref()))
+    }
+}
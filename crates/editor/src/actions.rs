@@ -216,6 +216,9 @@ impl_actions!(
 gpui::actions!(
     editor,
     [
+        AcceptConflictBoth,
+        AcceptConflictOurs,
+        AcceptConflictTheirs,
         AcceptInlineCompletion,
         AcceptPartialCopilotSuggestion,
         AcceptPartialInlineCompletion,
@@ -262,6 +265,7 @@ gpui::actions!(
         FindAllReferences,
         Fold,
         FoldAll,
+        FoldAllImports,
         FoldFunctionBodies,
         FoldRecursive,
         FoldSelectedRanges,
@@ -269,6 +273,7 @@ gpui::actions!(
         ToggleFoldRecursive,
         Format,
         FormatSelections,
+        GoToConflict,
         GoToDeclaration,
         GoToDeclarationSplit,
         GoToDefinition,
@@ -277,8 +282,11 @@ gpui::actions!(
         GoToHunk,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToPrevConflict,
         GoToPrevDiagnostic,
         GoToPrevHunk,
+        GoToSubtype,
+        GoToSupertype,
         GoToTypeDefinition,
         GoToTypeDefinitionSplit,
         HalfPageDown,
@@ -367,6 +375,8 @@ gpui::actions!(
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StageSelectedHunks,
+        StageSelectedLines,
         SwitchSourceHeader,
         Tab,
         TabPrev,
@@ -391,6 +401,8 @@ gpui::actions!(
         UnfoldRecursive,
         UniqueLinesCaseInsensitive,
         UniqueLinesCaseSensitive,
+        UnstageSelectedHunks,
+        UnstageSelectedLines,
     ]
 );
 
@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use gpui::{Context, Entity, Task, Window};
+use language::{
+    language_settings::{language_settings, LanguageSettings},
+    Buffer,
+};
+use project::Project;
+use text::BufferId;
+
+use crate::{code_lens::CodeLensRequest, folding_ranges::FoldingRangesRequest};
+use crate::{semantic_tokens::SemanticTokensRequest, Editor, EditorMode};
+
+/// How long a buffer has to stop changing before its LSP data is requested again.
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// A language server request whose response the editor keeps for each of its buffers.
+pub(crate) trait BufferLspRequest: 'static {
+    type Response: 'static;
+
+    /// What is requested, for error messages.
+    const NAME: &'static str;
+
+    fn enabled(settings: &LanguageSettings) -> bool;
+
+    fn data(editor: &mut Editor) -> &mut BufferLspData<Self::Response>;
+
+    fn request(
+        project: &mut Project,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Self::Response>>;
+
+    /// Called after responses have been added or removed.
+    fn data_changed(editor: &mut Editor, cx: &mut Context<Editor>);
+}
+
+/// The latest response to a [`BufferLspRequest`] for each buffer, and the pending
+/// requests that will replace them.
+pub(crate) struct BufferLspData<T> {
+    responses: HashMap<BufferId, T>,
+    refresh_tasks: HashMap<BufferId, Task<()>>,
+}
+
+impl<T> Default for BufferLspData<T> {
+    fn default() -> Self {
+        Self {
+            responses: HashMap::default(),
+            refresh_tasks: HashMap::default(),
+        }
+    }
+}
+
+impl<T> BufferLspData<T> {
+    pub fn get(&self, buffer_id: &BufferId) -> Option<&T> {
+        self.responses.get(buffer_id)
+    }
+}
+
+impl Editor {
+    /// Requests the LSP data shown in the editor for the given buffer, or for all of
+    /// its buffers, once they have stopped changing for a moment.
+    pub(crate) fn refresh_buffer_lsp_data(
+        &mut self,
+        buffer_id: Option<BufferId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.refresh_lsp_request::<SemanticTokensRequest>(buffer_id, window, cx);
+        self.refresh_lsp_request::<CodeLensRequest>(buffer_id, window, cx);
+        self.refresh_lsp_request::<FoldingRangesRequest>(buffer_id, window, cx);
+    }
+
+    /// Drops the LSP data of buffers that are no longer in the editor.
+    pub(crate) fn prune_buffer_lsp_data(&mut self, cx: &mut Context<Self>) {
+        self.prune_lsp_request::<SemanticTokensRequest>(cx);
+        self.prune_lsp_request::<CodeLensRequest>(cx);
+        self.prune_lsp_request::<FoldingRangesRequest>(cx);
+    }
+
+    fn refresh_lsp_request<R: BufferLspRequest>(
+        &mut self,
+        buffer_id: Option<BufferId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        if self.mode != EditorMode::Full {
+            return;
+        }
+
+        for buffer in self.prune_lsp_request::<R>(cx) {
+            let id = buffer.read(cx).remote_id();
+            if buffer_id.is_some_and(|buffer_id| buffer_id != id) {
+                continue;
+            }
+            let project = project.clone();
+            let task = cx.spawn_in(window, |editor, mut cx| async move {
+                cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+                let Ok(request) =
+                    project.update(&mut cx, |project, cx| R::request(project, &buffer, cx))
+                else {
+                    return;
+                };
+                let response = request.await;
+                editor
+                    .update(&mut cx, |editor, cx| match response {
+                        Ok(response) => {
+                            R::data(editor).responses.insert(id, response);
+                            R::data_changed(editor, cx);
+                        }
+                        Err(error) => log::error!("failed to fetch {}: {error:#}", R::NAME),
+                    })
+                    .ok();
+            });
+            R::data(self).refresh_tasks.insert(id, task);
+        }
+    }
+
+    /// Drops the responses and pending requests of buffers that are gone or have the
+    /// request disabled, and returns the buffers that have it enabled.
+    fn prune_lsp_request<R: BufferLspRequest>(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) -> Vec<Entity<Buffer>> {
+        let buffers = self
+            .buffer
+            .read(cx)
+            .all_buffers()
+            .into_iter()
+            .filter(|buffer| {
+                let buffer = buffer.read(cx);
+                R::enabled(&language_settings(
+                    buffer.language().map(|language| language.name()),
+                    buffer.file(),
+                    cx,
+                ))
+            })
+            .collect::<Vec<_>>();
+        let enabled = buffers
+            .iter()
+            .map(|buffer| buffer.read(cx).remote_id())
+            .collect::<HashSet<_>>();
+
+        let data = R::data(self);
+        data.refresh_tasks
+            .retain(|buffer_id, _| enabled.contains(buffer_id));
+        let responses_len = data.responses.len();
+        data.responses
+            .retain(|buffer_id, _| enabled.contains(buffer_id));
+        if data.responses.len() != responses_len {
+            R::data_changed(self, cx);
+        }
+        buffers
+    }
+}
@@ -0,0 +1,349 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use gpui::{AnyElement, Context, Entity, Task, WeakEntity, Window};
+use language::{
+    language_settings::LanguageSettings, Buffer, OffsetRangeExt as _, Point, ToPoint as _,
+};
+use project::{CodeLens, Project};
+use text::BufferId;
+use ui::prelude::*;
+use workspace::notifications::NotifyTaskExt as _;
+
+use crate::{
+    buffer_lsp_data::{BufferLspData, BufferLspRequest},
+    BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, Editor,
+};
+
+/// The code lenses last received for each of the editor's buffers, and the blocks showing them.
+#[derive(Default)]
+pub(crate) struct CodeLensState {
+    lenses: BufferLspData<Vec<CodeLens>>,
+    block_ids: HashSet<CustomBlockId>,
+}
+
+pub(crate) struct CodeLensRequest;
+
+impl BufferLspRequest for CodeLensRequest {
+    type Response = Vec<CodeLens>;
+
+    const NAME: &'static str = "code lenses";
+
+    fn enabled(settings: &LanguageSettings) -> bool {
+        settings.code_lens
+    }
+
+    fn data(editor: &mut Editor) -> &mut BufferLspData<Self::Response> {
+        &mut editor.code_lens.lenses
+    }
+
+    fn request(
+        project: &mut Project,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Self::Response>> {
+        project.code_lens(buffer, cx)
+    }
+
+    fn data_changed(editor: &mut Editor, cx: &mut Context<Editor>) {
+        editor.update_code_lens_blocks(cx);
+    }
+}
+
+impl Editor {
+    /// Replaces the code lens blocks with one block per line that has resolved lenses.
+    fn update_code_lens_blocks(&mut self, cx: &mut Context<Self>) {
+        let block_ids = std::mem::take(&mut self.code_lens.block_ids);
+        if !block_ids.is_empty() {
+            self.remove_blocks(block_ids, None, cx);
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let editor = cx.entity().downgrade();
+        let mut blocks = Vec::new();
+        for (excerpt_id, buffer, range) in snapshot.excerpts() {
+            let buffer_id = buffer.remote_id();
+            let Some(lenses) = self.code_lens.lenses.get(&buffer_id) else {
+                continue;
+            };
+            let context = range.context.to_point(buffer);
+            let mut lenses_by_row = HashMap::<u32, Vec<CodeLens>>::default();
+            for lens in lenses {
+                if lens.title().is_none() {
+                    continue;
+                }
+                let row = lens.range.start.to_point(buffer).row;
+                if row >= context.start.row && row <= context.end.row {
+                    lenses_by_row.entry(row).or_default().push(lens.clone());
+                }
+            }
+
+            let mut rows = lenses_by_row.into_iter().collect::<Vec<_>>();
+            rows.sort_by_key(|(row, _)| *row);
+            for (row, lenses) in rows {
+                let Some(position) = snapshot.anchor_in_excerpt(
+                    excerpt_id,
+                    buffer.anchor_before(Point::new(row, 0).max(context.start)),
+                ) else {
+                    continue;
+                };
+                let indent = buffer.indent_size_for_line(row).len;
+                let editor = editor.clone();
+                let lenses: Arc<[CodeLens]> = lenses.into();
+                blocks.push(BlockProperties {
+                    placement: BlockPlacement::Above(position),
+                    height: 1,
+                    style: BlockStyle::Flex,
+                    render: Arc::new(move |cx| {
+                        render_code_lenses(buffer_id, &lenses, indent, &editor, cx)
+                    }),
+                    priority: 0,
+                });
+            }
+        }
+
+        if !blocks.is_empty() {
+            self.code_lens.block_ids = self.insert_blocks(blocks, None, cx).into_iter().collect();
+        }
+        cx.notify();
+    }
+
+    /// Resolves the lens if needed and executes its command on the language server, opening
+    /// any edits the command makes. Commands the language server does not provide are
+    /// reported as errors.
+    pub fn apply_code_lens(
+        &mut self,
+        buffer_id: BufferId,
+        lens: CodeLens,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let Some(buffer) = self.buffer.read(cx).buffer(buffer_id) else {
+            return;
+        };
+        let title = lens.title().unwrap_or_default().to_string();
+        let apply_code_lens =
+            project.update(cx, |project, cx| project.apply_code_lens(buffer, lens, cx));
+        let workspace = workspace.downgrade();
+        cx.spawn_in(window, |editor, cx| async move {
+            let project_transaction = apply_code_lens.await?;
+            Self::open_project_transaction(&editor, workspace, project_transaction, title, cx).await
+        })
+        .detach_and_notify_err(window, cx);
+    }
+}
+
+fn render_code_lenses(
+    buffer_id: BufferId,
+    lenses: &[CodeLens],
+    indent: u32,
+    editor: &WeakEntity<Editor>,
+    cx: &mut BlockContext,
+) -> AnyElement {
+    h_flex()
+        .id(cx.block_id)
+        .h(cx.line_height)
+        .pl(cx.anchor_x + cx.em_width * indent as f32)
+        .gap_1()
+        .block_mouse_down()
+        .children(lenses.iter().enumerate().map(|(ix, lens)| {
+            let editor = editor.clone();
+            let lens = lens.clone();
+            Button::new(
+                ("code-lens", ix),
+                lens.title().unwrap_or_default().to_string(),
+            )
+            .label_size(LabelSize::Small)
+            .color(Color::Muted)
+            .on_click(move |_, window, cx| {
+                editor
+                    .update(cx, |editor, cx| {
+                        editor.apply_code_lens(buffer_id, lens.clone(), window, cx)
+                    })
+                    .ok();
+            })
+        }))
+        .into_any_element()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{self, AtomicUsize};
+    use std::time::Duration;
+
+    use futures::StreamExt as _;
+    use indoc::indoc;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+
+    fn lsp_lens(line: u32, title: &str, command: &str) -> lsp::CodeLens {
+        lsp::CodeLens {
+            range: lsp::Range::new(lsp::Position::new(line, 0), lsp::Position::new(line, 1)),
+            command: Some(lsp::Command {
+                title: title.into(),
+                command: command.into(),
+                arguments: None,
+            }),
+            data: None,
+        }
+    }
+
+    async fn code_lens_test_context(cx: &mut gpui::TestAppContext) -> EditorLspTestContext {
+        init_test(cx, |settings| {
+            settings.defaults.code_lens = Some(true);
+        });
+        EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: None,
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["test.run".into()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await
+    }
+
+    #[gpui::test]
+    async fn test_code_lens_blocks(cx: &mut gpui::TestAppContext) {
+        let mut cx = code_lens_test_context(cx).await;
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        cx.lsp
+            .handle_request::<lsp::request::CodeLensRequest, _, _>({
+                let requests = requests.clone();
+                move |_, _| {
+                    requests.fetch_add(1, atomic::Ordering::SeqCst);
+                    async move {
+                        Ok(Some(vec![
+                            lsp_lens(0, "Run", "test.run"),
+                            lsp_lens(0, "Debug", "test.debug"),
+                            lsp_lens(3, "1 reference", "test.references"),
+                        ]))
+                    }
+                }
+            });
+        cx.set_state(indoc! {"
+            fn main() {
+                ˇa();
+            }
+            fn a() {}"});
+        cx.executor().advance_clock(Duration::from_millis(500));
+        cx.run_until_parked();
+
+        let requests_after_open = requests.load(atomic::Ordering::SeqCst);
+        assert!(requests_after_open > 0);
+        cx.update_editor(|editor, _, cx| {
+            let buffer_id = editor
+                .buffer()
+                .read(cx)
+                .as_singleton()
+                .unwrap()
+                .read(cx)
+                .remote_id();
+            let titles = editor
+                .code_lens
+                .lenses
+                .get(&buffer_id)
+                .unwrap()
+                .iter()
+                .map(|lens| lens.title().unwrap_or_default().to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(titles, ["Run", "Debug", "1 reference"]);
+            // One block per line that has lenses.
+            assert_eq!(editor.code_lens.block_ids.len(), 2);
+        });
+
+        // Edits are debounced into a single request for the edited buffer.
+        cx.update_editor(|editor, window, cx| {
+            editor.handle_input("b", window, cx);
+            editor.handle_input("c", window, cx);
+        });
+        cx.executor().advance_clock(Duration::from_millis(100));
+        cx.run_until_parked();
+        assert_eq!(requests.load(atomic::Ordering::SeqCst), requests_after_open);
+        cx.executor().advance_clock(Duration::from_millis(500));
+        cx.run_until_parked();
+        assert_eq!(
+            requests.load(atomic::Ordering::SeqCst),
+            requests_after_open + 1
+        );
+    }
+
+    #[gpui::test]
+    async fn test_apply_code_lens(cx: &mut gpui::TestAppContext) {
+        let mut cx = code_lens_test_context(cx).await;
+
+        cx.lsp
+            .handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _| async move {
+                Ok(Some(vec![
+                    lsp_lens(0, "Run", "test.run"),
+                    lsp_lens(0, "Debug", "test.debug"),
+                ]))
+            });
+        cx.set_state(indoc! {"
+            fn main() {
+                ˇa();
+            }"});
+        cx.executor().advance_clock(Duration::from_millis(500));
+        cx.run_until_parked();
+
+        let (buffer_id, lenses) = cx.update_editor(|editor, _, cx| {
+            let buffer_id = editor
+                .buffer()
+                .read(cx)
+                .as_singleton()
+                .unwrap()
+                .read(cx)
+                .remote_id();
+            (
+                buffer_id,
+                editor.code_lens.lenses.get(&buffer_id).unwrap().clone(),
+            )
+        });
+
+        let executed_commands = Arc::new(Mutex::new(Vec::new()));
+        let mut execute_requests = cx
+            .lsp
+            .handle_request::<lsp::request::ExecuteCommand, _, _>({
+                let executed_commands = executed_commands.clone();
+                move |params, _| {
+                    executed_commands.lock().push(params.command);
+                    async move { Ok(None) }
+                }
+            });
+
+        // A command that the language server provides is executed by it.
+        cx.update_editor(|editor, window, cx| {
+            editor.apply_code_lens(buffer_id, lenses[0].clone(), window, cx)
+        });
+        execute_requests.next().await.unwrap();
+        cx.run_until_parked();
+        assert_eq!(*executed_commands.lock(), ["test.run"]);
+        assert!(cx.update_workspace(|workspace, _, _| workspace.notification_ids().is_empty()));
+
+        // Any other command is reported instead of being sent to the language server.
+        cx.update_editor(|editor, window, cx| {
+            editor.apply_code_lens(buffer_id, lenses[1].clone(), window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(*executed_commands.lock(), ["test.run"]);
+        assert_eq!(
+            cx.update_workspace(|workspace, _, _| workspace.notification_ids().len()),
+            1
+        );
+    }
+}
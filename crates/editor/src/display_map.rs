@@ -82,6 +82,10 @@ pub trait ToDisplayPoint {
 
 type TextHighlights = TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
 type InlayHighlights = TreeMap<TypeId, TreeMap<InlayId, (HighlightStyle, InlayHighlight)>>;
+/// Styled ranges reported by language servers' semantic tokens, sorted and non-overlapping.
+pub(crate) type SemanticTokenHighlights = Arc<[(Range<Anchor>, HighlightStyle)]>;
+/// Foldable ranges reported by language servers, sorted by their start.
+pub(crate) type LspFoldingRanges = Arc<[Range<Anchor>]>;
 
 /// Decides how text in a [`MultiBuffer`] should be displayed in a buffer, handling inlay hints,
 /// folding, hard tabs, soft wrapping, custom blocks (like diagnostics), and highlighting.
@@ -105,6 +109,10 @@ pub struct DisplayMap {
     text_highlights: TextHighlights,
     /// Regions of inlays that should be highlighted.
     inlay_highlights: InlayHighlights,
+    /// Semantic tokens that are highlighted on top of the syntax highlighting.
+    semantic_token_highlights: Option<SemanticTokenHighlights>,
+    /// Ranges that language servers report as foldable, which take precedence over indentation.
+    lsp_folding_ranges: LspFoldingRanges,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     crease_map: CreaseMap,
     pub(crate) fold_placeholder: FoldPlaceholder,
@@ -157,6 +165,8 @@ impl DisplayMap {
             fold_placeholder,
             text_highlights: Default::default(),
             inlay_highlights: Default::default(),
+            semantic_token_highlights: None,
+            lsp_folding_ranges: Arc::from([]),
             clip_at_line_ends: false,
             masked: false,
         }
@@ -184,6 +194,8 @@ impl DisplayMap {
             crease_snapshot: self.crease_map.snapshot(),
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
+            semantic_token_highlights: self.semantic_token_highlights.clone(),
+            lsp_folding_ranges: self.lsp_folding_ranges.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
             masked: self.masked,
             fold_placeholder: self.fold_placeholder.clone(),
@@ -486,6 +498,17 @@ impl DisplayMap {
         let highlights = self.text_highlights.get(&type_id)?;
         Some((highlights.0, &highlights.1))
     }
+    pub(crate) fn set_semantic_token_highlights(
+        &mut self,
+        highlights: Option<SemanticTokenHighlights>,
+    ) {
+        self.semantic_token_highlights = highlights;
+    }
+
+    pub(crate) fn set_lsp_folding_ranges(&mut self, ranges: LspFoldingRanges) {
+        self.lsp_folding_ranges = ranges;
+    }
+
     pub fn clear_highlights(&mut self, type_id: TypeId) -> bool {
         let mut cleared = self.text_highlights.remove(&type_id).is_some();
         cleared |= self.inlay_highlights.remove(&type_id).is_some();
@@ -541,7 +564,11 @@ impl DisplayMap {
             .and_then(|buffer| buffer.language())
             .map(|l| l.name());
         let file = buffer.and_then(|buffer| buffer.file());
-        language_settings(language, file, cx).tab_size
+        let settings = language_settings(language, file, cx);
+        match buffer {
+            Some(buffer) => buffer.settings_overrides().apply(settings).tab_size,
+            None => settings.tab_size,
+        }
     }
 
     #[cfg(test)]
@@ -558,6 +585,7 @@ impl DisplayMap {
 pub(crate) struct Highlights<'a> {
     pub text_highlights: Option<&'a TextHighlights>,
     pub inlay_highlights: Option<&'a InlayHighlights>,
+    pub semantic_token_highlights: Option<&'a SemanticTokenHighlights>,
     pub styles: HighlightStyles,
 }
 
@@ -692,6 +720,8 @@ pub struct DisplaySnapshot {
     block_snapshot: BlockSnapshot,
     text_highlights: TextHighlights,
     inlay_highlights: InlayHighlights,
+    semantic_token_highlights: Option<SemanticTokenHighlights>,
+    lsp_folding_ranges: LspFoldingRanges,
     clip_at_line_ends: bool,
     masked: bool,
     pub(crate) fold_placeholder: FoldPlaceholder,
@@ -874,6 +904,7 @@ impl DisplaySnapshot {
             Highlights {
                 text_highlights: Some(&self.text_highlights),
                 inlay_highlights: Some(&self.inlay_highlights),
+                semantic_token_highlights: self.semantic_token_highlights.as_ref(),
                 styles: highlight_styles,
             },
         )
@@ -1189,6 +1220,22 @@ impl DisplaySnapshot {
             .unwrap_or(false)
     }
 
+    /// The outermost multi-line range reported by a language server that starts on the given row.
+    fn lsp_folding_range_for_buffer_row(&self, buffer_row: MultiBufferRow) -> Option<Range<Point>> {
+        let row_start = self
+            .buffer_snapshot
+            .anchor_before(Point::new(buffer_row.0, 0));
+        let start_ix = self
+            .lsp_folding_ranges
+            .partition_point(|range| range.start.cmp(&row_start, &self.buffer_snapshot).is_lt());
+        self.lsp_folding_ranges[start_ix..]
+            .iter()
+            .map(|range| range.to_point(&self.buffer_snapshot))
+            .take_while(|range| range.start.row == buffer_row.0)
+            .filter(|range| range.end.row > range.start.row)
+            .max_by_key(|range| range.end)
+    }
+
     pub fn crease_for_buffer_row(&self, buffer_row: MultiBufferRow) -> Option<Crease<Point>> {
         let start = MultiBufferPoint::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
         if let Some(crease) = self
@@ -1225,6 +1272,17 @@ impl DisplaySnapshot {
                     render_toggle: render_toggle.clone(),
                 }),
             }
+        } else if let Some(range) = self
+            .lsp_folding_range_for_buffer_row(buffer_row)
+            .filter(|_| !self.is_line_folded(buffer_row))
+        {
+            Some(Crease::Inline {
+                range,
+                placeholder: self.fold_placeholder.clone(),
+                render_toggle: None,
+                render_trailer: None,
+                metadata: None,
+            })
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
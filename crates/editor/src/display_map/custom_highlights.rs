@@ -12,6 +12,8 @@ use std::{
 };
 use sum_tree::TreeMap;
 
+use super::SemanticTokenHighlights;
+
 pub struct CustomHighlightsChunks<'a> {
     buffer_chunks: MultiBufferChunks<'a>,
     buffer_chunk: Option<Chunk<'a>>,
@@ -19,23 +21,33 @@ pub struct CustomHighlightsChunks<'a> {
     multibuffer_snapshot: &'a MultiBufferSnapshot,
 
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
-    active_highlights: BTreeMap<TypeId, HighlightStyle>,
+    active_highlights: BTreeMap<HighlightTag, HighlightStyle>,
     text_highlights: Option<&'a TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>>,
+    semantic_token_highlights: Option<&'a SemanticTokenHighlights>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct HighlightEndpoint {
     offset: usize,
     is_start: bool,
-    tag: TypeId,
+    tag: HighlightTag,
     style: HighlightStyle,
 }
 
+/// Identifies an active highlight. Semantic tokens sort before text highlights, so that
+/// highlights such as search matches are applied on top of them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+enum HighlightTag {
+    SemanticToken(usize),
+    Text(TypeId),
+}
+
 impl<'a> CustomHighlightsChunks<'a> {
     pub fn new(
         range: Range<usize>,
         language_aware: bool,
         text_highlights: Option<&'a TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>>,
+        semantic_token_highlights: Option<&'a SemanticTokenHighlights>,
         multibuffer_snapshot: &'a MultiBufferSnapshot,
     ) -> Self {
         Self {
@@ -44,9 +56,11 @@ impl<'a> CustomHighlightsChunks<'a> {
             offset: range.start,
 
             text_highlights,
+            semantic_token_highlights,
             highlight_endpoints: create_highlight_endpoints(
                 &range,
                 text_highlights,
+                semantic_token_highlights,
                 multibuffer_snapshot,
             ),
             active_highlights: Default::default(),
@@ -55,8 +69,12 @@ impl<'a> CustomHighlightsChunks<'a> {
     }
 
     pub fn seek(&mut self, new_range: Range<usize>) {
-        self.highlight_endpoints =
-            create_highlight_endpoints(&new_range, self.text_highlights, self.multibuffer_snapshot);
+        self.highlight_endpoints = create_highlight_endpoints(
+            &new_range,
+            self.text_highlights,
+            self.semantic_token_highlights,
+            self.multibuffer_snapshot,
+        );
         self.offset = new_range.start;
         self.buffer_chunks.seek(new_range);
         self.buffer_chunk.take();
@@ -67,13 +85,46 @@ impl<'a> CustomHighlightsChunks<'a> {
 fn create_highlight_endpoints(
     range: &Range<usize>,
     text_highlights: Option<&TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>>,
+    semantic_token_highlights: Option<&SemanticTokenHighlights>,
     buffer: &MultiBufferSnapshot,
 ) -> iter::Peekable<vec::IntoIter<HighlightEndpoint>> {
     let mut highlight_endpoints = Vec::new();
+    let start = buffer.anchor_after(range.start);
+    let end = buffer.anchor_after(range.end);
+    if let Some(tokens) = semantic_token_highlights {
+        let start_ix = match tokens.binary_search_by(|(probe, _)| {
+            if probe.end.cmp(&start, &buffer).is_gt() {
+                cmp::Ordering::Greater
+            } else {
+                cmp::Ordering::Less
+            }
+        }) {
+            Ok(i) | Err(i) => i,
+        };
+
+        for (ix, (range, style)) in tokens.iter().enumerate().skip(start_ix) {
+            if range.start.cmp(&end, &buffer).is_ge() {
+                break;
+            }
+
+            let tag = HighlightTag::SemanticToken(ix);
+            highlight_endpoints.push(HighlightEndpoint {
+                offset: range.start.to_offset(&buffer),
+                is_start: true,
+                tag,
+                style: *style,
+            });
+            highlight_endpoints.push(HighlightEndpoint {
+                offset: range.end.to_offset(&buffer),
+                is_start: false,
+                tag,
+                style: *style,
+            });
+        }
+    }
     if let Some(text_highlights) = text_highlights {
-        let start = buffer.anchor_after(range.start);
-        let end = buffer.anchor_after(range.end);
         for (&tag, text_highlights) in text_highlights.iter() {
+            let tag = HighlightTag::Text(tag);
             let style = text_highlights.0;
             let ranges = &text_highlights.1;
 
@@ -107,8 +158,8 @@ fn create_highlight_endpoints(
                 });
             }
         }
-        highlight_endpoints.sort();
     }
+    highlight_endpoints.sort();
     highlight_endpoints.into_iter().peekable()
 }
 
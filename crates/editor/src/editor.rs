@@ -15,12 +15,15 @@
 pub mod actions;
 mod blame_entry_tooltip;
 mod blink_manager;
+mod buffer_lsp_data;
 mod clangd_ext;
 mod code_context_menus;
+mod code_lens;
 pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -37,6 +40,7 @@ mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod semantic_tokens;
 pub mod tasks;
 
 #[cfg(test)]
@@ -75,7 +79,9 @@ use code_context_menus::{
     AvailableCodeAction, CodeActionContents, CodeActionsItem, CodeActionsMenu, CodeContextMenu,
     CompletionEntry, CompletionsMenu, ContextMenuOrigin,
 };
-use git::blame::GitBlame;
+use code_lens::CodeLensState;
+use folding_ranges::FoldingRangesState;
+use git::{blame::GitBlame, conflicts::ConflictState};
 use gpui::{
     div, impl_actions, point, prelude::*, px, relative, size, Action, AnyElement, App,
     AsyncWindowContext, AvailableSpace, Bounds, ClipboardEntry, ClipboardItem, Context,
@@ -94,7 +100,9 @@ use inline_completion::{InlineCompletionProvider, InlineCompletionProviderHandle
 pub use items::MAX_TAB_TITLE_LEN;
 use itertools::Itertools;
 use language::{
-    language_settings::{self, all_language_settings, language_settings, InlayHintSettings},
+    language_settings::{
+        self, all_language_settings, language_settings, InlayHintSettings, SelectionRangesProvider,
+    },
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CharKind, CodeLabel,
     CursorShape, Diagnostic, Documentation, EditPreview, HighlightedText, IndentKind, IndentSize,
     Language, OffsetRangeExt, Point, Selection, SelectionGoal, TextObject, TransactionId,
@@ -138,6 +146,7 @@ use scroll::{Autoscroll, OngoingScroll, ScrollAnchor, ScrollManager, ScrollbarAu
 use selections_collection::{
     resolve_selections, MutableSelectionsCollection, SelectionsCollection,
 };
+use semantic_tokens::SemanticTokensState;
 use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsLocation, SettingsStore};
 use smallvec::SmallVec;
@@ -668,6 +677,10 @@ pub struct Editor {
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
+    conflicts: ConflictState,
+    semantic_tokens: SemanticTokensState,
+    code_lens: CodeLensState,
+    folding_ranges: FoldingRangesState,
     pending_rename: Option<RenameState>,
     searchable: bool,
     cursor_shape: CursorShape,
@@ -730,6 +743,7 @@ pub struct Editor {
     tasks_update_task: Option<Task<()>>,
     in_project_search: bool,
     previous_search_ranges: Option<Arc<[Range<Anchor>]>>,
+    replacement_previews: Vec<InlayId>,
     breadcrumb_header: Option<String>,
     focused_block: Option<FocusedBlock>,
     next_scroll_position: NextScrollCursorCenterTopBottom,
@@ -989,13 +1003,20 @@ pub struct ClipboardSelection {
 }
 
 #[derive(Debug)]
-pub(crate) struct NavigationData {
+pub struct NavigationData {
     cursor_anchor: Anchor,
     cursor_position: Point,
     scroll_anchor: ScrollAnchor,
     scroll_top_row: u32,
 }
 
+impl NavigationData {
+    /// The cursor position that navigating to this entry restores.
+    pub fn cursor_position(&self) -> Point {
+        self.cursor_position
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GotoDefinitionKind {
     Symbol,
@@ -1231,6 +1252,9 @@ impl Editor {
                         if let project::Event::RefreshInlayHints = event {
                             editor
                                 .refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                        } else if let project::Event::WorktreeUpdatedGitRepositories(_) = event {
+                            // Files become conflicted, or stop being so, as merges start and end.
+                            editor.refresh_conflicts(window, cx);
                         } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                             if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                                 let focus_handle = editor.focus_handle(cx);
@@ -1427,8 +1451,13 @@ impl Editor {
             ],
             tasks_update_task: None,
             linked_edit_ranges: Default::default(),
+            conflicts: ConflictState::default(),
+            semantic_tokens: SemanticTokensState::default(),
+            code_lens: CodeLensState::default(),
+            folding_ranges: FoldingRangesState::default(),
             in_project_search: false,
             previous_search_ranges: None,
+            replacement_previews: Vec::new(),
             breadcrumb_header: None,
             focused_block: None,
             next_scroll_position: NextScrollCursorCenterTopBottom::default(),
@@ -1440,6 +1469,8 @@ impl Editor {
             text_style_refinement: None,
         };
         this.tasks_update_task = Some(this.refresh_runnables(window, cx));
+        this.refresh_conflicts(window, cx);
+        this.refresh_buffer_lsp_data(None, window, cx);
         this._subscriptions.extend(project_subscriptions);
 
         this.end_selection(window, cx);
@@ -5979,7 +6010,7 @@ impl Editor {
         cx: &App,
     ) -> u32 {
         let settings = buffer.settings_at(selection.start, cx);
-        let tab_size = settings.tab_size.get();
+        let indent_columns = settings.indent_size().get();
         let indent_kind = if settings.hard_tabs {
             IndentKind::Tab
         } else {
@@ -6011,10 +6042,11 @@ impl Editor {
             let current_indent = snapshot.indent_size_for_line(MultiBufferRow(row));
             let indent_delta = match (current_indent.kind, indent_kind) {
                 (IndentKind::Space, IndentKind::Space) => {
-                    let columns_to_next_tab_stop = tab_size - (current_indent.len % tab_size);
+                    let columns_to_next_tab_stop =
+                        indent_columns - (current_indent.len % indent_columns);
                     IndentSize::spaces(columns_to_next_tab_stop)
                 }
-                (IndentKind::Tab, IndentKind::Space) => IndentSize::spaces(tab_size),
+                (IndentKind::Tab, IndentKind::Space) => IndentSize::spaces(indent_columns),
                 (_, IndentKind::Tab) => IndentSize::tab(),
             };
 
@@ -6059,7 +6091,7 @@ impl Editor {
             let snapshot = buffer.snapshot(cx);
             for selection in &selections {
                 let settings = buffer.settings_at(selection.start, cx);
-                let tab_size = settings.tab_size.get();
+                let indent_columns = settings.indent_size().get();
                 let mut rows = selection.spanned_rows(false, &display_map);
 
                 // Avoid re-outdenting a row that has already been outdented by a
@@ -6075,9 +6107,9 @@ impl Editor {
                     if indent_size.len > 0 {
                         let deletion_len = match indent_size.kind {
                             IndentKind::Space => {
-                                let columns_to_prev_tab_stop = indent_size.len % tab_size;
+                                let columns_to_prev_tab_stop = indent_size.len % indent_columns;
                                 if columns_to_prev_tab_stop == 0 {
-                                    tab_size
+                                    indent_columns
                                 } else {
                                     columns_to_prev_tab_stop
                                 }
@@ -9378,41 +9410,22 @@ impl Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.uses_lsp_selection_ranges(cx) {
+            self.select_larger_lsp_selection_ranges(window, cx);
+            return;
+        }
+
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = self.buffer.read(cx).snapshot(cx);
         let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
-
-        let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
-        let mut selected_larger_node = false;
         let new_selections = old_selections
             .iter()
             .map(|selection| {
-                let old_range = selection.start..selection.end;
-                let mut new_range = old_range.clone();
-                let mut new_node = None;
-                while let Some((node, containing_range)) = buffer.syntax_ancestor(new_range.clone())
-                {
-                    new_node = Some(node);
-                    new_range = containing_range;
-                    if !display_map.intersects_fold(new_range.start)
-                        && !display_map.intersects_fold(new_range.end)
-                    {
-                        break;
-                    }
-                }
-
-                if let Some(node) = new_node {
-                    // Log the ancestor, to support using this action as a way to explore TreeSitter
-                    // nodes. Parent and grandparent are also logged because this operation will not
-                    // visit nodes that have the same range as their parent.
-                    log::info!("Node: {node:?}");
-                    let parent = node.parent();
-                    log::info!("Parent: {parent:?}");
-                    let grandparent = parent.and_then(|x| x.parent());
-                    log::info!("Grandparent: {grandparent:?}");
-                }
-
-                selected_larger_node |= new_range != old_range;
+                let new_range = Self::larger_syntax_node_range(
+                    &buffer,
+                    &display_map,
+                    selection.start..selection.end,
+                );
                 Selection {
                     id: selection.id,
                     start: new_range.start,
@@ -9422,14 +9435,163 @@ impl Editor {
                 }
             })
             .collect::<Vec<_>>();
+        self.push_larger_selections(old_selections, new_selections, window, cx);
+    }
+
+    fn larger_syntax_node_range(
+        buffer: &MultiBufferSnapshot,
+        display_map: &DisplaySnapshot,
+        old_range: Range<usize>,
+    ) -> Range<usize> {
+        let mut new_range = old_range;
+        let mut new_node = None;
+        while let Some((node, containing_range)) = buffer.syntax_ancestor(new_range.clone()) {
+            new_node = Some(node);
+            new_range = containing_range;
+            if !display_map.intersects_fold(new_range.start)
+                && !display_map.intersects_fold(new_range.end)
+            {
+                break;
+            }
+        }
+
+        if let Some(node) = new_node {
+            // Log the ancestor, to support using this action as a way to explore TreeSitter
+            // nodes. Parent and grandparent are also logged because this operation will not
+            // visit nodes that have the same range as their parent.
+            log::info!("Node: {node:?}");
+            let parent = node.parent();
+            log::info!("Parent: {parent:?}");
+            let grandparent = parent.and_then(|x| x.parent());
+            log::info!("Grandparent: {grandparent:?}");
+        }
+        new_range
+    }
+
+    fn uses_lsp_selection_ranges(&self, cx: &App) -> bool {
+        if self.project.is_none() {
+            return false;
+        }
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let language = snapshot.language_at(head).map(|language| language.name());
+        language_settings(language, snapshot.file_at(head), cx).selection_ranges
+            == SelectionRangesProvider::LanguageServer
+    }
+
+    /// Expands each selection to the smallest enclosing selection range reported by the
+    /// language server, or to the enclosing syntax node when the server reports none.
+    fn select_larger_lsp_selection_ranges(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
+        let positions = {
+            let multi_buffer = self.buffer.read(cx);
+            let snapshot = multi_buffer.snapshot(cx);
+            old_selections
+                .iter()
+                .map(|selection| {
+                    let excerpt_id = snapshot.anchor_before(selection.start).excerpt_id;
+                    let (buffer, position) =
+                        multi_buffer.text_anchor_for_position(selection.start, cx)?;
+                    Some((excerpt_id, buffer, position))
+                })
+                .collect::<Vec<_>>()
+        };
+        let requests = project.update(cx, |project, cx| {
+            positions
+                .into_iter()
+                .map(|position| {
+                    let (excerpt_id, buffer, position) = position?;
+                    Some((
+                        excerpt_id,
+                        project.selection_ranges(&buffer, vec![position], cx),
+                    ))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        cx.spawn_in(window, |editor, mut cx| async move {
+            let mut lsp_ranges = Vec::with_capacity(requests.len());
+            for request in requests {
+                lsp_ranges.push(match request {
+                    Some((excerpt_id, task)) => task
+                        .await
+                        .log_err()
+                        .and_then(|ranges| Some((excerpt_id, ranges.into_iter().next()?))),
+                    None => None,
+                });
+            }
+
+            editor.update_in(&mut cx, |editor, window, cx| {
+                if *editor.selections.all::<usize>(cx) != *old_selections {
+                    return;
+                }
+                let display_map = editor.display_map.update(cx, |map, cx| map.snapshot(cx));
+                let buffer = &display_map.buffer_snapshot;
+                let new_selections = old_selections
+                    .iter()
+                    .zip(lsp_ranges)
+                    .map(|(selection, ranges)| {
+                        let old_range = selection.start..selection.end;
+                        let new_range = ranges
+                            .and_then(|(excerpt_id, ranges)| {
+                                ranges.into_iter().find_map(|range| {
+                                    let start = buffer
+                                        .anchor_in_excerpt(excerpt_id, range.start)?
+                                        .to_offset(buffer);
+                                    let end = buffer
+                                        .anchor_in_excerpt(excerpt_id, range.end)?
+                                        .to_offset(buffer);
+                                    (start <= old_range.start && end >= old_range.end && start
+                                        ..end != old_range)
+                                        .then_some(start..end)
+                                })
+                            })
+                            .unwrap_or_else(|| {
+                                Self::larger_syntax_node_range(
+                                    buffer,
+                                    &display_map,
+                                    old_range.clone(),
+                                )
+                            });
+                        Selection {
+                            id: selection.id,
+                            start: new_range.start,
+                            end: new_range.end,
+                            goal: SelectionGoal::None,
+                            reversed: selection.reversed,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                editor.push_larger_selections(old_selections, new_selections, window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
 
+    /// Selects the given larger selections, remembering the old ones so that
+    /// [`SelectSmallerSyntaxNode`] can restore them.
+    fn push_larger_selections(
+        &mut self,
+        old_selections: Box<[Selection<usize>]>,
+        new_selections: Vec<Selection<usize>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let selected_larger_node = old_selections
+            .iter()
+            .zip(&new_selections)
+            .any(|(old, new)| old.start != new.start || old.end != new.end);
         if selected_larger_node {
+            let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
             stack.push(old_selections);
             self.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
                 s.select(new_selections);
             });
+            self.select_larger_syntax_node_stack = stack;
         }
-        self.select_larger_syntax_node_stack = stack;
     }
 
     pub fn select_smaller_syntax_node(
@@ -10087,6 +10249,106 @@ impl Editor {
         self.go_to_definition_of_kind(GotoDefinitionKind::Type, true, window, cx)
     }
 
+    pub fn go_to_supertype(
+        &mut self,
+        _: &GoToSupertype,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Navigated>> {
+        self.go_to_related_types(true, window, cx)
+    }
+
+    pub fn go_to_subtype(
+        &mut self,
+        _: &GoToSubtype,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Navigated>> {
+        self.go_to_related_types(false, window, cx)
+    }
+
+    /// Navigates to the supertypes or subtypes of the type under the newest cursor, opening
+    /// them in a multibuffer when there is more than one.
+    fn go_to_related_types(
+        &mut self,
+        supertypes: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Navigated>> {
+        let Some(project) = self.project.clone() else {
+            return Task::ready(Ok(Navigated::No));
+        };
+        let head = self.selections.newest::<usize>(cx).head();
+        let Some((buffer, head)) = self.buffer.read(cx).text_anchor_for_position(head, cx) else {
+            return Task::ready(Ok(Navigated::No));
+        };
+        let prepare = project.update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, head, cx)
+        });
+
+        cx.spawn_in(window, |editor, mut cx| async move {
+            let Some(item) = prepare.await?.into_iter().next() else {
+                return Ok(Navigated::No);
+            };
+            let title = if supertypes {
+                format!("Supertypes of `{}`", item.name)
+            } else {
+                format!("Subtypes of `{}`", item.name)
+            };
+            let related_types = project
+                .update(&mut cx, |project, cx| {
+                    if supertypes {
+                        project.supertypes(item, cx)
+                    } else {
+                        project.subtypes(item, cx)
+                    }
+                })?
+                .await?;
+            let mut locations = related_types
+                .into_iter()
+                .map(|item| Location {
+                    buffer: item.range.buffer,
+                    range: item.selection_range,
+                })
+                .collect::<Vec<_>>();
+
+            if locations.len() > 1 {
+                let workspace = editor.update(&mut cx, |editor, _| editor.workspace())?;
+                let Some(workspace) = workspace else {
+                    return Ok(Navigated::No);
+                };
+                workspace.update_in(&mut cx, |workspace, window, cx| {
+                    Self::open_locations_in_multibuffer(
+                        workspace,
+                        locations,
+                        title,
+                        false,
+                        MultibufferSelectionMode::First,
+                        window,
+                        cx,
+                    );
+                })?;
+                return Ok(Navigated::Yes);
+            }
+
+            let links = locations
+                .pop()
+                .map(|target| {
+                    HoverLink::Text(LocationLink {
+                        origin: None,
+                        target,
+                    })
+                })
+                .into_iter()
+                .collect();
+            editor
+                .update_in(&mut cx, |editor, window, cx| {
+                    editor.navigate_to_hover_links(None, links, false, window, cx)
+                })?
+                .await
+        })
+    }
+
     fn go_to_definition_of_kind(
         &mut self,
         kind: GotoDefinitionKind,
@@ -13320,6 +13582,11 @@ impl Editor {
                         }
                     }
                 }
+                self.refresh_conflicts(window, cx);
+                let edited_buffer_id = buffer_edited
+                    .as_ref()
+                    .map(|buffer| buffer.read(cx).remote_id());
+                self.refresh_buffer_lsp_data(edited_buffer_id, window, cx);
                 cx.emit(EditorEvent::BufferEdited);
                 cx.emit(SearchEvent::MatchesInvalidated);
                 if *singleton_buffer_edited {
@@ -13371,7 +13638,9 @@ impl Editor {
                 excerpts,
             } => {
                 self.tasks_update_task = Some(self.refresh_runnables(window, cx));
+                self.refresh_conflicts(window, cx);
                 let buffer_id = buffer.read(cx).remote_id();
+                self.refresh_buffer_lsp_data(Some(buffer_id), window, cx);
                 if self.buffer.read(cx).change_set_for(buffer_id).is_none() {
                     if let Some(project) = &self.project {
                         get_unstaged_changes_for_buffers(
@@ -13394,6 +13663,7 @@ impl Editor {
                 let buffer = self.buffer.read(cx);
                 self.registered_buffers
                     .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+                self.prune_buffer_lsp_data(cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
             multi_buffer::Event::ExcerptsEdited { ids } => {
@@ -13413,6 +13683,7 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, window, cx);
+                self.refresh_buffer_lsp_data(Some(*buffer_id), window, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -13448,6 +13719,8 @@ impl Editor {
     fn settings_changed(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         self.tasks_update_task = Some(self.refresh_runnables(window, cx));
         self.refresh_inline_completion(true, false, window, cx);
+        self.refresh_buffer_lsp_data(None, window, cx);
+        self.update_semantic_token_highlights(cx);
         self.refresh_inlay_hints(
             InlayHintRefreshReason::SettingsChange(inlay_hint_settings(
                 self.selections.newest_anchor().head(),
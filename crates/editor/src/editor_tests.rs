@@ -7,6 +7,7 @@ use crate::{
     },
     JoinLines,
 };
+use ::git::status::{FileStatus, StatusCode, UnmergedStatus, UnmergedStatusCode};
 use futures::StreamExt;
 use gpui::{
     div, BackgroundExecutor, SemanticVersion, TestAppContext, UpdateGlobal, VisualTestContext,
@@ -5613,6 +5614,124 @@ async fn test_select_larger_smaller_syntax_node(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_select_larger_lsp_selection_range(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.selection_ranges =
+            Some(language_settings::SelectionRangesProvider::LanguageServer)
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    cx.set_state("fn main() { let a = fooˇ(1); }");
+    let mut requests = cx
+        .lsp
+        .handle_request::<lsp::request::SelectionRangeRequest, _, _>(|_, _| async move {
+            Ok(Some(vec![lsp::SelectionRange {
+                range: lsp::Range::new(lsp::Position::new(0, 20), lsp::Position::new(0, 23)),
+                parent: Some(Box::new(lsp::SelectionRange {
+                    range: lsp::Range::new(lsp::Position::new(0, 20), lsp::Position::new(0, 26)),
+                    parent: None,
+                })),
+            }]))
+        });
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, window, cx)
+    });
+    requests.next().await;
+    cx.run_until_parked();
+    cx.assert_editor_state("fn main() { let a = «fooˇ»(1); }");
+
+    // The innermost range is already selected, so its parent is selected next.
+    cx.update_editor(|editor, window, cx| {
+        editor.select_larger_syntax_node(&SelectLargerSyntaxNode, window, cx)
+    });
+    requests.next().await;
+    cx.run_until_parked();
+    cx.assert_editor_state("fn main() { let a = «foo(1)ˇ»; }");
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, window, cx)
+    });
+    cx.assert_editor_state("fn main() { let a = «fooˇ»(1); }");
+}
+
+#[gpui::test]
+async fn test_fold_lsp_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.use_lsp_folding_ranges = Some(true);
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    cx.lsp
+        .handle_request::<lsp::request::FoldingRangeRequest, _, _>(|_, _| async move {
+            Ok(Some(vec![
+                lsp::FoldingRange {
+                    start_line: 0,
+                    start_character: None,
+                    end_line: 2,
+                    end_character: None,
+                    kind: Some(lsp::FoldingRangeKind::Imports),
+                    collapsed_text: None,
+                },
+                lsp::FoldingRange {
+                    start_line: 4,
+                    start_character: Some(10),
+                    end_line: 6,
+                    end_character: Some(0),
+                    kind: None,
+                    collapsed_text: None,
+                },
+            ]))
+        });
+    cx.set_state(indoc! {"
+        use a;
+        use b;
+        use c;
+
+        fn main() {
+            ˇa();
+        }"});
+    cx.executor().advance_clock(Duration::from_millis(500));
+    cx.run_until_parked();
+
+    cx.update_editor(|editor, window, cx| {
+        let snapshot = editor.snapshot(window, cx);
+        assert_eq!(
+            snapshot
+                .crease_for_buffer_row(MultiBufferRow(4))
+                .map(|crease| crease.range().clone()),
+            Some(Point::new(4, 10)..Point::new(6, 0))
+        );
+        editor.fold_all_imports(&FoldAllImports, window, cx);
+    });
+    assert_eq!(
+        cx.display_text(),
+        indoc! {"
+            use a;⋯
+
+            fn main() {
+                a();
+            }"}
+    );
+}
+
 #[gpui::test]
 async fn test_fold_function_bodies(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -14778,6 +14897,113 @@ async fn test_goto_definition_with_find_all_references_fallback(cx: &mut gpui::T
     });
 }
 
+#[gpui::test]
+async fn test_go_to_supertype_and_subtype(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorLspTestContext::new_with_adapter(
+        Arc::into_inner(rust_lang()).unwrap(),
+        FakeLspAdapter {
+            initializer: Some(Box::new(|fake_server: &mut lsp::FakeLanguageServer| {
+                fake_server.advertise_type_hierarchy_provider(lsp::ServerCapabilities::default())
+            })),
+            ..FakeLspAdapter::default()
+        },
+        cx,
+    )
+    .await;
+
+    cx.set_state(indoc! {"
+        trait Shape {}
+        struct Cˇircle;
+        struct Square;
+    "});
+    let shape_range = cx.lsp_range(indoc! {"
+        trait «Shape» {}
+        struct Circle;
+        struct Square;
+    "});
+    let circle_range = cx.lsp_range(indoc! {"
+        trait Shape {}
+        struct «Circle»;
+        struct Square;
+    "});
+    let square_range = cx.lsp_range(indoc! {"
+        trait Shape {}
+        struct Circle;
+        struct «Square»;
+    "});
+    let item = move |name: &str, range: lsp::Range, uri: lsp::Url| lsp::TypeHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::STRUCT,
+        tags: None,
+        detail: None,
+        uri,
+        range,
+        selection_range: range,
+        data: None,
+    };
+    cx.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(move |uri, params, _| {
+        let item = if params.text_document_position_params.position.line == 0 {
+            item("Shape", shape_range, uri)
+        } else {
+            item("Circle", circle_range, uri)
+        };
+        async move { Ok(Some(vec![item])) }
+    });
+    cx.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>(move |uri, params, _| {
+        assert_eq!(params.item.name, "Circle");
+        async move { Ok(Some(vec![item("Shape", shape_range, uri)])) }
+    });
+    cx.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(move |uri, params, _| {
+        assert_eq!(params.item.name, "Shape");
+        async move {
+            Ok(Some(vec![
+                item("Circle", circle_range, uri.clone()),
+                item("Square", square_range, uri),
+            ]))
+        }
+    });
+
+    let navigated = cx
+        .update_editor(|editor, window, cx| editor.go_to_supertype(&GoToSupertype, window, cx))
+        .await
+        .unwrap();
+    assert_eq!(navigated, Navigated::Yes);
+    cx.assert_editor_state(indoc! {"
+        trait «Shapeˇ» {}
+        struct Circle;
+        struct Square;
+    "});
+
+    let navigated = cx
+        .update_editor(|editor, window, cx| editor.go_to_subtype(&GoToSubtype, window, cx))
+        .await
+        .unwrap();
+    assert_eq!(navigated, Navigated::Yes);
+    cx.assert_editor_state(indoc! {"
+        trait «Shapeˇ» {}
+        struct Circle;
+        struct Square;
+    "});
+    let subtypes_editor =
+        cx.update_workspace(|workspace, _, cx| workspace.active_item_as::<Editor>(cx).unwrap());
+    cx.update_editor(|_, _, test_editor_cx| {
+        assert_ne!(
+            subtypes_editor,
+            test_editor_cx.entity(),
+            "Several subtypes should be opened in a new multibuffer"
+        );
+        assert_eq!(
+            subtypes_editor
+                .read(test_editor_cx)
+                .buffer()
+                .read(test_editor_cx)
+                .title(test_editor_cx),
+            "Subtypes of `Shape`"
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_find_enclosing_node_with_task(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -15553,6 +15779,189 @@ async fn test_rename_with_duplicate_edits(cx: &mut gpui::TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_resolve_conflicts(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_git_status(CONFLICTED);
+
+    cx.set_state(indoc! {"
+        fn main() {
+        ˇ<<<<<<< HEAD
+            ours();
+        =======
+            theirs();
+        >>>>>>> feature
+        }
+        <<<<<<< HEAD
+        a
+        =======
+        b
+        >>>>>>> feature
+    "});
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _, _| {
+        assert_eq!(editor.conflict_regions().len(), 2);
+        assert_eq!(
+            editor.conflict_regions()[0].ours_name.as_deref(),
+            Some("HEAD")
+        );
+    });
+
+    cx.update_editor(|editor, window, cx| {
+        editor.accept_conflict_both(&AcceptConflictBoth, window, cx)
+    });
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+        ˇ    ours();
+            theirs();
+        }
+        <<<<<<< HEAD
+        a
+        =======
+        b
+        >>>>>>> feature
+    "});
+    cx.update_editor(|editor, window, cx| {
+        assert_eq!(editor.conflict_regions().len(), 1);
+        editor.go_to_conflict(&GoToConflict, window, cx);
+        editor.accept_conflict_theirs(&AcceptConflictTheirs, window, cx);
+    });
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            ours();
+            theirs();
+        }
+        ˇb
+    "});
+    cx.update_editor(|editor, _, _| assert!(editor.conflict_regions().is_empty()));
+}
+
+const CONFLICTED: FileStatus = FileStatus::Unmerged(UnmergedStatus {
+    first_head: UnmergedStatusCode::Updated,
+    second_head: UnmergedStatusCode::Updated,
+});
+
+#[gpui::test]
+async fn test_conflicts_only_in_unmerged_files(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+
+    // Markers in a file that isn't being merged are just text.
+    cx.set_git_status(FileStatus::worktree(StatusCode::Modified));
+    cx.set_state(indoc! {"
+        ˇ<<<<<<< HEAD
+        a
+        =======
+        b
+        >>>>>>> feature
+    "});
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _, _| assert!(editor.conflict_regions().is_empty()));
+
+    cx.set_git_status(CONFLICTED);
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _, _| assert_eq!(editor.conflict_regions().len(), 1));
+
+    // Once the merge is over, the conflicts are no longer shown.
+    cx.set_git_status(FileStatus::worktree(StatusCode::Modified));
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _, _| assert!(editor.conflict_regions().is_empty()));
+}
+
+#[gpui::test]
+async fn test_conflicts_rescanned_around_edits(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_git_status(CONFLICTED);
+
+    cx.set_state(indoc! {"
+        fn main() {ˇ
+        <<<<<<< HEAD
+            ours();
+        =======
+            theirs();
+        >>>>>>> feature
+        }
+        <<<<<<< HEAD
+        a
+        =======
+        b
+        >>>>>>> feature
+    "});
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    let conflicts = |cx: &mut EditorTestContext| {
+        cx.update_editor(|editor, _, cx| {
+            let snapshot = editor.buffer.read(cx).snapshot(cx);
+            editor
+                .conflict_regions()
+                .iter()
+                .map(|region| {
+                    snapshot
+                        .text_for_range(region.ours.clone())
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(conflicts(&mut cx), ["    ours();\n", "a\n"]);
+    let block_ids = cx.update_editor(|editor, _, _| editor.conflicts.block_ids.clone());
+
+    // Edits outside of the conflicts leave their controls alone.
+    cx.update_editor(|editor, window, cx| editor.insert(" // merged", window, cx));
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    assert_eq!(conflicts(&mut cx), ["    ours();\n", "a\n"]);
+    cx.update_editor(|editor, _, _| assert_eq!(editor.conflicts.block_ids, block_ids));
+
+    // Breaking a conflict's markers removes only that conflict.
+    cx.update_editor(|editor, window, cx| {
+        let separator = editor
+            .buffer
+            .read(cx)
+            .snapshot(cx)
+            .text()
+            .find("=======")
+            .unwrap();
+        editor.edit([(separator..separator + 1, "")], cx);
+        editor.change_selections(None, window, cx, |s| s.select_ranges([0..0]));
+    });
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    assert_eq!(conflicts(&mut cx), ["a\n"]);
+
+    // Typing a conflict after the last one finds it, along with the ones before.
+    cx.set_selections_state(indoc! {"
+        fn main() { // merged
+        <<<<<<< HEAD
+            ours();
+        ======
+            theirs();
+        >>>>>>> feature
+        }
+        <<<<<<< HEAD
+        a
+        =======
+        b
+        >>>>>>> feature
+        ˇ"});
+    cx.update_editor(|editor, window, cx| {
+        editor.insert("<<<<<<< HEAD\nc\n=======\nd\n>>>>>>> feature\n", window, cx)
+    });
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+    assert_eq!(conflicts(&mut cx), ["a\n", "c\n"]);
+}
+
 #[gpui::test]
 async fn test_rename_without_prepare(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
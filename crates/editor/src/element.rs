@@ -20,9 +20,10 @@ use crate::{
     EditorSettings, EditorSnapshot, EditorStyle, ExpandExcerpts, FocusedBlock, GoToHunk,
     GoToPrevHunk, GutterDimensions, HalfPageDown, HalfPageUp, HandleInput, HoveredCursor,
     InlineCompletion, JumpData, LineDown, LineUp, OpenExcerpts, PageDown, PageUp, Point,
-    RevertSelectedHunks, RowExt, RowRangeExt, SelectPhase, Selection, SoftWrap,
-    StickyHeaderExcerpt, ToPoint, ToggleFold, CURSORS_VISIBLE_FOR, FILE_HEADER_HEIGHT,
-    GIT_BLAME_MAX_AUTHOR_CHARS_DISPLAYED, MAX_LINE_LEN, MULTI_BUFFER_EXCERPT_HEADER_HEIGHT,
+    RevertSelectedHunks, RowExt, RowRangeExt, SelectPhase, Selection, SoftWrap, StageSelectedHunks,
+    StickyHeaderExcerpt, ToPoint, ToggleFold, UnstageSelectedHunks, CURSORS_VISIBLE_FOR,
+    FILE_HEADER_HEIGHT, GIT_BLAME_MAX_AUTHOR_CHARS_DISPLAYED, MAX_LINE_LEN,
+    MULTI_BUFFER_EXCERPT_HEADER_HEIGHT,
 };
 use client::ParticipantIndex;
 use collections::{BTreeMap, HashMap, HashSet};
@@ -373,11 +374,22 @@ impl EditorElement {
                 .go_to_type_definition_split(action, window, cx)
                 .detach_and_log_err(cx);
         });
+        register_action(editor, window, |editor, action, window, cx| {
+            editor
+                .go_to_supertype(action, window, cx)
+                .detach_and_log_err(cx);
+        });
+        register_action(editor, window, |editor, action, window, cx| {
+            editor
+                .go_to_subtype(action, window, cx)
+                .detach_and_log_err(cx);
+        });
         register_action(editor, window, Editor::open_url);
         register_action(editor, window, Editor::open_selected_filename);
         register_action(editor, window, Editor::fold);
         register_action(editor, window, Editor::fold_at_level);
         register_action(editor, window, Editor::fold_all);
+        register_action(editor, window, Editor::fold_all_imports);
         register_action(editor, window, Editor::fold_function_bodies);
         register_action(editor, window, Editor::fold_at);
         register_action(editor, window, Editor::fold_recursive);
@@ -488,6 +500,15 @@ impl EditorElement {
         register_action(editor, window, Editor::accept_inline_completion);
         register_action(editor, window, Editor::revert_file);
         register_action(editor, window, Editor::revert_selected_hunks);
+        register_action(editor, window, Editor::stage_selected_hunks);
+        register_action(editor, window, Editor::unstage_selected_hunks);
+        register_action(editor, window, Editor::stage_selected_lines);
+        register_action(editor, window, Editor::unstage_selected_lines);
+        register_action(editor, window, Editor::accept_conflict_ours);
+        register_action(editor, window, Editor::accept_conflict_theirs);
+        register_action(editor, window, Editor::accept_conflict_both);
+        register_action(editor, window, Editor::go_to_conflict);
+        register_action(editor, window, Editor::go_to_prev_conflict);
         register_action(editor, window, Editor::apply_all_diff_hunks);
         register_action(editor, window, Editor::apply_selected_diff_hunks);
         register_action(editor, window, Editor::open_active_item_in_terminal);
@@ -8692,5 +8713,59 @@ fn diff_hunk_controls(
                     }
                 }),
         )
+        .child(
+            IconButton::new(("stage-hunk", row as u64), IconName::SquarePlus)
+                .shape(IconButtonShape::Square)
+                .icon_size(IconSize::Small)
+                .tooltip({
+                    let focus_handle = editor.focus_handle(cx);
+                    move |window, cx| {
+                        Tooltip::for_action_in(
+                            "Stage Hunk",
+                            &StageSelectedHunks,
+                            &focus_handle,
+                            window,
+                            cx,
+                        )
+                    }
+                })
+                .on_click({
+                    let editor = editor.clone();
+                    move |_event, window, cx| {
+                        editor.update(cx, |editor, cx| {
+                            let snapshot = editor.snapshot(window, cx);
+                            let point = hunk_range.start.to_point(&snapshot.buffer_snapshot);
+                            editor.stage_hunks_in_ranges([point..point].into_iter(), window, cx);
+                        });
+                    }
+                }),
+        )
+        .child(
+            IconButton::new(("unstage-hunk", row as u64), IconName::SquareMinus)
+                .shape(IconButtonShape::Square)
+                .icon_size(IconSize::Small)
+                .tooltip({
+                    let focus_handle = editor.focus_handle(cx);
+                    move |window, cx| {
+                        Tooltip::for_action_in(
+                            "Unstage Hunk",
+                            &UnstageSelectedHunks,
+                            &focus_handle,
+                            window,
+                            cx,
+                        )
+                    }
+                })
+                .on_click({
+                    let editor = editor.clone();
+                    move |_event, window, cx| {
+                        editor.update(cx, |editor, cx| {
+                            let snapshot = editor.snapshot(window, cx);
+                            let point = hunk_range.start.to_point(&snapshot.buffer_snapshot);
+                            editor.unstage_hunks_in_ranges([point..point].into_iter(), window, cx);
+                        });
+                    }
+                }),
+        )
         .into_any_element()
 }
@@ -0,0 +1,111 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::Result;
+use gpui::{Context, Entity, Task, Window};
+use language::{language_settings::LanguageSettings, Buffer};
+use multi_buffer::Anchor;
+use project::{FoldingRange, Project};
+
+use crate::{
+    actions::FoldAllImports,
+    buffer_lsp_data::{BufferLspData, BufferLspRequest},
+    display_map::Crease,
+    Editor,
+};
+
+/// The folding ranges last received for each of the editor's buffers.
+#[derive(Default)]
+pub(crate) struct FoldingRangesState {
+    ranges: BufferLspData<Vec<FoldingRange>>,
+}
+
+pub(crate) struct FoldingRangesRequest;
+
+impl BufferLspRequest for FoldingRangesRequest {
+    type Response = Vec<FoldingRange>;
+
+    const NAME: &'static str = "folding ranges";
+
+    fn enabled(settings: &LanguageSettings) -> bool {
+        settings.use_lsp_folding_ranges
+    }
+
+    fn data(editor: &mut Editor) -> &mut BufferLspData<Self::Response> {
+        &mut editor.folding_ranges.ranges
+    }
+
+    fn request(
+        project: &mut Project,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Self::Response>> {
+        project.folding_ranges(buffer, cx)
+    }
+
+    fn data_changed(editor: &mut Editor, cx: &mut Context<Editor>) {
+        editor.update_lsp_folding_ranges(cx);
+    }
+}
+
+impl Editor {
+    fn update_lsp_folding_ranges(&mut self, cx: &mut Context<Self>) {
+        let ranges = self.lsp_folding_ranges(|_| true, cx);
+        self.display_map.update(cx, |display_map, _| {
+            display_map.set_lsp_folding_ranges(Arc::from(ranges))
+        });
+        cx.notify();
+    }
+
+    /// The folding ranges within the editor's excerpts that match the predicate, in order.
+    fn lsp_folding_ranges(
+        &self,
+        predicate: impl Fn(&FoldingRange) -> bool,
+        cx: &mut Context<Self>,
+    ) -> Vec<Range<Anchor>> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut ranges = Vec::new();
+        for (excerpt_id, buffer, range) in snapshot.excerpts() {
+            let Some(folding_ranges) = self.folding_ranges.ranges.get(&buffer.remote_id()) else {
+                continue;
+            };
+            let context = range.context;
+            for folding_range in folding_ranges {
+                if !predicate(folding_range)
+                    || folding_range
+                        .range
+                        .start
+                        .cmp(&context.start, buffer)
+                        .is_lt()
+                    || folding_range.range.end.cmp(&context.end, buffer).is_gt()
+                {
+                    continue;
+                }
+                if let Some((start, end)) = snapshot
+                    .anchor_in_excerpt(excerpt_id, folding_range.range.start)
+                    .zip(snapshot.anchor_in_excerpt(excerpt_id, folding_range.range.end))
+                {
+                    ranges.push(start..end);
+                }
+            }
+        }
+        ranges
+    }
+
+    pub fn fold_all_imports(
+        &mut self,
+        _: &FoldAllImports,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let placeholder = self.display_map.read(cx).fold_placeholder.clone();
+        let creases = self
+            .lsp_folding_ranges(
+                |range| range.kind == Some(lsp::FoldingRangeKind::Imports),
+                cx,
+            )
+            .into_iter()
+            .map(|range| Crease::simple(range, placeholder.clone()))
+            .collect();
+        self.fold_creases(creases, true, window, cx);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod blame;
+pub mod conflicts;
 pub mod project_diff;
+pub mod staging;
@@ -0,0 +1,542 @@
+use std::{ops::Range, sync::Arc, time::Duration};
+
+use collections::{HashMap, HashSet};
+use git::conflict::parse_conflicts;
+use gpui::{Action, AnyElement, Context, Task, WeakEntity, Window};
+use language::{BufferSnapshot, Point};
+use multi_buffer::{Anchor, MultiBufferSnapshot, ToOffset as _};
+use project::ProjectItem as _;
+use text::{BufferId, OffsetRangeExt as _, ToOffset as _};
+use ui::{prelude::*, Tooltip};
+
+use crate::{
+    scroll::Autoscroll, AcceptConflictBoth, AcceptConflictOurs, AcceptConflictTheirs, BlockContext,
+    BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, Editor, EditorMode, GoToConflict,
+    GoToPrevConflict,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+const CONFLICT_START_MARKER: &str = "<<<<<<<";
+
+/// A merge conflict found in one of the editor's excerpts.
+#[derive(Clone, Debug)]
+pub struct ConflictRegion {
+    /// The whole conflict, including the marker lines.
+    pub range: Range<Anchor>,
+    pub ours: Range<Anchor>,
+    pub theirs: Range<Anchor>,
+    pub ours_name: Option<SharedString>,
+    pub theirs_name: Option<SharedString>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+#[derive(Default)]
+pub(crate) struct ConflictState {
+    regions: Arc<[ConflictRegion]>,
+    pub(crate) block_ids: HashSet<CustomBlockId>,
+    /// The conflicts last found in each conflicted buffer, so that later scans only need to
+    /// parse the text around the edits made since.
+    buffers: HashMap<BufferId, BufferConflicts>,
+    refresh_task: Option<Task<()>>,
+}
+
+#[derive(Clone)]
+struct BufferConflicts {
+    version: clock::Global,
+    conflicts: Vec<BufferConflict>,
+}
+
+/// A conflict in a buffer, before it is mapped into the excerpts showing it.
+#[derive(Clone)]
+struct BufferConflict {
+    range: Range<text::Anchor>,
+    ours: Range<text::Anchor>,
+    theirs: Range<text::Anchor>,
+    ours_name: Option<SharedString>,
+    theirs_name: Option<SharedString>,
+}
+
+enum OursHighlight {}
+enum TheirsHighlight {}
+
+impl Editor {
+    pub fn conflict_regions(&self) -> &[ConflictRegion] {
+        &self.conflicts.regions
+    }
+
+    /// Re-scans the editor's conflicted buffers for conflict markers after a short delay.
+    pub(crate) fn refresh_conflicts(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.mode != EditorMode::Full {
+            return;
+        }
+        self.conflicts.refresh_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+            cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            let Ok((snapshot, buffers)) = editor.update(&mut cx, |editor, cx| {
+                let buffers = editor
+                    .conflicted_buffers(cx)
+                    .into_iter()
+                    .map(|buffer| {
+                        let previous = editor.conflicts.buffers.get(&buffer.remote_id()).cloned();
+                        (buffer, previous)
+                    })
+                    .collect::<Vec<_>>();
+                (editor.buffer.read(cx).snapshot(cx), buffers)
+            }) else {
+                return;
+            };
+            let (buffers, regions) = cx
+                .background_executor()
+                .spawn(async move {
+                    let buffers = buffers
+                        .into_iter()
+                        .map(|(buffer, previous)| {
+                            (buffer.remote_id(), find_buffer_conflicts(&buffer, previous))
+                        })
+                        .collect::<HashMap<_, _>>();
+                    let regions = conflict_regions(&snapshot, &buffers);
+                    (buffers, regions)
+                })
+                .await;
+            editor
+                .update(&mut cx, |editor, cx| {
+                    editor.conflicts.buffers = buffers;
+                    editor.set_conflict_regions(regions, cx)
+                })
+                .ok();
+        }));
+    }
+
+    /// The buffers that git reports as unmerged. Conflict markers anywhere else are just text.
+    fn conflicted_buffers(&self, cx: &App) -> Vec<BufferSnapshot> {
+        let Some(project) = self.project.as_ref() else {
+            return Vec::new();
+        };
+        let project = project.read(cx);
+        self.buffer
+            .read(cx)
+            .all_buffers()
+            .into_iter()
+            .filter_map(|buffer| {
+                let project_path = buffer.read(cx).project_path(cx)?;
+                let status = project.project_path_git_status(&project_path, cx)?;
+                status.is_conflicted().then(|| buffer.read(cx).snapshot())
+            })
+            .collect()
+    }
+
+    fn set_conflict_regions(&mut self, regions: Vec<ConflictRegion>, cx: &mut Context<Self>) {
+        // Rebuilding the blocks re-renders them, so they're left alone when the conflicts
+        // haven't moved, e.g. after an edit elsewhere in the buffer.
+        if regions.len() == self.conflicts.regions.len()
+            && regions
+                .iter()
+                .zip(self.conflicts.regions.iter())
+                .all(|(new, old)| new.same_as(old))
+        {
+            return;
+        }
+
+        let block_ids = std::mem::take(&mut self.conflicts.block_ids);
+        self.remove_blocks(block_ids, None, cx);
+
+        let editor = cx.entity().downgrade();
+        let blocks = regions.iter().enumerate().map(|(ix, region)| {
+            let editor = editor.clone();
+            let region = region.clone();
+            BlockProperties {
+                placement: BlockPlacement::Above(region.range.start),
+                height: 1,
+                style: BlockStyle::Flex,
+                render: Arc::new(move |cx| render_conflict_controls(ix, &region, &editor, cx)),
+                priority: 0,
+            }
+        });
+        self.conflicts.block_ids = self.insert_blocks(blocks, None, cx).into_iter().collect();
+
+        let ours = regions
+            .iter()
+            .map(|region| region.range.start..region.ours.end)
+            .collect::<Vec<_>>();
+        let theirs = regions
+            .iter()
+            .map(|region| region.theirs.start..region.range.end)
+            .collect::<Vec<_>>();
+        self.highlight_background::<OursHighlight>(
+            &ours,
+            |colors| colors.version_control_added_background,
+            cx,
+        );
+        self.highlight_background::<TheirsHighlight>(
+            &theirs,
+            |colors| colors.version_control_modified_background,
+            cx,
+        );
+
+        self.conflicts.regions = regions.into();
+        cx.notify();
+    }
+
+    /// Replaces the given conflicts, markers included, with the chosen side(s).
+    pub fn resolve_conflicts(
+        &mut self,
+        regions: impl IntoIterator<Item = ConflictRegion>,
+        resolution: ConflictResolution,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let edits = regions
+            .into_iter()
+            .map(|region| {
+                let mut text = String::new();
+                if resolution != ConflictResolution::Theirs {
+                    text.extend(snapshot.text_for_range(region.ours.clone()));
+                }
+                if resolution != ConflictResolution::Ours {
+                    text.extend(snapshot.text_for_range(region.theirs.clone()));
+                }
+                (region.range, text)
+            })
+            .collect::<Vec<_>>();
+        if edits.is_empty() {
+            return;
+        }
+        self.transact(window, cx, |editor, _window, cx| {
+            editor
+                .buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        });
+    }
+
+    pub fn accept_conflict_ours(
+        &mut self,
+        _: &AcceptConflictOurs,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let regions = self.selected_conflict_regions(cx);
+        self.resolve_conflicts(regions, ConflictResolution::Ours, window, cx);
+    }
+
+    pub fn accept_conflict_theirs(
+        &mut self,
+        _: &AcceptConflictTheirs,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let regions = self.selected_conflict_regions(cx);
+        self.resolve_conflicts(regions, ConflictResolution::Theirs, window, cx);
+    }
+
+    pub fn accept_conflict_both(
+        &mut self,
+        _: &AcceptConflictBoth,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let regions = self.selected_conflict_regions(cx);
+        self.resolve_conflicts(regions, ConflictResolution::Both, window, cx);
+    }
+
+    pub fn go_to_conflict(
+        &mut self,
+        _: &GoToConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let cursor = self.selections.newest::<usize>(cx).head();
+        let target = self
+            .conflicts
+            .regions
+            .iter()
+            .map(|region| region.range.start.to_offset(&snapshot))
+            .find(|start| *start > cursor);
+        if let Some(target) = target {
+            self.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.select_ranges([target..target])
+            });
+        }
+    }
+
+    pub fn go_to_prev_conflict(
+        &mut self,
+        _: &GoToPrevConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let cursor = self.selections.newest::<usize>(cx).head();
+        let target = self
+            .conflicts
+            .regions
+            .iter()
+            .rev()
+            .map(|region| region.range.start.to_offset(&snapshot))
+            .find(|start| *start < cursor);
+        if let Some(target) = target {
+            self.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.select_ranges([target..target])
+            });
+        }
+    }
+
+    /// The conflicts that intersect any of the selections.
+    fn selected_conflict_regions(&self, cx: &mut Context<Self>) -> Vec<ConflictRegion> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<usize>(cx);
+        self.conflicts
+            .regions
+            .iter()
+            .filter(|region| {
+                let start = region.range.start.to_offset(&snapshot);
+                let end = region.range.end.to_offset(&snapshot);
+                selections
+                    .iter()
+                    .any(|selection| selection.start <= end && selection.end >= start)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+impl ConflictRegion {
+    fn same_as(&self, other: &Self) -> bool {
+        self.range == other.range
+            && self.ours == other.ours
+            && self.theirs == other.theirs
+            && self.ours_name == other.ours_name
+            && self.theirs_name == other.theirs_name
+    }
+}
+
+/// Finds the conflicts in a buffer. When the conflicts in an earlier version of the buffer
+/// are known, only the text around the edits made since then is parsed again.
+fn find_buffer_conflicts(
+    buffer: &BufferSnapshot,
+    previous: Option<BufferConflicts>,
+) -> BufferConflicts {
+    let version = buffer.version().clone();
+    let Some(previous) = previous else {
+        return BufferConflicts {
+            version,
+            conflicts: parse_buffer_conflicts(buffer, 0..buffer.len()),
+        };
+    };
+    if !version.changed_since(&previous.version) {
+        return previous;
+    }
+
+    let mut conflicts = previous.conflicts;
+    let mut ranges = buffer
+        .edits_since::<usize>(&previous.version)
+        .map(|edit| rescan_range(buffer, &conflicts, edit.new))
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|range| range.start);
+    let mut merged_ranges: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged_ranges.last_mut() {
+            Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+            _ => merged_ranges.push(range),
+        }
+    }
+
+    for range in merged_ranges {
+        let start_ix = conflicts
+            .partition_point(|conflict| conflict.range.start.to_offset(buffer) < range.start);
+        let end_ix = conflicts
+            .partition_point(|conflict| conflict.range.start.to_offset(buffer) < range.end);
+        conflicts.splice(start_ix..end_ix, parse_buffer_conflicts(buffer, range));
+    }
+    BufferConflicts { version, conflicts }
+}
+
+/// The text to parse again after an edit, given the conflicts found before it.
+///
+/// Parsing restarts at the last conflict start marker since the previous conflict ended, or
+/// at the edited line when there is none. It stops at the next conflict start marker after
+/// the edit, since the parser forgets everything before such a line.
+fn rescan_range(
+    buffer: &BufferSnapshot,
+    conflicts: &[BufferConflict],
+    edit: Range<usize>,
+) -> Range<usize> {
+    let mut start = buffer.point_to_offset(Point::new(buffer.offset_to_point(edit.start).row, 0));
+    let mut end = edit.end;
+    let mut previous_end = 0;
+    for conflict in conflicts {
+        let range = conflict.range.to_offset(buffer);
+        if range.end < edit.start {
+            previous_end = range.end;
+        } else if range.start <= edit.end {
+            start = start.min(range.start);
+            end = end.max(range.end);
+        }
+    }
+
+    let previous_end_row = buffer.offset_to_point(previous_end).row;
+    let start_row = buffer.offset_to_point(start).row;
+    if let Some(row) = (previous_end_row..start_row)
+        .rev()
+        .find(|row| is_conflict_start(buffer, *row))
+    {
+        start = buffer.point_to_offset(Point::new(row, 0));
+    }
+
+    let start_row = buffer.offset_to_point(start).row;
+    let end_row = buffer.offset_to_point(end).row;
+    if (start_row..=end_row).any(|row| is_conflict_start(buffer, row)) {
+        let max_row = buffer.max_point().row;
+        end = (end_row + 1..=max_row)
+            .find(|row| is_conflict_start(buffer, *row))
+            .map_or(buffer.len(), |row| {
+                buffer.point_to_offset(Point::new(row, 0))
+            });
+    } else {
+        end = buffer.point_to_offset(Point::new(end_row, buffer.line_len(end_row)));
+    }
+    start..end
+}
+
+fn is_conflict_start(buffer: &BufferSnapshot, row: u32) -> bool {
+    let marker_len = CONFLICT_START_MARKER.len() as u32;
+    buffer.line_len(row) >= marker_len
+        && buffer
+            .text_for_range(Point::new(row, 0)..Point::new(row, marker_len))
+            .flat_map(str::chars)
+            .eq(CONFLICT_START_MARKER.chars())
+}
+
+fn parse_buffer_conflicts(buffer: &BufferSnapshot, range: Range<usize>) -> Vec<BufferConflict> {
+    let text = buffer.text_for_range(range.clone()).collect::<String>();
+    if !text.contains(CONFLICT_START_MARKER) {
+        return Vec::new();
+    }
+    let anchor = |offset: usize| buffer.anchor_before(range.start + offset);
+    parse_conflicts(&text)
+        .into_iter()
+        .map(|conflict| BufferConflict {
+            range: anchor(conflict.range.start)..anchor(conflict.range.end),
+            ours: anchor(conflict.ours.start)..anchor(conflict.ours.end),
+            theirs: anchor(conflict.theirs.start)..anchor(conflict.theirs.end),
+            ours_name: conflict.ours_name.map(Into::into),
+            theirs_name: conflict.theirs_name.map(Into::into),
+        })
+        .collect()
+}
+
+/// Maps the conflicts of each buffer into the excerpts that fully contain them.
+fn conflict_regions(
+    snapshot: &MultiBufferSnapshot,
+    buffers: &HashMap<BufferId, BufferConflicts>,
+) -> Vec<ConflictRegion> {
+    let mut regions = Vec::new();
+    for (excerpt_id, buffer, excerpt_range) in snapshot.excerpts() {
+        let Some(conflicts) = buffers.get(&buffer.remote_id()) else {
+            continue;
+        };
+        let context = excerpt_range.context.to_offset(buffer);
+        let anchor = |anchor: text::Anchor| snapshot.anchor_in_excerpt(excerpt_id, anchor);
+        for conflict in &conflicts.conflicts {
+            let range = conflict.range.to_offset(buffer);
+            if range.start < context.start || range.end > context.end {
+                continue;
+            }
+            let (
+                Some(start),
+                Some(end),
+                Some(ours_start),
+                Some(ours_end),
+                Some(theirs_start),
+                Some(theirs_end),
+            ) = (
+                anchor(conflict.range.start),
+                anchor(conflict.range.end),
+                anchor(conflict.ours.start),
+                anchor(conflict.ours.end),
+                anchor(conflict.theirs.start),
+                anchor(conflict.theirs.end),
+            )
+            else {
+                continue;
+            };
+            regions.push(ConflictRegion {
+                range: start..end,
+                ours: ours_start..ours_end,
+                theirs: theirs_start..theirs_end,
+                ours_name: conflict.ours_name.clone(),
+                theirs_name: conflict.theirs_name.clone(),
+            });
+        }
+    }
+    regions
+}
+
+fn render_conflict_controls(
+    ix: usize,
+    region: &ConflictRegion,
+    editor: &WeakEntity<Editor>,
+    cx: &mut BlockContext,
+) -> AnyElement {
+    let button = |id: &'static str,
+                  label: String,
+                  resolution: ConflictResolution,
+                  tooltip: &'static str,
+                  action: &'static dyn Action| {
+        let editor = editor.clone();
+        let region = region.clone();
+        Button::new((id, ix), label)
+            .label_size(LabelSize::Small)
+            .tooltip(move |window, cx| Tooltip::for_action(tooltip, action, window, cx))
+            .on_click(move |_, window, cx| {
+                editor
+                    .update(cx, |editor, cx| {
+                        editor.resolve_conflicts([region.clone()], resolution, window, cx)
+                    })
+                    .ok();
+            })
+    };
+    let ours_label = match &region.ours_name {
+        Some(name) => format!("Accept Ours ({name})"),
+        None => "Accept Ours".to_string(),
+    };
+    let theirs_label = match &region.theirs_name {
+        Some(name) => format!("Accept Theirs ({name})"),
+        None => "Accept Theirs".to_string(),
+    };
+
+    h_flex()
+        .id(cx.block_id)
+        .h(cx.line_height)
+        .pl(cx.anchor_x)
+        .gap_1()
+        .block_mouse_down()
+        .child(button(
+            "accept-ours",
+            ours_label,
+            ConflictResolution::Ours,
+            "Keep the current change",
+            &AcceptConflictOurs,
+        ))
+        .child(button(
+            "accept-theirs",
+            theirs_label,
+            ConflictResolution::Theirs,
+            "Keep the incoming change",
+            &AcceptConflictTheirs,
+        ))
+        .child(button(
+            "accept-both",
+            "Accept Both".to_string(),
+            ConflictResolution::Both,
+            "Keep both changes",
+            &AcceptConflictBoth,
+        ))
+        .into_any_element()
+}
@@ -0,0 +1,180 @@
+use std::ops::Range;
+
+use anyhow::Context as _;
+use collections::HashMap;
+use gpui::{Context, Window};
+use language::{LineEnding, Point};
+use text::{BufferId, ToPoint as _};
+use workspace::notifications::NotifyTaskExt as _;
+
+use crate::{
+    Editor, StageSelectedHunks, StageSelectedLines, UnstageSelectedHunks, UnstageSelectedLines,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum IndexUpdate {
+    Stage,
+    Unstage { whole_hunks: bool },
+}
+
+impl Editor {
+    /// Stages every unstaged hunk that intersects a selection.
+    pub fn stage_selected_hunks(
+        &mut self,
+        _: &StageSelectedHunks,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let selections = self.selections.all(cx).into_iter().map(|s| s.range());
+        self.stage_hunks_in_ranges(selections, window, cx);
+    }
+
+    /// Unstages every staged hunk that intersects a selection.
+    pub fn unstage_selected_hunks(
+        &mut self,
+        _: &UnstageSelectedHunks,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let selections = self.selections.all(cx).into_iter().map(|s| s.range());
+        self.unstage_hunks_in_ranges(selections, window, cx);
+    }
+
+    /// Stages only the changed lines that are selected, leaving the rest of their hunks
+    /// unstaged.
+    pub fn stage_selected_lines(
+        &mut self,
+        _: &StageSelectedLines,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let rows = self.selected_buffer_rows(cx);
+        self.update_index_for_rows(rows, IndexUpdate::Stage, window, cx);
+    }
+
+    /// Unstages only the staged lines that are selected.
+    pub fn unstage_selected_lines(
+        &mut self,
+        _: &UnstageSelectedLines,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let rows = self.selected_buffer_rows(cx);
+        self.update_index_for_rows(
+            rows,
+            IndexUpdate::Unstage { whole_hunks: false },
+            window,
+            cx,
+        );
+    }
+
+    pub(crate) fn stage_hunks_in_ranges(
+        &mut self,
+        ranges: impl Iterator<Item = Range<Point>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.snapshot(window, cx);
+        let mut rows: HashMap<BufferId, Vec<Range<u32>>> = HashMap::default();
+        for hunk in snapshot.hunks_for_ranges(ranges) {
+            let Some(buffer) = self.buffer.read(cx).buffer(hunk.buffer_id) else {
+                continue;
+            };
+            let buffer = buffer.read(cx);
+            let start = hunk.buffer_range.start.to_point(buffer).row;
+            let end = hunk.buffer_range.end.to_point(buffer).row;
+            // Hunks that only remove lines are attached to the row that follows them.
+            rows.entry(hunk.buffer_id)
+                .or_default()
+                .push(start..end.max(start + 1));
+        }
+        self.update_index_for_rows(rows, IndexUpdate::Stage, window, cx);
+    }
+
+    pub(crate) fn unstage_hunks_in_ranges(
+        &mut self,
+        ranges: impl Iterator<Item = Range<Point>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let rows = self.buffer_rows_for_ranges(ranges, cx);
+        self.update_index_for_rows(rows, IndexUpdate::Unstage { whole_hunks: true }, window, cx);
+    }
+
+    fn selected_buffer_rows(&self, cx: &mut Context<Self>) -> HashMap<BufferId, Vec<Range<u32>>> {
+        let selections = self.selections.all::<Point>(cx);
+        self.buffer_rows_for_ranges(selections.into_iter().map(|s| s.range()), cx)
+    }
+
+    /// Converts multibuffer ranges into the rows they cover in each underlying buffer.
+    fn buffer_rows_for_ranges(
+        &self,
+        ranges: impl Iterator<Item = Range<Point>>,
+        cx: &mut Context<Self>,
+    ) -> HashMap<BufferId, Vec<Range<u32>>> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut rows: HashMap<BufferId, Vec<Range<u32>>> = HashMap::default();
+        for range in ranges {
+            for (buffer, buffer_range, _) in snapshot.range_to_buffer_ranges(range) {
+                let start = buffer_range.start.to_point(buffer).row;
+                let end = buffer_range.end.to_point(buffer).row;
+                rows.entry(buffer.remote_id())
+                    .or_default()
+                    .push(start..end + 1);
+            }
+        }
+        rows
+    }
+
+    /// Rewrites the index text of each buffer's file so that the changes on the given rows
+    /// are staged or unstaged.
+    fn update_index_for_rows(
+        &mut self,
+        rows: HashMap<BufferId, Vec<Range<u32>>>,
+        update: IndexUpdate,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(git_state) = self
+            .project
+            .as_ref()
+            .and_then(|project| project.read(cx).git_state().cloned())
+        else {
+            return;
+        };
+        let updates = rows
+            .into_iter()
+            .filter_map(|(buffer_id, rows)| {
+                let buffer = self.buffer.read(cx).buffer(buffer_id)?;
+                let buffer = buffer.read(cx);
+                let project_path = buffer.project_path(cx)?;
+                let (repository, repo_path) = git_state
+                    .read(cx)
+                    .repository_for_project_path(&project_path)?;
+                // Compare against the index using the line endings the file is saved with.
+                let mut buffer_text = buffer.text();
+                if buffer.line_ending() == LineEnding::Windows {
+                    buffer_text = buffer_text.replace('\n', "\r\n");
+                }
+                Some(match update {
+                    IndexUpdate::Stage => repository.stage_lines(repo_path, buffer_text, rows),
+                    IndexUpdate::Unstage { whole_hunks } => {
+                        repository.unstage_lines(repo_path, buffer_text, rows, whole_hunks)
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        if updates.is_empty() {
+            return;
+        }
+
+        cx.background_executor()
+            .spawn(async move {
+                for update in updates {
+                    update.await.context("index update was canceled")??;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_notify_err(window, cx);
+    }
+}
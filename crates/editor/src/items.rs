@@ -3,7 +3,7 @@ use crate::{
     persistence::{SerializedEditor, DB},
     scroll::ScrollAnchor,
     Anchor, Autoscroll, Editor, EditorEvent, EditorSettings, ExcerptId, ExcerptRange, FormatTarget,
-    MultiBuffer, MultiBufferSnapshot, NavigationData, SearchWithinRange, ToPoint as _,
+    Inlay, MultiBuffer, MultiBufferSnapshot, NavigationData, SearchWithinRange, ToPoint as _,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashSet;
@@ -29,7 +29,7 @@ use std::{
     any::TypeId,
     borrow::Cow,
     cmp::{self, Ordering},
-    iter,
+    iter, mem,
     ops::Range,
     path::Path,
     sync::Arc,
@@ -37,7 +37,7 @@ use std::{
 use text::{BufferId, Selection};
 use theme::{Theme, ThemeSettings};
 use ui::{h_flex, prelude::*, IconDecorationKind, Label};
-use util::{paths::PathExt, ResultExt, TryFutureExt};
+use util::{paths::PathExt, post_inc, ResultExt, TryFutureExt};
 use workspace::item::{Dedup, ItemSettings, SerializableItem, TabContentParams};
 use workspace::{
     item::{BreadcrumbText, FollowEvent},
@@ -1414,19 +1414,10 @@ impl SearchableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
-        let text: Cow<_> = if text.len() == 1 {
-            text.first().cloned().unwrap().into()
-        } else {
-            let joined_chunks = text.join("");
-            joined_chunks.into()
-        };
-
-        if let Some(replacement) = query.replacement_for(&text) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        if let Some(replacement) = replacement_for_match(&buffer, identifier, query) {
             self.transact(window, cx, |this, _, cx| {
-                this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
+                this.edit([(identifier.clone(), replacement)], cx);
             });
         }
     }
@@ -1437,20 +1428,11 @@ impl SearchableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
+        let buffer = self.buffer.read(cx).snapshot(cx);
         let mut edits = vec![];
         for m in matches {
-            let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
-            let text: Cow<_> = if text.len() == 1 {
-                text.first().cloned().unwrap().into()
-            } else {
-                let joined_chunks = text.join("");
-                joined_chunks.into()
-            };
-
-            if let Some(replacement) = query.replacement_for(&text) {
-                edits.push((m.clone(), Arc::from(&*replacement)));
+            if let Some(replacement) = replacement_for_match(&buffer, m, query) {
+                edits.push((m.clone(), replacement));
             }
         }
 
@@ -1593,6 +1575,66 @@ impl SearchableItem for Editor {
     }
 }
 
+impl Editor {
+    /// Shows what each match would be replaced with, right after the match, so that rewrites
+    /// can be checked before they are applied. Passing no query removes the previews.
+    pub fn show_replacement_previews(
+        &mut self,
+        matches: &[Range<Anchor>],
+        query: Option<&SearchQuery>,
+        cx: &mut Context<Self>,
+    ) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let mut previews = Vec::new();
+        if let Some(query) = query.filter(|query| query.replacement().is_some()) {
+            for range in matches {
+                if let Some(replacement) = replacement_for_match(&buffer, range, query) {
+                    previews.push(Inlay::inline_completion(
+                        post_inc(&mut self.next_inlay_id),
+                        range.end,
+                        format!(" → {replacement}"),
+                    ));
+                }
+            }
+        }
+
+        let to_remove = mem::take(&mut self.replacement_previews);
+        self.replacement_previews = previews.iter().map(|inlay| inlay.id).collect();
+        if !to_remove.is_empty() || !previews.is_empty() {
+            self.splice_inlays(to_remove, previews, cx);
+        }
+    }
+}
+
+/// Structural matches are rewritten using the syntax tree of the buffer they were found in,
+/// while text and regex matches only need the matched text.
+fn replacement_for_match(
+    buffer: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<Arc<str>> {
+    if query.is_structural() {
+        let buffer_ranges = buffer.range_to_buffer_ranges(range.clone());
+        let [(buffer, range, _)] = buffer_ranges.as_slice() else {
+            return None;
+        };
+        return query
+            .structural_replacement(buffer, range.clone())
+            .map(Arc::from);
+    }
+
+    let text = buffer.text_for_range(range.clone()).collect::<Vec<_>>();
+    let text: Cow<_> = if text.len() == 1 {
+        text.first().cloned().unwrap().into()
+    } else {
+        let joined_chunks = text.join("");
+        joined_chunks.into()
+    };
+    query
+        .replacement_for(&text)
+        .map(|replacement| Arc::from(&*replacement))
+}
+
 pub fn active_match_index(
     ranges: &[Range<Anchor>],
     cursor: &Anchor,
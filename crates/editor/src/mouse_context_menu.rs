@@ -211,6 +211,14 @@ pub fn deploy_context_menu(
                     } else {
                         builder.disabled_action(COPY_PERMALINK_LABEL, Box::new(CopyPermalinkToLine))
                     }
+                })
+                .map(|builder| {
+                    const FILE_HISTORY_LABEL: &str = "View File History";
+                    if has_git_repo {
+                        builder.action(FILE_HISTORY_LABEL, Box::new(git::FileHistory))
+                    } else {
+                        builder.disabled_action(FILE_HISTORY_LABEL, Box::new(git::FileHistory))
+                    }
                 });
             match focus {
                 Some(focus) => builder.context(focus),
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use collections::HashMap;
+use gpui::{Context, Entity, HighlightStyle, Task};
+use language::{language_settings::LanguageSettings, Buffer};
+use project::{BufferSemanticTokens, Project};
+use text::BufferId;
+use theme::ActiveTheme as _;
+
+use crate::{
+    buffer_lsp_data::{BufferLspData, BufferLspRequest},
+    Editor,
+};
+
+/// The semantic tokens last received for each of the editor's buffers.
+#[derive(Default)]
+pub(crate) struct SemanticTokensState {
+    tokens: BufferLspData<BufferSemanticTokens>,
+}
+
+pub(crate) struct SemanticTokensRequest;
+
+impl BufferLspRequest for SemanticTokensRequest {
+    type Response = BufferSemanticTokens;
+
+    const NAME: &'static str = "semantic tokens";
+
+    fn enabled(settings: &LanguageSettings) -> bool {
+        settings.semantic_tokens
+    }
+
+    fn data(editor: &mut Editor) -> &mut BufferLspData<Self::Response> {
+        &mut editor.semantic_tokens.tokens
+    }
+
+    fn request(
+        project: &mut Project,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Self::Response>> {
+        project.semantic_tokens(buffer, cx)
+    }
+
+    fn data_changed(editor: &mut Editor, cx: &mut Context<Editor>) {
+        editor.update_semantic_token_highlights(cx);
+    }
+}
+
+impl Editor {
+    /// Maps the stored tokens into the editor's excerpts and styles them with the current theme.
+    pub(crate) fn update_semantic_token_highlights(&mut self, cx: &mut Context<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let syntax = cx.theme().syntax().clone();
+        let mut styles = HashMap::<(BufferId, u32, u32), Option<HighlightStyle>>::default();
+        let mut highlights = Vec::new();
+        for (excerpt_id, buffer, range) in snapshot.excerpts() {
+            let buffer_id = buffer.remote_id();
+            let Some(tokens) = self.semantic_tokens.tokens.get(&buffer_id) else {
+                continue;
+            };
+            let context = range.context;
+            let start_ix = tokens
+                .tokens
+                .partition_point(|token| token.range.end.cmp(&context.start, buffer).is_le());
+            for token in &tokens.tokens[start_ix..] {
+                if token.range.start.cmp(&context.end, buffer).is_ge() {
+                    break;
+                }
+                let style = *styles
+                    .entry((buffer_id, token.token_type, token.token_modifiers))
+                    .or_insert_with(|| {
+                        let modifiers = tokens.modifiers(token).collect::<Vec<_>>();
+                        syntax.semantic_token_style(tokens.token_type(token)?, &modifiers)
+                    });
+                let Some(style) = style else {
+                    continue;
+                };
+
+                let start = if token.range.start.cmp(&context.start, buffer).is_lt() {
+                    context.start
+                } else {
+                    token.range.start
+                };
+                let end = if token.range.end.cmp(&context.end, buffer).is_gt() {
+                    context.end
+                } else {
+                    token.range.end
+                };
+                if let Some((start, end)) = snapshot
+                    .anchor_in_excerpt(excerpt_id, start)
+                    .zip(snapshot.anchor_in_excerpt(excerpt_id, end))
+                {
+                    highlights.push((start..end, style));
+                }
+            }
+        }
+
+        let highlights = (!highlights.is_empty()).then(|| Arc::from(highlights));
+        self.display_map.update(cx, |display_map, _| {
+            display_map.set_semantic_token_highlights(highlights)
+        });
+        cx.notify();
+    }
+}
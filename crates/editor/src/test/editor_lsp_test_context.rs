@@ -84,6 +84,24 @@ impl EditorLspTestContext {
         language: Language,
         capabilities: lsp::ServerCapabilities,
         cx: &mut gpui::TestAppContext,
+    ) -> EditorLspTestContext {
+        Self::new_with_adapter(
+            language,
+            FakeLspAdapter {
+                capabilities,
+                ..Default::default()
+            },
+            cx,
+        )
+        .await
+    }
+
+    /// Like [`Self::new`], with a fake language server that is set up by the adapter given,
+    /// e.g. to answer the `initialize` request differently.
+    pub async fn new_with_adapter(
+        language: Language,
+        adapter: FakeLspAdapter,
+        cx: &mut gpui::TestAppContext,
     ) -> EditorLspTestContext {
         let app_state = cx.update(AppState::test);
 
@@ -106,13 +124,7 @@ impl EditorLspTestContext {
         let project = Project::test(app_state.fs.clone(), [], cx).await;
 
         let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-        let mut fake_servers = language_registry.register_fake_lsp(
-            language.name(),
-            FakeLspAdapter {
-                capabilities,
-                ..Default::default()
-            },
-        );
+        let mut fake_servers = language_registry.register_fake_lsp(language.name(), adapter);
         language_registry.add(Arc::new(language));
 
         let root = Self::root_path();
@@ -4,7 +4,7 @@ use crate::{
 };
 use collections::BTreeMap;
 use futures::Future;
-use git::diff::DiffHunkStatus;
+use git::{diff::DiffHunkStatus, status::FileStatus};
 use gpui::{
     prelude::*, AnyWindowHandle, App, Context, Entity, Focusable as _, Keystroke, Pixels, Point,
     VisualTestContext, Window, WindowHandle,
@@ -297,6 +297,19 @@ impl EditorTestContext {
         self.cx.run_until_parked();
     }
 
+    pub fn set_git_status(&mut self, status: FileStatus) {
+        self.cx.run_until_parked();
+        let fs = self.update_editor(|editor, _, cx| {
+            editor.project.as_ref().unwrap().read(cx).fs().as_fake()
+        });
+        let path = self.update_buffer(|buffer, _| buffer.file().unwrap().path().clone());
+        fs.set_status_for_repo_via_git_operation(
+            &Self::root_path().join(".git"),
+            &[(path.as_ref(), status)],
+        );
+        self.cx.run_until_parked();
+    }
+
     /// Change the editor's text and selections using a string containing
     /// embedded range markers that represent the ranges and directions of
     /// each selection.
@@ -1,8 +1,11 @@
 pub mod extension_builder;
 mod extension_host_proxy;
 mod extension_manifest;
+#[cfg(any(test, feature = "test-support"))]
+mod fake_extension;
 mod types;
 
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -16,6 +19,8 @@ use semantic_version::SemanticVersion;
 
 pub use crate::extension_host_proxy::*;
 pub use crate::extension_manifest::*;
+#[cfg(any(test, feature = "test-support"))]
+pub use crate::fake_extension::FakeExtension;
 pub use crate::types::*;
 
 /// Initializes the `extension` crate.
@@ -40,6 +45,18 @@ pub trait KeyValueStoreDelegate: Send + Sync + 'static {
     fn insert(&self, key: String, docs: String) -> Task<Result<()>>;
 }
 
+/// The editor that an extension command runs in.
+///
+/// Ranges are byte offsets into the buffer's text.
+pub trait EditorDelegate: Send + Sync + 'static {
+    fn text(&self) -> String;
+    fn path(&self) -> Option<String>;
+    fn language_name(&self) -> Option<String>;
+    fn selections(&self) -> Vec<Range<usize>>;
+    fn edit(&self, edits: Vec<TextEdit>) -> Result<()>;
+    fn set_selections(&self, selections: Vec<Range<usize>>) -> Result<()>;
+}
+
 #[async_trait]
 pub trait Extension: Send + Sync + 'static {
     /// Returns the [`ExtensionManifest`] for this extension.
@@ -112,6 +129,30 @@ pub trait Extension: Send + Sync + 'static {
         package_name: Arc<str>,
         kv_store: Arc<dyn KeyValueStoreDelegate>,
     ) -> Result<()>;
+
+    async fn run_editor_command(
+        &self,
+        command_id: Arc<str>,
+        editor: Arc<dyn EditorDelegate>,
+    ) -> Result<()>;
+
+    async fn tasks_for_worktree(
+        &self,
+        provider_id: Arc<str>,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<TaskTemplate>>;
+
+    async fn format_buffer(
+        &self,
+        formatter_id: Arc<str>,
+        request: FormatRequest,
+    ) -> Result<Vec<TextEdit>>;
+
+    async fn code_actions(
+        &self,
+        provider_id: Arc<str>,
+        request: CodeActionRequest,
+    ) -> Result<Vec<CodeAction>>;
 }
 
 pub fn parse_wasm_extension_version(
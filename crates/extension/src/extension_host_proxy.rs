@@ -29,6 +29,10 @@ pub struct ExtensionHostProxy {
     slash_command_proxy: RwLock<Option<Arc<dyn ExtensionSlashCommandProxy>>>,
     context_server_proxy: RwLock<Option<Arc<dyn ExtensionContextServerProxy>>>,
     indexed_docs_provider_proxy: RwLock<Option<Arc<dyn ExtensionIndexedDocsProviderProxy>>>,
+    command_proxy: RwLock<Option<Arc<dyn ExtensionCommandProxy>>>,
+    task_provider_proxy: RwLock<Option<Arc<dyn ExtensionTaskProviderProxy>>>,
+    formatter_proxy: RwLock<Option<Arc<dyn ExtensionFormatterProxy>>>,
+    code_action_provider_proxy: RwLock<Option<Arc<dyn ExtensionCodeActionProviderProxy>>>,
 }
 
 impl ExtensionHostProxy {
@@ -54,6 +58,10 @@ impl ExtensionHostProxy {
             slash_command_proxy: RwLock::default(),
             context_server_proxy: RwLock::default(),
             indexed_docs_provider_proxy: RwLock::default(),
+            command_proxy: RwLock::default(),
+            task_provider_proxy: RwLock::default(),
+            formatter_proxy: RwLock::default(),
+            code_action_provider_proxy: RwLock::default(),
         }
     }
 
@@ -93,6 +101,27 @@ impl ExtensionHostProxy {
             .write()
             .replace(Arc::new(proxy));
     }
+
+    pub fn register_command_proxy(&self, proxy: impl ExtensionCommandProxy) {
+        self.command_proxy.write().replace(Arc::new(proxy));
+    }
+
+    pub fn register_task_provider_proxy(&self, proxy: impl ExtensionTaskProviderProxy) {
+        self.task_provider_proxy.write().replace(Arc::new(proxy));
+    }
+
+    pub fn register_formatter_proxy(&self, proxy: impl ExtensionFormatterProxy) {
+        self.formatter_proxy.write().replace(Arc::new(proxy));
+    }
+
+    pub fn register_code_action_provider_proxy(
+        &self,
+        proxy: impl ExtensionCodeActionProviderProxy,
+    ) {
+        self.code_action_provider_proxy
+            .write()
+            .replace(Arc::new(proxy));
+    }
 }
 
 pub trait ExtensionThemeProxy: Send + Sync + 'static {
@@ -372,3 +401,129 @@ impl ExtensionIndexedDocsProviderProxy for ExtensionHostProxy {
         proxy.register_indexed_docs_provider(extension, provider_id)
     }
 }
+
+pub trait ExtensionCommandProxy: Send + Sync + 'static {
+    fn register_command(&self, extension: Arc<dyn Extension>, command_id: Arc<str>, cx: &mut App);
+
+    fn remove_command(&self, extension_id: &Arc<str>, command_id: &Arc<str>, cx: &mut App);
+}
+
+impl ExtensionCommandProxy for ExtensionHostProxy {
+    fn register_command(&self, extension: Arc<dyn Extension>, command_id: Arc<str>, cx: &mut App) {
+        let Some(proxy) = self.command_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.register_command(extension, command_id, cx)
+    }
+
+    fn remove_command(&self, extension_id: &Arc<str>, command_id: &Arc<str>, cx: &mut App) {
+        let Some(proxy) = self.command_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.remove_command(extension_id, command_id, cx)
+    }
+}
+
+pub trait ExtensionTaskProviderProxy: Send + Sync + 'static {
+    fn register_task_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        cx: &mut App,
+    );
+
+    fn remove_task_provider(&self, extension_id: &Arc<str>, provider_id: &Arc<str>, cx: &mut App);
+}
+
+impl ExtensionTaskProviderProxy for ExtensionHostProxy {
+    fn register_task_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        cx: &mut App,
+    ) {
+        let Some(proxy) = self.task_provider_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.register_task_provider(extension, provider_id, cx)
+    }
+
+    fn remove_task_provider(&self, extension_id: &Arc<str>, provider_id: &Arc<str>, cx: &mut App) {
+        let Some(proxy) = self.task_provider_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.remove_task_provider(extension_id, provider_id, cx)
+    }
+}
+
+pub trait ExtensionFormatterProxy: Send + Sync + 'static {
+    fn register_formatter(&self, extension: Arc<dyn Extension>, formatter_id: Arc<str>);
+
+    fn remove_formatter(&self, extension_id: &Arc<str>, formatter_id: &Arc<str>);
+}
+
+impl ExtensionFormatterProxy for ExtensionHostProxy {
+    fn register_formatter(&self, extension: Arc<dyn Extension>, formatter_id: Arc<str>) {
+        let Some(proxy) = self.formatter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.register_formatter(extension, formatter_id)
+    }
+
+    fn remove_formatter(&self, extension_id: &Arc<str>, formatter_id: &Arc<str>) {
+        let Some(proxy) = self.formatter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.remove_formatter(extension_id, formatter_id)
+    }
+}
+
+pub trait ExtensionCodeActionProviderProxy: Send + Sync + 'static {
+    fn register_code_action_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        cx: &mut App,
+    );
+
+    fn remove_code_action_provider(
+        &self,
+        extension_id: &Arc<str>,
+        provider_id: &Arc<str>,
+        cx: &mut App,
+    );
+}
+
+impl ExtensionCodeActionProviderProxy for ExtensionHostProxy {
+    fn register_code_action_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        cx: &mut App,
+    ) {
+        let Some(proxy) = self.code_action_provider_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.register_code_action_provider(extension, provider_id, cx)
+    }
+
+    fn remove_code_action_provider(
+        &self,
+        extension_id: &Arc<str>,
+        provider_id: &Arc<str>,
+        cx: &mut App,
+    ) {
+        let Some(proxy) = self.code_action_provider_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.remove_code_action_provider(extension_id, provider_id, cx)
+    }
+}
@@ -84,6 +84,14 @@ pub struct ExtensionManifest {
     #[serde(default)]
     pub indexed_docs_providers: BTreeMap<Arc<str>, IndexedDocsProviderEntry>,
     #[serde(default)]
+    pub commands: BTreeMap<Arc<str>, CommandManifestEntry>,
+    #[serde(default)]
+    pub task_providers: BTreeMap<Arc<str>, TaskProviderManifestEntry>,
+    #[serde(default)]
+    pub formatters: BTreeMap<Arc<str>, FormatterManifestEntry>,
+    #[serde(default)]
+    pub code_action_providers: BTreeMap<Arc<str>, CodeActionProviderManifestEntry>,
+    #[serde(default)]
     pub snippets: Option<PathBuf>,
 }
 
@@ -151,6 +159,39 @@ pub struct SlashCommandManifestEntry {
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct IndexedDocsProviderEntry {}
 
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct CommandManifestEntry {
+    /// The title of the command, as shown in the command palette.
+    pub title: String,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct TaskProviderManifestEntry {
+    /// The list of languages the provided tasks are shown for.
+    ///
+    /// The tasks are shown regardless of the language when this is empty.
+    #[serde(default)]
+    pub languages: Vec<LanguageName>,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct FormatterManifestEntry {
+    /// The list of languages the formatter can format.
+    ///
+    /// The formatter can format any language when this is empty.
+    #[serde(default)]
+    pub languages: Vec<LanguageName>,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct CodeActionProviderManifestEntry {
+    /// The list of languages the provider offers code actions for.
+    ///
+    /// Code actions are requested for buffers of any language when this is empty.
+    #[serde(default)]
+    pub languages: Vec<LanguageName>,
+}
+
 impl ExtensionManifest {
     pub async fn load(fs: Arc<dyn Fs>, extension_dir: &Path) -> Result<Self> {
         let extension_name = extension_dir
@@ -217,6 +258,10 @@ fn manifest_from_old_manifest(
         context_servers: BTreeMap::default(),
         slash_commands: BTreeMap::default(),
         indexed_docs_providers: BTreeMap::default(),
+        commands: BTreeMap::default(),
+        task_providers: BTreeMap::default(),
+        formatters: BTreeMap::default(),
+        code_action_providers: BTreeMap::default(),
         snippets: None,
     }
 }
@@ -0,0 +1,227 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use ::lsp::LanguageServerName;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use language::LanguageName;
+use parking_lot::Mutex;
+
+use crate::{
+    CodeAction, CodeActionRequest, CodeLabel, Command, Completion, EditorDelegate, Extension,
+    ExtensionManifest, FormatRequest, KeyValueStoreDelegate, ProjectDelegate, SchemaVersion,
+    SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, Symbol, TaskTemplate,
+    TextEdit, WorktreeDelegate,
+};
+
+type EditorCommandHandler = Box<dyn Fn(Arc<str>, Arc<dyn EditorDelegate>) -> Result<()> + Send>;
+type CodeActionsHandler =
+    Box<dyn Fn(Arc<str>, CodeActionRequest) -> Result<Vec<CodeAction>> + Send>;
+type TasksFuture = BoxFuture<'static, Result<Vec<TaskTemplate>>>;
+type TasksHandler = Box<dyn Fn(Arc<str>, Arc<dyn WorktreeDelegate>) -> TasksFuture + Send>;
+
+/// An in-memory [`Extension`] for tests.
+///
+/// Only the capabilities that a test registers a handler for are supported;
+/// everything else returns an error.
+pub struct FakeExtension {
+    manifest: Arc<ExtensionManifest>,
+    editor_command_handler: Mutex<Option<EditorCommandHandler>>,
+    code_actions_handler: Mutex<Option<CodeActionsHandler>>,
+    tasks_handler: Mutex<Option<TasksHandler>>,
+}
+
+impl FakeExtension {
+    pub fn new(id: &str, update_manifest: impl FnOnce(&mut ExtensionManifest)) -> Self {
+        let mut manifest = ExtensionManifest {
+            id: id.into(),
+            name: id.to_string(),
+            version: "0.1.0".into(),
+            schema_version: SchemaVersion::ZERO,
+            description: None,
+            repository: None,
+            authors: Vec::new(),
+            lib: Default::default(),
+            themes: Vec::new(),
+            icon_themes: Vec::new(),
+            languages: Vec::new(),
+            grammars: Default::default(),
+            language_servers: Default::default(),
+            context_servers: Default::default(),
+            slash_commands: Default::default(),
+            indexed_docs_providers: Default::default(),
+            commands: Default::default(),
+            task_providers: Default::default(),
+            formatters: Default::default(),
+            code_action_providers: Default::default(),
+            snippets: None,
+        };
+        update_manifest(&mut manifest);
+        Self {
+            manifest: Arc::new(manifest),
+            editor_command_handler: Default::default(),
+            code_actions_handler: Default::default(),
+            tasks_handler: Default::default(),
+        }
+    }
+
+    pub fn on_run_editor_command(
+        &self,
+        handler: impl Fn(Arc<str>, Arc<dyn EditorDelegate>) -> Result<()> + Send + 'static,
+    ) {
+        *self.editor_command_handler.lock() = Some(Box::new(handler));
+    }
+
+    pub fn on_code_actions(
+        &self,
+        handler: impl Fn(Arc<str>, CodeActionRequest) -> Result<Vec<CodeAction>> + Send + 'static,
+    ) {
+        *self.code_actions_handler.lock() = Some(Box::new(handler));
+    }
+
+    pub fn on_tasks_for_worktree(
+        &self,
+        handler: impl Fn(Arc<str>, Arc<dyn WorktreeDelegate>) -> TasksFuture + Send + 'static,
+    ) {
+        *self.tasks_handler.lock() = Some(Box::new(handler));
+    }
+
+    fn unsupported<T>(&self, capability: &str) -> Result<T> {
+        Err(anyhow!(
+            "fake extension {:?} does not support {capability}",
+            self.manifest.id
+        ))
+    }
+}
+
+#[async_trait]
+impl Extension for FakeExtension {
+    fn manifest(&self) -> Arc<ExtensionManifest> {
+        self.manifest.clone()
+    }
+
+    fn work_dir(&self) -> Arc<Path> {
+        Path::new("/extensions/work")
+            .join(&*self.manifest.id)
+            .into()
+    }
+
+    async fn language_server_command(
+        &self,
+        _: LanguageServerName,
+        _: LanguageName,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Command> {
+        self.unsupported("language servers")
+    }
+
+    async fn language_server_initialization_options(
+        &self,
+        _: LanguageServerName,
+        _: LanguageName,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Option<String>> {
+        self.unsupported("language servers")
+    }
+
+    async fn language_server_workspace_configuration(
+        &self,
+        _: LanguageServerName,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Option<String>> {
+        self.unsupported("language servers")
+    }
+
+    async fn labels_for_completions(
+        &self,
+        _: LanguageServerName,
+        _: Vec<Completion>,
+    ) -> Result<Vec<Option<CodeLabel>>> {
+        self.unsupported("language servers")
+    }
+
+    async fn labels_for_symbols(
+        &self,
+        _: LanguageServerName,
+        _: Vec<Symbol>,
+    ) -> Result<Vec<Option<CodeLabel>>> {
+        self.unsupported("language servers")
+    }
+
+    async fn complete_slash_command_argument(
+        &self,
+        _: SlashCommand,
+        _: Vec<String>,
+    ) -> Result<Vec<SlashCommandArgumentCompletion>> {
+        self.unsupported("slash commands")
+    }
+
+    async fn run_slash_command(
+        &self,
+        _: SlashCommand,
+        _: Vec<String>,
+        _: Option<Arc<dyn WorktreeDelegate>>,
+    ) -> Result<SlashCommandOutput> {
+        self.unsupported("slash commands")
+    }
+
+    async fn context_server_command(
+        &self,
+        _: Arc<str>,
+        _: Arc<dyn ProjectDelegate>,
+    ) -> Result<Command> {
+        self.unsupported("context servers")
+    }
+
+    async fn suggest_docs_packages(&self, _: Arc<str>) -> Result<Vec<String>> {
+        self.unsupported("indexed docs providers")
+    }
+
+    async fn index_docs(
+        &self,
+        _: Arc<str>,
+        _: Arc<str>,
+        _: Arc<dyn KeyValueStoreDelegate>,
+    ) -> Result<()> {
+        self.unsupported("indexed docs providers")
+    }
+
+    async fn run_editor_command(
+        &self,
+        command_id: Arc<str>,
+        editor: Arc<dyn EditorDelegate>,
+    ) -> Result<()> {
+        match self.editor_command_handler.lock().as_ref() {
+            Some(handler) => handler(command_id, editor),
+            None => self.unsupported("editor commands"),
+        }
+    }
+
+    async fn tasks_for_worktree(
+        &self,
+        provider_id: Arc<str>,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<TaskTemplate>> {
+        let tasks = match self.tasks_handler.lock().as_ref() {
+            Some(handler) => handler(provider_id, worktree),
+            None => return self.unsupported("task providers"),
+        };
+        tasks.await
+    }
+
+    async fn format_buffer(&self, _: Arc<str>, _: FormatRequest) -> Result<Vec<TextEdit>> {
+        self.unsupported("formatters")
+    }
+
+    async fn code_actions(
+        &self,
+        provider_id: Arc<str>,
+        request: CodeActionRequest,
+    ) -> Result<Vec<CodeAction>> {
+        match self.code_actions_handler.lock().as_ref() {
+            Some(handler) => handler(provider_id, request),
+            None => self.unsupported("code action providers"),
+        }
+    }
+}
@@ -1,10 +1,18 @@
+mod code_action;
+mod editor;
+mod formatter;
 mod lsp;
 mod slash_command;
+mod task;
 
 use std::ops::Range;
 
+pub use code_action::*;
+pub use editor::*;
+pub use formatter::*;
 pub use lsp::*;
 pub use slash_command::*;
+pub use task::*;
 
 /// A list of environment variables.
 pub type EnvVars = Vec<(String, String)>;
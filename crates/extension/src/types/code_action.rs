@@ -0,0 +1,27 @@
+use std::ops::Range;
+
+use crate::TextEdit;
+
+/// A request for the code actions that apply to a range of a buffer.
+///
+/// All ranges are byte offsets into the buffer's text.
+#[derive(Debug, Clone)]
+pub struct CodeActionRequest {
+    /// The text of the buffer.
+    pub text: String,
+    /// The path of the buffer relative to its worktree, if it has one.
+    pub path: Option<String>,
+    /// The name of the language of the buffer, if it has one.
+    pub language_name: Option<String>,
+    /// The range of the buffer that the code actions should apply to.
+    pub range: Range<usize>,
+}
+
+/// An action that edits a buffer, shown in the code actions menu.
+#[derive(Debug, Clone)]
+pub struct CodeAction {
+    /// The title of the code action.
+    pub title: String,
+    /// The edits that the code action makes to the text of the request.
+    pub edits: Vec<TextEdit>,
+}
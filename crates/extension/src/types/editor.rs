@@ -0,0 +1,10 @@
+use std::ops::Range;
+
+/// An edit to the text of a buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The byte range of the text to replace.
+    pub range: Range<usize>,
+    /// The text to replace the range with.
+    pub new_text: String,
+}
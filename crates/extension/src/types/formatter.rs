@@ -0,0 +1,16 @@
+use std::ops::Range;
+
+/// A request to format the text of a buffer.
+///
+/// All ranges are byte offsets into the buffer's text.
+#[derive(Debug, Clone)]
+pub struct FormatRequest {
+    /// The text of the buffer.
+    pub text: String,
+    /// The path of the buffer relative to its worktree, if it has one.
+    pub path: Option<String>,
+    /// The name of the language of the buffer, if it has one.
+    pub language_name: Option<String>,
+    /// The ranges of the text to format, or `None` to format the whole buffer.
+    pub ranges: Option<Vec<Range<usize>>>,
+}
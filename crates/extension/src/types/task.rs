@@ -0,0 +1,18 @@
+use crate::EnvVars;
+
+/// A template for a task that runs a command in a terminal.
+#[derive(Debug, Clone)]
+pub struct TaskTemplate {
+    /// The human-readable label of the task.
+    pub label: String,
+    /// The command to run.
+    pub command: String,
+    /// The arguments to pass to the command.
+    pub args: Vec<String>,
+    /// The environment variables to set for the command.
+    pub env: EnvVars,
+    /// The directory to run the command in, relative to the worktree root.
+    pub cwd: Option<String>,
+    /// The tags used to attach the task to runnables in the editor.
+    pub tags: Vec<String>,
+}
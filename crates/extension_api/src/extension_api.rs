@@ -15,6 +15,9 @@ pub use serde_json;
 // that we may want to shadow to provide a cleaner Rust API.
 pub use wit::{
     download_file, make_file_executable,
+    zed::extension::code_action::{CodeAction, CodeActionRequest},
+    zed::extension::editor::{Editor, TextEdit},
+    zed::extension::formatter::FormatRequest,
     zed::extension::github::{
         github_release_by_tag_name, latest_github_release, GithubRelease, GithubReleaseAsset,
         GithubReleaseOptions,
@@ -27,6 +30,7 @@ pub use wit::{
     zed::extension::slash_command::{
         SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, SlashCommandOutputSection,
     },
+    zed::extension::task_provider::TaskTemplate,
     CodeLabel, CodeLabelSpan, CodeLabelSpanLiteral, Command, DownloadedFileType, EnvVars,
     KeyValueStore, LanguageServerInstallationStatus, Project, Range, Worktree,
 };
@@ -156,6 +160,46 @@ pub trait Extension: Send + Sync {
     ) -> Result<(), String> {
         Err("`index_docs` not implemented".to_string())
     }
+
+    /// Runs the specified command in the given editor.
+    ///
+    /// Commands are declared in the `commands` section of the extension's manifest.
+    fn run_editor_command(&mut self, _command_id: &CommandId, _editor: &Editor) -> Result<()> {
+        Err("`run_editor_command` not implemented".to_string())
+    }
+
+    /// Returns the task templates that the specified task provider contributes to the given worktree.
+    ///
+    /// Task providers are declared in the `task_providers` section of the extension's manifest.
+    fn tasks_for_worktree(
+        &mut self,
+        _provider_id: &TaskProviderId,
+        _worktree: &Worktree,
+    ) -> Result<Vec<TaskTemplate>> {
+        Err("`tasks_for_worktree` not implemented".to_string())
+    }
+
+    /// Formats a buffer using the specified formatter, returning the edits to apply to its text.
+    ///
+    /// Formatters are declared in the `formatters` section of the extension's manifest.
+    fn format_buffer(
+        &mut self,
+        _formatter_id: &FormatterId,
+        _request: FormatRequest,
+    ) -> Result<Vec<TextEdit>> {
+        Err("`format_buffer` not implemented".to_string())
+    }
+
+    /// Returns the code actions that the specified code action provider offers for a range of a buffer.
+    ///
+    /// Code action providers are declared in the `code_action_providers` section of the extension's manifest.
+    fn code_actions(
+        &mut self,
+        _provider_id: &CodeActionProviderId,
+        _request: CodeActionRequest,
+    ) -> Result<Vec<CodeAction>> {
+        Err("`code_actions` not implemented".to_string())
+    }
 }
 
 /// Registers the provided type as a Zed extension.
@@ -195,7 +239,7 @@ mod wit {
 
     wit_bindgen::generate!({
         skip: ["init-extension"],
-        path: "./wit/since_v0.2.0",
+        path: "./wit/since_v0.3.0",
     });
 }
 
@@ -298,6 +342,26 @@ impl wit::Guest for Component {
     ) -> Result<(), String> {
         extension().index_docs(provider, package, database)
     }
+
+    fn run_editor_command(command_id: String, editor: &Editor) -> Result<()> {
+        let command_id = CommandId(command_id);
+        extension().run_editor_command(&command_id, editor)
+    }
+
+    fn tasks_for_worktree(provider_id: String, worktree: &Worktree) -> Result<Vec<TaskTemplate>> {
+        let provider_id = TaskProviderId(provider_id);
+        extension().tasks_for_worktree(&provider_id, worktree)
+    }
+
+    fn format_buffer(formatter_id: String, request: FormatRequest) -> Result<Vec<TextEdit>> {
+        let formatter_id = FormatterId(formatter_id);
+        extension().format_buffer(&formatter_id, request)
+    }
+
+    fn code_actions(provider_id: String, request: CodeActionRequest) -> Result<Vec<CodeAction>> {
+        let provider_id = CodeActionProviderId(provider_id);
+        extension().code_actions(&provider_id, request)
+    }
 }
 
 /// The ID of a language server.
@@ -332,6 +396,70 @@ impl fmt::Display for ContextServerId {
     }
 }
 
+/// The ID of an editor command.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct CommandId(String);
+
+impl AsRef<str> for CommandId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CommandId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The ID of a task provider.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct TaskProviderId(String);
+
+impl AsRef<str> for TaskProviderId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TaskProviderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The ID of a formatter.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct FormatterId(String);
+
+impl AsRef<str> for FormatterId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for FormatterId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The ID of a code action provider.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct CodeActionProviderId(String);
+
+impl AsRef<str> for CodeActionProviderId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CodeActionProviderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl CodeLabelSpan {
     /// Returns a [`CodeLabelSpan::CodeRange`].
     pub fn code_range(range: impl Into<wit::Range>) -> Self {
@@ -13,9 +13,11 @@ use collections::{btree_map, BTreeMap, HashMap, HashSet};
 use extension::extension_builder::{CompileExtensionOptions, ExtensionBuilder};
 pub use extension::ExtensionManifest;
 use extension::{
-    ExtensionContextServerProxy, ExtensionGrammarProxy, ExtensionHostProxy,
+    ExtensionCodeActionProviderProxy, ExtensionCommandProxy, ExtensionContextServerProxy,
+    ExtensionFormatterProxy, ExtensionGrammarProxy, ExtensionHostProxy,
     ExtensionIndexedDocsProviderProxy, ExtensionLanguageProxy, ExtensionLanguageServerProxy,
-    ExtensionSlashCommandProxy, ExtensionSnippetProxy, ExtensionThemeProxy,
+    ExtensionSlashCommandProxy, ExtensionSnippetProxy, ExtensionTaskProviderProxy,
+    ExtensionThemeProxy,
 };
 use fs::{Fs, RemoveOptions};
 use futures::{
@@ -1077,6 +1079,20 @@ impl ExtensionStore {
                         .remove_language_server(&language, language_server_name);
                 }
             }
+            for command_id in extension.manifest.commands.keys() {
+                self.proxy.remove_command(extension_id, command_id, cx);
+            }
+            for provider_id in extension.manifest.task_providers.keys() {
+                self.proxy
+                    .remove_task_provider(extension_id, provider_id, cx);
+            }
+            for formatter_id in extension.manifest.formatters.keys() {
+                self.proxy.remove_formatter(extension_id, formatter_id);
+            }
+            for provider_id in extension.manifest.code_action_providers.keys() {
+                self.proxy
+                    .remove_code_action_provider(extension_id, provider_id, cx);
+            }
         }
 
         self.wasm_extensions
@@ -1275,6 +1291,32 @@ impl ExtensionStore {
                         this.proxy
                             .register_indexed_docs_provider(extension.clone(), provider_id.clone());
                     }
+
+                    for (command_id, _command) in &manifest.commands {
+                        this.proxy
+                            .register_command(extension.clone(), command_id.clone(), cx);
+                    }
+
+                    for (provider_id, _provider) in &manifest.task_providers {
+                        this.proxy.register_task_provider(
+                            extension.clone(),
+                            provider_id.clone(),
+                            cx,
+                        );
+                    }
+
+                    for (formatter_id, _formatter) in &manifest.formatters {
+                        this.proxy
+                            .register_formatter(extension.clone(), formatter_id.clone());
+                    }
+
+                    for (provider_id, _provider) in &manifest.code_action_providers {
+                        this.proxy.register_code_action_provider(
+                            extension.clone(),
+                            provider_id.clone(),
+                            cx,
+                        );
+                    }
                 }
 
                 this.wasm_extensions.extend(wasm_extensions);
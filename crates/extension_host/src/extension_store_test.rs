@@ -162,6 +162,10 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         context_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        commands: BTreeMap::default(),
+                        task_providers: BTreeMap::default(),
+                        formatters: BTreeMap::default(),
+                        code_action_providers: BTreeMap::default(),
                         snippets: None,
                     }),
                     dev: false,
@@ -190,6 +194,10 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         context_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        commands: BTreeMap::default(),
+                        task_providers: BTreeMap::default(),
+                        formatters: BTreeMap::default(),
+                        code_action_providers: BTreeMap::default(),
                         snippets: None,
                     }),
                     dev: false,
@@ -355,6 +363,10 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                 context_servers: BTreeMap::default(),
                 slash_commands: BTreeMap::default(),
                 indexed_docs_providers: BTreeMap::default(),
+                commands: BTreeMap::default(),
+                task_providers: BTreeMap::default(),
+                formatters: BTreeMap::default(),
+                code_action_providers: BTreeMap::default(),
                 snippets: None,
             }),
             dev: false,
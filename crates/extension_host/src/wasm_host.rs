@@ -4,8 +4,10 @@ use crate::ExtensionManifest;
 use anyhow::{anyhow, bail, Context as _, Result};
 use async_trait::async_trait;
 use extension::{
-    CodeLabel, Command, Completion, ExtensionHostProxy, KeyValueStoreDelegate, ProjectDelegate,
-    SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, Symbol, WorktreeDelegate,
+    CodeAction, CodeActionRequest, CodeLabel, Command, Completion, EditorDelegate,
+    ExtensionHostProxy, FormatRequest, KeyValueStoreDelegate, ProjectDelegate, SlashCommand,
+    SlashCommandArgumentCompletion, SlashCommandOutput, Symbol, TaskTemplate, TextEdit,
+    WorktreeDelegate,
 };
 use fs::{normalize_path, Fs};
 use futures::future::LocalBoxFuture;
@@ -296,6 +298,84 @@ impl extension::Extension for WasmExtension {
         })
         .await
     }
+
+    async fn run_editor_command(
+        &self,
+        command_id: Arc<str>,
+        editor: Arc<dyn EditorDelegate>,
+    ) -> Result<()> {
+        self.call(|extension, store| {
+            async move {
+                let editor_resource = store.data_mut().table().push(editor)?;
+                extension
+                    .call_run_editor_command(store, command_id.as_ref(), editor_resource)
+                    .await?
+                    .map_err(|err| anyhow!("{err:?}"))?;
+
+                anyhow::Ok(())
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    async fn tasks_for_worktree(
+        &self,
+        provider_id: Arc<str>,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<TaskTemplate>> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                let tasks = extension
+                    .call_tasks_for_worktree(store, provider_id.as_ref(), resource)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                anyhow::Ok(tasks)
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    async fn format_buffer(
+        &self,
+        formatter_id: Arc<str>,
+        request: FormatRequest,
+    ) -> Result<Vec<TextEdit>> {
+        self.call(|extension, store| {
+            async move {
+                let edits = extension
+                    .call_format_buffer(store, formatter_id.as_ref(), request)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                anyhow::Ok(edits)
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    async fn code_actions(
+        &self,
+        provider_id: Arc<str>,
+        request: CodeActionRequest,
+    ) -> Result<Vec<CodeAction>> {
+        self.call(|extension, store| {
+            async move {
+                let actions = extension
+                    .call_code_actions(store, provider_id.as_ref(), request)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                anyhow::Ok(actions)
+            }
+            .boxed()
+        })
+        .await
+    }
 }
 
 pub struct WasmState {
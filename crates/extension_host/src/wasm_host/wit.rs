@@ -3,11 +3,12 @@ mod since_v0_0_4;
 mod since_v0_0_6;
 mod since_v0_1_0;
 mod since_v0_2_0;
-use extension::{KeyValueStoreDelegate, WorktreeDelegate};
+mod since_v0_3_0;
+use extension::{EditorDelegate, KeyValueStoreDelegate, WorktreeDelegate};
 use language::LanguageName;
 use lsp::LanguageServerName;
 use release_channel::ReleaseChannel;
-use since_v0_2_0 as latest;
+use since_v0_3_0 as latest;
 
 use super::{wasm_engine, WasmState};
 use anyhow::{anyhow, Context as _, Result};
@@ -58,7 +59,7 @@ pub fn wasm_api_version_range(release_channel: ReleaseChannel) -> RangeInclusive
 
     let max_version = match release_channel {
         ReleaseChannel::Dev | ReleaseChannel::Nightly => latest::MAX_VERSION,
-        ReleaseChannel::Stable | ReleaseChannel::Preview => latest::MAX_VERSION,
+        ReleaseChannel::Stable | ReleaseChannel::Preview => since_v0_2_0::MAX_VERSION,
     };
 
     since_v0_0_1::MIN_VERSION..=max_version
@@ -88,6 +89,7 @@ pub fn authorize_access_to_unreleased_wasm_api_version(
 }
 
 pub enum Extension {
+    V030(since_v0_3_0::Extension),
     V020(since_v0_2_0::Extension),
     V010(since_v0_1_0::Extension),
     V006(since_v0_0_6::Extension),
@@ -102,14 +104,22 @@ impl Extension {
         version: SemanticVersion,
         component: &Component,
     ) -> Result<Self> {
-        // Note: The release channel can be used to stage a new version of the extension API.
-        let _ = release_channel;
-
         if version >= latest::MIN_VERSION {
+            authorize_access_to_unreleased_wasm_api_version(release_channel)?;
+
             let extension =
                 latest::Extension::instantiate_async(store, component, latest::linker())
                     .await
                     .context("failed to instantiate wasm extension")?;
+            Ok(Self::V030(extension))
+        } else if version >= since_v0_2_0::MIN_VERSION {
+            let extension = since_v0_2_0::Extension::instantiate_async(
+                store,
+                component,
+                since_v0_2_0::linker(),
+            )
+            .await
+            .context("failed to instantiate wasm extension")?;
             Ok(Self::V020(extension))
         } else if version >= since_v0_1_0::MIN_VERSION {
             let extension = since_v0_1_0::Extension::instantiate_async(
@@ -152,6 +162,7 @@ impl Extension {
 
     pub async fn call_init_extension(&self, store: &mut Store<WasmState>) -> Result<()> {
         match self {
+            Extension::V030(ext) => ext.call_init_extension(store).await,
             Extension::V020(ext) => ext.call_init_extension(store).await,
             Extension::V010(ext) => ext.call_init_extension(store).await,
             Extension::V006(ext) => ext.call_init_extension(store).await,
@@ -168,10 +179,14 @@ impl Extension {
         resource: Resource<Arc<dyn WorktreeDelegate>>,
     ) -> Result<Result<Command, String>> {
         match self {
-            Extension::V020(ext) => {
+            Extension::V030(ext) => {
                 ext.call_language_server_command(store, &language_server_id.0, resource)
                     .await
             }
+            Extension::V020(ext) => Ok(ext
+                .call_language_server_command(store, &language_server_id.0, resource)
+                .await?
+                .map(|command| command.into())),
             Extension::V010(ext) => Ok(ext
                 .call_language_server_command(store, &language_server_id.0, resource)
                 .await?
@@ -214,6 +229,14 @@ impl Extension {
         resource: Resource<Arc<dyn WorktreeDelegate>>,
     ) -> Result<Result<Option<String>, String>> {
         match self {
+            Extension::V030(ext) => {
+                ext.call_language_server_initialization_options(
+                    store,
+                    &language_server_id.0,
+                    resource,
+                )
+                .await
+            }
             Extension::V020(ext) => {
                 ext.call_language_server_initialization_options(
                     store,
@@ -271,6 +294,14 @@ impl Extension {
         resource: Resource<Arc<dyn WorktreeDelegate>>,
     ) -> Result<Result<Option<String>, String>> {
         match self {
+            Extension::V030(ext) => {
+                ext.call_language_server_workspace_configuration(
+                    store,
+                    &language_server_id.0,
+                    resource,
+                )
+                .await
+            }
             Extension::V020(ext) => {
                 ext.call_language_server_workspace_configuration(
                     store,
@@ -306,10 +337,19 @@ impl Extension {
         completions: Vec<latest::Completion>,
     ) -> Result<Result<Vec<Option<CodeLabel>>, String>> {
         match self {
-            Extension::V020(ext) => {
+            Extension::V030(ext) => {
                 ext.call_labels_for_completions(store, &language_server_id.0, &completions)
                     .await
             }
+            Extension::V020(ext) => Ok(ext
+                .call_labels_for_completions(store, &language_server_id.0, &completions)
+                .await?
+                .map(|labels| {
+                    labels
+                        .into_iter()
+                        .map(|label| label.map(Into::into))
+                        .collect()
+                })),
             Extension::V010(ext) => Ok(ext
                 .call_labels_for_completions(
                     store,
@@ -347,10 +387,19 @@ impl Extension {
         symbols: Vec<latest::Symbol>,
     ) -> Result<Result<Vec<Option<CodeLabel>>, String>> {
         match self {
-            Extension::V020(ext) => {
+            Extension::V030(ext) => {
                 ext.call_labels_for_symbols(store, &language_server_id.0, &symbols)
                     .await
             }
+            Extension::V020(ext) => Ok(ext
+                .call_labels_for_symbols(store, &language_server_id.0, &symbols)
+                .await?
+                .map(|labels| {
+                    labels
+                        .into_iter()
+                        .map(|label| label.map(Into::into))
+                        .collect()
+                })),
             Extension::V010(ext) => Ok(ext
                 .call_labels_for_symbols(
                     store,
@@ -388,6 +437,10 @@ impl Extension {
         arguments: &[String],
     ) -> Result<Result<Vec<SlashCommandArgumentCompletion>, String>> {
         match self {
+            Extension::V030(ext) => {
+                ext.call_complete_slash_command_argument(store, command, arguments)
+                    .await
+            }
             Extension::V020(ext) => {
                 ext.call_complete_slash_command_argument(store, command, arguments)
                     .await
@@ -408,6 +461,10 @@ impl Extension {
         resource: Option<Resource<Arc<dyn WorktreeDelegate>>>,
     ) -> Result<Result<SlashCommandOutput, String>> {
         match self {
+            Extension::V030(ext) => {
+                ext.call_run_slash_command(store, command, arguments, resource)
+                    .await
+            }
             Extension::V020(ext) => {
                 ext.call_run_slash_command(store, command, arguments, resource)
                     .await
@@ -429,10 +486,14 @@ impl Extension {
         project: Resource<ExtensionProject>,
     ) -> Result<Result<Command, String>> {
         match self {
-            Extension::V020(ext) => {
+            Extension::V030(ext) => {
                 ext.call_context_server_command(store, &context_server_id, project)
                     .await
             }
+            Extension::V020(ext) => Ok(ext
+                .call_context_server_command(store, &context_server_id, project)
+                .await?
+                .map(|command| command.into())),
             Extension::V001(_) | Extension::V004(_) | Extension::V006(_) | Extension::V010(_) => {
                 Err(anyhow!(
                     "`context_server_command` not available prior to v0.2.0"
@@ -447,6 +508,7 @@ impl Extension {
         provider: &str,
     ) -> Result<Result<Vec<String>, String>> {
         match self {
+            Extension::V030(ext) => ext.call_suggest_docs_packages(store, provider).await,
             Extension::V020(ext) => ext.call_suggest_docs_packages(store, provider).await,
             Extension::V010(ext) => ext.call_suggest_docs_packages(store, provider).await,
             Extension::V001(_) | Extension::V004(_) | Extension::V006(_) => Err(anyhow!(
@@ -463,6 +525,10 @@ impl Extension {
         kv_store: Resource<Arc<dyn KeyValueStoreDelegate>>,
     ) -> Result<Result<(), String>> {
         match self {
+            Extension::V030(ext) => {
+                ext.call_index_docs(store, provider, package_name, kv_store)
+                    .await
+            }
             Extension::V020(ext) => {
                 ext.call_index_docs(store, provider, package_name, kv_store)
                     .await
@@ -476,6 +542,83 @@ impl Extension {
             }
         }
     }
+
+    pub async fn call_run_editor_command(
+        &self,
+        store: &mut Store<WasmState>,
+        command_id: &str,
+        editor: Resource<Arc<dyn EditorDelegate>>,
+    ) -> Result<Result<(), String>> {
+        match self {
+            Extension::V030(ext) => ext.call_run_editor_command(store, command_id, editor).await,
+            Extension::V001(_)
+            | Extension::V004(_)
+            | Extension::V006(_)
+            | Extension::V010(_)
+            | Extension::V020(_) => Err(anyhow!(
+                "`run_editor_command` not available prior to v0.3.0"
+            )),
+        }
+    }
+
+    pub async fn call_tasks_for_worktree(
+        &self,
+        store: &mut Store<WasmState>,
+        provider_id: &str,
+        worktree: Resource<Arc<dyn WorktreeDelegate>>,
+    ) -> Result<Result<Vec<extension::TaskTemplate>, String>> {
+        match self {
+            Extension::V030(ext) => Ok(ext
+                .call_tasks_for_worktree(store, provider_id, worktree)
+                .await?
+                .map(|tasks| tasks.into_iter().map(Into::into).collect())),
+            Extension::V001(_)
+            | Extension::V004(_)
+            | Extension::V006(_)
+            | Extension::V010(_)
+            | Extension::V020(_) => Err(anyhow!(
+                "`tasks_for_worktree` not available prior to v0.3.0"
+            )),
+        }
+    }
+
+    pub async fn call_format_buffer(
+        &self,
+        store: &mut Store<WasmState>,
+        formatter_id: &str,
+        request: extension::FormatRequest,
+    ) -> Result<Result<Vec<extension::TextEdit>, String>> {
+        match self {
+            Extension::V030(ext) => Ok(ext
+                .call_format_buffer(store, formatter_id, &request.into())
+                .await?
+                .map(|edits| edits.into_iter().map(Into::into).collect())),
+            Extension::V001(_)
+            | Extension::V004(_)
+            | Extension::V006(_)
+            | Extension::V010(_)
+            | Extension::V020(_) => Err(anyhow!("`format_buffer` not available prior to v0.3.0")),
+        }
+    }
+
+    pub async fn call_code_actions(
+        &self,
+        store: &mut Store<WasmState>,
+        provider_id: &str,
+        request: extension::CodeActionRequest,
+    ) -> Result<Result<Vec<extension::CodeAction>, String>> {
+        match self {
+            Extension::V030(ext) => Ok(ext
+                .call_code_actions(store, provider_id, &request.into())
+                .await?
+                .map(|actions| actions.into_iter().map(Into::into).collect())),
+            Extension::V001(_)
+            | Extension::V004(_)
+            | Extension::V006(_)
+            | Extension::V010(_)
+            | Extension::V020(_) => Err(anyhow!("`code_actions` not available prior to v0.3.0")),
+        }
+    }
 }
 
 trait ToWasmtimeResult<T> {
@@ -0,0 +1,355 @@
+use std::{cell::RefCell, ops::Range, rc::Rc, sync::Arc};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use editor::{CodeActionProvider, Editor, EditorMode, ExcerptId};
+use extension::{
+    CodeActionRequest, Extension, ExtensionCodeActionProviderProxy, ExtensionHostProxy,
+};
+use gpui::{App, Entity, Global, Task, Window};
+use language::{
+    anchor_offset_edits, Anchor, Buffer, BufferId, LanguageName, LanguageServerId, ToOffset as _,
+};
+use project::{CodeAction, ProjectTransaction};
+use util::ResultExt as _;
+
+const EXTENSION_CODE_ACTION_PROVIDER_ID: &str = "extensions";
+
+pub(crate) fn init(cx: &mut App) {
+    cx.set_global(ExtensionCodeActionProviders::default());
+
+    let proxy = ExtensionHostProxy::default_global(cx);
+    proxy.register_code_action_provider_proxy(ExtensionCodeActionProvidersProxy);
+
+    cx.observe_new(|editor: &mut Editor, window, cx| {
+        let Some(window) = window else {
+            return;
+        };
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+
+        editor.add_code_action_provider(
+            Rc::new(ExtensionCodeActionProvider::default()),
+            window,
+            cx,
+        );
+    })
+    .detach();
+}
+
+#[derive(Default)]
+struct ExtensionCodeActionProviders {
+    providers: Vec<(Arc<dyn Extension>, Arc<str>)>,
+}
+
+impl Global for ExtensionCodeActionProviders {}
+
+impl ExtensionCodeActionProviders {
+    fn providers_for_language(
+        &self,
+        language_name: Option<&LanguageName>,
+    ) -> Vec<(Arc<dyn Extension>, Arc<str>)> {
+        self.providers
+            .iter()
+            .filter(|(extension, provider_id)| {
+                let Some(provider) = extension.manifest().code_action_providers.get(provider_id)
+                else {
+                    return false;
+                };
+                provider.languages.is_empty()
+                    || language_name.map_or(false, |name| provider.languages.contains(name))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+struct ExtensionCodeActionProvidersProxy;
+
+impl ExtensionCodeActionProviderProxy for ExtensionCodeActionProvidersProxy {
+    fn register_code_action_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        cx: &mut App,
+    ) {
+        let providers = &mut cx.global_mut::<ExtensionCodeActionProviders>().providers;
+        providers.retain(|(existing, existing_provider_id)| {
+            existing.manifest().id != extension.manifest().id
+                || existing_provider_id != &provider_id
+        });
+        providers.push((extension, provider_id));
+    }
+
+    fn remove_code_action_provider(
+        &self,
+        extension_id: &Arc<str>,
+        provider_id: &Arc<str>,
+        cx: &mut App,
+    ) {
+        cx.global_mut::<ExtensionCodeActionProviders>()
+            .providers
+            .retain(|(extension, existing_provider_id)| {
+                &extension.manifest().id != extension_id || existing_provider_id != provider_id
+            });
+    }
+}
+
+/// Offers the code actions of the extensions' code action providers in an editor.
+///
+/// The edits of the code actions most recently listed for each buffer are kept
+/// here, and the code actions refer to them by ID through their `data`.
+#[derive(Default)]
+struct ExtensionCodeActionProvider {
+    state: Rc<RefCell<ExtensionCodeActionState>>,
+}
+
+#[derive(Default)]
+struct ExtensionCodeActionState {
+    next_action_id: u64,
+    edits: HashMap<BufferId, HashMap<u64, Vec<(Range<Anchor>, String)>>>,
+}
+
+impl CodeActionProvider for ExtensionCodeActionProvider {
+    fn id(&self) -> Arc<str> {
+        EXTENSION_CODE_ACTION_PROVIDER_ID.into()
+    }
+
+    fn code_actions(
+        &self,
+        buffer: &Entity<Buffer>,
+        range: Range<Anchor>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let buffer = buffer.read(cx);
+        let buffer_id = buffer.remote_id();
+        let language_name = buffer.language().map(|language| language.name());
+        let providers = cx
+            .global::<ExtensionCodeActionProviders>()
+            .providers_for_language(language_name.as_ref());
+        if providers.is_empty() {
+            return Task::ready(Ok(Vec::new()));
+        }
+
+        let snapshot = buffer.snapshot();
+        let request = CodeActionRequest {
+            text: snapshot.text(),
+            path: buffer
+                .file()
+                .map(|file| file.path().to_string_lossy().into_owned()),
+            language_name: language_name.map(|name| name.to_string()),
+            range: range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot),
+        };
+        let state = self.state.clone();
+
+        cx.spawn(|_| async move {
+            let mut code_actions = Vec::new();
+            let mut edits = HashMap::default();
+            for (extension, provider_id) in providers {
+                let Some(provider_actions) = extension
+                    .code_actions(provider_id.clone(), request.clone())
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "failed to get code actions from provider {:?} of extension {:?}",
+                            provider_id,
+                            extension.manifest().id
+                        )
+                    })
+                    .log_err()
+                else {
+                    continue;
+                };
+
+                for action in provider_actions {
+                    let action_edits = action
+                        .edits
+                        .into_iter()
+                        .map(|edit| (edit.range, edit.new_text))
+                        .collect();
+                    let Some(action_edits) = anchor_offset_edits(&snapshot, action_edits)
+                        .with_context(|| format!("invalid code action {:?}", action.title))
+                        .log_err()
+                    else {
+                        continue;
+                    };
+
+                    let action_id = {
+                        let mut state = state.borrow_mut();
+                        state.next_action_id += 1;
+                        state.next_action_id
+                    };
+                    edits.insert(action_id, action_edits);
+                    code_actions.push(CodeAction {
+                        server_id: LanguageServerId(0),
+                        range: range.clone(),
+                        lsp_action: lsp::CodeAction {
+                            title: action.title,
+                            data: Some(action_id.into()),
+                            ..Default::default()
+                        },
+                    });
+                }
+            }
+
+            state.borrow_mut().edits.insert(buffer_id, edits);
+            Ok(code_actions)
+        })
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Entity<Buffer>,
+        action: CodeAction,
+        _: ExcerptId,
+        push_to_history: bool,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<ProjectTransaction>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let edits = action
+            .lsp_action
+            .data
+            .as_ref()
+            .and_then(|data| data.as_u64())
+            .and_then(|action_id| {
+                self.state
+                    .borrow()
+                    .edits
+                    .get(&buffer_id)?
+                    .get(&action_id)
+                    .cloned()
+            });
+        let Some(edits) = edits else {
+            return Task::ready(Err(anyhow!(
+                "code action {:?} is no longer available",
+                action.lsp_action.title
+            )));
+        };
+
+        let transaction = buffer.update(cx, |buffer, cx| {
+            buffer.finalize_last_transaction();
+            buffer.start_transaction();
+            buffer.edit(edits, None, cx);
+
+            if buffer.end_transaction(cx).is_some() {
+                let transaction = buffer.finalize_last_transaction().unwrap().clone();
+                if !push_to_history {
+                    buffer.forget_transaction(transaction.id);
+                }
+                Some(transaction)
+            } else {
+                None
+            }
+        });
+
+        let mut project_transaction = ProjectTransaction::default();
+        if let Some(transaction) = transaction {
+            project_transaction.0.insert(buffer, transaction);
+        }
+        Task::ready(Ok(project_transaction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use extension::{CodeAction as ExtensionCodeAction, FakeExtension, TextEdit};
+    use gpui::{AppContext as _, TestAppContext};
+
+    #[gpui::test]
+    async fn test_extension_code_actions(cx: &mut TestAppContext) {
+        cx.update(|cx| cx.set_global(ExtensionCodeActionProviders::default()));
+
+        let extension = Arc::new(FakeExtension::new("my-extension", |manifest| {
+            manifest
+                .code_action_providers
+                .insert("upcase".into(), Default::default());
+        }));
+        extension.on_code_actions(|provider_id, request| {
+            assert_eq!(provider_id.as_ref(), "upcase");
+            Ok(vec![ExtensionCodeAction {
+                title: "Upcase".into(),
+                edits: vec![TextEdit {
+                    new_text: request.text[request.range.clone()].to_uppercase(),
+                    range: request.range,
+                }],
+            }])
+        });
+        cx.update(|cx| {
+            ExtensionCodeActionProvidersProxy.register_code_action_provider(
+                extension.clone(),
+                "upcase".into(),
+                cx,
+            )
+        });
+
+        let buffer_a = cx.new(|cx| Buffer::local("one two", cx));
+        let buffer_b = cx.new(|cx| Buffer::local("three four", cx));
+        let provider = ExtensionCodeActionProvider::default();
+        let cx = cx.add_empty_window();
+
+        let code_actions = |buffer: &Entity<Buffer>,
+                            range: Range<usize>,
+                            cx: &mut gpui::VisualTestContext| {
+            cx.update(|window, cx| {
+                let snapshot = buffer.read(cx).snapshot();
+                let range = snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end);
+                provider.code_actions(buffer, range, window, cx)
+            })
+        };
+        let apply_code_action =
+            |buffer: &Entity<Buffer>, action: CodeAction, cx: &mut gpui::VisualTestContext| {
+                cx.update(|window, cx| {
+                    provider.apply_code_action(
+                        buffer.clone(),
+                        action,
+                        ExcerptId::min(),
+                        true,
+                        window,
+                        cx,
+                    )
+                })
+            };
+
+        let actions_a = code_actions(&buffer_a, 0..3, cx).await.unwrap();
+        let actions_b = code_actions(&buffer_b, 6..10, cx).await.unwrap();
+        assert_eq!(actions_a.len(), 1);
+        assert_eq!(actions_a[0].lsp_action.title, "Upcase");
+        assert_eq!(actions_b.len(), 1);
+
+        // Listing the code actions of one buffer keeps the ones of other buffers,
+        // and the edits still apply after the buffer changes.
+        buffer_a.update(cx, |buffer, cx| buffer.edit([(0..0, "zero ")], None, cx));
+        apply_code_action(&buffer_a, actions_a[0].clone(), cx)
+            .await
+            .unwrap();
+        apply_code_action(&buffer_b, actions_b[0].clone(), cx)
+            .await
+            .unwrap();
+        buffer_a.read_with(cx, |buffer, _| assert_eq!(buffer.text(), "zero ONE two"));
+        buffer_b.read_with(cx, |buffer, _| assert_eq!(buffer.text(), "three FOUR"));
+
+        // Code actions with invalid edits are left out, and listing the code
+        // actions again replaces the previous ones of the same buffer.
+        extension.on_code_actions(|_, _| {
+            Ok(vec![ExtensionCodeAction {
+                title: "Broken".into(),
+                edits: vec![TextEdit {
+                    range: 0..100,
+                    new_text: String::new(),
+                }],
+            }])
+        });
+        let actions = code_actions(&buffer_a, 0..3, cx).await.unwrap();
+        assert!(actions.is_empty());
+        assert!(apply_code_action(&buffer_a, actions_a[0].clone(), cx)
+            .await
+            .is_err());
+        apply_code_action(&buffer_b, actions_b[0].clone(), cx)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,443 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use command_palette_hooks::CommandPaletteCommands;
+use editor::Editor;
+use extension::{EditorDelegate, Extension, ExtensionCommandProxy, ExtensionHostProxy, TextEdit};
+use gpui::{impl_actions, App, Context, Global, Window};
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use workspace::{notifications::NotifyTaskExt as _, Workspace};
+
+/// Runs a command provided by an extension in the active editor.
+///
+/// The command is identified by the extension's ID and the command's ID,
+/// separated by a colon (e.g. `my-extension:sort-lines`).
+#[derive(PartialEq, Clone, Deserialize, JsonSchema)]
+pub struct RunCommand {
+    pub command: String,
+}
+
+impl_actions!(extensions, [RunCommand]);
+
+pub(crate) fn init(cx: &mut App) {
+    cx.set_global(ExtensionCommandRegistry::default());
+
+    let proxy = ExtensionHostProxy::default_global(cx);
+    proxy.register_command_proxy(ExtensionCommandRegistryProxy);
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(run_command);
+    })
+    .detach();
+}
+
+#[derive(Default)]
+struct ExtensionCommandRegistry {
+    commands: HashMap<String, Arc<dyn Extension>>,
+}
+
+impl Global for ExtensionCommandRegistry {}
+
+fn qualified_command_id(extension_id: &str, command_id: &str) -> String {
+    format!("{extension_id}:{command_id}")
+}
+
+struct ExtensionCommandRegistryProxy;
+
+impl ExtensionCommandProxy for ExtensionCommandRegistryProxy {
+    fn register_command(&self, extension: Arc<dyn Extension>, command_id: Arc<str>, cx: &mut App) {
+        let manifest = extension.manifest();
+        let Some(command) = manifest.commands.get(&command_id) else {
+            return;
+        };
+
+        let id = qualified_command_id(&manifest.id, &command_id);
+        CommandPaletteCommands::update_global(cx, |commands, _| {
+            commands.add(
+                id.clone(),
+                format!("{}: {}", manifest.name, command.title),
+                Box::new(RunCommand {
+                    command: id.clone(),
+                }),
+            );
+        });
+        cx.global_mut::<ExtensionCommandRegistry>()
+            .commands
+            .insert(id, extension);
+    }
+
+    fn remove_command(&self, extension_id: &Arc<str>, command_id: &Arc<str>, cx: &mut App) {
+        let id = qualified_command_id(extension_id, command_id);
+        CommandPaletteCommands::update_global(cx, |commands, _| commands.remove(&id));
+        cx.global_mut::<ExtensionCommandRegistry>()
+            .commands
+            .remove(&id);
+    }
+}
+
+fn run_command(
+    workspace: &mut Workspace,
+    action: &RunCommand,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(extension) = cx
+        .global::<ExtensionCommandRegistry>()
+        .commands
+        .get(&action.command)
+        .cloned()
+    else {
+        workspace.show_error(
+            &anyhow!("unknown extension command {:?}", action.command),
+            cx,
+        );
+        return;
+    };
+    let Some((_, command_id)) = action.command.split_once(':') else {
+        return;
+    };
+    let command_id: Arc<str> = command_id.into();
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        workspace.show_error(
+            &anyhow!("extension commands can only be run in an editor"),
+            cx,
+        );
+        return;
+    };
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        workspace.show_error(
+            &anyhow!("extension commands can't be run in multibuffers"),
+            cx,
+        );
+        return;
+    };
+
+    let selections = editor.update(cx, |editor, cx| editor.selections.ranges::<usize>(cx));
+    let delegate = {
+        let buffer = buffer.read(cx);
+        Arc::new(ExtensionEditor {
+            path: buffer
+                .file()
+                .map(|file| file.path().to_string_lossy().into_owned()),
+            language_name: buffer
+                .language()
+                .map(|language| language.name().to_string()),
+            state: Mutex::new(ExtensionEditorState {
+                text: buffer.text(),
+                selections,
+                edits: Vec::new(),
+                new_selections: None,
+            }),
+        })
+    };
+    let version = buffer.read(cx).version();
+
+    cx.spawn_in(window, |_, mut cx| async move {
+        extension
+            .run_editor_command(command_id.clone(), delegate.clone())
+            .await
+            .with_context(|| format!("failed to run extension command {command_id:?}"))?;
+
+        let state = std::mem::take(&mut *delegate.state.lock());
+        if state.edits.is_empty() && state.new_selections.is_none() {
+            return Ok(());
+        }
+
+        editor.update_in(&mut cx, |editor, window, cx| {
+            if buffer.read(cx).version().changed_since(&version) {
+                return Err(anyhow!(
+                    "the buffer changed while running extension command {command_id:?}"
+                ));
+            }
+
+            editor.transact(window, cx, |editor, window, cx| {
+                for edits in state.edits {
+                    editor.edit(
+                        edits.into_iter().map(|edit| (edit.range, edit.new_text)),
+                        cx,
+                    );
+                }
+                if let Some(selections) = state.new_selections {
+                    editor.change_selections(None, window, cx, |s| s.select_ranges(selections));
+                }
+            });
+            Ok(())
+        })?
+    })
+    .detach_and_notify_err(window, cx);
+}
+
+/// A snapshot of an editor that extension commands read and edit.
+///
+/// The edits and selections are recorded, and applied to the editor after the
+/// command returns.
+struct ExtensionEditor {
+    path: Option<String>,
+    language_name: Option<String>,
+    state: Mutex<ExtensionEditorState>,
+}
+
+#[derive(Default)]
+struct ExtensionEditorState {
+    text: String,
+    selections: Vec<Range<usize>>,
+    /// The batches of edits made by the command, each relative to the text
+    /// after the previous batch.
+    edits: Vec<Vec<TextEdit>>,
+    new_selections: Option<Vec<Range<usize>>>,
+}
+
+impl ExtensionEditorState {
+    fn validate_range(&self, range: &Range<usize>) -> Result<()> {
+        if range.start > range.end
+            || range.end > self.text.len()
+            || !self.text.is_char_boundary(range.start)
+            || !self.text.is_char_boundary(range.end)
+        {
+            return Err(anyhow!("invalid range {range:?}"));
+        }
+        Ok(())
+    }
+}
+
+impl EditorDelegate for ExtensionEditor {
+    fn text(&self) -> String {
+        self.state.lock().text.clone()
+    }
+
+    fn path(&self) -> Option<String> {
+        self.path.clone()
+    }
+
+    fn language_name(&self) -> Option<String> {
+        self.language_name.clone()
+    }
+
+    fn selections(&self) -> Vec<Range<usize>> {
+        let state = self.state.lock();
+        state
+            .new_selections
+            .clone()
+            .unwrap_or_else(|| state.selections.clone())
+    }
+
+    fn edit(&self, mut edits: Vec<TextEdit>) -> Result<()> {
+        let mut state = self.state.lock();
+        edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+        for (ix, edit) in edits.iter().enumerate() {
+            state.validate_range(&edit.range)?;
+            if ix > 0 && edits[ix - 1].range.end > edit.range.start {
+                return Err(anyhow!(
+                    "overlapping edits at {:?} and {:?}",
+                    edits[ix - 1].range,
+                    edit.range
+                ));
+            }
+        }
+
+        for edit in edits.iter().rev() {
+            state.text.replace_range(edit.range.clone(), &edit.new_text);
+        }
+        let ExtensionEditorState {
+            selections,
+            new_selections,
+            ..
+        } = &mut *state;
+        for selection in selections
+            .iter_mut()
+            .chain(new_selections.iter_mut().flatten())
+        {
+            selection.start = offset_after_edits(selection.start, &edits);
+            selection.end = offset_after_edits(selection.end, &edits);
+        }
+        state.edits.push(edits);
+        Ok(())
+    }
+
+    fn set_selections(&self, selections: Vec<Range<usize>>) -> Result<()> {
+        if selections.is_empty() {
+            return Err(anyhow!("an editor must have at least one selection"));
+        }
+        let mut state = self.state.lock();
+        for selection in &selections {
+            state.validate_range(selection)?;
+        }
+        state.new_selections = Some(selections);
+        Ok(())
+    }
+}
+
+/// Returns where the given offset ends up after applying the given sorted,
+/// non-overlapping edits. Offsets inside an edit move to the end of its new text.
+fn offset_after_edits(offset: usize, edits: &[TextEdit]) -> usize {
+    let mut delta = 0isize;
+    for edit in edits {
+        if edit.range.start >= offset && edit.range.end > offset {
+            break;
+        }
+        if edit.range.end > offset {
+            return edit.range.start.saturating_add_signed(delta) + edit.new_text.len();
+        }
+        delta += edit.new_text.len() as isize - edit.range.len() as isize;
+    }
+    offset.saturating_add_signed(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use extension::FakeExtension;
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use workspace::AppState;
+
+    fn edit(range: Range<usize>, new_text: &str) -> TextEdit {
+        TextEdit {
+            range,
+            new_text: new_text.into(),
+        }
+    }
+
+    fn extension_editor(text: &str, selections: Vec<Range<usize>>) -> ExtensionEditor {
+        ExtensionEditor {
+            path: None,
+            language_name: None,
+            state: Mutex::new(ExtensionEditorState {
+                text: text.into(),
+                selections,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_offset_after_edits() {
+        let edits = [edit(2..4, "abc"), edit(6..6, "x")];
+        assert_eq!(offset_after_edits(0, &edits), 0);
+        assert_eq!(offset_after_edits(2, &edits), 2);
+        assert_eq!(offset_after_edits(3, &edits), 5);
+        assert_eq!(offset_after_edits(4, &edits), 5);
+        assert_eq!(offset_after_edits(5, &edits), 6);
+        assert_eq!(offset_after_edits(6, &edits), 8);
+        assert_eq!(offset_after_edits(8, &edits), 10);
+
+        let edits = [edit(0..5, "")];
+        assert_eq!(offset_after_edits(3, &edits), 0);
+        assert_eq!(offset_after_edits(7, &edits), 2);
+    }
+
+    #[test]
+    fn test_extension_editor_edits() {
+        let editor = extension_editor("one two three", vec![4..7]);
+
+        // Edits before and after the selection move it by the edits before it.
+        editor
+            .edit(vec![edit(8..13, "3"), edit(0..3, "1")])
+            .unwrap();
+        assert_eq!(editor.text(), "1 two 3");
+        assert_eq!(editor.selections(), vec![2..5]);
+
+        // Each batch of edits is relative to the text after the previous one.
+        editor.set_selections(vec![6..7]).unwrap();
+        editor.edit(vec![edit(2..5, "2")]).unwrap();
+        assert_eq!(editor.text(), "1 2 3");
+        assert_eq!(editor.selections(), vec![4..5]);
+
+        let state = editor.state.lock();
+        assert_eq!(
+            state.edits,
+            vec![
+                vec![edit(0..3, "1"), edit(8..13, "3")],
+                vec![edit(2..5, "2")]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extension_editor_invalid_edits() {
+        let editor = extension_editor("héllo world", vec![0..0]);
+
+        assert!(editor.edit(vec![edit(0..5, "a"), edit(4..8, "b")]).is_err());
+        assert!(editor.edit(vec![edit(5..3, "a")]).is_err());
+        assert!(editor.edit(vec![edit(0..100, "a")]).is_err());
+        assert!(editor.edit(vec![edit(0..2, "a")]).is_err());
+        assert!(editor.set_selections(vec![0..100]).is_err());
+        assert!(editor.set_selections(Vec::new()).is_err());
+
+        // Rejected edits leave the text alone.
+        assert_eq!(editor.text(), "héllo world");
+        assert!(editor.state.lock().edits.is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_run_command(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            init(cx);
+        });
+
+        let extension = Arc::new(FakeExtension::new("my-extension", |manifest| {
+            manifest.commands.insert(
+                "shout".into(),
+                extension::CommandManifestEntry {
+                    title: "Shout".into(),
+                },
+            );
+        }));
+        extension.on_run_editor_command(|command_id, editor| {
+            assert_eq!(command_id.as_ref(), "shout");
+            let text = editor.text();
+            let edits = editor
+                .selections()
+                .into_iter()
+                .map(|range| TextEdit {
+                    new_text: text[range.clone()].to_uppercase(),
+                    range,
+                })
+                .collect();
+            editor.edit(edits)?;
+            editor.set_selections(vec![0..0])
+        });
+        cx.update(|cx| {
+            ExtensionCommandRegistryProxy.register_command(extension.clone(), "shout".into(), cx)
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "a.txt": "one two three" }))
+            .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let editor = workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_path((worktree_id, "a.txt"), None, true, window, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        editor.update_in(cx, |editor, window, cx| {
+            editor.change_selections(None, window, cx, |s| s.select_ranges([4..7]))
+        });
+
+        cx.dispatch_action(RunCommand {
+            command: "my-extension:shout".into(),
+        });
+        cx.run_until_parked();
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "one TWO three");
+            assert_eq!(editor.selections.ranges::<usize>(cx), vec![0..0]);
+        });
+    }
+}
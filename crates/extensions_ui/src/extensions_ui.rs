@@ -1,4 +1,6 @@
 mod components;
+mod extension_code_actions;
+mod extension_commands;
 mod extension_suggest;
 mod extension_version_selector;
 
@@ -36,6 +38,9 @@ use crate::extension_version_selector::{
 actions!(zed, [InstallDevExtension]);
 
 pub fn init(cx: &mut App) {
+    extension_code_actions::init(cx);
+    extension_commands::init(cx);
+
     cx.observe_new(move |workspace: &mut Workspace, window, cx| {
         let Some(window) = window else {
             return;
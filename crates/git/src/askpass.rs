@@ -0,0 +1,216 @@
+use futures::future::BoxFuture;
+use futures::FutureExt as _;
+
+/// Receives progress output and answers credential prompts while a git
+/// command that talks to a remote (fetch, pull, push) is running.
+pub trait GitRemoteDelegate: Send + Sync {
+    /// Called with each line of progress output written by git, from the
+    /// thread running the git command.
+    fn progress(&self, line: &str);
+
+    /// Called when git (or ssh) asks for credentials. The returned future
+    /// resolves once the user has answered.
+    ///
+    /// Resolving to `None` cancels the prompt, which makes the command fail.
+    fn ask_pass(&self, prompt: String) -> BoxFuture<'static, Option<String>>;
+}
+
+/// A delegate that ignores progress and declines every credential prompt.
+pub struct NoopRemoteDelegate;
+
+impl GitRemoteDelegate for NoopRemoteDelegate {
+    fn progress(&self, _line: &str) {}
+
+    fn ask_pass(&self, _prompt: String) -> BoxFuture<'static, Option<String>> {
+        futures::future::ready(None).boxed()
+    }
+}
+
+#[cfg(unix)]
+pub use unix::AskPassSession;
+
+#[cfg(not(unix))]
+pub use fallback::AskPassSession;
+
+#[cfg(unix)]
+mod unix {
+    use super::GitRemoteDelegate;
+    use anyhow::{Context as _, Result};
+    use std::{
+        ffi::CString,
+        fs,
+        io::{Read as _, Write as _},
+        os::unix::{ffi::OsStrExt as _, fs::OpenOptionsExt as _, fs::PermissionsExt as _},
+        path::{Path, PathBuf},
+        process::Command,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+
+    /// Answers `GIT_ASKPASS`/`SSH_ASKPASS` prompts for a single git invocation.
+    ///
+    /// Git runs the generated script with the prompt as its argument. The
+    /// script writes the prompt into one FIFO and reads the answer back from
+    /// another, while a background thread forwards each prompt to the delegate.
+    pub struct AskPassSession {
+        dir: tempfile::TempDir,
+        done: Arc<AtomicBool>,
+    }
+
+    impl AskPassSession {
+        pub fn new(delegate: Arc<dyn GitRemoteDelegate>) -> Result<Self> {
+            let dir = tempfile::Builder::new()
+                .prefix("zed-askpass")
+                .tempdir()
+                .context("creating askpass directory")?;
+            let prompt_path = dir.path().join("prompt");
+            let response_path = dir.path().join("response");
+            make_fifo(&prompt_path)?;
+            make_fifo(&response_path)?;
+
+            let script_path = dir.path().join("askpass.sh");
+            let script = format!(
+                "#!/bin/sh\nprintf '%s' \"$*\" > '{}'\ncat '{}'\n",
+                prompt_path.display(),
+                response_path.display()
+            );
+            fs::write(&script_path, script).context("writing askpass script")?;
+            fs::set_permissions(&script_path, fs::Permissions::from_mode(0o700))?;
+
+            let done = Arc::new(AtomicBool::new(false));
+            std::thread::spawn({
+                let done = done.clone();
+                move || {
+                    while !done.load(Ordering::SeqCst) {
+                        let Ok(mut prompt_fifo) = fs::File::open(&prompt_path) else {
+                            break;
+                        };
+                        let mut prompt = String::new();
+                        if prompt_fifo.read_to_string(&mut prompt).is_err()
+                            || done.load(Ordering::SeqCst)
+                        {
+                            break;
+                        }
+                        // This thread only serves this git invocation, so it can wait for the answer.
+                        let response = futures::executor::block_on(
+                            delegate.ask_pass(prompt.trim().to_string()),
+                        )
+                        .unwrap_or_default();
+                        let Ok(mut response_fifo) =
+                            fs::OpenOptions::new().write(true).open(&response_path)
+                        else {
+                            break;
+                        };
+                        writeln!(response_fifo, "{response}").ok();
+                    }
+                }
+            });
+
+            Ok(Self { dir, done })
+        }
+
+        pub fn script_path(&self) -> PathBuf {
+            self.dir.path().join("askpass.sh")
+        }
+
+        pub fn configure(&self, command: &mut Command) {
+            let script_path = self.script_path();
+            command
+                .env("GIT_ASKPASS", &script_path)
+                .env("SSH_ASKPASS", &script_path)
+                .env("SSH_ASKPASS_REQUIRE", "force");
+        }
+    }
+
+    impl Drop for AskPassSession {
+        fn drop(&mut self) {
+            self.done.store(true, Ordering::SeqCst);
+            // Wake up the listener if it is waiting for a prompt. This fails
+            // harmlessly when the listener is not currently blocked on the FIFO.
+            fs::OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(self.dir.path().join("prompt"))
+                .ok();
+        }
+    }
+
+    fn make_fifo(path: &Path) -> Result<()> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        // SAFETY: `c_path` is a valid, nul-terminated path.
+        let result = unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) };
+        if result != 0 {
+            return Err(std::io::Error::last_os_error())
+                .with_context(|| format!("creating fifo at {path:?}"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod fallback {
+    use super::GitRemoteDelegate;
+    use anyhow::Result;
+    use std::{process::Command, sync::Arc};
+
+    /// Credential prompts are not forwarded on this platform; git is run with
+    /// terminal prompts disabled and fails if it needs credentials.
+    pub struct AskPassSession;
+
+    impl AskPassSession {
+        pub fn new(_delegate: Arc<dyn GitRemoteDelegate>) -> Result<Self> {
+            Ok(Self)
+        }
+
+        pub fn configure(&self, _command: &mut Command) {}
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use futures::FutureExt as _;
+    use parking_lot::Mutex;
+    use std::{process::Command, sync::Arc};
+
+    struct RecordingDelegate {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl GitRemoteDelegate for RecordingDelegate {
+        fn progress(&self, _line: &str) {}
+
+        fn ask_pass(&self, prompt: String) -> BoxFuture<'static, Option<String>> {
+            self.prompts.lock().push(prompt);
+            futures::future::ready(Some("hunter2".to_string())).boxed()
+        }
+    }
+
+    #[test]
+    fn test_askpass_script_round_trip() {
+        let delegate = Arc::new(RecordingDelegate {
+            prompts: Mutex::new(Vec::new()),
+        });
+        let session = AskPassSession::new(delegate.clone()).unwrap();
+
+        for _ in 0..2 {
+            let output = Command::new(session.script_path())
+                .arg("Password for 'https://example.com':")
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), "hunter2\n");
+        }
+
+        drop(session);
+        assert_eq!(
+            *delegate.prompts.lock(),
+            vec![
+                "Password for 'https://example.com':".to_string(),
+                "Password for 'https://example.com':".to_string()
+            ]
+        );
+    }
+}
@@ -1,8 +1,76 @@
+use crate::repository::RepoPath;
 use crate::Oid;
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
 use collections::HashMap;
+use gpui::SharedString;
 use std::path::Path;
 
+/// The format passed to `git log`, producing one NUL-separated record per commit.
+pub(crate) const LOG_FORMAT: &str = "--format=%H%x00%an%x00%ae%x00%ct%x00%s";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub sha: Oid,
+    pub author_name: SharedString,
+    pub author_email: SharedString,
+    /// Commit time, normalized to Unix Epoch format.
+    pub commit_timestamp: i64,
+    /// The first line of the commit message.
+    pub subject: SharedString,
+}
+
+/// Restricts which commits are returned by [`crate::repository::GitRepository::log`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    /// Only include commits touching this path, following renames of single files.
+    pub path: Option<RepoPath>,
+    /// Only include commits whose author name or email contains this text, ignoring case.
+    pub author: Option<String>,
+    /// The number of most recent matching commits to skip.
+    pub skip: usize,
+    /// The maximum number of commits to return.
+    pub limit: usize,
+}
+
+/// A file changed by a commit, with its contents before and after the commit.
+///
+/// A missing text means the file did not exist on that side (or was not valid UTF-8).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitFileDiff {
+    pub path: RepoPath,
+    pub old_text: Option<String>,
+    pub new_text: Option<String>,
+}
+
+pub(crate) fn parse_git_log(output: &str) -> Result<Vec<CommitSummary>> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mut fields = line.splitn(5, '\0');
+            let sha = fields
+                .next()
+                .context("missing commit sha")?
+                .parse::<Oid>()?;
+            let author_name = fields.next().context("missing author name")?;
+            let author_email = fields.next().context("missing author email")?;
+            let commit_timestamp = fields
+                .next()
+                .context("missing commit timestamp")?
+                .parse::<i64>()
+                .context("invalid commit timestamp")?;
+            let subject = fields.next().context("missing commit subject")?;
+            Ok(CommitSummary {
+                sha,
+                author_name: author_name.to_string().into(),
+                author_email: author_email.to_string().into(),
+                commit_timestamp,
+                subject: subject.to_string().into(),
+            })
+        })
+        .collect()
+}
+
 pub fn get_messages(working_directory: &Path, shas: &[Oid]) -> Result<HashMap<Oid, String>> {
     if shas.is_empty() {
         return Ok(HashMap::default());
@@ -36,3 +104,39 @@ pub fn get_messages(working_directory: &Path, shas: &[Oid]) -> Result<HashMap<Oi
         )
         .collect::<HashMap<Oid, String>>())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_log() {
+        let output = concat!(
+            "2f5d0b5c0d8d6c4b0c6f6b8f4b1d0e1f2a3b4c5d\x00Jane Doe\x00jane@example.com\x001700000000\x00Fix the thing\n",
+            "8a1e3f7f4c2d5e6b7a8c9d0e1f2a3b4c5d6e7f80\x00John\x00john@example.com\x001690000000\x00Merge branch 'main' into feature\n",
+        );
+        let commits = parse_git_log(output).unwrap();
+        assert_eq!(commits.len(), 2);
+
+        assert_eq!(
+            commits[0].sha.to_string(),
+            "2f5d0b5c0d8d6c4b0c6f6b8f4b1d0e1f2a3b4c5d"
+        );
+        assert_eq!(commits[0].author_name.as_ref(), "Jane Doe");
+        assert_eq!(commits[0].author_email.as_ref(), "jane@example.com");
+        assert_eq!(commits[0].commit_timestamp, 1700000000);
+        assert_eq!(commits[0].subject.as_ref(), "Fix the thing");
+
+        assert_eq!(commits[1].author_name.as_ref(), "John");
+        assert_eq!(
+            commits[1].subject.as_ref(),
+            "Merge branch 'main' into feature"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_git_log() {
+        assert!(parse_git_log("not-a-sha\x00a\x00b\x001\x00c\n").is_err());
+        assert!(parse_git_log("2f5d0b5c0d8d6c4b0c6f6b8f4b1d0e1f2a3b4c5d\x00a\n").is_err());
+    }
+}
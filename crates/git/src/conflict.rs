@@ -0,0 +1,224 @@
+use std::ops::Range;
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// A region of text delimited by the conflict markers that git writes when a merge,
+/// rebase or stash application can't combine two versions of a file.
+///
+/// All ranges are byte offsets into the text that was parsed. Content ranges cover whole
+/// lines, including their trailing newline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// The whole conflict, from the start of the `<<<<<<<` line to the end of the `>>>>>>>` line.
+    pub range: Range<usize>,
+    /// The name following the `<<<<<<<` marker, e.g. `HEAD`.
+    pub ours_name: Option<String>,
+    pub ours: Range<usize>,
+    /// The common ancestor's version, only present in `diff3` and `zdiff3` style conflicts.
+    pub base: Option<Range<usize>>,
+    /// The name following the `>>>>>>>` marker, e.g. the branch being merged.
+    pub theirs_name: Option<String>,
+    pub theirs: Range<usize>,
+}
+
+enum ParseState {
+    Outside,
+    Ours {
+        start: usize,
+        name: Option<String>,
+        content_start: usize,
+    },
+    Base {
+        start: usize,
+        name: Option<String>,
+        ours: Range<usize>,
+        content_start: usize,
+    },
+    Theirs {
+        start: usize,
+        name: Option<String>,
+        ours: Range<usize>,
+        base: Option<Range<usize>>,
+        content_start: usize,
+    },
+}
+
+/// Finds all well-formed conflicts in the given text, in order.
+///
+/// Incomplete conflicts are skipped, and a `<<<<<<<` marker inside a conflict starts a new one.
+pub fn parse_conflicts(text: &str) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let mut state = ParseState::Outside;
+    let mut line_start = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+
+        if let Some(name) = marker_name(content, OURS_MARKER) {
+            state = ParseState::Ours {
+                start: line_start,
+                name,
+                content_start: line_end,
+            };
+        } else {
+            state = match state {
+                ParseState::Ours {
+                    start,
+                    name,
+                    content_start,
+                } if marker_name(content, BASE_MARKER).is_some() => ParseState::Base {
+                    start,
+                    name,
+                    ours: content_start..line_start,
+                    content_start: line_end,
+                },
+                ParseState::Ours {
+                    start,
+                    name,
+                    content_start,
+                } if content == SEPARATOR_MARKER => ParseState::Theirs {
+                    start,
+                    name,
+                    ours: content_start..line_start,
+                    base: None,
+                    content_start: line_end,
+                },
+                ParseState::Base {
+                    start,
+                    name,
+                    ours,
+                    content_start,
+                } if content == SEPARATOR_MARKER => ParseState::Theirs {
+                    start,
+                    name,
+                    ours,
+                    base: Some(content_start..line_start),
+                    content_start: line_end,
+                },
+                ParseState::Theirs {
+                    start,
+                    name,
+                    ours,
+                    base,
+                    content_start,
+                } => match marker_name(content, THEIRS_MARKER) {
+                    Some(theirs_name) => {
+                        conflicts.push(Conflict {
+                            range: start..line_end,
+                            ours_name: name,
+                            ours,
+                            base,
+                            theirs_name,
+                            theirs: content_start..line_start,
+                        });
+                        ParseState::Outside
+                    }
+                    None => ParseState::Theirs {
+                        start,
+                        name,
+                        ours,
+                        base,
+                        content_start,
+                    },
+                },
+                state => state,
+            };
+        }
+
+        line_start = line_end;
+    }
+
+    conflicts
+}
+
+/// Returns the name following a conflict marker if the line starts with that marker,
+/// or `None` if it doesn't.
+fn marker_name(line: &str, marker: &str) -> Option<Option<String>> {
+    let rest = line.strip_prefix(marker)?;
+    if rest.is_empty() {
+        Some(None)
+    } else {
+        let name = rest.strip_prefix(' ')?.trim();
+        Some((!name.is_empty()).then(|| name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unindent::Unindent as _;
+
+    #[test]
+    fn test_parse_conflicts() {
+        let text = "
+            fn main() {
+            <<<<<<< HEAD
+                println!(\"ours\");
+            =======
+                println!(\"theirs\");
+                println!(\"more theirs\");
+            >>>>>>> feature
+            }
+            <<<<<<< HEAD
+            a
+            ||||||| base
+            b
+            =======
+            c
+            >>>>>>> 1234567 (Commit message)
+        "
+        .unindent();
+
+        let conflicts = parse_conflicts(&text);
+        assert_eq!(conflicts.len(), 2);
+
+        let first = &conflicts[0];
+        assert!(text[first.range.clone()].starts_with("<<<<<<< HEAD\n"));
+        assert!(text[first.range.clone()].ends_with(">>>>>>> feature\n"));
+        assert_eq!(first.ours_name.as_deref(), Some("HEAD"));
+        assert_eq!(&text[first.ours.clone()], "    println!(\"ours\");\n");
+        assert_eq!(first.base, None);
+        assert_eq!(first.theirs_name.as_deref(), Some("feature"));
+        assert_eq!(
+            &text[first.theirs.clone()],
+            "    println!(\"theirs\");\n    println!(\"more theirs\");\n"
+        );
+
+        let second = &conflicts[1];
+        assert_eq!(second.range.end, text.len());
+        assert_eq!(&text[second.ours.clone()], "a\n");
+        assert_eq!(&text[second.base.clone().unwrap()], "b\n");
+        assert_eq!(&text[second.theirs.clone()], "c\n");
+        assert_eq!(
+            second.theirs_name.as_deref(),
+            Some("1234567 (Commit message)")
+        );
+    }
+
+    #[test]
+    fn test_parse_incomplete_conflicts() {
+        let text = "
+            <<<<<<< HEAD
+            abandoned
+            <<<<<<<
+            =======
+            >>>>>>>
+            =======
+            <<<<<<<< not a marker
+            >>>>>>> stray
+        "
+        .unindent();
+
+        let conflicts = parse_conflicts(&text);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].ours_name, None);
+        assert_eq!(conflicts[0].theirs_name, None);
+        assert!(conflicts[0].ours.is_empty());
+        assert!(conflicts[0].theirs.is_empty());
+        assert!(text[conflicts[0].range.clone()].starts_with("<<<<<<<\n"));
+    }
+}
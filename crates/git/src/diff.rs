@@ -355,6 +355,140 @@ impl BufferDiff {
     }
 }
 
+/// Returns `base_text` with only some of the line changes that turn it into `target_text`
+/// applied, e.g. to stage a subset of a file's changes into the index.
+///
+/// `include` is called with the rows of `target_text` covered by each changed line. An added
+/// line covers its own row. The removed lines of a hunk are paired with its added lines in
+/// order, so a removed line covers the row of the line that replaces it. Removed lines without
+/// a replacement cover the hunk's last row, or an empty range at the row following the removal
+/// when the hunk only removes lines.
+pub fn apply_line_changes(
+    base_text: &str,
+    target_text: &str,
+    mut include: impl FnMut(Range<u32>) -> bool,
+) -> String {
+    let Some(patch) = BufferDiff::diff(base_text, target_text) else {
+        return base_text.to_string();
+    };
+    let base_line_starts = line_starts(base_text);
+
+    let mut result = String::with_capacity(target_text.len());
+    let mut base_offset = 0;
+    for hunk_index in 0..patch.num_hunks() {
+        let Ok((hunk, line_count)) = patch.hunk(hunk_index) else {
+            continue;
+        };
+        let (old_rows, new_rows) = hunk_row_ranges(&hunk);
+        let hunk_base_start =
+            base_line_starts[(old_rows.start as usize).min(base_line_starts.len() - 1)];
+        let hunk_base_end =
+            base_line_starts[(old_rows.end as usize).min(base_line_starts.len() - 1)];
+        result.push_str(&base_text[base_offset..hunk_base_start]);
+
+        let mut deletions = Vec::new();
+        let mut additions = Vec::new();
+        for line_index in 0..line_count {
+            let Ok(line) = patch.line_in_hunk(hunk_index, line_index) else {
+                continue;
+            };
+            let content = String::from_utf8_lossy(line.content()).into_owned();
+            match line.origin_value() {
+                GitDiffLineType::Deletion => deletions.push(content),
+                GitDiffLineType::Addition => additions.push(content),
+                _ => {}
+            }
+        }
+
+        // Each removed line is followed by the line that replaces it, so that the lines
+        // of a partially applied hunk keep their order.
+        for index in 0..deletions.len().max(additions.len()) {
+            if let Some(deletion) = deletions.get(index) {
+                let rows = if new_rows.is_empty() {
+                    new_rows.clone()
+                } else {
+                    let row = (new_rows.start + index as u32).min(new_rows.end - 1);
+                    row..row + 1
+                };
+                if !include(rows) {
+                    result.push_str(deletion);
+                }
+            }
+            if let Some(addition) = additions.get(index) {
+                let row = new_rows.start + index as u32;
+                if include(row..row + 1) {
+                    result.push_str(addition);
+                }
+            }
+        }
+        base_offset = hunk_base_end.max(base_offset);
+    }
+    result.push_str(&base_text[base_offset..]);
+    result
+}
+
+/// Maps a row of `target_text` to the corresponding row of `base_text`. Rows inside a
+/// changed region are clamped to the base rows of that region.
+pub fn base_row_for_target_row(base_text: &str, target_text: &str, row: u32) -> u32 {
+    let Some(patch) = BufferDiff::diff(base_text, target_text) else {
+        return row;
+    };
+    let mut divergence: i64 = 0;
+    for hunk_index in 0..patch.num_hunks() {
+        let Ok((hunk, _)) = patch.hunk(hunk_index) else {
+            continue;
+        };
+        let (old_rows, new_rows) = hunk_row_ranges(&hunk);
+        if row < new_rows.start {
+            break;
+        }
+        if row < new_rows.end {
+            return (old_rows.start + (row - new_rows.start)).min(old_rows.end);
+        }
+        divergence += old_rows.len() as i64 - new_rows.len() as i64;
+    }
+    (row as i64 + divergence).max(0) as u32
+}
+
+/// Returns the rows of `target_text` covered by each hunk of changes from `base_text`.
+/// A hunk that only removes lines covers an empty range at the row following the removal.
+pub fn changed_rows(base_text: &str, target_text: &str) -> Vec<Range<u32>> {
+    let Some(patch) = BufferDiff::diff(base_text, target_text) else {
+        return Vec::new();
+    };
+    (0..patch.num_hunks())
+        .filter_map(|hunk_index| {
+            let (hunk, _) = patch.hunk(hunk_index).ok()?;
+            Some(hunk_row_ranges(&hunk).1)
+        })
+        .collect()
+}
+
+/// Returns the zero-based old and new row ranges of a hunk produced without context lines.
+/// Git reports the line preceding the change as the start of an empty side.
+fn hunk_row_ranges(hunk: &libgit::DiffHunk<'_>) -> (Range<u32>, Range<u32>) {
+    let row_range = |start: u32, lines: u32| {
+        if lines == 0 {
+            start..start
+        } else {
+            start - 1..start - 1 + lines
+        }
+    };
+    (
+        row_range(hunk.old_start(), hunk.old_lines()),
+        row_range(hunk.new_start(), hunk.new_lines()),
+    )
+}
+
+/// The byte offset at which each line of the text starts, followed by the text's length.
+fn line_starts(text: &str) -> Vec<usize> {
+    iter::once(0)
+        .chain(text.match_indices('\n').map(|(ix, _)| ix + 1))
+        .filter(|offset| *offset < text.len())
+        .chain(iter::once(text.len()))
+        .collect()
+}
+
 /// Range (crossing new lines), old, new
 #[cfg(any(test, feature = "test-support"))]
 #[track_caller]
@@ -614,4 +748,173 @@ mod tests {
         let range = diff_6.compare(&diff_5, &buffer).unwrap();
         assert_eq!(range.to_point(&buffer), Point::new(7, 0)..Point::new(8, 0));
     }
+
+    #[test]
+    fn test_apply_line_changes() {
+        let base_text = "
+            one
+            two
+            three
+            four
+            five
+        "
+        .unindent();
+        let target_text = "
+            zero
+            one
+            TWO
+            three
+            five
+            six
+        "
+        .unindent();
+
+        // Including every change produces the target, and including none keeps the base.
+        assert_eq!(
+            apply_line_changes(&base_text, &target_text, |_| true),
+            target_text
+        );
+        assert_eq!(
+            apply_line_changes(&base_text, &target_text, |_| false),
+            base_text
+        );
+
+        // Only the modification of `two`.
+        let touches = |selected: Range<u32>| {
+            move |rows: Range<u32>| {
+                if rows.is_empty() {
+                    selected.contains(&rows.start)
+                } else {
+                    rows.start < selected.end && selected.start < rows.end
+                }
+            }
+        };
+        assert_eq!(
+            apply_line_changes(&base_text, &target_text, touches(2..3)),
+            "
+            one
+            TWO
+            three
+            four
+            five
+            "
+            .unindent()
+        );
+
+        // The removal of `four`, which appears at the row of `five`, and the added `six`.
+        assert_eq!(
+            apply_line_changes(&base_text, &target_text, touches(4..6)),
+            "
+            one
+            two
+            three
+            five
+            six
+            "
+            .unindent()
+        );
+    }
+
+    #[test]
+    fn test_apply_line_changes_in_mixed_hunk() {
+        let base_text = "
+            one
+            two
+            three
+            four
+            five
+        "
+        .unindent();
+        let target_text = "
+            one
+            TWO
+            THREE
+            five
+        "
+        .unindent();
+        let touches = |selected: Range<u32>| {
+            move |rows: Range<u32>| {
+                if rows.is_empty() {
+                    selected.contains(&rows.start)
+                } else {
+                    rows.start < selected.end && selected.start < rows.end
+                }
+            }
+        };
+
+        // Only `two` is replaced by `TWO`; the rest of the hunk stays in the base.
+        assert_eq!(
+            apply_line_changes(&base_text, &target_text, touches(1..2)),
+            "
+            one
+            TWO
+            three
+            four
+            five
+            "
+            .unindent()
+        );
+
+        // The last added line also takes the removed line that has no replacement.
+        assert_eq!(
+            apply_line_changes(&base_text, &target_text, touches(2..3)),
+            "
+            one
+            two
+            THREE
+            five
+            "
+            .unindent()
+        );
+    }
+
+    #[test]
+    fn test_base_row_for_target_row() {
+        let base_text = "
+            one
+            two
+            three
+            four
+        "
+        .unindent();
+        let target_text = "
+            zero
+            one
+            three
+            THREE
+            four
+        "
+        .unindent();
+
+        assert_eq!(base_row_for_target_row(&base_text, &target_text, 0), 0);
+        assert_eq!(base_row_for_target_row(&base_text, &target_text, 1), 0);
+        assert_eq!(base_row_for_target_row(&base_text, &target_text, 2), 2);
+        assert_eq!(base_row_for_target_row(&base_text, &target_text, 3), 3);
+        assert_eq!(base_row_for_target_row(&base_text, &target_text, 4), 3);
+    }
+
+    #[test]
+    fn test_changed_rows() {
+        let base_text = "
+            one
+            two
+            three
+            four
+        "
+        .unindent();
+        let target_text = "
+            zero
+            one
+            three
+            THREE
+            four
+        "
+        .unindent();
+
+        assert_eq!(
+            changed_rows(&base_text, &target_text),
+            vec![0..1, 2..2, 3..4]
+        );
+        assert_eq!(changed_rows(&base_text, &base_text), Vec::new());
+    }
 }
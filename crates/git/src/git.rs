@@ -1,16 +1,21 @@
+pub mod askpass;
 pub mod blame;
 pub mod commit;
+pub mod conflict;
 pub mod diff;
 mod hosting_provider;
 mod remote;
 pub mod repository;
+pub mod stash;
 pub mod status;
 
 use anyhow::{anyhow, Context as _, Result};
-use gpui::actions;
+use gpui::{actions, impl_actions};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::ffi::OsStr;
 use std::fmt;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::LazyLock;
 
@@ -41,10 +46,26 @@ actions!(
         RevertAll,
         CommitChanges,
         CommitAllChanges,
-        ClearCommitMessage
+        ClearCommitMessage,
+        Fetch,
+        Pull,
+        Push,
+        ForcePush,
+        StashAll,
+        StashPop,
+        ViewCommitLog,
+        FileHistory
     ]
 );
 
+/// Opens the commit history of the file or directory at the given absolute path.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, JsonSchema)]
+pub struct OpenFileHistory {
+    pub path: PathBuf,
+}
+
+impl_actions!(git, [OpenFileHistory]);
+
 /// The length of a Git short SHA.
 pub const SHORT_SHA_LENGTH: usize = 7;
 
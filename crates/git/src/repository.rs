@@ -1,6 +1,12 @@
+use crate::askpass::{AskPassSession, GitRemoteDelegate};
+use crate::commit::{parse_git_log, CommitFileDiff, CommitSummary, LogOptions, LOG_FORMAT};
+use crate::diff::{apply_line_changes, base_row_for_target_row, changed_rows};
+use crate::stash::{
+    parse_stash_list, stash_entry_moved, stash_revision, StashEntry, STASH_LIST_FORMAT,
+};
 use crate::status::FileStatus;
-use crate::GitHostingProviderRegistry;
 use crate::{blame::Blame, status::GitStatus};
+use crate::{GitHostingProviderRegistry, Oid};
 use anyhow::{anyhow, Context as _, Result};
 use collections::{HashMap, HashSet};
 use git2::BranchType;
@@ -8,13 +14,18 @@ use gpui::SharedString;
 use parking_lot::Mutex;
 use rope::Rope;
 use std::borrow::Borrow;
+use std::io::{Read as _, Write as _};
+use std::process::Stdio;
 use std::sync::LazyLock;
 use std::{
     cmp::Ordering,
+    mem,
+    ops::Range,
     path::{Component, Path, PathBuf},
     sync::Arc,
 };
 use sum_tree::MapSeekTarget;
+use text::LineEnding;
 use util::command::new_std_command;
 use util::ResultExt;
 
@@ -24,6 +35,58 @@ pub struct Branch {
     pub name: SharedString,
     /// Timestamp of most recent commit, normalized to Unix Epoch format.
     pub unix_timestamp: Option<i64>,
+    pub upstream: Option<Upstream>,
+}
+
+impl Branch {
+    pub fn tracking_status(&self) -> Option<UpstreamTrackingStatus> {
+        self.upstream
+            .as_ref()
+            .and_then(|upstream| upstream.tracking)
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Upstream {
+    /// The full name of the upstream ref, e.g. `refs/remotes/origin/main`.
+    pub ref_name: SharedString,
+    /// `None` when the upstream ref no longer exists.
+    pub tracking: Option<UpstreamTrackingStatus>,
+}
+
+impl Upstream {
+    /// Returns the name of the remote this upstream belongs to, if it is a remote-tracking ref.
+    pub fn remote_name(&self) -> Option<&str> {
+        self.ref_name
+            .strip_prefix("refs/remotes/")
+            .and_then(|stripped| stripped.split('/').next())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct UpstreamTrackingStatus {
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Remote {
+    pub name: SharedString,
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum PushOptions {
+    SetUpstream,
+    Force,
+    /// Force-pushes a branch that has no upstream yet and sets it.
+    ForceSetUpstream,
+}
+
+/// The captured output of a git command that talked to a remote.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RemoteCommandOutput {
+    pub stdout: String,
+    pub stderr: String,
 }
 
 pub trait GitRepository: Send + Sync {
@@ -33,6 +96,13 @@ pub trait GitRepository: Send + Sync {
     /// Note that for symlink entries, this will return the contents of the symlink, not the target.
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Loads the contents of a file as of the HEAD commit.
+    fn load_committed_text(&self, path: &RepoPath) -> Option<String>;
+
+    /// Writes the given contents into the index at the path, without touching the worktree.
+    /// Passing `None` removes the path from the index.
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
@@ -63,6 +133,63 @@ pub trait GitRepository: Send + Sync {
     fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
 
     fn commit(&self, message: &str, name_and_email: Option<(&str, &str)>) -> Result<()>;
+
+    /// Returns the remotes of the repository. When a branch name is given and that branch
+    /// has a configured upstream remote, only that remote is returned.
+    fn get_remotes(&self, branch_name: Option<&str>) -> Result<Vec<Remote>>;
+
+    /// Fetches from all remotes.
+    fn fetch(&self, delegate: Arc<dyn GitRemoteDelegate>) -> Result<RemoteCommandOutput>;
+
+    /// Pulls the given branch from the given remote into the current branch.
+    fn pull(
+        &self,
+        branch_name: &str,
+        upstream_name: &str,
+        delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> Result<RemoteCommandOutput>;
+
+    /// Pushes the given branch to the given remote.
+    fn push(
+        &self,
+        branch_name: &str,
+        upstream_name: &str,
+        options: Option<PushOptions>,
+        delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> Result<RemoteCommandOutput>;
+
+    /// Stashes all local changes, including untracked files, and resets the worktree to HEAD.
+    fn stash_all(&self, message: Option<&str>) -> Result<()>;
+
+    /// Returns the stash entries, most recent first.
+    fn stash_entries(&self) -> Result<Vec<StashEntry>>;
+
+    /// Applies the stash entry at the given index, keeping it in the stash.
+    ///
+    /// Entries shift whenever the stash changes, so each stash operation takes the oid of
+    /// the listed entry too, and fails without doing anything if `stash@{index}` is no
+    /// longer that entry.
+    fn stash_apply(&self, index: usize, oid: Oid) -> Result<()>;
+
+    /// Applies the stash entry at the given index and removes it from the stash.
+    fn stash_pop(&self, index: usize, oid: Oid) -> Result<()>;
+
+    /// Removes the stash entry at the given index without applying it.
+    fn stash_drop(&self, index: usize, oid: Oid) -> Result<()>;
+
+    /// Returns the files changed by the stash entry at the given index, relative to the
+    /// commit the entry was created on.
+    fn stash_diff(&self, index: usize, oid: Oid) -> Result<Vec<CommitFileDiff>>;
+
+    /// Returns the commits reachable from HEAD matching the given options, most recent first.
+    fn log(&self, options: &LogOptions) -> Result<Vec<CommitSummary>>;
+
+    /// Returns the files changed by the given commit, relative to its first parent.
+    fn commit_diff(&self, sha: &str) -> Result<Vec<CommitFileDiff>>;
+
+    /// Returns the paths that differ between the working directory and the point where the
+    /// current branch diverged from the given one, including uncommitted and untracked files.
+    fn changed_paths_since(&self, base: &str) -> Result<Vec<RepoPath>>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -91,6 +218,130 @@ impl RealGitRepository {
     }
 }
 
+impl RealGitRepository {
+    fn working_directory(&self) -> Result<PathBuf> {
+        self.repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")
+            .map(Path::to_path_buf)
+    }
+
+    /// Runs a git command that talks to a remote, streaming its progress to the delegate
+    /// and routing credential prompts through an askpass session.
+    fn run_remote_command(
+        &self,
+        args: &[&str],
+        delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> Result<RemoteCommandOutput> {
+        let working_directory = self.working_directory()?;
+        let askpass = AskPassSession::new(delegate.clone())?;
+
+        let mut command = new_std_command(&self.git_binary_path);
+        command
+            .current_dir(&working_directory)
+            .args(args)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        askpass.configure(&mut command);
+
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("failed to spawn git {}", args.join(" ")))?;
+
+        let mut stdout = child
+            .stdout
+            .take()
+            .context("failed to capture git stdout")?;
+        let stdout_reader = std::thread::spawn(move || {
+            let mut output = String::new();
+            stdout.read_to_string(&mut output).map(|_| output)
+        });
+
+        // Git reports progress on stderr, rewriting the current line with carriage returns.
+        let mut stderr = child
+            .stderr
+            .take()
+            .context("failed to capture git stderr")?;
+        let mut stderr_output = Vec::new();
+        let mut line = Vec::new();
+        let mut buffer = [0; 1024];
+        loop {
+            let read = stderr.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            for &byte in &buffer[..read] {
+                stderr_output.push(byte);
+                if byte == b'\r' || byte == b'\n' {
+                    if !line.is_empty() {
+                        delegate.progress(String::from_utf8_lossy(&line).trim());
+                        line.clear();
+                    }
+                } else {
+                    line.push(byte);
+                }
+            }
+        }
+        if !line.is_empty() {
+            delegate.progress(String::from_utf8_lossy(&line).trim());
+        }
+
+        let status = child.wait()?;
+        let stdout = stdout_reader
+            .join()
+            .map_err(|_| anyhow!("failed to read git stdout"))??;
+        let stderr = String::from_utf8_lossy(&stderr_output).into_owned();
+        drop(askpass);
+
+        if !status.success() {
+            return Err(anyhow!(
+                "git {} failed ({status}):\n{}",
+                args.first().copied().unwrap_or_default(),
+                stderr.trim()
+            ));
+        }
+        Ok(RemoteCommandOutput { stdout, stderr })
+    }
+}
+
+impl RealGitRepository {
+    fn run_git_command(&self, args: &[&str]) -> Result<String> {
+        let working_directory = self.working_directory()?;
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(args)
+            .output()
+            .with_context(|| format!("failed to run git {}", args.join(" ")))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed ({}):\n{}",
+                args.first().copied().unwrap_or_default(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Returns the revision of the stash entry at `index`, once it's known to still be `oid`.
+    fn verified_stash_revision(&self, index: usize, oid: Oid) -> Result<String> {
+        let revision = stash_revision(index);
+        let current = self
+            .repository
+            .lock()
+            .revparse_single(&revision)
+            .ok()
+            .map(|object| object.id());
+        if current != Some(oid.0) {
+            return Err(stash_entry_moved(index, oid));
+        }
+        Ok(revision)
+    }
+}
+
 // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
 const GIT_MODE_SYMLINK: u32 = 0o120000;
 
@@ -130,6 +381,62 @@ impl GitRepository for RealGitRepository {
         None
     }
 
+    fn load_committed_text(&self, path: &RepoPath) -> Option<String> {
+        let repo = self.repository.lock();
+        let tree = repo.head().ok()?.peel_to_tree().log_err()?;
+        let entry = tree.get_path(path).ok()?;
+        if entry.filemode() as u32 == GIT_MODE_SYMLINK {
+            return None;
+        }
+        let content = repo.find_blob(entry.id()).log_err()?.content().to_owned();
+        String::from_utf8(content).log_err()
+    }
+
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        let path_str = path.to_str().context("repo path is not valid UTF-8")?;
+        let Some(content) = content else {
+            self.run_git_command(&["update-index", "--force-remove", "--", path_str])?;
+            return Ok(());
+        };
+
+        let mut child = new_std_command(&self.git_binary_path)
+            .current_dir(self.working_directory()?)
+            // Hash the contents as if they were read from the file, so that its clean and
+            // end-of-line filters apply just as they would for `git add`.
+            .args(["hash-object", "-w", "--stdin"])
+            .arg(format!("--path={path_str}"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("failed to spawn git hash-object")?;
+        child
+            .stdin
+            .take()
+            .context("failed to open git hash-object stdin")?
+            .write_all(content.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git hash-object failed ({}):\n{}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let sha = String::from_utf8(output.stdout)?;
+
+        const STAGE_NORMAL: i32 = 0;
+        let mode = self
+            .repository
+            .lock()
+            .index()?
+            .get_path(path, STAGE_NORMAL)
+            .map_or(0o100644, |entry| entry.mode);
+        let cache_info = format!("{mode:o},{},{path_str}", sha.trim());
+        self.run_git_command(&["update-index", "--add", "--cacheinfo", &cache_info])?;
+        Ok(())
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let repo = self.repository.lock();
         let remote = repo.find_remote(name).ok()?;
@@ -188,10 +495,12 @@ impl GitRepository for RealGitRepository {
                         time::UtcOffset::from_whole_seconds(timezone_offset * 60).ok()?;
                     let unix_timestamp =
                         time::OffsetDateTime::from_unix_timestamp(unix_timestamp).ok()?;
+                    let upstream = upstream_for_branch(&repo, &branch);
                     Some(Branch {
                         is_head,
                         name,
                         unix_timestamp: Some(unix_timestamp.to_offset(utc_offset).unix_timestamp()),
+                        upstream,
                     })
                 })
             })
@@ -306,6 +615,332 @@ impl GitRepository for RealGitRepository {
         }
         Ok(())
     }
+
+    fn get_remotes(&self, branch_name: Option<&str>) -> Result<Vec<Remote>> {
+        let repo = self.repository.lock();
+        if let Some(branch_name) = branch_name {
+            let upstream_remote = repo
+                .find_branch(branch_name, BranchType::Local)
+                .ok()
+                .and_then(|branch| {
+                    let ref_name = branch.get().name()?.to_string();
+                    let remote = repo.branch_upstream_remote(&ref_name).ok()?;
+                    remote.as_str().map(|name| name.to_string())
+                });
+            if let Some(remote) = upstream_remote {
+                return Ok(vec![Remote {
+                    name: remote.into(),
+                }]);
+            }
+        }
+
+        Ok(repo
+            .remotes()?
+            .iter()
+            .flatten()
+            .map(|name| Remote {
+                name: name.to_string().into(),
+            })
+            .collect())
+    }
+
+    fn fetch(&self, delegate: Arc<dyn GitRemoteDelegate>) -> Result<RemoteCommandOutput> {
+        self.run_remote_command(&["fetch", "--all", "--progress"], delegate)
+    }
+
+    fn pull(
+        &self,
+        branch_name: &str,
+        upstream_name: &str,
+        delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> Result<RemoteCommandOutput> {
+        self.run_remote_command(
+            &["pull", "--progress", upstream_name, branch_name],
+            delegate,
+        )
+    }
+
+    fn push(
+        &self,
+        branch_name: &str,
+        upstream_name: &str,
+        options: Option<PushOptions>,
+        delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> Result<RemoteCommandOutput> {
+        let mut args = vec!["push", "--progress"];
+        match options {
+            Some(PushOptions::SetUpstream) => args.push("--set-upstream"),
+            Some(PushOptions::Force) => args.push("--force-with-lease"),
+            Some(PushOptions::ForceSetUpstream) => {
+                args.extend(["--set-upstream", "--force-with-lease"])
+            }
+            None => {}
+        }
+        args.push(upstream_name);
+        args.push(branch_name);
+        self.run_remote_command(&args, delegate)
+    }
+
+    fn stash_all(&self, message: Option<&str>) -> Result<()> {
+        let mut args = vec!["stash", "push", "--include-untracked"];
+        if let Some(message) = message {
+            args.push("--message");
+            args.push(message);
+        }
+        self.run_git_command(&args)?;
+        Ok(())
+    }
+
+    fn stash_entries(&self) -> Result<Vec<StashEntry>> {
+        let output = self.run_git_command(&["stash", "list", STASH_LIST_FORMAT])?;
+        parse_stash_list(&output)
+    }
+
+    fn stash_apply(&self, index: usize, oid: Oid) -> Result<()> {
+        let revision = self.verified_stash_revision(index, oid)?;
+        self.run_git_command(&["stash", "apply", &revision])?;
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize, oid: Oid) -> Result<()> {
+        let revision = self.verified_stash_revision(index, oid)?;
+        self.run_git_command(&["stash", "pop", &revision])?;
+        Ok(())
+    }
+
+    fn stash_drop(&self, index: usize, oid: Oid) -> Result<()> {
+        let revision = self.verified_stash_revision(index, oid)?;
+        self.run_git_command(&["stash", "drop", &revision])?;
+        Ok(())
+    }
+
+    fn stash_diff(&self, index: usize, oid: Oid) -> Result<Vec<CommitFileDiff>> {
+        let revision = self.verified_stash_revision(index, oid)?;
+        let repo = self.repository.lock();
+        let stash = repo
+            .revparse_single(&revision)?
+            .peel_to_commit()
+            .with_context(|| format!("no stash entry at index {index}"))?;
+        let base_tree = stash.parent(0)?.tree()?;
+        let mut files = tree_diff_files(&repo, Some(&base_tree), &stash.tree()?)?;
+
+        // Untracked files are stored in the third parent, when the entry was created with them.
+        if let Ok(untracked) = stash.parent(2) {
+            files.extend(tree_diff_files(&repo, None, &untracked.tree()?)?);
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    fn log(&self, options: &LogOptions) -> Result<Vec<CommitSummary>> {
+        // `git log` fails in a repository without commits, which simply has no history yet.
+        if self.repository.lock().head().is_err() {
+            return Ok(Vec::new());
+        }
+
+        let skip = format!("--skip={}", options.skip);
+        let max_count = format!("--max-count={}", options.limit);
+        let mut args = vec!["log", LOG_FORMAT, &skip, &max_count];
+        let author = options
+            .author
+            .as_ref()
+            .map(|author| format!("--author={author}"));
+        if let Some(author) = author.as_deref() {
+            args.push("--fixed-strings");
+            args.push("--regexp-ignore-case");
+            args.push(author);
+        }
+        let path = options.path.as_ref().map(|path| path.to_string_lossy());
+        if let Some(path) = path.as_deref() {
+            // `--follow` only works for a single file, and fails for directories.
+            if !self.working_directory()?.join(path).is_dir() {
+                args.push("--follow");
+            }
+            args.push("--");
+            args.push(path);
+        }
+        let output = self.run_git_command(&args)?;
+        parse_git_log(&output)
+    }
+
+    fn commit_diff(&self, sha: &str) -> Result<Vec<CommitFileDiff>> {
+        let repo = self.repository.lock();
+        let commit = repo
+            .revparse_single(sha)?
+            .peel_to_commit()
+            .with_context(|| format!("{sha} is not a commit"))?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        tree_diff_files(&repo, parent_tree.as_ref(), &commit.tree()?)
+    }
+
+    fn changed_paths_since(&self, base: &str) -> Result<Vec<RepoPath>> {
+        let repo = self.repository.lock();
+        let base_commit = repo
+            .revparse_single(base)?
+            .peel_to_commit()
+            .with_context(|| format!("{base} is not a commit"))?;
+        let base_tree = match repo.head().ok().and_then(|head| head.target()) {
+            Some(head_oid) => {
+                let merge_base = repo.merge_base(head_oid, base_commit.id())?;
+                repo.find_commit(merge_base)?.tree()?
+            }
+            None => base_commit.tree()?,
+        };
+
+        let mut options = git2::DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut options))?;
+        let mut paths = diff
+            .deltas()
+            .filter_map(|delta| {
+                let path = delta.new_file().path().or(delta.old_file().path())?;
+                Some(RepoPath::from(path))
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+}
+
+/// Stages the changes between the index and `buffer_text` that touch any of the given
+/// buffer rows, leaving the rest of the file's changes unstaged.
+pub fn stage_lines(
+    repo: &dyn GitRepository,
+    path: &RepoPath,
+    buffer_text: &str,
+    rows: &[Range<u32>],
+) -> Result<()> {
+    let index_text = repo.load_index_text(path).unwrap_or_default();
+    let buffer_text = with_line_endings_of(buffer_text, &index_text);
+    let new_index_text = apply_line_changes(&index_text, &buffer_text, |changed_rows| {
+        rows.iter().any(|rows| rows_touch(rows, &changed_rows))
+    });
+    repo.set_index_text(path, Some(new_index_text))
+}
+
+/// Unstages the changes between HEAD and the index that correspond to any of the given
+/// buffer rows, keeping the rest of the file's staged changes. With `whole_hunks`, every
+/// staged hunk touched by the rows is unstaged in full.
+pub fn unstage_lines(
+    repo: &dyn GitRepository,
+    path: &RepoPath,
+    buffer_text: &str,
+    rows: &[Range<u32>],
+    whole_hunks: bool,
+) -> Result<()> {
+    let Some(index_text) = repo.load_index_text(path) else {
+        return Ok(());
+    };
+    let committed_text = repo.load_committed_text(path);
+    let buffer_text = with_line_endings_of(buffer_text, &index_text);
+    let mut index_rows = rows
+        .iter()
+        .map(|rows| {
+            let start = base_row_for_target_row(&index_text, &buffer_text, rows.start);
+            let end = base_row_for_target_row(&index_text, &buffer_text, rows.end);
+            start..end.max(start + 1)
+        })
+        .collect::<Vec<_>>();
+    if whole_hunks {
+        index_rows = changed_rows(committed_text.as_deref().unwrap_or_default(), &index_text)
+            .into_iter()
+            .filter(|hunk_rows| index_rows.iter().any(|rows| rows_touch(rows, hunk_rows)))
+            .map(|hunk_rows| hunk_rows.start..hunk_rows.end.max(hunk_rows.start + 1))
+            .collect();
+    }
+    let new_index_text = apply_line_changes(
+        committed_text.as_deref().unwrap_or_default(),
+        &index_text,
+        |changed_rows| {
+            !index_rows
+                .iter()
+                .any(|rows| rows_touch(rows, &changed_rows))
+        },
+    );
+    // A file that isn't in HEAD is removed from the index once all of it is unstaged.
+    let new_index_text =
+        (committed_text.is_some() || !new_index_text.is_empty()).then_some(new_index_text);
+    repo.set_index_text(path, new_index_text)
+}
+
+/// Converts `text` to the line endings used by `base_text`, so that lines which only differ
+/// in their line ending aren't treated as changed. When `base_text` has no lines to go by,
+/// `text` keeps its own line endings.
+fn with_line_endings_of(text: &str, base_text: &str) -> String {
+    if !base_text.contains('\n') {
+        return text.to_string();
+    }
+    let mut text = text.to_string();
+    LineEnding::normalize(&mut text);
+    match LineEnding::detect(base_text) {
+        LineEnding::Unix => text,
+        LineEnding::Windows => text.replace('\n', "\r\n"),
+    }
+}
+
+/// Whether a change covering `changed_rows` should be included for the `selected_rows`.
+/// Removed lines cover an empty range, and are selected along with the row they precede.
+fn rows_touch(selected_rows: &Range<u32>, changed_rows: &Range<u32>) -> bool {
+    if changed_rows.is_empty() {
+        selected_rows.contains(&changed_rows.start)
+    } else {
+        changed_rows.start < selected_rows.end && selected_rows.start < changed_rows.end
+    }
+}
+
+/// Returns the contents of every file that differs between the two trees.
+fn tree_diff_files(
+    repo: &git2::Repository,
+    old_tree: Option<&git2::Tree>,
+    new_tree: &git2::Tree,
+) -> Result<Vec<CommitFileDiff>> {
+    let diff = repo.diff_tree_to_tree(old_tree, Some(new_tree), None)?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| {
+            let path = delta.new_file().path().or(delta.old_file().path())?;
+            Some(CommitFileDiff {
+                path: RepoPath::from(path),
+                old_text: blob_text(repo, delta.old_file().id()),
+                new_text: blob_text(repo, delta.new_file().id()),
+            })
+        })
+        .collect())
+}
+
+fn blob_text(repo: &git2::Repository, oid: git2::Oid) -> Option<String> {
+    if oid.is_zero() {
+        return None;
+    }
+    let blob = repo.find_blob(oid).ok()?;
+    String::from_utf8(blob.content().to_vec()).ok()
+}
+
+fn upstream_for_branch(repo: &git2::Repository, branch: &git2::Branch) -> Option<Upstream> {
+    let local_ref_name = branch.get().name()?;
+    let upstream_ref_name = repo.branch_upstream_name(local_ref_name).ok()?;
+    let ref_name: SharedString = upstream_ref_name.as_str()?.to_string().into();
+
+    let tracking = tracking_status_for_branch(repo, branch);
+    Some(Upstream { ref_name, tracking })
+}
+
+fn tracking_status_for_branch(
+    repo: &git2::Repository,
+    branch: &git2::Branch,
+) -> Option<UpstreamTrackingStatus> {
+    let local_oid = branch.get().target()?;
+    let upstream_oid = branch.upstream().ok()?.get().target()?;
+    let (ahead, behind) = repo.graph_ahead_behind(local_oid, upstream_oid).ok()?;
+    Some(UpstreamTrackingStatus {
+        ahead: ahead as u32,
+        behind: behind as u32,
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -318,10 +953,27 @@ pub struct FakeGitRepositoryState {
     pub dot_git_dir: PathBuf,
     pub event_emitter: smol::channel::Sender<PathBuf>,
     pub index_contents: HashMap<PathBuf, String>,
+    pub head_contents: HashMap<PathBuf, String>,
     pub blames: HashMap<PathBuf, Blame>,
     pub statuses: HashMap<RepoPath, FileStatus>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
+    pub remotes: Vec<String>,
+    pub upstreams: HashMap<String, Upstream>,
+    pub stash_entries: Vec<StashEntry>,
+    /// The changes recorded by every stash entry ever created, including dropped ones.
+    pub stashes: HashMap<Oid, FakeStash>,
+    pub commits: Vec<CommitSummary>,
+    /// The paths changed by each of the `commits`.
+    pub commit_paths: HashMap<Oid, Vec<RepoPath>>,
+}
+
+/// The changes stashed by [`FakeGitRepository::stash_all`].
+#[derive(Debug, Clone, Default)]
+pub struct FakeStash {
+    pub statuses: HashMap<RepoPath, FileStatus>,
+    /// The fake repository has no working copy, so the index stands in for it.
+    pub files: Vec<CommitFileDiff>,
 }
 
 impl FakeGitRepository {
@@ -331,15 +983,42 @@ impl FakeGitRepository {
 }
 
 impl FakeGitRepositoryState {
+    fn stash_entry(&self, index: usize, oid: Oid) -> Result<&StashEntry> {
+        self.stash_entries
+            .get(index)
+            .filter(|entry| entry.oid == oid)
+            .ok_or_else(|| stash_entry_moved(index, oid))
+    }
+
+    fn apply_stash(&mut self, oid: Oid) {
+        if let Some(stash) = self.stashes.get(&oid) {
+            self.statuses.extend(stash.statuses.clone());
+        }
+    }
+
+    fn drop_stash_entry(&mut self, index: usize) {
+        self.stash_entries.remove(index);
+        for (index, entry) in self.stash_entries.iter_mut().enumerate() {
+            entry.index = index;
+        }
+    }
+
     pub fn new(dot_git_dir: PathBuf, event_emitter: smol::channel::Sender<PathBuf>) -> Self {
         FakeGitRepositoryState {
             dot_git_dir,
             event_emitter,
             index_contents: Default::default(),
+            head_contents: Default::default(),
             blames: Default::default(),
             statuses: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
+            remotes: Default::default(),
+            upstreams: Default::default(),
+            stash_entries: Default::default(),
+            stashes: Default::default(),
+            commits: Default::default(),
+            commit_paths: Default::default(),
         }
     }
 }
@@ -352,6 +1031,24 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
+    fn load_committed_text(&self, path: &RepoPath) -> Option<String> {
+        let state = self.state.lock();
+        state.head_contents.get(&**path).cloned()
+    }
+
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        let mut state = self.state.lock();
+        match content {
+            Some(content) => state.index_contents.insert(path.to_path_buf(), content),
+            None => state.index_contents.remove(path.as_ref()),
+        };
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -404,6 +1101,7 @@ impl GitRepository for FakeGitRepository {
                 is_head: Some(branch_name) == current_branch.as_ref(),
                 name: branch_name.into(),
                 unix_timestamp: None,
+                upstream: state.upstreams.get(branch_name).cloned(),
             })
             .collect())
     }
@@ -453,6 +1151,228 @@ impl GitRepository for FakeGitRepository {
     fn commit(&self, _message: &str, _name_and_email: Option<(&str, &str)>) -> Result<()> {
         unimplemented!()
     }
+
+    fn get_remotes(&self, _branch_name: Option<&str>) -> Result<Vec<Remote>> {
+        let state = self.state.lock();
+        Ok(state
+            .remotes
+            .iter()
+            .map(|name| Remote {
+                name: name.clone().into(),
+            })
+            .collect())
+    }
+
+    fn fetch(&self, delegate: Arc<dyn GitRemoteDelegate>) -> Result<RemoteCommandOutput> {
+        let state = self.state.lock();
+        if state.remotes.is_empty() {
+            return Err(anyhow!("no remote repository specified"));
+        }
+        for remote in &state.remotes {
+            delegate.progress(&format!("Fetching {remote}"));
+        }
+        Ok(RemoteCommandOutput::default())
+    }
+
+    fn pull(
+        &self,
+        branch_name: &str,
+        upstream_name: &str,
+        _delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> Result<RemoteCommandOutput> {
+        let mut state = self.state.lock();
+        if !state.remotes.iter().any(|remote| remote == upstream_name) {
+            return Err(anyhow!("no such remote: {upstream_name}"));
+        }
+        if let Some(tracking) = state
+            .upstreams
+            .get_mut(branch_name)
+            .and_then(|upstream| upstream.tracking.as_mut())
+        {
+            tracking.behind = 0;
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(RemoteCommandOutput::default())
+    }
+
+    fn push(
+        &self,
+        branch_name: &str,
+        upstream_name: &str,
+        options: Option<PushOptions>,
+        _delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> Result<RemoteCommandOutput> {
+        let mut state = self.state.lock();
+        if !state.remotes.iter().any(|remote| remote == upstream_name) {
+            return Err(anyhow!("no such remote: {upstream_name}"));
+        }
+        if matches!(
+            options,
+            Some(PushOptions::SetUpstream | PushOptions::ForceSetUpstream)
+        ) {
+            state.upstreams.insert(
+                branch_name.to_string(),
+                Upstream {
+                    ref_name: format!("refs/remotes/{upstream_name}/{branch_name}").into(),
+                    tracking: Some(UpstreamTrackingStatus::default()),
+                },
+            );
+        } else if let Some(tracking) = state
+            .upstreams
+            .get_mut(branch_name)
+            .and_then(|upstream| upstream.tracking.as_mut())
+        {
+            tracking.ahead = 0;
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(RemoteCommandOutput::default())
+    }
+
+    fn stash_all(&self, message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.statuses.is_empty() {
+            return Err(anyhow!("No local changes to save"));
+        }
+        let branch = state.current_branch_name.clone();
+        let message = match message {
+            Some(message) => format!(
+                "On {}: {message}",
+                branch.as_deref().unwrap_or("(no branch)")
+            ),
+            None => format!("WIP on {}", branch.as_deref().unwrap_or("(no branch)")),
+        };
+        let mut files = state
+            .statuses
+            .keys()
+            .map(|path| CommitFileDiff {
+                path: path.clone(),
+                old_text: state.head_contents.get(&**path).cloned(),
+                new_text: state.index_contents.get(&**path).cloned(),
+            })
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let stash = FakeStash {
+            statuses: mem::take(&mut state.statuses),
+            files,
+        };
+        let mut oid = [0; 20];
+        oid[..8].copy_from_slice(&(state.stashes.len() as u64 + 1).to_be_bytes());
+        let oid = Oid::from_bytes(&oid)?;
+        state.stashes.insert(oid, stash);
+        state.stash_entries.insert(
+            0,
+            StashEntry {
+                index: 0,
+                oid,
+                message: message.into(),
+                branch: branch.map(Into::into),
+                unix_timestamp: 0,
+            },
+        );
+        for (index, entry) in state.stash_entries.iter_mut().enumerate() {
+            entry.index = index;
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_entries(&self) -> Result<Vec<StashEntry>> {
+        Ok(self.state.lock().stash_entries.clone())
+    }
+
+    fn stash_apply(&self, index: usize, oid: Oid) -> Result<()> {
+        let mut state = self.state.lock();
+        state.stash_entry(index, oid)?;
+        state.apply_stash(oid);
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize, oid: Oid) -> Result<()> {
+        let mut state = self.state.lock();
+        state.stash_entry(index, oid)?;
+        state.apply_stash(oid);
+        state.drop_stash_entry(index);
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_drop(&self, index: usize, oid: Oid) -> Result<()> {
+        let mut state = self.state.lock();
+        state.stash_entry(index, oid)?;
+        state.drop_stash_entry(index);
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_diff(&self, index: usize, oid: Oid) -> Result<Vec<CommitFileDiff>> {
+        let state = self.state.lock();
+        state.stash_entry(index, oid)?;
+        Ok(state
+            .stashes
+            .get(&oid)
+            .map(|stash| stash.files.clone())
+            .unwrap_or_default())
+    }
+
+    fn log(&self, options: &LogOptions) -> Result<Vec<CommitSummary>> {
+        let state = self.state.lock();
+        Ok(state
+            .commits
+            .iter()
+            .filter(|commit| {
+                options.author.as_ref().map_or(true, |author| {
+                    let author = author.to_lowercase();
+                    commit.author_name.to_lowercase().contains(&author)
+                        || commit.author_email.to_lowercase().contains(&author)
+                })
+            })
+            .filter(|commit| {
+                options.path.as_ref().map_or(true, |path| {
+                    state
+                        .commit_paths
+                        .get(&commit.sha)
+                        .is_some_and(|paths| paths.iter().any(|changed| changed.starts_with(path)))
+                })
+            })
+            .skip(options.skip)
+            .take(options.limit)
+            .cloned()
+            .collect())
+    }
+
+    fn commit_diff(&self, _sha: &str) -> Result<Vec<CommitFileDiff>> {
+        unimplemented!()
+    }
+
+    fn changed_paths_since(&self, base: &str) -> Result<Vec<RepoPath>> {
+        let state = self.state.lock();
+        if !state.branches.contains(base) {
+            return Err(anyhow!("no such branch: {base}"));
+        }
+        // The fake repository has no history, so only uncommitted changes differ from a branch.
+        let mut paths = state.statuses.keys().cloned().collect::<Vec<_>>();
+        paths.sort();
+        Ok(paths)
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -571,3 +1491,139 @@ impl<'a> MapSeekTarget<RepoPath> for RepoPathDescendants<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::StatusCode;
+
+    fn fake_repository(
+        index_text: &str,
+    ) -> (
+        Arc<dyn GitRepository>,
+        Arc<Mutex<FakeGitRepositoryState>>,
+        smol::channel::Receiver<PathBuf>,
+    ) {
+        let (event_emitter, events) = smol::channel::unbounded();
+        let state = Arc::new(Mutex::new(FakeGitRepositoryState::new(
+            PathBuf::from("/.git"),
+            event_emitter,
+        )));
+        state
+            .lock()
+            .index_contents
+            .insert(PathBuf::from("file.txt"), index_text.to_string());
+        (FakeGitRepository::open(state.clone()), state, events)
+    }
+
+    #[test]
+    fn test_stage_lines_with_crlf_line_endings() {
+        let path = RepoPath::from("file.txt");
+
+        // The index keeps the file's CRLF line endings.
+        let (repo, state, _events) = fake_repository("one\r\ntwo\r\nthree\r\n");
+        stage_lines(
+            repo.as_ref(),
+            &path,
+            "one\r\nTWO\r\nthree\r\nfour\r\n",
+            &[1..2],
+        )
+        .unwrap();
+        assert_eq!(
+            state.lock().index_contents[Path::new("file.txt")],
+            "one\r\nTWO\r\nthree\r\n"
+        );
+
+        // The index was normalized to LF line endings, e.g. by `core.autocrlf`.
+        let (repo, state, _events) = fake_repository("one\ntwo\nthree\n");
+        stage_lines(
+            repo.as_ref(),
+            &path,
+            "one\r\nTWO\r\nthree\r\nfour\r\n",
+            &[3..4],
+        )
+        .unwrap();
+        assert_eq!(
+            state.lock().index_contents[Path::new("file.txt")],
+            "one\ntwo\nthree\nfour\n"
+        );
+    }
+
+    #[test]
+    fn test_stage_lines_in_mixed_hunk() {
+        let path = RepoPath::from("file.txt");
+        let (repo, state, _events) = fake_repository("one\ntwo\nthree\nfour\n");
+        stage_lines(repo.as_ref(), &path, "one\nTWO\nTHREE\nfour\n", &[2..3]).unwrap();
+        assert_eq!(
+            state.lock().index_contents[Path::new("file.txt")],
+            "one\ntwo\nTHREE\nfour\n"
+        );
+    }
+
+    #[test]
+    fn test_fake_stash_operations() {
+        let path = RepoPath::from("file.txt");
+        let (repo, state, _events) = fake_repository("one\nTWO\n");
+        state
+            .lock()
+            .head_contents
+            .insert(PathBuf::from("file.txt"), "one\ntwo\n".to_string());
+        let stash_change = |message: &str| {
+            state
+                .lock()
+                .statuses
+                .insert(path.clone(), StatusCode::Modified.index());
+            repo.stash_all(Some(message)).unwrap();
+            assert!(state.lock().statuses.is_empty());
+        };
+        stash_change("first");
+        stash_change("second");
+
+        let entries = repo.stash_entries().unwrap();
+        let messages = entries
+            .iter()
+            .map(|entry| entry.message.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            ["On (no branch): second", "On (no branch): first"]
+        );
+        let (second, first) = (entries[0].clone(), entries[1].clone());
+        assert_ne!(first.oid, second.oid);
+
+        assert_eq!(
+            repo.stash_diff(first.index, first.oid).unwrap(),
+            [CommitFileDiff {
+                path: path.clone(),
+                old_text: Some("one\ntwo\n".to_string()),
+                new_text: Some("one\nTWO\n".to_string()),
+            }]
+        );
+
+        // Applying keeps the entry in the stash.
+        repo.stash_apply(first.index, first.oid).unwrap();
+        assert!(state.lock().statuses.contains_key(&path));
+        assert_eq!(repo.stash_entries().unwrap().len(), 2);
+        state.lock().statuses.clear();
+
+        // Dropping removes the entry without applying it, and moves the older entries up.
+        repo.stash_drop(second.index, second.oid).unwrap();
+        assert!(state.lock().statuses.is_empty());
+        let entries = repo.stash_entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].index, entries[0].oid), (0, first.oid));
+
+        // Entries listed before the stash changed are refused, rather than acting on
+        // whichever entry now has their index.
+        assert!(repo.stash_apply(first.index, first.oid).is_err());
+        assert!(repo.stash_pop(second.index, second.oid).is_err());
+        assert!(repo.stash_diff(second.index, second.oid).is_err());
+        assert!(state.lock().statuses.is_empty());
+        assert_eq!(repo.stash_entries().unwrap().len(), 1);
+
+        // Popping applies the entry and removes it.
+        repo.stash_pop(0, first.oid).unwrap();
+        assert!(state.lock().statuses.contains_key(&path));
+        assert!(repo.stash_entries().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::Oid;
+use anyhow::{anyhow, Context as _, Result};
+use gpui::SharedString;
+
+/// The format passed to `git stash list`, producing one NUL-separated record per entry.
+pub(crate) const STASH_LIST_FORMAT: &str = "--format=%H%x00%ct%x00%gs";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The position of the entry in the stash, as in `stash@{index}`.
+    pub index: usize,
+    pub oid: Oid,
+    /// The reflog subject, e.g. `WIP on main: 1234567 Fix the thing`.
+    pub message: SharedString,
+    /// The branch that was checked out when the entry was created.
+    pub branch: Option<SharedString>,
+    /// Creation time, normalized to Unix Epoch format.
+    pub unix_timestamp: i64,
+}
+
+impl StashEntry {
+    /// The revision naming this entry, e.g. `stash@{0}`.
+    pub fn revision(&self) -> String {
+        stash_revision(self.index)
+    }
+}
+
+pub(crate) fn stash_revision(index: usize) -> String {
+    format!("stash@{{{index}}}")
+}
+
+/// The error for an operation on a stash entry that has moved or been dropped since the
+/// stash was listed.
+pub(crate) fn stash_entry_moved(index: usize, oid: Oid) -> anyhow::Error {
+    anyhow!(
+        "the stash has changed since it was listed: {} is no longer {}",
+        stash_revision(index),
+        oid.display_short()
+    )
+}
+
+pub(crate) fn parse_stash_list(output: &str) -> Result<Vec<StashEntry>> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let mut fields = line.splitn(3, '\0');
+            let oid = fields.next().context("missing stash sha")?.parse::<Oid>()?;
+            let unix_timestamp = fields
+                .next()
+                .context("missing stash timestamp")?
+                .parse::<i64>()
+                .context("invalid stash timestamp")?;
+            let message = fields.next().context("missing stash message")?;
+            Ok(StashEntry {
+                index,
+                oid,
+                message: message.to_string().into(),
+                branch: branch_from_stash_message(message).map(Into::into),
+                unix_timestamp,
+            })
+        })
+        .collect()
+}
+
+/// Extracts the branch from messages such as `WIP on main: ...` or `On main: ...`.
+fn branch_from_stash_message(message: &str) -> Option<String> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+    let (branch, _) = rest.split_once(':')?;
+    if branch == "(no branch)" {
+        None
+    } else {
+        Some(branch.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stash_list() {
+        let output = concat!(
+            "2f5d0b5c0d8d6c4b0c6f6b8f4b1d0e1f2a3b4c5d\x001700000000\x00WIP on main: 1234567 Fix the thing\n",
+            "8a1e3f7f4c2d5e6b7a8c9d0e1f2a3b4c5d6e7f80\x001690000000\x00On feature/x: half done: really\n",
+            "0123456789abcdef0123456789abcdef01234567\x001680000000\x00WIP on (no branch): 7654321 Detached\n",
+        );
+        let entries = parse_stash_list(output).unwrap();
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].index, 0);
+        assert_eq!(entries[0].revision(), "stash@{0}");
+        assert_eq!(
+            entries[0].oid.to_string(),
+            "2f5d0b5c0d8d6c4b0c6f6b8f4b1d0e1f2a3b4c5d"
+        );
+        assert_eq!(entries[0].unix_timestamp, 1700000000);
+        assert_eq!(
+            entries[0].message.as_ref(),
+            "WIP on main: 1234567 Fix the thing"
+        );
+        assert_eq!(entries[0].branch.as_deref(), Some("main"));
+
+        assert_eq!(entries[1].index, 1);
+        assert_eq!(
+            entries[1].message.as_ref(),
+            "On feature/x: half done: really"
+        );
+        assert_eq!(entries[1].branch.as_deref(), Some("feature/x"));
+
+        assert_eq!(entries[2].index, 2);
+        assert_eq!(entries[2].branch, None);
+    }
+
+    #[test]
+    fn test_parse_empty_stash_list() {
+        assert_eq!(parse_stash_list("").unwrap(), Vec::new());
+    }
+}
@@ -83,8 +83,8 @@ impl FileStatus {
             }
             .into(),
             [b'D', b'D'] => UnmergedStatus {
-                first_head: UnmergedStatusCode::Added,
-                second_head: UnmergedStatusCode::Added,
+                first_head: UnmergedStatusCode::Deleted,
+                second_head: UnmergedStatusCode::Deleted,
             }
             .into(),
             [x, b'U'] => UnmergedStatus {
@@ -238,6 +238,22 @@ impl StatusCode {
     }
 }
 
+impl UnmergedStatus {
+    /// Describes the conflict the way `git status` does, e.g. "both modified".
+    pub fn description(self) -> &'static str {
+        use UnmergedStatusCode::*;
+        match (self.first_head, self.second_head) {
+            (Deleted, Deleted) => "both deleted",
+            (Added, Updated) => "added by us",
+            (Updated, Deleted) => "deleted by them",
+            (Updated, Added) => "added by them",
+            (Deleted, Updated) => "deleted by us",
+            (Added, Added) => "both added",
+            _ => "both modified",
+        }
+    }
+}
+
 impl UnmergedStatusCode {
     fn from_byte(byte: u8) -> anyhow::Result<Self> {
         match byte {
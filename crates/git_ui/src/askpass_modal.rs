@@ -0,0 +1,92 @@
+use editor::Editor;
+use futures::channel::oneshot;
+use gpui::{
+    App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, SharedString, Subscription,
+};
+use ui::prelude::*;
+use workspace::ModalView;
+
+/// Asks the user for a credential requested by a running git command.
+pub struct AskPassModal {
+    prompt: SharedString,
+    editor: Entity<Editor>,
+    sender: Option<oneshot::Sender<Option<String>>>,
+    _subscription: Subscription,
+}
+
+impl ModalView for AskPassModal {}
+
+impl EventEmitter<DismissEvent> for AskPassModal {}
+
+impl Focusable for AskPassModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl AskPassModal {
+    pub fn new(
+        prompt: String,
+        sender: oneshot::Sender<Option<String>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            // Usernames are safe to show, everything else is treated as a secret.
+            if !prompt.to_lowercase().contains("username") {
+                editor.set_masked(true, cx);
+            }
+            editor
+        });
+        // Declines the prompt when the modal is dismissed without confirming.
+        let _subscription = cx.on_release(|this, _| {
+            if let Some(sender) = this.sender.take() {
+                sender.send(None).ok();
+            }
+        });
+        Self {
+            prompt: prompt.trim().to_string().into(),
+            editor,
+            sender: Some(sender),
+            _subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(sender) = self.sender.take() {
+            sender.send(None).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        let response = self.editor.read(cx).text(cx);
+        if let Some(sender) = self.sender.take() {
+            sender.send(Some(response)).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for AskPassModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .elevation_2(cx)
+            .key_context("AskPassModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Icon::new(IconName::FileLock).color(Color::Muted))
+                    .child(Label::new(self.prompt.clone())),
+            )
+            .child(div().px_2().py_1().child(self.editor.clone()))
+    }
+}
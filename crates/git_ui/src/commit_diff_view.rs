@@ -0,0 +1,361 @@
+use anyhow::Context as _;
+use editor::{Editor, EditorEvent, ExcerptRange, MultiBuffer};
+use futures::channel::oneshot;
+use git::{
+    commit::{CommitFileDiff, CommitSummary},
+    stash::StashEntry,
+};
+use gpui::{AnyView, App, Entity, EventEmitter, FocusHandle, Focusable, SharedString};
+use language::{Buffer, Capability, DiskState};
+use project::{buffer_store::BufferChangeSet, git::RepositoryHandle, File, Project};
+use std::{any::TypeId, sync::Arc};
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::{
+    item::Item, notifications::DetachAndPromptErr, searchable::SearchableItemHandle, Workspace,
+};
+
+/// Shows the changes made by a commit or recorded in a stash entry, with every file
+/// diffed against the commit it was based on.
+pub struct CommitDiffView {
+    editor: Entity<Editor>,
+    title: SharedString,
+}
+
+impl CommitDiffView {
+    /// Loads the changes of the given commit and opens them in the active pane.
+    pub fn deploy_commit(
+        workspace: &mut Workspace,
+        commit: &CommitSummary,
+        repository: RepositoryHandle,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let title = format!("{} {}", commit.sha.display_short(), commit.subject).into();
+        let files = repository.commit_diff(commit.sha.to_string().into());
+        Self::deploy(workspace, title, files, repository, window, cx);
+    }
+
+    /// Loads the changes of the given stash entry and opens them in the active pane.
+    pub fn deploy_stash(
+        workspace: &mut Workspace,
+        entry: &StashEntry,
+        repository: RepositoryHandle,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let title = format!("{}: {}", entry.revision(), entry.message).into();
+        let files = repository.stash_diff(entry.index, entry.oid);
+        Self::deploy(workspace, title, files, repository, window, cx);
+    }
+
+    fn deploy(
+        workspace: &mut Workspace,
+        title: SharedString,
+        files: oneshot::Receiver<anyhow::Result<Vec<CommitFileDiff>>>,
+        repository: RepositoryHandle,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        cx.spawn_in(window, |workspace, mut cx| async move {
+            let files = files.await.context("diff operation was canceled")??;
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let view = cx.new(|cx| Self::new(title, files, &repository, project, window, cx));
+                workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to show changes", window, cx, |e, _, _| {
+            Some(format!("{e}"))
+        });
+    }
+
+    fn new(
+        title: SharedString,
+        files: Vec<CommitFileDiff>,
+        repository: &RepositoryHandle,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadOnly));
+        let languages = project.read(cx).languages().clone();
+
+        let mut change_sets = Vec::new();
+        for file in files {
+            let Some(project_path) = repository.unrelativize(&file.path) else {
+                continue;
+            };
+            let worktree = project
+                .read(cx)
+                .worktree_for_id(project_path.worktree_id, cx);
+            let buffer = cx.new(|cx| {
+                let mut buffer = Buffer::local(file.new_text.unwrap_or_default(), cx);
+                buffer.set_capability(Capability::ReadOnly, cx);
+                // Give the buffer the path of the changed file, so that excerpt headers
+                // show it and jumping to the file opens the working copy.
+                if let Some(worktree) = worktree {
+                    buffer.file_updated(
+                        Arc::new(File {
+                            worktree,
+                            path: project_path.path.clone(),
+                            disk_state: DiskState::New,
+                            entry_id: None,
+                            is_local: false,
+                            is_private: false,
+                        }),
+                        cx,
+                    );
+                }
+                buffer
+            });
+
+            cx.spawn({
+                let buffer = buffer.downgrade();
+                let languages = languages.clone();
+                let path = project_path.path.clone();
+                |_, mut cx| async move {
+                    let language = languages.language_for_file_path(&path).await.ok();
+                    buffer
+                        .update(&mut cx, |buffer, cx| {
+                            buffer.set_language_registry(languages);
+                            buffer.set_language(language, cx);
+                        })
+                        .log_err();
+                }
+            })
+            .detach();
+
+            change_sets.push(cx.new(|cx| {
+                let mut change_set = BufferChangeSet::new(&buffer, cx);
+                let _ = change_set.set_base_text(
+                    file.old_text.unwrap_or_default(),
+                    buffer.read(cx).text_snapshot(),
+                    cx,
+                );
+                change_set
+            }));
+
+            multibuffer.update(cx, |multibuffer, cx| {
+                let len = buffer.read(cx).len();
+                multibuffer.push_excerpts(
+                    buffer,
+                    [ExcerptRange {
+                        context: 0..len,
+                        primary: None,
+                    }],
+                    cx,
+                );
+            });
+        }
+
+        let editor = cx.new(|cx| {
+            let mut editor =
+                Editor::for_multibuffer(multibuffer.clone(), Some(project), true, window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.buffer().update(cx, |multibuffer, cx| {
+                for change_set in change_sets {
+                    multibuffer.add_change_set(change_set, cx);
+                }
+            });
+            editor
+        });
+
+        Self { editor, title }
+    }
+}
+
+impl Render for CommitDiffView {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .size_full()
+            .key_context("CommitDiffView")
+            .child(self.editor.clone())
+    }
+}
+
+impl Focusable for CommitDiffView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for CommitDiffView {}
+
+impl Item for CommitDiffView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some(self.title.clone())
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(self.title.clone())
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::added_to_workspace(editor, workspace, window, cx)
+        });
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn std::any::Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: workspace::ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::{repository::RepoPath, status::StatusCode};
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use std::path::Path;
+    use workspace::AppState;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    fn diff_view_count(workspace: &Entity<Workspace>, cx: &mut VisualTestContext) -> usize {
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_pane()
+                .read(cx)
+                .items_of_type::<CommitDiffView>()
+                .count()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_stash_diff_view(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "one\nTWO\n",
+            }),
+        )
+        .await;
+        fs.with_git_state(Path::new("/dir/.git"), false, |state| {
+            state.current_branch_name = Some("main".into());
+            state
+                .head_contents
+                .insert("a.txt".into(), "one\ntwo\n".into());
+            state
+                .index_contents
+                .insert("a.txt".into(), "one\nTWO\n".into());
+            state
+                .statuses
+                .insert(RepoPath::from("a.txt"), StatusCode::Modified.index());
+        });
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        cx.run_until_parked();
+        let repository =
+            project.read_with(cx, |project, cx| project.active_repository(cx).unwrap());
+        repository
+            .stash_all(Some("Work in progress".into()))
+            .await
+            .unwrap()
+            .unwrap();
+        let entries = repository.stash_entries().await.unwrap().unwrap();
+        assert_eq!(entries.len(), 1);
+
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        workspace.update_in(cx, |workspace, window, cx| {
+            CommitDiffView::deploy_stash(workspace, &entries[0], repository.clone(), window, cx);
+        });
+        cx.run_until_parked();
+
+        let view = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<CommitDiffView>(cx).unwrap()
+        });
+        view.read_with(cx, |view, cx| {
+            assert_eq!(view.title.as_ref(), "stash@{0}: On main: Work in progress");
+            let texts = view
+                .editor
+                .read(cx)
+                .buffer()
+                .read(cx)
+                .all_buffers()
+                .into_iter()
+                .map(|buffer| buffer.read(cx).text())
+                .collect::<Vec<_>>();
+            assert_eq!(texts, ["one\nTWO\n"]);
+        });
+
+        // Once the entry is gone from the stash, it can no longer be shown.
+        repository
+            .stash_drop(entries[0].index, entries[0].oid)
+            .await
+            .unwrap()
+            .unwrap();
+        workspace.update_in(cx, |workspace, window, cx| {
+            CommitDiffView::deploy_stash(workspace, &entries[0], repository.clone(), window, cx);
+        });
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        assert_eq!(diff_view_count(&workspace, cx), 1);
+    }
+}
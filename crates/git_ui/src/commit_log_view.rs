@@ -0,0 +1,601 @@
+use crate::commit_diff_view::CommitDiffView;
+use editor::{Editor, EditorEvent};
+use git::{
+    commit::{CommitSummary, LogOptions},
+    repository::RepoPath,
+    FileHistory, OpenFileHistory, ViewCommitLog,
+};
+use gpui::{
+    uniform_list, AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, KeyContext,
+    ScrollStrategy, Subscription, Task, UniformListScrollHandle, WeakEntity,
+};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrev};
+use project::{git::RepositoryHandle, ProjectPath};
+use std::time::Duration;
+use time::{OffsetDateTime, UtcOffset};
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use workspace::{
+    item::{Item, ItemEvent},
+    Workspace,
+};
+
+/// The number of commits requested from the repository at a time.
+const PAGE_SIZE: usize = 200;
+
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(250);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(
+        |workspace: &mut Workspace, _window, _cx: &mut Context<Workspace>| {
+            workspace.register_action(|workspace, _: &ViewCommitLog, window, cx| {
+                let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+                    return;
+                };
+                CommitLogView::deploy(workspace, repository, None, window, cx);
+            });
+            workspace.register_action(|workspace, _: &FileHistory, window, cx| {
+                let Some(project_path) = workspace
+                    .active_item(cx)
+                    .and_then(|item| item.project_path(cx))
+                else {
+                    return;
+                };
+                CommitLogView::deploy_for_project_path(workspace, &project_path, window, cx);
+            });
+            workspace.register_action(|workspace, action: &OpenFileHistory, window, cx| {
+                let Some(project_path) = workspace
+                    .project()
+                    .read(cx)
+                    .find_project_path(&action.path, cx)
+                else {
+                    return;
+                };
+                CommitLogView::deploy_for_project_path(workspace, &project_path, window, cx);
+            });
+        },
+    )
+    .detach();
+}
+
+/// Lists the commits of a repository, optionally restricted to a path or an author,
+/// and opens the changes of a commit when it is confirmed.
+pub struct CommitLogView {
+    workspace: WeakEntity<Workspace>,
+    repository: RepositoryHandle,
+    path_editor: Entity<Editor>,
+    author_editor: Entity<Editor>,
+    commits: Vec<CommitSummary>,
+    selected_index: Option<usize>,
+    has_more: bool,
+    error: Option<SharedString>,
+    load_task: Option<Task<()>>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl CommitLogView {
+    /// Opens the history of the repository containing the given path, filtered to that path.
+    pub fn deploy_for_project_path(
+        workspace: &mut Workspace,
+        project_path: &ProjectPath,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some((repository, repo_path)) = workspace
+            .project()
+            .read(cx)
+            .git_state()
+            .and_then(|git_state| git_state.read(cx).repository_for_project_path(project_path))
+        else {
+            return;
+        };
+        Self::deploy(workspace, repository, Some(repo_path), window, cx);
+    }
+
+    pub fn deploy(
+        workspace: &mut Workspace,
+        repository: RepositoryHandle,
+        path: Option<RepoPath>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let workspace_handle = cx.entity().downgrade();
+        let view = cx.new(|cx| Self::new(workspace_handle, repository, path, window, cx));
+        workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, window, cx);
+        view.focus_handle(cx).focus(window);
+    }
+
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        repository: RepositoryHandle,
+        path: Option<RepoPath>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let path_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Filter by path…", cx);
+            if let Some(path) = path.filter(|path| !path.as_os_str().is_empty()) {
+                editor.set_text(path.to_string(), window, cx);
+            }
+            editor
+        });
+        let author_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Filter by author…", cx);
+            editor
+        });
+
+        let subscriptions = [&path_editor, &author_editor]
+            .into_iter()
+            .map(|editor| {
+                cx.subscribe(editor, |this, _, event: &EditorEvent, cx| {
+                    if let EditorEvent::Edited { .. } = event {
+                        cx.emit(ItemEvent::UpdateTab);
+                        this.reload(true, cx);
+                    }
+                })
+            })
+            .collect();
+
+        let mut this = Self {
+            workspace,
+            repository,
+            path_editor,
+            author_editor,
+            commits: Vec::new(),
+            selected_index: None,
+            has_more: false,
+            error: None,
+            load_task: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            _subscriptions: subscriptions,
+        };
+        this.reload(false, cx);
+        this
+    }
+
+    fn path_filter(&self, cx: &App) -> Option<RepoPath> {
+        let text = self.path_editor.read(cx).text(cx);
+        let text = text.trim().trim_matches('/');
+        (!text.is_empty()).then(|| RepoPath::from_str(text))
+    }
+
+    fn author_filter(&self, cx: &App) -> Option<String> {
+        let text = self.author_editor.read(cx).text(cx);
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    fn title(&self, cx: &App) -> SharedString {
+        match self.path_filter(cx) {
+            Some(path) => format!("History: {path}").into(),
+            None => "Commit Log".into(),
+        }
+    }
+
+    fn reload(&mut self, debounce: bool, cx: &mut Context<Self>) {
+        self.commits.clear();
+        self.selected_index = None;
+        self.has_more = false;
+        self.load_page(debounce, cx);
+    }
+
+    fn load_page(&mut self, debounce: bool, cx: &mut Context<Self>) {
+        let options = LogOptions {
+            path: self.path_filter(cx),
+            author: self.author_filter(cx),
+            skip: self.commits.len(),
+            limit: PAGE_SIZE,
+        };
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(FILTER_DEBOUNCE).await;
+            }
+            let Ok(log) = this.update(&mut cx, |this, _| this.repository.log(options)) else {
+                return;
+            };
+            let result = log
+                .await
+                .map_err(|_| anyhow::anyhow!("The log operation was canceled"))
+                .and_then(|result| result);
+            this.update(&mut cx, |this, cx| {
+                match result {
+                    Ok(commits) => {
+                        this.has_more = commits.len() == PAGE_SIZE;
+                        this.commits.extend(commits);
+                        if this.selected_index.is_none() && !this.commits.is_empty() {
+                            this.selected_index = Some(0);
+                        }
+                        this.error = None;
+                    }
+                    Err(error) => {
+                        this.has_more = false;
+                        this.error = Some(error.to_string().into());
+                    }
+                }
+                this.load_task = None;
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn load_more(&mut self, cx: &mut Context<Self>) {
+        if self.has_more && self.load_task.is_none() {
+            self.load_page(false, cx);
+        }
+    }
+
+    fn dispatch_context(&self, window: &Window) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("CommitLogView");
+        if self.focus_handle.is_focused(window) {
+            dispatch_context.add("menu");
+            dispatch_context.add("CommitList");
+        }
+        dispatch_context
+    }
+
+    fn select_index(&mut self, index: usize, cx: &mut Context<Self>) {
+        self.selected_index = Some(index);
+        self.scroll_handle
+            .scroll_to_item(index, ScrollStrategy::Center);
+        // Fetch the next page once the selection reaches the end of the list.
+        if index + 1 == self.commits.len() {
+            self.load_more(cx);
+        }
+        cx.notify();
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, _window: &mut Window, cx: &mut Context<Self>) {
+        if !self.commits.is_empty() {
+            self.select_index(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &SelectLast, _window: &mut Window, cx: &mut Context<Self>) {
+        if !self.commits.is_empty() {
+            self.select_index(self.commits.len() - 1, cx);
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _window: &mut Window, cx: &mut Context<Self>) {
+        if self.commits.is_empty() {
+            return;
+        }
+        let index = match self.selected_index {
+            Some(index) => (index + 1).min(self.commits.len() - 1),
+            None => 0,
+        };
+        self.select_index(index, cx);
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, _window: &mut Window, cx: &mut Context<Self>) {
+        if self.commits.is_empty() {
+            return;
+        }
+        let index = self
+            .selected_index
+            .map_or(0, |index| index.saturating_sub(1));
+        self.select_index(index, cx);
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(index) = self.selected_index {
+            self.open_commit(index, window, cx);
+        }
+    }
+
+    fn open_commit(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(commit) = self.commits.get(index).cloned() else {
+            return;
+        };
+        self.selected_index = Some(index);
+        let repository = self.repository.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                CommitDiffView::deploy_commit(workspace, &commit, repository, window, cx);
+            })
+            .ok();
+        cx.notify();
+    }
+
+    fn render_filters(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let filter_editor = |editor: &Entity<Editor>| {
+            h_flex()
+                .flex_1()
+                .px_2()
+                .py_1()
+                .border_1()
+                .border_color(cx.theme().colors().border)
+                .rounded_md()
+                .child(editor.clone())
+        };
+        h_flex()
+            .p_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(filter_editor(&self.path_editor))
+            .child(filter_editor(&self.author_editor))
+    }
+
+    fn render_commit(&self, index: usize, cx: &mut Context<Self>) -> AnyElement {
+        let commit = &self.commits[index];
+        let local_offset = chrono::Local::now().offset().local_minus_utc();
+        let date = OffsetDateTime::from_unix_timestamp(commit.commit_timestamp)
+            .map(|timestamp| {
+                time_format::format_localized_timestamp(
+                    timestamp,
+                    OffsetDateTime::now_utc(),
+                    UtcOffset::from_whole_seconds(local_offset).unwrap_or(UtcOffset::UTC),
+                    time_format::TimestampFormat::Relative,
+                )
+            })
+            .unwrap_or_default();
+
+        ListItem::new(("commit", index))
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(self.selected_index == Some(index))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .overflow_x_hidden()
+                    .child(
+                        Label::new(commit.sha.display_short())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_x_hidden()
+                            .child(Label::new(commit.subject.clone()).single_line()),
+                    )
+                    .child(
+                        div()
+                            .id(("commit-author", index))
+                            .child(
+                                Label::new(commit.author_name.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                                    .single_line(),
+                            )
+                            .tooltip(Tooltip::text(commit.author_email.clone())),
+                    )
+                    .child(
+                        Label::new(date)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line(),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.open_commit(index, window, cx);
+            }))
+            .into_any_element()
+    }
+
+    fn render_status(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        if let Some(error) = self.error.clone() {
+            return Some(
+                Label::new(error)
+                    .size(LabelSize::Small)
+                    .color(Color::Error)
+                    .into_any_element(),
+            );
+        }
+        if self.load_task.is_some() {
+            return Some(
+                Label::new("Loading…")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .into_any_element(),
+            );
+        }
+        if self.has_more {
+            return Some(
+                Button::new("load-more-commits", "Load More")
+                    .label_size(LabelSize::Small)
+                    .on_click(cx.listener(|this, _, _window, cx| this.load_more(cx)))
+                    .into_any_element(),
+            );
+        }
+        if self.commits.is_empty() {
+            return Some(
+                Label::new("No commits")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .into_any_element(),
+            );
+        }
+        None
+    }
+}
+
+impl Render for CommitLogView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let commit_count = self.commits.len();
+        v_flex()
+            .id("commit-log-view")
+            .key_context(self.dispatch_context(window))
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_filters(cx))
+            .child(
+                uniform_list(cx.entity().clone(), "commits", commit_count, {
+                    move |this, range, _window, cx| {
+                        range
+                            .map(|index| this.render_commit(index, cx))
+                            .collect::<Vec<_>>()
+                    }
+                })
+                .flex_1()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+            .children(
+                self.render_status(cx)
+                    .map(|status| h_flex().p_2().justify_center().child(status)),
+            )
+    }
+}
+
+impl Focusable for CommitLogView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<ItemEvent> for CommitLogView {}
+
+impl Item for CommitLogView {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::FileGit))
+    }
+
+    fn tab_content_text(&self, _window: &Window, cx: &App) -> Option<SharedString> {
+        Some(self.title(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        Some(self.title(cx))
+    }
+
+    fn to_item_events(event: &ItemEvent, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use std::path::Path;
+    use workspace::AppState;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    fn commit(sha: char, author: &str, subject: &str) -> CommitSummary {
+        CommitSummary {
+            sha: sha.to_string().repeat(40).parse().unwrap(),
+            author_name: author.to_string().into(),
+            author_email: format!("{}@example.com", author.to_lowercase()).into(),
+            commit_timestamp: 0,
+            subject: subject.to_string().into(),
+        }
+    }
+
+    fn subjects(view: &Entity<CommitLogView>, cx: &mut VisualTestContext) -> Vec<String> {
+        view.read_with(cx, |view, _| {
+            view.commits
+                .iter()
+                .map(|commit| commit.subject.to_string())
+                .collect()
+        })
+    }
+
+    fn set_filter(editor: &Entity<Editor>, text: &str, cx: &mut VisualTestContext) {
+        editor.update_in(cx, |editor, window, cx| editor.set_text(text, window, cx));
+        cx.executor().advance_clock(FILTER_DEBOUNCE);
+        cx.run_until_parked();
+    }
+
+    #[gpui::test]
+    async fn test_commit_log_filters(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "a",
+                "src": { "b.txt": "b" },
+            }),
+        )
+        .await;
+        fs.with_git_state(Path::new("/dir/.git"), false, |state| {
+            for (commit, paths) in [
+                (commit('3', "Alice", "Change b"), vec!["src/b.txt"]),
+                (
+                    commit('2', "Bob", "Change a and b"),
+                    vec!["a.txt", "src/b.txt"],
+                ),
+                (commit('1', "A.lice", "Add a"), vec!["a.txt"]),
+            ] {
+                let paths = paths.into_iter().map(RepoPath::from).collect();
+                state.commit_paths.insert(commit.sha, paths);
+                state.commits.push(commit);
+            }
+        });
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        cx.run_until_parked();
+        let repository =
+            project.read_with(cx, |project, cx| project.active_repository(cx).unwrap());
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let view = workspace.update_in(cx, |workspace, window, cx| {
+            CommitLogView::deploy(workspace, repository.clone(), None, window, cx);
+            workspace.active_item_as::<CommitLogView>(cx).unwrap()
+        });
+        cx.run_until_parked();
+        assert_eq!(subjects(&view, cx), ["Change b", "Change a and b", "Add a"]);
+        view.read_with(cx, |view, cx| {
+            assert_eq!(view.selected_index, Some(0));
+            assert_eq!(view.title(cx).to_string(), "Commit Log");
+        });
+
+        // A directory matches the commits changing any file inside it.
+        let (path_editor, author_editor) = view.read_with(cx, |view, _| {
+            (view.path_editor.clone(), view.author_editor.clone())
+        });
+        set_filter(&path_editor, "src/", cx);
+        assert_eq!(subjects(&view, cx), ["Change b", "Change a and b"]);
+        view.read_with(cx, |view, cx| {
+            assert_eq!(view.title(cx).to_string(), "History: src")
+        });
+
+        set_filter(&path_editor, "a.txt", cx);
+        assert_eq!(subjects(&view, cx), ["Change a and b", "Add a"]);
+
+        // Authors are matched literally and ignoring case.
+        set_filter(&author_editor, "a.lice", cx);
+        assert_eq!(subjects(&view, cx), ["Add a"]);
+
+        set_filter(&path_editor, "", cx);
+        set_filter(&author_editor, "ALICE", cx);
+        assert_eq!(subjects(&view, cx), ["Change b"]);
+
+        // Opening the history of a file fills in the path filter.
+        let view = workspace.update_in(cx, |workspace, window, cx| {
+            CommitLogView::deploy(workspace, repository, Some("a.txt".into()), window, cx);
+            workspace.active_item_as::<CommitLogView>(cx).unwrap()
+        });
+        cx.run_until_parked();
+        assert_eq!(subjects(&view, cx), ["Change a and b", "Add a"]);
+    }
+}
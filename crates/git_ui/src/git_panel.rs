@@ -1,25 +1,40 @@
+use crate::askpass_modal::AskPassModal;
+use crate::commit_diff_view::CommitDiffView;
 use crate::git_panel_settings::StatusStyle;
 use crate::repository_selector::RepositorySelectorPopoverMenu;
 use crate::{
     git_panel_settings::GitPanelSettings, git_status_icon, repository_selector::RepositorySelector,
 };
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use db::kvp::KEY_VALUE_STORE;
-use editor::actions::MoveToEnd;
+use editor::actions::{ExpandAllHunkDiffs, MoveToEnd};
 use editor::scroll::ScrollbarAutoHide;
 use editor::{Editor, EditorMode, EditorSettings, MultiBuffer, ShowScrollbar};
-use futures::channel::mpsc;
-use futures::StreamExt as _;
-use git::repository::RepoPath;
+use futures::channel::{mpsc, oneshot};
+use futures::future::BoxFuture;
+use futures::{FutureExt as _, StreamExt as _};
+use git::askpass::GitRemoteDelegate;
+use git::repository::{Branch, PushOptions, RemoteCommandOutput, RepoPath};
+use git::stash::StashEntry;
 use git::status::FileStatus;
-use git::{CommitAllChanges, CommitChanges, RevertAll, StageAll, ToggleStaged, UnstageAll};
+use git::{
+    CommitAllChanges, CommitChanges, Fetch, ForcePush, Oid, Pull, Push, RevertAll, StageAll,
+    StashAll, StashPop, ToggleStaged, UnstageAll, ViewCommitLog,
+};
 use gpui::*;
 use menu::{SelectFirst, SelectLast, SelectNext, SelectPrev};
 use project::git::RepositoryHandle;
 use project::{Fs, Project, ProjectPath};
 use serde::{Deserialize, Serialize};
 use settings::Settings as _;
-use std::{collections::HashSet, ops::Range, path::PathBuf, sync::Arc, time::Duration, usize};
+use std::{
+    collections::HashSet,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+    usize,
+};
 use theme::ThemeSettings;
 use ui::{
     prelude::*, ButtonLike, Checkbox, Divider, DividerColor, ElevationIndex, ListItem,
@@ -72,6 +87,31 @@ struct SerializedGitPanel {
     width: Option<Pixels>,
 }
 
+/// A fetch, pull or push that is currently running.
+struct PendingRemoteOperation {
+    label: SharedString,
+    progress: Option<SharedString>,
+}
+
+/// Forwards progress and credential prompts of a remote operation to the git panel.
+struct PanelRemoteDelegate {
+    progress: mpsc::UnboundedSender<String>,
+    ask_pass: mpsc::UnboundedSender<(String, oneshot::Sender<Option<String>>)>,
+}
+
+impl GitRemoteDelegate for PanelRemoteDelegate {
+    fn progress(&self, line: &str) {
+        self.progress.unbounded_send(line.to_string()).ok();
+    }
+
+    fn ask_pass(&self, prompt: String) -> BoxFuture<'static, Option<String>> {
+        let (sender, receiver) = oneshot::channel();
+        // If the panel is gone, the sender is dropped and the prompt is canceled.
+        self.ask_pass.unbounded_send((prompt, sender)).ok();
+        receiver.map(|response| response.ok().flatten()).boxed()
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct GitListEntry {
     depth: usize,
@@ -101,6 +141,11 @@ pub struct GitPanel {
     all_staged: Option<bool>,
     width: Option<Pixels>,
     err_sender: mpsc::Sender<anyhow::Error>,
+    current_branch: Option<Branch>,
+    pending_remote_operation: Option<PendingRemoteOperation>,
+    update_branch_task: Task<()>,
+    stash_entries: Vec<StashEntry>,
+    update_stash_task: Task<()>,
 }
 
 fn commit_message_editor(
@@ -214,6 +259,11 @@ impl GitPanel {
                 project,
                 err_sender,
                 workspace,
+                current_branch: None,
+                pending_remote_operation: None,
+                update_branch_task: Task::ready(()),
+                stash_entries: Vec::new(),
+                update_stash_task: Task::ready(()),
             };
             git_panel.schedule_update(window, cx);
             git_panel.show_scrollbar = git_panel.should_show_scrollbar(cx);
@@ -243,11 +293,25 @@ impl GitPanel {
             window,
             move |workspace, _, event: &Event, window, cx| match event.clone() {
                 Event::OpenedEntry { path } => {
-                    workspace
-                        .open_path_preview(path, None, false, false, window, cx)
-                        .detach_and_prompt_err("Failed to open file", window, cx, |e, _, _| {
-                            Some(format!("{e}"))
-                        });
+                    let open_task =
+                        workspace.open_path_preview(path, None, false, false, window, cx);
+                    // Show the file's changes expanded, so that their hunks can be staged and
+                    // unstaged right away.
+                    cx.spawn_in(window, |_, mut cx| async move {
+                        let item = open_task.await?;
+                        if let Some(editor) = item.downcast::<Editor>() {
+                            editor.update_in(&mut cx, |editor, window, cx| {
+                                editor.expand_all_diff_hunks(&ExpandAllHunkDiffs, window, cx)
+                            })?;
+                        }
+                        anyhow::Ok(())
+                    })
+                    .detach_and_prompt_err(
+                        "Failed to open file",
+                        window,
+                        cx,
+                        |e, _, _| Some(format!("{e}")),
+                    );
                 }
                 Event::Focus => { /* TODO */ }
             },
@@ -720,6 +784,8 @@ impl GitPanel {
             if let Some(this) = handle.upgrade() {
                 this.update_in(&mut cx, |this, window, cx| {
                     this.update_visible_entries(cx);
+                    this.update_current_branch(cx);
+                    this.update_stash_entries(cx);
                     let active_repository = this.active_repository.as_ref();
                     this.commit_editor =
                         cx.new(|cx| commit_message_editor(active_repository, window, cx));
@@ -787,15 +853,325 @@ impl GitPanel {
         }
         self.all_staged = all_staged;
 
-        // Sort entries by path to maintain consistent order
-        self.visible_entries
-            .sort_by(|a, b| a.repo_path.cmp(&b.repo_path));
+        // List conflicted entries first, then sort by path to maintain consistent order
+        self.visible_entries.sort_by(|a, b| {
+            b.status
+                .is_conflicted()
+                .cmp(&a.status.is_conflicted())
+                .then_with(|| a.repo_path.cmp(&b.repo_path))
+        });
 
         self.select_first_entry_if_none(cx);
 
         cx.notify();
     }
 
+    fn update_current_branch(&mut self, cx: &mut Context<Self>) {
+        let Some(repo) = self.active_repository.as_ref() else {
+            self.current_branch = None;
+            return;
+        };
+        let work_directory: &Path = &repo.repository_entry;
+        let repository_path = ProjectPath {
+            worktree_id: repo.worktree_id,
+            path: work_directory.into(),
+        };
+        let branches = self.project.read(cx).branches(repository_path, cx);
+        self.update_branch_task = cx.spawn(|this, mut cx| async move {
+            let Some(branches) = branches.await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.current_branch = branches.into_iter().find(|branch| branch.is_head);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn update_stash_entries(&mut self, cx: &mut Context<Self>) {
+        let Some(repo) = self.active_repository.as_ref() else {
+            self.stash_entries.clear();
+            return;
+        };
+        let stash_entries = repo.stash_entries();
+        self.update_stash_task = cx.spawn(|this, mut cx| async move {
+            let Some(stash_entries) = stash_entries
+                .await
+                .map_err(|_| anyhow::anyhow!("The list stash operation was canceled"))
+                .and_then(|result| result)
+                .log_err()
+            else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.stash_entries = stash_entries;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn stash_all(&mut self, _: &git::StashAll, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.as_ref() else {
+            return;
+        };
+        let result = active_repository.stash_all(None);
+        self.finish_stash_operation("stash", result, window, cx);
+    }
+
+    fn pop_latest_stash(&mut self, _: &git::StashPop, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(entry) = self.stash_entries.first() {
+            self.pop_stash(entry.index, entry.oid, window, cx);
+        }
+    }
+
+    fn apply_stash(&mut self, index: usize, oid: Oid, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.as_ref() else {
+            return;
+        };
+        let result = active_repository.stash_apply(index, oid);
+        self.finish_stash_operation("stash apply", result, window, cx);
+    }
+
+    fn pop_stash(&mut self, index: usize, oid: Oid, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.as_ref() else {
+            return;
+        };
+        let result = active_repository.stash_pop(index, oid);
+        self.finish_stash_operation("stash pop", result, window, cx);
+    }
+
+    fn drop_stash(&mut self, index: usize, oid: Oid, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.as_ref() else {
+            return;
+        };
+        let result = active_repository.stash_drop(index, oid);
+        self.finish_stash_operation("stash drop", result, window, cx);
+    }
+
+    fn show_stash(&mut self, entry: StashEntry, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                CommitDiffView::deploy_stash(workspace, &entry, active_repository, window, cx);
+            })
+            .ok();
+    }
+
+    fn finish_stash_operation(
+        &mut self,
+        operation: &'static str,
+        result: oneshot::Receiver<anyhow::Result<()>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        cx.spawn_in(window, |this, mut cx| async move {
+            let result = result
+                .await
+                .map_err(|_| anyhow::anyhow!("The {operation} operation was canceled"))
+                .and_then(|result| result);
+            this.update_in(&mut cx, |this, window, cx| {
+                if let Err(e) = result {
+                    this.show_err_toast("git stash error", e, cx);
+                }
+                this.schedule_update(window, cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn fetch(&mut self, _: &git::Fetch, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let delegate = self.start_remote_operation("Fetching", window, cx);
+        let output = active_repository.fetch(delegate);
+        self.finish_remote_operation("fetch", output, window, cx);
+    }
+
+    fn pull(&mut self, _: &git::Pull, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let Some(branch) = self.current_branch.as_ref() else {
+            return;
+        };
+        let Some((remote_name, upstream_branch_name)) =
+            branch.upstream.as_ref().and_then(|upstream| {
+                let remote_name = upstream.remote_name()?;
+                let branch_name = upstream
+                    .ref_name
+                    .strip_prefix(&format!("refs/remotes/{remote_name}/"))?;
+                Some((
+                    SharedString::from(remote_name.to_string()),
+                    SharedString::from(branch_name.to_string()),
+                ))
+            })
+        else {
+            self.show_err_toast(
+                "git pull error",
+                anyhow::anyhow!("Branch {} has no upstream to pull from", branch.name),
+                cx,
+            );
+            return;
+        };
+        let delegate = self.start_remote_operation("Pulling", window, cx);
+        let output = active_repository.pull(upstream_branch_name, remote_name, delegate);
+        self.finish_remote_operation("pull", output, window, cx);
+    }
+
+    fn push(&mut self, force: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let Some(branch) = self.current_branch.clone() else {
+            return;
+        };
+        let upstream_remote = branch
+            .upstream
+            .as_ref()
+            .and_then(|upstream| upstream.remote_name())
+            .map(|remote_name| SharedString::from(remote_name.to_string()));
+        let remotes = active_repository.get_remotes(Some(branch.name.clone()));
+        cx.spawn_in(window, |this, mut cx| async move {
+            let (remote_name, options) = match upstream_remote {
+                Some(remote_name) => (remote_name, force.then_some(PushOptions::Force)),
+                None => {
+                    let remotes = remotes.await??;
+                    // Prefer the conventional remote name when the branch has no upstream yet.
+                    let remote = remotes
+                        .iter()
+                        .find(|remote| remote.name.as_ref() == "origin")
+                        .or_else(|| remotes.first())
+                        .context("Repository has no remotes to push to")?;
+                    let options = if force {
+                        PushOptions::ForceSetUpstream
+                    } else {
+                        PushOptions::SetUpstream
+                    };
+                    (remote.name.clone(), Some(options))
+                }
+            };
+            this.update_in(&mut cx, |this, window, cx| {
+                let delegate = this.start_remote_operation("Pushing", window, cx);
+                let output =
+                    active_repository.push(branch.name.clone(), remote_name, options, delegate);
+                this.finish_remote_operation("push", output, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to push", window, cx, |e, _, _| Some(format!("{e}")));
+    }
+
+    /// Shows the operation as pending and returns a delegate that reports its progress to
+    /// the panel and asks for credentials through a modal.
+    fn start_remote_operation(
+        &mut self,
+        label: &'static str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Arc<dyn GitRemoteDelegate> {
+        let (progress_sender, mut progress_receiver) = mpsc::unbounded();
+        let (ask_pass_sender, mut ask_pass_receiver) = mpsc::unbounded();
+        self.pending_remote_operation = Some(PendingRemoteOperation {
+            label: label.into(),
+            progress: None,
+        });
+        cx.notify();
+
+        cx.spawn(|this, mut cx| async move {
+            while let Some(line) = progress_receiver.next().await {
+                let updated = this.update(&mut cx, |this, cx| {
+                    if let Some(operation) = this.pending_remote_operation.as_mut() {
+                        operation.progress = Some(line.into());
+                        cx.notify();
+                    }
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, |_, mut cx| async move {
+            while let Some((prompt, response)) = ask_pass_receiver.next().await {
+                let opened = workspace.update_in(&mut cx, |workspace, window, cx| {
+                    workspace.toggle_modal(window, cx, |window, cx| {
+                        AskPassModal::new(prompt, response, window, cx)
+                    });
+                });
+                if opened.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+
+        Arc::new(PanelRemoteDelegate {
+            progress: progress_sender,
+            ask_pass: ask_pass_sender,
+        })
+    }
+
+    fn finish_remote_operation(
+        &mut self,
+        operation: &'static str,
+        output: oneshot::Receiver<anyhow::Result<RemoteCommandOutput>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        cx.spawn_in(window, |this, mut cx| async move {
+            let result = output
+                .await
+                .map_err(|_| anyhow::anyhow!("The {operation} operation was canceled"))
+                .and_then(|result| result);
+            this.update_in(&mut cx, |this, window, cx| {
+                this.pending_remote_operation = None;
+                match result {
+                    Ok(output) => this.show_remote_output(operation, output, cx),
+                    Err(e) => this.show_err_toast("git remote operation error", e, cx),
+                }
+                this.schedule_update(window, cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn show_remote_output(
+        &self,
+        operation: &'static str,
+        output: RemoteCommandOutput,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        // Git writes its summary to stderr; the last line is the most informative one.
+        let summary = output
+            .stderr
+            .lines()
+            .chain(output.stdout.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .last()
+            .map(|line| format!("git {operation}: {line}"))
+            .unwrap_or_else(|| format!("git {operation} completed"));
+        workspace.update(cx, |workspace, cx| {
+            let toast = Toast::new(
+                NotificationId::Named("git remote operation".into()),
+                summary,
+            )
+            .autohide();
+            workspace.show_toast(toast, cx);
+        });
+    }
+
     fn show_err_toast(&self, id: &'static str, e: anyhow::Error, cx: &mut Context<Self>) {
         let Some(workspace) = self.workspace.upgrade() else {
             return;
@@ -878,43 +1254,186 @@ impl GitPanel {
                 self.render_repository_selector(cx).into_any_element()
             }))
             .child(div().flex_grow())
-            .child(h_flex().gap_2().child(if self.all_staged.unwrap_or(false) {
-                self.panel_button("unstage-all", "Unstage All")
+            .child(self.render_remote_controls(cx))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .when(self.active_repository.is_some(), |this| {
+                        this.child(
+                            self.panel_button("view-commit-log", "History")
+                                .tooltip({
+                                    let focus_handle = focus_handle.clone();
+                                    move |window, cx| {
+                                        Tooltip::for_action_in(
+                                            "View commit history",
+                                            &ViewCommitLog,
+                                            &focus_handle,
+                                            window,
+                                            cx,
+                                        )
+                                    }
+                                })
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(ViewCommitLog.boxed_clone(), cx)
+                                }),
+                        )
+                    })
+                    .when(entry_count > 0, |this| {
+                        this.child(
+                            self.panel_button("stash-all", "Stash")
+                                .tooltip({
+                                    let focus_handle = focus_handle.clone();
+                                    move |window, cx| {
+                                        Tooltip::for_action_in(
+                                            "Stash all changes",
+                                            &StashAll,
+                                            &focus_handle,
+                                            window,
+                                            cx,
+                                        )
+                                    }
+                                })
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.stash_all(&StashAll, window, cx)
+                                })),
+                        )
+                    })
+                    .child(if self.all_staged.unwrap_or(false) {
+                        self.panel_button("unstage-all", "Unstage All")
+                            .tooltip({
+                                let focus_handle = focus_handle.clone();
+                                move |window, cx| {
+                                    Tooltip::for_action_in(
+                                        "Unstage all changes",
+                                        &UnstageAll,
+                                        &focus_handle,
+                                        window,
+                                        cx,
+                                    )
+                                }
+                            })
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.unstage_all(&UnstageAll, window, cx)
+                            }))
+                    } else {
+                        self.panel_button("stage-all", "Stage All")
+                            .tooltip({
+                                let focus_handle = focus_handle.clone();
+                                move |window, cx| {
+                                    Tooltip::for_action_in(
+                                        "Stage all changes",
+                                        &StageAll,
+                                        &focus_handle,
+                                        window,
+                                        cx,
+                                    )
+                                }
+                            })
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.stage_all(&StageAll, window, cx)
+                            }))
+                    }),
+            )
+    }
+
+    fn render_remote_controls(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        let Some(branch) = self.current_branch.as_ref() else {
+            return h_flex().into_any_element();
+        };
+
+        if let Some(operation) = self.pending_remote_operation.as_ref() {
+            let label = match operation.progress.as_ref() {
+                Some(progress) => format!("{}: {progress}", operation.label),
+                None => format!("{}…", operation.label),
+            };
+            return h_flex()
+                .id("pending-remote-operation")
+                .gap_1()
+                .max_w_48()
+                .overflow_x_hidden()
+                .mr_2()
+                .child(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    Label::new(label)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .single_line(),
+                )
+                .into_any_element();
+        }
+
+        let tracking = branch.tracking_status().unwrap_or_default();
+        let has_upstream = branch.upstream.is_some();
+
+        h_flex()
+            .gap_1()
+            .mr_2()
+            .child(
+                Label::new(branch.name.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .single_line(),
+            )
+            .when(tracking.ahead > 0, |this| {
+                this.child(
+                    Label::new(format!("↑{}", tracking.ahead))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .when(tracking.behind > 0, |this| {
+                this.child(
+                    Label::new(format!("↓{}", tracking.behind))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .child(
+                IconButton::new("git-fetch", IconName::ArrowCircle)
+                    .icon_size(IconSize::Small)
                     .tooltip({
                         let focus_handle = focus_handle.clone();
                         move |window, cx| {
-                            Tooltip::for_action_in(
-                                "Unstage all changes",
-                                &UnstageAll,
-                                &focus_handle,
-                                window,
-                                cx,
-                            )
+                            Tooltip::for_action_in("Fetch", &Fetch, &focus_handle, window, cx)
                         }
                     })
-                    .on_click(cx.listener(move |this, _, window, cx| {
-                        this.unstage_all(&UnstageAll, window, cx)
-                    }))
-            } else {
-                self.panel_button("stage-all", "Stage All")
+                    .on_click(cx.listener(|this, _, window, cx| this.fetch(&Fetch, window, cx))),
+            )
+            .when(has_upstream, |this| {
+                this.child(
+                    IconButton::new("git-pull", IconName::ArrowDown)
+                        .icon_size(IconSize::Small)
+                        .tooltip({
+                            let focus_handle = focus_handle.clone();
+                            move |window, cx| {
+                                Tooltip::for_action_in("Pull", &Pull, &focus_handle, window, cx)
+                            }
+                        })
+                        .on_click(cx.listener(|this, _, window, cx| this.pull(&Pull, window, cx))),
+                )
+            })
+            .child(
+                IconButton::new("git-push", IconName::ArrowUp)
+                    .icon_size(IconSize::Small)
                     .tooltip({
                         let focus_handle = focus_handle.clone();
+                        let label = if has_upstream {
+                            "Push"
+                        } else {
+                            "Publish Branch"
+                        };
                         move |window, cx| {
-                            Tooltip::for_action_in(
-                                "Stage all changes",
-                                &StageAll,
-                                &focus_handle,
-                                window,
-                                cx,
-                            )
+                            Tooltip::for_action_in(label, &Push, &focus_handle, window, cx)
                         }
                     })
-                    .on_click(
-                        cx.listener(move |this, _, window, cx| {
-                            this.stage_all(&StageAll, window, cx)
-                        }),
-                    )
-            }))
+                    .on_click(cx.listener(|this, _, window, cx| this.push(false, window, cx))),
+            )
+            .into_any_element()
     }
 
     pub fn render_repository_selector(&self, cx: &mut Context<Self>) -> impl IntoElement {
@@ -1136,6 +1655,77 @@ impl GitPanel {
             .children(self.render_scrollbar(cx))
     }
 
+    fn render_stash_entries(
+        &self,
+        has_write_access: bool,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .id("stash-entries")
+            .max_h_32()
+            .overflow_y_scroll()
+            .child(
+                h_flex().px_2().child(
+                    Label::new(format!("Stashes ({})", self.stash_entries.len()))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .children(self.stash_entries.iter().map(|entry| {
+                let index = entry.index;
+                let oid = entry.oid;
+                ListItem::new(("stash-entry", index))
+                    .spacing(ListItemSpacing::Sparse)
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .overflow_x_hidden()
+                            .child(
+                                Label::new(entry.revision())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(
+                                Label::new(entry.message.clone())
+                                    .size(LabelSize::Small)
+                                    .single_line(),
+                            ),
+                    )
+                    .on_click({
+                        let entry = entry.clone();
+                        cx.listener(move |this, _, window, cx| {
+                            this.show_stash(entry.clone(), window, cx)
+                        })
+                    })
+                    .end_slot(h_flex().when(has_write_access, |this| {
+                        this.child(
+                            IconButton::new(("apply-stash", index), IconName::Check)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Apply Stash"))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.apply_stash(index, oid, window, cx)
+                                })),
+                        )
+                        .child(
+                            IconButton::new(("pop-stash", index), IconName::ArrowUpFromLine)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Pop Stash"))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.pop_stash(index, oid, window, cx)
+                                })),
+                        )
+                        .child(
+                            IconButton::new(("drop-stash", index), IconName::Trash)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Drop Stash"))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.drop_stash(index, oid, window, cx)
+                                })),
+                        )
+                    }))
+            }))
+    }
+
     fn entry_label(&self, label: impl Into<SharedString>, color: Color) -> Label {
         Label::new(label.into()).color(color).single_line()
     }
@@ -1152,6 +1742,10 @@ impl GitPanel {
         let status_style = GitPanelSettings::get_global(cx).status_style;
         let status = entry_details.status;
         let has_conflict = status.is_conflicted();
+        let unmerged_status = match status {
+            FileStatus::Unmerged(unmerged_status) => Some(unmerged_status),
+            _ => None,
+        };
         let is_modified = status.is_modified();
         let is_deleted = status.is_deleted();
 
@@ -1259,7 +1853,16 @@ impl GitPanel {
                         .child(
                             self.entry_label(entry_details.display_name.clone(), label_color)
                                 .when(status.is_deleted(), |this| this.strikethrough(true)),
-                        ),
+                        )
+                        .when_some(unmerged_status, |this, unmerged_status| {
+                            this.child(
+                                div().ml_2().child(
+                                    Label::new(unmerged_status.description())
+                                        .size(LabelSize::Small)
+                                        .color(Color::Conflict),
+                                ),
+                            )
+                        }),
                 ),
         )
     }
@@ -1336,6 +1939,12 @@ impl Render for GitPanel {
                 .on_action(cx.listener(|this, &RevertAll, window, cx| {
                     this.discard_all(&RevertAll, window, cx)
                 }))
+                .on_action(cx.listener(Self::fetch))
+                .on_action(cx.listener(Self::pull))
+                .on_action(cx.listener(|this, &Push, window, cx| this.push(false, window, cx)))
+                .on_action(cx.listener(|this, &ForcePush, window, cx| this.push(true, window, cx)))
+                .on_action(cx.listener(Self::stash_all))
+                .on_action(cx.listener(Self::pop_latest_stash))
                 .when(can_commit, |git_panel| {
                     git_panel
                         .on_action({
@@ -1397,6 +2006,10 @@ impl Render for GitPanel {
             } else {
                 self.render_empty_state(cx).into_any_element()
             })
+            .when(!self.stash_entries.is_empty(), |this| {
+                this.child(self.render_divider(cx))
+                    .child(self.render_stash_entries(has_write_access, cx))
+            })
             .child(self.render_divider(cx))
             .child(self.render_commit_editor(name_and_email, can_commit, cx))
     }
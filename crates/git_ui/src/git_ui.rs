@@ -4,12 +4,16 @@ use git_panel_settings::GitPanelSettings;
 use gpui::App;
 use ui::{ActiveTheme, Color, Icon, IconName, IntoElement};
 
+mod askpass_modal;
+mod commit_diff_view;
+mod commit_log_view;
 pub mod git_panel;
 mod git_panel_settings;
 pub mod repository_selector;
 
 pub fn init(cx: &mut App) {
     GitPanelSettings::register(cx);
+    commit_log_view::init(cx);
 }
 
 // TODO: Add updated status colors to theme
@@ -6,7 +6,7 @@ pub use crate::{
 };
 use crate::{
     diagnostic_set::{DiagnosticEntry, DiagnosticGroup},
-    language_settings::{language_settings, LanguageSettings},
+    language_settings::{language_settings, LanguageSettings, LanguageSettingsOverrides},
     markdown::parse_markdown,
     outline::OutlineItem,
     syntax_map::{
@@ -115,6 +115,7 @@ pub struct Buffer {
     parsing_in_background: bool,
     parse_status: (watch::Sender<ParseStatus>, watch::Receiver<ParseStatus>),
     non_text_state_update_count: usize,
+    settings_overrides: LanguageSettingsOverrides,
     diagnostics: SmallVec<[(LanguageServerId, DiagnosticSet); 2]>,
     remote_selections: TreeMap<ReplicaId, SelectionSet>,
     diagnostics_timestamp: clock::Lamport,
@@ -150,6 +151,7 @@ pub struct BufferSnapshot {
     diagnostics: SmallVec<[(LanguageServerId, DiagnosticSet); 2]>,
     remote_selections: TreeMap<ReplicaId, SelectionSet>,
     language: Option<Arc<Language>>,
+    settings_overrides: LanguageSettingsOverrides,
     non_text_state_update_count: usize,
 }
 
@@ -950,6 +952,7 @@ impl Buffer {
             syntax_map,
             parsing_in_background: false,
             non_text_state_update_count: 0,
+            settings_overrides: Default::default(),
             sync_parse_timeout: Duration::from_millis(1),
             parse_status: async_watch::channel(ParseStatus::Idle),
             autoindent_requests: Default::default(),
@@ -992,6 +995,7 @@ impl Buffer {
                 diagnostics: Default::default(),
                 remote_selections: Default::default(),
                 language,
+                settings_overrides: Default::default(),
                 non_text_state_update_count: 0,
             }
         }
@@ -1012,6 +1016,7 @@ impl Buffer {
             remote_selections: self.remote_selections.clone(),
             diagnostics: self.diagnostics.clone(),
             language: self.language.clone(),
+            settings_overrides: self.settings_overrides,
             non_text_state_update_count: self.non_text_state_update_count,
         }
     }
@@ -1192,6 +1197,37 @@ impl Buffer {
         cx.emit(BufferEvent::LanguageChanged);
     }
 
+    /// Returns the language settings that are overridden for this buffer.
+    pub fn settings_overrides(&self) -> &LanguageSettingsOverrides {
+        &self.settings_overrides
+    }
+
+    /// Overrides some of the language settings for this buffer, regardless of its file.
+    pub fn set_settings_overrides(
+        &mut self,
+        overrides: LanguageSettingsOverrides,
+        cx: &mut Context<Self>,
+    ) {
+        if overrides != self.settings_overrides {
+            self.settings_overrides = overrides;
+            self.non_text_state_update_count += 1;
+            cx.notify();
+        }
+    }
+
+    /// Returns the settings for the language at the given location.
+    pub fn settings_at<'a, D: ToOffset>(
+        &'a self,
+        position: D,
+        cx: &'a App,
+    ) -> Cow<'a, LanguageSettings> {
+        self.settings_overrides.apply(language_settings(
+            self.language_at(position).map(|l| l.name()),
+            self.file.as_ref(),
+            cx,
+        ))
+    }
+
     /// Assign a language registry to the buffer. This allows the buffer to retrieve
     /// other languages if parts of the buffer are written in different languages.
     pub fn set_language_registry(&self, language_registry: Arc<LanguageRegistry>) {
@@ -2718,15 +2754,11 @@ impl BufferSnapshot {
     /// Returns [`IndentSize`] for a given position that respects user settings
     /// and language preferences.
     pub fn language_indent_size_at<T: ToOffset>(&self, position: T, cx: &App) -> IndentSize {
-        let settings = language_settings(
-            self.language_at(position).map(|l| l.name()),
-            self.file(),
-            cx,
-        );
+        let settings = self.settings_at(position, cx);
         if settings.hard_tabs {
             IndentSize::tab()
         } else {
-            IndentSize::spaces(settings.tab_size.get())
+            IndentSize::spaces(settings.indent_size().get())
         }
     }
 
@@ -3074,11 +3106,11 @@ impl BufferSnapshot {
         position: D,
         cx: &'a App,
     ) -> Cow<'a, LanguageSettings> {
-        language_settings(
+        self.settings_overrides.apply(language_settings(
             self.language_at(position).map(|l| l.name()),
             self.file.as_ref(),
             cx,
-        )
+        ))
     }
 
     pub fn char_classifier_at<T: ToOffset>(&self, point: T) -> CharClassifier {
@@ -4087,6 +4119,7 @@ impl Clone for BufferSnapshot {
             remote_selections: self.remote_selections.clone(),
             diagnostics: self.diagnostics.clone(),
             language: self.language.clone(),
+            settings_overrides: self.settings_overrides,
             non_text_state_update_count: self.non_text_state_update_count,
         }
     }
@@ -67,6 +67,40 @@ fn test_line_endings(cx: &mut gpui::App) {
     });
 }
 
+#[gpui::test]
+fn test_anchor_offset_edits(cx: &mut App) {
+    cx.new(|cx| {
+        let mut buffer = Buffer::local("one\ntwö\nthree", cx);
+        let snapshot = buffer.snapshot();
+
+        // edits are applied in order of their ranges, even if they were
+        // given out of order and the buffer has changed since.
+        let edits = anchor_offset_edits(
+            &snapshot,
+            vec![(9..14, "THREE".into()), (0..3, "ONE".into())],
+        )
+        .unwrap();
+        buffer.edit([(4..4, "zero ")], None, cx);
+        buffer.edit(edits, None, cx);
+        assert_eq!(buffer.text(), "ONE\nzero twö\nTHREE");
+
+        // overlapping edits
+        assert!(
+            anchor_offset_edits(&snapshot, vec![(0..3, "a".into()), (2..5, "b".into())]).is_err()
+        );
+        // reversed ranges, ranges past the end and ranges inside a character
+        assert!(anchor_offset_edits(&snapshot, vec![(3..2, "a".into())]).is_err());
+        assert!(anchor_offset_edits(&snapshot, vec![(10..20, "a".into())]).is_err());
+        assert!(anchor_offset_edits(&snapshot, vec![(6..7, "o".into())]).is_err());
+        // adjacent edits don't overlap
+        assert!(
+            anchor_offset_edits(&snapshot, vec![(0..3, "a".into()), (3..4, "b".into())]).is_ok()
+        );
+
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_select_language(cx: &mut App) {
     init_settings(cx, |_| {});
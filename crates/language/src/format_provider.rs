@@ -0,0 +1,63 @@
+//! Provides support for formatters that run in-process rather than as a
+//! language server or an external command, such as the ones provided by extensions.
+
+use std::{ops::Range, path::Path, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use text::{Anchor, Bias};
+
+use crate::{BufferSnapshot, LanguageName};
+
+/// A request to format the text of a buffer.
+///
+/// All ranges are byte offsets into the buffer's text.
+#[derive(Clone, Debug)]
+pub struct FormatRequest {
+    pub text: String,
+    /// The path of the buffer relative to its worktree, if it has one.
+    pub path: Option<Arc<Path>>,
+    pub language_name: Option<LanguageName>,
+    /// The ranges of the text to format, or `None` to format the whole buffer.
+    pub ranges: Option<Vec<Range<usize>>>,
+}
+
+#[async_trait]
+pub trait FormatProvider: Send + Sync {
+    /// The languages this formatter can format. It can format any language when this is empty.
+    fn languages(&self) -> Vec<LanguageName>;
+
+    /// Returns the edits that format the requested text, as non-overlapping
+    /// ranges into the text of the request and their replacements.
+    async fn format(&self, request: FormatRequest) -> Result<Vec<(Range<usize>, String)>>;
+}
+
+/// Converts edits given as byte ranges into the text of the snapshot, such as the ones
+/// returned by a [`FormatProvider`], to anchored edits that still apply after the buffer changes.
+///
+/// Fails if the ranges overlap or don't lie on character boundaries within the text.
+pub fn anchor_offset_edits(
+    snapshot: &BufferSnapshot,
+    mut edits: Vec<(Range<usize>, String)>,
+) -> Result<Vec<(Range<Anchor>, String)>> {
+    edits.sort_by_key(|(range, _)| (range.start, range.end));
+    let mut previous_end = 0;
+    edits
+        .into_iter()
+        .map(|(range, new_text)| {
+            if range.start < previous_end
+                || range.start > range.end
+                || range.end > snapshot.len()
+                || snapshot.clip_offset(range.start, Bias::Left) != range.start
+                || snapshot.clip_offset(range.end, Bias::Left) != range.end
+            {
+                return Err(anyhow!("invalid edit range {range:?}"));
+            }
+            previous_end = range.end;
+            Ok((
+                snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end),
+                new_text,
+            ))
+        })
+        .collect()
+}
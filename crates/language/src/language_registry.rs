@@ -3,8 +3,8 @@ use crate::{
         all_language_settings, AllLanguageSettingsContent, LanguageSettingsContent,
     },
     task_context::ContextProvider,
-    with_parser, CachedLspAdapter, File, Language, LanguageConfig, LanguageId, LanguageMatcher,
-    LanguageServerName, LspAdapter, ToolchainLister, PLAIN_TEXT,
+    with_parser, CachedLspAdapter, File, FormatProvider, Language, LanguageConfig, LanguageId,
+    LanguageMatcher, LanguageServerName, LspAdapter, ToolchainLister, PLAIN_TEXT,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::{hash_map, HashMap, HashSet};
@@ -110,6 +110,7 @@ struct LanguageRegistryState {
     lsp_adapters: HashMap<LanguageName, Vec<Arc<CachedLspAdapter>>>,
     available_lsp_adapters:
         HashMap<LanguageServerName, Arc<dyn Fn() -> Arc<CachedLspAdapter> + 'static + Send + Sync>>,
+    format_providers: HashMap<Arc<str>, Arc<dyn FormatProvider>>,
     loading_languages: HashMap<LanguageId, Vec<oneshot::Sender<Result<Arc<Language>>>>>,
     subscription: (watch::Sender<()>, watch::Receiver<()>),
     theme: Option<Arc<Theme>>,
@@ -235,6 +236,7 @@ impl LanguageRegistry {
                 loading_languages: Default::default(),
                 lsp_adapters: Default::default(),
                 available_lsp_adapters: HashMap::default(),
+                format_providers: HashMap::default(),
                 subscription: watch::channel(),
                 theme: Default::default(),
                 version: 0,
@@ -892,6 +894,19 @@ impl LanguageRegistry {
             .unwrap_or_default()
     }
 
+    /// Registers a formatter that can be selected in the `formatter` setting by the given ID.
+    pub fn register_format_provider(&self, id: Arc<str>, provider: Arc<dyn FormatProvider>) {
+        self.state.write().format_providers.insert(id, provider);
+    }
+
+    pub fn remove_format_provider(&self, id: &str) {
+        self.state.write().format_providers.remove(id);
+    }
+
+    pub fn format_provider(&self, id: &str) -> Option<Arc<dyn FormatProvider>> {
+        self.state.read().format_providers.get(id).cloned()
+    }
+
     pub fn update_lsp_status(
         &self,
         server_name: LanguageServerName,
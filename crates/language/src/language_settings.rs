@@ -74,6 +74,12 @@ pub struct LanguageSettings {
     /// Whether to indent lines using tab characters, as opposed to multiple
     /// spaces.
     pub hard_tabs: bool,
+    /// How many columns to indent lines by, when it differs from `tab_size`.
+    ///
+    /// This isn't read from the settings files, and is only set by
+    /// [`LanguageSettingsOverrides`].
+    #[serde(skip)]
+    pub indent_size: Option<NonZeroU32>,
     /// How to soft-wrap long lines of text.
     pub soft_wrap: SoftWrap,
     /// The column at which to soft-wrap lines, for buffers where soft-wrap
@@ -136,6 +142,15 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to highlight code with semantic tokens from language servers,
+    /// on top of the syntax highlighting.
+    pub semantic_tokens: bool,
+    /// Whether to show code lenses from language servers above the lines they refer to.
+    pub code_lens: bool,
+    /// Whether to fold using the ranges reported by language servers, in addition to indentation.
+    pub use_lsp_folding_ranges: bool,
+    /// Which provider expands and shrinks selections to syntax nodes.
+    pub selection_ranges: SelectionRangesProvider,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
     /// Whether to pop the completions menu while typing in an editor without
@@ -150,6 +165,11 @@ impl LanguageSettings {
     /// A token representing the rest of the available language servers.
     const REST_OF_LANGUAGE_SERVERS: &'static str = "...";
 
+    /// Returns how many columns a level of indentation occupies.
+    pub fn indent_size(&self) -> NonZeroU32 {
+        self.indent_size.unwrap_or(self.tab_size)
+    }
+
     /// Returns the customized list of language servers from the list of
     /// available language servers.
     pub fn customized_language_servers(
@@ -195,6 +215,53 @@ impl LanguageSettings {
     }
 }
 
+/// Language settings that are overridden for a single buffer, on top of the
+/// settings that apply to its file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LanguageSettingsOverrides {
+    /// How many columns a tab should occupy.
+    pub tab_size: Option<NonZeroU32>,
+    /// How many columns to indent lines by.
+    pub indent_size: Option<NonZeroU32>,
+    /// Whether to indent lines using tab characters.
+    pub hard_tabs: Option<bool>,
+    /// Whether to show tabs and spaces in the editor.
+    pub show_whitespaces: Option<ShowWhitespaceSetting>,
+}
+
+impl LanguageSettingsOverrides {
+    /// Returns these overrides, falling back to `other` for the ones that aren't set.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            tab_size: self.tab_size.or(other.tab_size),
+            indent_size: self.indent_size.or(other.indent_size),
+            hard_tabs: self.hard_tabs.or(other.hard_tabs),
+            show_whitespaces: self.show_whitespaces.or(other.show_whitespaces),
+        }
+    }
+
+    /// Returns the given settings with these overrides applied.
+    pub fn apply<'a>(&self, settings: Cow<'a, LanguageSettings>) -> Cow<'a, LanguageSettings> {
+        if *self == Self::default() {
+            return settings;
+        }
+        let mut settings = settings.into_owned();
+        if let Some(tab_size) = self.tab_size {
+            settings.tab_size = tab_size;
+        }
+        if let Some(indent_size) = self.indent_size {
+            settings.indent_size = Some(indent_size);
+        }
+        if let Some(hard_tabs) = self.hard_tabs {
+            settings.hard_tabs = hard_tabs;
+        }
+        if let Some(show_whitespaces) = self.show_whitespaces {
+            settings.show_whitespaces = show_whitespaces;
+        }
+        Cow::Owned(settings)
+    }
+}
+
 /// The provider that supplies inline completions.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -380,6 +447,26 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to highlight code with semantic tokens from language servers that support them,
+    /// on top of the syntax highlighting. Tokens are styled with the `semantic.*` syntax keys
+    /// of the theme, falling back to the styles of the matching syntax highlights.
+    ///
+    /// Default: false
+    pub semantic_tokens: Option<bool>,
+    /// Whether to show code lenses (such as reference counts or "Run test" buttons) from
+    /// language servers above the lines they refer to.
+    ///
+    /// Default: false
+    pub code_lens: Option<bool>,
+    /// Whether to fold using the folding ranges reported by language servers, including
+    /// comment, import and region ranges, before falling back to indentation.
+    ///
+    /// Default: false
+    pub use_lsp_folding_ranges: Option<bool>,
+    /// Which provider the `editor::SelectLargerSyntaxNode` action uses to expand selections.
+    ///
+    /// Default: tree_sitter
+    pub selection_ranges: Option<SelectionRangesProvider>,
     /// Whether indentation of pasted content should be adjusted based on the context.
     ///
     /// Default: true
@@ -549,6 +636,18 @@ impl<'de> Deserialize<'de> for FormatOnSave {
     }
 }
 
+/// Controls what expands selections to larger syntax nodes.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionRangesProvider {
+    /// Expand to the enclosing tree-sitter syntax node.
+    #[default]
+    TreeSitter,
+    /// Expand to the enclosing selection range reported by the language server, falling back
+    /// to tree-sitter when the server reports none.
+    LanguageServer,
+}
+
 /// Controls how whitespace should be displayedin the editor.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -708,6 +807,12 @@ pub enum Formatter {
     },
     /// Files should be formatted using code actions executed by language servers.
     CodeActions(HashMap<String, bool>),
+    /// Format code using a formatter provided by an extension.
+    Extension {
+        /// The ID of the extension and the ID of the formatter, separated by a colon
+        /// (e.g. `my-extension:my-formatter`).
+        name: Arc<str>,
+    },
 }
 
 /// The settings for indent guides.
@@ -1166,6 +1271,13 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
+    merge(&mut settings.code_lens, src.code_lens);
+    merge(
+        &mut settings.use_lsp_folding_ranges,
+        src.use_lsp_folding_ranges,
+    );
+    merge(&mut settings.selection_ranges, src.selection_ranges);
     merge(&mut settings.tasks, src.tasks.clone());
 
     merge(
@@ -1273,6 +1385,17 @@ mod tests {
                 .into()
             )))
         );
+        let raw = "{\"formatter\": {\"extension\": {\"name\": \"my-extension:my-formatter\"}}}";
+        let settings: LanguageSettingsContent = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            settings.formatter,
+            Some(SelectedFormatter::List(FormatterList(
+                Formatter::Extension {
+                    name: "my-extension:my-formatter".into()
+                }
+                .into()
+            )))
+        );
     }
 
     #[test]
@@ -0,0 +1,69 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use extension::{Extension, ExtensionFormatterProxy};
+use language::{FormatProvider, FormatRequest, LanguageName};
+
+use crate::LanguageServerRegistryProxy;
+
+/// Returns the ID that the formatter is selected by in the `formatter` setting.
+fn format_provider_id(extension_id: &str, formatter_id: &str) -> String {
+    format!("{extension_id}:{formatter_id}")
+}
+
+impl ExtensionFormatterProxy for LanguageServerRegistryProxy {
+    fn register_formatter(&self, extension: Arc<dyn Extension>, formatter_id: Arc<str>) {
+        let id = format_provider_id(&extension.manifest().id, &formatter_id);
+        self.language_registry.register_format_provider(
+            id.into(),
+            Arc::new(ExtensionFormatProvider {
+                extension,
+                formatter_id,
+            }),
+        );
+    }
+
+    fn remove_formatter(&self, extension_id: &Arc<str>, formatter_id: &Arc<str>) {
+        self.language_registry
+            .remove_format_provider(&format_provider_id(extension_id, formatter_id));
+    }
+}
+
+struct ExtensionFormatProvider {
+    extension: Arc<dyn Extension>,
+    formatter_id: Arc<str>,
+}
+
+#[async_trait]
+impl FormatProvider for ExtensionFormatProvider {
+    fn languages(&self) -> Vec<LanguageName> {
+        self.extension
+            .manifest()
+            .formatters
+            .get(&self.formatter_id)
+            .map(|formatter| formatter.languages.clone())
+            .unwrap_or_default()
+    }
+
+    async fn format(&self, request: FormatRequest) -> Result<Vec<(Range<usize>, String)>> {
+        let edits = self
+            .extension
+            .format_buffer(
+                self.formatter_id.clone(),
+                extension::FormatRequest {
+                    text: request.text,
+                    path: request.path.map(|path| path.to_string_lossy().into_owned()),
+                    language_name: request.language_name.map(|name| name.to_string()),
+                    ranges: request.ranges,
+                },
+            )
+            .await?;
+
+        Ok(edits
+            .into_iter()
+            .map(|edit| (edit.range, edit.new_text))
+            .collect())
+    }
+}
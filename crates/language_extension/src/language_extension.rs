@@ -1,3 +1,4 @@
+mod extension_format_provider;
 mod extension_lsp_adapter;
 
 use std::path::PathBuf;
@@ -7,6 +8,8 @@ use anyhow::Result;
 use extension::{ExtensionGrammarProxy, ExtensionHostProxy, ExtensionLanguageProxy};
 use language::{LanguageMatcher, LanguageName, LanguageRegistry, LoadedLanguage};
 
+pub use extension_lsp_adapter::WorktreeDelegateAdapter;
+
 pub fn init(
     extension_host_proxy: Arc<ExtensionHostProxy>,
     language_registry: Arc<LanguageRegistry>,
@@ -14,7 +17,8 @@ pub fn init(
     let language_server_registry_proxy = LanguageServerRegistryProxy { language_registry };
     extension_host_proxy.register_grammar_proxy(language_server_registry_proxy.clone());
     extension_host_proxy.register_language_proxy(language_server_registry_proxy.clone());
-    extension_host_proxy.register_language_server_proxy(language_server_registry_proxy);
+    extension_host_proxy.register_language_server_proxy(language_server_registry_proxy.clone());
+    extension_host_proxy.register_formatter_proxy(language_server_registry_proxy);
 }
 
 #[derive(Clone)]
@@ -84,6 +84,9 @@ pub struct LanguageServer {
     process_name: Arc<str>,
     binary: LanguageServerBinary,
     capabilities: RwLock<ServerCapabilities>,
+    /// Whether the server reported `typeHierarchyProvider`, which [`ServerCapabilities`]
+    /// doesn't have a field for.
+    type_hierarchy_provider: bool,
     /// Configuration sent to the server, stored for display in the language server logs
     /// buffer. This is represented as the message sent to the LSP in order to avoid cloning it (can
     /// be large in cases like sending schemas to the json server).
@@ -295,6 +298,18 @@ pub struct AdapterServerCapabilities {
     pub server_capabilities: ServerCapabilities,
     // List of code actions supported by the LspAdapter matching the server
     pub code_action_kinds: Option<Vec<CodeActionKind>>,
+    // Whether the server supports `textDocument/prepareTypeHierarchy`
+    pub type_hierarchy_provider: bool,
+}
+
+/// The `initialize` request, with a result that keeps the capabilities which
+/// [`ServerCapabilities`] drops.
+enum InitializeWithRawResult {}
+
+impl request::Request for InitializeWithRawResult {
+    type Params = InitializeParams;
+    type Result = Value;
+    const METHOD: &'static str = request::Initialize::METHOD;
 }
 
 /// Experimental: Informs the end user about the state of the server
@@ -481,6 +496,7 @@ impl LanguageServer {
                 .unwrap_or_default(),
             binary,
             capabilities: Default::default(),
+            type_hierarchy_provider: false,
             configuration,
             code_action_kinds,
             next_id: Default::default(),
@@ -772,6 +788,82 @@ impl LanguageServer {
                         did_save: Some(true),
                         ..TextDocumentSyncClientCapabilities::default()
                     }),
+                    call_hierarchy: Some(CallHierarchyClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    type_hierarchy: Some(TypeHierarchyClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    code_lens: Some(CodeLensClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    folding_range: Some(FoldingRangeClientCapabilities {
+                        dynamic_registration: Some(false),
+                        line_folding_only: Some(false),
+                        folding_range_kind: Some(FoldingRangeKindCapability {
+                            value_set: Some(vec![
+                                FoldingRangeKind::Comment,
+                                FoldingRangeKind::Imports,
+                                FoldingRangeKind::Region,
+                            ]),
+                        }),
+                        folding_range: Some(FoldingRangeCapability {
+                            collapsed_text: Some(true),
+                        }),
+                        ..FoldingRangeClientCapabilities::default()
+                    }),
+                    selection_range: Some(SelectionRangeClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    semantic_tokens: Some(SemanticTokensClientCapabilities {
+                        dynamic_registration: Some(false),
+                        requests: SemanticTokensClientCapabilitiesRequests {
+                            range: None,
+                            full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        },
+                        token_types: vec![
+                            SemanticTokenType::NAMESPACE,
+                            SemanticTokenType::TYPE,
+                            SemanticTokenType::CLASS,
+                            SemanticTokenType::ENUM,
+                            SemanticTokenType::INTERFACE,
+                            SemanticTokenType::STRUCT,
+                            SemanticTokenType::TYPE_PARAMETER,
+                            SemanticTokenType::PARAMETER,
+                            SemanticTokenType::VARIABLE,
+                            SemanticTokenType::PROPERTY,
+                            SemanticTokenType::ENUM_MEMBER,
+                            SemanticTokenType::EVENT,
+                            SemanticTokenType::FUNCTION,
+                            SemanticTokenType::METHOD,
+                            SemanticTokenType::MACRO,
+                            SemanticTokenType::KEYWORD,
+                            SemanticTokenType::MODIFIER,
+                            SemanticTokenType::COMMENT,
+                            SemanticTokenType::STRING,
+                            SemanticTokenType::NUMBER,
+                            SemanticTokenType::REGEXP,
+                            SemanticTokenType::OPERATOR,
+                            SemanticTokenType::DECORATOR,
+                        ],
+                        token_modifiers: vec![
+                            SemanticTokenModifier::DECLARATION,
+                            SemanticTokenModifier::DEFINITION,
+                            SemanticTokenModifier::READONLY,
+                            SemanticTokenModifier::STATIC,
+                            SemanticTokenModifier::DEPRECATED,
+                            SemanticTokenModifier::ABSTRACT,
+                            SemanticTokenModifier::ASYNC,
+                            SemanticTokenModifier::MODIFICATION,
+                            SemanticTokenModifier::DOCUMENTATION,
+                            SemanticTokenModifier::DEFAULT_LIBRARY,
+                        ],
+                        formats: vec![TokenFormat::RELATIVE],
+                        overlapping_token_support: Some(false),
+                        multiline_token_support: Some(false),
+                        server_cancel_support: Some(true),
+                        augments_syntax_tokens: Some(true),
+                    }),
                     ..TextDocumentClientCapabilities::default()
                 }),
                 experimental: Some(json!({
@@ -820,7 +912,11 @@ impl LanguageServer {
         };
 
         cx.spawn(|_| async move {
-            let response = self.request::<request::Initialize>(params).await?;
+            let response = self.request::<InitializeWithRawResult>(params).await?;
+            self.type_hierarchy_provider = response
+                .pointer("/capabilities/typeHierarchyProvider")
+                .is_some_and(|provider| !provider.is_null() && *provider != Value::Bool(false));
+            let response = serde_json::from_value::<InitializeResult>(response)?;
             if let Some(info) = response.server_info {
                 self.process_name = info.name.into();
             }
@@ -1054,6 +1150,7 @@ impl LanguageServer {
         AdapterServerCapabilities {
             server_capabilities: self.capabilities(),
             code_action_kinds: self.code_action_kinds(),
+            type_hierarchy_provider: self.type_hierarchy_provider,
         }
     }
 
@@ -1358,6 +1455,26 @@ impl FakeLanguageServer {
         (server, fake)
     }
 
+    /// Answers the `initialize` request with the capabilities given and `typeHierarchyProvider`,
+    /// which [`ServerCapabilities`] can not express.
+    pub fn advertise_type_hierarchy_provider(&self, capabilities: ServerCapabilities) {
+        let name = self.server.name().to_string();
+        self.handle_request::<InitializeWithRawResult, _, _>(move |_, _| {
+            let result = serde_json::to_value(InitializeResult {
+                capabilities: capabilities.clone(),
+                server_info: Some(ServerInfo {
+                    name: name.clone(),
+                    ..Default::default()
+                }),
+            });
+            async move {
+                let mut result = result?;
+                result["capabilities"]["typeHierarchyProvider"] = Value::Bool(true);
+                Ok(result)
+            }
+        });
+    }
+
     #[cfg(target_os = "windows")]
     fn root_path() -> &'static Path {
         Path::new("C:\\")
@@ -1602,6 +1719,49 @@ mod tests {
         fake.receive_notification::<notification::Exit>().await;
     }
 
+    #[gpui::test]
+    async fn test_type_hierarchy_provider(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            release_channel::init(SemanticVersion::default(), cx);
+        });
+
+        for (provider, expected) in [
+            (json!(true), true),
+            (json!({ "workDoneProgress": false }), true),
+            (json!(false), false),
+            (Value::Null, false),
+        ] {
+            let (server, fake) = FakeLanguageServer::new(
+                LanguageServerId(0),
+                LanguageServerBinary {
+                    path: "path/to/language-server".into(),
+                    arguments: vec![],
+                    env: None,
+                },
+                "the-lsp".to_string(),
+                Default::default(),
+                cx.to_async(),
+            );
+            fake.handle_request::<InitializeWithRawResult, _, _>(move |_, _| {
+                let provider = provider.clone();
+                async move { Ok(json!({ "capabilities": { "typeHierarchyProvider": provider } })) }
+            });
+
+            let configuration = DidChangeConfigurationParams {
+                settings: Default::default(),
+            };
+            let server = cx
+                .update(|cx| server.initialize(None, configuration.into(), cx))
+                .await
+                .unwrap();
+            assert_eq!(
+                server.adapter_server_capabilities().type_hierarchy_provider,
+                expected
+            );
+            fake.handle_request::<request::Shutdown, _, _>(|_, _| async move { Ok(()) });
+        }
+    }
+
     #[gpui::test]
     fn test_deserialize_string_digit_id() {
         let json = r#"{"jsonrpc":"2.0","id":"2","method":"workspace/configuration","params":{"items":[{"scopeUri":"file:///Users/mph/Devel/personal/hello-scala/","section":"metals"}]}}"#;
@@ -4,6 +4,11 @@ use gpui::{
 use language::HighlightId;
 use std::{fmt::Display, ops::Range, path::PathBuf};
 
+use crate::{
+    math::{MathError, MathNode},
+    mermaid::{MermaidDiagram, MermaidError},
+};
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum ParsedMarkdownElement {
@@ -12,9 +17,13 @@ pub enum ParsedMarkdownElement {
     Table(ParsedMarkdownTable),
     BlockQuote(ParsedMarkdownBlockQuote),
     CodeBlock(ParsedMarkdownCodeBlock),
+    /// A fenced `mermaid` code block, rendered as a diagram.
+    Mermaid(ParsedMarkdownMermaid),
     /// A paragraph of text and other inline elements.
     Paragraph(MarkdownParagraph),
     HorizontalRule(Range<usize>),
+    /// The definition of a footnote, such as `[^1]: The footnote.`
+    FootnoteDefinition(ParsedMarkdownFootnoteDefinition),
 }
 
 impl ParsedMarkdownElement {
@@ -25,17 +34,57 @@ impl ParsedMarkdownElement {
             Self::Table(table) => table.source_range.clone(),
             Self::BlockQuote(block_quote) => block_quote.source_range.clone(),
             Self::CodeBlock(code_block) => code_block.source_range.clone(),
+            Self::Mermaid(mermaid) => mermaid.code_block.source_range.clone(),
             Self::Paragraph(text) => match text.get(0)? {
                 MarkdownParagraphChunk::Text(t) => t.source_range.clone(),
                 MarkdownParagraphChunk::Image(image) => image.source_range.clone(),
+                MarkdownParagraphChunk::Math(math) => math.source_range.clone(),
             },
             Self::HorizontalRule(range) => range.clone(),
+            Self::FootnoteDefinition(definition) => definition.source_range.clone(),
         })
     }
 
     pub fn is_list_item(&self) -> bool {
         matches!(self, Self::ListItem(_))
     }
+
+    /// Whether this element contains a reference to the footnote with the given label.
+    pub fn contains_footnote_reference(&self, label: &str) -> bool {
+        fn paragraph_contains(paragraph: &MarkdownParagraph, label: &str) -> bool {
+            paragraph.iter().any(|chunk| match chunk {
+                MarkdownParagraphChunk::Text(text) => text.regions.iter().any(|region| {
+                    matches!(
+                        &region.link,
+                        Some(Link::FootnoteDefinition { label: target }) if target == label
+                    )
+                }),
+                MarkdownParagraphChunk::Image(_) | MarkdownParagraphChunk::Math(_) => false,
+            })
+        }
+
+        match self {
+            Self::Paragraph(paragraph) => paragraph_contains(paragraph, label),
+            Self::Heading(heading) => paragraph_contains(&heading.contents, label),
+            Self::ListItem(list_item) => list_item
+                .content
+                .iter()
+                .any(|element| element.contains_footnote_reference(label)),
+            Self::BlockQuote(block_quote) => block_quote
+                .children
+                .iter()
+                .any(|element| element.contains_footnote_reference(label)),
+            Self::FootnoteDefinition(definition) => definition
+                .children
+                .iter()
+                .any(|element| element.contains_footnote_reference(label)),
+            Self::Table(table) => std::iter::once(&table.header)
+                .chain(&table.body)
+                .flat_map(|row| &row.children)
+                .any(|cell| paragraph_contains(cell, label)),
+            Self::CodeBlock(_) | Self::Mermaid(_) | Self::HorizontalRule(_) => false,
+        }
+    }
 }
 
 pub type MarkdownParagraph = Vec<MarkdownParagraphChunk>;
@@ -45,6 +94,8 @@ pub type MarkdownParagraph = Vec<MarkdownParagraphChunk>;
 pub enum MarkdownParagraphChunk {
     Text(ParsedMarkdownText),
     Image(Image),
+    /// Inline or display math.
+    Math(ParsedMarkdownMath),
 }
 
 #[derive(Debug)]
@@ -53,6 +104,43 @@ pub struct ParsedMarkdown {
     pub children: Vec<ParsedMarkdownElement>,
 }
 
+impl ParsedMarkdown {
+    /// The top-level headings of the document, along with the index of their block.
+    pub fn headings(&self) -> impl Iterator<Item = (usize, &ParsedMarkdownHeading)> {
+        self.children
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, element)| match element {
+                ParsedMarkdownElement::Heading(heading) => Some((ix, heading)),
+                _ => None,
+            })
+    }
+
+    /// The index of the block that a link to the given anchor should scroll to.
+    pub fn block_index_for_anchor(&self, anchor: &str) -> Option<usize> {
+        self.headings()
+            .find(|(_, heading)| heading.anchor.eq_ignore_ascii_case(anchor))
+            .map(|(ix, _)| ix)
+    }
+
+    /// The index of the block containing the definition of the footnote with the given label.
+    pub fn block_index_for_footnote_definition(&self, label: &str) -> Option<usize> {
+        self.children.iter().position(|element| {
+            matches!(
+                element,
+                ParsedMarkdownElement::FootnoteDefinition(definition) if definition.label == label
+            )
+        })
+    }
+
+    /// The index of the first block referencing the footnote with the given label.
+    pub fn block_index_for_footnote_reference(&self, label: &str) -> Option<usize> {
+        self.children
+            .iter()
+            .position(|element| element.contains_footnote_reference(label))
+    }
+}
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct ParsedMarkdownListItem {
@@ -80,15 +168,50 @@ pub struct ParsedMarkdownCodeBlock {
     pub highlights: Option<Vec<(Range<usize>, HighlightId)>>,
 }
 
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownMermaid {
+    /// The code block of the diagram, which is shown when it can't be parsed.
+    pub code_block: ParsedMarkdownCodeBlock,
+    pub diagram: Result<MermaidDiagram, MermaidError>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownMath {
+    pub source_range: Range<usize>,
+    /// The TeX source of the expression, which is shown when it can't be parsed.
+    pub source: SharedString,
+    /// Whether the expression is typeset on its own line, as for `$$x$$`, rather than
+    /// within the text, as for `$x$`.
+    pub display: bool,
+    pub expression: Result<MathNode, MathError>,
+}
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct ParsedMarkdownHeading {
     pub source_range: Range<usize>,
     pub level: HeadingLevel,
     pub contents: MarkdownParagraph,
+    /// The anchor that links to this heading, such as `getting-started` for `# Getting Started`.
+    pub anchor: SharedString,
 }
 
-#[derive(Debug, PartialEq)]
+impl ParsedMarkdownHeading {
+    /// The text of the heading, stripped of any formatting.
+    pub fn text(&self) -> String {
+        self.contents
+            .iter()
+            .filter_map(|chunk| match chunk {
+                MarkdownParagraphChunk::Text(text) => Some(text.contents.as_str()),
+                MarkdownParagraphChunk::Image(_) | MarkdownParagraphChunk::Math(_) => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum HeadingLevel {
     H1,
     H2,
@@ -98,6 +221,16 @@ pub enum HeadingLevel {
     H6,
 }
 
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownFootnoteDefinition {
+    pub source_range: Range<usize>,
+    pub label: SharedString,
+    /// The number shown for the footnote, in the order footnotes first appear in the document.
+    pub number: usize,
+    pub children: Vec<ParsedMarkdownElement>,
+}
+
 #[derive(Debug)]
 pub struct ParsedMarkdownTable {
     pub source_range: Range<usize>,
@@ -161,6 +294,56 @@ pub struct ParsedMarkdownText {
     pub regions: Vec<ParsedRegion>,
 }
 
+impl ParsedMarkdownText {
+    /// Splits the text after each run of whitespace, so that it can wrap around inline
+    /// elements such as math. Highlights and regions are split along with the text.
+    pub fn split_words(&self) -> Vec<ParsedMarkdownText> {
+        let mut words = Vec::new();
+        let mut word_start = 0;
+        let mut after_whitespace = false;
+        for (ix, c) in self.contents.char_indices() {
+            if after_whitespace && !c.is_whitespace() {
+                words.push(self.slice(word_start..ix));
+                word_start = ix;
+            }
+            after_whitespace = c.is_whitespace();
+        }
+        if word_start < self.contents.len() {
+            words.push(self.slice(word_start..self.contents.len()));
+        }
+        words
+    }
+
+    fn slice(&self, range: Range<usize>) -> ParsedMarkdownText {
+        let clip = |clipped: &Range<usize>| {
+            let start = clipped.start.max(range.start);
+            let end = clipped.end.min(range.end);
+            (start < end).then(|| start - range.start..end - range.start)
+        };
+        let mut region_ranges = Vec::new();
+        let mut regions = Vec::new();
+        for (region_range, region) in self.region_ranges.iter().zip(&self.regions) {
+            if let Some(region_range) = clip(region_range) {
+                region_ranges.push(region_range);
+                regions.push(region.clone());
+            }
+        }
+        ParsedMarkdownText {
+            source_range: self.source_range.clone(),
+            contents: self.contents[range.clone()].to_string(),
+            highlights: self
+                .highlights
+                .iter()
+                .filter_map(|(highlight_range, highlight)| {
+                    Some((clip(highlight_range)?, highlight.clone()))
+                })
+                .collect(),
+            region_ranges,
+            regions,
+        }
+    }
+}
+
 /// A run of highlighted Markdown text.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MarkdownHighlight {
@@ -246,6 +429,24 @@ pub enum Link {
         /// The absolute path to the item.
         path: PathBuf,
     },
+    /// A link to a heading, such as `#installation` or `./docs.md#installation`.
+    Heading {
+        /// The absolute path to the Markdown file containing the heading,
+        /// or `None` if the heading is in the current document.
+        path: Option<PathBuf>,
+        /// The anchor of the heading, without the leading `#`.
+        anchor: String,
+    },
+    /// A footnote reference, which links to the definition of the footnote.
+    FootnoteDefinition {
+        /// The label of the footnote.
+        label: String,
+    },
+    /// A link from a footnote definition back to where the footnote is referenced.
+    FootnoteReference {
+        /// The label of the footnote.
+        label: String,
+    },
 }
 
 impl Link {
@@ -254,6 +455,23 @@ impl Link {
             return Some(Link::Web { url: text });
         }
 
+        if let Some(anchor) = text.strip_prefix('#') {
+            return Some(Link::Heading {
+                path: None,
+                anchor: anchor.to_string(),
+            });
+        }
+
+        if let Some((path, anchor)) = text.split_once('#') {
+            return match Link::identify(file_location_directory, path.to_string())? {
+                Link::Path { path, .. } => Some(Link::Heading {
+                    path: Some(path),
+                    anchor: anchor.to_string(),
+                }),
+                _ => None,
+            };
+        }
+
         let path = PathBuf::from(&text);
         if path.is_absolute() && path.exists() {
             return Some(Link::Path {
@@ -279,6 +497,14 @@ impl Display for Link {
         match self {
             Link::Web { url } => write!(f, "{}", url),
             Link::Path { display_path, .. } => write!(f, "{}", display_path.display()),
+            Link::Heading {
+                path: Some(path),
+                anchor,
+            } => write!(f, "{}#{}", path.display(), anchor),
+            Link::Heading { path: None, anchor } => write!(f, "#{}", anchor),
+            Link::FootnoteDefinition { label } | Link::FootnoteReference { label } => {
+                write!(f, "[^{}]", label)
+            }
         }
     }
 }
@@ -299,6 +525,9 @@ impl Image {
         file_location_directory: Option<PathBuf>,
     ) -> Option<Self> {
         let link = Link::identify(file_location_directory, text)?;
+        if !matches!(link, Link::Web { .. } | Link::Path { .. }) {
+            return None;
+        }
         Some(Self {
             source_range,
             link,
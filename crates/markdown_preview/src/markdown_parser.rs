@@ -1,10 +1,10 @@
-use crate::markdown_elements::*;
+use crate::{markdown_elements::*, math, mermaid};
 use async_recursion::async_recursion;
 use collections::FxHashMap;
-use gpui::FontWeight;
+use gpui::{FontWeight, SharedString};
 use language::LanguageRegistry;
 use pulldown_cmark::{Alignment, Event, Options, Parser, Tag, TagEnd};
-use std::{ops::Range, path::PathBuf, sync::Arc, vec};
+use std::{mem, ops::Range, path::PathBuf, sync::Arc, vec};
 
 pub async fn parse_markdown(
     markdown_input: &str,
@@ -34,6 +34,10 @@ struct MarkdownParser<'a> {
     parsed: Vec<ParsedMarkdownElement>,
     file_location_directory: Option<PathBuf>,
     language_registry: Option<Arc<LanguageRegistry>>,
+    /// The number of each footnote label, in the order they first appear
+    footnote_numbers: FxHashMap<String, usize>,
+    /// How many headings have been given each anchor so far
+    heading_anchors: FxHashMap<String, usize>,
 }
 
 struct MarkdownListItem {
@@ -62,6 +66,8 @@ impl<'a> MarkdownParser<'a> {
             language_registry,
             cursor: 0,
             parsed: vec![],
+            footnote_numbers: FxHashMap::default(),
+            heading_anchors: FxHashMap::default(),
         }
     }
 
@@ -101,6 +107,8 @@ impl<'a> MarkdownParser<'a> {
             | Event::Code(_)
             | Event::Html(_)
             | Event::FootnoteReference(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_)
             | Event::Start(Tag::Link { .. })
             | Event::Start(Tag::Emphasis)
             | Event::Start(Tag::Strong)
@@ -134,10 +142,11 @@ impl<'a> MarkdownParser<'a> {
                     let text = self.parse_text(false, Some(source_range));
                     Some(vec![ParsedMarkdownElement::Paragraph(text)])
                 }
-                Tag::Heading { level, .. } => {
+                Tag::Heading { level, id, .. } => {
                     let level = *level;
+                    let id = id.as_ref().map(|id| id.to_string());
                     self.cursor += 1;
-                    let heading = self.parse_heading(level);
+                    let heading = self.parse_heading(level, id);
                     Some(vec![ParsedMarkdownElement::Heading(heading)])
                 }
                 Tag::Table(alignment) => {
@@ -172,8 +181,23 @@ impl<'a> MarkdownParser<'a> {
                     self.cursor += 1;
 
                     let code_block = self.parse_code_block(language).await;
+                    if code_block.language.as_deref() == Some("mermaid") {
+                        let diagram = mermaid::parse_mermaid(&code_block.contents);
+                        return Some(vec![ParsedMarkdownElement::Mermaid(
+                            ParsedMarkdownMermaid {
+                                code_block,
+                                diagram,
+                            },
+                        )]);
+                    }
                     Some(vec![ParsedMarkdownElement::CodeBlock(code_block)])
                 }
+                Tag::FootnoteDefinition(label) => {
+                    let label = label.to_string();
+                    self.cursor += 1;
+                    let definition = self.parse_footnote_definition(label).await;
+                    Some(vec![ParsedMarkdownElement::FootnoteDefinition(definition)])
+                }
                 _ => None,
             },
             Event::Rule => {
@@ -214,7 +238,7 @@ impl<'a> MarkdownParser<'a> {
                 break;
             }
 
-            let (current, _) = self.current().unwrap();
+            let (current, current_range) = self.current().unwrap();
             let prev_len = text.len();
             match current {
                 Event::SoftBreak => {
@@ -356,6 +380,71 @@ impl<'a> MarkdownParser<'a> {
                         link: link.clone(),
                     });
                 }
+                Event::InlineMath(source) => match math::parse_math(source) {
+                    Ok(expression) => {
+                        if !text.is_empty() {
+                            markdown_text_like.push(MarkdownParagraphChunk::Text(
+                                ParsedMarkdownText {
+                                    source_range: source_range.clone(),
+                                    contents: mem::take(&mut text),
+                                    highlights: mem::take(&mut highlights),
+                                    region_ranges: mem::take(&mut region_ranges),
+                                    regions: mem::take(&mut regions),
+                                },
+                            ));
+                        }
+                        markdown_text_like.push(MarkdownParagraphChunk::Math(ParsedMarkdownMath {
+                            source_range: current_range.clone(),
+                            source: source.to_string().into(),
+                            display: false,
+                            expression: Ok(expression),
+                        }));
+                    }
+                    // Show the source of expressions that can't be typeset as code.
+                    Err(_) => {
+                        text.push('$');
+                        text.push_str(source);
+                        text.push('$');
+                        region_ranges.push(prev_len..text.len());
+                        regions.push(ParsedRegion {
+                            code: true,
+                            link: link.clone(),
+                        });
+                    }
+                },
+                Event::FootnoteReference(label) => {
+                    let label = label.to_string();
+                    text.push_str(&format!("[{}]", self.footnote_number(&label)));
+                    highlights.push((
+                        prev_len..text.len(),
+                        MarkdownHighlight::Style(MarkdownHighlightStyle {
+                            underline: true,
+                            ..Default::default()
+                        }),
+                    ));
+                    region_ranges.push(prev_len..text.len());
+                    regions.push(ParsedRegion {
+                        code: false,
+                        link: Some(Link::FootnoteDefinition { label }),
+                    });
+                }
+                Event::DisplayMath(source) => {
+                    if !text.is_empty() {
+                        markdown_text_like.push(MarkdownParagraphChunk::Text(ParsedMarkdownText {
+                            source_range: source_range.clone(),
+                            contents: mem::take(&mut text),
+                            highlights: mem::take(&mut highlights),
+                            region_ranges: mem::take(&mut region_ranges),
+                            regions: mem::take(&mut regions),
+                        }));
+                    }
+                    markdown_text_like.push(MarkdownParagraphChunk::Math(ParsedMarkdownMath {
+                        source_range: current_range.clone(),
+                        source: source.to_string().into(),
+                        display: true,
+                        expression: math::parse_math(source),
+                    }));
+                }
                 Event::Start(tag) => match tag {
                     Tag::Emphasis => italic_depth += 1,
                     Tag::Strong => bold_depth += 1,
@@ -415,7 +504,11 @@ impl<'a> MarkdownParser<'a> {
         markdown_text_like
     }
 
-    fn parse_heading(&mut self, level: pulldown_cmark::HeadingLevel) -> ParsedMarkdownHeading {
+    fn parse_heading(
+        &mut self,
+        level: pulldown_cmark::HeadingLevel,
+        id: Option<String>,
+    ) -> ParsedMarkdownHeading {
         let (_event, source_range) = self.previous().unwrap();
         let source_range = source_range.clone();
         let text = self.parse_text(true, None);
@@ -423,6 +516,10 @@ impl<'a> MarkdownParser<'a> {
         // Advance past the heading end tag
         self.cursor += 1;
 
+        // Headings without an explicit `{#id}` get the same anchor that GitHub would give them
+        let anchor = id.unwrap_or_else(|| heading_anchor(&text));
+        let anchor = self.unique_heading_anchor(anchor);
+
         ParsedMarkdownHeading {
             source_range: source_range.clone(),
             level: match level {
@@ -434,6 +531,57 @@ impl<'a> MarkdownParser<'a> {
                 pulldown_cmark::HeadingLevel::H6 => HeadingLevel::H6,
             },
             contents: text,
+            anchor,
+        }
+    }
+
+    fn unique_heading_anchor(&mut self, anchor: String) -> SharedString {
+        let count = self.heading_anchors.entry(anchor.clone()).or_insert(0);
+        let unique_anchor = if *count == 0 {
+            anchor
+        } else {
+            format!("{}-{}", anchor, count)
+        };
+        *count += 1;
+        unique_anchor.into()
+    }
+
+    fn footnote_number(&mut self, label: &str) -> usize {
+        let next_number = self.footnote_numbers.len() + 1;
+        *self
+            .footnote_numbers
+            .entry(label.to_string())
+            .or_insert(next_number)
+    }
+
+    #[async_recursion]
+    async fn parse_footnote_definition(
+        &mut self,
+        label: String,
+    ) -> ParsedMarkdownFootnoteDefinition {
+        let (_event, source_range) = self.previous().unwrap();
+        let source_range = source_range.clone();
+        let number = self.footnote_number(&label);
+        let mut children = vec![];
+
+        while !self.eof() {
+            if let Some((Event::End(TagEnd::FootnoteDefinition), _)) = self.current() {
+                self.cursor += 1;
+                break;
+            }
+
+            if let Some(block) = self.parse_block().await {
+                children.extend(block);
+            } else {
+                self.cursor += 1;
+            }
+        }
+
+        ParsedMarkdownFootnoteDefinition {
+            source_range,
+            label: label.into(),
+            number,
+            children,
         }
     }
 
@@ -745,6 +893,24 @@ impl<'a> MarkdownParser<'a> {
     }
 }
 
+/// Converts the text of a heading into an anchor, such as `getting-started` for `Getting Started`.
+fn heading_anchor(contents: &MarkdownParagraph) -> String {
+    let mut anchor = String::new();
+    for chunk in contents {
+        let MarkdownParagraphChunk::Text(text) = chunk else {
+            continue;
+        };
+        for c in text.contents.chars() {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                anchor.extend(c.to_lowercase());
+            } else if c == ' ' {
+                anchor.push('-');
+            }
+        }
+    }
+    anchor
+}
+
 #[cfg(test)]
 mod tests {
     use core::panic;
@@ -1329,6 +1495,242 @@ fn main() {
         );
     }
 
+    #[gpui::test]
+    async fn test_mermaid_block() {
+        let parsed = parse("```mermaid\nflowchart LR\nA --> B\n```\n").await;
+
+        assert_eq!(
+            parsed.children,
+            vec![ParsedMarkdownElement::Mermaid(ParsedMarkdownMermaid {
+                code_block: ParsedMarkdownCodeBlock {
+                    source_range: 0..35,
+                    language: Some("mermaid".to_string()),
+                    contents: "flowchart LR\nA --> B".into(),
+                    highlights: None,
+                },
+                diagram: mermaid::parse_mermaid("flowchart LR\nA --> B"),
+            })]
+        );
+
+        let parsed = parse("```mermaid\npie\n```\n").await;
+        let ParsedMarkdownElement::Mermaid(mermaid) = &parsed.children[0] else {
+            panic!("expected a mermaid block, got {:?}", parsed.children[0]);
+        };
+        assert!(mermaid.diagram.is_err());
+    }
+
+    #[gpui::test]
+    async fn test_inline_math() {
+        let parsed = parse("Energy $E = mc^2$ holds").await;
+
+        assert_eq!(
+            parsed.children,
+            vec![ParsedMarkdownElement::Paragraph(vec![
+                MarkdownParagraphChunk::Text(ParsedMarkdownText {
+                    source_range: 0..23,
+                    contents: "Energy ".to_string(),
+                    highlights: Vec::new(),
+                    region_ranges: Vec::new(),
+                    regions: Vec::new(),
+                }),
+                MarkdownParagraphChunk::Math(ParsedMarkdownMath {
+                    source_range: 7..17,
+                    source: "E = mc^2".into(),
+                    display: false,
+                    expression: math::parse_math("E = mc^2"),
+                }),
+                MarkdownParagraphChunk::Text(ParsedMarkdownText {
+                    source_range: 0..23,
+                    contents: " holds".to_string(),
+                    highlights: Vec::new(),
+                    region_ranges: Vec::new(),
+                    regions: Vec::new(),
+                }),
+            ])]
+        );
+
+        let parsed = parse(r"$\foo$").await;
+
+        assert_eq!(
+            parsed.children,
+            vec![ParsedMarkdownElement::Paragraph(vec![
+                MarkdownParagraphChunk::Text(ParsedMarkdownText {
+                    source_range: 0..6,
+                    contents: r"$\foo$".to_string(),
+                    highlights: Vec::new(),
+                    region_ranges: vec![0..6],
+                    regions: vec![ParsedRegion {
+                        code: true,
+                        link: None,
+                    }],
+                })
+            ])]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_split_words() {
+        let parsed = parse("Some **bold text** and [a link](https://zed.dev)").await;
+        let ParsedMarkdownElement::Paragraph(paragraph) = &parsed.children[0] else {
+            panic!("expected a paragraph, got {:?}", parsed.children[0]);
+        };
+        let MarkdownParagraphChunk::Text(text) = &paragraph[0] else {
+            panic!("expected text, got {:?}", paragraph[0]);
+        };
+
+        let words = text.split_words();
+        assert_eq!(
+            words
+                .iter()
+                .map(|word| word.contents.as_str())
+                .collect::<Vec<_>>(),
+            ["Some ", "bold ", "text ", "and ", "a ", "link"]
+        );
+        assert_eq!(
+            words
+                .iter()
+                .map(|word| word
+                    .highlights
+                    .iter()
+                    .map(|(range, _)| range.clone())
+                    .collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            [
+                vec![],
+                vec![0..5],
+                vec![0..4],
+                vec![],
+                vec![0..2],
+                vec![0..4]
+            ]
+        );
+        assert_eq!(words[4].region_ranges, [0..2]);
+        assert_eq!(words[5].region_ranges, [0..4]);
+        assert_eq!(words[5].regions, text.regions);
+    }
+
+    #[gpui::test]
+    async fn test_display_math() {
+        let parsed = parse("Where\n$$\\frac{1}{2}$$").await;
+
+        assert_eq!(
+            parsed.children,
+            vec![ParsedMarkdownElement::Paragraph(vec![
+                MarkdownParagraphChunk::Text(ParsedMarkdownText {
+                    source_range: 0..21,
+                    contents: "Where ".to_string(),
+                    highlights: Vec::new(),
+                    region_ranges: Vec::new(),
+                    regions: Vec::new(),
+                }),
+                MarkdownParagraphChunk::Math(ParsedMarkdownMath {
+                    source_range: 6..21,
+                    source: r"\frac{1}{2}".into(),
+                    display: true,
+                    expression: math::parse_math(r"\frac{1}{2}"),
+                }),
+            ])]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_heading_anchors() {
+        let parsed =
+            parse("# Getting Started\n\n## Getting Started\n\n### Options {#custom-id}\n").await;
+
+        let anchors: Vec<_> = parsed
+            .headings()
+            .map(|(_, heading)| heading.anchor.to_string())
+            .collect();
+        assert_eq!(
+            anchors,
+            vec!["getting-started", "getting-started-1", "custom-id"]
+        );
+        assert_eq!(parsed.block_index_for_anchor("Getting-Started"), Some(0));
+        assert_eq!(parsed.block_index_for_anchor("custom-id"), Some(2));
+        assert_eq!(parsed.block_index_for_anchor("options"), None);
+    }
+
+    #[gpui::test]
+    async fn test_heading_links() {
+        let parsed = parse("[Usage](#usage)").await;
+
+        let ParsedMarkdownElement::Paragraph(paragraph) = &parsed.children[0] else {
+            panic!("expected a paragraph, got {:?}", parsed.children[0]);
+        };
+        let MarkdownParagraphChunk::Text(text) = &paragraph[0] else {
+            panic!("expected text, got {:?}", paragraph[0]);
+        };
+        assert_eq!(
+            text.regions,
+            vec![ParsedRegion {
+                code: false,
+                link: Some(Link::Heading {
+                    path: None,
+                    anchor: "usage".to_string(),
+                }),
+            }]
+        );
+
+        let manifest_path = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        assert_eq!(
+            Link::identify(None, format!("{}#dependencies", manifest_path)),
+            Some(Link::Heading {
+                path: Some(PathBuf::from(manifest_path)),
+                anchor: "dependencies".to_string(),
+            })
+        );
+        assert_eq!(Link::identify(None, "missing.md#usage".to_string()), None);
+    }
+
+    #[gpui::test]
+    async fn test_footnotes() {
+        let parsed =
+            parse("Text[^note] and[^other].\n\n[^other]: Other.\n\n[^note]: The note.\n").await;
+
+        let ParsedMarkdownElement::Paragraph(paragraph) = &parsed.children[0] else {
+            panic!("expected a paragraph, got {:?}", parsed.children[0]);
+        };
+        let MarkdownParagraphChunk::Text(text) = &paragraph[0] else {
+            panic!("expected text, got {:?}", paragraph[0]);
+        };
+        assert_eq!(text.contents, "Text[1] and[2].");
+        assert_eq!(text.region_ranges, vec![4..7, 11..14]);
+        assert_eq!(
+            text.regions,
+            vec![
+                ParsedRegion {
+                    code: false,
+                    link: Some(Link::FootnoteDefinition {
+                        label: "note".to_string()
+                    }),
+                },
+                ParsedRegion {
+                    code: false,
+                    link: Some(Link::FootnoteDefinition {
+                        label: "other".to_string()
+                    }),
+                },
+            ]
+        );
+
+        let definitions: Vec<_> = parsed.children[1..]
+            .iter()
+            .map(|element| {
+                let ParsedMarkdownElement::FootnoteDefinition(definition) = element else {
+                    panic!("expected a footnote definition, got {:?}", element);
+                };
+                (definition.label.to_string(), definition.number)
+            })
+            .collect();
+        assert_eq!(
+            definitions,
+            vec![("other".to_string(), 2), ("note".to_string(), 1)]
+        );
+        assert_eq!(parsed.block_index_for_footnote_definition("note"), Some(2));
+        assert_eq!(parsed.block_index_for_footnote_reference("other"), Some(0));
+    }
+
     fn rust_lang() -> Arc<Language> {
         Arc::new(Language::new(
             LanguageConfig {
@@ -1348,6 +1750,7 @@ fn main() {
         ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
             source_range,
             level: HeadingLevel::H1,
+            anchor: heading_anchor(&contents).into(),
             contents,
         })
     }
@@ -1356,6 +1759,7 @@ fn main() {
         ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
             source_range,
             level: HeadingLevel::H2,
+            anchor: heading_anchor(&contents).into(),
             contents,
         })
     }
@@ -1364,6 +1768,7 @@ fn main() {
         ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
             source_range,
             level: HeadingLevel::H3,
+            anchor: heading_anchor(&contents).into(),
             contents,
         })
     }
@@ -5,8 +5,13 @@ pub mod markdown_elements;
 pub mod markdown_parser;
 pub mod markdown_preview_view;
 pub mod markdown_renderer;
+pub mod math;
+pub mod mermaid;
 
-actions!(markdown, [OpenPreview, OpenPreviewToTheSide]);
+actions!(
+    markdown,
+    [OpenPreview, OpenPreviewToTheSide, ToggleTableOfContents]
+);
 
 pub fn init(cx: &mut App) {
     cx.observe_new(|workspace: &mut Workspace, window, cx| {
@@ -7,22 +7,22 @@ use editor::scroll::{Autoscroll, AutoscrollStrategy};
 use editor::{Editor, EditorEvent};
 use gpui::{
     list, App, ClickEvent, Context, Entity, EventEmitter, FocusHandle, Focusable,
-    InteractiveElement, IntoElement, ListState, ParentElement, Render, Styled, Subscription, Task,
-    WeakEntity, Window,
+    InteractiveElement, IntoElement, ListOffset, ListState, ParentElement, Render, Styled,
+    Subscription, Task, WeakEntity, Window,
 };
 use language::LanguageRegistry;
-use ui::prelude::*;
+use ui::{prelude::*, Tooltip};
 use workspace::item::{Item, ItemHandle};
-use workspace::{Pane, Workspace};
+use workspace::{OpenVisible, Pane, Workspace};
 
-use crate::markdown_elements::ParsedMarkdownElement;
-use crate::OpenPreviewToTheSide;
+use crate::markdown_elements::{Link, ParsedMarkdownElement};
 use crate::{
     markdown_elements::ParsedMarkdown,
     markdown_parser::parse_markdown,
     markdown_renderer::{render_markdown_block, RenderContext},
     OpenPreview,
 };
+use crate::{OpenPreviewToTheSide, ToggleTableOfContents};
 
 const REPARSE_DEBOUNCE: Duration = Duration::from_millis(200);
 
@@ -37,6 +37,9 @@ pub struct MarkdownPreviewView {
     fallback_tab_description: SharedString,
     language_registry: Arc<LanguageRegistry>,
     parsing_markdown_task: Option<Task<Result<()>>>,
+    /// The heading to scroll to once the contents of a newly opened file have been parsed.
+    pending_anchor: Option<String>,
+    show_table_of_contents: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -163,25 +166,15 @@ impl MarkdownPreviewView {
                                         let view = view.clone();
                                         move |checked, source_range, window, cx| {
                                             view.update(cx, |view, cx| {
-                                                if let Some(editor) = view
-                                                    .active_editor
-                                                    .as_ref()
-                                                    .map(|s| s.editor.clone())
-                                                {
-                                                    editor.update(cx, |editor, cx| {
-                                                        let task_marker =
-                                                            if checked { "[x]" } else { "[ ]" };
-
-                                                        editor.edit(
-                                                            vec![(source_range, task_marker)],
-                                                            cx,
-                                                        );
-                                                    });
-                                                    view.parse_markdown_from_active_editor(
-                                                        false, window, cx,
-                                                    );
-                                                    cx.notify();
-                                                }
+                                                view.toggle_task(checked, source_range, window, cx)
+                                            })
+                                        }
+                                    })
+                                    .with_link_clicked_callback({
+                                        let view = view.clone();
+                                        move |link, window, cx| {
+                                            view.update(cx, |view, cx| {
+                                                view.navigate_to_link(link, window, cx)
                                             })
                                         }
                                     });
@@ -258,6 +251,8 @@ impl MarkdownPreviewView {
                 fallback_tab_description: fallback_description
                     .unwrap_or_else(|| "Markdown Preview".into()),
                 parsing_markdown_task: None,
+                pending_anchor: None,
+                show_table_of_contents: false,
             };
 
             this.set_editor(active_editor, window, cx);
@@ -393,11 +388,43 @@ impl MarkdownPreviewView {
                 let scroll_top = view.list_state.logical_scroll_top();
                 view.list_state.reset(markdown_blocks_count);
                 view.list_state.scroll_to(scroll_top);
+                if let Some(anchor) = view.pending_anchor.take() {
+                    view.scroll_to_anchor(&anchor, cx);
+                }
                 cx.notify();
             })
         })
     }
 
+    /// Writes the new state of a task list item's checkbox back to the previewed source.
+    fn toggle_task(
+        &mut self,
+        checked: bool,
+        source_range: Range<usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(editor) = self.active_editor.as_ref().map(|s| s.editor.clone()) else {
+            return;
+        };
+        editor.update(cx, |editor, cx| {
+            // The source may have been edited since the preview was last parsed.
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let current_marker = snapshot
+                .text_for_range(source_range.clone())
+                .collect::<String>();
+            if !matches!(current_marker.as_str(), "[ ]" | "[x]" | "[X]") {
+                return;
+            }
+
+            let task_marker = if checked { "[x]" } else { "[ ]" };
+
+            editor.edit(vec![(source_range, task_marker)], cx);
+        });
+        self.parse_markdown_from_active_editor(false, window, cx);
+        cx.notify();
+    }
+
     fn move_cursor_to_block(
         &self,
         window: &mut Window,
@@ -417,19 +444,181 @@ impl MarkdownPreviewView {
         }
     }
 
-    /// The absolute path of the file that is currently being previewed.
-    fn get_folder_for_active_editor(editor: &Editor, cx: &App) -> Option<PathBuf> {
-        if let Some(file) = editor.file_at(0, cx) {
-            if let Some(file) = file.as_local() {
-                file.abs_path(cx).parent().map(|p| p.to_path_buf())
-            } else {
-                None
+    fn navigate_to_link(&mut self, link: &Link, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(contents) = &self.contents else {
+            return;
+        };
+
+        let block_index = match link {
+            Link::Heading { path: None, anchor } => contents.block_index_for_anchor(anchor),
+            Link::Heading {
+                path: Some(path),
+                anchor,
+            } => {
+                let is_active_file = self.active_editor.as_ref().is_some_and(|state| {
+                    Self::get_path_for_active_editor(state.editor.read(cx), cx).as_ref()
+                        == Some(path)
+                });
+                if !is_active_file {
+                    self.open_markdown_file_at_anchor(path.clone(), anchor.clone(), window, cx);
+                    return;
+                }
+                contents.block_index_for_anchor(anchor)
             }
-        } else {
-            None
+            Link::FootnoteDefinition { label } => {
+                contents.block_index_for_footnote_definition(label)
+            }
+            Link::FootnoteReference { label } => contents.block_index_for_footnote_reference(label),
+            Link::Web { .. } | Link::Path { .. } => None,
+        };
+
+        if let Some(block_index) = block_index {
+            self.scroll_to_block(block_index, cx);
         }
     }
 
+    fn scroll_to_anchor(&mut self, anchor: &str, cx: &mut Context<Self>) {
+        if let Some(block_index) = self
+            .contents
+            .as_ref()
+            .and_then(|contents| contents.block_index_for_anchor(anchor))
+        {
+            self.scroll_to_block(block_index, cx);
+        }
+    }
+
+    fn scroll_to_block(&mut self, block_index: usize, cx: &mut Context<Self>) {
+        self.selected_block = block_index;
+        self.list_state.scroll_to(ListOffset {
+            item_ix: block_index,
+            offset_in_item: px(0.),
+        });
+        cx.notify();
+    }
+
+    /// Opens another Markdown file next to the previewed one, and previews it
+    /// scrolled to the given heading.
+    fn open_markdown_file_at_anchor(
+        &mut self,
+        path: PathBuf,
+        anchor: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+
+        let open_task = workspace.update(cx, |workspace, cx| {
+            let pane = self
+                .active_editor
+                .as_ref()
+                .and_then(|state| workspace.pane_for(&state.editor))
+                .map(|pane| pane.downgrade());
+            workspace.open_paths(vec![path], OpenVisible::None, pane, window, cx)
+        });
+
+        cx.spawn_in(window, move |view, mut cx| async move {
+            let Some(item) = open_task.await.pop().flatten() else {
+                return Ok(());
+            };
+            let item = item?;
+            view.update_in(&mut cx, |view, window, cx| {
+                if let Some(editor) = item.act_as::<Editor>(cx) {
+                    if Self::is_markdown_file(&editor, cx) {
+                        view.pending_anchor = Some(anchor);
+                        view.set_editor(editor, window, cx);
+                        // The editor may already be previewed, in which case it wasn't reparsed.
+                        view.parse_markdown_from_active_editor(false, window, cx);
+                    }
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn toggle_table_of_contents(
+        &mut self,
+        _: &ToggleTableOfContents,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.show_table_of_contents = !self.show_table_of_contents;
+        cx.notify();
+    }
+
+    fn render_table_of_contents(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let headings = self
+            .contents
+            .as_ref()
+            .map(|contents| contents.headings().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let min_level = headings.iter().map(|(_, heading)| heading.level).min();
+        // The section containing the selected block.
+        let current_heading = headings
+            .iter()
+            .map(|(ix, _)| *ix)
+            .filter(|ix| *ix <= self.selected_block)
+            .last();
+
+        v_flex()
+            .id("table-of-contents")
+            .flex_none()
+            .w(px(220.))
+            .h_full()
+            .overflow_y_scroll()
+            .ml_2()
+            .pl_2()
+            .border_l_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                div().px_2().pb_1().child(
+                    Label::new("Contents")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .children(headings.iter().map(|(ix, heading)| {
+                let ix = *ix;
+                let indent =
+                    min_level.map_or(0, |min_level| heading.level as usize - min_level as usize);
+                div()
+                    .id(("table-of-contents-heading", ix))
+                    .debug_selector(move || format!("TABLE_OF_CONTENTS-{ix}"))
+                    .pl(px(8. + 12. * indent as f32))
+                    .pr_2()
+                    .py_0p5()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .hover(|style| style.bg(cx.theme().colors().element_hover))
+                    .child(
+                        Label::new(heading.text())
+                            .size(LabelSize::Small)
+                            .color(if Some(ix) == current_heading {
+                                Color::Default
+                            } else {
+                                Color::Muted
+                            })
+                            .single_line()
+                            .text_ellipsis(),
+                    )
+                    .on_click(cx.listener(move |this, _, _, cx| this.scroll_to_block(ix, cx)))
+            }))
+    }
+
+    /// The absolute path of the file that is currently being previewed.
+    fn get_path_for_active_editor(editor: &Editor, cx: &App) -> Option<PathBuf> {
+        let file = editor.file_at(0, cx)?;
+        Some(file.as_local()?.abs_path(cx))
+    }
+
+    /// The absolute path of the folder containing the file that is currently being previewed.
+    fn get_folder_for_active_editor(editor: &Editor, cx: &App) -> Option<PathBuf> {
+        Self::get_path_for_active_editor(editor, cx)?
+            .parent()
+            .map(|p| p.to_path_buf())
+    }
+
     fn get_block_index_under_cursor(&self, selection_range: Range<usize>) -> usize {
         let mut block_index = None;
         let cursor = selection_range.start;
@@ -505,17 +694,243 @@ impl Item for MarkdownPreviewView {
 
 impl Render for MarkdownPreviewView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let has_headings = self
+            .contents
+            .as_ref()
+            .is_some_and(|contents| contents.headings().next().is_some());
+
         v_flex()
             .id("MarkdownPreview")
             .key_context("MarkdownPreview")
             .track_focus(&self.focus_handle(cx))
+            .on_action(cx.listener(Self::toggle_table_of_contents))
             .size_full()
             .bg(cx.theme().colors().editor_background)
             .p_4()
             .child(
-                div()
+                h_flex()
                     .flex_grow()
-                    .map(|this| this.child(list(self.list_state.clone()).size_full())),
+                    .items_start()
+                    .relative()
+                    .child(
+                        div()
+                            .flex_grow()
+                            .h_full()
+                            .map(|this| this.child(list(self.list_state.clone()).size_full())),
+                    )
+                    .when(self.show_table_of_contents && has_headings, |this| {
+                        this.child(self.render_table_of_contents(cx))
+                    })
+                    .when(has_headings, |this| {
+                        this.child(
+                            div().absolute().top_0().right_0().child(
+                                IconButton::new("toggle-table-of-contents", IconName::ListTree)
+                                    .icon_size(IconSize::Small)
+                                    .toggle_state(self.show_table_of_contents)
+                                    .tooltip(|window, cx| {
+                                        Tooltip::for_action(
+                                            "Toggle Table of Contents",
+                                            &ToggleTableOfContents,
+                                            window,
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.toggle_table_of_contents(
+                                            &ToggleTableOfContents,
+                                            window,
+                                            cx,
+                                        )
+                                    })),
+                            ),
+                        )
+                    }),
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use gpui::{Modifiers, TestAppContext, VisualTestContext};
+    use language::{Language, LanguageConfig, LanguageMatcher};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_toggle_task(cx: &mut TestAppContext) {
+        let (_, editor, cx) =
+            open_preview(json!({ "tasks.md": "- [ ] Task\n" }), "/dir/tasks.md", cx).await;
+
+        let checkbox = cx.debug_bounds("TASK-2").unwrap();
+        cx.simulate_click(checkbox.center(), Modifiers::none());
+        assert_eq!(editor_text(&editor, cx), "- [x] Task\n");
+        cx.executor().advance_clock(REPARSE_DEBOUNCE);
+        cx.run_until_parked();
+
+        // A click on a preview that is older than the source doesn't edit the source.
+        editor.update(cx, |editor, cx| editor.edit([(0..0, "# Title\n")], cx));
+        let checkbox = cx.debug_bounds("TASK-2").unwrap();
+        cx.simulate_click(checkbox.center(), Modifiers::none());
+        assert_eq!(editor_text(&editor, cx), "# Title\n- [x] Task\n");
+
+        // Once the preview has caught up, the task can be toggled again.
+        cx.executor().advance_clock(REPARSE_DEBOUNCE);
+        cx.run_until_parked();
+        let checkbox = cx.debug_bounds("TASK-10").unwrap();
+        cx.simulate_click(checkbox.center(), Modifiers::none());
+        assert_eq!(editor_text(&editor, cx), "# Title\n- [ ] Task\n");
+    }
+
+    #[gpui::test]
+    async fn test_footnote_links(cx: &mut TestAppContext) {
+        let (preview, _, cx) = open_preview(
+            json!({ "notes.md": "See the note[^1].\n\n# Heading\n\n[^1]: The note.\n" }),
+            "/dir/notes.md",
+            cx,
+        )
+        .await;
+
+        preview.update_in(cx, |preview, window, cx| {
+            let label = "1".to_string();
+            preview.navigate_to_link(&Link::FootnoteDefinition { label }, window, cx);
+            assert_eq!(preview.selected_block, 2);
+
+            let label = "1".to_string();
+            preview.navigate_to_link(&Link::FootnoteReference { label }, window, cx);
+            assert_eq!(preview.selected_block, 0);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_heading_link_to_other_file(cx: &mut TestAppContext) {
+        let (preview, _, cx) = open_preview(
+            json!({
+                "a.md": "[Second](b.md#second)\n",
+                "b.md": "# First\n\nText.\n\n# Second\n",
+            }),
+            "/dir/a.md",
+            cx,
+        )
+        .await;
+
+        preview.update_in(cx, |preview, window, cx| {
+            let link = Link::Heading {
+                path: Some(PathBuf::from("/dir/b.md")),
+                anchor: "second".to_string(),
+            };
+            preview.navigate_to_link(&link, window, cx);
+        });
+        cx.run_until_parked();
+
+        preview.update(cx, |preview, cx| {
+            let editor = preview.active_editor.as_ref().unwrap().editor.read(cx);
+            assert_eq!(
+                MarkdownPreviewView::get_path_for_active_editor(editor, cx),
+                Some(PathBuf::from("/dir/b.md"))
+            );
+            assert_eq!(preview.selected_block, 2);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_table_of_contents(cx: &mut TestAppContext) {
+        let (preview, _, cx) = open_preview(
+            json!({ "doc.md": "# First\n\nText.\n\n## Second\n\nMore text.\n" }),
+            "/dir/doc.md",
+            cx,
+        )
+        .await;
+
+        assert!(cx.debug_bounds("TABLE_OF_CONTENTS-2").is_none());
+        preview.update_in(cx, |preview, window, cx| {
+            preview.toggle_table_of_contents(&ToggleTableOfContents, window, cx)
+        });
+        cx.run_until_parked();
+
+        let heading = cx.debug_bounds("TABLE_OF_CONTENTS-2").unwrap();
+        cx.simulate_click(heading.center(), Modifiers::none());
+        preview.update(cx, |preview, _| assert_eq!(preview.selected_block, 2));
+    }
+
+    /// Opens the file at the given path in an editor, and previews it next to it.
+    async fn open_preview<'a>(
+        tree: serde_json::Value,
+        path: &str,
+        cx: &'a mut TestAppContext,
+    ) -> (
+        Entity<MarkdownPreviewView>,
+        Entity<Editor>,
+        &'a mut VisualTestContext,
+    ) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", tree).await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        project.update(cx, |project, _| project.languages().add(markdown_lang()));
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let editor = workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_abs_path(Path::new(path).to_path_buf(), true, window, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        let preview = workspace.update_in(cx, |workspace, window, cx| {
+            let language_registry = workspace.project().read(cx).languages().clone();
+            let preview = MarkdownPreviewView::new(
+                MarkdownPreviewMode::Default,
+                editor.clone(),
+                workspace.weak_handle(),
+                language_registry,
+                None,
+                window,
+                cx,
+            );
+            workspace.add_item_to_active_pane(Box::new(preview.clone()), None, true, window, cx);
+            preview
+        });
+        cx.run_until_parked();
+        (preview, editor, cx)
+    }
+
+    fn editor_text(editor: &Entity<Editor>, cx: &mut VisualTestContext) -> String {
+        editor.update(cx, |editor, cx| editor.text(cx))
+    }
+
+    fn markdown_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Markdown".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["md".into()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        ))
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+
+            theme::init(theme::LoadThemes::JustBase, cx);
+
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+    }
+}
@@ -1,14 +1,21 @@
-use crate::markdown_elements::{
-    HeadingLevel, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
-    ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock, ParsedMarkdownElement,
-    ParsedMarkdownHeading, ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownTable,
-    ParsedMarkdownTableAlignment, ParsedMarkdownTableRow,
+use crate::{
+    markdown_elements::{
+        HeadingLevel, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
+        ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock, ParsedMarkdownElement,
+        ParsedMarkdownFootnoteDefinition, ParsedMarkdownHeading, ParsedMarkdownListItem,
+        ParsedMarkdownListItemType, ParsedMarkdownMath, ParsedMarkdownMermaid, ParsedMarkdownTable,
+        ParsedMarkdownTableAlignment, ParsedMarkdownTableRow,
+    },
+    math::{self, MathFont, MathFontMetrics, MathGlyph, MathLayout, MathStyle},
+    mermaid::{ArrowHead, DiagramLayout, LayoutLine, LineStyle, NodeShape, ShapeKind},
 };
 use gpui::{
-    div, img, px, rems, AbsoluteLength, AnyElement, App, AppContext as _, ClipboardItem, Context,
-    DefiniteLength, Div, Element, ElementId, Entity, HighlightStyle, Hsla, ImageSource,
-    InteractiveText, IntoElement, Keystroke, Length, Modifiers, ParentElement, Render, Resource,
-    SharedString, Styled, StyledText, TextStyle, WeakEntity, Window,
+    canvas, div, fill, img, point, px, quad, rems, size, AbsoluteLength, AnyElement, App,
+    AppContext as _, Bounds, ClipboardItem, Context, DefiniteLength, Div, Element, ElementId,
+    Entity, FontStyle, FontWeight, HighlightStyle, Hsla, ImageSource, InteractiveText, IntoElement,
+    Keystroke, Length, Modifiers, ParentElement, PathBuilder, Pixels, Point, Render, Resource,
+    ShapedLine, SharedString, Size, Styled, StyledText, TextRun, TextStyle, WeakEntity, Window,
+    WindowTextSystem,
 };
 use settings::Settings;
 use std::{
@@ -23,9 +30,11 @@ use ui::{
     LabelSize, LinkPreview, StatefulInteractiveElement, StyledExt, StyledImage, ToggleState,
     Tooltip, VisibleOnHover,
 };
+use util::ResultExt as _;
 use workspace::Workspace;
 
 type CheckboxClickedCallback = Arc<Box<dyn Fn(bool, Range<usize>, &mut Window, &mut App)>>;
+type LinkClickedCallback = Arc<Box<dyn Fn(&Link, &mut Window, &mut App)>>;
 
 #[derive(Clone)]
 pub struct RenderContext {
@@ -34,6 +43,8 @@ pub struct RenderContext {
     buffer_font_family: SharedString,
     buffer_text_style: TextStyle,
     text_style: TextStyle,
+    text_system: Arc<WindowTextSystem>,
+    rem_size: Pixels,
     border_color: Hsla,
     text_color: Hsla,
     text_muted_color: Hsla,
@@ -42,6 +53,7 @@ pub struct RenderContext {
     syntax_theme: Arc<SyntaxTheme>,
     indent: usize,
     checkbox_clicked_callback: Option<CheckboxClickedCallback>,
+    link_clicked_callback: Option<LinkClickedCallback>,
 }
 
 impl RenderContext {
@@ -64,6 +76,8 @@ impl RenderContext {
             buffer_font_family,
             buffer_text_style,
             text_style: window.text_style(),
+            text_system: window.text_system().clone(),
+            rem_size: window.rem_size(),
             syntax_theme: theme.syntax().clone(),
             border_color: theme.colors().border,
             text_color: theme.colors().text,
@@ -71,6 +85,7 @@ impl RenderContext {
             code_block_background_color: theme.colors().surface_background,
             code_span_background_color: theme.colors().editor_document_highlight_read_background,
             checkbox_clicked_callback: None,
+            link_clicked_callback: None,
         }
     }
 
@@ -82,6 +97,16 @@ impl RenderContext {
         self
     }
 
+    /// Handles clicks on links that navigate within the preview,
+    /// such as links to headings and footnotes.
+    pub fn with_link_clicked_callback(
+        mut self,
+        callback: impl Fn(&Link, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.link_clicked_callback = Some(Arc::new(Box::new(callback)));
+        self
+    }
+
     fn next_id(&mut self, span: &Range<usize>) -> ElementId {
         let id = format!("markdown-{}-{}-{}", self.next_id, span.start, span.end);
         self.next_id += 1;
@@ -135,7 +160,9 @@ pub fn render_markdown_block(block: &ParsedMarkdownElement, cx: &mut RenderConte
         Table(table) => render_markdown_table(table, cx),
         BlockQuote(block_quote) => render_markdown_block_quote(block_quote, cx),
         CodeBlock(code_block) => render_markdown_code_block(code_block, cx),
+        Mermaid(mermaid) => render_markdown_mermaid(mermaid, cx),
         HorizontalRule(_) => render_markdown_rule(cx),
+        FootnoteDefinition(definition) => render_markdown_footnote_definition(definition, cx),
     }
 }
 
@@ -180,6 +207,10 @@ fn render_markdown_list_item(
         Unordered => "•".into_any_element(),
         Task(checked, range) => div()
             .id(cx.next_id(range))
+            .debug_selector({
+                let start = range.start;
+                move || format!("TASK-{start}")
+            })
             .mt(px(3.))
             .child(
                 Checkbox::new(
@@ -202,17 +233,15 @@ fn render_markdown_list_item(
                                     _ => return,
                                 };
 
-                                if window.modifiers().secondary() {
-                                    callback(checked, range.clone(), window, cx);
-                                }
+                                callback(checked, range.clone(), window, cx);
                             }
                         })
                     },
                 ),
             )
-            .hover(|s| s.cursor_pointer())
-            .tooltip(|_, cx| {
-                InteractiveMarkdownElementTooltip::new(None, "toggle checkbox", cx).into()
+            .when(cx.checkbox_clicked_callback.is_some(), |this| {
+                this.hover(|s| s.cursor_pointer())
+                    .tooltip(Tooltip::text("Toggle task"))
             })
             .into_any_element(),
     };
@@ -232,6 +261,41 @@ fn render_markdown_list_item(
     cx.with_common_p(item).into_any()
 }
 
+fn render_markdown_footnote_definition(
+    parsed: &ParsedMarkdownFootnoteDefinition,
+    cx: &mut RenderContext,
+) -> AnyElement {
+    let back_reference = cx.link_clicked_callback.clone().map(|callback| {
+        let link = Link::FootnoteReference {
+            label: parsed.label.to_string(),
+        };
+        IconButton::new(cx.next_id(&parsed.source_range), IconName::ArrowUp)
+            .icon_size(IconSize::Small)
+            .on_click(move |_, window, cx| callback(&link, window, cx))
+            .tooltip(Tooltip::text("Back to reference"))
+    });
+
+    let contents: Vec<AnyElement> = parsed
+        .children
+        .iter()
+        .map(|c| render_markdown_block(c, cx))
+        .collect();
+
+    let item = h_flex()
+        .items_start()
+        .text_sm()
+        .child(
+            div()
+                .mr_2()
+                .text_color(cx.text_muted_color)
+                .child(format!("{}.", parsed.number)),
+        )
+        .child(div().children(contents).w_full())
+        .children(back_reference);
+
+    cx.with_common_p(item).into_any()
+}
+
 fn paragraph_len(paragraphs: &MarkdownParagraph) -> usize {
     paragraphs
         .iter()
@@ -239,6 +303,7 @@ fn paragraph_len(paragraphs: &MarkdownParagraph) -> usize {
             MarkdownParagraphChunk::Text(text) => text.contents.len(),
             // TODO: Scale column width based on image size
             MarkdownParagraphChunk::Image(_) => 1,
+            MarkdownParagraphChunk::Math(math) => math.source.len(),
         })
         .sum()
 }
@@ -411,6 +476,453 @@ fn render_markdown_code_block(
         .into_any()
 }
 
+fn render_markdown_mermaid(parsed: &ParsedMarkdownMermaid, cx: &mut RenderContext) -> AnyElement {
+    let diagram = match &parsed.diagram {
+        Ok(diagram) => diagram,
+        Err(error) => {
+            return v_flex()
+                .gap_1()
+                .child(render_markdown_code_block(&parsed.code_block, cx))
+                .child(
+                    Label::new(format!("Failed to render diagram: {error}"))
+                        .size(LabelSize::Small)
+                        .color(Color::Error),
+                )
+                .into_any();
+        }
+    };
+
+    let layout = diagram.layout(&|text| measure_diagram_text(text, cx));
+    let diagram_size = layout.size;
+    let label_background_color = cx.code_block_background_color;
+    let labels = layout
+        .labels
+        .iter()
+        .map(|label| {
+            let bounds = label.bounds;
+            v_flex()
+                .absolute()
+                .left(px(bounds.origin.x))
+                .top(px(bounds.origin.y))
+                .when(label.centered, |this| {
+                    this.w(px(bounds.size.width)).items_center()
+                })
+                .when(label.background, |this| {
+                    this.bg(label_background_color).rounded_sm()
+                })
+                .children(
+                    label
+                        .text
+                        .split('\n')
+                        .map(|line| div().whitespace_nowrap().child(line.to_string())),
+                )
+        })
+        .collect::<Vec<_>>();
+    let colors = DiagramColors {
+        line: cx.text_muted_color,
+        node_background: cx.code_block_background_color,
+        note_background: cx.code_span_background_color,
+    };
+
+    let element_id = cx.next_id(&parsed.code_block.source_range);
+    cx.with_common_p(div())
+        .id(element_id)
+        .overflow_x_scroll()
+        .child(
+            div()
+                .relative()
+                .w(px(diagram_size.width))
+                .h(px(diagram_size.height))
+                .text_color(cx.text_color)
+                .child(
+                    canvas(
+                        |_, _, _| {},
+                        move |bounds, _, window, _| {
+                            paint_diagram(&layout, bounds.origin, &colors, window)
+                        },
+                    )
+                    .size_full(),
+                )
+                .children(labels),
+        )
+        .into_any()
+}
+
+/// Measures the text of a diagram label, which is split into lines at `\n`.
+fn measure_diagram_text(text: &str, cx: &RenderContext) -> Size<f32> {
+    let font_size = cx.text_style.font_size.to_pixels(cx.rem_size);
+    let line_height = cx.text_style.line_height_in_pixels(cx.rem_size);
+    let mut width = 0_f32;
+    let mut line_count = 0;
+    for line in text.split('\n') {
+        line_count += 1;
+        if let Ok(layout) = cx.text_system.layout_line(
+            line.to_string(),
+            font_size,
+            &[cx.text_style.to_run(line.len())],
+        ) {
+            width = width.max(layout.width.0);
+        }
+    }
+    size(width.ceil(), line_height.0 * line_count as f32)
+}
+
+struct DiagramColors {
+    line: Hsla,
+    node_background: Hsla,
+    note_background: Hsla,
+}
+
+const ARROW_SIZE: f32 = 8.;
+const DASH_LENGTH: f32 = 4.;
+const DASH_GAP: f32 = 3.;
+
+fn paint_diagram(
+    layout: &DiagramLayout,
+    origin: Point<Pixels>,
+    colors: &DiagramColors,
+    window: &mut Window,
+) {
+    let to_window = |p: Point<f32>| point(origin.x + px(p.x), origin.y + px(p.y));
+
+    for line in &layout.lines {
+        paint_diagram_line(line, &to_window, colors.line, window);
+    }
+
+    for shape in &layout.shapes {
+        let bounds = Bounds::new(
+            to_window(shape.bounds.origin),
+            size(px(shape.bounds.size.width), px(shape.bounds.size.height)),
+        );
+        let (corner_radius, background) = match shape.kind {
+            ShapeKind::Node(NodeShape::Rhombus) => {
+                let center = bounds.center();
+                let corners = [
+                    point(center.x, bounds.top()),
+                    point(bounds.right(), center.y),
+                    point(center.x, bounds.bottom()),
+                    point(bounds.left(), center.y),
+                ];
+                paint_polygon(&corners, colors.node_background, colors.line, window);
+                continue;
+            }
+            ShapeKind::Node(NodeShape::Rectangle) => (px(0.), colors.node_background),
+            ShapeKind::Node(NodeShape::Rounded) => (px(6.), colors.node_background),
+            ShapeKind::Node(NodeShape::Stadium) => {
+                (bounds.size.height / 2., colors.node_background)
+            }
+            ShapeKind::Node(NodeShape::Circle) => (bounds.size.width / 2., colors.node_background),
+            ShapeKind::Note => (px(2.), colors.note_background),
+            ShapeKind::Frame => (px(0.), gpui::transparent_black()),
+        };
+        window.paint_quad(quad(bounds, corner_radius, background, px(1.), colors.line));
+    }
+}
+
+fn paint_polygon(corners: &[Point<Pixels>], background: Hsla, border: Hsla, window: &mut Window) {
+    let mut fill = PathBuilder::fill();
+    let mut stroke = PathBuilder::stroke(px(1.));
+    for builder in [&mut fill, &mut stroke] {
+        builder.move_to(corners[0]);
+        for corner in &corners[1..] {
+            builder.line_to(*corner);
+        }
+        builder.close();
+    }
+    if let Ok(path) = fill.build() {
+        window.paint_path(path, background);
+    }
+    if let Ok(path) = stroke.build() {
+        window.paint_path(path, border);
+    }
+}
+
+fn paint_diagram_line(
+    line: &LayoutLine,
+    to_window: &dyn Fn(Point<f32>) -> Point<Pixels>,
+    color: Hsla,
+    window: &mut Window,
+) {
+    let [.., before_end, end] = line.points.as_slice() else {
+        return;
+    };
+    let (end, before_end) = (*end, *before_end);
+    let length = (end.x - before_end.x).hypot(end.y - before_end.y);
+    let direction = if length > 0. {
+        point(
+            (end.x - before_end.x) / length,
+            (end.y - before_end.y) / length,
+        )
+    } else {
+        point(1., 0.)
+    };
+    let normal = point(-direction.y, direction.x);
+    // A point relative to the end of the line, `along` it backwards and `across` it.
+    let at = |along: f32, across: f32| {
+        to_window(point(
+            end.x - direction.x * along + normal.x * across,
+            end.y - direction.y * along + normal.y * across,
+        ))
+    };
+
+    // Filled arrow heads cover the end of the line, which would otherwise poke through their tips.
+    let mut points = line.points.clone();
+    if line.arrow == ArrowHead::Filled && length > ARROW_SIZE {
+        if let Some(last) = points.last_mut() {
+            *last = point(
+                end.x - direction.x * ARROW_SIZE,
+                end.y - direction.y * ARROW_SIZE,
+            );
+        }
+    }
+
+    let width = if line.style == LineStyle::Thick {
+        px(2.5)
+    } else {
+        px(1.)
+    };
+    let mut builder = PathBuilder::stroke(width);
+    match line.style {
+        LineStyle::Dotted => {
+            for segment in points.windows(2) {
+                add_dashes(&mut builder, segment[0], segment[1], to_window);
+            }
+        }
+        LineStyle::Solid | LineStyle::Thick => {
+            builder.move_to(to_window(points[0]));
+            for p in &points[1..] {
+                builder.line_to(to_window(*p));
+            }
+        }
+    }
+    if let Ok(path) = builder.build() {
+        window.paint_path(path, color);
+    }
+
+    let half = ARROW_SIZE / 2.;
+    let arrow_head = match line.arrow {
+        ArrowHead::None => return,
+        ArrowHead::Filled => {
+            let mut builder = PathBuilder::fill();
+            builder.move_to(at(0., 0.));
+            builder.line_to(at(ARROW_SIZE, half));
+            builder.line_to(at(ARROW_SIZE, -half));
+            builder.close();
+            builder
+        }
+        ArrowHead::Open => {
+            let mut builder = PathBuilder::stroke(width);
+            builder.move_to(at(ARROW_SIZE, half));
+            builder.line_to(at(0., 0.));
+            builder.line_to(at(ARROW_SIZE, -half));
+            builder
+        }
+        ArrowHead::Cross => {
+            let mut builder = PathBuilder::stroke(width);
+            builder.move_to(at(0., half));
+            builder.line_to(at(ARROW_SIZE, -half));
+            builder.move_to(at(0., -half));
+            builder.line_to(at(ARROW_SIZE, half));
+            builder
+        }
+    };
+    if let Ok(path) = arrow_head.build() {
+        window.paint_path(path, color);
+    }
+}
+
+fn add_dashes(
+    builder: &mut PathBuilder,
+    start: Point<f32>,
+    end: Point<f32>,
+    to_window: &dyn Fn(Point<f32>) -> Point<Pixels>,
+) {
+    let length = (end.x - start.x).hypot(end.y - start.y);
+    if length == 0. {
+        return;
+    }
+    let along = |offset: f32| {
+        to_window(point(
+            start.x + (end.x - start.x) * offset / length,
+            start.y + (end.y - start.y) * offset / length,
+        ))
+    };
+    let mut offset = 0.;
+    while offset < length {
+        builder.move_to(along(offset));
+        builder.line_to(along((offset + DASH_LENGTH).min(length)));
+        offset += DASH_LENGTH + DASH_GAP;
+    }
+}
+
+/// The size of display math, relative to the size of the text.
+const DISPLAY_MATH_SCALE: f32 = 1.2;
+
+fn render_markdown_math(parsed: &ParsedMarkdownMath, cx: &mut RenderContext) -> AnyElement {
+    let expression = match &parsed.expression {
+        Ok(expression) => expression,
+        Err(error) => {
+            return v_flex()
+                .gap_1()
+                .child(
+                    div()
+                        .font_family(cx.buffer_font_family.clone())
+                        .px_3()
+                        .py_3()
+                        .bg(cx.code_block_background_color)
+                        .rounded_md()
+                        .child(format!("$${}$$", parsed.source)),
+                )
+                .child(
+                    Label::new(format!("Failed to render math: {error}"))
+                        .size(LabelSize::Small)
+                        .color(Color::Error),
+                )
+                .into_any();
+        }
+    };
+
+    let mut text_style = cx.text_style.clone();
+    text_style.color = cx.text_color;
+    let font_size = text_style.font_size.to_pixels(cx.rem_size);
+    let font_id = cx.text_system.resolve_font(&text_style.font());
+    let metrics = MathFontMetrics {
+        x_height: cx.text_system.x_height(font_id, font_size) / font_size,
+        cap_height: cx.text_system.cap_height(font_id, font_size) / font_size,
+        descent: cx.text_system.descent(font_id, font_size) / font_size,
+    };
+    let measure = |text: &str, font_size: f32, font: MathFont| {
+        cx.text_system
+            .layout_line(
+                text.to_string(),
+                px(font_size),
+                &[math_text_run(&text_style, font, text.len())],
+            )
+            .map_or(0., |line| line.width.0)
+    };
+    let (style, scale) = if parsed.display {
+        (MathStyle::Display, DISPLAY_MATH_SCALE)
+    } else {
+        (MathStyle::Text, 1.)
+    };
+    let layout = math::typeset_math(expression, style, scale * font_size.0, metrics, &measure);
+    let (ascent, descent) = (layout.ascent, layout.descent);
+
+    let glyphs = layout.glyphs.clone();
+    let color = cx.text_color;
+    let element = div().w(px(layout.width)).h(px(ascent + descent)).child(
+        canvas(
+            move |_, window, _| shape_math_glyphs(&glyphs, &text_style, window),
+            move |bounds, lines, window, cx| {
+                paint_math(&layout, lines, bounds.origin, color, window, cx)
+            },
+        )
+        .size_full(),
+    );
+
+    if parsed.display {
+        h_flex()
+            .w_full()
+            .justify_center()
+            .py_2()
+            .child(element)
+            .into_any()
+    } else {
+        // Inline math is centered on the line like the words around it, and padded so
+        // that its baseline lines up with theirs.
+        let line_height = cx.text_style.line_height_in_pixels(cx.rem_size).0;
+        let baseline_below_center = (cx.text_system.ascent(font_id, font_size)
+            - cx.text_system.descent(font_id, font_size))
+        .0 / 2.;
+        let half_height = (ascent - baseline_below_center)
+            .max(descent + baseline_below_center)
+            .max(line_height / 2.);
+        div()
+            .pt(px(half_height + baseline_below_center - ascent))
+            .pb(px(half_height - baseline_below_center - descent))
+            .child(element)
+            .into_any()
+    }
+}
+
+fn math_text_run(text_style: &TextStyle, font: MathFont, len: usize) -> TextRun {
+    text_style
+        .clone()
+        .highlight(HighlightStyle {
+            font_style: font.italic.then_some(FontStyle::Italic),
+            font_weight: font.bold.then_some(FontWeight::BOLD),
+            ..Default::default()
+        })
+        .to_run(len)
+}
+
+fn shape_math_glyphs(
+    glyphs: &[MathGlyph],
+    text_style: &TextStyle,
+    window: &mut Window,
+) -> Vec<Option<ShapedLine>> {
+    glyphs
+        .iter()
+        .map(|glyph| {
+            window
+                .text_system()
+                .shape_line(
+                    glyph.text.clone().into(),
+                    px(glyph.font_size),
+                    &[math_text_run(text_style, glyph.font, glyph.text.len())],
+                )
+                .log_err()
+        })
+        .collect()
+}
+
+fn paint_math(
+    layout: &MathLayout,
+    lines: Vec<Option<ShapedLine>>,
+    origin: Point<Pixels>,
+    color: Hsla,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let to_window = |p: Point<f32>| point(origin.x + px(p.x), origin.y + px(layout.ascent + p.y));
+
+    for (glyph, line) in layout.glyphs.iter().zip(lines) {
+        let Some(line) = line else {
+            continue;
+        };
+        let baseline = to_window(glyph.origin);
+        line.paint(
+            point(baseline.x, baseline.y - line.ascent),
+            line.ascent + line.descent,
+            window,
+            cx,
+        )
+        .log_err();
+    }
+
+    for rule in &layout.rules {
+        let bounds = Bounds::new(
+            to_window(rule.origin),
+            size(px(rule.size.width), px(rule.size.height)),
+        );
+        window.paint_quad(fill(bounds, color));
+    }
+
+    for stroke in &layout.strokes {
+        let [first, rest @ ..] = stroke.as_slice() else {
+            continue;
+        };
+        let mut builder = PathBuilder::stroke(px(layout.rule_thickness));
+        builder.move_to(to_window(*first));
+        for p in rest {
+            builder.line_to(to_window(*p));
+        }
+        if let Ok(path) = builder.build() {
+            window.paint_path(path, color);
+        }
+    }
+}
+
 fn render_markdown_paragraph(parsed: &MarkdownParagraph, cx: &mut RenderContext) -> AnyElement {
     cx.with_common_p(div())
         .children(render_markdown_text(parsed, cx))
@@ -421,91 +933,115 @@ fn render_markdown_paragraph(parsed: &MarkdownParagraph, cx: &mut RenderContext)
 
 fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext) -> Vec<AnyElement> {
     let mut any_element = vec![];
+    // Text is split into words that wrap around inline math.
+    let wrap_words = parsed_new
+        .iter()
+        .any(|chunk| matches!(chunk, MarkdownParagraphChunk::Math(math) if !math.display));
     // these values are cloned in-order satisfy borrow checker
     let syntax_theme = cx.syntax_theme.clone();
     let workspace_clone = cx.workspace.clone();
+    let link_clicked_callback = cx.link_clicked_callback.clone();
     let code_span_bg_color = cx.code_span_background_color;
     let text_style = cx.text_style.clone();
 
     for parsed_region in parsed_new {
         match parsed_region {
             MarkdownParagraphChunk::Text(parsed) => {
-                let element_id = cx.next_id(&parsed.source_range);
-
-                let highlights = gpui::combine_highlights(
-                    parsed.highlights.iter().filter_map(|(range, highlight)| {
-                        highlight
-                            .to_highlight_style(&syntax_theme)
-                            .map(|style| (range.clone(), style))
-                    }),
-                    parsed.regions.iter().zip(&parsed.region_ranges).filter_map(
-                        |(region, range)| {
-                            if region.code {
-                                Some((
-                                    range.clone(),
-                                    HighlightStyle {
-                                        background_color: Some(code_span_bg_color),
-                                        ..Default::default()
-                                    },
-                                ))
-                            } else {
-                                None
-                            }
-                        },
-                    ),
-                );
-                let mut links = Vec::new();
-                let mut link_ranges = Vec::new();
-                for (range, region) in parsed.region_ranges.iter().zip(&parsed.regions) {
-                    if let Some(link) = region.link.clone() {
-                        links.push(link);
-                        link_ranges.push(range.clone());
+                let split_words;
+                let words = if wrap_words {
+                    split_words = parsed.split_words();
+                    split_words.as_slice()
+                } else {
+                    std::slice::from_ref(parsed)
+                };
+                for parsed in words {
+                    let element_id = cx.next_id(&parsed.source_range);
+
+                    let highlights = gpui::combine_highlights(
+                        parsed.highlights.iter().filter_map(|(range, highlight)| {
+                            highlight
+                                .to_highlight_style(&syntax_theme)
+                                .map(|style| (range.clone(), style))
+                        }),
+                        parsed.regions.iter().zip(&parsed.region_ranges).filter_map(
+                            |(region, range)| {
+                                if region.code {
+                                    Some((
+                                        range.clone(),
+                                        HighlightStyle {
+                                            background_color: Some(code_span_bg_color),
+                                            ..Default::default()
+                                        },
+                                    ))
+                                } else {
+                                    None
+                                }
+                            },
+                        ),
+                    );
+                    let mut links = Vec::new();
+                    let mut link_ranges = Vec::new();
+                    for (range, region) in parsed.region_ranges.iter().zip(&parsed.regions) {
+                        if let Some(link) = region.link.clone() {
+                            links.push(link);
+                            link_ranges.push(range.clone());
+                        }
                     }
-                }
-                let workspace = workspace_clone.clone();
-                let element = div()
-                    .child(
-                        InteractiveText::new(
-                            element_id,
-                            StyledText::new(parsed.contents.clone())
-                                .with_highlights(&text_style, highlights),
-                        )
-                        .tooltip({
-                            let links = links.clone();
-                            let link_ranges = link_ranges.clone();
-                            move |idx, _, cx| {
-                                for (ix, range) in link_ranges.iter().enumerate() {
-                                    if range.contains(&idx) {
-                                        return Some(LinkPreview::new(&links[ix].to_string(), cx));
+                    let workspace = workspace_clone.clone();
+                    let link_clicked_callback = link_clicked_callback.clone();
+                    let element = div()
+                        .child(
+                            InteractiveText::new(
+                                element_id,
+                                StyledText::new(parsed.contents.clone())
+                                    .with_highlights(&text_style, highlights),
+                            )
+                            .tooltip({
+                                let links = links.clone();
+                                let link_ranges = link_ranges.clone();
+                                move |idx, _, cx| {
+                                    for (ix, range) in link_ranges.iter().enumerate() {
+                                        if range.contains(&idx) {
+                                            return Some(LinkPreview::new(
+                                                &links[ix].to_string(),
+                                                cx,
+                                            ));
+                                        }
                                     }
+                                    None
                                 }
-                                None
-                            }
-                        })
-                        .on_click(
-                            link_ranges,
-                            move |clicked_range_ix, window, cx| match &links[clicked_range_ix] {
-                                Link::Web { url } => cx.open_url(url),
-                                Link::Path { path, .. } => {
-                                    if let Some(workspace) = &workspace {
-                                        _ = workspace.update(cx, |workspace, cx| {
-                                            workspace
-                                                .open_abs_path(path.clone(), false, window, cx)
-                                                .detach();
-                                        });
+                            })
+                            .on_click(
+                                link_ranges,
+                                move |clicked_range_ix, window, cx| match &links[clicked_range_ix] {
+                                    Link::Web { url } => cx.open_url(url),
+                                    Link::Path { path, .. } => {
+                                        if let Some(workspace) = &workspace {
+                                            _ = workspace.update(cx, |workspace, cx| {
+                                                workspace
+                                                    .open_abs_path(path.clone(), false, window, cx)
+                                                    .detach();
+                                            });
+                                        }
                                     }
-                                }
-                            },
-                        ),
-                    )
-                    .into_any();
-                any_element.push(element);
+                                    link => {
+                                        if let Some(callback) = &link_clicked_callback {
+                                            callback(link, window, cx);
+                                        }
+                                    }
+                                },
+                            ),
+                        )
+                        .into_any();
+                    any_element.push(element);
+                }
             }
 
             MarkdownParagraphChunk::Image(image) => {
                 let image_resource = match image.link.clone() {
                     Link::Web { url } => Resource::Uri(url.into()),
                     Link::Path { path, .. } => Resource::Path(Arc::from(path)),
+                    _ => continue,
                 };
 
                 let element_id = cx.next_id(&image.source_range);
@@ -546,6 +1082,7 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                                             });
                                         }
                                     }
+                                    _ => {}
                                 }
                             }
                         }
@@ -553,10 +1090,18 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                     .into_any();
                 any_element.push(image_element);
             }
+
+            MarkdownParagraphChunk::Math(math) => {
+                any_element.push(render_markdown_math(math, cx));
+            }
         }
     }
 
-    any_element
+    if wrap_words {
+        vec![h_flex().flex_wrap().children(any_element).into_any()]
+    } else {
+        any_element
+    }
 }
 
 fn render_markdown_rule(cx: &mut RenderContext) -> AnyElement {
@@ -0,0 +1,1321 @@
+//! Parsing and typesetting of the subset of TeX math that the Markdown preview supports.
+//!
+//! Expressions are typeset into a [`MathLayout`] of positioned glyphs, rules and
+//! strokes, following the spacing rules of TeX, which `markdown_renderer` paints.
+//! Inline math is typeset in text style, so that it fits within a line of text,
+//! and display math in display style.
+
+use std::fmt;
+
+use gpui::{point, size, Bounds, Point};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathSymbolKind {
+    /// A single letter, rendered in italics.
+    Variable,
+    Number,
+    Operator,
+    /// An operator whose limits are placed above and below it in display math, such as `\sum`.
+    LargeOperator,
+    /// A function name, such as `\sin` or `\lim`.
+    Function,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MathNode {
+    Symbol {
+        text: String,
+        kind: MathSymbolKind,
+    },
+    Group(Vec<MathNode>),
+    Scripts {
+        base: Box<MathNode>,
+        subscript: Option<Box<MathNode>>,
+        superscript: Option<Box<MathNode>>,
+    },
+    Fraction {
+        numerator: Box<MathNode>,
+        denominator: Box<MathNode>,
+    },
+    Root {
+        index: Option<Box<MathNode>>,
+        radicand: Box<MathNode>,
+    },
+    Accent {
+        accent: MathAccent,
+        base: Box<MathNode>,
+    },
+    Bold(Box<MathNode>),
+    /// Upright text, from `\text`, `\mathrm` or `\operatorname`.
+    Text(String),
+    /// Horizontal space, in ems.
+    Space(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathAccent {
+    Hat,
+    Bar,
+    Vector,
+    Dot,
+    DoubleDot,
+    Tilde,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MathError {
+    pub message: String,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for MathError {}
+
+/// How an expression is typeset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathStyle {
+    /// On a line of its own, with limits above and below large operators.
+    Display,
+    /// Within a line of text, with smaller fractions and limits beside large operators.
+    Text,
+}
+
+/// The style of the font that a glyph is drawn in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MathFont {
+    pub italic: bool,
+    pub bold: bool,
+}
+
+/// The vertical metrics of the font that math is typeset in, relative to its size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MathFontMetrics {
+    pub x_height: f32,
+    pub cap_height: f32,
+    pub descent: f32,
+}
+
+/// A typeset expression. Coordinates are in pixels, relative to the start of its baseline.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MathLayout {
+    pub width: f32,
+    /// How far the expression extends above its baseline.
+    pub ascent: f32,
+    /// How far the expression extends below its baseline.
+    pub descent: f32,
+    pub glyphs: Vec<MathGlyph>,
+    /// Filled rectangles, such as fraction bars.
+    pub rules: Vec<Bounds<f32>>,
+    /// Lines drawn with the rule thickness, such as radical signs and accents.
+    pub strokes: Vec<Vec<Point<f32>>>,
+    pub rule_thickness: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MathGlyph {
+    pub text: String,
+    /// The start of the glyph's baseline.
+    pub origin: Point<f32>,
+    pub font_size: f32,
+    pub font: MathFont,
+}
+
+impl MathLayout {
+    /// Adds another layout, with the start of its baseline at `origin`.
+    fn append(&mut self, other: MathLayout, origin: Point<f32>) {
+        self.width = self.width.max(origin.x + other.width);
+        self.ascent = self.ascent.max(other.ascent - origin.y);
+        self.descent = self.descent.max(other.descent + origin.y);
+        self.glyphs
+            .extend(other.glyphs.into_iter().map(|glyph| MathGlyph {
+                origin: glyph.origin + origin,
+                ..glyph
+            }));
+        self.rules.extend(
+            other
+                .rules
+                .into_iter()
+                .map(|rule| Bounds::new(rule.origin + origin, rule.size)),
+        );
+        self.strokes.extend(
+            other
+                .strokes
+                .into_iter()
+                .map(|stroke| stroke.into_iter().map(|p| p + origin).collect()),
+        );
+    }
+}
+
+/// Parses a TeX math expression, without its `$` delimiters.
+pub fn parse_math(source: &str) -> Result<MathNode, MathError> {
+    let mut parser = MathParser {
+        chars: source.chars().collect(),
+        cursor: 0,
+    };
+    let nodes = parser.parse_sequence(None)?;
+    Ok(MathNode::Group(nodes))
+}
+
+/// Typesets a parsed expression at the given font size, where `measure` returns
+/// the width of text at a font size.
+pub fn typeset_math(
+    node: &MathNode,
+    style: MathStyle,
+    font_size: f32,
+    metrics: MathFontMetrics,
+    measure: &dyn Fn(&str, f32, MathFont) -> f32,
+) -> MathLayout {
+    let typesetter = Typesetter {
+        metrics,
+        measure,
+        rule_thickness: (RULE_THICKNESS * font_size).max(1.),
+        min_font_size: MIN_SCRIPT_SCALE * font_size,
+    };
+    let mut layout = typesetter.node(
+        node,
+        Env {
+            font_size,
+            style,
+            script: false,
+            bold: false,
+        },
+    );
+    layout.rule_thickness = typesetter.rule_thickness;
+    layout
+}
+
+struct MathParser {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl MathParser {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, MathError> {
+        Err(MathError {
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.cursor).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.cursor += 1;
+        }
+    }
+
+    /// Parses atoms until the end of the input, or until `terminator` is reached (and consumed).
+    fn parse_sequence(&mut self, terminator: Option<char>) -> Result<Vec<MathNode>, MathError> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let Some(c) = self.peek() else {
+                return match terminator {
+                    Some(terminator) => self.error(format!("missing `{terminator}`")),
+                    None => Ok(nodes),
+                };
+            };
+
+            if Some(c) == terminator {
+                self.cursor += 1;
+                return Ok(nodes);
+            }
+
+            match c {
+                '}' => return self.error("unexpected `}`"),
+                '^' | '_' => {
+                    self.cursor += 1;
+                    let (base, mut subscript, mut superscript) = match nodes.pop() {
+                        Some(MathNode::Scripts {
+                            base,
+                            subscript,
+                            superscript,
+                        }) => (base, subscript, superscript),
+                        Some(node) => (Box::new(node), None, None),
+                        None => (Box::new(MathNode::Group(Vec::new())), None, None),
+                    };
+                    let script = Box::new(self.parse_argument()?);
+                    let slot = if c == '^' {
+                        &mut superscript
+                    } else {
+                        &mut subscript
+                    };
+                    if slot.is_some() {
+                        return self.error(if c == '^' {
+                            "double superscript"
+                        } else {
+                            "double subscript"
+                        });
+                    }
+                    *slot = Some(script);
+                    nodes.push(MathNode::Scripts {
+                        base,
+                        subscript,
+                        superscript,
+                    });
+                }
+                '\'' => {
+                    self.cursor += 1;
+                    nodes.push(operator("′"));
+                }
+                _ => {
+                    if let Some(node) = self.parse_atom()? {
+                        nodes.push(node);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses the argument of a command or script: a braced group, a command or a single character.
+    fn parse_argument(&mut self) -> Result<MathNode, MathError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => self.error("missing argument"),
+            Some('}') => self.error("missing argument"),
+            Some('{') => {
+                self.cursor += 1;
+                Ok(group(self.parse_sequence(Some('}'))?))
+            }
+            Some(_) => match self.parse_atom()? {
+                Some(node) => Ok(node),
+                None => self.parse_argument(),
+            },
+        }
+    }
+
+    fn parse_raw_text_argument(&mut self) -> Result<String, MathError> {
+        self.skip_whitespace();
+        if self.peek() != Some('{') {
+            return self.error("expected `{`");
+        }
+        self.cursor += 1;
+        let mut depth = 0;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.cursor += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(text),
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        self.error("missing `}`")
+    }
+
+    /// Parses a single atom. Returns `None` for constructs that produce no output, such as `\left`.
+    fn parse_atom(&mut self) -> Result<Option<MathNode>, MathError> {
+        let Some(c) = self.peek() else {
+            return self.error("unexpected end of expression");
+        };
+        self.cursor += 1;
+
+        let node = match c {
+            '{' => group(self.parse_sequence(Some('}'))?),
+            '\\' => return self.parse_command(),
+            '~' => MathNode::Space(0.3),
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::from(c);
+                while let Some(c) = self.peek().filter(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                    self.cursor += 1;
+                }
+                MathNode::Symbol {
+                    text: number,
+                    kind: MathSymbolKind::Number,
+                }
+            }
+            c if c.is_alphabetic() => MathNode::Symbol {
+                text: c.to_string(),
+                kind: MathSymbolKind::Variable,
+            },
+            '&' | '#' | '%' => return self.error(format!("unsupported character `{c}`")),
+            c => operator(&c.to_string()),
+        };
+        Ok(Some(node))
+    }
+
+    fn parse_command(&mut self) -> Result<Option<MathNode>, MathError> {
+        let Some(first) = self.peek() else {
+            return self.error("expected a command after `\\`");
+        };
+        self.cursor += 1;
+        let name = if first.is_ascii_alphabetic() {
+            let mut name = String::from(first);
+            while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+                name.push(c);
+                self.cursor += 1;
+            }
+            name
+        } else {
+            first.to_string()
+        };
+
+        let node = match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => MathNode::Fraction {
+                numerator: Box::new(self.parse_argument()?),
+                denominator: Box::new(self.parse_argument()?),
+            },
+            "sqrt" => {
+                self.skip_whitespace();
+                let index = if self.peek() == Some('[') {
+                    self.cursor += 1;
+                    Some(Box::new(group(self.parse_sequence(Some(']'))?)))
+                } else {
+                    None
+                };
+                MathNode::Root {
+                    index,
+                    radicand: Box::new(self.parse_argument()?),
+                }
+            }
+            "text" | "textrm" | "mathrm" | "operatorname" | "textit" | "mbox" => {
+                MathNode::Text(self.parse_raw_text_argument()?)
+            }
+            "mathbf" | "boldsymbol" | "textbf" => MathNode::Bold(Box::new(self.parse_argument()?)),
+            "mathit" | "mathcal" | "mathsf" | "mathtt" => self.parse_argument()?,
+            "mathbb" => {
+                let text = self.parse_raw_text_argument()?;
+                let text = text
+                    .chars()
+                    .map(|c| double_struck(c).unwrap_or(c))
+                    .collect();
+                MathNode::Symbol {
+                    text,
+                    kind: MathSymbolKind::Variable,
+                }
+            }
+            "hat" | "widehat" => self.parse_accent(MathAccent::Hat)?,
+            "bar" | "overline" => self.parse_accent(MathAccent::Bar)?,
+            "vec" | "overrightarrow" => self.parse_accent(MathAccent::Vector)?,
+            "dot" => self.parse_accent(MathAccent::Dot)?,
+            "ddot" => self.parse_accent(MathAccent::DoubleDot)?,
+            "tilde" | "widetilde" => self.parse_accent(MathAccent::Tilde)?,
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl"
+            | "Bigr" => {
+                self.skip_whitespace();
+                match self.peek() {
+                    Some('.') => {
+                        self.cursor += 1;
+                        return Ok(None);
+                    }
+                    Some(_) => return self.parse_atom(),
+                    None => return self.error(format!("missing delimiter after `\\{name}`")),
+                }
+            }
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => return Ok(None),
+            "," => MathNode::Space(0.17),
+            ":" | ">" => MathNode::Space(0.22),
+            ";" => MathNode::Space(0.28),
+            " " => MathNode::Space(0.3),
+            "quad" => MathNode::Space(1.),
+            "qquad" => MathNode::Space(2.),
+            "!" => return Ok(None),
+            "{" | "}" | "|" | "#" | "$" | "%" | "&" | "_" => {
+                operator(if name == "|" { "‖" } else { &name })
+            }
+            "\\" => return self.error("line breaks are not supported"),
+            "begin" => {
+                let environment = self.parse_raw_text_argument()?;
+                return self.error(format!("the `{environment}` environment is not supported"));
+            }
+            name => {
+                if let Some(text) = large_operator(name) {
+                    MathNode::Symbol {
+                        text: text.to_string(),
+                        kind: MathSymbolKind::LargeOperator,
+                    }
+                } else if FUNCTIONS.contains(&name) {
+                    MathNode::Symbol {
+                        text: name.to_string(),
+                        kind: MathSymbolKind::Function,
+                    }
+                } else if let Some(text) = greek_letter(name) {
+                    MathNode::Symbol {
+                        text: text.to_string(),
+                        kind: MathSymbolKind::Variable,
+                    }
+                } else if let Some(text) = symbol(name) {
+                    operator(text)
+                } else {
+                    return self.error(format!("unknown command `\\{name}`"));
+                }
+            }
+        };
+        Ok(Some(node))
+    }
+
+    fn parse_accent(&mut self, accent: MathAccent) -> Result<MathNode, MathError> {
+        Ok(MathNode::Accent {
+            accent,
+            base: Box::new(self.parse_argument()?),
+        })
+    }
+}
+
+fn operator(text: &str) -> MathNode {
+    MathNode::Symbol {
+        text: text.to_string(),
+        kind: MathSymbolKind::Operator,
+    }
+}
+
+/// Unwraps groups of a single node, so that `x^{2}` and `x^2` parse the same.
+fn group(mut nodes: Vec<MathNode>) -> MathNode {
+    if nodes.len() == 1 {
+        nodes.pop().unwrap()
+    } else {
+        MathNode::Group(nodes)
+    }
+}
+
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "det", "dim", "ker", "deg", "arg", "gcd", "lim", "max", "min", "sup",
+    "inf", "Pr",
+];
+
+fn large_operator(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "bigcup" => "⋃",
+        "bigcap" => "⋂",
+        "bigoplus" => "⨁",
+        "bigotimes" => "⨂",
+        _ => return None,
+    })
+}
+
+fn greek_letter(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        _ => return None,
+    })
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "times" => "×",
+        "cdot" => "⋅",
+        "pm" => "±",
+        "mp" => "∓",
+        "div" => "÷",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "emptyset" | "varnothing" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "prime" => "′",
+        "angle" => "∠",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "lvert" | "rvert" | "vert" => "|",
+        "lVert" | "rVert" | "Vert" => "‖",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        _ => return None,
+    })
+}
+
+fn double_struck(c: char) -> Option<char> {
+    Some(match c {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        'A'..='Z' => char::from_u32('𝔸' as u32 + (c as u32 - 'A' as u32))?,
+        _ => return None,
+    })
+}
+
+/// Returns whether the given operator is a relation or binary operator that
+/// is surrounded by spaces.
+fn is_relation(symbol: &str) -> bool {
+    matches!(
+        symbol,
+        "=" | "<"
+            | ">"
+            | "+"
+            | "-"
+            | "×"
+            | "⋅"
+            | "±"
+            | "∓"
+            | "÷"
+            | "≤"
+            | "≥"
+            | "≠"
+            | "≪"
+            | "≫"
+            | "≈"
+            | "≡"
+            | "∼"
+            | "≃"
+            | "≅"
+            | "∝"
+            | "∈"
+            | "∉"
+            | "⊂"
+            | "⊆"
+            | "⊃"
+            | "⊇"
+            | "∪"
+            | "∩"
+            | "→"
+            | "←"
+            | "↔"
+            | "⇒"
+            | "⇐"
+            | "⇔"
+            | "↦"
+    )
+}
+
+/// The size of scripts, relative to their base.
+const SCRIPT_SCALE: f32 = 0.7;
+/// The smallest size of scripts, relative to the size of the expression.
+const MIN_SCRIPT_SCALE: f32 = 0.5;
+/// The size of large operators such as `\sum`, relative to the surrounding text.
+const DISPLAY_OPERATOR_SCALE: f32 = 1.6;
+const TEXT_OPERATOR_SCALE: f32 = 1.2;
+/// The thickness of fraction bars and radical signs, relative to the font size.
+const RULE_THICKNESS: f32 = 0.05;
+/// The spaces around binary operators and relations, after function names and
+/// large operators, after scripts and on either side of fractions, in ems.
+const OPERATOR_SPACE: f32 = 0.25;
+const THIN_SPACE: f32 = 0.17;
+const SCRIPT_SPACE: f32 = 0.05;
+const NULL_DELIMITER_SPACE: f32 = 0.12;
+
+/// Characters that don't extend above the x-height.
+const SHORT_CHARS: &str = "acemnorsuvwxzıαεϵικνοπσςτυωγημρχψ+−=×⋅±∓÷<>≤≥≠≈∼≃≡∝∗∘∙~:,.";
+/// Characters that extend below the baseline.
+const DEEP_CHARS: &str = "fgjpqyβγζημξρςφϕχψ,;()[]{}|‖/∫∬∭∮∑∏∐⋃⋂⨁⨂⌊⌋⌈⌉⟨⟩∣∥";
+
+#[derive(Clone, Copy)]
+struct Env {
+    font_size: f32,
+    style: MathStyle,
+    /// Whether this is within a script, where operators aren't spaced.
+    script: bool,
+    bold: bool,
+}
+
+struct Typesetter<'a> {
+    metrics: MathFontMetrics,
+    measure: &'a dyn Fn(&str, f32, MathFont) -> f32,
+    rule_thickness: f32,
+    min_font_size: f32,
+}
+
+impl Typesetter<'_> {
+    fn node(&self, node: &MathNode, env: Env) -> MathLayout {
+        match node {
+            MathNode::Symbol { text, kind } => self.symbol(text, *kind, env),
+            MathNode::Group(nodes) => self.sequence(nodes, env),
+            MathNode::Scripts {
+                base,
+                subscript,
+                superscript,
+            } => self.scripts(base, subscript.as_deref(), superscript.as_deref(), env),
+            MathNode::Fraction {
+                numerator,
+                denominator,
+            } => self.fraction(numerator, denominator, env),
+            MathNode::Root { index, radicand } => self.root(index.as_deref(), radicand, env),
+            MathNode::Accent { accent, base } => self.accent(*accent, base, env),
+            MathNode::Bold(node) => self.node(node, Env { bold: true, ..env }),
+            MathNode::Text(text) => self.glyph(
+                text,
+                env.font_size,
+                MathFont {
+                    italic: false,
+                    bold: env.bold,
+                },
+            ),
+            MathNode::Space(width) => MathLayout {
+                width: width * env.font_size,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// The height of the math axis above the baseline, which fractions and large
+    /// operators are centered on.
+    fn axis(&self, font_size: f32) -> f32 {
+        self.metrics.x_height / 2. * font_size
+    }
+
+    fn script_env(&self, env: Env) -> Env {
+        Env {
+            font_size: (SCRIPT_SCALE * env.font_size).max(self.min_font_size),
+            style: MathStyle::Text,
+            script: true,
+            ..env
+        }
+    }
+
+    fn glyph(&self, text: &str, font_size: f32, font: MathFont) -> MathLayout {
+        if text.is_empty() {
+            return MathLayout::default();
+        }
+        let ascent = if text.chars().all(|c| SHORT_CHARS.contains(c)) {
+            self.metrics.x_height
+        } else {
+            self.metrics.cap_height
+        };
+        let descent = if text.chars().any(|c| DEEP_CHARS.contains(c)) {
+            self.metrics.descent
+        } else {
+            0.
+        };
+        MathLayout {
+            width: (self.measure)(text, font_size, font),
+            ascent: ascent * font_size,
+            descent: descent * font_size,
+            glyphs: vec![MathGlyph {
+                text: text.to_string(),
+                origin: point(0., 0.),
+                font_size,
+                font,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn symbol(&self, text: &str, kind: MathSymbolKind, env: Env) -> MathLayout {
+        let font = MathFont {
+            italic: kind == MathSymbolKind::Variable,
+            bold: env.bold,
+        };
+        match kind {
+            MathSymbolKind::LargeOperator => {
+                let scale = match env.style {
+                    MathStyle::Display => DISPLAY_OPERATOR_SCALE,
+                    MathStyle::Text => TEXT_OPERATOR_SCALE,
+                };
+                let glyph = self.glyph(text, scale * env.font_size, font);
+                let shift = (glyph.ascent - glyph.descent) / 2. - self.axis(env.font_size);
+                let mut layout = MathLayout::default();
+                layout.append(glyph, point(0., shift));
+                layout
+            }
+            MathSymbolKind::Operator if text == "-" => self.glyph("−", env.font_size, font),
+            _ => self.glyph(text, env.font_size, font),
+        }
+    }
+
+    fn sequence(&self, nodes: &[MathNode], env: Env) -> MathLayout {
+        let mut layout = MathLayout::default();
+        let mut x = 0.;
+        for (ix, node) in nodes.iter().enumerate() {
+            // Operators at the start of an expression or after an opening delimiter,
+            // such as the minus in `-x` or `(-1)`, are unary and not spaced.
+            let is_binary = !env.script
+                && is_spaced_operator(node)
+                && ix > 0
+                && !is_spaced_operator(&nodes[ix - 1])
+                && !is_opening_delimiter(&nodes[ix - 1]);
+            let space = if is_binary {
+                OPERATOR_SPACE * env.font_size
+            } else {
+                0.
+            };
+            let child = self.node(node, env);
+            let width = child.width;
+            layout.append(child, point(x + space, 0.));
+            x += width + 2. * space;
+            if is_operator_atom(node) {
+                x += THIN_SPACE * env.font_size;
+            }
+        }
+        layout.width = layout.width.max(x);
+        layout
+    }
+
+    fn scripts(
+        &self,
+        base: &MathNode,
+        subscript: Option<&MathNode>,
+        superscript: Option<&MathNode>,
+        env: Env,
+    ) -> MathLayout {
+        let base_layout = self.node(base, env);
+        let script_env = self.script_env(env);
+        let subscript = subscript.map(|node| self.node(node, script_env));
+        let superscript = superscript.map(|node| self.node(node, script_env));
+        if env.style == MathStyle::Display && has_limits(base) {
+            return self.limits(base_layout, subscript, superscript, env);
+        }
+
+        let font_size = env.font_size;
+        let x_height = self.metrics.x_height * font_size;
+        let mut shift_up = 0.4 * font_size;
+        let mut shift_down = if superscript.is_some() { 0.25 } else { 0.15 } * font_size;
+        // Scripts of anything but a single symbol are placed relative to its extent.
+        if !matches!(base, MathNode::Symbol { .. }) {
+            shift_up = shift_up.max(base_layout.ascent - 0.39 * script_env.font_size);
+            shift_down = shift_down.max(base_layout.descent + 0.05 * script_env.font_size);
+        }
+        if let Some(superscript) = &superscript {
+            shift_up = shift_up.max(superscript.descent + x_height / 4.);
+        }
+        if let Some(subscript) = &subscript {
+            shift_down = shift_down.max(subscript.ascent - 0.8 * x_height);
+        }
+        if let (Some(subscript), Some(superscript)) = (&subscript, &superscript) {
+            let gap = (shift_up - superscript.descent) - (subscript.ascent - shift_down);
+            let min_gap = 4. * self.rule_thickness;
+            if gap < min_gap {
+                shift_down += min_gap - gap;
+            }
+        }
+
+        let base_width = base_layout.width;
+        let mut layout = MathLayout::default();
+        layout.append(base_layout, point(0., 0.));
+        let mut scripts_width: f32 = 0.;
+        for (script, shift) in [(superscript, -shift_up), (subscript, shift_down)] {
+            if let Some(script) = script {
+                scripts_width = scripts_width.max(script.width);
+                layout.append(script, point(base_width, shift));
+            }
+        }
+        layout.width = base_width + scripts_width + SCRIPT_SPACE * font_size;
+        layout
+    }
+
+    /// Places scripts centered above and below their base, as for `\sum` in display math.
+    fn limits(
+        &self,
+        base: MathLayout,
+        subscript: Option<MathLayout>,
+        superscript: Option<MathLayout>,
+        env: Env,
+    ) -> MathLayout {
+        let gap = 0.15 * env.font_size;
+        let width = [Some(&base), subscript.as_ref(), superscript.as_ref()]
+            .into_iter()
+            .flatten()
+            .fold(0_f32, |width, layout| width.max(layout.width));
+        let (base_ascent, base_descent) = (base.ascent, base.descent);
+
+        let mut layout = MathLayout::default();
+        let base_x = (width - base.width) / 2.;
+        layout.append(base, point(base_x, 0.));
+        if let Some(superscript) = superscript {
+            let origin = point(
+                (width - superscript.width) / 2.,
+                -(base_ascent + gap + superscript.descent),
+            );
+            layout.append(superscript, origin);
+        }
+        if let Some(subscript) = subscript {
+            let origin = point(
+                (width - subscript.width) / 2.,
+                base_descent + gap + subscript.ascent,
+            );
+            layout.append(subscript, origin);
+        }
+        layout
+    }
+
+    fn fraction(&self, numerator: &MathNode, denominator: &MathNode, env: Env) -> MathLayout {
+        let display = env.style == MathStyle::Display;
+        // The parts of display fractions are in text style, while those of text
+        // fractions are shrunk like scripts.
+        let part_env = if display {
+            Env {
+                style: MathStyle::Text,
+                ..env
+            }
+        } else {
+            self.script_env(env)
+        };
+        let numerator = self.node(numerator, part_env);
+        let denominator = self.node(denominator, part_env);
+
+        let font_size = env.font_size;
+        let thickness = self.rule_thickness;
+        let axis = self.axis(font_size);
+        let gap = if display { 3. * thickness } else { thickness }.max(0.08 * font_size);
+        let (min_shift_up, min_shift_down) = if display { (0.68, 0.69) } else { (0.39, 0.34) };
+        let shift_up =
+            (axis + thickness / 2. + gap + numerator.descent).max(min_shift_up * font_size);
+        let shift_down =
+            (denominator.ascent + gap + thickness / 2. - axis).max(min_shift_down * font_size);
+
+        let padding = NULL_DELIMITER_SPACE * font_size;
+        let inner_width = numerator.width.max(denominator.width);
+        let mut layout = MathLayout::default();
+        let numerator_x = padding + (inner_width - numerator.width) / 2.;
+        let denominator_x = padding + (inner_width - denominator.width) / 2.;
+        layout.append(numerator, point(numerator_x, -shift_up));
+        layout.append(denominator, point(denominator_x, shift_down));
+        layout.rules.push(Bounds::new(
+            point(padding / 2., -axis - thickness / 2.),
+            size(inner_width + padding, thickness),
+        ));
+        layout.width = inner_width + 2. * padding;
+        layout
+    }
+
+    fn root(&self, index: Option<&MathNode>, radicand: &MathNode, env: Env) -> MathLayout {
+        let font_size = env.font_size;
+        let thickness = self.rule_thickness;
+        let radicand = self.node(radicand, env);
+        let clearance = thickness + 0.1 * font_size;
+        // The radical sign reaches from just below the radicand to the bar over it.
+        let top = -(radicand.ascent + clearance + thickness / 2.);
+        let bottom = radicand.descent + 0.05 * font_size;
+        let height = bottom - top;
+        let sign_width = 0.45 * font_size + 0.1 * height;
+        let tick = (0.35 * font_size).min(height / 2.);
+
+        let mut layout = MathLayout::default();
+        let mut sign_x = 0.;
+        if let Some(index) = index {
+            let index = self.node(index, self.script_env(self.script_env(env)));
+            sign_x = (index.width - 0.55 * sign_width).max(0.);
+            let index_y = bottom - 0.6 * height - index.descent;
+            layout.append(index, point(0., index_y));
+        }
+        let radicand_x = sign_x + sign_width + 0.05 * font_size;
+        let end_x = radicand_x + radicand.width + 0.05 * font_size;
+        layout.append(radicand, point(radicand_x, 0.));
+        layout.strokes.push(vec![
+            point(sign_x, bottom - tick + 0.05 * font_size),
+            point(sign_x + 0.1 * font_size, bottom - tick),
+            point(sign_x + 0.4 * sign_width, bottom),
+            point(sign_x + sign_width, top),
+            point(end_x, top),
+        ]);
+        layout.width = end_x;
+        layout.ascent = layout.ascent.max(thickness / 2. - top);
+        layout.descent = layout.descent.max(bottom + thickness / 2.);
+        layout
+    }
+
+    fn accent(&self, accent: MathAccent, base: &MathNode, env: Env) -> MathLayout {
+        let font_size = env.font_size;
+        let thickness = self.rule_thickness;
+        let mut layout = self.node(base, env);
+        // Accents over italic letters follow their slant.
+        let skew = if matches!(
+            base,
+            MathNode::Symbol {
+                kind: MathSymbolKind::Variable,
+                ..
+            }
+        ) {
+            0.08 * font_size
+        } else {
+            0.
+        };
+        let center = layout.width / 2. + skew;
+        let half_width = (0.45 * layout.width).max(0.2 * font_size);
+        let (left, right) = (center - half_width, center + half_width);
+        let height = 0.18 * font_size;
+        let bottom = -(layout.ascent + 0.1 * font_size);
+        let middle = bottom - height / 2.;
+        let top = bottom - height;
+
+        match accent {
+            MathAccent::Hat => layout.strokes.push(vec![
+                point(left, bottom),
+                point(center, top),
+                point(right, bottom),
+            ]),
+            MathAccent::Bar => layout
+                .strokes
+                .push(vec![point(left, middle), point(right, middle)]),
+            MathAccent::Vector => {
+                let head = height / 2.;
+                layout
+                    .strokes
+                    .push(vec![point(left, middle), point(right, middle)]);
+                layout.strokes.push(vec![
+                    point(right - head, top),
+                    point(right, middle),
+                    point(right - head, bottom),
+                ]);
+            }
+            MathAccent::Tilde => layout.strokes.push(
+                (0..=8)
+                    .map(|step| {
+                        let t = step as f32 / 8.;
+                        let y = middle - height / 2. * (t * std::f32::consts::TAU).sin();
+                        point(left + 2. * half_width * t, y)
+                    })
+                    .collect(),
+            ),
+            MathAccent::Dot | MathAccent::DoubleDot => {
+                let dot = 2.5 * thickness;
+                let centers = if accent == MathAccent::Dot {
+                    vec![center]
+                } else {
+                    vec![center - 0.12 * font_size, center + 0.12 * font_size]
+                };
+                for x in centers {
+                    layout.rules.push(Bounds::new(
+                        point(x - dot / 2., middle - dot / 2.),
+                        size(dot, dot),
+                    ));
+                }
+            }
+        }
+        layout.ascent = thickness - top;
+        layout.width = layout.width.max(right);
+        layout
+    }
+}
+
+fn is_spaced_operator(node: &MathNode) -> bool {
+    matches!(
+        node,
+        MathNode::Symbol {
+            text,
+            kind: MathSymbolKind::Operator,
+        } if is_relation(text)
+    )
+}
+
+fn is_opening_delimiter(node: &MathNode) -> bool {
+    matches!(
+        node,
+        MathNode::Symbol {
+            text,
+            kind: MathSymbolKind::Operator,
+        } if matches!(text.as_str(), "(" | "[" | "{" | "⟨" | "⌊" | "⌈" | ",")
+    )
+}
+
+/// Returns whether the node is a function name such as `\sin` or a large operator
+/// such as `\sum`, which are followed by a thin space.
+fn is_operator_atom(node: &MathNode) -> bool {
+    match node {
+        MathNode::Symbol { kind, .. } => matches!(
+            kind,
+            MathSymbolKind::Function | MathSymbolKind::LargeOperator
+        ),
+        MathNode::Scripts { base, .. } => is_operator_atom(base),
+        _ => false,
+    }
+}
+
+/// Returns whether the scripts of the given base are placed above and below it in
+/// display math, as for `\sum`.
+fn has_limits(base: &MathNode) -> bool {
+    match base {
+        MathNode::Symbol {
+            text,
+            kind: MathSymbolKind::LargeOperator,
+        } => !text.starts_with(['∫', '∬', '∭', '∮']),
+        MathNode::Symbol {
+            text,
+            kind: MathSymbolKind::Function,
+        } => matches!(
+            text.as_str(),
+            "lim" | "max" | "min" | "sup" | "inf" | "det" | "gcd" | "Pr"
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const METRICS: MathFontMetrics = MathFontMetrics {
+        x_height: 0.5,
+        cap_height: 0.7,
+        descent: 0.2,
+    };
+
+    /// Typesets at a font size of 10, in a font whose glyphs are all half an em wide.
+    fn typeset(source: &str, style: MathStyle) -> MathLayout {
+        typeset_math(
+            &parse_math(source).unwrap(),
+            style,
+            10.,
+            METRICS,
+            &|text, font_size, _| text.chars().count() as f32 * font_size / 2.,
+        )
+    }
+
+    /// The text, position and size of each glyph, rounded to hundredths.
+    fn glyphs(layout: &MathLayout) -> Vec<(&str, f32, f32, f32)> {
+        let round = |value: f32| (value * 100.).round() / 100.;
+        layout
+            .glyphs
+            .iter()
+            .map(|glyph| {
+                (
+                    glyph.text.as_str(),
+                    round(glyph.origin.x),
+                    round(glyph.origin.y),
+                    round(glyph.font_size),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_scripts_and_fractions() {
+        assert_eq!(
+            parse_math(r"\frac{a}{b^2}").unwrap(),
+            MathNode::Group(vec![MathNode::Fraction {
+                numerator: Box::new(MathNode::Symbol {
+                    text: "a".into(),
+                    kind: MathSymbolKind::Variable,
+                }),
+                denominator: Box::new(MathNode::Scripts {
+                    base: Box::new(MathNode::Symbol {
+                        text: "b".into(),
+                        kind: MathSymbolKind::Variable,
+                    }),
+                    subscript: None,
+                    superscript: Some(Box::new(MathNode::Symbol {
+                        text: "2".into(),
+                        kind: MathSymbolKind::Number,
+                    })),
+                }),
+            }])
+        );
+        assert_eq!(parse_math("x_1^2"), parse_math("x^{2}_{1}"));
+    }
+
+    #[test]
+    fn test_typeset_inline_math() {
+        let layout = typeset("E = mc^2", MathStyle::Text);
+        // The relation is surrounded by spaces and the superscript is raised and shrunk.
+        assert_eq!(
+            glyphs(&layout),
+            [
+                ("E", 0., 0., 10.),
+                ("=", 7.5, 0., 10.),
+                ("m", 15., 0., 10.),
+                ("c", 20., 0., 10.),
+                ("2", 25., -4., 7.),
+            ]
+        );
+        assert!(layout.glyphs[2].font.italic);
+        assert!(!layout.glyphs[4].font.italic);
+        assert_eq!(layout.width, 29.);
+        assert_eq!(layout.descent, 0.);
+
+        // Minus signs are unary at the start of an expression and binary between operands.
+        assert_eq!(
+            glyphs(&typeset("-a - b", MathStyle::Text)),
+            [
+                ("−", 0., 0., 10.),
+                ("a", 5., 0., 10.),
+                ("−", 12.5, 0., 10.),
+                ("b", 20., 0., 10.),
+            ]
+        );
+        // Operators within scripts aren't spaced.
+        assert_eq!(
+            glyphs(&typeset("x_{i+1}", MathStyle::Text)),
+            [
+                ("x", 0., 0., 10.),
+                ("i", 5., 1.5, 7.),
+                ("+", 8.5, 1.5, 7.),
+                ("1", 12., 1.5, 7.),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typeset_inline_and_display_styles() {
+        // Limits are placed beside large operators in inline math, and around them in display math.
+        let inline = typeset(r"\sum_{k=1}^n k", MathStyle::Text);
+        let display = typeset(r"\sum_{k=1}^n k", MathStyle::Display);
+        assert_eq!(
+            glyphs(&inline)
+                .iter()
+                .map(|glyph| glyph.1)
+                .collect::<Vec<_>>(),
+            [0., 6., 6., 9.5, 13., 18.7]
+        );
+        assert_eq!(
+            glyphs(&display)
+                .iter()
+                .map(|glyph| glyph.1)
+                .collect::<Vec<_>>(),
+            [1.25, 3.5, 0., 3.5, 7., 12.2]
+        );
+        assert_eq!(display.glyphs[0].font_size, 16.);
+        assert!(display.glyphs[1].origin.y < -display.glyphs[0].font_size / 2.);
+        assert!(display.glyphs[2].origin.y > 0.);
+
+        // Fractions in inline math are shrunk like scripts.
+        let inline = typeset(r"\frac{1}{2}", MathStyle::Text);
+        let display = typeset(r"\frac{1}{2}", MathStyle::Display);
+        assert_eq!(glyphs(&inline), [("1", 1.2, -4., 7.), ("2", 1.2, 3.9, 7.)]);
+        assert_eq!(
+            glyphs(&display),
+            [("1", 1.2, -6.8, 10.), ("2", 1.2, 8., 10.)]
+        );
+        // The bar is centered on the math axis and slightly wider than the parts.
+        assert_eq!(inline.rules.len(), 1);
+        let bar = inline.rules[0];
+        assert_eq!((bar.origin.y, bar.size.height), (-3., 1.));
+        assert!((bar.size.width - 4.7).abs() < 0.01);
+        assert!((display.width - 7.4).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_typeset_roots_and_accents() {
+        let layout = typeset(r"\sqrt{x}", MathStyle::Text);
+        assert_eq!(layout.strokes.len(), 1);
+        let sign = &layout.strokes[0];
+        let bar_y = sign.last().unwrap().y;
+        // The bar over the radicand clears it and ends after it.
+        assert!(bar_y < -layout.glyphs[0].font_size * METRICS.x_height);
+        assert!(sign.last().unwrap().x > layout.glyphs[0].origin.x + 5.);
+        assert_eq!(layout.ascent, 0.5 - bar_y);
+
+        let layout = typeset(r"\hat{x} + \vec{v}", MathStyle::Text);
+        assert_eq!(layout.strokes.len(), 3);
+        assert!(layout.strokes.iter().flatten().all(|p| p.y < -5.));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_math(r"\frac{a}").unwrap_err().message,
+            "missing argument"
+        );
+        assert_eq!(parse_math(r"{x").unwrap_err().message, "missing `}`");
+        assert_eq!(parse_math(r"x}").unwrap_err().message, "unexpected `}`");
+        assert_eq!(
+            parse_math(r"x^2^3").unwrap_err().message,
+            "double superscript"
+        );
+        assert_eq!(
+            parse_math(r"\foo").unwrap_err().message,
+            "unknown command `\\foo`"
+        );
+        assert_eq!(
+            parse_math(r"\begin{matrix} a \end{matrix}")
+                .unwrap_err()
+                .message,
+            "the `matrix` environment is not supported"
+        );
+    }
+}
@@ -0,0 +1,1619 @@
+//! Parsing and layout of the subset of Mermaid diagrams that the Markdown
+//! preview can render: flowcharts and sequence diagrams.
+//!
+//! A layout is computed from the measured sizes of the labels, and consists of
+//! shapes, lines and labels that the renderer paints as they are.
+
+use std::fmt;
+
+use gpui::{point, size, Bounds, Point, Size};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MermaidDiagram {
+    Flowchart(Flowchart),
+    Sequence(SequenceDiagram),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MermaidError {
+    /// The 1-based line of the diagram source where the error occurred.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for MermaidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for MermaidError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowchartDirection {
+    TopToBottom,
+    BottomToTop,
+    LeftToRight,
+    RightToLeft,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flowchart {
+    pub direction: FlowchartDirection,
+    pub nodes: Vec<FlowchartNode>,
+    pub edges: Vec<FlowchartEdge>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowchartNode {
+    pub id: String,
+    pub label: String,
+    pub shape: NodeShape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeShape {
+    /// `A[label]`
+    Rectangle,
+    /// `A(label)`
+    Rounded,
+    /// `A([label])`
+    Stadium,
+    /// `A((label))`
+    Circle,
+    /// `A{label}`
+    Rhombus,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowchartEdge {
+    /// The index of the node the edge starts from.
+    pub from: usize,
+    /// The index of the node the edge points to.
+    pub to: usize,
+    pub label: Option<String>,
+    pub style: LineStyle,
+    pub arrow: ArrowHead,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    Solid,
+    Dotted,
+    Thick,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrowHead {
+    None,
+    Filled,
+    Open,
+    Cross,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceDiagram {
+    pub participants: Vec<Participant>,
+    pub items: Vec<SequenceItem>,
+    /// Whether the messages are numbered, with `autonumber`.
+    pub autonumber: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SequenceItem {
+    Message(SequenceMessage),
+    Note {
+        placement: NotePlacement,
+        text: String,
+    },
+    /// The start of a `loop`, `alt`, `opt`, `par`, `critical` or `break` block.
+    BlockStart {
+        keyword: String,
+        label: String,
+    },
+    /// An `else`, `and` or `option` section of the enclosing block.
+    BlockSection {
+        keyword: String,
+        label: String,
+    },
+    BlockEnd,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceMessage {
+    /// The index of the participant sending the message.
+    pub from: usize,
+    /// The index of the participant receiving the message.
+    pub to: usize,
+    pub text: String,
+    pub style: LineStyle,
+    pub arrow: ArrowHead,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotePlacement {
+    LeftOf(usize),
+    RightOf(usize),
+    Over(usize, usize),
+}
+
+/// Parses the source of a fenced `mermaid` code block.
+pub fn parse_mermaid(source: &str) -> Result<MermaidDiagram, MermaidError> {
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(ix, line)| (ix + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with("%%"));
+
+    let Some((line_number, header)) = lines.next() else {
+        return Err(error(1, "the diagram is empty"));
+    };
+    let mut words = header.trim_end_matches(';').split_whitespace();
+    match words.next().unwrap_or_default() {
+        "flowchart" | "graph" => {
+            let direction = match words.next() {
+                None | Some("TD") | Some("TB") => FlowchartDirection::TopToBottom,
+                Some("BT") => FlowchartDirection::BottomToTop,
+                Some("LR") => FlowchartDirection::LeftToRight,
+                Some("RL") => FlowchartDirection::RightToLeft,
+                Some(direction) => {
+                    return Err(error(
+                        line_number,
+                        format!("unknown direction `{direction}`"),
+                    ))
+                }
+            };
+            parse_flowchart(direction, lines).map(MermaidDiagram::Flowchart)
+        }
+        "sequenceDiagram" => parse_sequence_diagram(lines).map(MermaidDiagram::Sequence),
+        kind => Err(error(
+            line_number,
+            format!("`{kind}` diagrams are not supported"),
+        )),
+    }
+}
+
+fn error(line: usize, message: impl Into<String>) -> MermaidError {
+    MermaidError {
+        line,
+        message: message.into(),
+    }
+}
+
+/// Converts a label from the diagram source to the text to display.
+fn label_text(label: &str) -> String {
+    let label = label.trim();
+    let label = label
+        .strip_prefix('"')
+        .and_then(|label| label.strip_suffix('"'))
+        .unwrap_or(label);
+    label
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n")
+        .replace("<br>", "\n")
+}
+
+/// A cursor over the remaining text of a statement.
+struct StatementParser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> StatementParser<'a> {
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        if let Some(rest) = self.rest.strip_prefix(prefix) {
+            self.rest = rest;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let end = self.rest.find(|c| !predicate(c)).unwrap_or(self.rest.len());
+        let (taken, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        taken
+    }
+
+    /// Takes the text up to the given delimiter, and consumes the delimiter.
+    fn take_until(&mut self, delimiter: &str) -> Option<&'a str> {
+        let ix = self.rest.find(delimiter)?;
+        let taken = &self.rest[..ix];
+        self.rest = &self.rest[ix + delimiter.len()..];
+        Some(taken)
+    }
+}
+
+fn parse_flowchart<'a>(
+    direction: FlowchartDirection,
+    lines: impl Iterator<Item = (usize, &'a str)>,
+) -> Result<Flowchart, MermaidError> {
+    let mut flowchart = Flowchart {
+        direction,
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+
+    for (line_number, line) in lines {
+        for statement in split_statements(line) {
+            let statement = statement.trim();
+            match statement.split_whitespace().next() {
+                None => continue,
+                Some("classDef" | "class" | "style" | "linkStyle" | "click" | "direction") => {
+                    continue
+                }
+                Some("subgraph" | "end") => {
+                    return Err(error(line_number, "subgraphs are not supported"))
+                }
+                Some(_) => {}
+            }
+            flowchart
+                .parse_statement(statement)
+                .map_err(|message| error(line_number, message))?;
+        }
+    }
+
+    Ok(flowchart)
+}
+
+/// Splits a line on the `;` that separate statements, outside of labels.
+fn split_statements(line: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut depth = 0_usize;
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (ix, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '[' | '(' | '{' if !in_quotes => depth += 1,
+            ']' | ')' | '}' if !in_quotes => depth = depth.saturating_sub(1),
+            ';' if !in_quotes && depth == 0 => {
+                statements.push(&line[start..ix]);
+                start = ix + 1;
+            }
+            _ => {}
+        }
+    }
+    statements.push(&line[start..]);
+    statements
+}
+
+impl Flowchart {
+    /// Parses a chain of nodes and links, such as `A[Start] --> B{Valid?} -->|yes| C`.
+    fn parse_statement(&mut self, statement: &str) -> Result<(), String> {
+        let mut parser = StatementParser { rest: statement };
+        let mut from = self.parse_node(&mut parser)?;
+        loop {
+            parser.skip_whitespace();
+            if parser.rest.is_empty() {
+                return Ok(());
+            }
+
+            let (style, arrow, mut label) = parse_link(&mut parser)?;
+            parser.skip_whitespace();
+            if parser.eat("|") {
+                label = Some(
+                    parser
+                        .take_until("|")
+                        .ok_or_else(|| "missing closing `|`".to_string())?,
+                );
+            }
+
+            let to = self.parse_node(&mut parser)?;
+            self.edges.push(FlowchartEdge {
+                from,
+                to,
+                label: label.map(label_text).filter(|label| !label.is_empty()),
+                style,
+                arrow,
+            });
+            from = to;
+        }
+    }
+
+    fn parse_node(&mut self, parser: &mut StatementParser) -> Result<usize, String> {
+        parser.skip_whitespace();
+        let id = parser.take_while(|c| c.is_alphanumeric() || c == '_');
+        if id.is_empty() {
+            return Err(match parser.rest.chars().next() {
+                Some('&') => "`&` is not supported".to_string(),
+                Some(c) => format!("expected a node ID, found `{c}`"),
+                None => "expected a node ID".to_string(),
+            });
+        }
+
+        const SHAPES: [(&str, &str, NodeShape); 5] = [
+            ("((", "))", NodeShape::Circle),
+            ("([", "])", NodeShape::Stadium),
+            ("[", "]", NodeShape::Rectangle),
+            ("(", ")", NodeShape::Rounded),
+            ("{", "}", NodeShape::Rhombus),
+        ];
+        let mut shape_and_label = None;
+        for (open, close, shape) in SHAPES {
+            if parser.eat(open) {
+                let label = if parser.eat("\"") {
+                    let label = parser
+                        .take_until("\"")
+                        .ok_or_else(|| "missing closing `\"`".to_string())?;
+                    if !parser.eat(close) {
+                        return Err(format!("missing closing `{close}`"));
+                    }
+                    label
+                } else {
+                    parser
+                        .take_until(close)
+                        .ok_or_else(|| format!("missing closing `{close}`"))?
+                };
+                shape_and_label = Some((shape, label_text(label)));
+                break;
+            }
+        }
+
+        // Class names attached to the node, as in `A:::important`, only affect styling.
+        if parser.eat(":::") {
+            parser.take_while(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        }
+
+        let existing = self.nodes.iter().position(|node| node.id == id);
+        Ok(match (existing, shape_and_label) {
+            (Some(ix), Some((shape, label))) => {
+                self.nodes[ix].shape = shape;
+                self.nodes[ix].label = label;
+                ix
+            }
+            (Some(ix), None) => ix,
+            (None, shape_and_label) => {
+                let (shape, label) =
+                    shape_and_label.unwrap_or_else(|| (NodeShape::Rectangle, id.to_string()));
+                self.nodes.push(FlowchartNode {
+                    id: id.to_string(),
+                    label,
+                    shape,
+                });
+                self.nodes.len() - 1
+            }
+        })
+    }
+}
+
+/// Parses a link between two nodes, returning its style, its arrow head and
+/// the label written inside of it, as in `-- label -->`.
+fn parse_link<'a>(
+    parser: &mut StatementParser<'a>,
+) -> Result<(LineStyle, ArrowHead, Option<&'a str>), String> {
+    let arrow_head = |parser: &mut StatementParser| {
+        if parser.eat(">") {
+            ArrowHead::Filled
+        } else {
+            ArrowHead::None
+        }
+    };
+
+    if parser.eat("-.") {
+        parser.take_while(|c| c == '.');
+        if parser.eat("-") {
+            return Ok((LineStyle::Dotted, arrow_head(parser), None));
+        }
+        let label = parser
+            .take_until(".-")
+            .ok_or_else(|| "missing end of link".to_string())?;
+        return Ok((LineStyle::Dotted, arrow_head(parser), Some(label)));
+    }
+
+    for (line, style) in [('-', LineStyle::Solid), ('=', LineStyle::Thick)] {
+        let mut link = String::new();
+        link.push(line);
+        link.push(line);
+        if !parser.rest.starts_with(&link) {
+            continue;
+        }
+
+        let length = parser.take_while(|c| c == line).len();
+        if parser.eat(">") {
+            return Ok((style, ArrowHead::Filled, None));
+        }
+        if length >= 3 {
+            return Ok((style, ArrowHead::None, None));
+        }
+        let label = parser
+            .take_until(&link)
+            .ok_or_else(|| "missing end of link".to_string())?;
+        parser.take_while(|c| c == line);
+        return Ok((style, arrow_head(parser), Some(label)));
+    }
+
+    Err(match parser.rest.chars().next() {
+        Some('&') => "`&` is not supported".to_string(),
+        _ => format!("expected a link, found `{}`", parser.rest),
+    })
+}
+
+fn parse_sequence_diagram<'a>(
+    lines: impl Iterator<Item = (usize, &'a str)>,
+) -> Result<SequenceDiagram, MermaidError> {
+    let mut diagram = SequenceDiagram {
+        participants: Vec::new(),
+        items: Vec::new(),
+        autonumber: false,
+    };
+    let mut open_blocks = Vec::new();
+    let mut last_line_number = 1;
+
+    for (line_number, line) in lines {
+        last_line_number = line_number;
+        let line = line.trim_end_matches(';');
+        let (keyword, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(keyword, rest)| (keyword, rest.trim()));
+        match keyword {
+            "participant" | "actor" => {
+                let (id, label) = match rest.split_once(" as ") {
+                    Some((id, label)) => (id.trim(), label_text(label)),
+                    None => (rest, label_text(rest)),
+                };
+                if id.is_empty() {
+                    return Err(error(line_number, "missing participant name"));
+                }
+                let ix = diagram.participant(id);
+                diagram.participants[ix].label = label;
+            }
+            "autonumber" => diagram.autonumber = true,
+            "activate" | "deactivate" => {}
+            "title" | "box" | "create" | "destroy" | "links" | "link" | "properties" => {
+                return Err(error(line_number, format!("`{keyword}` is not supported")))
+            }
+            "loop" | "alt" | "opt" | "par" | "critical" | "break" => {
+                open_blocks.push(keyword);
+                diagram.items.push(SequenceItem::BlockStart {
+                    keyword: keyword.to_string(),
+                    label: label_text(rest),
+                });
+            }
+            "else" | "and" | "option" => {
+                if open_blocks.is_empty() {
+                    return Err(error(
+                        line_number,
+                        format!("`{keyword}` outside of a block"),
+                    ));
+                }
+                diagram.items.push(SequenceItem::BlockSection {
+                    keyword: keyword.to_string(),
+                    label: label_text(rest),
+                });
+            }
+            "end" => {
+                if open_blocks.pop().is_none() {
+                    return Err(error(line_number, "`end` without a block"));
+                }
+                diagram.items.push(SequenceItem::BlockEnd);
+            }
+            _ if keyword.eq_ignore_ascii_case("note") => {
+                let item = diagram
+                    .parse_note(rest)
+                    .map_err(|message| error(line_number, message))?;
+                diagram.items.push(item);
+            }
+            _ => {
+                let message = diagram
+                    .parse_message(line)
+                    .map_err(|message| error(line_number, message))?;
+                diagram.items.push(SequenceItem::Message(message));
+            }
+        }
+    }
+
+    if let Some(keyword) = open_blocks.pop() {
+        return Err(error(
+            last_line_number,
+            format!("missing `end` for `{keyword}`"),
+        ));
+    }
+
+    Ok(diagram)
+}
+
+impl SequenceDiagram {
+    /// Returns the index of the participant with the given ID, adding it if it is not declared yet.
+    fn participant(&mut self, id: &str) -> usize {
+        if let Some(ix) = self.participants.iter().position(|p| p.id == id) {
+            return ix;
+        }
+        self.participants.push(Participant {
+            id: id.to_string(),
+            label: id.to_string(),
+        });
+        self.participants.len() - 1
+    }
+
+    fn parse_note(&mut self, rest: &str) -> Result<SequenceItem, String> {
+        let (position, text) = rest
+            .split_once(':')
+            .ok_or_else(|| "missing `:` after the note position".to_string())?;
+        let position = position.trim();
+        let placement = if let Some(id) = position.strip_prefix("left of ") {
+            NotePlacement::LeftOf(self.participant(id.trim()))
+        } else if let Some(id) = position.strip_prefix("right of ") {
+            NotePlacement::RightOf(self.participant(id.trim()))
+        } else if let Some(ids) = position.strip_prefix("over ") {
+            match ids.split_once(',') {
+                Some((first, second)) => NotePlacement::Over(
+                    self.participant(first.trim()),
+                    self.participant(second.trim()),
+                ),
+                None => {
+                    let ix = self.participant(ids.trim());
+                    NotePlacement::Over(ix, ix)
+                }
+            }
+        } else {
+            return Err(format!("unknown note position `{position}`"));
+        };
+        Ok(SequenceItem::Note {
+            placement,
+            text: label_text(text),
+        })
+    }
+
+    fn parse_message(&mut self, line: &str) -> Result<SequenceMessage, String> {
+        const ARROWS: [(&str, LineStyle, ArrowHead); 8] = [
+            ("-->>", LineStyle::Dotted, ArrowHead::Filled),
+            ("->>", LineStyle::Solid, ArrowHead::Filled),
+            ("--x", LineStyle::Dotted, ArrowHead::Cross),
+            ("-x", LineStyle::Solid, ArrowHead::Cross),
+            ("--)", LineStyle::Dotted, ArrowHead::Open),
+            ("-)", LineStyle::Solid, ArrowHead::Open),
+            ("-->", LineStyle::Dotted, ArrowHead::None),
+            ("->", LineStyle::Solid, ArrowHead::None),
+        ];
+
+        let (participants, text) = line.split_once(':').unwrap_or((line, ""));
+        let arrow_start = participants
+            .find('-')
+            .ok_or_else(|| format!("unknown statement `{line}`"))?;
+        let (from, arrow) = participants.split_at(arrow_start);
+        let (arrow_len, style, arrow_head) = ARROWS
+            .iter()
+            .find(|(token, _, _)| arrow.starts_with(token))
+            .map(|(token, style, arrow_head)| (token.len(), *style, *arrow_head))
+            .ok_or_else(|| format!("unknown arrow in `{line}`"))?;
+
+        // Activation shorthands, as in `A->>+B`, don't affect the layout.
+        let to = arrow[arrow_len..]
+            .trim()
+            .trim_start_matches(['+', '-'])
+            .trim();
+        let from = from.trim();
+        if from.is_empty() || to.is_empty() {
+            return Err(format!("missing participant in `{line}`"));
+        }
+
+        Ok(SequenceMessage {
+            from: self.participant(from),
+            to: self.participant(to),
+            text: label_text(text),
+            style,
+            arrow: arrow_head,
+        })
+    }
+}
+
+/// A diagram laid out in pixels, with its top-left corner at the origin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagramLayout {
+    pub size: Size<f32>,
+    /// The lines to paint first, below the shapes.
+    pub lines: Vec<LayoutLine>,
+    pub shapes: Vec<LayoutShape>,
+    /// The labels to paint last, above the shapes and lines.
+    pub labels: Vec<LayoutLabel>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutLine {
+    pub points: Vec<Point<f32>>,
+    pub style: LineStyle,
+    /// The arrow head at the last point.
+    pub arrow: ArrowHead,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutShape {
+    pub bounds: Bounds<f32>,
+    pub kind: ShapeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeKind {
+    Node(NodeShape),
+    Note,
+    /// The outline of a block of a sequence diagram.
+    Frame,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutLabel {
+    pub bounds: Bounds<f32>,
+    pub text: String,
+    /// Whether the label needs a background to stand out from the lines it is placed on.
+    pub background: bool,
+    pub centered: bool,
+}
+
+const MARGIN: f32 = 8.;
+const NODE_PADDING: Size<f32> = Size {
+    width: 16.,
+    height: 10.,
+};
+const RANK_GAP: f32 = 48.;
+const NODE_GAP: f32 = 24.;
+const LABEL_MARGIN: f32 = 8.;
+const SELF_LOOP_SIZE: f32 = 16.;
+
+impl MermaidDiagram {
+    /// Lays the diagram out, using `measure` to get the size of a label's text.
+    pub fn layout(&self, measure: &dyn Fn(&str) -> Size<f32>) -> DiagramLayout {
+        let mut layout = match self {
+            MermaidDiagram::Flowchart(flowchart) => flowchart.layout(measure),
+            MermaidDiagram::Sequence(diagram) => diagram.layout(measure),
+        };
+        layout.normalize();
+        layout
+    }
+}
+
+impl DiagramLayout {
+    fn new() -> Self {
+        Self {
+            size: Size::default(),
+            lines: Vec::new(),
+            shapes: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Moves the contents of the layout so that they start at the margin, and computes its size.
+    fn normalize(&mut self) {
+        let mut min = point(f32::MAX, f32::MAX);
+        let mut max = point(f32::MIN, f32::MIN);
+        let mut include = |p: Point<f32>| {
+            min = point(min.x.min(p.x), min.y.min(p.y));
+            max = point(max.x.max(p.x), max.y.max(p.y));
+        };
+        for line in &self.lines {
+            line.points.iter().copied().for_each(&mut include);
+        }
+        for bounds in self
+            .shapes
+            .iter()
+            .map(|shape| shape.bounds)
+            .chain(self.labels.iter().map(|label| label.bounds))
+        {
+            include(bounds.origin);
+            include(point(bounds.right(), bounds.bottom()));
+        }
+        if min.x > max.x {
+            return;
+        }
+
+        let offset = point(MARGIN - min.x, MARGIN - min.y);
+        for line in &mut self.lines {
+            for p in &mut line.points {
+                *p = point(p.x + offset.x, p.y + offset.y);
+            }
+        }
+        for bounds in self
+            .shapes
+            .iter_mut()
+            .map(|shape| &mut shape.bounds)
+            .chain(self.labels.iter_mut().map(|label| &mut label.bounds))
+        {
+            bounds.origin = point(bounds.origin.x + offset.x, bounds.origin.y + offset.y);
+        }
+        self.size = size(max.x - min.x + 2. * MARGIN, max.y - min.y + 2. * MARGIN);
+    }
+}
+
+fn padded(size_: Size<f32>, padding: Size<f32>) -> Size<f32> {
+    size(
+        size_.width + 2. * padding.width,
+        size_.height + 2. * padding.height,
+    )
+}
+
+fn centered_bounds(center: Point<f32>, size_: Size<f32>) -> Bounds<f32> {
+    Bounds::new(
+        point(center.x - size_.width / 2., center.y - size_.height / 2.),
+        size_,
+    )
+}
+
+impl Flowchart {
+    fn is_vertical(&self) -> bool {
+        matches!(
+            self.direction,
+            FlowchartDirection::TopToBottom | FlowchartDirection::BottomToTop
+        )
+    }
+
+    fn layout(&self, measure: &dyn Fn(&str) -> Size<f32>) -> DiagramLayout {
+        let mut layout = DiagramLayout::new();
+        let node_count = self.nodes.len();
+        let vertical = self.is_vertical();
+        // Sizes along the direction of the flowchart (main) and across it (cross).
+        let to_main_cross = |size_: Size<f32>| {
+            if vertical {
+                (size_.height, size_.width)
+            } else {
+                (size_.width, size_.height)
+            }
+        };
+
+        let label_sizes = self
+            .nodes
+            .iter()
+            .map(|node| measure(&node.label))
+            .collect::<Vec<_>>();
+        let node_sizes = self
+            .nodes
+            .iter()
+            .zip(&label_sizes)
+            .map(|(node, label_size)| node_size(node.shape, *label_size))
+            .collect::<Vec<_>>();
+
+        let ranks = self.ranks();
+        let rank_count = ranks.iter().max().map_or(0, |rank| rank + 1);
+        let layers = self.ordered_layers(&ranks, rank_count);
+
+        // The size of each rank along the main axis, and the gap after it, which fits the labels of the edges crossing it.
+        let mut rank_sizes = vec![0_f32; rank_count];
+        for (ix, size_) in node_sizes.iter().enumerate() {
+            let (main, _) = to_main_cross(*size_);
+            rank_sizes[ranks[ix]] = rank_sizes[ranks[ix]].max(main);
+        }
+        let mut rank_gaps = vec![RANK_GAP; rank_count];
+        for edge in &self.edges {
+            if let Some(label) = &edge.label {
+                let (main, _) = to_main_cross(measure(label));
+                let rank = ranks[edge.from].min(ranks[edge.to]);
+                rank_gaps[rank] = rank_gaps[rank].max(main + 4. * LABEL_MARGIN);
+            }
+        }
+        let mut rank_positions = Vec::with_capacity(rank_count);
+        let mut position = 0.;
+        for (rank_size, rank_gap) in rank_sizes.iter().zip(&rank_gaps) {
+            rank_positions.push(position);
+            position += rank_size + rank_gap;
+        }
+        let total_main = position - rank_gaps.last().copied().unwrap_or_default();
+
+        let layer_cross_size = |layer: &[usize]| {
+            layer
+                .iter()
+                .map(|ix| to_main_cross(node_sizes[*ix]).1)
+                .sum::<f32>()
+                + NODE_GAP * layer.len().saturating_sub(1) as f32
+        };
+        let max_cross = layers
+            .iter()
+            .map(|layer| layer_cross_size(layer))
+            .fold(0., f32::max);
+
+        let mut centers = vec![point(0., 0.); node_count];
+        for (rank, layer) in layers.iter().enumerate() {
+            let mut cross = (max_cross - layer_cross_size(layer)) / 2.;
+            for ix in layer {
+                let (_, node_cross) = to_main_cross(node_sizes[*ix]);
+                let mut main = rank_positions[rank] + rank_sizes[rank] / 2.;
+                if matches!(
+                    self.direction,
+                    FlowchartDirection::BottomToTop | FlowchartDirection::RightToLeft
+                ) {
+                    main = total_main - main;
+                }
+                let cross_center = cross + node_cross / 2.;
+                centers[*ix] = if vertical {
+                    point(cross_center, main)
+                } else {
+                    point(main, cross_center)
+                };
+                cross += node_cross + NODE_GAP;
+            }
+        }
+
+        for (ix, node) in self.nodes.iter().enumerate() {
+            layout.shapes.push(LayoutShape {
+                bounds: centered_bounds(centers[ix], node_sizes[ix]),
+                kind: ShapeKind::Node(node.shape),
+            });
+            layout.labels.push(LayoutLabel {
+                bounds: centered_bounds(centers[ix], label_sizes[ix]),
+                text: node.label.clone(),
+                background: false,
+                centered: true,
+            });
+        }
+
+        for edge in &self.edges {
+            let from_bounds = centered_bounds(centers[edge.from], node_sizes[edge.from]);
+            let (points, label_anchor) = if edge.from == edge.to {
+                let right = from_bounds.right();
+                let center_y = centers[edge.from].y;
+                let loop_top = center_y - SELF_LOOP_SIZE / 2.;
+                let loop_bottom = center_y + SELF_LOOP_SIZE / 2.;
+                let points = vec![
+                    point(right, loop_top),
+                    point(right + SELF_LOOP_SIZE, loop_top),
+                    point(right + SELF_LOOP_SIZE, loop_bottom),
+                    point(right, loop_bottom),
+                ];
+                (points, None)
+            } else {
+                let to_bounds = centered_bounds(centers[edge.to], node_sizes[edge.to]);
+                let start =
+                    boundary_point(self.nodes[edge.from].shape, from_bounds, centers[edge.to]);
+                let end = boundary_point(self.nodes[edge.to].shape, to_bounds, centers[edge.from]);
+                let middle = point((start.x + end.x) / 2., (start.y + end.y) / 2.);
+                (vec![start, end], Some(middle))
+            };
+
+            if let Some(label) = &edge.label {
+                let label_size = measure(label);
+                let bounds = match label_anchor {
+                    Some(middle) => centered_bounds(middle, label_size),
+                    None => Bounds::new(
+                        point(
+                            from_bounds.right() + SELF_LOOP_SIZE + LABEL_MARGIN / 2.,
+                            centers[edge.from].y - label_size.height / 2.,
+                        ),
+                        label_size,
+                    ),
+                };
+                layout.labels.push(LayoutLabel {
+                    bounds,
+                    text: label.clone(),
+                    background: true,
+                    centered: true,
+                });
+            }
+
+            layout.lines.push(LayoutLine {
+                points,
+                style: edge.style,
+                arrow: edge.arrow,
+            });
+        }
+
+        layout
+    }
+
+    /// Assigns each node to a rank, such that edges point to higher ranks,
+    /// ignoring the edges that close cycles.
+    fn ranks(&self) -> Vec<usize> {
+        let node_count = self.nodes.len();
+        let mut outgoing = vec![Vec::new(); node_count];
+        for edge in &self.edges {
+            outgoing[edge.from].push(edge.to);
+        }
+
+        #[derive(Clone, Copy, PartialEq)]
+        enum Visit {
+            New,
+            InProgress,
+            Done,
+        }
+
+        fn visit(
+            node: usize,
+            outgoing: &[Vec<usize>],
+            visits: &mut [Visit],
+            acyclic_edges: &mut Vec<(usize, usize)>,
+            postorder: &mut Vec<usize>,
+        ) {
+            visits[node] = Visit::InProgress;
+            for &target in &outgoing[node] {
+                match visits[target] {
+                    Visit::InProgress => {}
+                    Visit::Done => acyclic_edges.push((node, target)),
+                    Visit::New => {
+                        acyclic_edges.push((node, target));
+                        visit(target, outgoing, visits, acyclic_edges, postorder);
+                    }
+                }
+            }
+            visits[node] = Visit::Done;
+            postorder.push(node);
+        }
+
+        let mut visits = vec![Visit::New; node_count];
+        let mut acyclic_edges = Vec::new();
+        let mut postorder = Vec::with_capacity(node_count);
+        for node in 0..node_count {
+            if visits[node] == Visit::New {
+                visit(
+                    node,
+                    &outgoing,
+                    &mut visits,
+                    &mut acyclic_edges,
+                    &mut postorder,
+                );
+            }
+        }
+
+        // The reverse postorder is a topological order of the acyclic edges.
+        let mut ranks = vec![0; node_count];
+        for &node in postorder.iter().rev() {
+            for &(from, to) in &acyclic_edges {
+                if from == node {
+                    ranks[to] = ranks[to].max(ranks[from] + 1);
+                }
+            }
+        }
+        ranks
+    }
+
+    /// Groups the nodes by rank, ordering each rank to reduce the crossings of edges.
+    fn ordered_layers(&self, ranks: &[usize], rank_count: usize) -> Vec<Vec<usize>> {
+        let mut layers = vec![Vec::new(); rank_count];
+        for (ix, rank) in ranks.iter().enumerate() {
+            layers[*rank].push(ix);
+        }
+
+        let mut positions = vec![0_f32; ranks.len()];
+        let update_positions = |layers: &[Vec<usize>], positions: &mut [f32]| {
+            for layer in layers {
+                for (position, ix) in layer.iter().enumerate() {
+                    positions[*ix] = position as f32;
+                }
+            }
+        };
+        update_positions(&layers, &mut positions);
+
+        for iteration in 0..4 {
+            let downwards = iteration % 2 == 0;
+            let rank_order: Vec<usize> = if downwards {
+                (1..rank_count).collect()
+            } else {
+                (0..rank_count.saturating_sub(1)).rev().collect()
+            };
+            for rank in rank_order {
+                let barycenter = |ix: usize| {
+                    let neighbors = self
+                        .edges
+                        .iter()
+                        .filter_map(|edge| {
+                            let (node, neighbor) = if downwards {
+                                (edge.to, edge.from)
+                            } else {
+                                (edge.from, edge.to)
+                            };
+                            let is_adjacent = if downwards {
+                                ranks[neighbor] < rank
+                            } else {
+                                ranks[neighbor] > rank
+                            };
+                            (node == ix && is_adjacent).then_some(positions[neighbor])
+                        })
+                        .collect::<Vec<_>>();
+                    if neighbors.is_empty() {
+                        positions[ix]
+                    } else {
+                        neighbors.iter().sum::<f32>() / neighbors.len() as f32
+                    }
+                };
+                let mut keyed = layers[rank]
+                    .iter()
+                    .map(|ix| (barycenter(*ix), *ix))
+                    .collect::<Vec<_>>();
+                keyed.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+                layers[rank] = keyed.into_iter().map(|(_, ix)| ix).collect();
+                update_positions(&layers, &mut positions);
+            }
+        }
+
+        layers
+    }
+}
+
+fn node_size(shape: NodeShape, label_size: Size<f32>) -> Size<f32> {
+    let padded_size = padded(label_size, NODE_PADDING);
+    match shape {
+        NodeShape::Rectangle | NodeShape::Rounded => padded_size,
+        NodeShape::Stadium => size(
+            padded_size.width + padded_size.height / 2.,
+            padded_size.height,
+        ),
+        NodeShape::Circle => {
+            let diameter = label_size.width.max(label_size.height) + NODE_PADDING.height * 2.;
+            size(diameter, diameter)
+        }
+        // A square rhombus fits the label when its diagonals are the sum of the label's width and height.
+        NodeShape::Rhombus => {
+            let diagonal = label_size.width + label_size.height + NODE_PADDING.width;
+            size(diagonal, diagonal)
+        }
+    }
+}
+
+/// Returns the point where the line from the center of a node towards `target` leaves the node.
+fn boundary_point(shape: NodeShape, bounds: Bounds<f32>, target: Point<f32>) -> Point<f32> {
+    let center = point(
+        bounds.origin.x + bounds.size.width / 2.,
+        bounds.origin.y + bounds.size.height / 2.,
+    );
+    let (dx, dy) = (target.x - center.x, target.y - center.y);
+    if dx == 0. && dy == 0. {
+        return center;
+    }
+    let (half_width, half_height) = (bounds.size.width / 2., bounds.size.height / 2.);
+    let t = match shape {
+        NodeShape::Rectangle | NodeShape::Rounded | NodeShape::Stadium => {
+            let tx = if dx == 0. {
+                f32::MAX
+            } else {
+                half_width / dx.abs()
+            };
+            let ty = if dy == 0. {
+                f32::MAX
+            } else {
+                half_height / dy.abs()
+            };
+            tx.min(ty)
+        }
+        NodeShape::Circle => half_width / (dx * dx + dy * dy).sqrt(),
+        NodeShape::Rhombus => 1. / (dx.abs() / half_width + dy.abs() / half_height),
+    };
+    point(center.x + dx * t, center.y + dy * t)
+}
+
+const PARTICIPANT_GAP: f32 = 32.;
+const MESSAGE_GAP: f32 = 16.;
+const NOTE_PADDING: Size<f32> = Size {
+    width: 8.,
+    height: 6.,
+};
+const FRAME_INSET: f32 = 6.;
+
+impl SequenceDiagram {
+    fn layout(&self, measure: &dyn Fn(&str) -> Size<f32>) -> DiagramLayout {
+        let mut layout = DiagramLayout::new();
+
+        let messages = self.numbered_messages();
+        let label_sizes = self
+            .participants
+            .iter()
+            .map(|participant| measure(&participant.label))
+            .collect::<Vec<_>>();
+        let box_sizes = label_sizes
+            .iter()
+            .map(|label_size| padded(*label_size, NODE_PADDING))
+            .collect::<Vec<_>>();
+        let box_height = box_sizes
+            .iter()
+            .map(|size_| size_.height)
+            .fold(0., f32::max);
+
+        // Space the participants so that the boxes and the texts between them fit.
+        let mut centers = Vec::with_capacity(self.participants.len());
+        for (ix, box_size) in box_sizes.iter().enumerate() {
+            centers.push(match ix {
+                0 => box_size.width / 2.,
+                _ => {
+                    centers[ix - 1]
+                        + (box_sizes[ix - 1].width + box_size.width) / 2.
+                        + PARTICIPANT_GAP
+                }
+            });
+        }
+        let require_distance = |centers: &mut [f32], left: usize, right: usize, distance: f32| {
+            if right >= centers.len() || left >= right {
+                return;
+            }
+            let deficit = distance - (centers[right] - centers[left]);
+            if deficit > 0. {
+                for center in &mut centers[right..] {
+                    *center += deficit;
+                }
+            }
+        };
+        for (item, text) in self.items.iter().zip(&messages) {
+            match item {
+                SequenceItem::Message(message) => {
+                    let text_width = measure(text).width;
+                    if message.from == message.to {
+                        require_distance(
+                            &mut centers,
+                            message.from,
+                            message.from + 1,
+                            SELF_LOOP_SIZE + text_width + 2. * LABEL_MARGIN,
+                        );
+                    } else {
+                        let (left, right) =
+                            (message.from.min(message.to), message.from.max(message.to));
+                        require_distance(&mut centers, left, right, text_width + 3. * LABEL_MARGIN);
+                    }
+                }
+                SequenceItem::Note { placement, text } => {
+                    let width = padded(measure(text), NOTE_PADDING).width + 2. * LABEL_MARGIN;
+                    match *placement {
+                        NotePlacement::RightOf(ix) => {
+                            require_distance(&mut centers, ix, ix + 1, width)
+                        }
+                        NotePlacement::LeftOf(ix) if ix > 0 => {
+                            require_distance(&mut centers, ix - 1, ix, width)
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut y = box_height + MESSAGE_GAP;
+        let left_edge = centers
+            .iter()
+            .zip(&box_sizes)
+            .map(|(center, size_)| center - size_.width / 2.)
+            .fold(0_f32, f32::min);
+        let right_edge = centers
+            .iter()
+            .zip(&box_sizes)
+            .map(|(center, size_)| center + size_.width / 2.)
+            .fold(0_f32, f32::max);
+        let mut open_frames: Vec<(f32, String)> = Vec::new();
+
+        for (item, text) in self.items.iter().zip(&messages) {
+            match item {
+                SequenceItem::Message(message) => {
+                    let text_size = measure(text);
+                    let from_x = centers[message.from];
+                    if message.from == message.to {
+                        let loop_height = SELF_LOOP_SIZE.max(text_size.height);
+                        layout.labels.push(LayoutLabel {
+                            bounds: Bounds::new(
+                                point(from_x + SELF_LOOP_SIZE + LABEL_MARGIN / 2., y),
+                                text_size,
+                            ),
+                            text: text.clone(),
+                            background: false,
+                            centered: false,
+                        });
+                        layout.lines.push(LayoutLine {
+                            points: vec![
+                                point(from_x, y),
+                                point(from_x + SELF_LOOP_SIZE, y),
+                                point(from_x + SELF_LOOP_SIZE, y + loop_height),
+                                point(from_x, y + loop_height),
+                            ],
+                            style: message.style,
+                            arrow: message.arrow,
+                        });
+                        y += loop_height + MESSAGE_GAP;
+                    } else {
+                        let to_x = centers[message.to];
+                        if !text.is_empty() {
+                            layout.labels.push(LayoutLabel {
+                                bounds: Bounds::new(
+                                    point((from_x + to_x - text_size.width) / 2., y),
+                                    text_size,
+                                ),
+                                text: text.clone(),
+                                background: false,
+                                centered: true,
+                            });
+                            y += text_size.height + LABEL_MARGIN / 2.;
+                        }
+                        layout.lines.push(LayoutLine {
+                            points: vec![point(from_x, y), point(to_x, y)],
+                            style: message.style,
+                            arrow: message.arrow,
+                        });
+                        y += MESSAGE_GAP;
+                    }
+                }
+                SequenceItem::Note { placement, text } => {
+                    let text_size = measure(text);
+                    let mut note_size = padded(text_size, NOTE_PADDING);
+                    let left = match *placement {
+                        NotePlacement::LeftOf(ix) => centers[ix] - LABEL_MARGIN - note_size.width,
+                        NotePlacement::RightOf(ix) => centers[ix] + LABEL_MARGIN,
+                        NotePlacement::Over(first, second) => {
+                            let (left, right) = (
+                                centers[first].min(centers[second]),
+                                centers[first].max(centers[second]),
+                            );
+                            note_size.width = note_size.width.max(right - left + 2. * LABEL_MARGIN);
+                            (left + right - note_size.width) / 2.
+                        }
+                    };
+                    let bounds = Bounds::new(point(left, y), note_size);
+                    layout.shapes.push(LayoutShape {
+                        bounds,
+                        kind: ShapeKind::Note,
+                    });
+                    layout.labels.push(LayoutLabel {
+                        bounds: centered_bounds(
+                            point(left + note_size.width / 2., y + note_size.height / 2.),
+                            text_size,
+                        ),
+                        text: text.clone(),
+                        background: false,
+                        centered: true,
+                    });
+                    y += note_size.height + MESSAGE_GAP;
+                }
+                SequenceItem::BlockStart { keyword, label } => {
+                    let (frame_left, _) = frame_edges(left_edge, right_edge, open_frames.len());
+                    let title = block_title(keyword, label);
+                    let title_size = measure(&title);
+                    layout.labels.push(LayoutLabel {
+                        bounds: Bounds::new(
+                            point(frame_left + FRAME_INSET, y + FRAME_INSET / 2.),
+                            title_size,
+                        ),
+                        text: title,
+                        background: false,
+                        centered: false,
+                    });
+                    open_frames.push((y, keyword.clone()));
+                    y += title_size.height + FRAME_INSET + MESSAGE_GAP;
+                }
+                SequenceItem::BlockSection { keyword, label } => {
+                    let (frame_left, frame_right) =
+                        frame_edges(left_edge, right_edge, open_frames.len().saturating_sub(1));
+                    layout.lines.push(LayoutLine {
+                        points: vec![point(frame_left, y), point(frame_right, y)],
+                        style: LineStyle::Dotted,
+                        arrow: ArrowHead::None,
+                    });
+                    let title = block_title(keyword, label);
+                    let title_size = measure(&title);
+                    layout.labels.push(LayoutLabel {
+                        bounds: Bounds::new(
+                            point(frame_left + FRAME_INSET, y + FRAME_INSET / 2.),
+                            title_size,
+                        ),
+                        text: title,
+                        background: false,
+                        centered: false,
+                    });
+                    y += title_size.height + FRAME_INSET + MESSAGE_GAP;
+                }
+                SequenceItem::BlockEnd => {
+                    if let Some((top, _)) = open_frames.pop() {
+                        let (frame_left, frame_right) =
+                            frame_edges(left_edge, right_edge, open_frames.len());
+                        layout.shapes.push(LayoutShape {
+                            bounds: Bounds::new(
+                                point(frame_left, top),
+                                size(frame_right - frame_left, y - top),
+                            ),
+                            kind: ShapeKind::Frame,
+                        });
+                        y += FRAME_INSET;
+                    }
+                }
+            }
+        }
+
+        // The participants are repeated below the diagram, with their lifelines in between.
+        for (ix, center) in centers.iter().enumerate() {
+            layout.lines.insert(
+                ix,
+                LayoutLine {
+                    points: vec![point(*center, box_height), point(*center, y)],
+                    style: LineStyle::Dotted,
+                    arrow: ArrowHead::None,
+                },
+            );
+            for top in [0., y] {
+                let box_bounds = Bounds::new(
+                    point(center - box_sizes[ix].width / 2., top),
+                    size(box_sizes[ix].width, box_height),
+                );
+                layout.shapes.push(LayoutShape {
+                    bounds: box_bounds,
+                    kind: ShapeKind::Node(NodeShape::Rectangle),
+                });
+                layout.labels.push(LayoutLabel {
+                    bounds: centered_bounds(point(*center, top + box_height / 2.), label_sizes[ix]),
+                    text: self.participants[ix].label.clone(),
+                    background: false,
+                    centered: true,
+                });
+            }
+        }
+
+        layout
+    }
+
+    /// Returns the text of each item, prefixing messages with their number when `autonumber` is set.
+    fn numbered_messages(&self) -> Vec<String> {
+        let mut number = 0;
+        self.items
+            .iter()
+            .map(|item| match item {
+                SequenceItem::Message(message) if self.autonumber => {
+                    number += 1;
+                    format!("{number}. {}", message.text)
+                }
+                SequenceItem::Message(message) => message.text.clone(),
+                SequenceItem::Note { text, .. } => text.clone(),
+                SequenceItem::BlockStart { .. }
+                | SequenceItem::BlockSection { .. }
+                | SequenceItem::BlockEnd => String::new(),
+            })
+            .collect()
+    }
+}
+
+/// Returns the left and right edges of a block frame, nested `depth` blocks deep.
+fn frame_edges(left_edge: f32, right_edge: f32, depth: usize) -> (f32, f32) {
+    let inset = FRAME_INSET * depth as f32;
+    (
+        left_edge - LABEL_MARGIN + inset,
+        right_edge + LABEL_MARGIN - inset,
+    )
+}
+
+fn block_title(keyword: &str, label: &str) -> String {
+    if label.is_empty() {
+        keyword.to_string()
+    } else {
+        format!("{keyword} [{label}]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn measure(text: &str) -> Size<f32> {
+        let width = text
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or_default();
+        size(width as f32 * 8., text.lines().count().max(1) as f32 * 16.)
+    }
+
+    fn node(id: &str, label: &str, shape: NodeShape) -> FlowchartNode {
+        FlowchartNode {
+            id: id.into(),
+            label: label.into(),
+            shape,
+        }
+    }
+
+    fn flowchart(source: &str) -> Flowchart {
+        match parse_mermaid(source).unwrap() {
+            MermaidDiagram::Flowchart(flowchart) => flowchart,
+            diagram => panic!("expected a flowchart, got {diagram:?}"),
+        }
+    }
+
+    fn sequence_diagram(source: &str) -> SequenceDiagram {
+        match parse_mermaid(source).unwrap() {
+            MermaidDiagram::Sequence(diagram) => diagram,
+            diagram => panic!("expected a sequence diagram, got {diagram:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_flowchart() {
+        let flowchart = flowchart(
+            "flowchart LR\n  %% a comment\n  A[Start] --> B{Is it valid?}\n  B -->|yes| C((Done))\n  B -. no .-> A; B == retry ==> B\n  D(\"Label with ] bracket\") --- A\n  style A fill:#f9f\n",
+        );
+
+        assert_eq!(flowchart.direction, FlowchartDirection::LeftToRight);
+        assert_eq!(
+            flowchart.nodes,
+            vec![
+                node("A", "Start", NodeShape::Rectangle),
+                node("B", "Is it valid?", NodeShape::Rhombus),
+                node("C", "Done", NodeShape::Circle),
+                node("D", "Label with ] bracket", NodeShape::Rounded),
+            ]
+        );
+        assert_eq!(
+            flowchart
+                .edges
+                .iter()
+                .map(|edge| (
+                    edge.from,
+                    edge.to,
+                    edge.label.as_deref(),
+                    edge.style,
+                    edge.arrow
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 1, None, LineStyle::Solid, ArrowHead::Filled),
+                (1, 2, Some("yes"), LineStyle::Solid, ArrowHead::Filled),
+                (1, 0, Some("no"), LineStyle::Dotted, ArrowHead::Filled),
+                (1, 1, Some("retry"), LineStyle::Thick, ArrowHead::Filled),
+                (3, 0, None, LineStyle::Solid, ArrowHead::None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_flowchart_chains_and_labels() {
+        let flowchart = flowchart("graph\nA -- one --> B --> C[\"Multi<br>line\"]");
+        assert_eq!(flowchart.direction, FlowchartDirection::TopToBottom);
+        assert_eq!(flowchart.nodes[2].label, "Multi\nline");
+        assert_eq!(
+            flowchart
+                .edges
+                .iter()
+                .map(|edge| (edge.from, edge.to, edge.label.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![(0, 1, Some("one")), (1, 2, None)]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_mermaid("pie\n\"a\": 1").unwrap_err(),
+            error(1, "`pie` diagrams are not supported")
+        );
+        assert_eq!(
+            parse_mermaid("flowchart TD\nA --> B\nsubgraph one\nend").unwrap_err(),
+            error(3, "subgraphs are not supported")
+        );
+        assert_eq!(
+            parse_mermaid("flowchart TD\nA[unclosed --> B").unwrap_err(),
+            error(2, "missing closing `]`")
+        );
+        assert_eq!(
+            parse_mermaid("flowchart TD\nA & B --> C").unwrap_err(),
+            error(2, "`&` is not supported")
+        );
+        assert_eq!(
+            parse_mermaid("sequenceDiagram\nA->>B: hi\nend").unwrap_err(),
+            error(3, "`end` without a block")
+        );
+        assert_eq!(
+            parse_mermaid("sequenceDiagram\nA => B").unwrap_err(),
+            error(2, "unknown statement `A => B`")
+        );
+        assert_eq!(
+            parse_mermaid("  \n%% only a comment\n").unwrap_err(),
+            error(1, "the diagram is empty")
+        );
+    }
+
+    #[test]
+    fn test_parse_sequence_diagram() {
+        let diagram = sequence_diagram(
+            "sequenceDiagram\n  participant C as Client\n  actor S as Server\n  autonumber\n  C->>+S: Request\n  loop Every minute\n    S-->>C: Ping\n  else Never\n    S-xS: Crash\n  end\n  Note over C,S: Done\n",
+        );
+
+        assert!(diagram.autonumber);
+        assert_eq!(
+            diagram.participants,
+            vec![
+                Participant {
+                    id: "C".into(),
+                    label: "Client".into(),
+                },
+                Participant {
+                    id: "S".into(),
+                    label: "Server".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            diagram.items,
+            vec![
+                SequenceItem::Message(SequenceMessage {
+                    from: 0,
+                    to: 1,
+                    text: "Request".into(),
+                    style: LineStyle::Solid,
+                    arrow: ArrowHead::Filled,
+                }),
+                SequenceItem::BlockStart {
+                    keyword: "loop".into(),
+                    label: "Every minute".into(),
+                },
+                SequenceItem::Message(SequenceMessage {
+                    from: 1,
+                    to: 0,
+                    text: "Ping".into(),
+                    style: LineStyle::Dotted,
+                    arrow: ArrowHead::Filled,
+                }),
+                SequenceItem::BlockSection {
+                    keyword: "else".into(),
+                    label: "Never".into(),
+                },
+                SequenceItem::Message(SequenceMessage {
+                    from: 1,
+                    to: 1,
+                    text: "Crash".into(),
+                    style: LineStyle::Solid,
+                    arrow: ArrowHead::Cross,
+                }),
+                SequenceItem::BlockEnd,
+                SequenceItem::Note {
+                    placement: NotePlacement::Over(0, 1),
+                    text: "Done".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flowchart_layout() {
+        let diagram =
+            parse_mermaid("flowchart TD\nA --> B\nA --> C\nB --> D\nC --> D\nD --> A").unwrap();
+        let layout = diagram.layout(&measure);
+        let centers = layout
+            .shapes
+            .iter()
+            .map(|shape| shape.bounds.center())
+            .collect::<Vec<_>>();
+
+        // The edge closing the cycle doesn't affect the ranks.
+        assert!(centers[0].y < centers[1].y);
+        assert_eq!(centers[1].y, centers[2].y);
+        assert!(centers[1].x < centers[2].x);
+        assert!(centers[2].y < centers[3].y);
+        assert_eq!(centers[0].x, centers[3].x);
+
+        // Edges start and end at the boundaries of the nodes.
+        let first_edge = &layout.lines[0];
+        assert!((first_edge.points[0].y - layout.shapes[0].bounds.bottom()).abs() < 0.01);
+        assert!((first_edge.points[1].y - layout.shapes[1].bounds.top()).abs() < 0.01);
+
+        assert!(layout.size.width >= layout.shapes[2].bounds.right() + MARGIN);
+        assert!(layout.size.height >= layout.shapes[3].bounds.bottom() + MARGIN);
+    }
+
+    #[test]
+    fn test_flowchart_layout_directions() {
+        let left_to_right = parse_mermaid("flowchart LR\nA --> B")
+            .unwrap()
+            .layout(&measure);
+        assert!(left_to_right.shapes[0].bounds.right() < left_to_right.shapes[1].bounds.left());
+
+        let bottom_to_top = parse_mermaid("flowchart BT\nA --> B")
+            .unwrap()
+            .layout(&measure);
+        assert!(bottom_to_top.shapes[0].bounds.top() > bottom_to_top.shapes[1].bounds.bottom());
+    }
+
+    #[test]
+    fn test_sequence_layout() {
+        let diagram =
+            parse_mermaid("sequenceDiagram\nA->>B: a rather long message to fit\nB-->>A: ok")
+                .unwrap();
+        let layout = diagram.layout(&measure);
+
+        let message = &layout.lines[2];
+        let reply = &layout.lines[3];
+        let message_width = (message.points[1].x - message.points[0].x).abs();
+        assert!(message_width >= measure("a rather long message to fit").width);
+        assert!(reply.points[0].y > message.points[0].y);
+        assert_eq!(reply.style, LineStyle::Dotted);
+
+        // Each participant is drawn above and below its lifeline.
+        assert_eq!(layout.shapes.len(), 4);
+        let lifeline = &layout.lines[0];
+        assert_eq!(lifeline.points[0].y, layout.shapes[0].bounds.bottom());
+        assert_eq!(lifeline.points[1].y, layout.shapes[1].bounds.top());
+    }
+}
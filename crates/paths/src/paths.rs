@@ -151,6 +151,12 @@ pub fn keymap_file() -> &'static PathBuf {
     KEYMAP_FILE.get_or_init(|| config_dir().join("keymap.json"))
 }
 
+/// Returns the path to the `vimrc` file, which is run when vim mode is enabled.
+pub fn vimrc_file() -> &'static PathBuf {
+    static VIMRC_FILE: OnceLock<PathBuf> = OnceLock::new();
+    VIMRC_FILE.get_or_init(|| config_dir().join("vimrc"))
+}
+
 /// Returns the path to the `tasks.json` file.
 pub fn tasks_file() -> &'static PathBuf {
     static TASKS_FILE: OnceLock<PathBuf> = OnceLock::new();
@@ -290,6 +296,14 @@ pub fn default_prettier_dir() -> &'static PathBuf {
     DEFAULT_PRETTIER_DIR.get_or_init(|| support_dir().join("prettier"))
 }
 
+/// Returns the path to the terminal shell integration directory.
+///
+/// This is where the scripts injected into the terminal shells are written to.
+pub fn shell_integration_dir() -> &'static PathBuf {
+    static SHELL_INTEGRATION_DIR: OnceLock<PathBuf> = OnceLock::new();
+    SHELL_INTEGRATION_DIR.get_or_init(|| support_dir().join("shell_integration"))
+}
+
 /// Returns the path to the remote server binaries directory.
 pub fn remote_servers_dir() -> &'static PathBuf {
     static REMOTE_SERVERS_DIR: OnceLock<PathBuf> = OnceLock::new();
@@ -1461,7 +1461,7 @@ impl BufferStore {
             let buffer = handle.read(cx);
             if let Some(entry_id) = buffer.entry_id(cx) {
                 open_buffers.insert(entry_id);
-            } else {
+            } else if query.scope_paths().is_none() {
                 limit = limit.saturating_sub(1);
                 unnamed_buffers.push(handle)
             };
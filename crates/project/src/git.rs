@@ -2,21 +2,37 @@ use crate::worktree_store::{WorktreeStore, WorktreeStoreEvent};
 use crate::{Project, ProjectPath};
 use anyhow::{anyhow, Context as _};
 use client::ProjectId;
-use futures::channel::mpsc;
-use futures::{SinkExt as _, StreamExt as _};
+use collections::HashMap;
+use futures::channel::{mpsc, oneshot};
+use futures::future::BoxFuture;
+use futures::{FutureExt as _, SinkExt as _, StreamExt as _};
 use git::{
-    repository::{GitRepository, RepoPath},
+    askpass::GitRemoteDelegate,
+    commit::{CommitFileDiff, CommitSummary, LogOptions},
+    repository::{
+        stage_lines, unstage_lines, GitRepository, PushOptions, Remote, RemoteCommandOutput,
+        RepoPath,
+    },
+    stash::StashEntry,
     status::{GitSummary, TrackedSummary},
+    Oid,
 };
 use gpui::{
     App, AppContext as _, Context, Entity, EventEmitter, SharedString, Subscription, WeakEntity,
 };
 use language::{Buffer, LanguageRegistry};
+use parking_lot::Mutex;
 use rpc::{proto, AnyProtoClient};
 use settings::WorktreeId;
-use std::sync::Arc;
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use text::Rope;
-use util::maybe;
+use util::{maybe, ResultExt};
 use worktree::{ProjectEntryId, RepositoryEntry, StatusEntry};
 
 pub struct GitState {
@@ -25,10 +41,32 @@ pub struct GitState {
     repositories: Vec<RepositoryHandle>,
     active_index: Option<usize>,
     update_sender: mpsc::UnboundedSender<(Message, mpsc::Sender<anyhow::Error>)>,
+    remote_operation_sender: mpsc::UnboundedSender<(Message, mpsc::Sender<anyhow::Error>)>,
     languages: Arc<LanguageRegistry>,
+    askpass_delegates: AskPassDelegates,
     _subscription: Subscription,
 }
 
+/// Delegates of in-flight remote operations on remote repositories, keyed by the id
+/// sent along with the request so that the host can route credential prompts back.
+#[derive(Clone, Default)]
+struct AskPassDelegates {
+    next_id: Arc<AtomicU64>,
+    delegates: Arc<Mutex<HashMap<u64, Arc<dyn GitRemoteDelegate>>>>,
+}
+
+impl AskPassDelegates {
+    fn register(&self, delegate: Arc<dyn GitRemoteDelegate>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.delegates.lock().insert(id, delegate);
+        id
+    }
+
+    fn unregister(&self, id: u64) {
+        self.delegates.lock().remove(&id);
+    }
+}
+
 #[derive(Clone)]
 pub struct RepositoryHandle {
     git_state: WeakEntity<GitState>,
@@ -37,6 +75,7 @@ pub struct RepositoryHandle {
     git_repo: Option<GitRepo>,
     commit_message: Entity<Buffer>,
     update_sender: mpsc::UnboundedSender<(Message, mpsc::Sender<anyhow::Error>)>,
+    remote_operation_sender: mpsc::UnboundedSender<(Message, mpsc::Sender<anyhow::Error>)>,
 }
 
 #[derive(Clone)]
@@ -80,6 +119,93 @@ enum Message {
     },
     Stage(GitRepo, Vec<RepoPath>),
     Unstage(GitRepo, Vec<RepoPath>),
+    GetRemotes {
+        git_repo: GitRepo,
+        branch_name: Option<SharedString>,
+        response: oneshot::Sender<anyhow::Result<Vec<Remote>>>,
+    },
+    Fetch {
+        git_repo: GitRepo,
+        delegate: Arc<dyn GitRemoteDelegate>,
+        response: oneshot::Sender<anyhow::Result<RemoteCommandOutput>>,
+    },
+    Pull {
+        git_repo: GitRepo,
+        branch_name: SharedString,
+        remote_name: SharedString,
+        delegate: Arc<dyn GitRemoteDelegate>,
+        response: oneshot::Sender<anyhow::Result<RemoteCommandOutput>>,
+    },
+    Push {
+        git_repo: GitRepo,
+        branch_name: SharedString,
+        remote_name: SharedString,
+        options: Option<PushOptions>,
+        delegate: Arc<dyn GitRemoteDelegate>,
+        response: oneshot::Sender<anyhow::Result<RemoteCommandOutput>>,
+    },
+    Stash {
+        git_repo: GitRepo,
+        message: Option<SharedString>,
+        response: oneshot::Sender<anyhow::Result<()>>,
+    },
+    UpdateStashEntry {
+        git_repo: GitRepo,
+        index: usize,
+        oid: Oid,
+        action: StashAction,
+        response: oneshot::Sender<anyhow::Result<()>>,
+    },
+    StashEntries {
+        git_repo: GitRepo,
+        response: oneshot::Sender<anyhow::Result<Vec<StashEntry>>>,
+    },
+    StashDiff {
+        git_repo: GitRepo,
+        index: usize,
+        oid: Oid,
+        response: oneshot::Sender<anyhow::Result<Vec<CommitFileDiff>>>,
+    },
+    Log {
+        git_repo: GitRepo,
+        options: LogOptions,
+        response: oneshot::Sender<anyhow::Result<Vec<CommitSummary>>>,
+    },
+    CommitDiff {
+        git_repo: GitRepo,
+        sha: SharedString,
+        response: oneshot::Sender<anyhow::Result<Vec<CommitFileDiff>>>,
+    },
+    ChangedPathsSince {
+        git_repo: GitRepo,
+        base: SharedString,
+        response: oneshot::Sender<anyhow::Result<Vec<RepoPath>>>,
+    },
+    StageLines {
+        git_repo: GitRepo,
+        path: RepoPath,
+        buffer_text: String,
+        rows: Vec<Range<u32>>,
+        stage: bool,
+        whole_hunks: bool,
+        response: oneshot::Sender<anyhow::Result<()>>,
+    },
+}
+
+impl Message {
+    fn is_remote_operation(&self) -> bool {
+        matches!(
+            self,
+            Message::Fetch { .. } | Message::Pull { .. } | Message::Push { .. }
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StashAction {
+    Apply,
+    Pop,
+    Drop,
 }
 
 pub enum Event {
@@ -96,160 +222,11 @@ impl GitState {
         project_id: Option<ProjectId>,
         cx: &mut Context<'_, Self>,
     ) -> Self {
-        let (update_sender, mut update_receiver) =
-            mpsc::unbounded::<(Message, mpsc::Sender<anyhow::Error>)>();
-        cx.spawn(|_, cx| async move {
-            while let Some((msg, mut err_sender)) = update_receiver.next().await {
-                let result = cx
-                    .background_executor()
-                    .spawn(async move {
-                        match msg {
-                            Message::StageAndCommit {
-                                git_repo,
-                                message,
-                                name_and_email,
-                                paths,
-                            } => {
-                                match git_repo {
-                                    GitRepo::Local(repo) => {
-                                        repo.stage_paths(&paths)?;
-                                        repo.commit(
-                                            &message.to_string(),
-                                            name_and_email.as_ref().map(|(name, email)| {
-                                                (name.as_ref(), email.as_ref())
-                                            }),
-                                        )?;
-                                    }
-                                    GitRepo::Remote {
-                                        project_id,
-                                        client,
-                                        worktree_id,
-                                        work_directory_id,
-                                    } => {
-                                        client
-                                            .request(proto::Stage {
-                                                project_id: project_id.0,
-                                                worktree_id: worktree_id.to_proto(),
-                                                work_directory_id: work_directory_id.to_proto(),
-                                                paths: paths
-                                                    .into_iter()
-                                                    .map(|repo_path| repo_path.to_proto())
-                                                    .collect(),
-                                            })
-                                            .await
-                                            .context("sending stage request")?;
-                                        let (name, email) = name_and_email.unzip();
-                                        client
-                                            .request(proto::Commit {
-                                                project_id: project_id.0,
-                                                worktree_id: worktree_id.to_proto(),
-                                                work_directory_id: work_directory_id.to_proto(),
-                                                message: message.to_string(),
-                                                name: name.map(String::from),
-                                                email: email.map(String::from),
-                                            })
-                                            .await
-                                            .context("sending commit request")?;
-                                    }
-                                }
-
-                                Ok(())
-                            }
-                            Message::Stage(repo, paths) => {
-                                match repo {
-                                    GitRepo::Local(repo) => repo.stage_paths(&paths)?,
-                                    GitRepo::Remote {
-                                        project_id,
-                                        client,
-                                        worktree_id,
-                                        work_directory_id,
-                                    } => {
-                                        client
-                                            .request(proto::Stage {
-                                                project_id: project_id.0,
-                                                worktree_id: worktree_id.to_proto(),
-                                                work_directory_id: work_directory_id.to_proto(),
-                                                paths: paths
-                                                    .into_iter()
-                                                    .map(|repo_path| repo_path.to_proto())
-                                                    .collect(),
-                                            })
-                                            .await
-                                            .context("sending stage request")?;
-                                    }
-                                }
-                                Ok(())
-                            }
-                            Message::Unstage(repo, paths) => {
-                                match repo {
-                                    GitRepo::Local(repo) => repo.unstage_paths(&paths)?,
-                                    GitRepo::Remote {
-                                        project_id,
-                                        client,
-                                        worktree_id,
-                                        work_directory_id,
-                                    } => {
-                                        client
-                                            .request(proto::Unstage {
-                                                project_id: project_id.0,
-                                                worktree_id: worktree_id.to_proto(),
-                                                work_directory_id: work_directory_id.to_proto(),
-                                                paths: paths
-                                                    .into_iter()
-                                                    .map(|repo_path| repo_path.to_proto())
-                                                    .collect(),
-                                            })
-                                            .await
-                                            .context("sending unstage request")?;
-                                    }
-                                }
-                                Ok(())
-                            }
-                            Message::Commit {
-                                git_repo,
-                                message,
-                                name_and_email,
-                            } => {
-                                match git_repo {
-                                    GitRepo::Local(repo) => repo.commit(
-                                        &message.to_string(),
-                                        name_and_email
-                                            .as_ref()
-                                            .map(|(name, email)| (name.as_ref(), email.as_ref())),
-                                    )?,
-                                    GitRepo::Remote {
-                                        project_id,
-                                        client,
-                                        worktree_id,
-                                        work_directory_id,
-                                    } => {
-                                        let (name, email) = name_and_email.unzip();
-                                        client
-                                            .request(proto::Commit {
-                                                project_id: project_id.0,
-                                                worktree_id: worktree_id.to_proto(),
-                                                work_directory_id: work_directory_id.to_proto(),
-                                                // TODO implement collaborative commit message buffer instead and use it
-                                                // If it works, remove `commit_with_message` method.
-                                                message: message.to_string(),
-                                                name: name.map(String::from),
-                                                email: email.map(String::from),
-                                            })
-                                            .await
-                                            .context("sending commit request")?;
-                                    }
-                                }
-                                Ok(())
-                            }
-                        }
-                    })
-                    .await;
-                if let Err(e) = result {
-                    err_sender.send(e).await.ok();
-                }
-            }
-        })
-        .detach();
+        let askpass_delegates = AskPassDelegates::default();
+        let update_sender = Self::spawn_message_queue(askpass_delegates.clone(), cx);
+        // Fetches, pulls and pushes can wait on the network and on credential prompts for a
+        // long time, so they are queued separately from local operations like staging.
+        let remote_operation_sender = Self::spawn_message_queue(askpass_delegates.clone(), cx);
 
         let _subscription = cx.subscribe(worktree_store, Self::on_worktree_store_event);
 
@@ -260,10 +237,58 @@ impl GitState {
             repositories: Vec::new(),
             active_index: None,
             update_sender,
+            remote_operation_sender,
+            askpass_delegates,
             _subscription,
         }
     }
 
+    /// Spawns a loop that runs the operations sent to the returned queue one at a time.
+    fn spawn_message_queue(
+        askpass_delegates: AskPassDelegates,
+        cx: &mut Context<'_, Self>,
+    ) -> mpsc::UnboundedSender<(Message, mpsc::Sender<anyhow::Error>)> {
+        let (sender, mut receiver) = mpsc::unbounded::<(Message, mpsc::Sender<anyhow::Error>)>();
+        cx.spawn(|_, cx| async move {
+            while let Some((msg, mut err_sender)) = receiver.next().await {
+                let result = cx
+                    .background_executor()
+                    .spawn(process_message(msg, askpass_delegates.clone()))
+                    .await;
+                if let Err(e) = result {
+                    err_sender.send(e).await.ok();
+                }
+            }
+        })
+        .detach();
+        sender
+    }
+
+    /// Returns the repository with the given work directory, as identified in proto messages.
+    pub fn repository_for_proto(
+        &self,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+    ) -> anyhow::Result<RepositoryHandle> {
+        self.repositories
+            .iter()
+            .find(|repository_handle| {
+                repository_handle.worktree_id == worktree_id
+                    && repository_handle.repository_entry.work_directory_id() == work_directory_id
+            })
+            .cloned()
+            .context("missing repository handle")
+    }
+
+    /// Returns the delegate of an in-flight remote operation started from this project.
+    pub fn askpass_delegate(&self, askpass_id: u64) -> Option<Arc<dyn GitRemoteDelegate>> {
+        self.askpass_delegates
+            .delegates
+            .lock()
+            .get(&askpass_id)
+            .cloned()
+    }
+
     pub fn active_repository(&self) -> Option<RepositoryHandle> {
         self.active_index
             .map(|index| self.repositories[index].clone())
@@ -337,6 +362,7 @@ impl GitState {
                                 git_repo,
                                 commit_message,
                                 update_sender: self.update_sender.clone(),
+                                remote_operation_sender: self.remote_operation_sender.clone(),
                             }
                         };
                         new_repositories.push(handle);
@@ -358,6 +384,30 @@ impl GitState {
     pub fn all_repositories(&self) -> Vec<RepositoryHandle> {
         self.repositories.clone()
     }
+
+    /// Returns the innermost repository containing the given path, along with the
+    /// path relative to that repository's root.
+    pub fn repository_for_project_path(
+        &self,
+        project_path: &ProjectPath,
+    ) -> Option<(RepositoryHandle, RepoPath)> {
+        self.repositories
+            .iter()
+            .filter(|repo| {
+                repo.worktree_id == project_path.worktree_id
+                    && project_path
+                        .path
+                        .starts_with(&**repo.repository_entry.work_directory)
+            })
+            .max_by_key(|repo| repo.repository_entry.work_directory.components().count())
+            .and_then(|repo| {
+                let repo_path = repo
+                    .repository_entry
+                    .relativize(&project_path.path)
+                    .log_err()?;
+                Some((repo.clone(), repo_path))
+            })
+    }
 }
 
 impl RepositoryHandle {
@@ -580,4 +630,991 @@ impl RepositoryHandle {
             commit_message.set_text("", cx);
         });
     }
+
+    pub fn get_remotes(
+        &self,
+        branch_name: Option<SharedString>,
+    ) -> oneshot::Receiver<anyhow::Result<Vec<Remote>>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::GetRemotes {
+                git_repo,
+                branch_name,
+                response,
+            },
+            "get remotes",
+        );
+        receiver
+    }
+
+    pub fn fetch(
+        &self,
+        delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> oneshot::Receiver<anyhow::Result<RemoteCommandOutput>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::Fetch {
+                git_repo,
+                delegate,
+                response,
+            },
+            "fetch",
+        );
+        receiver
+    }
+
+    pub fn pull(
+        &self,
+        branch_name: SharedString,
+        remote_name: SharedString,
+        delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> oneshot::Receiver<anyhow::Result<RemoteCommandOutput>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::Pull {
+                git_repo,
+                branch_name,
+                remote_name,
+                delegate,
+                response,
+            },
+            "pull",
+        );
+        receiver
+    }
+
+    pub fn push(
+        &self,
+        branch_name: SharedString,
+        remote_name: SharedString,
+        options: Option<PushOptions>,
+        delegate: Arc<dyn GitRemoteDelegate>,
+    ) -> oneshot::Receiver<anyhow::Result<RemoteCommandOutput>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::Push {
+                git_repo,
+                branch_name,
+                remote_name,
+                options,
+                delegate,
+                response,
+            },
+            "push",
+        );
+        receiver
+    }
+
+    pub fn stash_all(
+        &self,
+        message: Option<SharedString>,
+    ) -> oneshot::Receiver<anyhow::Result<()>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::Stash {
+                git_repo,
+                message,
+                response,
+            },
+            "stash",
+        );
+        receiver
+    }
+
+    /// Applies the stash entry at `index`, as long as it is still the entry with the given oid.
+    pub fn stash_apply(&self, index: usize, oid: Oid) -> oneshot::Receiver<anyhow::Result<()>> {
+        self.update_stash_entry(index, oid, StashAction::Apply, "stash apply")
+    }
+
+    pub fn stash_pop(&self, index: usize, oid: Oid) -> oneshot::Receiver<anyhow::Result<()>> {
+        self.update_stash_entry(index, oid, StashAction::Pop, "stash pop")
+    }
+
+    pub fn stash_drop(&self, index: usize, oid: Oid) -> oneshot::Receiver<anyhow::Result<()>> {
+        self.update_stash_entry(index, oid, StashAction::Drop, "stash drop")
+    }
+
+    fn update_stash_entry(
+        &self,
+        index: usize,
+        oid: Oid,
+        action: StashAction,
+        operation: &str,
+    ) -> oneshot::Receiver<anyhow::Result<()>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::UpdateStashEntry {
+                git_repo,
+                index,
+                oid,
+                action,
+                response,
+            },
+            operation,
+        );
+        receiver
+    }
+
+    pub fn stash_entries(&self) -> oneshot::Receiver<anyhow::Result<Vec<StashEntry>>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::StashEntries { git_repo, response },
+            "list stash",
+        );
+        receiver
+    }
+
+    pub fn stash_diff(
+        &self,
+        index: usize,
+        oid: Oid,
+    ) -> oneshot::Receiver<anyhow::Result<Vec<CommitFileDiff>>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::StashDiff {
+                git_repo,
+                index,
+                oid,
+                response,
+            },
+            "stash diff",
+        );
+        receiver
+    }
+
+    pub fn log(
+        &self,
+        options: LogOptions,
+    ) -> oneshot::Receiver<anyhow::Result<Vec<CommitSummary>>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::Log {
+                git_repo,
+                options,
+                response,
+            },
+            "log",
+        );
+        receiver
+    }
+
+    pub fn commit_diff(
+        &self,
+        sha: SharedString,
+    ) -> oneshot::Receiver<anyhow::Result<Vec<CommitFileDiff>>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::CommitDiff {
+                git_repo,
+                sha,
+                response,
+            },
+            "commit diff",
+        );
+        receiver
+    }
+
+    /// Returns the paths that changed since the current branch diverged from `base`,
+    /// including uncommitted and untracked files.
+    pub fn changed_paths_since(
+        &self,
+        base: SharedString,
+    ) -> oneshot::Receiver<anyhow::Result<Vec<RepoPath>>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::ChangedPathsSince {
+                git_repo,
+                base,
+                response,
+            },
+            "changed paths",
+        );
+        receiver
+    }
+
+    /// Stages the changes between the index and the given buffer text that touch the
+    /// given buffer rows.
+    pub fn stage_lines(
+        &self,
+        path: RepoPath,
+        buffer_text: String,
+        rows: Vec<Range<u32>>,
+    ) -> oneshot::Receiver<anyhow::Result<()>> {
+        self.send_stage_lines(path, buffer_text, rows, true, false)
+    }
+
+    /// Reverts the staged changes that touch the given buffer rows, leaving the buffer
+    /// itself untouched. With `whole_hunks`, every staged hunk touched by the rows is
+    /// reverted in full.
+    pub fn unstage_lines(
+        &self,
+        path: RepoPath,
+        buffer_text: String,
+        rows: Vec<Range<u32>>,
+        whole_hunks: bool,
+    ) -> oneshot::Receiver<anyhow::Result<()>> {
+        self.send_stage_lines(path, buffer_text, rows, false, whole_hunks)
+    }
+
+    fn send_stage_lines(
+        &self,
+        path: RepoPath,
+        buffer_text: String,
+        rows: Vec<Range<u32>>,
+        stage: bool,
+        whole_hunks: bool,
+    ) -> oneshot::Receiver<anyhow::Result<()>> {
+        let (response, receiver) = oneshot::channel();
+        self.send_message_with_response(
+            |git_repo| Message::StageLines {
+                git_repo,
+                path,
+                buffer_text,
+                rows,
+                stage,
+                whole_hunks,
+                response,
+            },
+            if stage {
+                "stage lines"
+            } else {
+                "unstage lines"
+            },
+        );
+        receiver
+    }
+
+    /// Queues an operation whose result is reported through its own response channel.
+    /// If the operation cannot be queued, the response sender is dropped and the
+    /// receiver resolves to `Canceled`.
+    fn send_message_with_response(
+        &self,
+        message: impl FnOnce(GitRepo) -> Message,
+        operation: &str,
+    ) {
+        let Some(git_repo) = self.git_repo.clone() else {
+            return;
+        };
+        let message = message(git_repo);
+        let sender = if message.is_remote_operation() {
+            &self.remote_operation_sender
+        } else {
+            &self.update_sender
+        };
+        // Failures are reported through the message's response channel instead.
+        let (err_sender, _) = mpsc::channel(1);
+        sender
+            .unbounded_send((message, err_sender))
+            .map_err(|_| anyhow!("Failed to submit {operation} operation"))
+            .log_err();
+    }
+}
+
+/// Runs a queued operation, either on the local repository or by forwarding it to the host.
+async fn process_message(msg: Message, askpass_delegates: AskPassDelegates) -> anyhow::Result<()> {
+    match msg {
+        Message::StageAndCommit {
+            git_repo,
+            message,
+            name_and_email,
+            paths,
+        } => {
+            match git_repo {
+                GitRepo::Local(repo) => {
+                    repo.stage_paths(&paths)?;
+                    repo.commit(
+                        &message.to_string(),
+                        name_and_email
+                            .as_ref()
+                            .map(|(name, email)| (name.as_ref(), email.as_ref())),
+                    )?;
+                }
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::Stage {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            paths: paths
+                                .into_iter()
+                                .map(|repo_path| repo_path.to_proto())
+                                .collect(),
+                        })
+                        .await
+                        .context("sending stage request")?;
+                    let (name, email) = name_and_email.unzip();
+                    client
+                        .request(proto::Commit {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            message: message.to_string(),
+                            name: name.map(String::from),
+                            email: email.map(String::from),
+                        })
+                        .await
+                        .context("sending commit request")?;
+                }
+            }
+
+            Ok(())
+        }
+        Message::Stage(repo, paths) => {
+            match repo {
+                GitRepo::Local(repo) => repo.stage_paths(&paths)?,
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::Stage {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            paths: paths
+                                .into_iter()
+                                .map(|repo_path| repo_path.to_proto())
+                                .collect(),
+                        })
+                        .await
+                        .context("sending stage request")?;
+                }
+            }
+            Ok(())
+        }
+        Message::Unstage(repo, paths) => {
+            match repo {
+                GitRepo::Local(repo) => repo.unstage_paths(&paths)?,
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::Unstage {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            paths: paths
+                                .into_iter()
+                                .map(|repo_path| repo_path.to_proto())
+                                .collect(),
+                        })
+                        .await
+                        .context("sending unstage request")?;
+                }
+            }
+            Ok(())
+        }
+        Message::Commit {
+            git_repo,
+            message,
+            name_and_email,
+        } => {
+            match git_repo {
+                GitRepo::Local(repo) => repo.commit(
+                    &message.to_string(),
+                    name_and_email
+                        .as_ref()
+                        .map(|(name, email)| (name.as_ref(), email.as_ref())),
+                )?,
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let (name, email) = name_and_email.unzip();
+                    client
+                        .request(proto::Commit {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            // TODO implement collaborative commit message buffer instead and use it
+                            // If it works, remove `commit_with_message` method.
+                            message: message.to_string(),
+                            name: name.map(String::from),
+                            email: email.map(String::from),
+                        })
+                        .await
+                        .context("sending commit request")?;
+                }
+            }
+            Ok(())
+        }
+        Message::GetRemotes {
+            git_repo,
+            branch_name,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.get_remotes(branch_name.as_deref()),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => client
+                    .request(proto::GetRemotes {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                        branch_name: branch_name.map(String::from),
+                    })
+                    .await
+                    .context("sending get remotes request")
+                    .map(|response| {
+                        response
+                            .remotes
+                            .into_iter()
+                            .map(|remote| Remote {
+                                name: remote.name.into(),
+                            })
+                            .collect()
+                    }),
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::Fetch {
+            git_repo,
+            delegate,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.fetch(delegate),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let askpass_id = askpass_delegates.register(delegate);
+                    let result = client
+                        .request(proto::Fetch {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            askpass_id,
+                        })
+                        .await
+                        .context("sending fetch request");
+                    askpass_delegates.unregister(askpass_id);
+                    result.map(remote_command_output_from_proto)
+                }
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::Pull {
+            git_repo,
+            branch_name,
+            remote_name,
+            delegate,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.pull(&branch_name, &remote_name, delegate),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let askpass_id = askpass_delegates.register(delegate);
+                    let result = client
+                        .request(proto::Pull {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            remote_name: remote_name.to_string(),
+                            branch_name: branch_name.to_string(),
+                            askpass_id,
+                        })
+                        .await
+                        .context("sending pull request");
+                    askpass_delegates.unregister(askpass_id);
+                    result.map(remote_command_output_from_proto)
+                }
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::Push {
+            git_repo,
+            branch_name,
+            remote_name,
+            options,
+            delegate,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.push(&branch_name, &remote_name, options, delegate),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let askpass_id = askpass_delegates.register(delegate);
+                    let result = client
+                        .request(proto::Push {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            remote_name: remote_name.to_string(),
+                            branch_name: branch_name.to_string(),
+                            options: options.map(|options| match options {
+                                PushOptions::SetUpstream => proto::push::PushOptions::SetUpstream,
+                                PushOptions::Force => proto::push::PushOptions::Force,
+                                PushOptions::ForceSetUpstream => {
+                                    proto::push::PushOptions::ForceSetUpstream
+                                }
+                            } as i32),
+                            askpass_id,
+                        })
+                        .await
+                        .context("sending push request");
+                    askpass_delegates.unregister(askpass_id);
+                    result.map(remote_command_output_from_proto)
+                }
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::Stash {
+            git_repo,
+            message,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.stash_all(message.as_deref()),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => client
+                    .request(proto::Stash {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                        message: message.map(String::from),
+                    })
+                    .await
+                    .context("sending stash request")
+                    .map(|_| ()),
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::UpdateStashEntry {
+            git_repo,
+            index,
+            oid,
+            action,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => match action {
+                    StashAction::Apply => repo.stash_apply(index, oid),
+                    StashAction::Pop => repo.stash_pop(index, oid),
+                    StashAction::Drop => repo.stash_drop(index, oid),
+                },
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let project_id = project_id.0;
+                    let worktree_id = worktree_id.to_proto();
+                    let work_directory_id = work_directory_id.to_proto();
+                    let index = index as u64;
+                    let sha = oid.to_string();
+                    match action {
+                        StashAction::Apply => client
+                            .request(proto::StashApply {
+                                project_id,
+                                worktree_id,
+                                work_directory_id,
+                                index,
+                                sha: sha.clone(),
+                            })
+                            .await
+                            .context("sending stash apply request"),
+                        StashAction::Pop => client
+                            .request(proto::StashPop {
+                                project_id,
+                                worktree_id,
+                                work_directory_id,
+                                index,
+                                sha: sha.clone(),
+                            })
+                            .await
+                            .context("sending stash pop request"),
+                        StashAction::Drop => client
+                            .request(proto::StashDrop {
+                                project_id,
+                                worktree_id,
+                                work_directory_id,
+                                index,
+                                sha: sha.clone(),
+                            })
+                            .await
+                            .context("sending stash drop request"),
+                    }
+                    .map(|_| ())
+                }
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::StashEntries { git_repo, response } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.stash_entries(),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => client
+                    .request(proto::GetStashEntries {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                    })
+                    .await
+                    .context("sending get stash entries request")
+                    .and_then(|response| {
+                        response
+                            .entries
+                            .into_iter()
+                            .map(stash_entry_from_proto)
+                            .collect()
+                    }),
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::StashDiff {
+            git_repo,
+            index,
+            oid,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.stash_diff(index, oid),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => client
+                    .request(proto::GetStashDiff {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                        index: index as u64,
+                        sha: oid.to_string(),
+                    })
+                    .await
+                    .context("sending get stash diff request")
+                    .map(|response| {
+                        response
+                            .files
+                            .into_iter()
+                            .map(commit_file_diff_from_proto)
+                            .collect()
+                    }),
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::Log {
+            git_repo,
+            options,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.log(&options),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => client
+                    .request(proto::GetCommitLog {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                        path: options.path.map(|path| path.to_proto()),
+                        author: options.author,
+                        skip: options.skip as u64,
+                        limit: options.limit as u64,
+                    })
+                    .await
+                    .context("sending get commit log request")
+                    .and_then(|response| {
+                        response
+                            .commits
+                            .into_iter()
+                            .map(commit_summary_from_proto)
+                            .collect()
+                    }),
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::CommitDiff {
+            git_repo,
+            sha,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.commit_diff(&sha),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => client
+                    .request(proto::GetCommitDiff {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                        sha: sha.to_string(),
+                    })
+                    .await
+                    .context("sending get commit diff request")
+                    .map(|response| {
+                        response
+                            .files
+                            .into_iter()
+                            .map(commit_file_diff_from_proto)
+                            .collect()
+                    }),
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::ChangedPathsSince {
+            git_repo,
+            base,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => repo.changed_paths_since(&base),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => client
+                    .request(proto::GetChangedPathsSince {
+                        project_id: project_id.0,
+                        worktree_id: worktree_id.to_proto(),
+                        work_directory_id: work_directory_id.to_proto(),
+                        base: base.to_string(),
+                    })
+                    .await
+                    .context("sending get changed paths request")
+                    .map(|response| {
+                        response
+                            .paths
+                            .iter()
+                            .map(|path| RepoPath::from_str(path))
+                            .collect()
+                    }),
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+        Message::StageLines {
+            git_repo,
+            path,
+            buffer_text,
+            rows,
+            stage,
+            whole_hunks,
+            response,
+        } => {
+            let result = match git_repo {
+                GitRepo::Local(repo) => {
+                    if stage {
+                        stage_lines(repo.as_ref(), &path, &buffer_text, &rows)
+                    } else {
+                        unstage_lines(repo.as_ref(), &path, &buffer_text, &rows, whole_hunks)
+                    }
+                }
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let rows = rows
+                        .into_iter()
+                        .map(|rows| proto::Range {
+                            start: rows.start as u64,
+                            end: rows.end as u64,
+                        })
+                        .collect();
+                    if stage {
+                        client
+                            .request(proto::StageLines {
+                                project_id: project_id.0,
+                                worktree_id: worktree_id.to_proto(),
+                                work_directory_id: work_directory_id.to_proto(),
+                                path: path.to_proto(),
+                                buffer_text,
+                                rows,
+                            })
+                            .await
+                            .context("sending stage lines request")
+                            .map(|_| ())
+                    } else {
+                        client
+                            .request(proto::UnstageLines {
+                                project_id: project_id.0,
+                                worktree_id: worktree_id.to_proto(),
+                                work_directory_id: work_directory_id.to_proto(),
+                                path: path.to_proto(),
+                                buffer_text,
+                                rows,
+                                whole_hunks,
+                            })
+                            .await
+                            .context("sending unstage lines request")
+                            .map(|_| ())
+                    }
+                }
+            };
+            response.send(result).ok();
+            Ok(())
+        }
+    }
+}
+
+fn remote_command_output_from_proto(response: proto::RemoteMessageResponse) -> RemoteCommandOutput {
+    RemoteCommandOutput {
+        stdout: response.stdout,
+        stderr: response.stderr,
+    }
+}
+
+/// Forwards progress and credential prompts of a remote operation that was requested
+/// over RPC to the client that requested it.
+pub struct ProtoGitRemoteDelegate {
+    pub client: AnyProtoClient,
+    pub project_id: u64,
+    pub worktree_id: WorktreeId,
+    pub work_directory_id: ProjectEntryId,
+    pub askpass_id: u64,
+    /// The guest that requested the operation, when it was forwarded by collab.
+    pub peer_id: Option<proto::PeerId>,
+}
+
+impl GitRemoteDelegate for ProtoGitRemoteDelegate {
+    fn progress(&self, line: &str) {
+        self.client
+            .send(proto::GitRemoteProgress {
+                project_id: self.project_id,
+                worktree_id: self.worktree_id.to_proto(),
+                work_directory_id: self.work_directory_id.to_proto(),
+                askpass_id: self.askpass_id,
+                line: line.to_string(),
+                peer_id: self.peer_id,
+            })
+            .log_err();
+    }
+
+    fn ask_pass(&self, prompt: String) -> BoxFuture<'static, Option<String>> {
+        let response = self.client.request(proto::AskPassRequest {
+            project_id: self.project_id,
+            worktree_id: self.worktree_id.to_proto(),
+            work_directory_id: self.work_directory_id.to_proto(),
+            askpass_id: self.askpass_id,
+            prompt,
+            peer_id: self.peer_id,
+        });
+        async move { response.await.log_err()?.response }.boxed()
+    }
+}
+
+pub fn remote_command_output_to_proto(output: RemoteCommandOutput) -> proto::RemoteMessageResponse {
+    proto::RemoteMessageResponse {
+        stdout: output.stdout,
+        stderr: output.stderr,
+    }
+}
+
+pub fn stash_entry_to_proto(entry: &StashEntry) -> proto::get_stash_entries_response::StashEntry {
+    proto::get_stash_entries_response::StashEntry {
+        index: entry.index as u64,
+        sha: entry.oid.to_string(),
+        message: entry.message.to_string(),
+        branch: entry.branch.as_ref().map(|branch| branch.to_string()),
+        unix_timestamp: entry.unix_timestamp,
+    }
+}
+
+fn stash_entry_from_proto(
+    entry: proto::get_stash_entries_response::StashEntry,
+) -> anyhow::Result<StashEntry> {
+    Ok(StashEntry {
+        index: entry.index as usize,
+        oid: entry.sha.parse::<Oid>()?,
+        message: entry.message.into(),
+        branch: entry.branch.map(Into::into),
+        unix_timestamp: entry.unix_timestamp,
+    })
+}
+
+pub fn commit_file_diff_to_proto(file: CommitFileDiff) -> proto::CommitFileDiff {
+    proto::CommitFileDiff {
+        path: file.path.to_proto(),
+        old_text: file.old_text,
+        new_text: file.new_text,
+    }
+}
+
+fn commit_file_diff_from_proto(file: proto::CommitFileDiff) -> CommitFileDiff {
+    CommitFileDiff {
+        path: RepoPath::from_str(&file.path),
+        old_text: file.old_text,
+        new_text: file.new_text,
+    }
+}
+
+pub fn commit_summary_to_proto(
+    commit: &CommitSummary,
+) -> proto::get_commit_log_response::CommitSummary {
+    proto::get_commit_log_response::CommitSummary {
+        sha: commit.sha.to_string(),
+        author_name: commit.author_name.to_string(),
+        author_email: commit.author_email.to_string(),
+        commit_timestamp: commit.commit_timestamp,
+        subject: commit.subject.to_string(),
+    }
+}
+
+fn commit_summary_from_proto(
+    commit: proto::get_commit_log_response::CommitSummary,
+) -> anyhow::Result<CommitSummary> {
+    Ok(CommitSummary {
+        sha: commit.sha.parse::<Oid>()?,
+        author_name: commit.author_name.into(),
+        author_email: commit.author_email.into(),
+        commit_timestamp: commit.commit_timestamp,
+        subject: commit.subject.into(),
+    })
 }
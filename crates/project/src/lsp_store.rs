@@ -7,11 +7,15 @@ use crate::{
     prettier_store::{self, PrettierStore, PrettierStoreEvent},
     project_settings::{LspSettings, ProjectSettings},
     relativize_path, resolve_path,
+    semantic_tokens::{
+        apply_token_edits, decode_tokens, encode_tokens, BufferSemanticTokens, LspSemanticTokens,
+        SemanticToken,
+    },
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
-    CodeAction, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _, ProjectPath,
-    ProjectTransaction, ResolveState, Symbol, ToolchainStore,
+    CodeAction, CodeLens, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _,
+    ProjectPath, ProjectTransaction, ResolveState, Symbol, ToolchainStore,
 };
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
@@ -30,16 +34,17 @@ use gpui::{
 use http_client::HttpClient;
 use itertools::Itertools as _;
 use language::{
+    anchor_offset_edits,
     language_settings::{
         language_settings, FormatOnSave, Formatter, LanguageSettings, SelectedFormatter,
     },
     markdown, point_to_lsp, prepare_completion_documentation,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
     range_from_lsp, range_to_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CodeLabel,
-    Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, Documentation, File as _, Language,
-    LanguageName, LanguageRegistry, LanguageServerBinaryStatus, LanguageToolchainStore, LocalFile,
-    LspAdapter, LspAdapterDelegate, Patch, PointUtf16, TextBufferSnapshot, ToOffset, ToPointUtf16,
-    Transaction, Unclipped,
+    Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, Documentation, File as _, FormatRequest,
+    Language, LanguageName, LanguageRegistry, LanguageServerBinaryStatus, LanguageToolchainStore,
+    LocalFile, LspAdapter, LspAdapterDelegate, Patch, PointUtf16, TextBufferSnapshot, ToOffset,
+    ToPointUtf16, Transaction, Unclipped,
 };
 use lsp::{
     notification::DidRenameFiles, CodeActionKind, CompletionContext, DiagnosticSeverity,
@@ -76,6 +81,7 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+use task::{Problem, ProblemSeverity};
 use text::{Anchor, BufferId, LineEnding, OffsetRangeExt};
 use util::{
     debug_panic, defer, maybe, merge_json_value_into, paths::SanitizedPath, post_inc, ResultExt,
@@ -93,6 +99,8 @@ pub use worktree::{
 
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 pub const SERVER_PROGRESS_THROTTLE_TIMEOUT: Duration = Duration::from_millis(100);
+/// A language server id, not backed by any server, that the diagnostics of all tasks are reported under.
+pub const TASK_DIAGNOSTICS_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormatTrigger {
@@ -116,6 +124,7 @@ pub enum FormatOperation {
     Lsp(Vec<(Range<Anchor>, String)>),
     External(Diff),
     Prettier(Diff),
+    Extension(Vec<(Range<Anchor>, String)>),
 }
 
 impl FormatTrigger {
@@ -161,6 +170,8 @@ pub struct LocalLspStore {
         >,
     >,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    task_diagnostics: HashMap<String, TaskDiagnostics>, // task label -> diagnostics
+    semantic_tokens: HashMap<BufferId, LspSemanticTokens>,
     _subscription: gpui::Subscription,
     registered_buffers: HashMap<BufferId, usize>,
 }
@@ -1402,6 +1413,9 @@ impl LocalLspStore {
                         FormatOperation::Prettier(diff) => {
                             b.apply_diff(diff, cx);
                         }
+                        FormatOperation::Extension(edits) => {
+                            b.edit(edits, None, cx);
+                        }
                     }
 
                     if let Some(transaction_id) = whitespace_transaction_id {
@@ -1515,10 +1529,66 @@ impl LocalLspStore {
                 }
                 None
             }
+            Formatter::Extension { name } => {
+                let edits = Self::format_via_format_provider(&lsp_store, buffer, ranges, name, cx)
+                    .await
+                    .with_context(|| {
+                        format!("failed to format via extension formatter {name:?}")
+                    })?;
+                Some(FormatOperation::Extension(edits))
+            }
         };
         anyhow::Ok(result)
     }
 
+    async fn format_via_format_provider(
+        lsp_store: &WeakEntity<LspStore>,
+        buffer: &FormattableBuffer,
+        ranges: Option<&Vec<Range<Anchor>>>,
+        name: &str,
+        cx: &mut AsyncApp,
+    ) -> Result<Vec<(Range<Anchor>, String)>> {
+        let provider = lsp_store
+            .update(cx, |lsp_store, _| lsp_store.languages.format_provider(name))?
+            .ok_or_else(|| anyhow!("formatter is not installed"))?;
+
+        let (snapshot, path) = buffer.handle.read_with(cx, |buffer, _| {
+            (
+                buffer.snapshot(),
+                buffer.file().map(|file| file.path().clone()),
+            )
+        })?;
+        let language_name = snapshot.language().map(|language| language.name());
+        let supported_languages = provider.languages();
+        if !supported_languages.is_empty()
+            && language_name
+                .as_ref()
+                .map_or(true, |name| !supported_languages.contains(name))
+        {
+            return Err(anyhow!(
+                "formatter does not support language {:?}",
+                language_name.as_ref().map(|name| name.as_ref())
+            ));
+        }
+
+        let edits = provider
+            .format(FormatRequest {
+                text: snapshot.text(),
+                path,
+                language_name,
+                ranges: ranges.map(|ranges| {
+                    ranges
+                        .iter()
+                        .map(|range| {
+                            range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot)
+                        })
+                        .collect()
+                }),
+            })
+            .await?;
+        anchor_offset_edits(&snapshot, edits)
+    }
+
     pub async fn format_ranges_via_lsp(
         this: &WeakEntity<LspStore>,
         buffer: &FormattableBuffer,
@@ -1976,6 +2046,7 @@ impl LocalLspStore {
     ) {
         buffer.update(cx, |buffer, cx| {
             self.buffer_snapshots.remove(&buffer.remote_id());
+            self.semantic_tokens.remove(&buffer.remote_id());
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server
                     .notify::<lsp::notification::DidCloseTextDocument>(
@@ -2864,7 +2935,9 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_format_buffers);
         client.add_model_request_handler(Self::handle_resolve_completion_documentation);
         client.add_model_request_handler(Self::handle_apply_code_action);
+        client.add_model_request_handler(Self::handle_apply_code_lens);
         client.add_model_request_handler(Self::handle_inlay_hints);
+        client.add_model_request_handler(Self::handle_semantic_tokens);
         client.add_model_request_handler(Self::handle_get_project_symbols);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
         client.add_model_request_handler(Self::handle_open_buffer_for_symbol);
@@ -2874,6 +2947,9 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_register_buffer_with_language_servers);
         client.add_model_request_handler(Self::handle_rename_project_entry);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCodeActions>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSelectionRanges>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCompletions>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetHover>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDefinition>);
@@ -2881,6 +2957,12 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_lsp_command::<GetTypeDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentHighlights>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetReferences>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSubtypes>);
         client.add_model_request_handler(Self::handle_lsp_command::<PrepareRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
@@ -2987,6 +3069,8 @@ impl LspStore {
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
                 buffers_being_formatted: Default::default(),
                 buffer_snapshots: Default::default(),
+                task_diagnostics: Default::default(),
+                semantic_tokens: Default::default(),
                 prettier_store,
                 environment,
                 http_client,
@@ -3803,6 +3887,93 @@ impl LspStore {
         }
     }
 
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        mut lens: CodeLens,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ApplyCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&lens)),
+            };
+            let buffer_store = self.buffer_store();
+            cx.spawn(move |_, mut cx| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await?
+                    .transaction
+                    .ok_or_else(|| anyhow!("missing transaction"))?;
+
+                buffer_store
+                    .update(&mut cx, |buffer_store, cx| {
+                        buffer_store.deserialize_project_transaction(response, true, cx)
+                    })?
+                    .await
+            })
+        } else if self.mode.is_local() {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_local_buffer(buffer, lens.server_id, cx)
+            else {
+                return Task::ready(Ok(Default::default()));
+            };
+            let lang_server = lang_server.clone();
+            cx.spawn(move |this, mut cx| async move {
+                if lens.lsp_lens.command.is_none()
+                    && GetCodeLens::can_resolve_lenses(&lang_server.capabilities())
+                {
+                    lens.lsp_lens = lang_server
+                        .request::<lsp::request::CodeLensResolve>(lens.lsp_lens.clone())
+                        .await
+                        .context("resolving a code lens")?;
+                }
+                let Some(command) = lens.lsp_lens.command else {
+                    return Ok(ProjectTransaction::default());
+                };
+                let command_supported = lang_server
+                    .capabilities()
+                    .execute_command_provider
+                    .is_some_and(|provider| provider.commands.contains(&command.command));
+                if !command_supported {
+                    return Err(anyhow!(
+                        "language server {} does not provide the {:?} command of code lens {:?}",
+                        lang_server.name(),
+                        command.command,
+                        command.title,
+                    ));
+                }
+
+                this.update(&mut cx, |this, _| {
+                    this.as_local_mut()
+                        .unwrap()
+                        .last_workspace_edits_by_language_server
+                        .remove(&lang_server.server_id());
+                })?;
+
+                lang_server
+                    .request::<lsp::request::ExecuteCommand>(lsp::ExecuteCommandParams {
+                        command: command.command,
+                        arguments: command.arguments.unwrap_or_default(),
+                        ..Default::default()
+                    })
+                    .await?;
+
+                this.update(&mut cx, |this, _| {
+                    this.as_local_mut()
+                        .unwrap()
+                        .last_workspace_edits_by_language_server
+                        .remove(&lang_server.server_id())
+                        .unwrap_or_default()
+                })
+            })
+        } else {
+            Task::ready(Err(anyhow!("no upstream client and not local")))
+        }
+    }
+
     pub fn resolve_inlay_hint(
         &self,
         hint: InlayHint,
@@ -4636,6 +4807,158 @@ impl LspStore {
         }
     }
 
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<BufferSemanticTokens>> {
+        if let Some((client, project_id)) = self.upstream_client() {
+            let request = proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+                version: serialize_version(&buffer.read(cx).version()),
+            };
+            let buffer = buffer.clone();
+            return cx.spawn(move |_, mut cx| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("semantic tokens proto request")?;
+                deserialize_semantic_tokens(response, &buffer, &mut cx).await
+            });
+        }
+
+        let buffer_ref = buffer.read(cx);
+        let buffer_id = buffer_ref.remote_id();
+        let Some(abs_path) = File::from_dyn(buffer_ref.file())
+            .and_then(|file| file.as_local().map(|file| file.abs_path(cx)))
+        else {
+            return Task::ready(Ok(BufferSemanticTokens::default()));
+        };
+        let Some((server, options)) = self
+            .language_servers_for_local_buffer(buffer_ref, cx)
+            .find_map(|(_, server)| {
+                let options = match server.capabilities().semantic_tokens_provider? {
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => {
+                        options
+                    }
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(
+                        options,
+                    ) => options.semantic_tokens_options,
+                };
+                Some((server.clone(), options))
+            })
+        else {
+            return Task::ready(Ok(BufferSemanticTokens::default()));
+        };
+        let supports_delta = match options.full {
+            Some(lsp::SemanticTokensFullOptions::Delta { delta }) => delta.unwrap_or(false),
+            Some(lsp::SemanticTokensFullOptions::Bool(true)) => false,
+            // Only full document requests are supported.
+            _ => return Task::ready(Ok(BufferSemanticTokens::default())),
+        };
+        let Ok(uri) = lsp::Url::from_file_path(&abs_path) else {
+            return Task::ready(Err(anyhow!("invalid path {abs_path:?}")));
+        };
+        let snapshot = buffer_ref.text_snapshot();
+        let previous = self
+            .as_local_mut()
+            .and_then(|local| local.semantic_tokens.remove(&buffer_id))
+            .filter(|previous| previous.server_id == server.server_id() && supports_delta);
+
+        cx.spawn(move |lsp_store, mut cx| async move {
+            let text_document = lsp::TextDocumentIdentifier::new(uri);
+            let (result_id, data) = match previous {
+                Some(LspSemanticTokens {
+                    result_id: Some(previous_result_id),
+                    mut data,
+                    ..
+                }) => {
+                    let response = server
+                        .request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                            lsp::SemanticTokensDeltaParams {
+                                text_document,
+                                previous_result_id,
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await?;
+                    match response {
+                        Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                            (tokens.result_id, encode_tokens(tokens.data))
+                        }
+                        Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                            apply_token_edits(&mut data, delta.edits);
+                            (delta.result_id, data)
+                        }
+                        Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+                            apply_token_edits(&mut data, edits);
+                            (None, data)
+                        }
+                        None => (None, Vec::new()),
+                    }
+                }
+                _ => {
+                    let response = server
+                        .request::<lsp::request::SemanticTokensFullRequest>(
+                            lsp::SemanticTokensParams {
+                                text_document,
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await?;
+                    match response {
+                        Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                            (tokens.result_id, encode_tokens(tokens.data))
+                        }
+                        Some(lsp::SemanticTokensResult::Partial(partial)) => {
+                            (None, encode_tokens(partial.data))
+                        }
+                        None => (None, Vec::new()),
+                    }
+                }
+            };
+
+            let (tokens, data) = cx
+                .background_executor()
+                .spawn(async move {
+                    let tokens = decode_tokens(&data, &snapshot);
+                    (tokens, data)
+                })
+                .await;
+            lsp_store.update(&mut cx, |lsp_store, _| {
+                if let Some(local) = lsp_store.as_local_mut() {
+                    local.semantic_tokens.insert(
+                        buffer_id,
+                        LspSemanticTokens {
+                            server_id: server.server_id(),
+                            result_id,
+                            data,
+                        },
+                    );
+                }
+            })?;
+
+            Ok(BufferSemanticTokens {
+                token_types: options
+                    .legend
+                    .token_types
+                    .iter()
+                    .map(|token_type| token_type.as_str().to_string())
+                    .collect(),
+                token_modifiers: options
+                    .legend
+                    .token_modifiers
+                    .iter()
+                    .map(|modifier| modifier.as_str().to_string())
+                    .collect(),
+                tokens: tokens.into(),
+            })
+        })
+    }
+
     pub fn signature_help<T: ToPointUtf16>(
         &mut self,
         buffer: &Entity<Buffer>,
@@ -5699,12 +6022,8 @@ impl LspStore {
         .await?;
         let response = this
             .update(&mut cx, |this, cx| {
-                this.request_lsp(
-                    buffer_handle.clone(),
-                    LanguageServerToQuery::Primary,
-                    request,
-                    cx,
-                )
+                let server = request.language_server_to_query();
+                this.request_lsp(buffer_handle.clone(), server, request, cx)
             })?
             .await?;
         this.update(&mut cx, |this, cx| {
@@ -5902,6 +6221,39 @@ impl LspStore {
         })
     }
 
+    async fn handle_apply_code_lens(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ApplyCodeLens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ApplyCodeLensResponse> {
+        let sender_id = envelope.original_sender_id().unwrap_or_default();
+        let lens = Self::deserialize_code_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
+        )?;
+        let apply_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.apply_code_lens(buffer, lens, cx))
+        })??;
+
+        let project_transaction = apply_code_lens.await?;
+        let project_transaction = this.update(&mut cx, |this, cx| {
+            this.buffer_store.update(cx, |buffer_store, cx| {
+                buffer_store.serialize_project_transaction_for_peer(
+                    project_transaction,
+                    sender_id,
+                    cx,
+                )
+            })
+        })?;
+        Ok(proto::ApplyCodeLensResponse {
+            transaction: Some(project_transaction),
+        })
+    }
+
     async fn handle_register_buffer_with_language_servers(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::RegisterBufferWithLanguageServers>,
@@ -6747,6 +7099,43 @@ impl LspStore {
         })
     }
 
+    async fn handle_semantic_tokens(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+        let tokens = this
+            .update(&mut cx, |lsp_store, cx| {
+                lsp_store.semantic_tokens(&buffer, cx)
+            })?
+            .await?;
+
+        Ok(proto::GetSemanticTokensResponse {
+            token_types: tokens.token_types.to_vec(),
+            token_modifiers: tokens.token_modifiers.to_vec(),
+            tokens: tokens
+                .tokens
+                .iter()
+                .map(|token| proto::SemanticToken {
+                    start: Some(serialize_anchor(&token.range.start)),
+                    end: Some(serialize_anchor(&token.range.end)),
+                    token_type: token.token_type,
+                    token_modifiers: token.token_modifiers,
+                })
+                .collect(),
+        })
+    }
+
     async fn handle_resolve_inlay_hint(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::ResolveInlayHint>,
@@ -7467,6 +7856,95 @@ impl LspStore {
         Ok(())
     }
 
+    /// Replaces the diagnostics previously reported by the task with the problems found in its output.
+    /// The diagnostics of all tasks are reported under [`TASK_DIAGNOSTICS_SERVER_ID`], so that the
+    /// diagnostics of language servers and of other tasks for the same files are kept intact.
+    pub fn update_task_diagnostics(
+        &mut self,
+        task_label: &str,
+        problems: Vec<(String, Problem)>,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let Some(local) = self.as_local_mut() else {
+            anyhow::bail!("called update_task_diagnostics on remote");
+        };
+        let mut diagnostics_by_path = TaskDiagnostics::default();
+        for (source, problem) in problems {
+            let group_id = post_inc(&mut local.next_diagnostic_group_id);
+            let start = PointUtf16::new(
+                problem.line.saturating_sub(1),
+                problem.column.map_or(0, |column| column.saturating_sub(1)),
+            );
+            let end = match (problem.end_line, problem.end_column, problem.column) {
+                (end_line, Some(end_column), _) => PointUtf16::new(
+                    end_line.unwrap_or(problem.line).saturating_sub(1),
+                    end_column.saturating_sub(1),
+                ),
+                (Some(end_line), None, _) => PointUtf16::new(end_line.saturating_sub(1), u32::MAX),
+                (None, None, Some(_)) => start,
+                // Without a column, highlight the whole line.
+                (None, None, None) => PointUtf16::new(start.row, u32::MAX),
+            };
+            diagnostics_by_path
+                .entry(problem.path)
+                .or_default()
+                .push(DiagnosticEntry {
+                    range: Unclipped(start)..Unclipped(end.max(start)),
+                    diagnostic: Diagnostic {
+                        source: Some(source),
+                        code: problem.code,
+                        severity: match problem.severity {
+                            ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                            ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                            ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                        },
+                        message: problem.message,
+                        group_id,
+                        is_primary: true,
+                        is_disk_based: true,
+                        is_unnecessary: false,
+                        data: None,
+                    },
+                });
+        }
+
+        let changed_paths = local
+            .task_diagnostics
+            .remove(task_label)
+            .unwrap_or_default()
+            .into_keys()
+            .chain(diagnostics_by_path.keys().cloned())
+            .collect::<HashSet<_>>();
+        if !diagnostics_by_path.is_empty() {
+            local
+                .task_diagnostics
+                .insert(task_label.to_string(), diagnostics_by_path);
+        }
+        let updates = changed_paths
+            .into_iter()
+            .map(|abs_path| {
+                let diagnostics = local
+                    .task_diagnostics
+                    .values()
+                    .filter_map(|task_diagnostics| task_diagnostics.get(&abs_path))
+                    .flatten()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                (abs_path, diagnostics)
+            })
+            .collect::<Vec<_>>();
+        for (abs_path, diagnostics) in updates {
+            self.update_diagnostic_entries(
+                TASK_DIAGNOSTICS_SERVER_ID,
+                abs_path,
+                None,
+                diagnostics,
+                cx,
+            )?;
+        }
+        Ok(())
+    }
+
     fn insert_newly_running_language_server(
         &mut self,
         adapter: Arc<CachedLspAdapter>,
@@ -7971,6 +8449,32 @@ impl LspStore {
         })
     }
 
+    pub(crate) fn serialize_code_lens(lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+        }
+    }
+
+    pub(crate) fn deserialize_code_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_lens = serde_json::from_slice(&lens.lsp_lens)?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(lens.server_id as usize),
+            range: start..end,
+            lsp_lens,
+        })
+    }
+
     fn update_last_formatting_failure<T>(&mut self, formatting_result: &anyhow::Result<T>) {
         match &formatting_result {
             Ok(_) => self.last_formatting_failure = None,
@@ -7986,6 +8490,39 @@ impl LspStore {
 
 impl EventEmitter<LspStoreEvent> for LspStore {}
 
+async fn deserialize_semantic_tokens(
+    response: proto::GetSemanticTokensResponse,
+    buffer: &Entity<Buffer>,
+    cx: &mut AsyncApp,
+) -> Result<BufferSemanticTokens> {
+    let mut anchors = Vec::with_capacity(response.tokens.len() * 2);
+    let mut tokens = Vec::with_capacity(response.tokens.len());
+    for token in response.tokens {
+        let start = token
+            .start
+            .and_then(deserialize_anchor)
+            .context("missing token start")?;
+        let end = token
+            .end
+            .and_then(deserialize_anchor)
+            .context("missing token end")?;
+        anchors.extend([start, end]);
+        tokens.push(SemanticToken {
+            range: start..end,
+            token_type: token.token_type,
+            token_modifiers: token.token_modifiers,
+        });
+    }
+    buffer
+        .update(cx, |buffer, _| buffer.wait_for_anchors(anchors))?
+        .await?;
+    Ok(BufferSemanticTokens {
+        token_types: response.token_types.into(),
+        token_modifiers: response.token_modifiers.into(),
+        tokens: tokens.into(),
+    })
+}
+
 fn remove_empty_hover_blocks(mut hover: Hover) -> Option<Hover> {
     hover
         .contents
@@ -8223,6 +8760,9 @@ struct LspBufferSnapshot {
     snapshot: TextBufferSnapshot,
 }
 
+/// Diagnostics produced by the problem matchers of a task, by the absolute path they are reported for.
+type TaskDiagnostics = HashMap<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>;
+
 /// A prompt requested by LSP server.
 #[derive(Clone, Debug)]
 pub struct LanguageServerPromptRequest {
@@ -10,6 +10,7 @@ pub mod lsp_store;
 pub mod prettier_store;
 pub mod project_settings;
 pub mod search;
+pub mod semantic_tokens;
 mod task_inventory;
 pub mod task_store;
 pub mod terminals;
@@ -22,7 +23,10 @@ mod project_tests;
 mod direnv;
 mod environment;
 pub use environment::EnvironmentErrorMessage;
-use git::RepositoryHandle;
+use git::{
+    commit_file_diff_to_proto, commit_summary_to_proto, remote_command_output_to_proto,
+    stash_entry_to_proto, ProtoGitRemoteDelegate, RepositoryHandle,
+};
 pub mod search_history;
 mod yarn;
 
@@ -46,7 +50,8 @@ use image_store::{ImageItemEvent, ImageStoreEvent};
 
 use ::git::{
     blame::Blame,
-    repository::{Branch, GitRepository, RepoPath},
+    commit::LogOptions,
+    repository::{Branch, GitRepository, PushOptions, RepoPath},
     status::FileStatus,
 };
 use gpui::{
@@ -57,8 +62,8 @@ use itertools::Itertools;
 use language::{
     language_settings::InlayHintKind, proto::split_operations, Buffer, BufferEvent,
     CachedLspAdapter, Capability, CodeLabel, Documentation, File as _, Language, LanguageName,
-    LanguageRegistry, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList, Transaction,
-    Unclipped,
+    LanguageRegistry, ParseStatus, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList,
+    Transaction, Unclipped,
 };
 use lsp::{
     CodeActionKind, CompletionContext, CompletionItemKind, DocumentHighlightKind, LanguageServer,
@@ -116,6 +121,7 @@ pub use lsp_store::{
     LanguageServerStatus, LanguageServerToQuery, LspStore, LspStoreEvent,
     SERVER_PROGRESS_THROTTLE_TIMEOUT,
 };
+pub use semantic_tokens::{BufferSemanticTokens, SemanticToken};
 pub use toolchain_store::ToolchainStore;
 const MAX_PROJECT_SEARCH_HISTORY_SIZE: usize = 500;
 const MAX_SEARCH_RESULT_FILES: usize = 5_000;
@@ -414,6 +420,24 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A code lens provided by a language server, shown above the line it refers to.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the code lens refers to.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server.
+    pub lsp_lens: lsp::CodeLens,
+}
+
+impl CodeLens {
+    /// The text to display for this lens, if it has been resolved.
+    pub fn title(&self) -> Option<&str> {
+        Some(self.lsp_lens.command.as_ref()?.title.as_str())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -467,6 +491,45 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// A symbol that can be the caller or the callee of a call, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct CallHierarchyItem {
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub detail: Option<String>,
+    /// The range enclosing the whole symbol, e.g. a function including its body.
+    pub range: Location,
+    /// The range of the symbol's name, in the same buffer as `range`.
+    pub selection_range: Range<Anchor>,
+    /// The raw item, which is passed back to the language server to query its calls.
+    pub lsp_item: lsp::CallHierarchyItem,
+    /// The language server that reported the item, and that its calls or related types are queried from.
+    pub language_server_id: LanguageServerId,
+}
+
+/// A call to or from a [`CallHierarchyItem`].
+#[derive(Clone, Debug)]
+pub struct CallHierarchyCall {
+    /// The caller for incoming calls, or the callee for outgoing calls.
+    pub item: CallHierarchyItem,
+    /// Where the calls are made: in the caller for incoming calls, and in the queried item
+    /// for outgoing calls.
+    pub call_sites: Vec<Location>,
+}
+
+/// A type whose supertypes and subtypes can be queried. Language servers describe types
+/// the same way as the symbols of a call hierarchy.
+pub type TypeHierarchyItem = CallHierarchyItem;
+
+/// A range of a buffer that can be folded, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct FoldingRange {
+    pub range: Range<Anchor>,
+    pub kind: Option<lsp::FoldingRangeKind>,
+    /// The text the server suggests to show in place of the folded range.
+    pub collapsed_text: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -609,6 +672,23 @@ impl Project {
         client.add_model_request_handler(Self::handle_stage);
         client.add_model_request_handler(Self::handle_unstage);
         client.add_model_request_handler(Self::handle_commit);
+        client.add_model_request_handler(Self::handle_get_remotes);
+        client.add_model_request_handler(Self::handle_fetch);
+        client.add_model_request_handler(Self::handle_pull);
+        client.add_model_request_handler(Self::handle_push);
+        client.add_model_request_handler(Self::handle_askpass);
+        client.add_model_message_handler(Self::handle_git_remote_progress);
+        client.add_model_request_handler(Self::handle_stash);
+        client.add_model_request_handler(Self::handle_stash_apply);
+        client.add_model_request_handler(Self::handle_stash_pop);
+        client.add_model_request_handler(Self::handle_stash_drop);
+        client.add_model_request_handler(Self::handle_get_stash_entries);
+        client.add_model_request_handler(Self::handle_get_stash_diff);
+        client.add_model_request_handler(Self::handle_get_commit_log);
+        client.add_model_request_handler(Self::handle_get_commit_diff);
+        client.add_model_request_handler(Self::handle_get_changed_paths_since);
+        client.add_model_request_handler(Self::handle_stage_lines);
+        client.add_model_request_handler(Self::handle_unstage_lines);
 
         WorktreeStore::init(&client);
         BufferStore::init(&client);
@@ -899,6 +979,8 @@ impl Project {
             ssh_proto.add_model_message_handler(Self::handle_update_project);
             ssh_proto.add_model_message_handler(Self::handle_toast);
             ssh_proto.add_model_request_handler(Self::handle_language_server_prompt_request);
+            ssh_proto.add_model_request_handler(Self::handle_askpass);
+            ssh_proto.add_model_message_handler(Self::handle_git_remote_progress);
             ssh_proto.add_model_message_handler(Self::handle_hide_toast);
             ssh_proto.add_model_request_handler(Self::handle_update_buffer_from_ssh);
             BufferStore::init(&ssh_proto);
@@ -2771,6 +2853,88 @@ impl Project {
         )
     }
 
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn incoming_calls(
+        &mut self,
+        item: CallHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.range.buffer.clone(),
+            LanguageServerToQuery::Other(item.language_server_id),
+            GetIncomingCalls { item },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &mut self,
+        item: CallHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.range.buffer.clone(),
+            LanguageServerToQuery::Other(item.language_server_id),
+            GetOutgoingCalls { item },
+            cx,
+        )
+    }
+
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &mut self,
+        item: TypeHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.range.buffer.clone(),
+            LanguageServerToQuery::Other(item.language_server_id),
+            GetSupertypes { item },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &mut self,
+        item: TypeHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.range.buffer.clone(),
+            LanguageServerToQuery::Other(item.language_server_id),
+            GetSubtypes { item },
+            cx,
+        )
+    }
+
     pub fn references<T: ToPointUtf16>(
         &mut self,
         buffer: &Entity<Buffer>,
@@ -2940,6 +3104,60 @@ impl Project {
         })
     }
 
+    pub fn code_lens(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetCodeLens,
+            cx,
+        )
+    }
+
+    pub fn folding_ranges(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<FoldingRange>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    /// Returns, for each of the given positions, the ranges a language server considers
+    /// worth selecting around it, from the innermost to the outermost.
+    pub fn selection_ranges(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        positions: Vec<Anchor>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<Vec<Range<Anchor>>>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSelectionRanges { positions },
+            cx,
+        )
+    }
+
+    /// Resolves the lens if needed, then asks its language server to execute the lens' command.
+    pub fn apply_code_lens(
+        &self,
+        buffer: Entity<Buffer>,
+        lens: CodeLens,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.apply_code_lens(buffer, lens, cx)
+        })
+    }
+
     fn prepare_rename_impl(
         &mut self,
         buffer: Entity<Buffer>,
@@ -3010,6 +3228,17 @@ impl Project {
         })
     }
 
+    /// Fetches the semantic tokens of a buffer from its language server, or returns no tokens
+    /// if none of the buffer's servers provide them.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<BufferSemanticTokens>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.semantic_tokens(buffer, cx))
+    }
+
     pub fn resolve_inlay_hint(
         &self,
         hint: InlayHint,
@@ -3048,6 +3277,17 @@ impl Project {
                 for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
                     let query = query.clone();
+                    if query.is_structural() {
+                        // Structural queries match against the syntax tree, so wait for
+                        // freshly opened buffers to finish parsing.
+                        let mut parse_status =
+                            buffer.read_with(&cx, |buffer, _| buffer.parse_status())?;
+                        while *parse_status.borrow() != ParseStatus::Idle {
+                            if parse_status.changed().await.is_err() {
+                                break;
+                            }
+                        }
+                    }
                     let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
                     chunk_results.push(cx.background_executor().spawn(async move {
                         let ranges = query
@@ -3124,6 +3364,12 @@ impl Project {
                     if !search_query.file_matches(file.path()) {
                         return false;
                     }
+                    if !search_query.path_in_scope(&ProjectPath {
+                        worktree_id: file.worktree_id(cx),
+                        path: file.path().clone(),
+                    }) {
+                        return false;
+                    }
                     if let Some(entry) = b
                         .entry_id(cx)
                         .and_then(|entry_id| worktree_store.entry_for_id(entry_id, cx))
@@ -3132,6 +3378,8 @@ impl Project {
                             return false;
                         }
                     }
+                } else if search_query.scope_paths().is_some() {
+                    return false;
                 }
                 true
             })
@@ -4086,6 +4334,464 @@ impl Project {
         }
     }
 
+    async fn handle_get_remotes(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetRemotes>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetRemotesResponse> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let branch_name = envelope.payload.branch_name.map(SharedString::from);
+        let remotes = repository_handle
+            .get_remotes(branch_name)
+            .await
+            .context("get remotes operation was canceled")??;
+        Ok(proto::GetRemotesResponse {
+            remotes: remotes
+                .into_iter()
+                .map(|remote| proto::get_remotes_response::Remote {
+                    name: remote.name.to_string(),
+                })
+                .collect(),
+        })
+    }
+
+    async fn handle_fetch(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::Fetch>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::RemoteMessageResponse> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let delegate = Self::git_remote_delegate(
+            &this,
+            &repository_handle,
+            envelope.payload.project_id,
+            envelope.payload.askpass_id,
+            envelope.original_sender_id,
+            &cx,
+        )?;
+        let output = repository_handle
+            .fetch(delegate)
+            .await
+            .context("fetch operation was canceled")??;
+        Ok(remote_command_output_to_proto(output))
+    }
+
+    async fn handle_pull(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::Pull>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::RemoteMessageResponse> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let delegate = Self::git_remote_delegate(
+            &this,
+            &repository_handle,
+            envelope.payload.project_id,
+            envelope.payload.askpass_id,
+            envelope.original_sender_id,
+            &cx,
+        )?;
+        let output = repository_handle
+            .pull(
+                envelope.payload.branch_name.into(),
+                envelope.payload.remote_name.into(),
+                delegate,
+            )
+            .await
+            .context("pull operation was canceled")??;
+        Ok(remote_command_output_to_proto(output))
+    }
+
+    async fn handle_push(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::Push>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::RemoteMessageResponse> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let options = envelope
+            .payload
+            .options
+            .and_then(proto::push::PushOptions::from_i32)
+            .map(|options| match options {
+                proto::push::PushOptions::SetUpstream => PushOptions::SetUpstream,
+                proto::push::PushOptions::Force => PushOptions::Force,
+                proto::push::PushOptions::ForceSetUpstream => PushOptions::ForceSetUpstream,
+            });
+        let delegate = Self::git_remote_delegate(
+            &this,
+            &repository_handle,
+            envelope.payload.project_id,
+            envelope.payload.askpass_id,
+            envelope.original_sender_id,
+            &cx,
+        )?;
+        let output = repository_handle
+            .push(
+                envelope.payload.branch_name.into(),
+                envelope.payload.remote_name.into(),
+                options,
+                delegate,
+            )
+            .await
+            .context("push operation was canceled")??;
+        Ok(remote_command_output_to_proto(output))
+    }
+
+    async fn handle_stash(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::Stash>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        repository_handle
+            .stash_all(envelope.payload.message.map(SharedString::from))
+            .await
+            .context("stash operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_apply(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::StashApply>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        repository_handle
+            .stash_apply(
+                envelope.payload.index as usize,
+                envelope.payload.sha.parse()?,
+            )
+            .await
+            .context("stash apply operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_pop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::StashPop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        repository_handle
+            .stash_pop(
+                envelope.payload.index as usize,
+                envelope.payload.sha.parse()?,
+            )
+            .await
+            .context("stash pop operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_drop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::StashDrop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        repository_handle
+            .stash_drop(
+                envelope.payload.index as usize,
+                envelope.payload.sha.parse()?,
+            )
+            .await
+            .context("stash drop operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_get_stash_entries(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetStashEntries>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetStashEntriesResponse> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let entries = repository_handle
+            .stash_entries()
+            .await
+            .context("list stash operation was canceled")??;
+        Ok(proto::GetStashEntriesResponse {
+            entries: entries.iter().map(stash_entry_to_proto).collect(),
+        })
+    }
+
+    async fn handle_get_stash_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetStashDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetStashDiffResponse> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let files = repository_handle
+            .stash_diff(
+                envelope.payload.index as usize,
+                envelope.payload.sha.parse()?,
+            )
+            .await
+            .context("stash diff operation was canceled")??;
+        Ok(proto::GetStashDiffResponse {
+            files: files.into_iter().map(commit_file_diff_to_proto).collect(),
+        })
+    }
+
+    async fn handle_get_commit_log(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetCommitLog>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetCommitLogResponse> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let options = LogOptions {
+            path: envelope.payload.path.as_deref().map(RepoPath::from_str),
+            author: envelope.payload.author,
+            skip: envelope.payload.skip as usize,
+            limit: envelope.payload.limit as usize,
+        };
+        let commits = repository_handle
+            .log(options)
+            .await
+            .context("log operation was canceled")??;
+        Ok(proto::GetCommitLogResponse {
+            commits: commits.iter().map(commit_summary_to_proto).collect(),
+        })
+    }
+
+    async fn handle_get_commit_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetCommitDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetCommitDiffResponse> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let files = repository_handle
+            .commit_diff(envelope.payload.sha.into())
+            .await
+            .context("commit diff operation was canceled")??;
+        Ok(proto::GetCommitDiffResponse {
+            files: files.into_iter().map(commit_file_diff_to_proto).collect(),
+        })
+    }
+
+    async fn handle_get_changed_paths_since(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetChangedPathsSince>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetChangedPathsSinceResponse> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let paths = repository_handle
+            .changed_paths_since(envelope.payload.base.into())
+            .await
+            .context("changed paths operation was canceled")??;
+        Ok(proto::GetChangedPathsSinceResponse {
+            paths: paths.iter().map(|path| path.to_proto()).collect(),
+        })
+    }
+
+    async fn handle_stage_lines(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::StageLines>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let rows = envelope
+            .payload
+            .rows
+            .into_iter()
+            .map(|rows| rows.start as u32..rows.end as u32)
+            .collect();
+        repository_handle
+            .stage_lines(
+                RepoPath::from_str(&envelope.payload.path),
+                envelope.payload.buffer_text,
+                rows,
+            )
+            .await
+            .context("stage lines operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_unstage_lines(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UnstageLines>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_handle = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let rows = envelope
+            .payload
+            .rows
+            .into_iter()
+            .map(|rows| rows.start as u32..rows.end as u32)
+            .collect();
+        repository_handle
+            .unstage_lines(
+                RepoPath::from_str(&envelope.payload.path),
+                envelope.payload.buffer_text,
+                rows,
+                envelope.payload.whole_hunks,
+            )
+            .await
+            .context("unstage lines operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_askpass(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::AskPassRequest>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::AskPassResponse> {
+        let delegate = this.update(&mut cx, |project, cx| {
+            project
+                .git_state()
+                .context("missing git state")?
+                .read(cx)
+                .askpass_delegate(envelope.payload.askpass_id)
+                .context("no remote operation is waiting for credentials")
+        })??;
+
+        let response = delegate.ask_pass(envelope.payload.prompt).await;
+        Ok(proto::AskPassResponse { response })
+    }
+
+    async fn handle_git_remote_progress(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRemoteProgress>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let delegate = this.update(&mut cx, |project, cx| {
+            project
+                .git_state()
+                .context("missing git state")?
+                .read(cx)
+                .askpass_delegate(envelope.payload.askpass_id)
+                .context("no remote operation is in progress")
+        })??;
+        delegate.progress(&envelope.payload.line);
+        Ok(())
+    }
+
+    /// Returns a delegate that forwards the progress and credential prompts of a remote
+    /// operation to the collaborator who requested it.
+    fn git_remote_delegate(
+        this: &Entity<Self>,
+        repository_handle: &RepositoryHandle,
+        project_id: u64,
+        askpass_id: u64,
+        peer_id: Option<proto::PeerId>,
+        cx: &AsyncApp,
+    ) -> Result<Arc<ProtoGitRemoteDelegate>> {
+        let client = this.read_with(cx, |this, _| this.client.clone())?;
+        Ok(Arc::new(ProtoGitRemoteDelegate {
+            client: client.into(),
+            project_id,
+            worktree_id: repository_handle.worktree_id,
+            work_directory_id: repository_handle.repository_entry.work_directory_id(),
+            askpass_id,
+            peer_id,
+        }))
+    }
+
+    fn repository_for_request(
+        this: &Entity<Self>,
+        worktree_id: u64,
+        work_directory_id: u64,
+        cx: &mut AsyncApp,
+    ) -> Result<RepositoryHandle> {
+        this.update(cx, |project, cx| {
+            project
+                .git_state()
+                .context("missing git state")?
+                .read(cx)
+                .repository_for_proto(
+                    WorktreeId::from_proto(worktree_id),
+                    ProjectEntryId::from_proto(work_directory_id),
+                )
+        })?
+    }
+
     fn respond_to_open_buffer_request(
         this: Entity<Self>,
         buffer: Entity<Buffer>,
@@ -22,7 +22,7 @@ use std::os;
 use std::{str::FromStr, sync::OnceLock};
 
 use std::{mem, num::NonZeroU32, ops::Range, task::Poll};
-use task::{ResolvedTask, TaskContext};
+use task::{Problem, ProblemSeverity, ResolvedTask, TaskContext};
 use unindent::Unindent as _;
 use util::{
     assert_set_eq,
@@ -1236,6 +1236,173 @@ async fn test_omitted_diagnostics(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_task_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = 1;\nlet b = 2;",
+            "b.rs": "let c = 3;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+    let problem = |path: &str, line, column, severity, message: &str| Problem {
+        path: PathBuf::from(path),
+        line,
+        column,
+        end_line: None,
+        end_column: None,
+        severity,
+        code: None,
+        message: message.to_string(),
+    };
+
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_task_diagnostics(
+                "cargo check",
+                vec![
+                    (
+                        "rustc".to_string(),
+                        problem("/dir/a.rs", 2, Some(5), ProblemSeverity::Error, "bad b"),
+                    ),
+                    (
+                        "rustc".to_string(),
+                        problem("/dir/b.rs", 1, None, ProblemSeverity::Warning, "bad line"),
+                    ),
+                ],
+                cx,
+            )
+            .unwrap();
+    });
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        let diagnostics = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| {
+                (
+                    entry.range,
+                    entry.diagnostic.source,
+                    entry.diagnostic.message,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            &[(
+                Point::new(1, 4)..Point::new(1, 5),
+                Some("rustc".to_string()),
+                "bad b".to_string()
+            )]
+        );
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 1,
+            }
+        );
+    });
+
+    // Rerunning the task replaces the diagnostics of its previous run.
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_task_diagnostics(
+                "cargo check",
+                vec![(
+                    "rustc".to_string(),
+                    problem("/dir/b.rs", 1, Some(5), ProblemSeverity::Error, "bad c"),
+                )],
+                cx,
+            )
+            .unwrap();
+    });
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .count(),
+            0
+        );
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 0,
+            }
+        );
+    });
+
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_task_diagnostics("cargo check", Vec::new(), cx)
+            .unwrap();
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary::default()
+        );
+    });
+
+    // Each task replaces only its own diagnostics of a file.
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_task_diagnostics(
+                "cargo check",
+                vec![(
+                    "rustc".to_string(),
+                    problem("/dir/a.rs", 1, Some(5), ProblemSeverity::Error, "bad a"),
+                )],
+                cx,
+            )
+            .unwrap();
+        lsp_store
+            .update_task_diagnostics(
+                "cargo clippy",
+                vec![(
+                    "clippy".to_string(),
+                    problem("/dir/a.rs", 2, Some(5), ProblemSeverity::Warning, "lint b"),
+                )],
+                cx,
+            )
+            .unwrap();
+    });
+    let diagnostic_messages = |buffer: &Entity<Buffer>, cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .map(|entry| entry.diagnostic.message)
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(diagnostic_messages(&buffer, cx), ["bad a", "lint b"]);
+
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_task_diagnostics("cargo check", Vec::new(), cx)
+            .unwrap();
+    });
+    assert_eq!(diagnostic_messages(&buffer, cx), ["lint b"]);
+}
+
 #[gpui::test]
 async fn test_disk_based_diagnostics_progress(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2658,6 +2825,314 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn callee() {}\nfn caller() { callee(); }\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let call_hierarchy_capabilities = lsp::ServerCapabilities {
+        call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+        ..lsp::ServerCapabilities::default()
+    };
+    let mut primary_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            name: "primary-server",
+            capabilities: call_hierarchy_capabilities.clone(),
+            ..FakeLspAdapter::default()
+        },
+    );
+    let mut other_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            name: "other-server",
+            capabilities: call_hierarchy_capabilities,
+            ..FakeLspAdapter::default()
+        },
+    );
+    let mut incapable_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            name: "incapable-server",
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let primary_server = primary_servers.next().await.unwrap();
+    let other_server = other_servers.next().await.unwrap();
+    let incapable_server = incapable_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    fn lsp_item(name: &str, line: u32, end_column: u32) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+            range: lsp::Range::new(
+                lsp::Position::new(line, 0),
+                lsp::Position::new(line, end_column),
+            ),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(line, 3),
+                lsp::Position::new(line, 9),
+            ),
+            data: None,
+        }
+    }
+    let call_site = lsp::Range::new(lsp::Position::new(1, 14), lsp::Position::new(1, 20));
+
+    primary_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 4)
+            );
+            Ok(Some(vec![lsp_item("callee", 0, 14)]))
+        },
+    );
+    primary_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "callee");
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: lsp_item("caller", 1, 25),
+                from_ranges: vec![call_site],
+            }]))
+        },
+    );
+    other_server.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "caller");
+            Ok(Some(vec![lsp::CallHierarchyOutgoingCall {
+                to: lsp_item("callee", 0, 14),
+                from_ranges: vec![call_site],
+            }]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, Point::new(0, 4), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let callee = items[0].clone();
+    assert_eq!(callee.name, "callee");
+    assert_eq!(callee.language_server_id, primary_server.server.server_id());
+    cx.update(|cx| {
+        let buffer = callee.range.buffer.read(cx);
+        assert_eq!(callee.range.range.to_offset(buffer), 0..14);
+        assert_eq!(callee.selection_range.to_offset(buffer), 3..9);
+    });
+
+    let incoming_calls = project
+        .update(cx, |project, cx| project.incoming_calls(callee.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(incoming_calls.len(), 1);
+    let caller = incoming_calls[0].item.clone();
+    assert_eq!(caller.name, "caller");
+    assert_eq!(caller.language_server_id, primary_server.server.server_id());
+    cx.update(|cx| {
+        let call_sites = &incoming_calls[0].call_sites;
+        assert_eq!(call_sites.len(), 1);
+        assert_eq!(
+            call_sites[0].range.to_offset(call_sites[0].buffer.read(cx)),
+            29..35,
+            "Call sites of incoming calls should be in the caller"
+        );
+    });
+
+    // Calls are queried from the language server that reported the item, not the primary one.
+    let mut other_caller = caller.clone();
+    other_caller.language_server_id = other_server.server.server_id();
+    let outgoing_calls = project
+        .update(cx, |project, cx| project.outgoing_calls(other_caller, cx))
+        .await
+        .unwrap();
+    assert_eq!(outgoing_calls.len(), 1);
+    assert_eq!(outgoing_calls[0].item.name, "callee");
+    assert_eq!(
+        outgoing_calls[0].item.language_server_id,
+        other_server.server.server_id()
+    );
+
+    // Servers that do not advertise the capability are not asked for calls.
+    let mut incapable_caller = caller;
+    incapable_caller.language_server_id = incapable_server.server.server_id();
+    let outgoing_calls = project
+        .update(cx, |project, cx| {
+            project.outgoing_calls(incapable_caller, cx)
+        })
+        .await
+        .unwrap();
+    assert!(outgoing_calls.is_empty());
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "trait Shape {}\nstruct Circle;\nstruct Square;\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let type_hierarchy_adapter = |name| FakeLspAdapter {
+        name,
+        initializer: Some(Box::new(|fake_server: &mut lsp::FakeLanguageServer| {
+            fake_server.advertise_type_hierarchy_provider(lsp::ServerCapabilities::default())
+        })),
+        ..FakeLspAdapter::default()
+    };
+    let mut primary_servers =
+        language_registry.register_fake_lsp("Rust", type_hierarchy_adapter("primary-server"));
+    let mut other_servers =
+        language_registry.register_fake_lsp("Rust", type_hierarchy_adapter("other-server"));
+    let mut incapable_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            name: "incapable-server",
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let primary_server = primary_servers.next().await.unwrap();
+    let other_server = other_servers.next().await.unwrap();
+    let incapable_server = incapable_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    fn lsp_item(name: &str, line: u32, name_start: u32) -> lsp::TypeHierarchyItem {
+        lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::STRUCT,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+            range: lsp::Range::new(lsp::Position::new(line, 0), lsp::Position::new(line, 14)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(line, name_start),
+                lsp::Position::new(line, name_start + name.len() as u32),
+            ),
+            data: None,
+        }
+    }
+
+    primary_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 8)
+            );
+            Ok(Some(vec![lsp_item("Circle", 1, 7)]))
+        },
+    );
+    primary_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "Circle");
+            Ok(Some(vec![lsp_item("Shape", 0, 6)]))
+        },
+    );
+    other_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "Shape");
+            Ok(Some(vec![
+                lsp_item("Circle", 1, 7),
+                lsp_item("Square", 2, 7),
+            ]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, Point::new(1, 8), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let circle = items[0].clone();
+    assert_eq!(circle.name, "Circle");
+    assert_eq!(circle.language_server_id, primary_server.server.server_id());
+
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(circle, cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    let shape = supertypes[0].clone();
+    assert_eq!(shape.name, "Shape");
+    assert_eq!(shape.language_server_id, primary_server.server.server_id());
+    cx.update(|cx| {
+        assert_eq!(
+            shape.selection_range.to_offset(shape.range.buffer.read(cx)),
+            6..11
+        );
+    });
+
+    // Related types are queried from the language server that reported the item, not the primary one.
+    let mut other_shape = shape.clone();
+    other_shape.language_server_id = other_server.server.server_id();
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(other_shape, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        assert_eq!(
+            subtypes
+                .iter()
+                .map(|item| (
+                    item.name.as_str(),
+                    item.selection_range.to_offset(item.range.buffer.read(cx))
+                ))
+                .collect::<Vec<_>>(),
+            vec![("Circle", 22..28), ("Square", 37..43)]
+        );
+    });
+
+    // Servers that do not advertise the capability are not asked for related types.
+    let mut incapable_shape = shape;
+    incapable_shape.language_server_id = incapable_server.server.server_id();
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(incapable_shape, cx))
+        .await
+        .unwrap();
+    assert!(subtypes.is_empty());
+}
+
 #[gpui::test]
 async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2946,6 +3421,118 @@ async fn test_apply_code_actions_with_commands(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_code_lens_resolve_and_apply(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "function a() {}\nfunction b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["_the/references".into()],
+                    ..Default::default()
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| p.open_local_buffer_with_lsp("/dir/a.ts", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    // The language server returns one resolved lens, and one that has to be resolved
+    // before it has a title.
+    let lenses = project.update(cx, |project, cx| project.code_lens(&buffer, cx));
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>(|mut lens, _| async move {
+        lens.command = Some(lsp::Command {
+            title: "2 references".into(),
+            command: "_the/references".into(),
+            arguments: lens.data.take().map(|data| vec![data]),
+        });
+        Ok(lens)
+    });
+    fake_server
+        .handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _| async move {
+            Ok(Some(vec![
+                lsp::CodeLens {
+                    range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+                    command: Some(lsp::Command {
+                        title: "Run".into(),
+                        command: "_the/run".into(),
+                        arguments: None,
+                    }),
+                    data: None,
+                },
+                lsp::CodeLens {
+                    range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 10)),
+                    command: None,
+                    data: Some(json!("b")),
+                },
+            ]))
+        })
+        .next()
+        .await;
+
+    let lenses = lenses.await.unwrap();
+    assert_eq!(
+        lenses.iter().map(|lens| lens.title()).collect::<Vec<_>>(),
+        [Some("Run"), Some("2 references")]
+    );
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            lenses[1].range.to_point(buffer),
+            Point::new(1, 9)..Point::new(1, 10)
+        );
+    });
+
+    // Applying a lens executes its command through the language server.
+    let apply = project.update(cx, |project, cx| {
+        project.apply_code_lens(buffer.clone(), lenses[1].clone(), cx)
+    });
+    fake_server
+        .handle_request::<lsp::request::ExecuteCommand, _, _>(|params, _| async move {
+            assert_eq!(params.command, "_the/references");
+            assert_eq!(params.arguments, vec![json!("b")]);
+            Ok(Some(json!(null)))
+        })
+        .next()
+        .await;
+    let transaction = apply.await.unwrap();
+    assert!(transaction.0.is_empty());
+
+    // Commands that the language server does not provide are not sent to it.
+    let apply = project.update(cx, |project, cx| {
+        project.apply_code_lens(buffer.clone(), lenses[0].clone(), cx)
+    });
+    let error = apply.await.unwrap_err();
+    assert!(
+        error.to_string().contains("\"_the/run\""),
+        "unexpected error: {error}"
+    );
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_save_file(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4319,6 +4906,45 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "fn one() { foo(1); foo(1, 2); }",
+            "two.rs": "fn two() { bar(foo(2)); }",
+            "three.rs": "fn three() { bar(3); }",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural(
+                "foo($ARG)",
+                false,
+                Default::default(),
+                Default::default(),
+                None
+            )
+            .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![11..17]),
+            ("dir/two.rs".to_string(), vec![15..21])
+        ])
+    );
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4751,6 +5377,84 @@ async fn test_search_multiple_worktrees_with_inclusions(cx: &mut gpui::TestAppCo
     );
 }
 
+#[gpui::test]
+async fn test_search_with_scope_paths(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": r#"// NEEDLE"#,
+            "two.rs": r#"// NEEDLE"#,
+            "src": {
+                "three.rs": r#"// NEEDLE"#,
+                "nested": {
+                    "four.rs": r#"// NEEDLE"#,
+                },
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree_id = project.read_with(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let query = || {
+        SearchQuery::text(
+            "NEEDLE",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        search(
+            &project,
+            query().with_scope_paths([(worktree_id, "src").into()]),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/src/three.rs".to_string(), vec![3..9]),
+            ("dir/src/nested/four.rs".to_string(), vec![3..9]),
+        ]),
+        "a directory scope should include all files within it"
+    );
+
+    assert_eq!(
+        search(
+            &project,
+            query().with_scope_paths([
+                (worktree_id, "two.rs").into(),
+                (worktree_id, "src/nested/four.rs").into(),
+            ]),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/two.rs".to_string(), vec![3..9]),
+            ("dir/src/nested/four.rs".to_string(), vec![3..9]),
+        ]),
+        "a scope of files should only include those files"
+    );
+
+    assert!(
+        search(&project, query().with_scope_paths([]), cx)
+            .await
+            .unwrap()
+            .is_empty(),
+        "an empty scope should not match anything"
+    );
+}
+
 #[gpui::test]
 async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -5159,6 +5863,106 @@ async fn test_hovers_with_empty_parts(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_format_via_format_provider(cx: &mut gpui::TestAppContext) {
+    use async_trait::async_trait;
+    use language::{
+        language_settings::{Formatter, FormatterList, SelectedFormatter},
+        FormatProvider, FormatRequest,
+    };
+
+    struct FakeFormatProvider {
+        requests: Arc<Mutex<Vec<FormatRequest>>>,
+        edits: Vec<(Range<usize>, String)>,
+    }
+
+    #[async_trait]
+    impl FormatProvider for FakeFormatProvider {
+        fn languages(&self) -> Vec<LanguageName> {
+            Vec::new()
+        }
+
+        async fn format(&self, request: FormatRequest) -> Result<Vec<(Range<usize>, String)>> {
+            self.requests.lock().push(request);
+            Ok(self.edits.clone())
+        }
+    }
+
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.formatter = Some(SelectedFormatter::List(FormatterList(
+                    Formatter::Extension {
+                        name: "my-extension:my-formatter".into(),
+                    }
+                    .into(),
+                )));
+            });
+        })
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.txt": "one\ntwo\nthree\n" }))
+        .await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/a.txt", cx)
+        })
+        .await
+        .unwrap();
+
+    let format = |cx: &mut gpui::TestAppContext| {
+        project.update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                LspFormatTarget::Buffers,
+                true,
+                lsp_store::FormatTrigger::Manual,
+                cx,
+            )
+        })
+    };
+
+    // The formatter isn't installed yet.
+    assert!(format(cx).await.is_err());
+
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let languages = project.read_with(cx, |project, _| project.languages().clone());
+    languages.register_format_provider(
+        "my-extension:my-formatter".into(),
+        Arc::new(FakeFormatProvider {
+            requests: requests.clone(),
+            edits: vec![(8..13, "THREE".into()), (0..3, "ONE".into())],
+        }),
+    );
+    format(cx).await.unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "ONE\ntwo\nTHREE\n");
+    });
+    {
+        let requests = requests.lock();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].text, "one\ntwo\nthree\n");
+        assert_eq!(requests[0].path.as_deref(), Some(Path::new("a.txt")));
+        assert_eq!(requests[0].ranges, None);
+    }
+
+    // Edits that overlap are rejected, leaving the buffer as it was.
+    languages.register_format_provider(
+        "my-extension:my-formatter".into(),
+        Arc::new(FakeFormatProvider {
+            requests: requests.clone(),
+            edits: vec![(0..5, "a".into()), (4..8, "b".into())],
+        }),
+    );
+    assert!(format(cx).await.is_err());
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "ONE\ntwo\nTHREE\n");
+    });
+}
+
 #[gpui::test]
 async fn test_code_actions_only_kinds(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -5761,6 +6565,110 @@ fn js_lang() -> Arc<Language> {
     ))
 }
 
+#[gpui::test]
+async fn test_git_remote_operations(cx: &mut gpui::TestAppContext) {
+    use ::git::askpass::{GitRemoteDelegate, NoopRemoteDelegate};
+    use ::git::repository::PushOptions;
+    use futures::{future::BoxFuture, FutureExt as _};
+
+    #[derive(Default)]
+    struct RecordingDelegate {
+        progress: Mutex<Vec<String>>,
+    }
+
+    impl GitRemoteDelegate for RecordingDelegate {
+        fn progress(&self, line: &str) {
+            self.progress.lock().push(line.to_string());
+        }
+
+        fn ask_pass(&self, _prompt: String) -> BoxFuture<'static, Option<String>> {
+            future::ready(None).boxed()
+        }
+    }
+
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "a.txt": "a",
+        }),
+    )
+    .await;
+    let dot_git = Path::new("/dir/.git");
+    fs.set_branch_name(dot_git, Some("main"));
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    cx.run_until_parked();
+    let repository = project.read_with(cx, |project, cx| project.active_repository(cx).unwrap());
+
+    let error = repository
+        .fetch(Arc::new(NoopRemoteDelegate))
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "no remote repository specified");
+
+    fs.with_git_state(dot_git, false, |state| {
+        state.remotes = vec!["origin".to_string()];
+    });
+
+    let delegate = Arc::new(RecordingDelegate::default());
+    repository.fetch(delegate.clone()).await.unwrap().unwrap();
+    assert_eq!(
+        *delegate.progress.lock(),
+        vec!["Fetching origin".to_string()]
+    );
+
+    let error = repository
+        .push(
+            "main".into(),
+            "upstream".into(),
+            Some(PushOptions::SetUpstream),
+            Arc::new(NoopRemoteDelegate),
+        )
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "no such remote: upstream");
+
+    repository
+        .push(
+            "main".into(),
+            "origin".into(),
+            Some(PushOptions::SetUpstream),
+            Arc::new(NoopRemoteDelegate),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    fs.with_git_state(dot_git, false, |state| {
+        assert_eq!(
+            state.upstreams["main"].ref_name.as_ref(),
+            "refs/remotes/origin/main"
+        );
+    });
+
+    let error = repository
+        .pull(
+            "main".into(),
+            "upstream".into(),
+            Arc::new(NoopRemoteDelegate),
+        )
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "no such remote: upstream");
+
+    repository
+        .pull("main".into(), "origin".into(), Arc::new(NoopRemoteDelegate))
+        .await
+        .unwrap()
+        .unwrap();
+}
+
 fn rust_lang() -> Arc<Language> {
     Arc::new(Language::new(
         LanguageConfig {
@@ -1,8 +1,10 @@
+use crate::ProjectPath;
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::Result;
 use client::proto;
+use collections::HashSet;
 use fancy_regex::{Captures, Regex, RegexBuilder};
-use gpui::Entity;
+use gpui::{Entity, SharedString};
 use language::{Buffer, BufferSnapshot, CharKind};
 use smol::future::yield_now;
 use std::{
@@ -15,6 +17,10 @@ use std::{
 use text::Anchor;
 use util::paths::PathMatcher;
 
+mod structural;
+
+pub use structural::StructuralQuery;
+
 pub enum SearchResult {
     Buffer {
         buffer: Entity<Buffer>,
@@ -30,12 +36,30 @@ pub enum SearchInputKind {
     Exclude,
 }
 
+/// A part of the project that a search is limited to, on top of its include and exclude filters.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SearchScope {
+    /// The buffers that are open in the workspace.
+    OpenBuffers,
+    /// Files with uncommitted changes: modified, staged or untracked.
+    GitChanges,
+    /// Files in a repository changed since its current branch diverged from the given branch.
+    ChangedSinceBranch {
+        /// The work directory of the repository that the branch belongs to.
+        repository: ProjectPath,
+        branch: SharedString,
+    },
+    /// Files within the given directory.
+    Directory(ProjectPath),
+}
+
 #[derive(Clone, Debug)]
 pub struct SearchInputs {
     query: Arc<str>,
     files_to_include: PathMatcher,
     files_to_exclude: PathMatcher,
     buffers: Option<Vec<Entity<Buffer>>>,
+    scope_paths: Option<Arc<HashSet<ProjectPath>>>,
 }
 
 impl SearchInputs {
@@ -51,6 +75,9 @@ impl SearchInputs {
     pub fn buffers(&self) -> &Option<Vec<Entity<Buffer>>> {
         &self.buffers
     }
+    pub fn scope_paths(&self) -> Option<&HashSet<ProjectPath>> {
+        self.scope_paths.as_deref()
+    }
 }
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
@@ -72,6 +99,13 @@ pub enum SearchQuery {
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    Structural {
+        query: Arc<StructuralQuery>,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -99,6 +133,7 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            scope_paths: None,
         };
         Ok(Self::Text {
             search: Arc::new(search),
@@ -146,6 +181,7 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            scope_paths: None,
         };
         Ok(Self::Regex {
             regex,
@@ -158,8 +194,50 @@ impl SearchQuery {
         })
     }
 
+    /// Matches code by its syntax tree, using either a code template with `$` metavariables
+    /// or a tree-sitter query.
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let structural_query = StructuralQuery::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            buffers,
+            scope_paths: None,
+        };
+        Ok(Self::Structural {
+            query: Arc::new(structural_query),
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchQuery) -> Result<Self> {
-        if message.regex {
+        let scope_paths = message.scoped.then(|| {
+            message
+                .scope_paths
+                .iter()
+                .cloned()
+                .map(ProjectPath::from_proto)
+                .collect::<Vec<_>>()
+        });
+        let query = if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -179,7 +257,17 @@ impl SearchQuery {
                 deserialize_path_matches(&message.files_to_exclude)?,
                 None, // search opened only don't need search remote
             )
-        }
+        }?;
+        Ok(match scope_paths {
+            Some(scope_paths) => query.with_scope_paths(scope_paths),
+            None => query,
+        })
+    }
+
+    /// Limits the search to the given files and to the files within the given directories.
+    pub fn with_scope_paths(mut self, paths: impl IntoIterator<Item = ProjectPath>) -> Self {
+        self.as_inner_mut().scope_paths = Some(Arc::new(paths.into_iter().collect()));
+        self
     }
 
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
@@ -191,6 +279,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -202,11 +294,19 @@ impl SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
             files_to_include: self.files_to_include().sources().join(","),
             files_to_exclude: self.files_to_exclude().sources().join(","),
+            scoped: self.scope_paths().is_some(),
+            scope_paths: self
+                .scope_paths()
+                .into_iter()
+                .flatten()
+                .map(ProjectPath::to_proto)
+                .collect(),
         }
     }
 
@@ -247,17 +347,19 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { query, .. } => Ok(query.could_match(reader)?),
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    /// Structural matches depend on the syntax tree around them, so use [`Self::structural_replacement`] for those.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
@@ -281,6 +383,24 @@ impl SearchQuery {
                     None
                 }
             }
+            SearchQuery::Structural { .. } => None,
+        }
+    }
+
+    /// Replaces a structural search hit at `range` in `buffer`, filling the replacement's
+    /// metavariables with the text they matched.
+    pub fn structural_replacement(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        match self {
+            SearchQuery::Structural {
+                query,
+                replacement: Some(replacement),
+                ..
+            } => query.replacement_for_match(buffer, range, replacement),
+            _ => None,
         }
     }
 
@@ -296,6 +416,15 @@ impl SearchQuery {
         }
 
         let range_offset = subrange.as_ref().map(|r| r.start).unwrap_or(0);
+        if let Self::Structural { query, .. } = self {
+            let range = subrange.unwrap_or(0..buffer.len());
+            return query
+                .matches(buffer, range)
+                .into_iter()
+                .map(|range| range.start - range_offset..range.end - range_offset)
+                .collect();
+        }
+
         let rope = if let Some(range) = subrange {
             buffer.as_rope().slice(range)
         } else {
@@ -376,6 +505,8 @@ impl SearchQuery {
                     }
                 }
             }
+
+            Self::Structural { .. } => {}
         }
 
         matches
@@ -393,6 +524,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -400,6 +532,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -411,6 +544,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -418,6 +554,10 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -434,6 +574,24 @@ impl SearchQuery {
         self.as_inner().buffers.is_some()
     }
 
+    pub fn scope_paths(&self) -> Option<&HashSet<ProjectPath>> {
+        self.as_inner().scope_paths()
+    }
+
+    /// Whether the path is one of the scope's paths or is within one of them.
+    /// Every path is in scope when the search isn't scoped.
+    pub fn path_in_scope(&self, project_path: &ProjectPath) -> bool {
+        let Some(scope_paths) = self.scope_paths() else {
+            return true;
+        };
+        project_path.path.ancestors().any(|ancestor| {
+            scope_paths.contains(&ProjectPath {
+                worktree_id: project_path.worktree_id,
+                path: ancestor.into(),
+            })
+        })
+    }
+
     pub fn filters_path(&self) -> bool {
         !(self.files_to_exclude().sources().is_empty()
             && self.files_to_include().sources().is_empty())
@@ -455,7 +613,17 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
+        }
+    }
+
+    fn as_inner_mut(&mut self) -> &mut SearchInputs {
+        match self {
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 }
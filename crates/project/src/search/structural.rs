@@ -0,0 +1,656 @@
+//! Structural search matches code by the shape of its syntax tree rather than by its text.
+//!
+//! A query is either a code template, where `$NAME` matches any single node and `$$$NAME`
+//! matches any number of sibling nodes (as in ast-grep), or a raw tree-sitter query whose
+//! `@match` capture (or first capture) is the matched node. Matches are found in every
+//! syntax layer of a buffer, so templates written in an injected language match too.
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use language::{
+    with_parser, with_query_cursor, BufferSnapshot, Language, LanguageName, Node, Tree,
+};
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    io::{self, BufRead},
+    ops::Range,
+    sync::Arc,
+};
+use tree_sitter::Query;
+
+const METAVARIABLE_PREFIX: &str = "zed_metavar_";
+const MULTI_METAVARIABLE_PREFIX: &str = "zed_metavars_";
+const MATCH_CAPTURE_NAME: &str = "match";
+
+#[derive(Debug)]
+pub struct StructuralQuery {
+    source: String,
+    kind: StructuralQueryKind,
+    /// The words a template can't match without. Empty for tree-sitter queries.
+    literals: Vec<String>,
+    /// The template parsed, or the query compiled, for each language searched so far.
+    compiled: Mutex<HashMap<LanguageName, Option<Arc<Compiled>>>>,
+}
+
+#[derive(Debug)]
+enum StructuralQueryKind {
+    /// Code written in the searched language, with `$` metavariables. The source has had its
+    /// metavariables replaced with placeholder identifiers so that it parses.
+    Template {
+        parseable_source: String,
+    },
+    TreeSitterQuery,
+}
+
+enum Compiled {
+    Pattern(Pattern),
+    Query(Query),
+}
+
+impl fmt::Debug for Compiled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pattern(pattern) => f.debug_tuple("Pattern").field(&pattern.source).finish(),
+            Self::Query(_) => f.debug_tuple("Query").finish(),
+        }
+    }
+}
+
+/// A template parsed in one particular language.
+struct Pattern {
+    tree: Tree,
+    source: String,
+}
+
+enum Metavariable<'a> {
+    Single(&'a str),
+    Multi(&'a str),
+}
+
+/// The text captured by each metavariable, as ranges in the searched buffer.
+type Bindings = HashMap<String, Range<usize>>;
+
+impl StructuralQuery {
+    pub fn new(source: &str) -> Result<Self> {
+        let source = source.trim();
+        if source.is_empty() {
+            return Err(anyhow!("structural search query is empty"));
+        }
+        let (kind, literals) = if is_tree_sitter_query(source) {
+            (StructuralQueryKind::TreeSitterQuery, Vec::new())
+        } else {
+            let parseable_source = replace_metavariables(source, |name, multi| {
+                if multi {
+                    format!("{MULTI_METAVARIABLE_PREFIX}{name}")
+                } else {
+                    format!("{METAVARIABLE_PREFIX}{name}")
+                }
+            });
+            let mut literals = replace_metavariables(source, |_, _| " ".to_string())
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|word| !word.is_empty())
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            literals.sort();
+            literals.dedup();
+            (StructuralQueryKind::Template { parseable_source }, literals)
+        };
+        Ok(Self {
+            source: source.to_string(),
+            kind,
+            literals,
+            compiled: Mutex::default(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn is_tree_sitter_query(&self) -> bool {
+        matches!(self.kind, StructuralQueryKind::TreeSitterQuery)
+    }
+
+    /// Cheaply rules out files that can't contain a match, before they are opened and parsed.
+    /// A template can only match text that contains all of its literal words. Those never
+    /// span lines, so the text is read a line at a time and reading stops once all are seen.
+    pub(crate) fn could_match(&self, reader: impl BufRead) -> io::Result<bool> {
+        let mut unseen = self.literals.iter().collect::<Vec<_>>();
+        let mut lines = reader.lines();
+        while !unseen.is_empty() {
+            let Some(line) = lines.next() else {
+                return Ok(false);
+            };
+            let line = line?;
+            unseen.retain(|word| !line.contains(word.as_str()));
+        }
+        Ok(true)
+    }
+
+    /// The template parsed in `language`, or the query compiled for it. Each language is
+    /// only compiled once per query, however many buffers are searched.
+    fn compiled(&self, language: &Language) -> Option<Arc<Compiled>> {
+        self.compiled
+            .lock()
+            .entry(language.name())
+            .or_insert_with(|| {
+                let compiled = match &self.kind {
+                    StructuralQueryKind::Template { parseable_source } => {
+                        Compiled::Pattern(Pattern::parse(parseable_source, language)?)
+                    }
+                    StructuralQueryKind::TreeSitterQuery => {
+                        Compiled::Query(compile_query(&self.source, language)?)
+                    }
+                };
+                Some(Arc::new(compiled))
+            })
+            .clone()
+    }
+
+    /// Returns the non-overlapping ranges within `range` that match this query.
+    pub(crate) fn matches(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Vec<Range<usize>> {
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            let Some(compiled) = self.compiled(layer.language) else {
+                continue;
+            };
+            match compiled.as_ref() {
+                Compiled::Pattern(pattern) => {
+                    let Some(pattern_root) = pattern.root() else {
+                        continue;
+                    };
+                    let matcher = Matcher { pattern, buffer };
+                    matcher.find_matches(pattern_root, layer.node(), &range, &mut matches);
+                }
+                Compiled::Query(query) => with_query_cursor(|cursor| {
+                    cursor.set_byte_range(range.clone());
+                    for mat in cursor.matches(query, layer.node(), SnapshotText(buffer)) {
+                        let Some(node) = match_node(query, mat.captures) else {
+                            continue;
+                        };
+                        let node_range = node.byte_range();
+                        if node_range.start >= range.start && node_range.end <= range.end {
+                            matches.push(node_range);
+                        }
+                    }
+                }),
+            }
+        }
+
+        matches.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        matches.dedup();
+        let mut last_end = 0;
+        matches.retain(|range| {
+            let keep = range.start >= last_end && !range.is_empty();
+            if keep {
+                last_end = range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Renders `replacement` for the match at `range`, substituting the text of each
+    /// metavariable (or capture, for tree-sitter queries) it refers to.
+    pub(crate) fn replacement_for_match(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        replacement: &str,
+    ) -> Option<String> {
+        let bindings = buffer.syntax_layers().find_map(|layer| {
+            let compiled = self.compiled(layer.language)?;
+            match compiled.as_ref() {
+                Compiled::Pattern(pattern) => {
+                    let pattern_root = pattern.root()?;
+                    let matcher = Matcher { pattern, buffer };
+                    let mut node = layer
+                        .node()
+                        .descendant_for_byte_range(range.start, range.end)?;
+                    // Several nested nodes can share the matched range, so try each of them.
+                    while node.byte_range() == range {
+                        let mut bindings = Bindings::default();
+                        if matcher.match_node(pattern_root, node, &mut bindings) {
+                            return Some(bindings);
+                        }
+                        node = node.parent()?;
+                    }
+                    None
+                }
+                Compiled::Query(query) => with_query_cursor(|cursor| {
+                    cursor.set_byte_range(range.clone());
+                    cursor
+                        .matches(query, layer.node(), SnapshotText(buffer))
+                        .find(|mat| {
+                            match_node(query, mat.captures)
+                                .is_some_and(|node| node.byte_range() == range)
+                        })
+                        .map(|mat| {
+                            mat.captures
+                                .iter()
+                                .map(|capture| {
+                                    let name = query.capture_names()[capture.index as usize];
+                                    (name.to_string(), capture.node.byte_range())
+                                })
+                                .collect::<Bindings>()
+                        })
+                }),
+            }
+        })?;
+
+        // Tree-sitter capture names are usually lowercase, so any bound name is substituted.
+        let is_name = |name: &str| is_metavariable_name(name) || bindings.contains_key(name);
+        Some(replace_names(
+            replacement,
+            is_name,
+            |name, multi| match bindings.get(name) {
+                Some(range) => buffer.text_for_range(range.clone()).collect(),
+                None if multi => format!("$$${name}"),
+                None => format!("${name}"),
+            },
+        ))
+    }
+}
+
+/// Gives tree-sitter queries the text of a buffer a chunk at a time, so that predicates
+/// can be evaluated without copying the whole buffer into a string.
+struct SnapshotText<'a>(&'a BufferSnapshot);
+
+struct ByteChunks<'a>(text::Chunks<'a>);
+
+impl<'a> tree_sitter::TextProvider<&'a [u8]> for SnapshotText<'a> {
+    type I = ByteChunks<'a>;
+
+    fn text(&mut self, node: Node) -> Self::I {
+        ByteChunks(self.0.text_for_range(node.byte_range()))
+    }
+}
+
+impl<'a> Iterator for ByteChunks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(str::as_bytes)
+    }
+}
+
+impl Pattern {
+    fn parse(source: &str, language: &Language) -> Option<Self> {
+        let grammar = language.grammar()?;
+        let tree = with_parser(|parser| {
+            parser.set_language(&grammar.ts_language).ok()?;
+            parser.parse(source, None)
+        })?;
+        Some(Self {
+            tree,
+            source: source.to_string(),
+        })
+    }
+
+    /// The node the template describes. Wrappers such as the source file or an expression
+    /// statement are skipped when the template is entirely made up of their only child.
+    fn root(&self) -> Option<Node> {
+        let trimmed_start = self.source.len() - self.source.trim_start().len();
+        let trimmed = trimmed_start..self.source.trim_end().len();
+
+        let mut node = self.tree.root_node();
+        loop {
+            let mut cursor = node.walk();
+            let mut children = node
+                .named_children(&mut cursor)
+                .filter(|child| !child.is_extra());
+            match (children.next(), children.next()) {
+                (Some(child), None) if child.byte_range() == trimmed => node = child,
+                _ => break,
+            }
+        }
+        (!node.has_error()).then_some(node)
+    }
+}
+
+struct Matcher<'a> {
+    pattern: &'a Pattern,
+    buffer: &'a BufferSnapshot,
+}
+
+impl<'a> Matcher<'a> {
+    fn find_matches(
+        &self,
+        pattern_root: Node,
+        root: Node,
+        range: &Range<usize>,
+        matches: &mut Vec<Range<usize>>,
+    ) {
+        let root_metavariable = self.metavariable(pattern_root).is_some();
+        let mut cursor = root.walk();
+        'outer: loop {
+            let node = cursor.node();
+            let node_range = node.byte_range();
+            let overlaps = node_range.start < range.end && node_range.end > range.start;
+            let contained = node_range.start >= range.start && node_range.end <= range.end;
+
+            let matched = contained
+                && if root_metavariable {
+                    node.is_named()
+                } else {
+                    node.kind_id() == pattern_root.kind_id()
+                        && self.match_node(pattern_root, node, &mut Bindings::default())
+                };
+            if matched {
+                matches.push(node_range);
+            } else if overlaps && cursor.goto_first_child() {
+                continue;
+            }
+
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+    }
+
+    fn match_node(&self, pattern: Node, target: Node, bindings: &mut Bindings) -> bool {
+        match self.metavariable(pattern) {
+            Some(Metavariable::Single(name)) | Some(Metavariable::Multi(name)) => {
+                return self.bind(name, target.byte_range(), bindings);
+            }
+            None => {}
+        }
+
+        if pattern.kind_id() != target.kind_id() {
+            return false;
+        }
+        let pattern_children = significant_children(pattern);
+        let target_children = significant_children(target);
+        if pattern_children.is_empty() {
+            return target_children.is_empty()
+                && self.pattern_text(pattern) == self.target_text(target.byte_range());
+        }
+        self.match_sequence(&pattern_children, &target_children, bindings)
+    }
+
+    fn match_sequence(&self, patterns: &[Node], targets: &[Node], bindings: &mut Bindings) -> bool {
+        let Some((pattern, remaining_patterns)) = patterns.split_first() else {
+            return targets.is_empty();
+        };
+
+        if let Some(Metavariable::Multi(name)) = self.metavariable(*pattern) {
+            // Try the shortest run of siblings first, so the rest of the template can match.
+            for count in 0..=targets.len() {
+                let mut attempt = bindings.clone();
+                let range = match (targets.first(), count.checked_sub(1)) {
+                    (Some(first), Some(last)) => first.start_byte()..targets[last].end_byte(),
+                    _ => {
+                        let offset = targets.first().map_or(0, |target| target.start_byte());
+                        offset..offset
+                    }
+                };
+                if self.bind(name, range, &mut attempt)
+                    && self.match_sequence(remaining_patterns, &targets[count..], &mut attempt)
+                {
+                    *bindings = attempt;
+                    return true;
+                }
+            }
+            return false;
+        }
+
+        let Some((target, remaining_targets)) = targets.split_first() else {
+            return false;
+        };
+        let mut attempt = bindings.clone();
+        if self.match_node(*pattern, *target, &mut attempt)
+            && self.match_sequence(remaining_patterns, remaining_targets, &mut attempt)
+        {
+            *bindings = attempt;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Binds a metavariable, or checks that it matches its earlier binding. `$_` never binds.
+    fn bind(&self, name: &str, range: Range<usize>, bindings: &mut Bindings) -> bool {
+        if name == "_" {
+            return true;
+        }
+        if let Some(bound) = bindings.get(name) {
+            return self.target_text(bound.clone()) == self.target_text(range);
+        }
+        bindings.insert(name.to_string(), range);
+        true
+    }
+
+    fn metavariable(&self, node: Node) -> Option<Metavariable<'a>> {
+        let text = self.pattern_text(node).trim();
+        if let Some(name) = text.strip_prefix(MULTI_METAVARIABLE_PREFIX) {
+            is_metavariable_name(name).then_some(Metavariable::Multi(name))
+        } else if let Some(name) = text.strip_prefix(METAVARIABLE_PREFIX) {
+            is_metavariable_name(name).then_some(Metavariable::Single(name))
+        } else {
+            None
+        }
+    }
+
+    fn pattern_text(&self, node: Node) -> &'a str {
+        &self.pattern.source[node.byte_range()]
+    }
+
+    fn target_text(&self, range: Range<usize>) -> String {
+        self.buffer.text_for_range(range).collect()
+    }
+}
+
+/// The children that take part in matching: comments and nodes inserted by error recovery
+/// are skipped.
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing())
+        .collect()
+}
+
+fn is_tree_sitter_query(source: &str) -> bool {
+    (source.starts_with('(') || source.starts_with('[')) && source.contains('@')
+}
+
+fn compile_query(source: &str, language: &Language) -> Option<Query> {
+    let grammar = language.grammar()?;
+    Query::new(&grammar.ts_language, source).ok()
+}
+
+/// The node a tree-sitter query match refers to: its `@match` capture, or its first capture.
+fn match_node<'tree>(
+    query: &Query,
+    captures: &[tree_sitter::QueryCapture<'tree>],
+) -> Option<Node<'tree>> {
+    let match_index = query.capture_index_for_name(MATCH_CAPTURE_NAME);
+    captures
+        .iter()
+        .find(|capture| Some(capture.index) == match_index)
+        .or_else(|| captures.first())
+        .map(|capture| capture.node)
+}
+
+fn is_metavariable_name(name: &str) -> bool {
+    name == "_" || {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_uppercase() || c == '_')
+            && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    }
+}
+
+/// Replaces each `$NAME` and `$$$NAME` in `text` with the result of `replace(NAME, is_multi)`.
+/// A bare `$$$` is an anonymous run of nodes, like `$$$_`.
+fn replace_metavariables(text: &str, replace: impl FnMut(&str, bool) -> String) -> String {
+    replace_names(text, is_metavariable_name, replace)
+}
+
+fn replace_names(
+    text: &str,
+    is_name: impl Fn(&str) -> bool,
+    mut replace: impl FnMut(&str, bool) -> String,
+) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(dollar) = rest.find('$') {
+        result.push_str(&rest[..dollar]);
+        rest = &rest[dollar..];
+
+        let multi = rest.starts_with("$$$");
+        let after_dollars = if multi { &rest[3..] } else { &rest[1..] };
+        let name_len = after_dollars
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after_dollars.len());
+        let name = &after_dollars[..name_len];
+        if is_name(name) {
+            result.push_str(&replace(name, multi));
+            rest = &after_dollars[name_len..];
+        } else if multi && name.is_empty() {
+            result.push_str(&replace("_", true));
+            rest = after_dollars;
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use language::{tree_sitter_rust, Buffer, Language, LanguageConfig, LanguageMatcher};
+    use std::sync::Arc;
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        ))
+    }
+
+    async fn parsed_buffer(text: &str, cx: &mut TestAppContext) -> BufferSnapshot {
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(rust_lang(), cx));
+        cx.executor().run_until_parked();
+        buffer.read_with(cx, |buffer, _| buffer.snapshot())
+    }
+
+    fn matched_text(buffer: &BufferSnapshot, query: &StructuralQuery) -> Vec<String> {
+        query
+            .matches(buffer, 0..buffer.len())
+            .into_iter()
+            .map(|range| buffer.text_for_range(range).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_replace_metavariables() {
+        let replaced =
+            replace_metavariables("foo($A, $$$REST) + $$$ + $_ + $lower + $", |name, multi| {
+                format!("<{name}{}>", if multi { "*" } else { "" })
+            });
+        assert_eq!(replaced, "foo(<A>, <REST*>) + <_*> + <_> + $lower + $");
+    }
+
+    #[gpui::test]
+    async fn test_template_matches(cx: &mut TestAppContext) {
+        let buffer = parsed_buffer(
+            r#"
+            fn main() {
+                let a = foo(1, bar(2));
+                foo(a);
+                foo();
+                // foo(commented)
+                baz(foo(x, x), "foo(text)");
+            }
+            "#,
+            cx,
+        )
+        .await;
+
+        let query = StructuralQuery::new("foo($A)").unwrap();
+        assert_eq!(matched_text(&buffer, &query), ["foo(a)"]);
+
+        let query = StructuralQuery::new("foo($$$ARGS)").unwrap();
+        assert_eq!(
+            matched_text(&buffer, &query),
+            ["foo(1, bar(2))", "foo(a)", "foo()", "foo(x, x)"]
+        );
+
+        // A metavariable used twice has to match the same text both times.
+        let query = StructuralQuery::new("foo($A, $A)").unwrap();
+        assert_eq!(matched_text(&buffer, &query), ["foo(x, x)"]);
+
+        let query = StructuralQuery::new("let $NAME = $VALUE;").unwrap();
+        assert_eq!(matched_text(&buffer, &query), ["let a = foo(1, bar(2));"]);
+
+        assert!(query.could_match("let value = 1;".as_bytes()).unwrap());
+        assert!(!StructuralQuery::new("foo($A)")
+            .unwrap()
+            .could_match("bar(1)".as_bytes())
+            .unwrap());
+        // The literal words can be on different lines, but all of them have to appear.
+        let query = StructuralQuery::new("foo($A).bar()").unwrap();
+        assert!(query
+            .could_match("x.foo(1)\n  .bar();\n".as_bytes())
+            .unwrap());
+        assert!(!query
+            .could_match("x.foo(1)\n  .baz();\n".as_bytes())
+            .unwrap());
+    }
+
+    #[gpui::test]
+    async fn test_tree_sitter_query_matches(cx: &mut TestAppContext) {
+        let buffer = parsed_buffer("fn one() {}\nfn two() { one() }\n", cx).await;
+
+        let query =
+            StructuralQuery::new("(function_item name: (identifier) @name) @match").unwrap();
+        assert!(query.is_tree_sitter_query());
+        assert_eq!(
+            matched_text(&buffer, &query),
+            ["fn one() {}", "fn two() { one() }"]
+        );
+
+        let ranges = query.matches(&buffer, 0..buffer.len());
+        assert_eq!(
+            query.replacement_for_match(&buffer, ranges[1].clone(), "fn $name() {}"),
+            Some("fn two() {}".to_string())
+        );
+        // The query is compiled once for Rust, and reused for every match and replacement.
+        assert_eq!(query.compiled.lock().len(), 1);
+    }
+
+    #[gpui::test]
+    async fn test_template_replacement(cx: &mut TestAppContext) {
+        let buffer = parsed_buffer("fn main() { foo(a, b, c); }\n", cx).await;
+
+        let query = StructuralQuery::new("foo($FIRST, $$$REST)").unwrap();
+        let ranges = query.matches(&buffer, 0..buffer.len());
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(
+            query.replacement_for_match(&buffer, ranges[0].clone(), "bar($$$REST, $FIRST)"),
+            Some("bar(b, c, a)".to_string())
+        );
+
+        // Ranges that aren't a match have no replacement.
+        assert_eq!(query.replacement_for_match(&buffer, 0..2, "bar()"), None);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::search::SearchScope;
+
 /// Determines the behavior to use when inserting a new query into the search history.
 #[derive(Default, Debug, Clone, PartialEq)]
 pub enum QueryInsertionBehavior {
@@ -28,11 +30,18 @@ impl SearchHistoryCursor {
 
 #[derive(Debug, Clone)]
 pub struct SearchHistory {
-    history: Vec<String>,
+    history: Vec<SearchHistoryEntry>,
     max_history_len: Option<usize>,
     insertion_behavior: QueryInsertionBehavior,
 }
 
+/// A query in the search history, along with the scope it was searched in, if any.
+#[derive(Debug, Clone, PartialEq)]
+struct SearchHistoryEntry {
+    query: String,
+    scope: Option<SearchScope>,
+}
+
 impl SearchHistory {
     pub fn new(max_history_len: Option<usize>, insertion_behavior: QueryInsertionBehavior) -> Self {
         SearchHistory {
@@ -43,23 +52,40 @@ impl SearchHistory {
     }
 
     pub fn add(&mut self, cursor: &mut SearchHistoryCursor, search_string: String) {
+        self.add_with_scope(cursor, search_string, None);
+    }
+
+    /// Adds a query that was searched within the given scope, so that recalling the query
+    /// also recalls its scope.
+    pub fn add_with_scope(
+        &mut self,
+        cursor: &mut SearchHistoryCursor,
+        search_string: String,
+        scope: Option<SearchScope>,
+    ) {
+        let entry = SearchHistoryEntry {
+            query: search_string,
+            scope,
+        };
         if let Some(selected_ix) = cursor.selection {
-            if self.history.get(selected_ix) == Some(&search_string) {
+            if self.history.get(selected_ix) == Some(&entry) {
                 return;
             }
         }
 
         if self.insertion_behavior == QueryInsertionBehavior::ReplacePreviousIfContains {
             if let Some(previously_searched) = self.history.last_mut() {
-                if search_string.contains(previously_searched.as_str()) {
-                    *previously_searched = search_string;
+                if previously_searched.scope == entry.scope
+                    && entry.query.contains(previously_searched.query.as_str())
+                {
+                    *previously_searched = entry;
                     cursor.selection = Some(self.history.len() - 1);
                     return;
                 }
             }
         }
 
-        self.history.push(search_string);
+        self.history.push(entry);
         if let Some(max_history_len) = self.max_history_len {
             if self.history.len() > max_history_len {
                 self.history.remove(0);
@@ -81,13 +107,22 @@ impl SearchHistory {
         }
         let next_index = selected + 1;
         cursor.selection = Some(next_index);
-        Some(&self.history[next_index])
+        Some(&self.history[next_index].query)
     }
 
     pub fn current(&self, cursor: &SearchHistoryCursor) -> Option<&str> {
         cursor
             .selection
-            .and_then(|selected_ix| self.history.get(selected_ix).map(|s| s.as_str()))
+            .and_then(|selected_ix| self.history.get(selected_ix))
+            .map(|entry| entry.query.as_str())
+    }
+
+    /// Returns the scope that the currently selected query was searched in.
+    pub fn scope(&self, cursor: &SearchHistoryCursor) -> Option<&SearchScope> {
+        cursor
+            .selection
+            .and_then(|selected_ix| self.history.get(selected_ix))
+            .and_then(|entry| entry.scope.as_ref())
     }
 
     pub fn previous(&mut self, cursor: &mut SearchHistoryCursor) -> Option<&str> {
@@ -108,7 +143,7 @@ impl SearchHistory {
         };
 
         cursor.selection = Some(prev_index);
-        Some(&self.history[prev_index])
+        Some(&self.history[prev_index].query)
     }
 }
 
@@ -163,6 +198,37 @@ mod tests {
         assert!(search_history.history.len() <= MAX_HISTORY_LEN);
     }
 
+    #[test]
+    fn test_add_with_scope() {
+        let mut search_history =
+            SearchHistory::new(None, QueryInsertionBehavior::ReplacePreviousIfContains);
+        let mut cursor = SearchHistoryCursor::default();
+
+        search_history.add(&mut cursor, "rust".to_string());
+        assert_eq!(search_history.scope(&cursor), None);
+
+        // A query in another scope is kept separate, even if it contains the previous query.
+        search_history.add_with_scope(
+            &mut cursor,
+            "rustlang".to_string(),
+            Some(SearchScope::GitChanges),
+        );
+        assert_eq!(search_history.history.len(), 2);
+        assert_eq!(search_history.current(&cursor), Some("rustlang"));
+        assert_eq!(
+            search_history.scope(&cursor),
+            Some(&SearchScope::GitChanges)
+        );
+
+        assert_eq!(search_history.previous(&mut cursor), Some("rust"));
+        assert_eq!(search_history.scope(&cursor), None);
+        assert_eq!(search_history.next(&mut cursor), Some("rustlang"));
+        assert_eq!(
+            search_history.scope(&cursor),
+            Some(&SearchScope::GitChanges)
+        );
+    }
+
     #[test]
     fn test_next_and_previous() {
         let mut search_history = SearchHistory::new(None, QueryInsertionBehavior::AlwaysInsert);
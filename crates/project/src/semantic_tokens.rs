@@ -0,0 +1,185 @@
+use std::{ops::Range, sync::Arc};
+
+use language::{point_from_lsp, Anchor, Bias};
+use lsp::LanguageServerId;
+
+/// The number of integers used to encode each token in the LSP wire format.
+const TOKEN_LEN: usize = 5;
+
+/// The semantic tokens of a buffer, as reported by its language server.
+#[derive(Clone, Debug, Default)]
+pub struct BufferSemanticTokens {
+    /// The names of the token types, indexed by [`SemanticToken::token_type`].
+    pub token_types: Arc<[String]>,
+    /// The names of the token modifiers, indexed by the bits of [`SemanticToken::token_modifiers`].
+    pub token_modifiers: Arc<[String]>,
+    /// The tokens, in buffer order.
+    pub tokens: Arc<[SemanticToken]>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SemanticToken {
+    pub range: Range<Anchor>,
+    pub token_type: u32,
+    pub token_modifiers: u32,
+}
+
+impl BufferSemanticTokens {
+    pub fn token_type(&self, token: &SemanticToken) -> Option<&str> {
+        self.token_types
+            .get(token.token_type as usize)
+            .map(String::as_str)
+    }
+
+    pub fn modifiers(&self, token: &SemanticToken) -> impl Iterator<Item = &str> {
+        self.token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| *ix < 32 && token.token_modifiers & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+    }
+}
+
+/// The last tokens a language server returned for a buffer, which later requests can ask a
+/// delta against.
+pub(crate) struct LspSemanticTokens {
+    pub server_id: LanguageServerId,
+    pub result_id: Option<String>,
+    pub data: Vec<u32>,
+}
+
+pub(crate) fn encode_tokens(tokens: Vec<lsp::SemanticToken>) -> Vec<u32> {
+    tokens
+        .into_iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+/// Applies the edits of a delta response, whose offsets all refer to the previous data.
+pub(crate) fn apply_token_edits(data: &mut Vec<u32>, mut edits: Vec<lsp::SemanticTokensEdit>) {
+    edits.sort_by_key(|edit| edit.start);
+    for edit in edits.into_iter().rev() {
+        let start = (edit.start as usize).min(data.len());
+        let end = (start + edit.delete_count as usize).min(data.len());
+        data.splice(start..end, encode_tokens(edit.data.unwrap_or_default()));
+    }
+}
+
+/// Resolves the relative positions of the encoded tokens against the buffer the server saw.
+pub(crate) fn decode_tokens(data: &[u32], snapshot: &text::BufferSnapshot) -> Vec<SemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len() / TOKEN_LEN);
+    let mut line = 0;
+    let mut start = 0;
+    for token in data.chunks_exact(TOKEN_LEN) {
+        let [delta_line, delta_start, length, token_type, token_modifiers] = token else {
+            continue;
+        };
+        if *delta_line > 0 {
+            line += delta_line;
+            start = *delta_start;
+        } else {
+            start += delta_start;
+        }
+        let token_start = lsp::Position::new(line, start);
+        let token_end = lsp::Position::new(line, start + length);
+        let token_start = snapshot.clip_point_utf16(point_from_lsp(token_start), Bias::Left);
+        let token_end = snapshot.clip_point_utf16(point_from_lsp(token_end), Bias::Left);
+        if token_start == token_end {
+            continue;
+        }
+        tokens.push(SemanticToken {
+            range: snapshot.anchor_after(token_start)..snapshot.anchor_before(token_end),
+            token_type: *token_type,
+            token_modifiers: *token_modifiers,
+        });
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text::{Buffer, BufferId, ToOffset as _};
+
+    #[test]
+    fn test_decode_token_edits() {
+        let buffer = Buffer::new(
+            0,
+            BufferId::new(1).unwrap(),
+            "let mut a = 1;\nfoo(a);\n".into(),
+        );
+        let snapshot = buffer.snapshot();
+
+        // `let`, `mut` and `a` on the first line, `foo` on the second.
+        let mut data = vec![0, 0, 3, 0, 0, 0, 4, 3, 0, 0, 0, 4, 1, 1, 1, 1, 0, 3, 2, 0];
+        let text_for = |tokens: &[SemanticToken]| {
+            tokens
+                .iter()
+                .map(|token| {
+                    let range = token.range.start.to_offset(&snapshot)
+                        ..token.range.end.to_offset(&snapshot);
+                    (
+                        snapshot.text_for_range(range).collect::<String>(),
+                        token.token_type,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            text_for(&decode_tokens(&data, &snapshot)),
+            [
+                ("let".to_string(), 0),
+                ("mut".to_string(), 0),
+                ("a".to_string(), 1),
+                ("foo".to_string(), 2)
+            ]
+        );
+
+        // Replace the `mut` token and add the argument of `foo`.
+        apply_token_edits(
+            &mut data,
+            vec![
+                lsp::SemanticTokensEdit {
+                    start: 20,
+                    delete_count: 0,
+                    data: Some(vec![lsp::SemanticToken {
+                        delta_line: 0,
+                        delta_start: 4,
+                        length: 1,
+                        token_type: 1,
+                        token_modifiers_bitset: 0,
+                    }]),
+                },
+                lsp::SemanticTokensEdit {
+                    start: 5,
+                    delete_count: 5,
+                    data: Some(vec![lsp::SemanticToken {
+                        delta_line: 0,
+                        delta_start: 4,
+                        length: 3,
+                        token_type: 3,
+                        token_modifiers_bitset: 0,
+                    }]),
+                },
+            ],
+        );
+        assert_eq!(
+            text_for(&decode_tokens(&data, &snapshot)),
+            [
+                ("let".to_string(), 0),
+                ("mut".to_string(), 3),
+                ("a".to_string(), 1),
+                ("foo".to_string(), 2),
+                ("a".to_string(), 1)
+            ]
+        );
+    }
+}
@@ -12,22 +12,23 @@ use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet, VecDeque};
 use gpui::{App, AppContext as _, Entity, SharedString, Task};
 use itertools::Itertools;
-use language::{ContextProvider, File, Language, LanguageToolchainStore, Location};
+use language::{ContextProvider, File, Language, LanguageName, LanguageToolchainStore, Location};
 use settings::{parse_json_with_comments, SettingsLocation};
 use task::{
     ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables, VariableName,
 };
 use text::{Point, ToPoint};
-use util::{post_inc, NumericPrefixWithSuffix, ResultExt as _};
+use util::{debug_panic, post_inc, NumericPrefixWithSuffix, ResultExt as _};
 use worktree::WorktreeId;
 
-use crate::worktree_store::WorktreeStore;
+use crate::{task_store::TaskGraph, worktree_store::WorktreeStore};
 
 /// Inventory tracks available tasks for a given project.
 #[derive(Debug, Default)]
 pub struct Inventory {
     last_scheduled_tasks: VecDeque<(TaskSourceKind, ResolvedTask)>,
     templates_from_settings: ParsedTemplates,
+    templates_from_extensions: HashMap<WorktreeId, Vec<ExtensionTemplates>>,
 }
 
 #[derive(Debug, Default)]
@@ -36,6 +37,15 @@ struct ParsedTemplates {
     worktree: HashMap<WorktreeId, HashMap<Arc<Path>, Vec<TaskTemplate>>>,
 }
 
+/// Task templates that an extension's task provider contributes to a worktree.
+#[derive(Debug)]
+struct ExtensionTemplates {
+    source_kind: TaskSourceKind,
+    /// The languages the templates are listed for, or all languages if empty.
+    languages: Vec<LanguageName>,
+    templates: Vec<TaskTemplate>,
+}
+
 /// Kind of a source the tasks are fetched from, used to display more source information in the UI.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum TaskSourceKind {
@@ -54,6 +64,13 @@ pub enum TaskSourceKind {
     },
     /// Languages-specific tasks coming from extensions.
     Language { name: SharedString },
+    /// Tasks contributed to a worktree by an extension's task provider.
+    Extension {
+        extension_id: SharedString,
+        extension_name: SharedString,
+        provider_id: SharedString,
+        worktree: WorktreeId,
+    },
 }
 
 impl TaskSourceKind {
@@ -71,6 +88,12 @@ impl TaskSourceKind {
                 format!("{id_base}_{id}_{}", directory_in_worktree.display())
             }
             TaskSourceKind::Language { name } => format!("language_{name}"),
+            TaskSourceKind::Extension {
+                extension_id,
+                provider_id,
+                worktree,
+                ..
+            } => format!("extension_{extension_id}_{provider_id}_{worktree}"),
         }
     }
 }
@@ -93,6 +116,7 @@ impl Inventory {
         let task_source_kind = language.as_ref().map(|language| TaskSourceKind::Language {
             name: language.name().into(),
         });
+        let language_name = language.as_ref().map(|language| language.name());
         let global_tasks = self.global_templates_from_settings();
         let language_tasks = language
             .and_then(|language| language.context_provider()?.associated_tasks(file, cx))
@@ -102,6 +126,7 @@ impl Inventory {
             .chain(global_tasks);
 
         self.worktree_templates_from_settings(worktree)
+            .chain(self.extension_templates(worktree, language_name.as_ref()))
             .chain(language_tasks)
             .collect()
     }
@@ -126,6 +151,7 @@ impl Inventory {
         let task_source_kind = language.as_ref().map(|language| TaskSourceKind::Language {
             name: language.name().into(),
         });
+        let language_name = language.as_ref().map(|language| language.name());
         let file = location
             .as_ref()
             .and_then(|location| location.buffer.read(cx).file().cloned());
@@ -177,6 +203,7 @@ impl Inventory {
             .chain(global_tasks);
         let worktree_tasks = self
             .worktree_templates_from_settings(worktree)
+            .chain(self.extension_templates(worktree, language_name.as_ref()))
             .chain(language_tasks);
 
         let new_resolved_tasks = worktree_tasks
@@ -242,6 +269,37 @@ impl Inventory {
         self.last_scheduled_tasks.retain(|(_, task)| &task.id != id);
     }
 
+    /// Resolves the tasks the task given depends on, recursively, with the same [`TaskContext`] the task was resolved with.
+    /// Dependencies are looked up by their labels among the templates from the task's source first,
+    /// then among the rest of the worktree's templates and the global ones.
+    pub fn task_graph(
+        &self,
+        task_source_kind: &TaskSourceKind,
+        task: ResolvedTask,
+    ) -> Result<TaskGraph> {
+        let worktree = match task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            TaskSourceKind::Extension { worktree, .. } => Some(*worktree),
+            _ => None,
+        };
+        let extension_templates = worktree
+            .and_then(|worktree| self.templates_from_extensions.get(&worktree))
+            .into_iter()
+            .flatten()
+            .flat_map(|extension_templates| {
+                extension_templates
+                    .templates
+                    .iter()
+                    .map(|template| (extension_templates.source_kind.clone(), template.clone()))
+            });
+        let templates = self
+            .worktree_templates_from_settings(worktree)
+            .chain(extension_templates)
+            .chain(self.global_templates_from_settings())
+            .collect::<Vec<_>>();
+        build_task_graph(&templates, task_source_kind, task, &mut Vec::new())
+    }
+
     fn global_templates_from_settings(
         &self,
     ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
@@ -288,6 +346,69 @@ impl Inventory {
         })
     }
 
+    fn extension_templates<'a>(
+        &'a self,
+        worktree: Option<WorktreeId>,
+        language: Option<&'a LanguageName>,
+    ) -> impl 'a + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
+        worktree
+            .and_then(|worktree| self.templates_from_extensions.get(&worktree))
+            .into_iter()
+            .flatten()
+            .filter(move |extension_templates| {
+                extension_templates.languages.is_empty()
+                    || language
+                        .is_some_and(|language| extension_templates.languages.contains(language))
+            })
+            .flat_map(|extension_templates| {
+                extension_templates
+                    .templates
+                    .iter()
+                    .map(|template| (extension_templates.source_kind.clone(), template.clone()))
+            })
+    }
+
+    /// Replaces the task templates that an extension's task provider contributes to a worktree.
+    ///
+    /// The templates are only listed for the given languages, or for any language if none are given.
+    pub fn update_extension_tasks(
+        &mut self,
+        source_kind: TaskSourceKind,
+        languages: Vec<LanguageName>,
+        templates: Vec<TaskTemplate>,
+    ) {
+        let TaskSourceKind::Extension { worktree, .. } = &source_kind else {
+            debug_panic!("expected an extension task source, got {source_kind:?}");
+            return;
+        };
+        let worktree_templates = self.templates_from_extensions.entry(*worktree).or_default();
+        worktree_templates
+            .retain(|extension_templates| extension_templates.source_kind != source_kind);
+        if !templates.is_empty() {
+            worktree_templates.push(ExtensionTemplates {
+                source_kind,
+                languages,
+                templates,
+            });
+        }
+    }
+
+    /// Removes the task templates that the given extension's task provider contributes to any worktree.
+    pub fn remove_extension_tasks(&mut self, extension_id: &str, provider_id: &str) {
+        for worktree_templates in self.templates_from_extensions.values_mut() {
+            worktree_templates.retain(|extension_templates| {
+                !matches!(
+                    &extension_templates.source_kind,
+                    TaskSourceKind::Extension {
+                        extension_id: id,
+                        provider_id: provider,
+                        ..
+                    } if id == extension_id && provider == provider_id
+                )
+            });
+        }
+    }
+
     /// Updates in-memory task metadata from the JSON string given.
     /// Will fail if the JSON is not a valid array of objects, but will continue if any object will not parse into a [`TaskTemplate`].
     ///
@@ -328,6 +449,55 @@ impl Inventory {
     }
 }
 
+fn build_task_graph(
+    templates: &[(TaskSourceKind, TaskTemplate)],
+    task_source_kind: &TaskSourceKind,
+    task: ResolvedTask,
+    dependents: &mut Vec<String>,
+) -> Result<TaskGraph> {
+    let label = &task.original_task().label;
+    if dependents.contains(label) {
+        anyhow::bail!(
+            "Task dependency cycle: {} -> {label}",
+            dependents.join(" -> ")
+        );
+    }
+
+    dependents.push(label.clone());
+    let mut dependencies = Vec::new();
+    for dependency_label in &task.original_task().depends_on {
+        let (dependency_source_kind, template) = templates
+            .iter()
+            .find(|(kind, template)| {
+                kind == task_source_kind && &template.label == dependency_label
+            })
+            .or_else(|| {
+                templates
+                    .iter()
+                    .find(|(_, template)| &template.label == dependency_label)
+            })
+            .with_context(|| {
+                format!("Task `{label}` depends on unknown task `{dependency_label}`")
+            })?;
+        let dependency = template
+            .resolve_task(&dependency_source_kind.to_id_base(), task.task_context())
+            .with_context(|| format!("Failed to resolve task `{dependency_label}`"))?;
+        dependencies.push(build_task_graph(
+            templates,
+            dependency_source_kind,
+            dependency,
+            dependents,
+        )?);
+    }
+    dependents.pop();
+
+    Ok(TaskGraph {
+        task_source_kind: task_source_kind.clone(),
+        task,
+        dependencies,
+    })
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
@@ -356,7 +526,8 @@ fn task_source_kind_preference(kind: &TaskSourceKind) -> u32 {
         TaskSourceKind::Language { .. } => 1,
         TaskSourceKind::UserInput => 2,
         TaskSourceKind::Worktree { .. } => 3,
-        TaskSourceKind::AbsPath { .. } => 4,
+        TaskSourceKind::Extension { .. } => 4,
+        TaskSourceKind::AbsPath { .. } => 5,
     }
 }
 
@@ -561,9 +732,14 @@ impl ContextProvider for ContextProviderWithTasks {
 
 #[cfg(test)]
 mod tests {
+    use futures::{
+        future::{self, LocalBoxFuture},
+        FutureExt as _,
+    };
     use gpui::TestAppContext;
     use pretty_assertions::assert_eq;
     use serde_json::json;
+    use std::{cell::RefCell, rc::Rc};
 
     use crate::task_store::TaskStore;
 
@@ -851,6 +1027,330 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_task_graph(cx: &mut TestAppContext) {
+        init_test(cx);
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    None,
+                    Some(
+                        &json!([
+                            {
+                                "label": "build",
+                                "depends_on": ["lint", "compile"],
+                                "depends_order": "parallel",
+                            },
+                            { "label": "lint", "command": "echo" },
+                            { "label": "compile", "command": "echo", "depends_on": ["codegen"] },
+                            { "label": "codegen", "command": "echo" },
+                            { "label": "failing", "command": "false" },
+                            { "label": "release", "command": "echo", "depends_on": ["failing", "lint"] },
+                            { "label": "cycle_a", "command": "echo", "depends_on": ["cycle_b"] },
+                            { "label": "cycle_b", "command": "echo", "depends_on": ["cycle_a"] },
+                            { "label": "broken", "command": "echo", "depends_on": ["missing"] },
+                            {
+                                "label": "diamond",
+                                "command": "echo",
+                                "depends_on": ["left", "right"],
+                                "depends_order": "parallel",
+                            },
+                            { "label": "left", "command": "echo", "depends_on": ["base"] },
+                            { "label": "right", "command": "echo", "depends_on": ["base"] },
+                            { "label": "base", "command": "echo" },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        let task_graph = |label: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let (task_source_kind, template) = inventory
+                    .list_tasks(None, None, None, cx)
+                    .into_iter()
+                    .find(|(_, template)| template.label == label)
+                    .unwrap();
+                let task = template
+                    .resolve_task(&task_source_kind.to_id_base(), &TaskContext::default())
+                    .unwrap();
+                inventory.task_graph(&task_source_kind, task)
+            })
+        };
+        let spawned = Rc::new(RefCell::new(Vec::new()));
+        let spawn: Rc<dyn Fn(ResolvedTask) -> LocalBoxFuture<'static, bool>> = Rc::new({
+            let spawned = spawned.clone();
+            move |task| {
+                spawned.borrow_mut().push(task.resolved_label.clone());
+                future::ready(task.resolved_label != "failing").boxed_local()
+            }
+        });
+
+        task_graph("build", cx)
+            .unwrap()
+            .run(spawn.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            spawned.take(),
+            vec!["lint", "codegen", "compile"],
+            "Task without a command should only run its dependencies"
+        );
+
+        let error = task_graph("release", cx)
+            .unwrap()
+            .run(spawn.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Task `failing` failed");
+        assert_eq!(
+            spawned.take(),
+            vec!["failing"],
+            "Nothing should run after a sequential dependency fails"
+        );
+
+        task_graph("diamond", cx)
+            .unwrap()
+            .run(spawn.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            spawned.take(),
+            vec!["base", "left", "right", "diamond"],
+            "A task that several dependencies depend on should run once"
+        );
+
+        let pending = Rc::new(());
+        let spawn_pending: Rc<dyn Fn(ResolvedTask) -> LocalBoxFuture<'static, bool>> = Rc::new({
+            let pending = pending.clone();
+            move |_| {
+                let pending = pending.clone();
+                async move {
+                    let _pending = pending;
+                    future::pending::<()>().await;
+                    true
+                }
+                .boxed_local()
+            }
+        });
+        let run = task_graph("diamond", cx).unwrap().run(spawn_pending);
+        assert!(run.now_or_never().is_none());
+        assert_eq!(
+            Rc::strong_count(&pending),
+            1,
+            "Dropping an unfinished run should drop the tasks it started"
+        );
+
+        assert_eq!(
+            task_graph("cycle_a", cx).unwrap_err().to_string(),
+            "Task dependency cycle: cycle_a -> cycle_b -> cycle_a"
+        );
+        assert_eq!(
+            task_graph("broken", cx).unwrap_err().to_string(),
+            "Task `broken` depends on unknown task `missing`"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_task_graph_inputs(cx: &mut TestAppContext) {
+        init_test(cx);
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    None,
+                    Some(
+                        &json!([
+                            {
+                                "label": "deploy",
+                                "command": "echo",
+                                "args": ["$ZED_INPUT_target"],
+                                "depends_on": ["configure", "migrate"],
+                                "inputs": [{ "id": "target", "type": "prompt_string" }],
+                            },
+                            {
+                                "label": "configure",
+                                "command": "echo",
+                                "args": ["$ZED_INPUT_environment"],
+                                "inputs": [{
+                                    "id": "environment",
+                                    "type": "pick_string",
+                                    "options": ["staging", "production"],
+                                }],
+                            },
+                            {
+                                "label": "migrate",
+                                "command": "echo",
+                                "args": ["$ZED_INPUT_environment"],
+                                "inputs": [{ "id": "environment", "type": "prompt_string" }],
+                            },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        let task_graph = inventory
+            .update(cx, |inventory, cx| {
+                let (task_source_kind, template) = inventory
+                    .list_tasks(None, None, None, cx)
+                    .into_iter()
+                    .find(|(_, template)| template.label == "deploy")
+                    .unwrap();
+                let task = template
+                    .resolve_task(&task_source_kind.to_id_base(), &TaskContext::default())
+                    .unwrap();
+                inventory.task_graph(&task_source_kind, task)
+            })
+            .unwrap();
+        let missing_input_ids = |task_graph: &TaskGraph| {
+            task_graph
+                .missing_inputs()
+                .into_iter()
+                .map(|input| input.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            missing_input_ids(&task_graph),
+            vec!["target", "environment"],
+            "Inputs of the dependencies should be asked for along with the task's own, each once"
+        );
+        assert_eq!(
+            missing_input_ids(
+                &task_graph
+                    .clone()
+                    .with_inputs(&[("target".to_string(), "web".to_string())])
+                    .unwrap()
+            ),
+            vec!["environment"]
+        );
+
+        let task_graph = task_graph
+            .with_inputs(&[
+                ("target".to_string(), "web".to_string()),
+                ("environment".to_string(), "staging".to_string()),
+            ])
+            .unwrap();
+        assert!(missing_input_ids(&task_graph).is_empty());
+        let spawned = Rc::new(RefCell::new(Vec::new()));
+        task_graph
+            .run(Rc::new({
+                let spawned = spawned.clone();
+                move |task: ResolvedTask| {
+                    spawned.borrow_mut().push((
+                        task.original_task().label.clone(),
+                        task.resolved.unwrap().args,
+                    ));
+                    future::ready(true).boxed_local()
+                }
+            }))
+            .await
+            .unwrap();
+        assert_eq!(
+            spawned.take(),
+            vec![
+                ("configure".to_string(), vec!["staging".to_string()]),
+                ("migrate".to_string(), vec!["staging".to_string()]),
+                ("deploy".to_string(), vec!["web".to_string()]),
+            ],
+            "Every task in the graph should be spawned with the values of its inputs"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_extension_tasks(cx: &mut TestAppContext) {
+        init_test(cx);
+        let inventory = cx.update(Inventory::new);
+        let worktree_1 = WorktreeId::from_usize(1);
+        let worktree_2 = WorktreeId::from_usize(2);
+        let extension_source = |provider_id: &'static str, worktree| TaskSourceKind::Extension {
+            extension_id: "my-extension".into(),
+            extension_name: "My Extension".into(),
+            provider_id: provider_id.into(),
+            worktree,
+        };
+        let template = |label: &str| TaskTemplate {
+            label: label.to_string(),
+            command: "echo".to_string(),
+            ..TaskTemplate::default()
+        };
+        inventory.update(cx, |inventory, _| {
+            inventory.update_extension_tasks(
+                extension_source("make", worktree_1),
+                Vec::new(),
+                vec![template("make build"), template("make test")],
+            );
+            inventory.update_extension_tasks(
+                extension_source("cargo", worktree_1),
+                vec!["Rust".into()],
+                vec![template("cargo run")],
+            );
+        });
+
+        assert_eq!(
+            list_tasks(&inventory, Some(worktree_1), cx).await,
+            vec![
+                (
+                    extension_source("make", worktree_1),
+                    "make build".to_string()
+                ),
+                (
+                    extension_source("make", worktree_1),
+                    "make test".to_string()
+                ),
+            ],
+            "Language-specific extension tasks should only be listed for their languages"
+        );
+        assert_eq!(
+            list_tasks(&inventory, Some(worktree_2), cx).await,
+            Vec::new(),
+            "Extension tasks should only be listed for their worktree"
+        );
+        let rust = Arc::new(Language::new(
+            language::LanguageConfig {
+                name: "Rust".into(),
+                ..Default::default()
+            },
+            None,
+        ));
+        let rust_task_names = inventory.update(cx, |inventory, cx| {
+            inventory
+                .list_tasks(None, Some(rust), Some(worktree_1), cx)
+                .into_iter()
+                .map(|(_, task)| task.label)
+                .sorted()
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            rust_task_names,
+            vec!["cargo run", "make build", "make test"]
+        );
+
+        inventory.update(cx, |inventory, _| {
+            inventory.update_extension_tasks(
+                extension_source("make", worktree_1),
+                Vec::new(),
+                vec![template("make all")],
+            );
+        });
+        assert_eq!(
+            task_template_names(&inventory, Some(worktree_1), cx),
+            vec!["make all"],
+            "Updating a provider's tasks should replace its previous tasks"
+        );
+
+        inventory.update(cx, |inventory, _| {
+            inventory.remove_extension_tasks("my-extension", "make");
+        });
+        assert_eq!(
+            task_template_names(&inventory, Some(worktree_1), cx),
+            Vec::<String>::new(),
+        );
+    }
+
     fn init_test(_cx: &mut TestAppContext) {
         if std::env::var("RUST_LOG").is_ok() {
             env_logger::try_init().ok();
@@ -1,9 +1,18 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    cell::RefCell,
+    path::PathBuf,
+    rc::{Rc, Weak},
+    sync::Arc,
+};
 
-use anyhow::Context as _;
+use anyhow::{anyhow, Context as _, Result};
 use collections::HashMap;
 use fs::Fs;
-use futures::StreamExt as _;
+use futures::{
+    channel::oneshot,
+    future::{self, LocalBoxFuture, Shared},
+    FutureExt as _, StreamExt as _,
+};
 use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Task, WeakEntity};
 use language::{
     proto::{deserialize_anchor, serialize_anchor},
@@ -11,13 +20,15 @@ use language::{
 };
 use rpc::{proto, AnyProtoClient, TypedEnvelope};
 use settings::{watch_config_file, SettingsLocation};
-use task::{TaskContext, TaskVariables, VariableName};
+use task::{
+    DependsOrder, ResolvedTask, TaskContext, TaskId, TaskInput, TaskVariables, VariableName,
+};
 use text::{BufferId, OffsetRangeExt};
 use util::ResultExt;
 
 use crate::{
     buffer_store::BufferStore, worktree_store::WorktreeStore, BasicContextProvider, Inventory,
-    ProjectEnvironment,
+    ProjectEnvironment, TaskSourceKind,
 };
 
 #[allow(clippy::large_enum_variant)] // platform-dependent warning
@@ -32,6 +43,7 @@ pub struct StoreState {
     buffer_store: WeakEntity<BufferStore>,
     worktree_store: Entity<WorktreeStore>,
     toolchain_store: Arc<dyn LanguageToolchainStore>,
+    pending_task_completions: HashMap<TaskId, Vec<oneshot::Sender<bool>>>,
     _global_task_config_watcher: Task<()>,
 }
 
@@ -46,6 +58,140 @@ enum StoreMode {
     },
 }
 
+/// A resolved task along with the resolved tasks it depends on, see [`Inventory::task_graph`].
+#[derive(Debug, Clone)]
+pub struct TaskGraph {
+    pub task_source_kind: TaskSourceKind,
+    pub task: ResolvedTask,
+    pub dependencies: Vec<TaskGraph>,
+}
+
+/// The runs of the tasks in a [`TaskGraph`] that have been started, by task id.
+/// A failed run resolves to its error message.
+type TaskRuns = RefCell<HashMap<TaskId, Shared<LocalBoxFuture<'static, Result<(), String>>>>>;
+
+impl TaskGraph {
+    /// Inputs of all tasks in the graph that the user has not provided the values for yet,
+    /// each input id listed once, so that the user is asked for all of them before anything runs.
+    pub fn missing_inputs(&self) -> Vec<TaskInput> {
+        let mut missing_inputs = Vec::new();
+        self.collect_missing_inputs(&mut missing_inputs);
+        missing_inputs
+    }
+
+    fn collect_missing_inputs(&self, missing_inputs: &mut Vec<TaskInput>) {
+        for input in self.task.missing_inputs() {
+            if !missing_inputs
+                .iter()
+                .any(|missing_input| missing_input.id == input.id)
+            {
+                missing_inputs.push(input.clone());
+            }
+        }
+        for dependency in &self.dependencies {
+            dependency.collect_missing_inputs(missing_inputs);
+        }
+    }
+
+    /// Resolves every task in the graph that declares some of the inputs given again, with their values,
+    /// see [`ResolvedTask::with_inputs`].
+    pub fn with_inputs(self, input_values: &[(String, String)]) -> Option<Self> {
+        let task_input_values = input_values
+            .iter()
+            .filter(|(input_id, _)| {
+                self.task
+                    .missing_inputs()
+                    .any(|input| &input.id == input_id)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let task = if task_input_values.is_empty() {
+            self.task
+        } else {
+            self.task
+                .with_inputs(&self.task_source_kind.to_id_base(), task_input_values)?
+        };
+        let dependencies = self
+            .dependencies
+            .into_iter()
+            .map(|dependency| dependency.with_inputs(input_values))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            task_source_kind: self.task_source_kind,
+            task,
+            dependencies,
+        })
+    }
+
+    /// Runs the dependencies in the task's [`DependsOrder`], then the task itself, if it has a command.
+    /// `spawn` resolves to whether the spawned task succeeded; nothing else is spawned after a failure.
+    /// A task that several tasks in the graph depend on is run only once.
+    pub fn run(
+        self,
+        spawn: Rc<dyn Fn(ResolvedTask) -> LocalBoxFuture<'static, bool>>,
+    ) -> LocalBoxFuture<'static, Result<()>> {
+        let runs = Rc::new(TaskRuns::default());
+        let run = self.run_shared(spawn, Rc::downgrade(&runs));
+        async move {
+            // The runs refer back to the map weakly, so dropping this future drops all of them.
+            let _runs = runs;
+            run.await
+        }
+        .boxed_local()
+    }
+
+    fn run_shared(
+        self,
+        spawn: Rc<dyn Fn(ResolvedTask) -> LocalBoxFuture<'static, bool>>,
+        runs: Weak<TaskRuns>,
+    ) -> LocalBoxFuture<'static, Result<()>> {
+        let Some(task_runs) = runs.upgrade() else {
+            return future::ready(Err(anyhow!("Task graph run was cancelled"))).boxed_local();
+        };
+        let run = task_runs
+            .borrow_mut()
+            .entry(self.task.id.clone())
+            .or_insert_with(|| {
+                self.run_dependencies_and_task(spawn, runs.clone())
+                    .map(|result| result.map_err(|error| format!("{error:#}")))
+                    .boxed_local()
+                    .shared()
+            })
+            .clone();
+        async move { run.await.map_err(|error| anyhow!(error)) }.boxed_local()
+    }
+
+    fn run_dependencies_and_task(
+        self,
+        spawn: Rc<dyn Fn(ResolvedTask) -> LocalBoxFuture<'static, bool>>,
+        runs: Weak<TaskRuns>,
+    ) -> LocalBoxFuture<'static, Result<()>> {
+        async move {
+            match self.task.original_task().depends_order {
+                DependsOrder::Sequence => {
+                    for dependency in self.dependencies {
+                        dependency.run_shared(spawn.clone(), runs.clone()).await?;
+                    }
+                }
+                DependsOrder::Parallel => {
+                    future::try_join_all(
+                        self.dependencies
+                            .into_iter()
+                            .map(|dependency| dependency.run_shared(spawn.clone(), runs.clone())),
+                    )
+                    .await?;
+                }
+            }
+            if self.task.has_command() {
+                let label = self.task.resolved_label.clone();
+                anyhow::ensure!(spawn(self.task).await, "Task `{label}` failed");
+            }
+            Ok(())
+        }
+        .boxed_local()
+    }
+}
+
 impl EventEmitter<crate::Event> for TaskStore {}
 
 impl TaskStore {
@@ -168,6 +314,7 @@ impl TaskStore {
             buffer_store,
             toolchain_store,
             worktree_store,
+            pending_task_completions: HashMap::default(),
             _global_task_config_watcher: Self::subscribe_to_global_task_file_changes(fs, cx),
         })
     }
@@ -190,6 +337,7 @@ impl TaskStore {
             buffer_store,
             toolchain_store,
             worktree_store,
+            pending_task_completions: HashMap::default(),
             _global_task_config_watcher: Self::subscribe_to_global_task_file_changes(fs, cx),
         })
     }
@@ -234,6 +382,30 @@ impl TaskStore {
         }
     }
 
+    /// Returns a receiver, resolving to whether the next terminal spawned for the task given finishes successfully.
+    pub fn task_completion(&mut self, task_id: TaskId) -> oneshot::Receiver<bool> {
+        let (completion_tx, completion_rx) = oneshot::channel();
+        if let TaskStore::Functional(state) = self {
+            state
+                .pending_task_completions
+                .entry(task_id)
+                .or_default()
+                .push(completion_tx);
+        }
+        completion_rx
+    }
+
+    /// Takes the completion senders for the task, whose terminal was just spawned.
+    pub(crate) fn task_spawned(&mut self, task_id: &TaskId) -> Vec<oneshot::Sender<bool>> {
+        match self {
+            TaskStore::Functional(state) => state
+                .pending_task_completions
+                .remove(task_id)
+                .unwrap_or_default(),
+            TaskStore::Noop => Vec::new(),
+        }
+    }
+
     pub fn shared(&mut self, remote_id: u64, new_downstream_client: AnyProtoClient, _cx: &mut App) {
         if let Self::Functional(StoreState {
             mode: StoreMode::Local {
@@ -13,7 +13,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use task::{Shell, ShellBuilder, SpawnInTerminal};
+use task::{ProblemMatcher, Shell, ShellBuilder, SpawnInTerminal};
 use terminal::{
     terminal_settings::{self, TerminalSettings, VenvSettings},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
@@ -230,6 +230,18 @@ impl Project {
 
         let mut python_venv_activate_command = None;
 
+        let task_problem_matchers = match &kind {
+            TerminalKind::Task(spawn_task)
+                if this.is_local() && !spawn_task.problem_matchers.is_empty() =>
+            {
+                Some((
+                    spawn_task.full_label.clone(),
+                    spawn_task.problem_matchers.clone(),
+                ))
+            }
+            _ => None,
+        };
+
         let (spawn_task, shell) = match kind {
             TerminalKind::Shell(_) => {
                 if let Some(python_venv_directory) = &python_venv_directory {
@@ -260,7 +272,13 @@ impl Project {
                             },
                         )
                     }
-                    None => (None, settings.shell.clone()),
+                    None => {
+                        let mut shell = settings.shell.clone();
+                        if settings.shell_integration {
+                            terminal::shell_integration::inject(&mut shell, &mut env).log_err();
+                        }
+                        (None, shell)
+                    }
                 }
             }
             TerminalKind::Task(spawn_task) => {
@@ -362,6 +380,40 @@ impl Project {
             if let Some(activate_command) = python_venv_activate_command {
                 this.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
+
+            if let Some((task_label, problem_matchers)) = task_problem_matchers {
+                this.report_task_problems(
+                    task_label,
+                    problem_matchers,
+                    path.clone(),
+                    &terminal_handle,
+                    cx,
+                );
+            }
+
+            if let Some(task_id) = terminal_handle.read(cx).task().map(|task| task.id.clone()) {
+                let completion_txs = this
+                    .task_store
+                    .update(cx, |task_store, _| task_store.task_spawned(&task_id));
+                if !completion_txs.is_empty() {
+                    let task_completed = terminal_handle.read(cx).wait_for_completed_task(cx);
+                    let terminal = terminal_handle.downgrade();
+                    cx.spawn(move |_, cx| async move {
+                        task_completed.await;
+                        let success = terminal
+                            .read_with(&cx, |terminal, _| {
+                                terminal.task().is_some_and(|task| {
+                                    task.status == TaskStatus::Completed { success: true }
+                                })
+                            })
+                            .unwrap_or(false);
+                        for completion_tx in completion_txs {
+                            completion_tx.send(success).ok();
+                        }
+                    })
+                    .detach();
+                }
+            }
             terminal_handle
         })
     }
@@ -500,6 +552,63 @@ impl Project {
         ))
     }
 
+    /// Clears the diagnostics of the task's previous run, and once the task completes,
+    /// reports the problems its problem matchers find in the output.
+    fn report_task_problems(
+        &mut self,
+        task_label: String,
+        problem_matchers: Vec<ProblemMatcher>,
+        cwd: Option<Arc<Path>>,
+        terminal_handle: &Entity<Terminal>,
+        cx: &mut Context<Project>,
+    ) {
+        let lsp_store = self.lsp_store.clone();
+        lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .update_task_diagnostics(&task_label, Vec::new(), cx)
+                .log_err();
+        });
+
+        let task_completed = terminal_handle.read(cx).wait_for_completed_task(cx);
+        let terminal = terminal_handle.downgrade();
+        cx.spawn(move |_, mut cx| async move {
+            task_completed.await;
+            let Some(output) = terminal.read_with(&cx, |terminal, _| terminal.task_output())?
+            else {
+                return Ok(());
+            };
+            let problems = cx
+                .background_executor()
+                .spawn(async move {
+                    problem_matchers
+                        .iter()
+                        .flat_map(|problem_matcher| {
+                            let base_directory = match &problem_matcher.base_directory {
+                                Some(base_directory) => Some(match &cwd {
+                                    Some(cwd) => cwd.join(base_directory),
+                                    None => PathBuf::from(base_directory),
+                                }),
+                                None => cwd.as_deref().map(Path::to_path_buf),
+                            };
+                            problem_matcher.find_problems(&output).into_iter().map(
+                                move |mut problem| {
+                                    if let Some(base_directory) = &base_directory {
+                                        problem.path = base_directory.join(&problem.path);
+                                    }
+                                    (problem_matcher.source.clone(), problem)
+                                },
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            lsp_store.update(&mut cx, |lsp_store, cx| {
+                lsp_store.update_task_diagnostics(&task_label, problems, cx)
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn activate_python_virtual_environment(
         &self,
         command: String,
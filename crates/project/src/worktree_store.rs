@@ -770,13 +770,20 @@ impl WorktreeStore {
             results.sort_by(|(a_path, _), (b_path, _)| a_path.cmp(b_path));
             for (path, is_file) in results {
                 if is_file {
+                    let project_path = ProjectPath {
+                        worktree_id: snapshot.id(),
+                        path: Arc::from(path),
+                    };
+                    if !query.path_in_scope(&project_path) {
+                        continue;
+                    }
                     if query.filters_path() {
                         let matched_path = if include_root {
                             let mut full_path = PathBuf::from(snapshot.root_name());
-                            full_path.push(&path);
+                            full_path.push(&project_path.path);
                             query.file_matches(&full_path)
                         } else {
-                            query.file_matches(&path)
+                            query.file_matches(&project_path.path)
                         };
                         if !matched_path {
                             continue;
@@ -788,10 +795,7 @@ impl WorktreeStore {
                         .send(MatchingEntry {
                             respond: tx,
                             worktree_path: snapshot.abs_path().clone(),
-                            path: ProjectPath {
-                                worktree_id: snapshot.id(),
-                                path: Arc::from(path),
-                            },
+                            path: project_path,
                         })
                         .await?;
                 } else {
@@ -843,6 +847,13 @@ impl WorktreeStore {
                     continue;
                 }
 
+                if !query.path_in_scope(&ProjectPath {
+                    worktree_id: snapshot.id(),
+                    path: entry.path.clone(),
+                }) {
+                    continue;
+                }
+
                 if query.filters_path() {
                     let matched_path = if include_root {
                         let mut full_path = PathBuf::from(snapshot.root_name());
@@ -939,6 +950,17 @@ impl WorktreeStore {
                             unix_timestamp: proto_branch
                                 .unix_timestamp
                                 .map(|timestamp| timestamp as i64),
+                            upstream: proto_branch.upstream.map(|upstream| {
+                                git::repository::Upstream {
+                                    ref_name: upstream.ref_name.into(),
+                                    tracking: upstream.tracking.map(|tracking| {
+                                        git::repository::UpstreamTrackingStatus {
+                                            ahead: tracking.ahead as u32,
+                                            behind: tracking.behind as u32,
+                                        }
+                                    }),
+                                }
+                            }),
                         })
                         .collect();
 
@@ -1130,6 +1152,13 @@ impl WorktreeStore {
                     is_head: branch.is_head,
                     name: branch.name.to_string(),
                     unix_timestamp: branch.unix_timestamp.map(|timestamp| timestamp as u64),
+                    upstream: branch.upstream.map(|upstream| proto::GitUpstream {
+                        ref_name: upstream.ref_name.to_string(),
+                        tracking: upstream.tracking.map(|tracking| proto::UpstreamTracking {
+                            ahead: tracking.ahead as u64,
+                            behind: tracking.behind as u64,
+                        }),
+                    }),
                 })
                 .collect(),
         })
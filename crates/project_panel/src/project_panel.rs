@@ -28,8 +28,8 @@ use indexmap::IndexMap;
 use language::DiagnosticSeverity;
 use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrev};
 use project::{
-    relativize_path, Entry, EntryKind, Fs, Project, ProjectEntryId, ProjectPath, Worktree,
-    WorktreeId,
+    relativize_path, search::SearchScope, Entry, EntryKind, Fs, Project, ProjectEntryId,
+    ProjectPath, Worktree, WorktreeId,
 };
 use project_panel_settings::{
     ProjectPanelDockPosition, ProjectPanelSettings, ShowDiagnostics, ShowIndentGuides,
@@ -197,6 +197,7 @@ actions!(
         OpenPermanent,
         ToggleFocus,
         NewSearchInDirectory,
+        NewScopedSearchInDirectory,
         UnfoldDirectory,
         FoldDirectory,
         SelectParent,
@@ -206,6 +207,7 @@ actions!(
         SelectPrevDiagnostic,
         SelectNextDirectory,
         SelectPrevDirectory,
+        ViewFileHistory,
     ]
 );
 
@@ -690,6 +692,10 @@ impl ProjectPanel {
                     if is_read_only {
                         menu.when(is_dir, |menu| {
                             menu.action("Search Inside", Box::new(NewSearchInDirectory))
+                                .action(
+                                    "Search Inside (Keep Filters)",
+                                    Box::new(NewScopedSearchInDirectory),
+                                )
                         })
                     } else {
                         menu.action("New File", Box::new(NewFile))
@@ -705,9 +711,14 @@ impl ProjectPanel {
                                 menu.action("Open in Default App", Box::new(OpenWithSystem))
                             })
                             .action("Open in Terminal", Box::new(OpenInTerminal))
+                            .action("View File History", Box::new(ViewFileHistory))
                             .when(is_dir, |menu| {
                                 menu.separator()
                                     .action("Find in Folder…", Box::new(NewSearchInDirectory))
+                                    .action(
+                                        "Find in Folder (Keep Filters)…",
+                                        Box::new(NewScopedSearchInDirectory),
+                                    )
                             })
                             .when(is_unfoldable, |menu| {
                                 menu.action("Unfold Directory", Box::new(UnfoldDirectory))
@@ -2224,6 +2235,19 @@ impl ProjectPanel {
         }
     }
 
+    fn view_file_history(
+        &mut self,
+        _: &ViewFileHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some((worktree, entry)) = self.selected_sub_entry(cx) {
+            if let Ok(path) = worktree.read(cx).absolutize(&entry.path) {
+                window.dispatch_action(git::OpenFileHistory { path }.boxed_clone(), cx);
+            }
+        }
+    }
+
     fn open_in_terminal(
         &mut self,
         _: &OpenInTerminal,
@@ -2299,6 +2323,41 @@ impl ProjectPanel {
         }
     }
 
+    /// Unlike [`Self::new_search_in_directory`], limits the search to the folder through its
+    /// scope, leaving the include and exclude filters untouched.
+    fn new_scoped_search_in_directory(
+        &mut self,
+        _: &NewScopedSearchInDirectory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some((worktree, entry)) = self.selected_sub_entry(cx) {
+            let path = if entry.is_dir() {
+                entry.path.clone()
+            } else {
+                entry
+                    .path
+                    .parent()
+                    .map_or_else(|| Arc::from(Path::new("")), Arc::from)
+            };
+            let project_path = ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path,
+            };
+
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    search::ProjectSearchView::new_search_in_scope(
+                        workspace,
+                        SearchScope::Directory(project_path),
+                        window,
+                        cx,
+                    );
+                })
+                .ok();
+        }
+    }
+
     fn move_entry(
         &mut self,
         entry_to_move: ProjectEntryId,
@@ -4419,6 +4478,8 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::copy_path))
                 .on_action(cx.listener(Self::copy_relative_path))
                 .on_action(cx.listener(Self::new_search_in_directory))
+                .on_action(cx.listener(Self::new_scoped_search_in_directory))
+                .on_action(cx.listener(Self::view_file_history))
                 .on_action(cx.listener(Self::unfold_directory))
                 .on_action(cx.listener(Self::fold_directory))
                 .on_action(cx.listener(Self::remove_from_project))
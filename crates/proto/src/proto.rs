@@ -379,6 +379,53 @@ messages!(
     (SyncExtensionsResponse, Background),
     (InstallExtension, Background),
     (RegisterBufferWithLanguageServers, Background),
+    (GetRemotes, Background),
+    (GetRemotesResponse, Background),
+    (Fetch, Background),
+    (Pull, Background),
+    (Push, Background),
+    (RemoteMessageResponse, Background),
+    (AskPassRequest, Background),
+    (AskPassResponse, Background),
+    (GitRemoteProgress, Background),
+    (Stash, Background),
+    (StashApply, Background),
+    (StashPop, Background),
+    (StashDrop, Background),
+    (GetStashEntries, Background),
+    (GetStashEntriesResponse, Background),
+    (GetStashDiff, Background),
+    (GetStashDiffResponse, Background),
+    (GetCommitLog, Background),
+    (GetCommitLogResponse, Background),
+    (GetCommitDiff, Background),
+    (GetCommitDiffResponse, Background),
+    (GetChangedPathsSince, Background),
+    (GetChangedPathsSinceResponse, Background),
+    (StageLines, Background),
+    (UnstageLines, Background),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetIncomingCallsResponse, Background),
+    (GetOutgoingCalls, Background),
+    (GetOutgoingCallsResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
+    (GetSupertypes, Background),
+    (GetSupertypesResponse, Background),
+    (GetSubtypes, Background),
+    (GetSubtypesResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (ApplyCodeLens, Background),
+    (ApplyCodeLensResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetSelectionRanges, Background),
+    (GetSelectionRangesResponse, Background),
 );
 
 request_messages!(
@@ -510,6 +557,33 @@ request_messages!(
     (SyncExtensions, SyncExtensionsResponse),
     (InstallExtension, Ack),
     (RegisterBufferWithLanguageServers, Ack),
+    (GetRemotes, GetRemotesResponse),
+    (Fetch, RemoteMessageResponse),
+    (Pull, RemoteMessageResponse),
+    (Push, RemoteMessageResponse),
+    (AskPassRequest, AskPassResponse),
+    (Stash, Ack),
+    (StashApply, Ack),
+    (StashPop, Ack),
+    (StashDrop, Ack),
+    (GetStashEntries, GetStashEntriesResponse),
+    (GetStashDiff, GetStashDiffResponse),
+    (GetCommitLog, GetCommitLogResponse),
+    (GetCommitDiff, GetCommitDiffResponse),
+    (GetChangedPathsSince, GetChangedPathsSinceResponse),
+    (StageLines, Ack),
+    (UnstageLines, Ack),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (GetIncomingCalls, GetIncomingCallsResponse),
+    (GetOutgoingCalls, GetOutgoingCallsResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
+    (GetSupertypes, GetSupertypesResponse),
+    (GetSubtypes, GetSubtypesResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (ApplyCodeLens, ApplyCodeLensResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetSelectionRanges, GetSelectionRangesResponse),
 );
 
 entity_messages!(
@@ -600,6 +674,34 @@ entity_messages!(
     GetPathMetadata,
     CancelLanguageServerWork,
     RegisterBufferWithLanguageServers,
+    GetRemotes,
+    Fetch,
+    Pull,
+    Push,
+    AskPassRequest,
+    GitRemoteProgress,
+    Stash,
+    StashApply,
+    StashPop,
+    StashDrop,
+    GetStashEntries,
+    GetStashDiff,
+    GetCommitLog,
+    GetCommitDiff,
+    GetChangedPathsSince,
+    StageLines,
+    UnstageLines,
+    PrepareCallHierarchy,
+    GetIncomingCalls,
+    GetOutgoingCalls,
+    PrepareTypeHierarchy,
+    GetSupertypes,
+    GetSubtypes,
+    GetSemanticTokens,
+    GetCodeLens,
+    ApplyCodeLens,
+    GetFoldingRanges,
+    GetSelectionRanges,
 );
 
 entity_messages!(
@@ -3,14 +3,20 @@ use extension::ExtensionHostProxy;
 use extension_host::headless_host::HeadlessExtensionStore;
 use fs::Fs;
 use futures::channel::mpsc;
-use git::repository::RepoPath;
+use git::{
+    commit::LogOptions,
+    repository::{PushOptions, RepoPath},
+};
 use gpui::{App, AppContext as _, AsyncApp, Context, Entity, PromptLevel, SharedString};
 use http_client::HttpClient;
 use language::{proto::serialize_operation, Buffer, BufferEvent, LanguageRegistry};
 use node_runtime::NodeRuntime;
 use project::{
     buffer_store::{BufferStore, BufferStoreEvent},
-    git::GitState,
+    git::{
+        commit_file_diff_to_proto, commit_summary_to_proto, remote_command_output_to_proto,
+        stash_entry_to_proto, GitState, ProtoGitRemoteDelegate, RepositoryHandle,
+    },
     project_settings::SettingsObserver,
     search::SearchQuery,
     task_store::TaskStore,
@@ -201,6 +207,21 @@ impl HeadlessProject {
         client.add_model_request_handler(Self::handle_stage);
         client.add_model_request_handler(Self::handle_unstage);
         client.add_model_request_handler(Self::handle_commit);
+        client.add_model_request_handler(Self::handle_get_remotes);
+        client.add_model_request_handler(Self::handle_fetch);
+        client.add_model_request_handler(Self::handle_pull);
+        client.add_model_request_handler(Self::handle_push);
+        client.add_model_request_handler(Self::handle_stash);
+        client.add_model_request_handler(Self::handle_stash_apply);
+        client.add_model_request_handler(Self::handle_stash_pop);
+        client.add_model_request_handler(Self::handle_stash_drop);
+        client.add_model_request_handler(Self::handle_get_stash_entries);
+        client.add_model_request_handler(Self::handle_get_stash_diff);
+        client.add_model_request_handler(Self::handle_get_commit_log);
+        client.add_model_request_handler(Self::handle_get_commit_diff);
+        client.add_model_request_handler(Self::handle_get_changed_paths_since);
+        client.add_model_request_handler(Self::handle_stage_lines);
+        client.add_model_request_handler(Self::handle_unstage_lines);
 
         client.add_request_handler(
             extensions.clone().downgrade(),
@@ -733,6 +754,405 @@ impl HeadlessProject {
             Ok(proto::Ack {})
         }
     }
+
+    async fn handle_get_remotes(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetRemotes>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetRemotesResponse> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let branch_name = envelope.payload.branch_name.map(SharedString::from);
+        let remotes = repository_handle
+            .get_remotes(branch_name)
+            .await
+            .context("get remotes operation was canceled")??;
+        Ok(proto::GetRemotesResponse {
+            remotes: remotes
+                .into_iter()
+                .map(|remote| proto::get_remotes_response::Remote {
+                    name: remote.name.to_string(),
+                })
+                .collect(),
+        })
+    }
+
+    async fn handle_fetch(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::Fetch>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::RemoteMessageResponse> {
+        let (repository_handle, session) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let delegate = Arc::new(ProtoGitRemoteDelegate {
+            client: session,
+            project_id: SSH_PROJECT_ID,
+            worktree_id: repository_handle.worktree_id,
+            work_directory_id: repository_handle.repository_entry.work_directory_id(),
+            askpass_id: envelope.payload.askpass_id,
+            peer_id: None,
+        });
+        let output = repository_handle
+            .fetch(delegate)
+            .await
+            .context("fetch operation was canceled")??;
+        Ok(remote_command_output_to_proto(output))
+    }
+
+    async fn handle_pull(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::Pull>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::RemoteMessageResponse> {
+        let (repository_handle, session) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let delegate = Arc::new(ProtoGitRemoteDelegate {
+            client: session,
+            project_id: SSH_PROJECT_ID,
+            worktree_id: repository_handle.worktree_id,
+            work_directory_id: repository_handle.repository_entry.work_directory_id(),
+            askpass_id: envelope.payload.askpass_id,
+            peer_id: None,
+        });
+        let output = repository_handle
+            .pull(
+                envelope.payload.branch_name.into(),
+                envelope.payload.remote_name.into(),
+                delegate,
+            )
+            .await
+            .context("pull operation was canceled")??;
+        Ok(remote_command_output_to_proto(output))
+    }
+
+    async fn handle_push(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::Push>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::RemoteMessageResponse> {
+        let (repository_handle, session) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let options = envelope
+            .payload
+            .options
+            .and_then(proto::push::PushOptions::from_i32)
+            .map(|options| match options {
+                proto::push::PushOptions::SetUpstream => PushOptions::SetUpstream,
+                proto::push::PushOptions::Force => PushOptions::Force,
+                proto::push::PushOptions::ForceSetUpstream => PushOptions::ForceSetUpstream,
+            });
+        let delegate = Arc::new(ProtoGitRemoteDelegate {
+            client: session,
+            project_id: SSH_PROJECT_ID,
+            worktree_id: repository_handle.worktree_id,
+            work_directory_id: repository_handle.repository_entry.work_directory_id(),
+            askpass_id: envelope.payload.askpass_id,
+            peer_id: None,
+        });
+        let output = repository_handle
+            .push(
+                envelope.payload.branch_name.into(),
+                envelope.payload.remote_name.into(),
+                options,
+                delegate,
+            )
+            .await
+            .context("push operation was canceled")??;
+        Ok(remote_command_output_to_proto(output))
+    }
+
+    async fn handle_stash(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::Stash>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        repository_handle
+            .stash_all(envelope.payload.message.map(SharedString::from))
+            .await
+            .context("stash operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_apply(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::StashApply>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        repository_handle
+            .stash_apply(
+                envelope.payload.index as usize,
+                envelope.payload.sha.parse()?,
+            )
+            .await
+            .context("stash apply operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_pop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::StashPop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        repository_handle
+            .stash_pop(
+                envelope.payload.index as usize,
+                envelope.payload.sha.parse()?,
+            )
+            .await
+            .context("stash pop operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_drop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::StashDrop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        repository_handle
+            .stash_drop(
+                envelope.payload.index as usize,
+                envelope.payload.sha.parse()?,
+            )
+            .await
+            .context("stash drop operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_get_stash_entries(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetStashEntries>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetStashEntriesResponse> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let entries = repository_handle
+            .stash_entries()
+            .await
+            .context("list stash operation was canceled")??;
+        Ok(proto::GetStashEntriesResponse {
+            entries: entries.iter().map(stash_entry_to_proto).collect(),
+        })
+    }
+
+    async fn handle_get_stash_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetStashDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetStashDiffResponse> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let files = repository_handle
+            .stash_diff(
+                envelope.payload.index as usize,
+                envelope.payload.sha.parse()?,
+            )
+            .await
+            .context("stash diff operation was canceled")??;
+        Ok(proto::GetStashDiffResponse {
+            files: files.into_iter().map(commit_file_diff_to_proto).collect(),
+        })
+    }
+
+    async fn handle_get_commit_log(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetCommitLog>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetCommitLogResponse> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let options = LogOptions {
+            path: envelope.payload.path.as_deref().map(RepoPath::from_str),
+            author: envelope.payload.author,
+            skip: envelope.payload.skip as usize,
+            limit: envelope.payload.limit as usize,
+        };
+        let commits = repository_handle
+            .log(options)
+            .await
+            .context("log operation was canceled")??;
+        Ok(proto::GetCommitLogResponse {
+            commits: commits.iter().map(commit_summary_to_proto).collect(),
+        })
+    }
+
+    async fn handle_get_commit_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetCommitDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetCommitDiffResponse> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let files = repository_handle
+            .commit_diff(envelope.payload.sha.into())
+            .await
+            .context("commit diff operation was canceled")??;
+        Ok(proto::GetCommitDiffResponse {
+            files: files.into_iter().map(commit_file_diff_to_proto).collect(),
+        })
+    }
+
+    async fn handle_get_changed_paths_since(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetChangedPathsSince>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetChangedPathsSinceResponse> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let paths = repository_handle
+            .changed_paths_since(envelope.payload.base.into())
+            .await
+            .context("changed paths operation was canceled")??;
+        Ok(proto::GetChangedPathsSinceResponse {
+            paths: paths.iter().map(|path| path.to_proto()).collect(),
+        })
+    }
+
+    async fn handle_stage_lines(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::StageLines>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let rows = envelope
+            .payload
+            .rows
+            .into_iter()
+            .map(|rows| rows.start as u32..rows.end as u32)
+            .collect();
+        repository_handle
+            .stage_lines(
+                RepoPath::from_str(&envelope.payload.path),
+                envelope.payload.buffer_text,
+                rows,
+            )
+            .await
+            .context("stage lines operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_unstage_lines(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UnstageLines>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let (repository_handle, _) = Self::repository_for_request(
+            &this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            &mut cx,
+        )?;
+
+        let rows = envelope
+            .payload
+            .rows
+            .into_iter()
+            .map(|rows| rows.start as u32..rows.end as u32)
+            .collect();
+        repository_handle
+            .unstage_lines(
+                RepoPath::from_str(&envelope.payload.path),
+                envelope.payload.buffer_text,
+                rows,
+                envelope.payload.whole_hunks,
+            )
+            .await
+            .context("unstage lines operation was canceled")??;
+        Ok(proto::Ack {})
+    }
+
+    fn repository_for_request(
+        this: &Entity<Self>,
+        worktree_id: u64,
+        work_directory_id: u64,
+        cx: &mut AsyncApp,
+    ) -> Result<(RepositoryHandle, AnyProtoClient)> {
+        this.update(cx, |project, cx| {
+            let repository_handle = project.git_state.read(cx).repository_for_proto(
+                WorktreeId::from_proto(worktree_id),
+                ProjectEntryId::from_proto(work_directory_id),
+            )?;
+            anyhow::Ok((repository_handle, project.session.clone()))
+        })?
+    }
 }
 
 fn prompt_to_proto(
@@ -8,6 +8,7 @@ use http_client::{BlockedHttpClient, FakeHttpClient};
 use language::{
     language_settings::{language_settings, AllLanguageSettings},
     Buffer, FakeLspAdapter, LanguageConfig, LanguageMatcher, LanguageRegistry, LineEnding,
+    OffsetRangeExt as _,
 };
 use lsp::{CompletionContext, CompletionResponse, CompletionTriggerKind, LanguageServerName};
 use node_runtime::NodeRuntime;
@@ -538,6 +539,159 @@ async fn test_remote_lsp(cx: &mut TestAppContext, server_cx: &mut TestAppContext
     })
 }
 
+#[gpui::test]
+async fn test_remote_call_hierarchy(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                ".git": {},
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }\nfn two() -> usize { one() }"
+                }
+            },
+        }),
+    )
+    .await;
+
+    let (project, headless) = init_test(&fs, cx, server_cx).await;
+
+    cx.update_entity(&project, |project, _| {
+        project.languages().register_test_language(LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".into()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        project.languages().register_fake_lsp_adapter(
+            "Rust",
+            FakeLspAdapter {
+                name: "rust-analyzer",
+                ..Default::default()
+            },
+        )
+    });
+
+    let mut fake_lsp = server_cx.update(|cx| {
+        headless.read(cx).languages.register_fake_language_server(
+            LanguageServerName("rust-analyzer".into()),
+            lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            None,
+        )
+    });
+
+    cx.run_until_parked();
+
+    let worktree_id = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap()
+        .0
+        .read_with(cx, |worktree, _| worktree.id());
+    cx.run_until_parked();
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_buffer_with_lsp((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    let fake_lsp = fake_lsp.next().await.unwrap();
+
+    fn lsp_item(name: &str, line: u32, end_column: u32) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: Some("fn() -> usize".to_string()),
+            uri: lsp::Url::from_file_path("/code/project1/src/lib.rs").unwrap(),
+            range: lsp::Range::new(
+                lsp::Position::new(line, 0),
+                lsp::Position::new(line, end_column),
+            ),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(line, 3),
+                lsp::Position::new(line, 6),
+            ),
+            data: Some(json!({ "id": name })),
+        }
+    }
+    let call_site = lsp::Range::new(lsp::Position::new(1, 20), lsp::Position::new(1, 23));
+    fake_lsp.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(|_, _| async move {
+        Ok(Some(vec![lsp_item("one", 0, 23)]))
+    });
+    fake_lsp.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        move |params, _| async move {
+            // The raw item should reach the server unchanged, including its data.
+            assert_eq!(params.item, lsp_item("one", 0, 23));
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: lsp_item("two", 1, 27),
+                from_ranges: vec![call_site],
+            }]))
+        },
+    );
+    fake_lsp.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item, lsp_item("two", 1, 27));
+            Ok(Some(vec![lsp::CallHierarchyOutgoingCall {
+                to: lsp_item("one", 0, 23),
+                from_ranges: vec![call_site],
+            }]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, 4, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let one = items[0].clone();
+    assert_eq!(one.name, "one");
+    assert_eq!(one.detail.as_deref(), Some("fn() -> usize"));
+    assert_eq!(one.language_server_id, fake_lsp.server.server_id());
+    cx.read(|cx| {
+        let buffer = one.range.buffer.read(cx);
+        assert_eq!(one.range.range.to_offset(buffer), 0..23);
+        assert_eq!(one.selection_range.to_offset(buffer), 3..6);
+    });
+
+    let incoming_calls = project
+        .update(cx, |project, cx| project.incoming_calls(one, cx))
+        .await
+        .unwrap();
+    assert_eq!(incoming_calls.len(), 1);
+    let two = incoming_calls[0].item.clone();
+    assert_eq!(two.name, "two");
+    assert_eq!(two.language_server_id, fake_lsp.server.server_id());
+    cx.read(|cx| {
+        let call_site = &incoming_calls[0].call_sites[0];
+        assert_eq!(call_site.range.to_offset(call_site.buffer.read(cx)), 44..47);
+    });
+
+    let outgoing_calls = project
+        .update(cx, |project, cx| project.outgoing_calls(two, cx))
+        .await
+        .unwrap();
+    assert_eq!(outgoing_calls.len(), 1);
+    assert_eq!(outgoing_calls[0].item.name, "one");
+    cx.read(|cx| {
+        let call_site = &outgoing_calls[0].call_sites[0];
+        assert_eq!(call_site.range.to_offset(call_site.buffer.read(cx)), 44..47);
+    });
+}
+
 #[gpui::test]
 async fn test_remote_cancel_language_server_work(
     cx: &mut TestAppContext,
@@ -98,6 +98,7 @@ pub struct BufferSearchBar {
     search_options: SearchOptions,
     default_options: SearchOptions,
     configured_options: SearchOptions,
+    smartcase_override: Option<bool>,
     query_contains_error: bool,
     dismissed: bool,
     search_history: SearchHistory,
@@ -673,6 +674,7 @@ impl BufferSearchBar {
             default_options: search_options,
             configured_options: search_options,
             search_options,
+            smartcase_override: None,
             pending_search: None,
             query_contains_error: false,
             dismissed: true,
@@ -1410,7 +1412,14 @@ impl BufferSearchBar {
     }
 
     pub fn should_use_smartcase_search(&mut self, cx: &mut Context<Self>) -> bool {
-        EditorSettings::get_global(cx).use_smartcase_search
+        self.smartcase_override
+            .unwrap_or(EditorSettings::get_global(cx).use_smartcase_search)
+    }
+
+    /// Overrides the `use_smartcase_search` setting for this search bar, or
+    /// goes back to the setting when `None`.
+    pub fn set_smartcase_override(&mut self, smartcase: Option<bool>) {
+        self.smartcase_override = smartcase;
     }
 
     pub fn is_contains_uppercase(&mut self, str: &String) -> bool {
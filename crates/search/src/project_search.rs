@@ -1,7 +1,7 @@
 use crate::{
     buffer_search::Deploy, BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery,
     ReplaceAll, ReplaceNext, SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive,
-    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleStructural, ToggleWholeWord,
 };
 use collections::{HashMap, HashSet};
 use editor::{
@@ -10,15 +10,16 @@ use editor::{
 };
 use futures::StreamExt;
 use gpui::{
-    actions, div, Action, AnyElement, AnyView, App, Axis, Context, Entity, EntityId, EventEmitter,
-    FocusHandle, Focusable, Global, Hsla, InteractiveElement, IntoElement, KeyContext,
-    ParentElement, Point, Render, SharedString, Styled, Subscription, Task, TextStyle,
+    actions, div, Action, AnyElement, AnyView, App, Axis, Context, Corner, Entity, EntityId,
+    EventEmitter, FocusHandle, Focusable, Global, Hsla, InteractiveElement, IntoElement,
+    KeyContext, ParentElement, Point, Render, SharedString, Styled, Subscription, Task, TextStyle,
     UpdateGlobal, WeakEntity, Window,
 };
 use language::Buffer;
 use menu::Confirm;
 use project::{
-    search::{SearchInputKind, SearchQuery},
+    git::RepositoryHandle,
+    search::{SearchInputKind, SearchQuery, SearchScope},
     search_history::SearchHistoryCursor,
     Project, ProjectPath,
 };
@@ -29,13 +30,15 @@ use std::{
     ops::{Not, Range},
     path::Path,
     pin::pin,
+    sync::Arc,
 };
 use theme::ThemeSettings;
 use ui::{
-    h_flex, prelude::*, utils::SearchInputWidth, v_flex, Icon, IconButton, IconButtonShape,
-    IconName, KeyBinding, Label, LabelCommon, LabelSize, Toggleable, Tooltip,
+    h_flex, prelude::*, utils::SearchInputWidth, v_flex, ContextMenu, Icon, IconButton,
+    IconButtonShape, IconName, KeyBinding, Label, LabelCommon, LabelSize, PopoverMenu, Toggleable,
+    Tooltip,
 };
-use util::paths::PathMatcher;
+use util::{paths::PathMatcher, ResultExt};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle},
     searchable::{Direction, SearchableItem, SearchableItemHandle},
@@ -80,6 +83,9 @@ pub fn init(cx: &mut App) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, _, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleStructural, _, cx| {
+            search_bar.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+        });
         register_workspace_action(
             workspace,
             move |search_bar, action: &ToggleReplace, window, cx| {
@@ -129,6 +135,14 @@ fn is_contains_uppercase(str: &str) -> bool {
     str.chars().any(|c| c.is_uppercase())
 }
 
+fn repository_project_path(repository: &RepositoryHandle) -> ProjectPath {
+    let work_directory: &Path = &repository.repository_entry;
+    ProjectPath {
+        worktree_id: repository.worktree_id,
+        path: work_directory.into(),
+    }
+}
+
 pub struct ProjectSearch {
     project: Entity<Project>,
     excerpts: Entity<MultiBuffer>,
@@ -166,7 +180,10 @@ pub struct ProjectSearchView {
     excluded_files_editor: Entity<Editor>,
     filters_enabled: bool,
     replace_enabled: bool,
-    included_opened_only: bool,
+    scope: Option<SearchScope>,
+    branch_scopes: Vec<SearchScope>,
+    branches_repository: Option<(ProjectPath, Option<Arc<str>>)>,
+    _load_branches: Task<()>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -234,11 +251,16 @@ impl ProjectSearch {
         }
     }
 
-    fn search(&mut self, query: SearchQuery, cx: &mut Context<Self>) {
-        let search = self.project.update(cx, |project, cx| {
+    fn search(&mut self, query: SearchQuery, scope: Option<SearchScope>, cx: &mut Context<Self>) {
+        let scope_paths = Self::scope_paths(&self.project, scope.as_ref(), cx);
+        self.project.update(cx, |project, _| {
             project
                 .search_history_mut(SearchInputKind::Query)
-                .add(&mut self.search_history_cursor, query.as_str().to_string());
+                .add_with_scope(
+                    &mut self.search_history_cursor,
+                    query.as_str().to_string(),
+                    scope,
+                );
             let included = query.as_inner().files_to_include().sources().join(",");
             if !included.is_empty() {
                 project
@@ -251,15 +273,24 @@ impl ProjectSearch {
                     .search_history_mut(SearchInputKind::Exclude)
                     .add(&mut self.search_excluded_history_cursor, excluded);
             }
-            project.search(query.clone(), cx)
         });
         self.last_search_query_text = Some(query.as_str().to_string());
         self.search_id += 1;
-        self.active_query = Some(query);
+        self.active_query = Some(query.clone());
         self.match_ranges.clear();
         self.pending_search = Some(cx.spawn(|this, mut cx| async move {
-            let mut matches = pin!(search.ready_chunks(1024));
+            let query = match scope_paths.await {
+                Some(scope_paths) => query.with_scope_paths(scope_paths),
+                None => query,
+            };
             let this = this.upgrade()?;
+            let search = this
+                .update(&mut cx, |this, cx| {
+                    this.project
+                        .update(cx, |project, cx| project.search(query, cx))
+                })
+                .ok()?;
+            let mut matches = pin!(search.ready_chunks(1024));
             this.update(&mut cx, |this, cx| {
                 this.match_ranges.clear();
                 this.excerpts.update(cx, |this, cx| this.clear(cx));
@@ -316,6 +347,54 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
+
+    /// Resolves the project paths that a search in the given scope is limited to,
+    /// or `None` when the scope doesn't limit the searched paths.
+    fn scope_paths(
+        project: &Entity<Project>,
+        scope: Option<&SearchScope>,
+        cx: &App,
+    ) -> Task<Option<Vec<ProjectPath>>> {
+        let repositories = project
+            .read(cx)
+            .git_state()
+            .map(|git_state| git_state.read(cx).all_repositories())
+            .unwrap_or_default();
+        match scope {
+            None | Some(SearchScope::OpenBuffers) => Task::ready(None),
+            Some(SearchScope::Directory(project_path)) => {
+                Task::ready(Some(vec![project_path.clone()]))
+            }
+            Some(SearchScope::GitChanges) => Task::ready(Some(
+                repositories
+                    .iter()
+                    .flat_map(|repository| {
+                        repository
+                            .status()
+                            .filter_map(|entry| repository.unrelativize(&entry.repo_path))
+                    })
+                    .collect(),
+            )),
+            Some(SearchScope::ChangedSinceBranch { repository, branch }) => {
+                let Some(repository) = repositories
+                    .into_iter()
+                    .find(|handle| &repository_project_path(handle) == repository)
+                else {
+                    return Task::ready(Some(Vec::new()));
+                };
+                let receiver = repository.changed_paths_since(branch.clone());
+                cx.foreground_executor().spawn(async move {
+                    let paths = receiver.await.ok()?.log_err().unwrap_or_default();
+                    Some(
+                        paths
+                            .iter()
+                            .filter_map(|path| repository.unrelativize(path))
+                            .collect(),
+                    )
+                })
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -607,6 +686,14 @@ impl ProjectSearchView {
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut Context<Self>) {
         self.search_options.toggle(option);
+        // A query is either a regex or a structural pattern, never both.
+        if self.search_options.contains(option) {
+            if option == SearchOptions::REGEX {
+                self.search_options.remove(SearchOptions::STRUCTURAL);
+            } else if option == SearchOptions::STRUCTURAL {
+                self.search_options.remove(SearchOptions::REGEX);
+            }
+        }
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.model.read(cx).project.downgrade(),
@@ -615,8 +702,68 @@ impl ProjectSearchView {
         });
     }
 
-    fn toggle_opened_only(&mut self, _window: &mut Window, _cx: &mut Context<Self>) {
-        self.included_opened_only = !self.included_opened_only;
+    fn set_scope(&mut self, scope: Option<SearchScope>, cx: &mut Context<Self>) {
+        if self.scope != scope {
+            self.scope = scope;
+            if self.model.read(cx).active_query.is_some() {
+                self.search(cx);
+            }
+            cx.notify();
+        }
+    }
+
+    fn scope_label(&self, cx: &App) -> SharedString {
+        self.label_for_scope(self.scope.as_ref(), cx)
+    }
+
+    fn label_for_scope(&self, scope: Option<&SearchScope>, cx: &App) -> SharedString {
+        match scope {
+            None => "Whole Project".into(),
+            Some(SearchScope::OpenBuffers) => "Open Files".into(),
+            Some(SearchScope::GitChanges) => "Uncommitted Changes".into(),
+            Some(SearchScope::ChangedSinceBranch { repository, branch }) => {
+                let project = self.model.read(cx).project.read(cx);
+                let repository_count = project
+                    .git_state()
+                    .map_or(0, |git_state| git_state.read(cx).all_repositories().len());
+                if repository_count > 1 {
+                    format!(
+                        "Changed Since {branch} in {}",
+                        self.project_path_label(repository, cx)
+                    )
+                    .into()
+                } else {
+                    format!("Changed Since {branch}").into()
+                }
+            }
+            Some(SearchScope::Directory(project_path)) => self.project_path_label(project_path, cx),
+        }
+    }
+
+    fn project_path_label(&self, project_path: &ProjectPath, cx: &App) -> SharedString {
+        let project = self.model.read(cx).project.read(cx);
+        let show_root_name =
+            project_path.path.as_os_str().is_empty() || project.visible_worktrees(cx).count() > 1;
+        match project.worktree_for_id(project_path.worktree_id, cx) {
+            Some(worktree) if show_root_name => Path::new(worktree.read(cx).root_name())
+                .join(&project_path.path)
+                .to_string_lossy()
+                .into_owned()
+                .into(),
+            _ => project_path.path.to_string_lossy().into_owned().into(),
+        }
+    }
+
+    /// Restores the scope that the currently selected query history entry was searched in.
+    fn restore_history_scope(&mut self, cx: &mut Context<Self>) {
+        let model = self.model.read(cx);
+        self.scope = model
+            .project
+            .read(cx)
+            .search_history(SearchInputKind::Query)
+            .scope(model.cursor(SearchInputKind::Query))
+            .cloned();
+        cx.notify();
     }
 
     fn replace_next(&mut self, _: &ReplaceNext, window: &mut Window, cx: &mut Context<Self>) {
@@ -636,6 +783,7 @@ impl ProjectSearchView {
             self.results_editor.update(cx, |editor, cx| {
                 editor.replace(&mat, &query, window, cx);
             });
+            self.update_replacement_previews(cx);
             self.select_match(Direction::Next, window, cx)
         }
     }
@@ -666,6 +814,23 @@ impl ProjectSearchView {
         self.model.update(cx, |model, _cx| {
             model.match_ranges = match_ranges;
         });
+        self.update_replacement_previews(cx);
+    }
+
+    /// Structural rewrites depend on the syntax around each match, so while replacing is
+    /// enabled, the results show what each match would become.
+    fn update_replacement_previews(&mut self, cx: &mut Context<Self>) {
+        let query = self
+            .model
+            .read(cx)
+            .active_query
+            .clone()
+            .filter(|query| self.replace_enabled && query.is_structural())
+            .map(|query| query.with_replacement(self.replacement(cx)));
+        let match_ranges = self.model.read(cx).match_ranges.clone();
+        self.results_editor.update(cx, |editor, cx| {
+            editor.show_replacement_previews(&match_ranges, query.as_ref(), cx);
+        });
     }
 
     pub fn new(
@@ -735,6 +900,14 @@ impl ProjectSearchView {
             }
             editor
         });
+        subscriptions.push(cx.subscribe(
+            &replacement_editor,
+            |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::Edited { .. } = event {
+                    this.update_replacement_previews(cx);
+                }
+            },
+        ));
         let results_editor = cx.new(|cx| {
             let mut editor =
                 Editor::for_multibuffer(excerpts, Some(project.clone()), true, window, cx);
@@ -791,6 +964,14 @@ impl ProjectSearchView {
             }
         }));
 
+        if let Some(git_state) = project.read(cx).git_state() {
+            subscriptions.push(
+                cx.subscribe(git_state, |this, _, _: &project::git::Event, cx| {
+                    this.refresh_branches(cx)
+                }),
+            );
+        }
+
         // Check if Worktrees have all been previously indexed
         let mut this = ProjectSearchView {
             workspace,
@@ -807,13 +988,80 @@ impl ProjectSearchView {
             excluded_files_editor,
             filters_enabled,
             replace_enabled: false,
-            included_opened_only: false,
+            scope: None,
+            branch_scopes: Vec::new(),
+            branches_repository: None,
+            _load_branches: Task::ready(()),
             _subscriptions: subscriptions,
         };
+        this.refresh_branches(cx);
         this.model_changed(window, cx);
         this
     }
 
+    /// Reloads the branches offered as search scopes whenever the active repository
+    /// or its checked out branch changes.
+    fn refresh_branches(&mut self, cx: &mut Context<Self>) {
+        let project = self.model.read(cx).project.clone();
+        let repository = project
+            .read(cx)
+            .git_state()
+            .and_then(|git_state| git_state.read(cx).active_repository())
+            .map(|repository| {
+                (
+                    repository_project_path(&repository),
+                    repository.repository_entry.branch(),
+                )
+            });
+        if repository == self.branches_repository {
+            return;
+        }
+        self.branches_repository = repository.clone();
+        self.branch_scopes.clear();
+        cx.notify();
+
+        let Some((repository_path, _)) = repository else {
+            self._load_branches = Task::ready(());
+            return;
+        };
+        let branches = project.read(cx).branches(repository_path.clone(), cx);
+        self._load_branches = cx.spawn(|this, mut cx| async move {
+            let Some(branches) = branches.await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.branch_scopes = branches
+                    .into_iter()
+                    .filter(|branch| !branch.is_head)
+                    .map(|branch| SearchScope::ChangedSinceBranch {
+                        repository: repository_path.clone(),
+                        branch: branch.name,
+                    })
+                    .collect();
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    /// Opens a new search limited to the given scope.
+    pub fn new_search_in_scope(
+        workspace: &mut Workspace,
+        scope: SearchScope,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let weak_workspace = cx.entity().downgrade();
+
+        let model = cx.new(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+        let search = cx.new(|cx| ProjectSearchView::new(weak_workspace, model, window, cx, None));
+        workspace.add_item_to_active_pane(Box::new(search.clone()), None, true, window, cx);
+        search.update(cx, |search, cx| {
+            search.scope = Some(scope);
+            search.focus_query_editor(window, cx)
+        });
+    }
+
     pub fn new_search_in_directory(
         workspace: &mut Workspace,
         dir_path: &Path,
@@ -865,7 +1113,7 @@ impl ProjectSearchView {
             .active_item(cx)
             .and_then(|item| item.downcast::<ProjectSearchView>())
         {
-            let new_query = search_view.update(cx, |search_view, cx| {
+            let (new_query, scope) = search_view.update(cx, |search_view, cx| {
                 let new_query = search_view.build_search_query(cx);
                 if new_query.is_some() {
                     if let Some(old_query) = search_view.model.read(cx).active_query.clone() {
@@ -875,12 +1123,12 @@ impl ProjectSearchView {
                         search_view.search_options = SearchOptions::from_query(&old_query);
                     }
                 }
-                new_query
+                (new_query, search_view.scope.clone())
             });
             if let Some(new_query) = new_query {
                 let model = cx.new(|cx| {
                     let mut model = ProjectSearch::new(workspace.project().clone(), cx);
-                    model.search(new_query, cx);
+                    model.search(new_query, scope.clone(), cx);
                     model
                 });
                 let weak_workspace = cx.entity().downgrade();
@@ -960,6 +1208,7 @@ impl ProjectSearchView {
 
         search.update(cx, |search, cx| {
             search.replace_enabled = action.replace_enabled;
+            search.update_replacement_previews(cx);
             if let Some(query) = query {
                 search.set_query(&query, window, cx);
             }
@@ -969,7 +1218,9 @@ impl ProjectSearchView {
 
     fn search(&mut self, cx: &mut Context<Self>) {
         if let Some(query) = self.build_search_query(cx) {
-            self.model.update(cx, |model, cx| model.search(query, cx));
+            let scope = self.scope.clone();
+            self.model
+                .update(cx, |model, cx| model.search(query, scope, cx));
         }
     }
 
@@ -980,7 +1231,7 @@ impl ProjectSearchView {
     fn build_search_query(&mut self, cx: &mut Context<Self>) -> Option<SearchQuery> {
         // Do not bail early in this function, as we want to fill out `self.panels_with_errors`.
         let text = self.query_editor.read(cx).text(cx);
-        let open_buffers = if self.included_opened_only {
+        let open_buffers = if self.scope == Some(SearchScope::OpenBuffers) {
             Some(self.open_buffers(cx))
         } else {
             None
@@ -1021,7 +1272,32 @@ impl ProjectSearchView {
                 }
             };
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            match SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                open_buffers,
+            ) {
+                Ok(query) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+
+                    Some(query)
+                }
+                Err(_e) => {
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+
+                    None
+                }
+            }
+        } else if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -1211,6 +1487,7 @@ impl ProjectSearchView {
                 self.focus_results_editor(window, cx);
             }
         }
+        self.update_replacement_previews(cx);
 
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
@@ -1470,6 +1747,7 @@ impl ProjectSearchBar {
         if let Some(search) = &self.active_project_search {
             search.update(cx, |this, cx| {
                 this.replace_enabled = !this.replace_enabled;
+                this.update_replacement_previews(cx);
                 let editor_to_focus = if this.replace_enabled {
                     this.replacement_editor.focus_handle(cx)
                 } else {
@@ -1501,28 +1779,90 @@ impl ProjectSearchBar {
         }
     }
 
-    fn toggle_opened_only(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
-        if let Some(search_view) = self.active_project_search.as_ref() {
-            search_view.update(cx, |search_view, cx| {
-                search_view.toggle_opened_only(window, cx);
-                if search_view.model.read(cx).active_query.is_some() {
-                    search_view.search(cx);
-                }
+    fn render_scope_menu(
+        &self,
+        search_view: &Entity<ProjectSearchView>,
+        cx: &App,
+    ) -> impl IntoElement {
+        let search = search_view.read(cx);
+        let scope = search.scope.clone();
+        let has_repositories = search
+            .model
+            .read(cx)
+            .project
+            .read(cx)
+            .git_state()
+            .map_or(false, |git_state| {
+                !git_state.read(cx).all_repositories().is_empty()
             });
+        let branch_scopes = search.branch_scopes.clone();
+        let search_view = search_view.downgrade();
+
+        PopoverMenu::new("project-search-scope")
+            .trigger(
+                Button::new("project-search-scope-trigger", search.scope_label(cx))
+                    .label_size(LabelSize::Small)
+                    .icon(IconName::ChevronDown)
+                    .icon_size(IconSize::XSmall)
+                    .icon_position(IconPosition::End)
+                    .icon_color(Color::Muted)
+                    .toggle_state(scope.is_some()),
+            )
+            .attach(Corner::BottomLeft)
+            .menu(move |window, cx| {
+                let scope = scope.clone();
+                let branch_scopes = branch_scopes.clone();
+                let search_view = search_view.clone();
+                Some(ContextMenu::build(window, cx, move |mut menu, _, cx| {
+                    let mut scopes: Vec<(SharedString, Option<SearchScope>)> = vec![
+                        ("Whole Project".into(), None),
+                        ("Open Files".into(), Some(SearchScope::OpenBuffers)),
+                    ];
+                    if has_repositories {
+                        scopes.push(("Uncommitted Changes".into(), Some(SearchScope::GitChanges)));
+                        if let Some(search_view) = search_view.upgrade() {
+                            let search_view = search_view.read(cx);
+                            scopes.extend(branch_scopes.iter().map(|branch_scope| {
+                                (
+                                    search_view.label_for_scope(Some(branch_scope), cx),
+                                    Some(branch_scope.clone()),
+                                )
+                            }));
+                        }
+                    }
+                    // Scopes that aren't offered by the menu itself, such as a directory or
+                    // a branch of a previously active repository, are still listed as selected.
+                    if let Some(current_scope) = scope.as_ref().filter(|current_scope| {
+                        !scopes
+                            .iter()
+                            .any(|(_, entry_scope)| entry_scope.as_ref() == Some(*current_scope))
+                    }) {
+                        let label = search_view
+                            .upgrade()
+                            .map(|search_view| search_view.read(cx).scope_label(cx))
+                            .unwrap_or_default();
+                        scopes.push((label, Some(current_scope.clone())));
+                    }
 
-            cx.notify();
-            true
-        } else {
-            false
-        }
-    }
-
-    fn is_opened_only_enabled(&self, cx: &App) -> bool {
-        if let Some(search_view) = self.active_project_search.as_ref() {
-            search_view.read(cx).included_opened_only
-        } else {
-            false
-        }
+                    for (label, entry_scope) in scopes {
+                        let search_view = search_view.clone();
+                        menu = menu.toggleable_entry(
+                            label,
+                            scope == entry_scope,
+                            IconPosition::Start,
+                            None,
+                            move |_, cx| {
+                                search_view
+                                    .update(cx, |search_view, cx| {
+                                        search_view.set_scope(entry_scope.clone(), cx)
+                                    })
+                                    .ok();
+                            },
+                        );
+                    }
+                    menu
+                }))
+            })
     }
 
     fn move_focus_to_results(&self, window: &mut Window, cx: &mut Context<Self>) {
@@ -1578,6 +1918,9 @@ impl ProjectSearchBar {
                             }
                         });
                         search_view.set_search_editor(kind, &new_query, window, cx);
+                        if kind == SearchInputKind::Query {
+                            search_view.restore_history_scope(cx);
+                        }
                     }
                 }
             });
@@ -1615,6 +1958,9 @@ impl ProjectSearchBar {
                                 .map(str::to_string)
                             {
                                 search_view.set_search_editor(kind, &new_query, window, cx);
+                                if kind == SearchInputKind::Query {
+                                    search_view.restore_history_scope(cx);
+                                }
                                 return;
                             }
                         }
@@ -1629,6 +1975,9 @@ impl ProjectSearchBar {
                             })
                         }) {
                             search_view.set_search_editor(kind, &new_query, window, cx);
+                            if kind == SearchInputKind::Query {
+                                search_view.restore_history_scope(cx);
+                            }
                         }
                     }
                 }
@@ -1693,10 +2042,10 @@ impl ProjectSearchBar {
 
 impl Render for ProjectSearchBar {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let Some(search) = self.active_project_search.clone() else {
+        let Some(search_view) = self.active_project_search.clone() else {
             return div();
         };
-        let search = search.read(cx);
+        let search = search_view.read(cx);
         let focus_handle = search.focus_handle(cx);
 
         let container_width = window.viewport_size().width;
@@ -1747,6 +2096,13 @@ impl Render for ProjectSearchBar {
                         cx.listener(|this, _, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
+                    ))
+                    .child(SearchOptions::STRUCTURAL.as_button(
+                        self.is_option_enabled(SearchOptions::STRUCTURAL, cx),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, _, cx| {
+                            this.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+                        }),
                     )),
             );
 
@@ -1997,15 +2353,7 @@ impl Render for ProjectSearchBar {
                     h_flex()
                         .min_w_64()
                         .gap_1()
-                        .child(
-                            IconButton::new("project-search-opened-only", IconName::FileSearch)
-                                .shape(IconButtonShape::Square)
-                                .toggle_state(self.is_opened_only_enabled(cx))
-                                .tooltip(Tooltip::text("Only Search Open Files"))
-                                .on_click(cx.listener(|this, _, window, cx| {
-                                    this.toggle_opened_only(window, cx);
-                                })),
-                        )
+                        .child(self.render_scope_menu(&search_view, cx))
                         .child(
                             SearchOptions::INCLUDE_IGNORED.as_button(
                                 search
@@ -2186,7 +2534,9 @@ pub mod tests {
 
     use super::*;
     use editor::{display_map::DisplayRow, DisplayPoint};
+    use git::status::FileStatus;
     use gpui::{Action, TestAppContext, VisualTestContext, WindowHandle};
+    use language::{tree_sitter_rust, Language, LanguageConfig, LanguageMatcher};
     use project::FakeFs;
     use serde_json::json;
     use settings::SettingsStore;
@@ -2319,6 +2669,85 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_structural_replacement_previews(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "main.rs": "fn main() {\n    foo(a, b);\n    foo(c, d);\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        project.update(cx, |project, _| project.languages().add(rust_lang()));
+        let window = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|window, cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), window, cx, None)
+        });
+
+        search_view
+            .update(cx, |search_view, _, _| {
+                search_view.search_options = SearchOptions::STRUCTURAL;
+            })
+            .unwrap();
+        perform_search(search_view, "foo($A, $B)", cx);
+
+        // Previews are only shown while replacing.
+        search_view
+            .update(cx, |search_view, window, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 2);
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("bar($B, $A)", window, cx));
+                let text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(!text.contains('→'), "unexpected previews in {text:?}");
+
+                search_view.replace_enabled = true;
+                search_view.update_replacement_previews(cx);
+                let text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(text.contains("foo(a, b) → bar(b, a);"), "{text:?}");
+                assert!(text.contains("foo(c, d) → bar(d, c);"), "{text:?}");
+            })
+            .unwrap();
+
+        // The previews follow the replacement as it is typed.
+        search_view
+            .update(cx, |search_view, window, cx| {
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("baz($A)", window, cx));
+                let text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(text.contains("foo(a, b) → baz(a);"), "{text:?}");
+                assert!(!text.contains("bar("), "{text:?}");
+
+                search_view.replace_all(&ReplaceAll, window, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        // Once applied, the rewritten code no longer matches, so nothing is previewed.
+        search_view
+            .update(cx, |search_view, _, cx| {
+                let text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(text.contains("    baz(a);\n    baz(c);"), "{text:?}");
+                assert!(!text.contains('→'), "{text:?}");
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
         init_test(cx);
@@ -2979,6 +3408,128 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_changed_since_branch_scope(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a": {
+                    ".git": {},
+                    "one.rs": "const ONE: usize = 1;",
+                },
+                "b": {
+                    ".git": {},
+                    "two.rs": "const TWO: usize = 2;",
+                },
+            }),
+        )
+        .await;
+        for dot_git in ["/dir/a/.git", "/dir/b/.git"] {
+            fs.insert_branches(Path::new(dot_git), &["main", "feature"]);
+        }
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/dir/a/.git"),
+            &[(Path::new("one.rs"), FileStatus::Untracked)],
+        );
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/dir/b/.git"),
+            &[(Path::new("two.rs"), FileStatus::Untracked)],
+        );
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        cx.run_until_parked();
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let repository = |path: &str| ProjectPath {
+            worktree_id,
+            path: Path::new(path).into(),
+        };
+        let window = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|window, cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), window, cx, None)
+        });
+        cx.run_until_parked();
+
+        let active_repository = project.read_with(cx, |project, cx| {
+            project
+                .git_state()
+                .unwrap()
+                .read(cx)
+                .active_repository()
+                .unwrap()
+        });
+        let active_path = repository_project_path(&active_repository);
+        search_view
+            .update(cx, |search_view, _, _| {
+                assert_eq!(
+                    search_view.branch_scopes,
+                    [SearchScope::ChangedSinceBranch {
+                        repository: active_path.clone(),
+                        branch: "feature".into(),
+                    }]
+                );
+            })
+            .unwrap();
+
+        // The branch comparison only covers the repository that the branch belongs to.
+        search_view
+            .update(cx, |search_view, window, cx| {
+                search_view.set_scope(
+                    Some(SearchScope::ChangedSinceBranch {
+                        repository: repository("a"),
+                        branch: "feature".into(),
+                    }),
+                    cx,
+                );
+                search_view.query_editor.update(cx, |query_editor, cx| {
+                    query_editor.set_text("const", window, cx)
+                });
+                search_view.search(cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        search_view
+            .update(cx, |search_view, _, cx| {
+                let text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert!(text.contains("ONE"), "{text:?}");
+                assert!(!text.contains("TWO"), "{text:?}");
+            })
+            .unwrap();
+
+        // Activating another repository offers that repository's branches instead.
+        let other_repository = project.read_with(cx, |project, cx| {
+            project
+                .git_state()
+                .unwrap()
+                .read(cx)
+                .all_repositories()
+                .into_iter()
+                .find(|repository| repository != &active_repository)
+                .unwrap()
+        });
+        cx.update(|cx| other_repository.activate(cx));
+        cx.run_until_parked();
+        search_view
+            .update(cx, |search_view, _, _| {
+                assert_ne!(repository_project_path(&other_repository), active_path);
+                assert_eq!(
+                    search_view.branch_scopes,
+                    [SearchScope::ChangedSinceBranch {
+                        repository: repository_project_path(&other_repository),
+                        branch: "feature".into(),
+                    }]
+                );
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_search_query_history(cx: &mut TestAppContext) {
         init_test(cx);
@@ -3861,6 +4412,20 @@ pub mod tests {
         });
     }
 
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        ))
+    }
+
     fn perform_search(
         search_view: WindowHandle<ProjectSearchView>,
         text: impl Into<Arc<str>>,
@@ -1,6 +1,7 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod problem_matcher;
 pub mod static_source;
 mod task_template;
 mod vscode_format;
@@ -13,7 +14,13 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use problem_matcher::{
+    Problem, ProblemMatcher, ProblemMatcherDefinition, ProblemPattern, ProblemSeverity,
+};
+pub use task_template::{
+    DependsOrder, HideStrategy, RevealStrategy, TaskInput, TaskInputKind, TaskTemplate,
+    TaskTemplates,
+};
 pub use vscode_format::VsCodeTaskFile;
 pub use zed_actions::RevealTarget;
 
@@ -58,6 +65,8 @@ pub struct SpawnInTerminal {
     pub show_summary: bool,
     /// Whether to show the command line in the task output.
     pub show_command: bool,
+    /// Problem matchers to find problems in the task's output with, reported as project diagnostics.
+    pub problem_matchers: Vec<ProblemMatcher>,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particular [`TaskContext`] and now is ready to spawn the actual task.
@@ -71,6 +80,8 @@ pub struct ResolvedTask {
     pub id: TaskId,
     /// A template the task got resolved from.
     original_task: TaskTemplate,
+    /// A context the task got resolved with, used to resolve the tasks it depends on.
+    task_context: TaskContext,
     /// Full, unshortened label of the task after all resolutions are made.
     pub resolved_label: String,
     /// Variables that were substituted during the task template resolution.
@@ -86,6 +97,50 @@ impl ResolvedTask {
         &self.original_task
     }
 
+    /// A context the task template was resolved with.
+    pub fn task_context(&self) -> &TaskContext {
+        &self.task_context
+    }
+
+    /// Whether the task has a command to spawn, rather than only running the tasks it depends on.
+    pub fn has_command(&self) -> bool {
+        !self.original_task.command.trim().is_empty()
+    }
+
+    /// Inputs of the task template that the user has not provided the values for yet.
+    pub fn missing_inputs(&self) -> impl Iterator<Item = &TaskInput> {
+        self.original_task.inputs.iter().filter(|input| {
+            self.task_context
+                .task_variables
+                .get(&VariableName::Input(Cow::Owned(input.id.clone())))
+                .is_none()
+        })
+    }
+
+    /// Resolves the task template again, with the values the user provided for its inputs,
+    /// keeping the overrides applied to the previous resolution.
+    pub fn with_inputs(
+        &self,
+        id_base: &str,
+        input_values: impl IntoIterator<Item = (String, String)>,
+    ) -> Option<Self> {
+        let mut task_context = self.task_context.clone();
+        for (input_id, value) in input_values {
+            task_context
+                .task_variables
+                .insert(VariableName::Input(Cow::Owned(input_id)), value);
+        }
+        let mut resolved_task = self.original_task.resolve_task(id_base, &task_context)?;
+        if let Some((resolved, previous)) =
+            resolved_task.resolved.as_mut().zip(self.resolved.as_ref())
+        {
+            resolved.reveal_target = previous.reveal_target;
+            resolved.allow_concurrent_runs = previous.allow_concurrent_runs;
+            resolved.use_new_terminal = previous.use_new_terminal;
+        }
+        Some(resolved_task)
+    }
+
     /// Variables that were substituted during the task template resolution.
     pub fn substituted_variables(&self) -> &HashSet<VariableName> {
         &self.substituted_variables
@@ -131,6 +186,9 @@ pub enum VariableName {
     /// Custom variable, provided by the plugin or other external source.
     /// Will be printed with `CUSTOM_` prefix to avoid potential conflicts with other variables.
     Custom(Cow<'static, str>),
+    /// A value the user provided for one of the task's [`TaskInput`]s, identified by the input's id.
+    /// Will be printed with `INPUT_` prefix.
+    Input(Cow<'static, str>),
 }
 
 impl VariableName {
@@ -166,6 +224,10 @@ impl FromStr for VariableName {
                     without_prefix.strip_prefix(ZED_CUSTOM_VARIABLE_NAME_PREFIX)
                 {
                     Self::Custom(Cow::Owned(custom_name.to_owned()))
+                } else if let Some(input_id) =
+                    without_prefix.strip_prefix(ZED_INPUT_VARIABLE_NAME_PREFIX)
+                {
+                    Self::Input(Cow::Owned(input_id.to_owned()))
                 } else {
                     return Err(());
                 }
@@ -178,6 +240,7 @@ impl FromStr for VariableName {
 /// A prefix that all [`VariableName`] variants are prefixed with when used in environment variables and similar template contexts.
 pub const ZED_VARIABLE_NAME_PREFIX: &str = "ZED_";
 const ZED_CUSTOM_VARIABLE_NAME_PREFIX: &str = "CUSTOM_";
+const ZED_INPUT_VARIABLE_NAME_PREFIX: &str = "INPUT_";
 
 impl std::fmt::Display for VariableName {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -197,6 +260,10 @@ impl std::fmt::Display for VariableName {
                 f,
                 "{ZED_VARIABLE_NAME_PREFIX}{ZED_CUSTOM_VARIABLE_NAME_PREFIX}{s}"
             ),
+            Self::Input(id) => write!(
+                f,
+                "{ZED_VARIABLE_NAME_PREFIX}{ZED_INPUT_VARIABLE_NAME_PREFIX}{id}"
+            ),
         }
     }
 }
@@ -0,0 +1,462 @@
+use std::path::PathBuf;
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt as _;
+
+/// A problem matcher of a task: either a built-in one, referenced by its name (e.g. `$rustc`), or a custom one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcherDefinition {
+    /// One of the built-in problem matchers: `$rustc`, `$tsc`, `$gcc`, `$eslint-compact` or `$eslint-stylish`.
+    BuiltIn(String),
+    /// A problem matcher described in place.
+    Custom(ProblemMatcher),
+}
+
+impl ProblemMatcherDefinition {
+    /// Returns the problem matcher, looking up the built-in ones by name.
+    pub fn problem_matcher(&self) -> Option<ProblemMatcher> {
+        match self {
+            Self::BuiltIn(name) => ProblemMatcher::built_in(name),
+            Self::Custom(problem_matcher) => Some(problem_matcher.clone()),
+        }
+    }
+}
+
+/// Finds problems (errors, warnings, etc.) in the task's output, turning them into project diagnostics.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemMatcher {
+    /// Name of the tool reporting the problems, displayed as the source of the diagnostics.
+    pub source: String,
+    /// A directory to resolve relative file paths against, defaults to the task's working directory.
+    #[serde(default)]
+    pub base_directory: Option<String>,
+    /// Severity of the problems which patterns do not capture it.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+    /// Patterns to match consecutive output lines of a single problem with.
+    pub pattern: Vec<ProblemPattern>,
+}
+
+/// A regular expression matching a line of task output, with indices of the capture groups
+/// containing parts of a problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match the output line with.
+    pub regexp: String,
+    /// Capture group of the file path.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// Capture group of the 1-based line number.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// Capture group of the 1-based column number.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// Capture group of the 1-based end line number.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// Capture group of the 1-based end column number.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// Capture group of the severity: `error`, `warning` or `info`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// Capture group of the problem's code.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// Capture group of the problem's message.
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the last pattern of a matcher matches any number of consecutive lines, each of them being a separate problem.
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+}
+
+/// How severe a problem is.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error (default).
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Info,
+}
+
+impl ProblemSeverity {
+    pub(crate) fn parse(severity: &str) -> Option<Self> {
+        match severity.to_ascii_lowercase().as_str() {
+            "error" | "fatal" => Some(Self::Error),
+            "warning" | "warn" => Some(Self::Warning),
+            "info" | "information" | "note" | "hint" => Some(Self::Info),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found in the task's output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// A path to the file with the problem, as printed by the task.
+    pub path: PathBuf,
+    /// The 1-based line of the problem.
+    pub line: u32,
+    /// The 1-based column of the problem, if any.
+    pub column: Option<u32>,
+    /// The 1-based line where the problem ends, if known.
+    pub end_line: Option<u32>,
+    /// The 1-based column where the problem ends, if known.
+    pub end_column: Option<u32>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// The problem's code, if any.
+    pub code: Option<String>,
+    /// The problem's description.
+    pub message: String,
+}
+
+#[derive(Default, Clone)]
+struct PartialProblem {
+    path: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl ProblemMatcher {
+    /// Looks up a built-in problem matcher by its name, e.g. `$rustc`.
+    pub fn built_in(name: &str) -> Option<Self> {
+        let (source, pattern) = match name {
+            "$rustc" => (
+                "rustc",
+                vec![
+                    ProblemPattern {
+                        regexp: r"^(warning|error)(?:\[(\w+)\])?: (.*)$".to_string(),
+                        severity: Some(1),
+                        code: Some(2),
+                        message: Some(3),
+                        ..ProblemPattern::default()
+                    },
+                    ProblemPattern {
+                        regexp: r"^\s*--> (.+?):(\d+):(\d+)$".to_string(),
+                        file: Some(1),
+                        line: Some(2),
+                        column: Some(3),
+                        ..ProblemPattern::default()
+                    },
+                ],
+            ),
+            "$tsc" => (
+                "tsc",
+                vec![ProblemPattern {
+                    regexp: r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+TS(\d+)\s*:\s*(.*)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    severity: Some(4),
+                    code: Some(5),
+                    message: Some(6),
+                    ..ProblemPattern::default()
+                }],
+            ),
+            "$gcc" => (
+                "gcc",
+                vec![ProblemPattern {
+                    regexp: r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error):\s+(.*)$"
+                        .to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    severity: Some(4),
+                    message: Some(5),
+                    ..ProblemPattern::default()
+                }],
+            ),
+            "$eslint-compact" => (
+                "eslint",
+                vec![ProblemPattern {
+                    regexp: r"^(.+):\sline\s(\d+),\scol\s(\d+),\s(Error|Warning|Info)\s-\s(.+)\s\((.+)\)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    severity: Some(4),
+                    message: Some(5),
+                    code: Some(6),
+                    ..ProblemPattern::default()
+                }],
+            ),
+            "$eslint-stylish" => (
+                "eslint",
+                vec![
+                    ProblemPattern {
+                        regexp: r"^((?:[a-zA-Z]:)*[./\\]+.*?)$".to_string(),
+                        file: Some(1),
+                        ..ProblemPattern::default()
+                    },
+                    ProblemPattern {
+                        regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.+?)(?:\s\s+(.*))?$"
+                            .to_string(),
+                        line: Some(1),
+                        column: Some(2),
+                        severity: Some(3),
+                        message: Some(4),
+                        code: Some(5),
+                        repeat: true,
+                        ..ProblemPattern::default()
+                    },
+                ],
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            source: source.to_string(),
+            base_directory: None,
+            severity: ProblemSeverity::default(),
+            pattern,
+        })
+    }
+
+    /// Finds all problems in the task output given.
+    /// Problems are only reported if all of the patterns match consecutive lines and a file, a line and a message are captured.
+    pub fn find_problems(&self, output: &str) -> Vec<Problem> {
+        let Some(regexes) = self
+            .pattern
+            .iter()
+            .map(|pattern| Regex::new(&pattern.regexp).log_err())
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Vec::new();
+        };
+        let Some((last_pattern, first_patterns)) = self.pattern.split_last() else {
+            return Vec::new();
+        };
+        let (last_regex, first_regexes) = regexes.split_last().unwrap();
+
+        let lines = output.lines().collect::<Vec<_>>();
+        let mut problems = Vec::new();
+        let mut ix = 0;
+        'lines: while ix < lines.len() {
+            let mut problem = PartialProblem::default();
+            for (offset, (pattern, regex)) in first_patterns.iter().zip(first_regexes).enumerate() {
+                match lines.get(ix + offset) {
+                    Some(line) if pattern.capture(regex, line, &mut problem) => {}
+                    _ => {
+                        ix += 1;
+                        continue 'lines;
+                    }
+                }
+            }
+
+            let mut line_ix = ix + first_patterns.len();
+            let mut matched_last_pattern = false;
+            while let Some(line) = lines.get(line_ix) {
+                let mut line_problem = problem.clone();
+                if !last_pattern.capture(last_regex, line, &mut line_problem) {
+                    break;
+                }
+                matched_last_pattern = true;
+                problems.extend(self.complete(line_problem));
+                line_ix += 1;
+                if !last_pattern.repeat {
+                    break;
+                }
+            }
+            ix = if matched_last_pattern {
+                line_ix
+            } else {
+                ix + 1
+            };
+        }
+        problems
+    }
+
+    fn complete(&self, problem: PartialProblem) -> Option<Problem> {
+        Some(Problem {
+            path: PathBuf::from(problem.path?),
+            line: problem.line?,
+            column: problem.column,
+            end_line: problem.end_line,
+            end_column: problem.end_column,
+            severity: problem.severity.unwrap_or(self.severity),
+            code: problem.code,
+            message: problem.message?,
+        })
+    }
+}
+
+impl ProblemPattern {
+    fn capture(&self, regex: &Regex, line: &str, problem: &mut PartialProblem) -> bool {
+        let Some(captures) = regex.captures(line) else {
+            return false;
+        };
+        let group = |ix: Option<usize>| {
+            captures
+                .get(ix?)
+                .map(|capture| capture.as_str().trim())
+                .filter(|capture| !capture.is_empty())
+        };
+        let number = |ix: Option<usize>| group(ix)?.parse::<u32>().ok();
+
+        if let Some(path) = group(self.file) {
+            problem.path = Some(path.to_string());
+        }
+        if let Some(line) = number(self.line) {
+            problem.line = Some(line);
+        }
+        if let Some(column) = number(self.column) {
+            problem.column = Some(column);
+        }
+        if let Some(end_line) = number(self.end_line) {
+            problem.end_line = Some(end_line);
+        }
+        if let Some(end_column) = number(self.end_column) {
+            problem.end_column = Some(end_column);
+        }
+        if let Some(severity) = group(self.severity).and_then(ProblemSeverity::parse) {
+            problem.severity = Some(severity);
+        }
+        if let Some(code) = group(self.code) {
+            problem.code = Some(code.to_string());
+        }
+        if let Some(message) = group(self.message) {
+            problem.message = Some(message.to_string());
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(name: &str, output: &str) -> Vec<Problem> {
+        ProblemMatcher::built_in(name)
+            .unwrap()
+            .find_problems(output)
+    }
+
+    #[test]
+    fn test_rustc_problems() {
+        let output = r#"   Compiling example v0.1.0 (/tmp/example)
+warning: unused variable: `x`
+ --> src/main.rs:2:9
+  |
+2 |     let x = 5;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+error[E0425]: cannot find value `y` in this scope
+  --> src/lib.rs:10:5
+   |
+warning: `example` (bin "example") generated 1 warning
+error: could not compile `example` (bin "example") due to 1 previous error"#;
+        assert_eq!(
+            problems("$rustc", output),
+            vec![
+                Problem {
+                    path: PathBuf::from("src/main.rs"),
+                    line: 2,
+                    column: Some(9),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `x`".to_string(),
+                },
+                Problem {
+                    path: PathBuf::from("src/lib.rs"),
+                    line: 10,
+                    column: Some(5),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0425".to_string()),
+                    message: "cannot find value `y` in this scope".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_line_problems() {
+        assert_eq!(
+            problems(
+                "$tsc",
+                "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'."
+            ),
+            vec![Problem {
+                path: PathBuf::from("src/index.ts"),
+                line: 3,
+                column: Some(7),
+                end_line: None,
+                end_column: None,
+                severity: ProblemSeverity::Error,
+                code: Some("2322".to_string()),
+                message: "Type 'string' is not assignable to type 'number'.".to_string(),
+            }]
+        );
+        assert_eq!(
+            problems(
+                "$gcc",
+                "main.c:4:12: warning: unused variable 'y' [-Wunused-variable]\nmain.c: In function 'main':"
+            ),
+            vec![Problem {
+                path: PathBuf::from("main.c"),
+                line: 4,
+                column: Some(12),
+                end_line: None,
+                end_column: None,
+                severity: ProblemSeverity::Warning,
+                code: None,
+                message: "unused variable 'y' [-Wunused-variable]".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_looping_problems() {
+        let output = r#"
+/home/user/project/src/app.js
+   1:10  error    'foo' is defined but never used  no-unused-vars
+  12:3   warning  Unexpected console statement     no-console
+
+✖ 2 problems (1 error, 1 warning)"#;
+        assert_eq!(
+            problems("$eslint-stylish", output)
+                .into_iter()
+                .map(|problem| (
+                    problem.path,
+                    problem.line,
+                    problem.severity,
+                    problem.code,
+                    problem.message
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    PathBuf::from("/home/user/project/src/app.js"),
+                    1,
+                    ProblemSeverity::Error,
+                    Some("no-unused-vars".to_string()),
+                    "'foo' is defined but never used".to_string()
+                ),
+                (
+                    PathBuf::from("/home/user/project/src/app.js"),
+                    12,
+                    ProblemSeverity::Warning,
+                    Some("no-console".to_string()),
+                    "Unexpected console statement".to_string()
+                ),
+            ]
+        );
+    }
+}
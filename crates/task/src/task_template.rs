@@ -9,8 +9,8 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    ProblemMatcherDefinition, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext,
+    TaskId, VariableName, ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -25,6 +25,7 @@ pub struct TaskTemplate {
     /// Human readable name of the task to display in the UI.
     pub label: String,
     /// Executable command to spawn.
+    #[serde(default)]
     pub command: String,
     /// Arguments to the command.
     #[serde(default)]
@@ -70,6 +71,22 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Labels of the tasks that have to finish successfully before this task is spawned.
+    /// A task with dependencies may omit its own `command` to only run its dependencies.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks from `depends_on`:
+    /// * `sequence` — one after another, in the order listed (default)
+    /// * `parallel` — all at the same time
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Problem matchers that turn the task's output into project diagnostics,
+    /// either built-in ones (`$rustc`, `$tsc`, `$gcc`, `$eslint-compact`, `$eslint-stylish`) or custom ones.
+    #[serde(default)]
+    pub problem_matchers: Vec<ProblemMatcherDefinition>,
+    /// Values to ask the user for before spawning the task, available as `$ZED_INPUT_<id>` variables.
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -98,6 +115,60 @@ pub enum HideStrategy {
     OnSuccess,
 }
 
+/// How to run the dependencies of a task.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Run the dependencies one after another, in the order they are listed.
+    #[default]
+    Sequence,
+    /// Run all the dependencies at the same time.
+    Parallel,
+}
+
+/// A value to ask the user for before the task is spawned.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskInput {
+    /// Identifier of the input, the task refers to its value with `$ZED_INPUT_<id>`.
+    pub id: String,
+    /// A text to display when asking for the value.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// How to obtain the value.
+    #[serde(flatten)]
+    pub kind: TaskInputKind,
+}
+
+/// How to obtain the value of a [`TaskInput`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskInputKind {
+    /// Ask the user to type the value in.
+    PromptString {
+        /// The value to suggest, unless the user provided another one before.
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Ask the user to pick the value out of the options.
+    PickString {
+        /// The values to pick from.
+        options: Vec<String>,
+        /// The option to select, unless the user picked another one before.
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Run a command and use its output as the value.
+    /// If the command prints several lines, the user picks one of them.
+    Command {
+        /// Executable command to spawn.
+        command: String,
+        /// Arguments to the command.
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
 /// A group of Tasks defined in a JSON file.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TaskTemplates(pub Vec<TaskTemplate>);
@@ -122,7 +193,9 @@ impl TaskTemplate {
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty()
+            || (self.command.trim().is_empty() && self.depends_on.is_empty())
+        {
             return None;
         }
 
@@ -198,6 +271,26 @@ impl TaskTemplate {
             &mut substituted_variables,
         )?;
 
+        let mut problem_matchers = Vec::with_capacity(self.problem_matchers.len());
+        for definition in &self.problem_matchers {
+            let Some(mut problem_matcher) = definition
+                .problem_matcher()
+                .with_context(|| format!("unknown problem matcher {definition:?}"))
+                .log_err()
+            else {
+                continue;
+            };
+            if let Some(base_directory) = &problem_matcher.base_directory {
+                problem_matcher.base_directory = Some(substitute_all_template_variables_in_str(
+                    base_directory,
+                    &task_variables,
+                    &variable_names,
+                    &mut substituted_variables,
+                )?);
+            }
+            problem_matchers.push(problem_matcher);
+        }
+
         let task_hash = to_hex_hash(self)
             .context("hashing task template")
             .log_err()?;
@@ -230,6 +323,7 @@ impl TaskTemplate {
             id: id.clone(),
             substituted_variables,
             original_task: self.clone(),
+            task_context: cx.clone(),
             resolved_label: full_label.clone(),
             resolved: Some(SpawnInTerminal {
                 id,
@@ -255,6 +349,7 @@ impl TaskTemplate {
                 shell: self.shell.clone(),
                 show_summary: self.show_summary,
                 show_command: self.show_command,
+                problem_matchers,
             }),
         })
     }
@@ -302,10 +397,15 @@ fn substitute_all_template_variables_in_str<A: AsRef<str>>(
                 name.push_str(default);
             }
             return Ok(Some(name));
-        } else if variable_name.starts_with(ZED_VARIABLE_NAME_PREFIX) {
+        } else if variable_name.starts_with(ZED_VARIABLE_NAME_PREFIX)
+            && !matches!(
+                variable_name.parse::<VariableName>(),
+                Ok(VariableName::Input(_))
+            )
+        {
             bail!("Unknown variable name: {variable_name}");
         }
-        // This is an unknown variable.
+        // This is an unknown variable, or an input the user has not provided the value for yet.
         // We should not error out, as they may come from user environment (e.g. $PATH). That means that the variable substitution might not be perfect.
         // If there's a default, we need to return the string verbatim as otherwise shellexpand will apply that default for us.
         if !default.is_empty() {
@@ -403,6 +503,16 @@ mod tests {
                 "should not resolve task with blank label and/or command: {task_with_blank_property:?}"
             );
         }
+
+        let task_with_dependencies_only = TaskTemplate {
+            command: "".to_string(),
+            depends_on: vec!["other_label".to_string()],
+            ..task_with_all_properties.clone()
+        };
+        let resolved_task = task_with_dependencies_only
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("should resolve task with blank command but with dependencies");
+        assert!(!resolved_task.has_command());
     }
 
     #[test]
@@ -671,6 +781,54 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn test_task_inputs() {
+        let task = TaskTemplate {
+            label: "Deploy to $ZED_INPUT_environment".into(),
+            command: "deploy".into(),
+            args: vec!["--env".into(), "$ZED_INPUT_environment".into()],
+            inputs: vec![TaskInput {
+                id: "environment".into(),
+                description: Some("Where to deploy".into()),
+                kind: TaskInputKind::PickString {
+                    options: vec!["staging".into(), "production".into()],
+                    default: None,
+                },
+            }],
+            ..TaskTemplate::default()
+        };
+
+        let resolved_task = task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("Tasks should resolve before the user provides their inputs");
+        assert_eq!(
+            resolved_task.resolved_label,
+            "Deploy to $ZED_INPUT_environment"
+        );
+        assert_eq!(
+            resolved_task
+                .missing_inputs()
+                .map(|input| input.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["environment"]
+        );
+
+        let resolved_task = resolved_task
+            .with_inputs(
+                TEST_ID_BASE,
+                [("environment".to_string(), "staging".to_string())],
+            )
+            .unwrap();
+        assert_eq!(resolved_task.missing_inputs().count(), 0);
+        assert_eq!(resolved_task.resolved_label, "Deploy to staging");
+        let spawn_in_terminal = resolved_task.resolved.unwrap();
+        assert_eq!(spawn_in_terminal.command_label, "deploy --env staging");
+        assert_eq!(
+            spawn_in_terminal.env.get("ZED_INPUT_environment"),
+            Some(&"staging".to_string())
+        );
+    }
+
     #[test]
     fn test_symbol_dependent_tasks() {
         let task_with_all_properties = TaskTemplate {
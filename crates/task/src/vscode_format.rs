@@ -1,9 +1,15 @@
-use anyhow::bail;
+use std::{borrow::Cow, iter};
+
+use anyhow::{bail, Context as _};
 use collections::HashMap;
 use serde::Deserialize;
+use serde_json_lenient::Value;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    DependsOrder, ProblemMatcher, ProblemMatcherDefinition, ProblemPattern, ProblemSeverity,
+    TaskInput, TaskInputKind, TaskTemplate, TaskTemplates, VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -41,6 +47,129 @@ enum Command {
     },
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemMatcher {
+    base: Option<String>,
+    owner: Option<String>,
+    source: Option<String>,
+    severity: Option<String>,
+    file_location: Option<Value>,
+    pattern: Option<Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+    #[serde(default, rename = "loop")]
+    repeat: bool,
+}
+
+impl VsCodeProblemPattern {
+    fn into_zed_format(self) -> ProblemPattern {
+        ProblemPattern {
+            regexp: self.regexp,
+            file: self.file,
+            line: self.line,
+            column: self.column,
+            end_line: self.end_line,
+            end_column: self.end_column,
+            severity: self.severity,
+            code: self.code,
+            message: self.message,
+            repeat: self.repeat,
+        }
+    }
+}
+
+/// Maps the names of VSCode's problem matchers to the Zed's built-in ones that parse the same output.
+fn built_in_problem_matcher_name(name: &str) -> anyhow::Result<&'static str> {
+    Ok(match name {
+        "$rustc" | "$rustc-watch" => "$rustc",
+        "$tsc" | "$tsc-watch" => "$tsc",
+        "$gcc" => "$gcc",
+        "$eslint-compact" => "$eslint-compact",
+        "$eslint-stylish" => "$eslint-stylish",
+        _ => bail!("Encountered unsupported problem matcher `{name}`"),
+    })
+}
+
+fn built_in_problem_matcher(name: &str) -> anyhow::Result<ProblemMatcher> {
+    let zed_name = built_in_problem_matcher_name(name)?;
+    ProblemMatcher::built_in(zed_name)
+        .with_context(|| format!("Missing built-in problem matcher `{zed_name}`"))
+}
+
+fn problem_matcher_from_vscode(
+    problem_matcher: &Value,
+    replacer: &EnvVariableReplacer,
+) -> anyhow::Result<ProblemMatcherDefinition> {
+    if let Value::String(name) = problem_matcher {
+        return Ok(ProblemMatcherDefinition::BuiltIn(
+            built_in_problem_matcher_name(name)?.to_string(),
+        ));
+    }
+    let problem_matcher =
+        serde_json_lenient::from_value::<VsCodeProblemMatcher>(problem_matcher.clone())?;
+
+    let mut zed_problem_matcher = match (problem_matcher.pattern, &problem_matcher.base) {
+        (Some(Value::String(name)), _) => built_in_problem_matcher(&name)?,
+        (Some(Value::Array(patterns)), _) => ProblemMatcher {
+            source: String::new(),
+            base_directory: None,
+            severity: ProblemSeverity::default(),
+            pattern: patterns
+                .into_iter()
+                .map(|pattern| {
+                    Ok(
+                        serde_json_lenient::from_value::<VsCodeProblemPattern>(pattern)?
+                            .into_zed_format(),
+                    )
+                })
+                .collect::<anyhow::Result<_>>()?,
+        },
+        (Some(pattern), _) => ProblemMatcher {
+            source: String::new(),
+            base_directory: None,
+            severity: ProblemSeverity::default(),
+            pattern: vec![
+                serde_json_lenient::from_value::<VsCodeProblemPattern>(pattern)?.into_zed_format(),
+            ],
+        },
+        (None, Some(base)) => built_in_problem_matcher(base)?,
+        (None, None) => bail!("Encountered problem matcher without `base` or `pattern`"),
+    };
+    if let Some(source) = problem_matcher.source.or(problem_matcher.owner) {
+        zed_problem_matcher.source = source;
+    }
+    if zed_problem_matcher.source.is_empty() {
+        zed_problem_matcher.source = "task".to_string();
+    }
+    if let Some(severity) = problem_matcher
+        .severity
+        .as_deref()
+        .and_then(ProblemSeverity::parse)
+    {
+        zed_problem_matcher.severity = severity;
+    }
+    // Either `"absolute"`, `"relative"`, `"autoDetect"`, or an array with one of those and a directory.
+    if let Some(Value::Array(file_location)) = &problem_matcher.file_location {
+        if let Some(Value::String(directory)) = file_location.get(1) {
+            zed_problem_matcher.base_directory = Some(replacer.replace(directory));
+        }
+    }
+    Ok(ProblemMatcherDefinition::Custom(zed_problem_matcher))
+}
+
 type VsCodeEnvVariable = String;
 type ZedEnvVariable = String;
 
@@ -63,6 +192,16 @@ impl EnvVariableReplacer {
                     ret.push_str(default);
                 }
             };
+            // VSCode refers to the task inputs with `${input:<id>}`.
+            if let Some(input_id) = default
+                .strip_prefix(':')
+                .filter(|_| variable_name == "input")
+            {
+                return Some(format!(
+                    "${{{}}}",
+                    VariableName::Input(Cow::Owned(input_id.to_owned()))
+                ));
+            }
             if let Some(substitution) = self.variables.get(variable_name) {
                 // Got a VSCode->Zed hit, perform a substitution
                 let mut name = format!("${{{substitution}");
@@ -83,23 +222,114 @@ impl EnvVariableReplacer {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum VsCodeInput {
+    PromptString {
+        id: String,
+        description: Option<String>,
+        default: Option<String>,
+    },
+    PickString {
+        id: String,
+        description: Option<String>,
+        options: Vec<Value>,
+        default: Option<String>,
+    },
+    Command {
+        id: String,
+        command: String,
+    },
+}
+
+impl VsCodeInput {
+    fn into_zed_format(self) -> anyhow::Result<TaskInput> {
+        Ok(match self {
+            Self::PromptString {
+                id,
+                description,
+                default,
+            } => TaskInput {
+                id,
+                description,
+                kind: TaskInputKind::PromptString { default },
+            },
+            Self::PickString {
+                id,
+                description,
+                options,
+                default,
+            } => TaskInput {
+                id,
+                description,
+                kind: TaskInputKind::PickString {
+                    // Options are either strings or `{ "label": ..., "value": ... }` objects.
+                    options: options
+                        .into_iter()
+                        .map(|option| match option {
+                            Value::String(value) => Ok(value),
+                            Value::Object(mut option) => match option.remove("value") {
+                                Some(Value::String(value)) => Ok(value),
+                                _ => bail!("Encountered `pickString` option without a `value`"),
+                            },
+                            other => bail!("Encountered unsupported `pickString` option: {other}"),
+                        })
+                        .collect::<anyhow::Result<_>>()?,
+                    default,
+                },
+            },
+            // VSCode's commands are not shell commands, but the ones of VSCode and its extensions.
+            Self::Command { id, command } => {
+                bail!("Encountered unsupported command input `{id}` running `{command}`")
+            }
+        })
+    }
+}
+
 impl VsCodeTaskDefinition {
-    fn into_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<TaskTemplate> {
-        if self.other_attributes.contains_key("dependsOn") {
-            bail!("Encountered unsupported `dependsOn` key during deserialization");
+    fn into_zed_format(
+        self,
+        replacer: &EnvVariableReplacer,
+        inputs: &[TaskInput],
+    ) -> anyhow::Result<TaskTemplate> {
+        let depends_on = match self.other_attributes.get("dependsOn") {
+            None => Vec::new(),
+            Some(Value::String(label)) => vec![label.clone()],
+            Some(Value::Array(labels)) => labels
+                .iter()
+                .map(|label| match label {
+                    Value::String(label) => Ok(label.clone()),
+                    _ => bail!("Encountered unsupported `dependsOn` task identifier: {label}"),
+                })
+                .collect::<anyhow::Result<_>>()?,
+            Some(other) => bail!("Encountered unsupported `dependsOn` value: {other}"),
+        };
+        // Unlike Zed, VSCode runs the dependencies in parallel by default.
+        let depends_order = match self.other_attributes.get("dependsOrder") {
+            None => DependsOrder::Parallel,
+            Some(Value::String(order)) if order == "parallel" => DependsOrder::Parallel,
+            Some(Value::String(order)) if order == "sequence" => DependsOrder::Sequence,
+            Some(other) => bail!("Encountered unsupported `dependsOrder` value: {other}"),
+        };
+        // Unsupported problem matchers are skipped, as the task is still useful without them.
+        let problem_matchers = match self.other_attributes.get("problemMatcher") {
+            None => Vec::new(),
+            Some(Value::Array(problem_matchers)) => problem_matchers.iter().collect(),
+            Some(problem_matcher) => vec![problem_matcher],
         }
+        .into_iter()
+        .filter_map(|problem_matcher| {
+            problem_matcher_from_vscode(problem_matcher, replacer).log_err()
+        })
+        .collect();
         // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
-        };
-
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        // as such tasks only run their dependencies.
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
@@ -108,12 +338,26 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            depends_on,
+            depends_order,
+            problem_matchers,
             ..Default::default()
         };
         if let Some(options) = self.options {
             ret.cwd = options.cwd.map(|cwd| replacer.replace(&cwd));
             ret.env = options.env;
         }
+        ret.inputs = inputs
+            .iter()
+            .filter(|input| {
+                let variable = VariableName::Input(Cow::Borrowed(input.id.as_str())).to_string();
+                iter::once(&ret.command)
+                    .chain(&ret.args)
+                    .chain(&ret.cwd)
+                    .any(|value| value.contains(&variable))
+            })
+            .cloned()
+            .collect();
         Ok(ret)
     }
 }
@@ -122,6 +366,8 @@ impl VsCodeTaskDefinition {
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
     tasks: Vec<VsCodeTaskDefinition>,
+    #[serde(default)]
+    inputs: Vec<Value>,
 }
 
 impl TryFrom<VsCodeTaskFile> for TaskTemplates {
@@ -140,10 +386,24 @@ impl TryFrom<VsCodeTaskFile> for TaskTemplates {
                 VariableName::SelectedText.to_string(),
             ),
         ]));
+        let inputs = value
+            .inputs
+            .into_iter()
+            .filter_map(|input| {
+                serde_json_lenient::from_value::<VsCodeInput>(input)
+                    .map_err(anyhow::Error::from)
+                    .and_then(VsCodeInput::into_zed_format)
+                    .log_err()
+            })
+            .collect::<Vec<_>>();
         let templates = value
             .tasks
             .into_iter()
-            .filter_map(|vscode_definition| vscode_definition.into_zed_format(&replacer).log_err())
+            .filter_map(|vscode_definition| {
+                vscode_definition
+                    .into_zed_format(&replacer, &inputs)
+                    .log_err()
+            })
             .collect();
         Ok(Self(templates))
     }
@@ -155,6 +415,7 @@ mod tests {
 
     use crate::{
         vscode_format::{Command, VsCodeTaskDefinition},
+        DependsOrder, ProblemMatcher, ProblemMatcherDefinition, TaskInput, TaskInputKind,
         TaskTemplate, TaskTemplates, VsCodeTaskFile,
     };
 
@@ -184,6 +445,7 @@ mod tests {
         );
         assert_eq!(replacer.replace("${PATH}"), "${PATH}");
         assert_eq!(replacer.replace("${PATH:food}"), "${PATH:food}");
+        assert_eq!(replacer.replace("${input:target}"), "${ZED_INPUT_target}");
         // And now, the actual replacing
         let replacer = EnvVariableReplacer::new(HashMap::from_iter([(
             "PATH".to_owned(),
@@ -257,6 +519,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcherDefinition::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -268,18 +531,21 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matchers: vec![ProblemMatcherDefinition::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matchers: vec![ProblemMatcherDefinition::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcherDefinition::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -360,32 +626,170 @@ mod tests {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matchers: vec![ProblemMatcherDefinition::Custom(ProblemMatcher {
+                    base_directory: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..ProblemMatcher::built_in("$tsc").unwrap()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![ProblemMatcherDefinition::Custom(ProblemMatcher {
+                    base_directory: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..ProblemMatcher::built_in("$tsc").unwrap()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcherDefinition::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcherDefinition::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![ProblemMatcherDefinition::Custom(ProblemMatcher {
+                    base_directory: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..ProblemMatcher::built_in("$tsc").unwrap()
+                })],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                depends_order: DependsOrder::Parallel,
+                problem_matchers: vec![ProblemMatcherDefinition::BuiltIn("$rustc".to_string())],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                depends_order: DependsOrder::Parallel,
+                problem_matchers: vec![ProblemMatcherDefinition::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
         ];
         let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn can_deserialize_depends_on() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "build",
+                        "dependsOn": "lint",
+                        "dependsOrder": "sequence",
+                        "type": "shell",
+                        "command": "make"
+                    },
+                    {
+                        "label": "object identifiers",
+                        "dependsOn": [{ "type": "npm", "script": "watch" }]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![TaskTemplate {
+                label: "build".to_string(),
+                command: "make".to_string(),
+                depends_on: vec!["lint".to_string()],
+                depends_order: DependsOrder::Sequence,
+                ..Default::default()
+            }],
+            "Tasks that depend on other tasks by their identifiers are not supported"
+        );
+    }
+
+    #[test]
+    fn can_deserialize_inputs() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "deploy",
+                        "type": "shell",
+                        "command": "deploy ${input:target}",
+                        "args": ["--message", "${input:message}"]
+                    },
+                    {
+                        "label": "build",
+                        "type": "shell",
+                        "command": "make"
+                    }
+                ],
+                "inputs": [
+                    {
+                        "id": "target",
+                        "type": "pickString",
+                        "description": "Where to deploy",
+                        "options": ["staging", { "label": "Production", "value": "production" }],
+                        "default": "staging"
+                    },
+                    {
+                        "id": "message",
+                        "type": "promptString"
+                    },
+                    {
+                        "id": "pickFile",
+                        "type": "command",
+                        "command": "extension.pickFile"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![
+                TaskTemplate {
+                    label: "deploy".to_string(),
+                    command: "deploy ${ZED_INPUT_target}".to_string(),
+                    args: vec!["--message".to_string(), "${ZED_INPUT_message}".to_string()],
+                    inputs: vec![
+                        TaskInput {
+                            id: "target".to_string(),
+                            description: Some("Where to deploy".to_string()),
+                            kind: TaskInputKind::PickString {
+                                options: vec!["staging".to_string(), "production".to_string()],
+                                default: Some("staging".to_string()),
+                            },
+                        },
+                        TaskInput {
+                            id: "message".to_string(),
+                            description: None,
+                            kind: TaskInputKind::PromptString { default: None },
+                        },
+                    ],
+                    ..Default::default()
+                },
+                TaskTemplate {
+                    label: "build".to_string(),
+                    command: "make".to_string(),
+                    ..Default::default()
+                },
+            ],
+            "Only the inputs referenced by the task are added to it, and command inputs are skipped"
+        );
+    }
 }
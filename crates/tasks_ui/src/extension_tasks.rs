@@ -0,0 +1,464 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use collections::{HashMap, HashSet};
+use extension::{Extension, ExtensionHostProxy, ExtensionTaskProviderProxy, WorktreeDelegate};
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task, WeakEntity,
+};
+use language::LspAdapterDelegate;
+use language_extension::WorktreeDelegateAdapter;
+use parking_lot::Mutex;
+use project::{lsp_store::LocalLspAdapterDelegate, Project, TaskSourceKind, WorktreeId};
+use task::TaskTemplate;
+use util::ResultExt as _;
+use workspace::Workspace;
+
+/// How long to wait after the files that the task providers read change
+/// before asking the task providers for the worktree's tasks again.
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub(crate) fn init(cx: &mut App) {
+    let providers = cx.new(|_| ExtensionTaskProviders::default());
+    cx.set_global(GlobalExtensionTaskProviders(providers.clone()));
+
+    let proxy = ExtensionHostProxy::default_global(cx);
+    proxy.register_task_provider_proxy(ExtensionTaskProvidersProxy);
+
+    cx.observe_new(move |workspace: &mut Workspace, _, cx| {
+        let project = workspace.project().clone();
+        providers.update(cx, |providers, cx| providers.add_project(project, cx));
+    })
+    .detach();
+}
+
+struct GlobalExtensionTaskProviders(Entity<ExtensionTaskProviders>);
+
+impl Global for GlobalExtensionTaskProviders {}
+
+#[derive(Clone)]
+struct TaskProvider {
+    extension: Arc<dyn Extension>,
+    provider_id: Arc<str>,
+}
+
+/// Keeps the task inventories of the open projects up to date with the task
+/// templates that extensions' task providers contribute to their worktrees.
+#[derive(Default)]
+struct ExtensionTaskProviders {
+    providers: Vec<TaskProvider>,
+    projects: HashMap<EntityId, ProjectTasks>,
+}
+
+struct ProjectTasks {
+    project: WeakEntity<Project>,
+    worktrees: HashMap<WorktreeId, WorktreeTasks>,
+    _subscriptions: [Subscription; 2],
+}
+
+struct WorktreeTasks {
+    /// The worktree files the task providers read when they last listed the worktree's tasks.
+    /// Only changes to these files make the providers list the tasks again.
+    read_paths: Arc<Mutex<HashSet<PathBuf>>>,
+    refresh_task: Task<()>,
+}
+
+impl ExtensionTaskProviders {
+    fn add_project(&mut self, project: Entity<Project>, cx: &mut Context<Self>) {
+        let project_id = project.entity_id();
+        if self.projects.contains_key(&project_id) {
+            return;
+        }
+
+        let subscriptions = [
+            cx.subscribe(&project, move |this, _, event, cx| match event {
+                project::Event::WorktreeAdded(worktree_id) => {
+                    this.refresh_worktree(project_id, *worktree_id, false, cx)
+                }
+                project::Event::WorktreeRemoved(worktree_id) => {
+                    if let Some(project_tasks) = this.projects.get_mut(&project_id) {
+                        project_tasks.worktrees.remove(worktree_id);
+                    }
+                }
+                project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                    let read_paths_changed = this
+                        .projects
+                        .get(&project_id)
+                        .and_then(|project_tasks| project_tasks.worktrees.get(worktree_id))
+                        .is_some_and(|worktree_tasks| {
+                            let read_paths = worktree_tasks.read_paths.lock();
+                            changes
+                                .iter()
+                                .any(|(path, _, _)| read_paths.contains(path.as_ref()))
+                        });
+                    if read_paths_changed {
+                        this.refresh_worktree(project_id, *worktree_id, true, cx)
+                    }
+                }
+                _ => {}
+            }),
+            cx.observe_release(&project, move |this, _, _| {
+                this.projects.remove(&project_id);
+            }),
+        ];
+        self.projects.insert(
+            project_id,
+            ProjectTasks {
+                project: project.downgrade(),
+                worktrees: HashMap::default(),
+                _subscriptions: subscriptions,
+            },
+        );
+        self.refresh_project(project_id, cx);
+    }
+
+    fn register_provider(&mut self, provider: TaskProvider, cx: &mut Context<Self>) {
+        let extension_id = provider.extension.manifest().id.clone();
+        self.providers.retain(|existing| {
+            existing.extension.manifest().id != extension_id
+                || existing.provider_id != provider.provider_id
+        });
+        self.providers.push(provider);
+
+        let project_ids = self.projects.keys().copied().collect::<Vec<_>>();
+        for project_id in project_ids {
+            self.refresh_project(project_id, cx);
+        }
+    }
+
+    fn remove_provider(&mut self, extension_id: &str, provider_id: &str, cx: &mut Context<Self>) {
+        self.providers.retain(|provider| {
+            provider.extension.manifest().id.as_ref() != extension_id
+                || provider.provider_id.as_ref() != provider_id
+        });
+
+        for project_tasks in self.projects.values() {
+            let Some(project) = project_tasks.project.upgrade() else {
+                continue;
+            };
+            let inventory = project
+                .read(cx)
+                .task_store()
+                .read(cx)
+                .task_inventory()
+                .cloned();
+            if let Some(inventory) = inventory {
+                inventory.update(cx, |inventory, _| {
+                    inventory.remove_extension_tasks(extension_id, provider_id)
+                });
+            }
+        }
+    }
+
+    fn refresh_project(&mut self, project_id: EntityId, cx: &mut Context<Self>) {
+        let Some(project) = self
+            .projects
+            .get(&project_id)
+            .and_then(|project_tasks| project_tasks.project.upgrade())
+        else {
+            return;
+        };
+        let worktree_ids = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).id())
+            .collect::<Vec<_>>();
+        for worktree_id in worktree_ids {
+            self.refresh_worktree(project_id, worktree_id, false, cx);
+        }
+    }
+
+    fn refresh_worktree(
+        &mut self,
+        project_id: EntityId,
+        worktree_id: WorktreeId,
+        debounce: bool,
+        cx: &mut Context<Self>,
+    ) {
+        if self.providers.is_empty() {
+            return;
+        }
+        let Some(project_tasks) = self.projects.get_mut(&project_id) else {
+            return;
+        };
+
+        let project = project_tasks.project.clone();
+        let providers = self.providers.clone();
+        let read_paths = project_tasks
+            .worktrees
+            .get(&worktree_id)
+            .map(|worktree_tasks| worktree_tasks.read_paths.clone())
+            .unwrap_or_default();
+        let refresh_task = cx.spawn({
+            let read_paths = read_paths.clone();
+            |_, mut cx| async move {
+                if debounce {
+                    cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+                }
+
+                let Some((inventory, worktree_root, delegate)) = project
+                    .update(&mut cx, |project, cx| {
+                        if !project.is_local() {
+                            return None;
+                        }
+                        let inventory = project.task_store().read(cx).task_inventory().cloned()?;
+                        let worktree = project.worktree_for_id(worktree_id, cx)?;
+                        if !worktree.read(cx).is_visible() {
+                            return None;
+                        }
+                        let worktree_root = worktree.read(cx).abs_path().to_path_buf();
+                        let http_client = project.client().http_client().clone();
+                        let delegate = project.lsp_store().update(cx, |_, cx| {
+                            LocalLspAdapterDelegate::new(
+                                project.languages().clone(),
+                                project.environment(),
+                                cx.weak_entity(),
+                                &worktree,
+                                http_client,
+                                project.fs().clone(),
+                                cx,
+                            ) as Arc<dyn LspAdapterDelegate>
+                        });
+                        Some((inventory, worktree_root, delegate))
+                    })
+                    .ok()
+                    .flatten()
+                else {
+                    return;
+                };
+
+                read_paths.lock().clear();
+                let delegate: Arc<dyn WorktreeDelegate> = Arc::new(RecordingWorktreeDelegate {
+                    delegate: WorktreeDelegateAdapter(delegate),
+                    read_paths,
+                });
+                for provider in &providers {
+                    let manifest = provider.extension.manifest();
+                    let languages = manifest
+                        .task_providers
+                        .get(&provider.provider_id)
+                        .map(|entry| entry.languages.clone())
+                        .unwrap_or_default();
+                    let source_kind = TaskSourceKind::Extension {
+                        extension_id: manifest.id.to_string().into(),
+                        extension_name: manifest.name.clone().into(),
+                        provider_id: provider.provider_id.to_string().into(),
+                        worktree: worktree_id,
+                    };
+                    let templates = provider
+                        .extension
+                        .tasks_for_worktree(provider.provider_id.clone(), delegate.clone())
+                        .await
+                        .with_context(|| {
+                            format!(
+                                "failed to get tasks from task provider {:?} of extension {:?}",
+                                provider.provider_id, manifest.id
+                            )
+                        })
+                        .log_err()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|template| task_template(template, &worktree_root))
+                        .collect();
+
+                    if inventory
+                        .update(&mut cx, |inventory, _| {
+                            inventory.update_extension_tasks(source_kind, languages, templates)
+                        })
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        });
+        project_tasks.worktrees.insert(
+            worktree_id,
+            WorktreeTasks {
+                read_paths,
+                refresh_task,
+            },
+        );
+    }
+}
+
+fn task_template(template: extension::TaskTemplate, worktree_root: &Path) -> TaskTemplate {
+    let cwd = match template.cwd {
+        Some(cwd) => worktree_root.join(cwd),
+        None => worktree_root.to_path_buf(),
+    };
+    TaskTemplate {
+        label: template.label,
+        command: template.command,
+        args: template.args,
+        env: template.env.into_iter().collect(),
+        cwd: Some(cwd.to_string_lossy().into_owned()),
+        tags: template.tags,
+        ..TaskTemplate::default()
+    }
+}
+
+struct ExtensionTaskProvidersProxy;
+
+impl ExtensionTaskProviderProxy for ExtensionTaskProvidersProxy {
+    fn register_task_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        cx: &mut App,
+    ) {
+        let providers = cx.global::<GlobalExtensionTaskProviders>().0.clone();
+        providers.update(cx, |providers, cx| {
+            providers.register_provider(
+                TaskProvider {
+                    extension,
+                    provider_id,
+                },
+                cx,
+            )
+        });
+    }
+
+    fn remove_task_provider(&self, extension_id: &Arc<str>, provider_id: &Arc<str>, cx: &mut App) {
+        let providers = cx.global::<GlobalExtensionTaskProviders>().0.clone();
+        providers.update(cx, |providers, cx| {
+            providers.remove_provider(extension_id, provider_id, cx)
+        });
+    }
+}
+
+/// A [`WorktreeDelegate`] that records the files the task providers read.
+struct RecordingWorktreeDelegate {
+    delegate: WorktreeDelegateAdapter,
+    read_paths: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+#[async_trait]
+impl WorktreeDelegate for RecordingWorktreeDelegate {
+    fn id(&self) -> u64 {
+        self.delegate.id()
+    }
+
+    fn root_path(&self) -> String {
+        self.delegate.root_path()
+    }
+
+    async fn read_text_file(&self, path: PathBuf) -> Result<String> {
+        self.read_paths.lock().insert(path.clone());
+        self.delegate.read_text_file(path).await
+    }
+
+    async fn which(&self, binary_name: String) -> Option<String> {
+        self.delegate.which(binary_name).await
+    }
+
+    async fn shell_env(&self) -> Vec<(String, String)> {
+        self.delegate.shell_env().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use extension::FakeExtension;
+    use futures::FutureExt as _;
+    use gpui::{AppContext as _, TestAppContext};
+    use project::FakeFs;
+    use serde_json::json;
+
+    use super::*;
+    use crate::tests::init_test;
+
+    #[gpui::test]
+    async fn test_refresh_when_read_files_change(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "tasks.txt": "build",
+                "src": { "main.rs": "" },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+        // The task provider lists a task for each line of `tasks.txt`.
+        let extension = Arc::new(FakeExtension::new("fake", |manifest| {
+            manifest
+                .task_providers
+                .insert("tasks".into(), Default::default());
+        }));
+        let provider_calls = Arc::new(AtomicUsize::new(0));
+        extension.on_tasks_for_worktree({
+            let provider_calls = provider_calls.clone();
+            move |_, worktree| {
+                provider_calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let tasks = worktree.read_text_file("tasks.txt".into()).await?;
+                    Ok(tasks
+                        .lines()
+                        .map(|label| extension::TaskTemplate {
+                            label: label.to_string(),
+                            command: "echo".to_string(),
+                            args: Vec::new(),
+                            env: Vec::new(),
+                            cwd: None,
+                            tags: Vec::new(),
+                        })
+                        .collect())
+                }
+                .boxed()
+            }
+        });
+
+        let providers = cx.new(|_| ExtensionTaskProviders::default());
+        providers.update(cx, |providers, cx| {
+            providers.add_project(project.clone(), cx);
+            providers.register_provider(
+                TaskProvider {
+                    extension: extension.clone(),
+                    provider_id: "tasks".into(),
+                },
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        assert_eq!(provider_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(extension_task_labels(&project, cx), ["build"]);
+
+        // Changes to the files the provider did not read leave the tasks alone.
+        fs.insert_file("/dir/src/main.rs", b"fn main() {}".to_vec())
+            .await;
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(provider_calls.load(Ordering::SeqCst), 1);
+
+        fs.insert_file("/dir/tasks.txt", b"build\ntest".to_vec())
+            .await;
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(provider_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(extension_task_labels(&project, cx), ["build", "test"]);
+    }
+
+    fn extension_task_labels(project: &Entity<Project>, cx: &mut TestAppContext) -> Vec<String> {
+        project.update(cx, |project, cx| {
+            let worktree_id = project.visible_worktrees(cx).next().unwrap().read(cx).id();
+            let inventory = project.task_store().read(cx).task_inventory().unwrap();
+            inventory
+                .read(cx)
+                .list_tasks(None, None, Some(worktree_id), cx)
+                .into_iter()
+                .filter(|(source_kind, _)| matches!(source_kind, TaskSourceKind::Extension { .. }))
+                .map(|(_, template)| template.label)
+                .collect()
+        })
+    }
+}
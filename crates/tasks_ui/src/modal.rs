@@ -13,7 +13,8 @@ use task::{ResolvedTask, RevealTarget, TaskContext, TaskTemplate};
 use ui::{
     div, h_flex, v_flex, ActiveTheme, Button, ButtonCommon, ButtonSize, Clickable, Color,
     FluentBuilder as _, Icon, IconButton, IconButtonShape, IconName, IconSize, IntoElement,
-    KeyBinding, LabelSize, ListItem, ListItemSpacing, RenderOnce, Toggleable, Tooltip,
+    KeyBinding, Label, LabelCommon, LabelSize, ListItem, ListItemSpacing, RenderOnce, Toggleable,
+    Tooltip,
 };
 use util::ResultExt;
 use workspace::{tasks::schedule_resolved_task, ModalView, Workspace};
@@ -369,8 +370,13 @@ impl PickerDelegate for TasksModalDelegate {
             TaskSourceKind::Language { name } => file_icons::FileIcons::get(cx)
                 .get_icon_for_type(&name.to_lowercase(), cx)
                 .map(Icon::from_path),
+            TaskSourceKind::Extension { .. } => Some(Icon::new(IconName::Blocks)),
         }
         .map(|icon| icon.color(Color::Muted).size(IconSize::Small));
+        let source_label = match source_kind {
+            TaskSourceKind::Extension { extension_name, .. } => Some(extension_name.clone()),
+            _ => None,
+        };
         let history_run_icon = if Some(ix) <= self.divider_index {
             Some(
                 Icon::new(IconName::HistoryRerun)
@@ -430,7 +436,18 @@ impl PickerDelegate for TasksModalDelegate {
                     item
                 })
                 .toggle_state(selected)
-                .child(highlighted_location.render(window, cx)),
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(highlighted_location.render(window, cx))
+                        .when_some(source_label, |row, source_label| {
+                            row.child(
+                                Label::new(source_label)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                        }),
+                ),
         )
     }
 
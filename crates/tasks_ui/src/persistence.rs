@@ -0,0 +1,37 @@
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    pub static ref TASKS_DB: TasksDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE task_inputs (
+                workspace_id INTEGER,
+                input_id TEXT NOT NULL,
+                value TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, input_id),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl TasksDb {
+    query! {
+        pub fn get_task_input(workspace_id: WorkspaceId, input_id: String) -> Result<Option<String>> {
+            SELECT value
+            FROM task_inputs
+            WHERE workspace_id = ? AND input_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_task_input(
+            workspace_id: WorkspaceId,
+            input_id: String,
+            value: String
+        ) -> Result<()> {
+            INSERT OR REPLACE INTO task_inputs(workspace_id, input_id, value)
+            VALUES (?, ?, ?)
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use futures::channel::oneshot;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    rems, App, AppContext as _, Context, DismissEvent, Entity, EventEmitter, Focusable,
+    ParentElement, Render, SharedString, Styled, Subscription, Task, Window,
+};
+use picker::{highlighted_match_with_paths::HighlightedMatch, Picker, PickerDelegate};
+use project::TaskSourceKind;
+use task::{ResolvedTask, TaskInput, TaskInputKind};
+use ui::{v_flex, Color, IntoElement, ListItem, ListItemSpacing, RenderOnce, Toggleable};
+use util::{command::new_smol_command, ResultExt};
+use workspace::{tasks::schedule_resolved_task_with_inputs, ModalView, Workspace};
+
+use crate::persistence::TASKS_DB;
+
+/// Asks the user for the values of the inputs one by one, then schedules the task with them.
+/// The inputs may belong to the task or to the tasks it depends on.
+/// The values are remembered per workspace, and suggested the next time the same input is asked for.
+pub(crate) fn prompt_task_inputs(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    task: ResolvedTask,
+    missing_inputs: Vec<TaskInput>,
+    omit_history: bool,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let workspace_id = workspace.database_id();
+    let is_local = workspace.project().read(cx).is_local();
+    let (cwd, env) = task
+        .resolved
+        .as_ref()
+        .map(|resolved| (resolved.cwd.clone(), resolved.env.clone()))
+        .unwrap_or_default();
+
+    cx.spawn_in(window, |workspace, mut cx| async move {
+        let result = async {
+            let mut input_values = Vec::with_capacity(missing_inputs.len());
+            for input in missing_inputs {
+                let last_value = workspace_id.and_then(|workspace_id| {
+                    TASKS_DB
+                        .get_task_input(workspace_id, input.id.clone())
+                        .log_err()
+                        .flatten()
+                });
+                let (options, default) = match &input.kind {
+                    TaskInputKind::PromptString { default } => {
+                        (None, last_value.or_else(|| default.clone()))
+                    }
+                    TaskInputKind::PickString { options, default } => (
+                        Some(options.clone()),
+                        last_value.or_else(|| default.clone()),
+                    ),
+                    TaskInputKind::Command { command, args } => {
+                        anyhow::ensure!(
+                            is_local,
+                            "Task input `{}` runs a command, unsupported in remote projects",
+                            input.id
+                        );
+                        let mut child = new_smol_command(command);
+                        child.args(args).envs(&env);
+                        if let Some(cwd) = &cwd {
+                            child.current_dir(cwd);
+                        }
+                        let output = child.output().await.with_context(|| {
+                            format!("running command `{command}` of task input `{}`", input.id)
+                        })?;
+                        anyhow::ensure!(
+                            output.status.success(),
+                            "Command `{command}` of task input `{}` failed: {}",
+                            input.id,
+                            String::from_utf8_lossy(&output.stderr).trim()
+                        );
+                        let mut lines = String::from_utf8_lossy(&output.stdout)
+                            .lines()
+                            .map(str::trim)
+                            .filter(|line| !line.is_empty())
+                            .map(ToOwned::to_owned)
+                            .collect::<Vec<_>>();
+                        if lines.len() <= 1 {
+                            input_values.push((input.id, lines.pop().unwrap_or_default()));
+                            continue;
+                        }
+                        (Some(lines), last_value)
+                    }
+                };
+
+                let (value_tx, value_rx) = oneshot::channel();
+                workspace.update_in(&mut cx, |workspace, window, cx| {
+                    workspace.toggle_modal(window, cx, |window, cx| {
+                        TaskInputModal::new(input.clone(), options, default, value_tx, window, cx)
+                    })
+                })?;
+                // The modal got dismissed without a value, so the task is not spawned.
+                let Ok(value) = value_rx.await else {
+                    return Ok(None);
+                };
+                input_values.push((input.id, value));
+            }
+            anyhow::Ok(Some(input_values))
+        }
+        .await;
+
+        match result {
+            Ok(Some(input_values)) => {
+                if let Some(workspace_id) = workspace_id {
+                    for (input_id, value) in &input_values {
+                        TASKS_DB
+                            .save_task_input(workspace_id, input_id.clone(), value.clone())
+                            .await
+                            .log_err();
+                    }
+                }
+                workspace.update(&mut cx, |workspace, cx| {
+                    schedule_resolved_task_with_inputs(
+                        workspace,
+                        task_source_kind,
+                        task,
+                        input_values,
+                        omit_history,
+                        cx,
+                    );
+                })
+            }
+            Ok(None) => Ok(()),
+            Err(error) => workspace.update(&mut cx, |workspace, cx| {
+                workspace.show_error(&error, cx);
+            }),
+        }
+    })
+    .detach_and_log_err(cx);
+}
+
+/// A modal asking the user for the value of a single task input.
+pub(crate) struct TaskInputModal {
+    picker: Entity<Picker<TaskInputModalDelegate>>,
+    _subscription: Subscription,
+}
+
+impl TaskInputModal {
+    fn new(
+        input: TaskInput,
+        options: Option<Vec<String>>,
+        default: Option<String>,
+        value_tx: oneshot::Sender<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let picker = cx.new(|cx| {
+            let delegate = TaskInputModalDelegate::new(input, options, default.clone(), value_tx);
+            let is_free_text = delegate.options.is_none();
+            let picker = Picker::uniform_list(delegate, window, cx);
+            if let Some(default) = default.filter(|_| is_free_text) {
+                picker.set_query(default, window, cx);
+            }
+            picker
+        });
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl Render for TaskInputModal {
+    fn render(&mut self, _window: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("TaskInputModal")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl EventEmitter<DismissEvent> for TaskInputModal {}
+
+impl Focusable for TaskInputModal {
+    fn focus_handle(&self, cx: &App) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for TaskInputModal {}
+
+pub(crate) struct TaskInputModalDelegate {
+    input: TaskInput,
+    /// Values to pick from, or `None` if the user types the value in.
+    options: Option<Vec<String>>,
+    default: Option<String>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+    placeholder_text: Arc<str>,
+    value_tx: Option<oneshot::Sender<String>>,
+}
+
+impl TaskInputModalDelegate {
+    fn new(
+        input: TaskInput,
+        options: Option<Vec<String>>,
+        default: Option<String>,
+        value_tx: oneshot::Sender<String>,
+    ) -> Self {
+        let placeholder_text = Arc::from(
+            input
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("Value of the task input `{}`", input.id)),
+        );
+        Self {
+            input,
+            options,
+            default,
+            matches: Vec::new(),
+            selected_index: 0,
+            query: String::new(),
+            placeholder_text,
+            value_tx: Some(value_tx),
+        }
+    }
+}
+
+impl PickerDelegate for TaskInputModalDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _: &mut App) -> Arc<str> {
+        self.placeholder_text.clone()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _: &mut App) -> SharedString {
+        if self.options.is_some() {
+            "No matching options".into()
+        } else {
+            format!("Press enter to use the value for `{}`", self.input.id).into()
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(options) = self.options.as_ref() else {
+            self.query = query;
+            return Task::ready(());
+        };
+        let candidates = options
+            .iter()
+            .enumerate()
+            .map(|(id, option)| StringMatchCandidate::new(id, option))
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, move |picker, mut cx| async move {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                true,
+                1000,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    // Until the user starts typing, preselect the last used or the default option.
+                    delegate.selected_index = if query.is_empty() {
+                        delegate
+                            .default
+                            .as_ref()
+                            .and_then(|default| {
+                                matches.iter().position(|hit| &hit.string == default)
+                            })
+                            .unwrap_or(0)
+                    } else {
+                        0
+                    };
+                    delegate.matches = matches;
+                    delegate.query = query;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let value = if self.options.is_some() {
+            match self.matches.get(self.selected_index) {
+                Some(hit) => hit.string.clone(),
+                None => return,
+            }
+        } else {
+            self.query.clone()
+        };
+        if let Some(value_tx) = self.value_tx.take() {
+            value_tx.send(value).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let highlighted_option = HighlightedMatch {
+            text: hit.string.clone(),
+            highlight_positions: hit.positions.clone(),
+            char_count: hit.string.chars().count(),
+            color: Color::Default,
+        };
+        Some(
+            ListItem::new(SharedString::from(format!("task-input-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(highlighted_option.render(window, cx)),
+        )
+    }
+}
@@ -7,15 +7,41 @@ use task::{RevealTarget, TaskId};
 use workspace::tasks::schedule_task;
 use workspace::{tasks::schedule_resolved_task, Workspace};
 
+mod extension_tasks;
 mod modal;
+mod persistence;
 mod settings;
+mod task_inputs;
 
 pub use modal::{Rerun, Spawn};
 
 pub fn init(cx: &mut App) {
     settings::TaskSettings::register(cx);
+    extension_tasks::init(cx);
     cx.observe_new(
-        |workspace: &mut Workspace, _window: Option<&mut Window>, _: &mut Context<Workspace>| {
+        |workspace: &mut Workspace, window: Option<&mut Window>, cx: &mut Context<Workspace>| {
+            if let Some(window) = window {
+                cx.subscribe_in(&cx.entity(), window, |workspace, _, event, window, cx| {
+                    if let workspace::Event::PromptTaskInputs {
+                        task_source_kind,
+                        task,
+                        inputs,
+                        omit_history,
+                    } = event
+                    {
+                        task_inputs::prompt_task_inputs(
+                            workspace,
+                            task_source_kind.clone(),
+                            task.as_ref().clone(),
+                            inputs.clone(),
+                            *omit_history,
+                            window,
+                            cx,
+                        );
+                    }
+                })
+                .detach();
+            }
             workspace
                 .register_action(spawn_task_or_modal)
                 .register_action(move |workspace, action: &modal::Rerun, window, cx| {
@@ -0,0 +1,723 @@
+//! Shell integration: the shell marks its prompts, commands and their output with
+//! [OSC 133](https://gitlab.freedesktop.org/Per_Bothner/specifications/blob/master/proposals/semantic-prompts.md)
+//! (FinalTerm) sequences, which lets the terminal know where each command starts and ends and how it exited.
+//!
+//! Alacritty ignores these sequences, so they are picked out of the PTY output before it gets parsed.
+//! Marks are positioned by the number of line feeds the shell has written to the primary screen
+//! before them, i.e. by logical line. Logical lines are mapped onto grid lines through the soft wrap
+//! flags of the grid, so marks stay in place when long lines wrap, when the terminal is resized and
+//! reflows its content, and when the scrollback history gets trimmed.
+
+use std::{
+    collections::VecDeque,
+    io,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    grid::Dimensions,
+    index::Line,
+    term::cell::Flags,
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+    Term,
+};
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use polling::{Event as PollingEvent, PollMode, Poller};
+use task::Shell;
+
+use crate::ZedListener;
+
+const BASH_SCRIPT: &str = include_str!("shell_integration/zed.bash");
+const ZSH_SCRIPT: &str = include_str!("shell_integration/zed.zsh");
+const FISH_SCRIPT: &str = include_str!("shell_integration/zed.fish");
+
+/// How many commands are remembered per terminal.
+const MAX_COMMANDS: usize = 1000;
+/// OSC sequences longer than this are not scanned for marks.
+const MAX_MARK_LEN: usize = 16 * 1024;
+/// CSI sequences longer than this are not scanned for alternate screen switches.
+const MAX_CSI_LEN: usize = 64;
+
+/// A command run in the terminal, as reported by the shell integration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// The line the prompt of the command starts at.
+    pub prompt_line: Line,
+    /// The first line of the command output, once the command is executed.
+    pub output_start: Option<Line>,
+    /// The line after the command output, once the command finishes.
+    pub output_end: Option<Line>,
+    /// The exit code of the command, if it finished and the shell reported it.
+    pub exit_code: Option<i32>,
+    /// The command line, if the shell reported it.
+    pub command: Option<String>,
+}
+
+impl CommandBlock {
+    pub fn is_finished(&self) -> bool {
+        self.output_end.is_some()
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.exit_code.is_some_and(|exit_code| exit_code != 0)
+    }
+}
+
+/// Modifies the shell and its environment so that the shell loads the integration script, if the shell is supported.
+/// Returns `false` if the shell is not supported.
+pub fn inject(shell: &mut Shell, env: &mut HashMap<String, String>) -> Result<bool> {
+    if cfg!(windows) {
+        return Ok(false);
+    }
+
+    let (program, has_args) = match shell {
+        Shell::System => match std::env::var("SHELL") {
+            Ok(program) => (program, false),
+            Err(_) => return Ok(false),
+        },
+        Shell::Program(program) => (program.clone(), false),
+        Shell::WithArguments { program, args, .. } => (program.clone(), !args.is_empty()),
+    };
+    let shell_name = Path::new(&program)
+        .file_name()
+        .map(|name| name.to_string_lossy().trim_start_matches('-').to_string())
+        .unwrap_or_default();
+    let scripts_dir = paths::shell_integration_dir();
+
+    match shell_name.as_str() {
+        // Custom arguments might conflict with the init file, or make the shell non-interactive.
+        "bash" if !has_args => {
+            let script_path = scripts_dir.join("zed.bash");
+            write_script(&script_path, BASH_SCRIPT)?;
+            *shell = Shell::WithArguments {
+                program,
+                args: vec![
+                    "--init-file".to_string(),
+                    script_path.to_string_lossy().to_string(),
+                ],
+                title_override: None,
+            };
+        }
+        "zsh" => {
+            let zdotdir = scripts_dir.join("zsh");
+            write_script(&zdotdir.join(".zshenv"), ZSH_SCRIPT)?;
+            if let Some(user_zdotdir) = env
+                .get("ZDOTDIR")
+                .cloned()
+                .or_else(|| std::env::var("ZDOTDIR").ok())
+            {
+                env.insert("ZED_USER_ZDOTDIR".to_string(), user_zdotdir);
+            }
+            env.insert("ZDOTDIR".to_string(), zdotdir.to_string_lossy().to_string());
+        }
+        "fish" => {
+            let data_dir = scripts_dir.join("fish_data");
+            write_script(
+                &data_dir
+                    .join("fish")
+                    .join("vendor_conf.d")
+                    .join("zed-shell-integration.fish"),
+                FISH_SCRIPT,
+            )?;
+            let user_data_dirs = env
+                .get("XDG_DATA_DIRS")
+                .cloned()
+                .or_else(|| std::env::var("XDG_DATA_DIRS").ok());
+            // Without `XDG_DATA_DIRS`, fish looks into the default data directories, which need to be kept.
+            let data_dirs = format!(
+                "{}:{}",
+                data_dir.to_string_lossy(),
+                user_data_dirs
+                    .as_deref()
+                    .unwrap_or("/usr/local/share:/usr/share")
+            );
+            if let Some(user_data_dirs) = user_data_dirs {
+                env.insert("ZED_USER_XDG_DATA_DIRS".to_string(), user_data_dirs);
+            }
+            env.insert("XDG_DATA_DIRS".to_string(), data_dirs);
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn write_script(path: &Path, contents: &str) -> Result<()> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("creating shell integration directory {parent:?}"))?;
+    }
+    std::fs::write(path, contents)
+        .with_context(|| format!("writing shell integration script {path:?}"))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ShellMark {
+    /// `OSC 133 ; A`
+    PromptStart,
+    /// `OSC 133 ; B`
+    CommandStart,
+    /// `OSC 133 ; C`
+    CommandExecuted,
+    /// `OSC 133 ; D [; <exit code>]`
+    CommandFinished { exit_code: Option<i32> },
+    /// `OSC 633 ; E ; <command line>`, as sent by the VS Code shell integration scripts.
+    CommandLine(String),
+}
+
+/// Marks found in the PTY output, waiting to be picked up by the terminal.
+#[derive(Debug, Default)]
+pub(crate) struct ShellMarks {
+    /// The number of line feeds written to the primary screen so far.
+    line_feeds: u64,
+    /// Whether full screen applications are drawing on the alternate screen, whose line feeds
+    /// don't move the primary screen.
+    alternate_screen: bool,
+    pending: Vec<(u64, ShellMark)>,
+}
+
+impl ShellMarks {
+    fn line_feed(&mut self) {
+        if !self.alternate_screen {
+            self.line_feeds += 1;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Ground,
+    Escape,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+/// Finds the OSC 133 marks in the PTY output, which may be split in arbitrary chunks.
+/// Also follows the switches to and from the alternate screen.
+#[derive(Debug, Default)]
+pub(crate) struct MarkScanner {
+    state: ScanState,
+    sequence: Vec<u8>,
+    /// Whether the current OSC sequence is not a mark, or the current sequence is too long.
+    ignore_sequence: bool,
+}
+
+impl MarkScanner {
+    pub(crate) fn scan(&mut self, bytes: &[u8], marks: &mut ShellMarks) {
+        for &byte in bytes {
+            self.advance(byte, marks);
+        }
+    }
+
+    fn advance(&mut self, byte: u8, marks: &mut ShellMarks) {
+        match self.state {
+            ScanState::Ground => match byte {
+                b'\n' => marks.line_feed(),
+                0x1b => self.state = ScanState::Escape,
+                _ => {}
+            },
+            ScanState::Escape => match byte {
+                b']' => {
+                    self.sequence.clear();
+                    self.ignore_sequence = false;
+                    self.state = ScanState::Osc;
+                }
+                b'[' => {
+                    self.sequence.clear();
+                    self.ignore_sequence = false;
+                    self.state = ScanState::Csi;
+                }
+                0x1b => {}
+                _ => {
+                    self.state = ScanState::Ground;
+                    self.advance(byte, marks);
+                }
+            },
+            ScanState::Csi => match byte {
+                // Parameter and intermediate bytes.
+                0x20..=0x3f => {
+                    if self.sequence.len() < MAX_CSI_LEN {
+                        self.sequence.push(byte);
+                    } else {
+                        self.ignore_sequence = true;
+                    }
+                }
+                0x40..=0x7e => self.finish_csi(byte, marks),
+                0x1b => self.state = ScanState::Escape,
+                0x18 | 0x1a => self.state = ScanState::Ground,
+                // Control characters are executed in the middle of the sequence.
+                b'\n' => marks.line_feed(),
+                _ => {}
+            },
+            ScanState::Osc => match byte {
+                0x07 => self.finish_sequence(marks),
+                0x1b => self.state = ScanState::OscEscape,
+                // Other control characters cancel the sequence.
+                0x18 | 0x1a => self.state = ScanState::Ground,
+                _ if self.ignore_sequence => {}
+                _ => {
+                    let len = self.sequence.len();
+                    let is_mark = len >= 4
+                        || [b"133;", b"633;"].iter().any(|prefix| {
+                            prefix[len] == byte && prefix.starts_with(&self.sequence)
+                        });
+                    if is_mark && len < MAX_MARK_LEN {
+                        self.sequence.push(byte);
+                    } else {
+                        self.ignore_sequence = true;
+                    }
+                }
+            },
+            ScanState::OscEscape => {
+                if byte == b'\\' {
+                    self.finish_sequence(marks);
+                } else {
+                    self.state = ScanState::Escape;
+                    self.advance(byte, marks);
+                }
+            }
+        }
+    }
+
+    fn finish_csi(&mut self, final_byte: u8, marks: &mut ShellMarks) {
+        self.state = ScanState::Ground;
+        if self.ignore_sequence || !matches!(final_byte, b'h' | b'l') {
+            return;
+        }
+        if let Some(modes) = self.sequence.strip_prefix(b"?") {
+            if modes
+                .split(|&byte| byte == b';')
+                .any(|mode| matches!(mode, b"47" | b"1047" | b"1049"))
+            {
+                marks.alternate_screen = final_byte == b'h';
+            }
+        }
+    }
+
+    fn finish_sequence(&mut self, marks: &mut ShellMarks) {
+        self.state = ScanState::Ground;
+        if !self.ignore_sequence {
+            if let Some(mark) = parse_mark(&self.sequence) {
+                marks.pending.push((marks.line_feeds, mark));
+            }
+        }
+        self.sequence.clear();
+    }
+}
+
+fn parse_mark(sequence: &[u8]) -> Option<ShellMark> {
+    let sequence = std::str::from_utf8(sequence).ok()?;
+    let (code, params) = sequence.split_once(';')?;
+    if code != "133" && code != "633" {
+        return None;
+    }
+    let (kind, rest) = match params.split_once(';') {
+        Some((kind, rest)) => (kind, Some(rest)),
+        None => (params, None),
+    };
+    match kind {
+        "A" => Some(ShellMark::PromptStart),
+        "B" => Some(ShellMark::CommandStart),
+        "C" => Some(ShellMark::CommandExecuted),
+        "D" => Some(ShellMark::CommandFinished {
+            exit_code: rest
+                .and_then(|rest| rest.split(';').next())
+                .and_then(|exit_code| exit_code.parse().ok()),
+        }),
+        "E" if code == "633" => Some(ShellMark::CommandLine(unescape_command_line(
+            rest.unwrap_or_default()
+                .split(';')
+                .next()
+                .unwrap_or_default(),
+        ))),
+        _ => None,
+    }
+}
+
+/// Reverts the escaping of `\` as `\\` and of other characters as `\xAB`, used to pass the command line in a mark.
+fn unescape_command_line(escaped: &str) -> String {
+    let bytes = escaped.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if bytes.get(i + 1) == Some(&b'\\') => {
+                unescaped.push(b'\\');
+                i += 2;
+            }
+            b'\\' if bytes.get(i + 1) == Some(&b'x') => {
+                match escaped
+                    .get(i + 2..i + 4)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(byte) => {
+                        unescaped.push(byte);
+                        i += 4;
+                    }
+                    None => {
+                        unescaped.push(b'\\');
+                        i += 1;
+                    }
+                }
+            }
+            byte => {
+                unescaped.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// A PTY that scans its output for shell integration marks, before handing it over to Alacritty.
+pub(crate) struct ShellIntegrationPty<T> {
+    pty: T,
+    scanner: MarkScanner,
+    marks: Arc<Mutex<ShellMarks>>,
+}
+
+impl<T: EventedReadWrite> io::Read for ShellIntegrationPty<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.pty.reader().read(buf)?;
+        if let Ok(mut marks) = self.marks.lock() {
+            self.scanner.scan(&buf[..read], &mut marks);
+        }
+        Ok(read)
+    }
+}
+
+impl<T: EventedReadWrite> EventedReadWrite for ShellIntegrationPty<T> {
+    type Reader = Self;
+    type Writer = T::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.pty.register(poll, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<T: EventedPty> EventedPty for ShellIntegrationPty<T> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<T: OnResize> OnResize for ShellIntegrationPty<T> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size)
+    }
+}
+
+/// A command, positioned by the number of line feeds written before each of its marks.
+#[derive(Debug, Default)]
+struct TrackedCommand {
+    prompt: u64,
+    output_start: Option<u64>,
+    output_end: Option<u64>,
+    exit_code: Option<i32>,
+    command: Option<String>,
+}
+
+/// The commands run in a terminal, as reported by the shell integration.
+#[derive(Default)]
+pub(crate) struct ShellIntegration {
+    marks: Arc<Mutex<ShellMarks>>,
+    commands: VecDeque<TrackedCommand>,
+    /// Whether the shell is reading a command line, between the end of its prompt and the
+    /// execution of the command.
+    at_prompt: bool,
+}
+
+impl ShellIntegration {
+    pub(crate) fn wrap_pty<T>(&self, pty: T) -> ShellIntegrationPty<T> {
+        ShellIntegrationPty {
+            pty,
+            scanner: MarkScanner::default(),
+            marks: self.marks.clone(),
+        }
+    }
+
+    /// Applies the marks scanned since the last call, returning the exit codes of the commands that finished.
+    pub(crate) fn process_marks(&mut self) -> Vec<Option<i32>> {
+        let pending = match self.marks.lock() {
+            Ok(mut marks) => std::mem::take(&mut marks.pending),
+            Err(_) => return Vec::new(),
+        };
+
+        let mut finished = Vec::new();
+        for (line_feeds, mark) in pending {
+            self.at_prompt = mark == ShellMark::CommandStart;
+            match mark {
+                ShellMark::PromptStart => {
+                    if self.commands.len() >= MAX_COMMANDS {
+                        self.commands.pop_front();
+                    }
+                    self.commands.push_back(TrackedCommand {
+                        prompt: line_feeds,
+                        ..TrackedCommand::default()
+                    });
+                }
+                ShellMark::CommandStart => {}
+                ShellMark::CommandLine(command) => {
+                    if let Some(current) = self.commands.back_mut() {
+                        current.command = Some(command);
+                    }
+                }
+                ShellMark::CommandExecuted => {
+                    if let Some(current) = self.commands.back_mut() {
+                        current.output_start = Some(line_feeds);
+                    }
+                }
+                ShellMark::CommandFinished { exit_code } => {
+                    if let Some(current) = self.commands.back_mut() {
+                        // Shells report empty command lines as finished, without executing anything.
+                        if current.output_start.is_some() && current.output_end.is_none() {
+                            current.output_end = Some(line_feeds);
+                            current.exit_code = exit_code;
+                            finished.push(exit_code);
+                        }
+                    }
+                }
+            }
+        }
+        finished
+    }
+
+    /// Whether the shell has printed its prompt and is waiting for a command line.
+    pub(crate) fn is_at_prompt(&self) -> bool {
+        self.at_prompt
+    }
+
+    /// Forgets all commands, e.g. after the terminal got cleared.
+    pub(crate) fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Positions the commands in the terminal grid, dropping the ones scrolled out of the history.
+    pub(crate) fn command_blocks(&mut self, term: &Term<ZedListener>) -> Vec<CommandBlock> {
+        let Ok(line_feeds) = self.marks.lock().map(|marks| marks.line_feeds) else {
+            return Vec::new();
+        };
+        let Some(first) = self.commands.front() else {
+            return Vec::new();
+        };
+        let line_starts = logical_line_starts(term, line_feeds.saturating_sub(first.prompt));
+        // `None` if the mark is in the part of the history that got trimmed.
+        let to_line = |mark_line_feeds: u64| {
+            let lines_above_cursor = line_feeds.saturating_sub(mark_line_feeds);
+            usize::try_from(lines_above_cursor)
+                .ok()
+                .and_then(|lines_above_cursor| line_starts.get(lines_above_cursor))
+                .copied()
+        };
+
+        // Keep the last command, as it might still be running.
+        while self.commands.len() > 1 {
+            let first = &self.commands[0];
+            if to_line(first.output_end.unwrap_or(first.prompt)).is_some() {
+                break;
+            }
+            self.commands.pop_front();
+        }
+
+        let topmost_line = term.topmost_line();
+        self.commands
+            .iter()
+            .map(|command| CommandBlock {
+                prompt_line: to_line(command.prompt).unwrap_or(topmost_line),
+                output_start: command
+                    .output_start
+                    .map(|line| to_line(line).unwrap_or(topmost_line)),
+                output_end: command
+                    .output_end
+                    .map(|line| to_line(line).unwrap_or(topmost_line)),
+                exit_code: command.exit_code,
+                command: command.command.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Returns the first grid line of the cursor's logical line and of up to `count` logical lines
+/// above it, nearest first. A logical line spans several grid lines when it is soft wrapped.
+fn logical_line_starts(term: &Term<ZedListener>, count: u64) -> Vec<Line> {
+    let grid = term.grid();
+    let topmost_line = term.topmost_line();
+    let last_column = term.last_column();
+    let wraps_into_next = |line: Line| grid[line][last_column].flags.contains(Flags::WRAPLINE);
+
+    let mut line_starts = Vec::new();
+    let mut line = grid.cursor.point.line;
+    loop {
+        while line > topmost_line && wraps_into_next(Line(line.0 - 1)) {
+            line = Line(line.0 - 1);
+        }
+        line_starts.push(line);
+        if line <= topmost_line || line_starts.len() as u64 > count {
+            break;
+        }
+        line = Line(line.0 - 1);
+    }
+    line_starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminalSize;
+    use alacritty_terminal::{term::Config, vte::ansi::Processor};
+    use gpui::{px, size};
+
+    fn scan_chunks(chunks: &[&[u8]]) -> ShellMarks {
+        let mut scanner = MarkScanner::default();
+        let mut marks = ShellMarks::default();
+        for chunk in chunks {
+            scanner.scan(chunk, &mut marks);
+        }
+        marks
+    }
+
+    #[test]
+    fn test_scan_marks() {
+        let marks = scan_chunks(&[
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]633;E;ls \\\\ -la\\x3b echo\x07",
+            b"\x1b]133;C\x07a\r\nb\r\n\x1b]133;D;2\x1b\\\x1b]0;title\x07",
+        ]);
+        assert_eq!(marks.line_feeds, 3);
+        assert_eq!(
+            marks.pending,
+            vec![
+                (0, ShellMark::PromptStart),
+                (0, ShellMark::CommandStart),
+                (1, ShellMark::CommandLine("ls \\ -la; echo".to_string())),
+                (1, ShellMark::CommandExecuted),
+                (3, ShellMark::CommandFinished { exit_code: Some(2) }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_marks_split_across_chunks() {
+        let marks = scan_chunks(&[
+            b"out\n\x1b",
+            b"]13",
+            b"3;D",
+            b";0\x1b",
+            b"\\\x1b]133;A",
+            b"\x07",
+        ]);
+        assert_eq!(
+            marks.pending,
+            vec![
+                (1, ShellMark::CommandFinished { exit_code: Some(0) }),
+                (1, ShellMark::PromptStart),
+            ]
+        );
+
+        let marks = scan_chunks(&[b"\x1b]133;D\x07\x1b]52;c;aGVsbG8=\x07\x1b[31m\x1b]133;X\x07"]);
+        assert_eq!(
+            marks.pending,
+            vec![(0, ShellMark::CommandFinished { exit_code: None })]
+        );
+    }
+
+    #[test]
+    fn test_process_marks() {
+        let mut shell_integration = ShellIntegration::default();
+        let mut scanner = MarkScanner::default();
+        scanner.scan(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07\r\n\x1b]133;D;0\x07\x1b]133;A\x07$ \x1b]133;B\x07false\r\n\
+            \x1b]633;E;false\x07\x1b]133;C\x07\x1b]133;D;1\x07\x1b]133;A\x07$ \x1b]133;B\x07",
+            &mut shell_integration.marks.lock().unwrap(),
+        );
+
+        // The empty command line is not reported as finished.
+        assert_eq!(shell_integration.process_marks(), vec![Some(1)]);
+        assert!(shell_integration.is_at_prompt());
+        assert_eq!(shell_integration.commands.len(), 3);
+        let failed = &shell_integration.commands[1];
+        assert_eq!(failed.prompt, 1);
+        assert_eq!(failed.output_start, Some(2));
+        assert_eq!(failed.output_end, Some(2));
+        assert_eq!(failed.exit_code, Some(1));
+        assert_eq!(failed.command.as_deref(), Some("false"));
+        assert!(shell_integration.process_marks().is_empty());
+
+        shell_integration.clear();
+        assert!(shell_integration.commands.is_empty());
+    }
+
+    #[test]
+    fn test_scan_marks_ignores_alternate_screen() {
+        let marks = scan_chunks(&[
+            b"a\r\n\x1b[?1049h\x1b[Hb\r\nc\r\n",
+            b"\x1b[?10",
+            b"49l\x1b]133;A\x07\r\n",
+        ]);
+        assert_eq!(marks.line_feeds, 2);
+        assert_eq!(marks.pending, vec![(1, ShellMark::PromptStart)]);
+    }
+
+    #[test]
+    fn test_command_blocks_follow_soft_wraps() {
+        let (events, _) = futures::channel::mpsc::unbounded();
+        let terminal_size =
+            |columns: f32| TerminalSize::new(px(1.), px(1.), size(px(columns), px(5.)));
+        let mut term = Term::new(Config::default(), &terminal_size(10.), ZedListener(events));
+        let mut shell_integration = ShellIntegration::default();
+        let output: &[u8] = b"\x1b]133;A\x07$ \x1b]133;B\x07echo\r\n\x1b]133;C\x07\
+            0123456789abcdef\r\n\x1b]133;D;0\x07\x1b]133;A\x07$ \x1b]133;B\x07";
+        MarkScanner::default().scan(output, &mut shell_integration.marks.lock().unwrap());
+        let mut parser: Processor = Processor::new();
+        parser.advance(&mut term, output);
+        shell_integration.process_marks();
+
+        // The output wraps onto a second grid line.
+        let blocks = shell_integration.command_blocks(&term);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].prompt_line, Line(0));
+        assert_eq!(blocks[0].output_start, Some(Line(1)));
+        assert_eq!(blocks[0].output_end, Some(Line(3)));
+        assert_eq!(blocks[1].prompt_line, Line(3));
+
+        // Once the terminal is wide enough, the output is reflowed onto a single grid line.
+        term.resize(terminal_size(20.));
+        let blocks = shell_integration.command_blocks(&term);
+        assert_eq!(blocks[0].prompt_line, Line(0));
+        assert_eq!(blocks[0].output_start, Some(Line(1)));
+        assert_eq!(blocks[0].output_end, Some(Line(2)));
+        assert_eq!(blocks[1].prompt_line, Line(2));
+    }
+}
@@ -0,0 +1,75 @@
+# Zed shell integration for bash, loaded with `--init-file`.
+# Marks the prompts, commands and command output with OSC 133 sequences.
+
+if [ -f ~/.bashrc ]; then
+    . ~/.bashrc
+fi
+
+if [[ $- == *i* && -z "$__zed_shell_integration" ]]; then
+    __zed_shell_integration=1
+    __zed_at_prompt=
+    __zed_command_running=
+
+    __zed_escape() {
+        local value="${1//\\/\\\\}"
+        value="${value//;/\\x3b}"
+        value="${value//$'\n'/\\x0a}"
+        builtin printf '%s' "$value"
+    }
+
+    __zed_preexec() {
+        if [[ -z "$__zed_at_prompt" || -n "$COMP_LINE" ]]; then
+            return
+        fi
+        __zed_at_prompt=
+        __zed_command_running=1
+        local command
+        command="$(HISTTIMEFORMAT= builtin history 1)"
+        if [[ $command =~ ^[[:space:]]*[0-9]+[*]?[[:space:]]+(.*)$ ]]; then
+            command="${BASH_REMATCH[1]}"
+        fi
+        builtin printf '\e]633;E;%s\a\e]133;C\a' "$(__zed_escape "$command")"
+    }
+
+    __zed_precmd() {
+        local exit_status=$?
+        __zed_at_prompt=
+        if [[ -n "$__zed_command_running" ]]; then
+            builtin printf '\e]133;D;%s\a' "$exit_status"
+        fi
+        __zed_command_running=
+        builtin printf '\e]133;A\a'
+        return $exit_status
+    }
+
+    __zed_prompt_ready() {
+        local exit_status=$?
+        __zed_at_prompt=1
+        return $exit_status
+    }
+
+    # `trap -p` prints the trap as `trap -- '<command>' DEBUG`.
+    __zed_trap_command() {
+        builtin printf '%s' "$3"
+    }
+    eval "__zed_user_debug_trap=\$(__zed_trap_command $(builtin trap -p DEBUG))"
+
+    __zed_set_status() {
+        return "$1"
+    }
+
+    # Runs the DEBUG trap the user had set up before this script, with the exit status of the
+    # last command still in place.
+    __zed_debug_trap() {
+        local exit_status=$?
+        __zed_preexec
+        if [[ -n "$__zed_user_debug_trap" ]]; then
+            __zed_set_status "$exit_status"
+            eval "$__zed_user_debug_trap"
+        fi
+    }
+
+    PS1="$PS1"'\[\e]133;B\a\]'
+    PROMPT_COMMAND="__zed_precmd;${PROMPT_COMMAND%;}${PROMPT_COMMAND:+;}__zed_prompt_ready"
+    trap '__zed_debug_trap' DEBUG
+fi
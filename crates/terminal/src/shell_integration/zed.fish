@@ -0,0 +1,42 @@
+# Zed shell integration for fish, loaded from `vendor_conf.d` through `XDG_DATA_DIRS`.
+# Marks the prompts, commands and command output with OSC 133 sequences.
+
+if set -q ZED_USER_XDG_DATA_DIRS
+    set -gx XDG_DATA_DIRS $ZED_USER_XDG_DATA_DIRS
+    set -e ZED_USER_XDG_DATA_DIRS
+else
+    set -e XDG_DATA_DIRS
+end
+
+status is-interactive; or exit
+set -q __zed_shell_integration; and exit
+set -g __zed_shell_integration 1
+
+function __zed_escape
+    string replace -a -- '\\' '\\\\' $argv[1] | string replace -a -- ';' '\\x3b' | string join '\\x0a'
+end
+
+function __zed_preexec --on-event fish_preexec
+    set -g __zed_command_running 1
+    printf '\e]633;E;%s\a\e]133;C\a' (__zed_escape "$argv[1]")
+end
+
+function __zed_postexec --on-event fish_postexec
+    set -l exit_status $status
+    if set -q __zed_command_running
+        printf '\e]133;D;%s\a' $exit_status
+        set -e __zed_command_running
+    end
+end
+
+function __zed_prompt_start --on-event fish_prompt
+    printf '\e]133;A\a'
+    # Wrap the prompt lazily, as the user configuration defining it is loaded after this file.
+    if functions -q fish_prompt; and not functions -q __zed_original_fish_prompt
+        functions -c fish_prompt __zed_original_fish_prompt
+        function fish_prompt
+            __zed_original_fish_prompt
+            printf '\e]133;B\a'
+        end
+    end
+end
@@ -3,6 +3,7 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -41,6 +42,7 @@ use futures::StreamExt;
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{CommandBlock, ShellIntegration};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, TaskId};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
@@ -79,6 +81,10 @@ actions!(
         ScrollPageDown,
         ScrollToTop,
         ScrollToBottom,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        CopyLastCommandOutput,
+        RerunLastCommand,
         ToggleViMode,
     ]
 );
@@ -108,6 +114,10 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// A command finished running in the shell, as reported by the shell integration.
+    CommandFinished {
+        exit_code: Option<i32>,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -435,6 +445,8 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let shell_integration = ShellIntegration::default();
+        let pty = shell_integration.wrap_pty(pty);
 
         //And connect them together
         let event_loop = EventLoop::new(
@@ -451,6 +463,7 @@ impl TerminalBuilder {
 
         let terminal = Terminal {
             task,
+            task_output_end: None,
             pty_tx: Notifier(pty_tx),
             completion_tx,
             term,
@@ -474,6 +487,7 @@ impl TerminalBuilder {
             vi_mode_enabled: false,
             is_ssh_terminal,
             python_venv_directory,
+            shell_integration,
         };
 
         Ok(TerminalBuilder {
@@ -572,6 +586,7 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub command_blocks: Vec<CommandBlock>,
 }
 
 #[derive(Clone)]
@@ -596,6 +611,7 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            command_blocks: Vec::new(),
         }
     }
 }
@@ -631,8 +647,12 @@ pub struct Terminal {
     url_regex: RegexSearch,
     word_regex: RegexSearch,
     task: Option<TaskState>,
+    /// The line the output of the finished task ends at, counted from the top of the scrollback
+    /// history when the task finished, so that the task summary is not a part of the output.
+    task_output_end: Option<usize>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    shell_integration: ShellIntegration,
 }
 
 pub struct TaskState {
@@ -713,6 +733,9 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
+                for exit_code in self.shell_integration.process_marks() {
+                    cx.emit(Event::CommandFinished { exit_code });
+                }
                 cx.emit(Event::Wakeup);
 
                 if self.pty_info.has_changed() {
@@ -790,6 +813,8 @@ impl Terminal {
                     term.grid_mut().reset_region((new_cursor.line + 1)..);
                 }
 
+                self.shell_integration.clear();
+
                 cx.emit(Event::Wakeup);
             }
             InternalEvent::Scroll(scroll) => {
@@ -1073,6 +1098,18 @@ impl Terminal {
         }
     }
 
+    /// Returns the output of the terminal's task once it finished, without the task summary.
+    /// A task terminal runs only its task, so the output starts at the top of the scrollback history.
+    pub fn task_output(&self) -> Option<String> {
+        let task_output_end = self.task_output_end?;
+        let term = self.term.lock();
+        let topmost_line = term.topmost_line();
+        let end_line = Line(topmost_line.0 + task_output_end as i32).min(term.bottommost_line());
+        let start = AlacPoint::new(topmost_line, Column(0));
+        let end = AlacPoint::new(end_line, term.last_column());
+        Some(term.bounds_to_string(start, end))
+    }
+
     pub fn select_all(&mut self) {
         let term = self.term.lock();
         let start = AlacPoint::new(term.topmost_line(), Column(0));
@@ -1307,6 +1344,7 @@ impl Terminal {
         }
 
         self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content.command_blocks = self.shell_integration.command_blocks(&terminal);
     }
 
     fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
@@ -1334,6 +1372,7 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            command_blocks: Vec::new(),
         }
     }
 
@@ -1362,6 +1401,97 @@ impl Terminal {
         lines
     }
 
+    /// The commands run in the terminal, if its shell reports them through the shell integration.
+    pub fn command_blocks(&self) -> &[CommandBlock] {
+        &self.last_content.command_blocks
+    }
+
+    pub fn last_finished_command(&self) -> Option<&CommandBlock> {
+        self.last_content
+            .command_blocks
+            .iter()
+            .rev()
+            .find(|block| block.is_finished())
+    }
+
+    /// Returns the output of the command, if it is executed and is still in the scrollback history.
+    pub fn command_output(&self, block: &CommandBlock) -> Option<String> {
+        let output_start = block.output_start?;
+        let term = self.term.lock();
+        let output_end = block.output_end.unwrap_or(term.bottommost_line() + 1);
+        if output_start < term.topmost_line() {
+            return None;
+        }
+        if output_end <= output_start {
+            return Some(String::new());
+        }
+        let start = AlacPoint::new(output_start, Column(0));
+        let end = AlacPoint::new(output_end - 1, term.last_column());
+        let output = term.bounds_to_string(start, end);
+        Some(
+            output
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim_end()
+                .to_string(),
+        )
+    }
+
+    pub fn scroll_to_previous_prompt(&mut self) {
+        let viewport_top = Line(-(self.last_content.display_offset as i32));
+        if let Some(block) = self
+            .last_content
+            .command_blocks
+            .iter()
+            .rev()
+            .find(|block| block.prompt_line < viewport_top)
+        {
+            self.scroll_prompt_to_top(block.prompt_line);
+        }
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        let viewport_top = Line(-(self.last_content.display_offset as i32));
+        match self
+            .last_content
+            .command_blocks
+            .iter()
+            .find(|block| block.prompt_line > viewport_top)
+        {
+            Some(block) if block.prompt_line.0 < 0 => self.scroll_prompt_to_top(block.prompt_line),
+            _ => self.scroll_to_bottom(),
+        }
+    }
+
+    fn scroll_prompt_to_top(&mut self, prompt_line: Line) {
+        let delta = -prompt_line.0 - self.last_content.display_offset as i32;
+        self.events
+            .push_back(InternalEvent::Scroll(AlacScroll::Delta(delta)));
+    }
+
+    /// Runs the last command reported by the shell integration again.
+    /// Returns `false` if there is no such command, or if the shell is not waiting at its
+    /// prompt, in which case the input would go to the running program instead.
+    pub fn rerun_last_command(&mut self) -> bool {
+        if !self.shell_integration.is_at_prompt() {
+            return false;
+        }
+        let Some(command) = self
+            .last_content
+            .command_blocks
+            .iter()
+            .rev()
+            .find_map(|block| block.command.clone())
+        else {
+            return false;
+        };
+        self.paste(&command);
+        self.input("\r".to_string());
+        true
+    }
+
     pub fn focus_in(&self) {
         if self.last_content.mode.contains(TermMode::FOCUS_IN_OUT) {
             self.write_to_pty("\x1b[I".to_string());
@@ -1736,7 +1866,7 @@ impl Terminal {
     }
 
     fn register_task_finished(&mut self, error_code: Option<i32>, cx: &mut Context<'_, Terminal>) {
-        self.completion_tx.try_send(()).ok();
+        notify_task_completion(&self.completion_tx);
         let task = match &mut self.task {
             Some(task) => task,
             None => {
@@ -1749,6 +1879,11 @@ impl Terminal {
         if task.status != TaskStatus::Running {
             return;
         }
+        {
+            let term = self.term.lock();
+            let cursor_line = term.grid().cursor.point.line;
+            self.task_output_end = usize::try_from(cursor_line.0 - term.topmost_line().0).ok();
+        }
         match error_code {
             Some(error_code) => {
                 task.status.register_task_exit(error_code);
@@ -1789,6 +1924,13 @@ impl Terminal {
     }
 }
 
+/// Wakes up every [`Terminal::wait_for_completed_task`] waiter: the message is received by one
+/// of them only, the others get woken up by the channel closing.
+fn notify_task_completion(completion_tx: &Sender<()>) {
+    completion_tx.try_send(()).ok();
+    completion_tx.close();
+}
+
 fn is_path_surrounded_by_common_symbols(path: &str) -> bool {
     // Avoid detecting `[]` or `()` strings as paths, surrounded by common symbols
     path.len() > 2
@@ -1998,11 +2140,13 @@ mod tests {
         index::{Column, Line, Point as AlacPoint},
         term::cell::Cell,
     };
+    use futures::FutureExt as _;
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, notify_task_completion, rgb_for_index, IndexedCell,
+        TerminalContent, TerminalSize,
     };
 
     #[test]
@@ -2014,6 +2158,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_task_completion_wakes_every_waiter() {
+        let (completion_tx, completion_rx) = smol::channel::bounded(1);
+        let waiters = [completion_rx.clone(), completion_rx.clone(), completion_rx];
+        notify_task_completion(&completion_tx);
+        for waiter in waiters {
+            assert!(
+                waiter.recv().now_or_never().is_some(),
+                "every waiter should be woken up"
+            );
+        }
+    }
+
     #[test]
     fn test_mouse_to_cell_test() {
         let mut rng = thread_rng();
@@ -28,7 +28,11 @@ use ui::{ParentElement, Tooltip};
 use workspace::Workspace;
 
 use std::mem;
-use std::{fmt::Debug, ops::RangeInclusive, rc::Rc};
+use std::{
+    fmt::Debug,
+    ops::{Range, RangeInclusive},
+    rc::Rc,
+};
 
 use crate::{BlockContext, BlockProperties, TerminalView};
 
@@ -47,6 +51,33 @@ pub struct LayoutState {
     gutter: Pixels,
     last_hovered_word: Option<HoveredWord>,
     block_below_cursor_element: Option<AnyElement>,
+    command_status_gutters: Vec<CommandStatusGutter>,
+}
+
+/// A bar in the gutter, next to the lines of a command reported by the shell integration,
+/// colored by the command exit status.
+struct CommandStatusGutter {
+    /// The displayed lines of the command, from its prompt to the end of its output.
+    lines: Range<i32>,
+    color: Hsla,
+}
+
+impl CommandStatusGutter {
+    fn paint(
+        &self,
+        origin: Point<Pixels>,
+        gutter: Pixels,
+        dimensions: &TerminalSize,
+        window: &mut Window,
+    ) {
+        let width = gutter / 4.;
+        let position = point(
+            origin.x + width / 2.,
+            origin.y + self.lines.start as f32 * dimensions.line_height,
+        );
+        let bar_size = size(width, self.lines.len() as f32 * dimensions.line_height);
+        window.paint_quad(fill(Bounds::new(position, bar_size), self.color));
+    }
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -743,11 +774,40 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    command_blocks,
                     ..
                 } = &self.terminal.read(cx).last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
 
+                let screen_lines = dimensions.num_lines() as i32;
+                let command_status_gutters = command_blocks
+                    .iter()
+                    // Prompts without an executed command are not marked.
+                    .filter(|block| block.output_start.is_some())
+                    .filter_map(|block| {
+                        let start = block.prompt_line.0 + display_offset as i32;
+                        let end = match block.output_end {
+                            Some(output_end) => output_end.0,
+                            None => cursor.point.line.0 + 1,
+                        } + display_offset as i32;
+                        let lines = start.max(0)..end.min(screen_lines);
+                        if lines.is_empty() {
+                            return None;
+                        }
+                        let color = if !block.is_finished() {
+                            theme.status().info
+                        } else if block.is_failed() {
+                            theme.status().error
+                        } else if block.exit_code.is_some() {
+                            theme.status().success
+                        } else {
+                            theme.status().hint
+                        };
+                        Some(CommandStatusGutter { lines, color })
+                    })
+                    .collect();
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
@@ -868,6 +928,7 @@ impl Element for TerminalElement {
                     gutter,
                     last_hovered_word,
                     block_below_cursor_element,
+                    command_status_gutters,
                 }
             },
         )
@@ -933,6 +994,15 @@ impl Element for TerminalElement {
                         }
                     });
 
+                    for command_status_gutter in &layout.command_status_gutters {
+                        command_status_gutter.paint(
+                            point(bounds.origin.x, origin.y),
+                            layout.gutter,
+                            &layout.dimensions,
+                            window,
+                        );
+                    }
+
                     for rect in &layout.rects {
                         rect.paint(origin, &layout.dimensions, window);
                     }
@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 
-use gpui::{HighlightStyle, Hsla};
+use gpui::{px, HighlightStyle, Hsla, StrikethroughStyle, UnderlineStyle};
 
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct SyntaxTheme {
@@ -51,6 +51,45 @@ impl SyntaxTheme {
         Some(ix as u32)
     }
 
+    /// Returns the style of a semantic token reported by a language server, or `None` if the
+    /// theme has no style for it.
+    ///
+    /// The token type is styled with the `semantic.<type>` key, falling back to the syntax
+    /// highlight that grammars use for the same kind of token. Each modifier then adds the
+    /// style of the `semantic.<type>.<modifier>` or `semantic.*.<modifier>` key.
+    pub fn semantic_token_style(
+        &self,
+        token_type: &str,
+        modifiers: &[&str],
+    ) -> Option<HighlightStyle> {
+        let mut style = self
+            .style_for_key(&format!("semantic.{token_type}"))
+            .or_else(|| self.style_for_key(highlight_name_for_token_type(token_type)?));
+        for modifier in modifiers {
+            let modifier_style = self
+                .style_for_key(&format!("semantic.{token_type}.{modifier}"))
+                .or_else(|| self.style_for_key(&format!("semantic.*.{modifier}")))
+                .or_else(|| default_modifier_style(modifier));
+            if let Some(modifier_style) = modifier_style {
+                let style = style.get_or_insert_with(HighlightStyle::default);
+                style.color = modifier_style.color.or(style.color);
+                style.font_weight = modifier_style.font_weight.or(style.font_weight);
+                style.font_style = modifier_style.font_style.or(style.font_style);
+                style.background_color = modifier_style.background_color.or(style.background_color);
+                style.underline = modifier_style.underline.or(style.underline);
+                style.strikethrough = modifier_style.strikethrough.or(style.strikethrough);
+                style.fade_out = modifier_style.fade_out.or(style.fade_out);
+            }
+        }
+        style
+    }
+
+    fn style_for_key(&self, name: &str) -> Option<HighlightStyle> {
+        self.highlights
+            .iter()
+            .find_map(|(key, style)| (key == name).then_some(*style))
+    }
+
     /// Returns a new [`Arc<SyntaxTheme>`] with the given syntax styles merged in.
     pub fn merge(base: Arc<Self>, user_syntax_styles: Vec<(String, HighlightStyle)>) -> Arc<Self> {
         if user_syntax_styles.is_empty() {
@@ -87,12 +126,90 @@ impl SyntaxTheme {
     }
 }
 
+/// The syntax highlight used by grammars for the standard semantic token types.
+fn highlight_name_for_token_type(token_type: &str) -> Option<&'static str> {
+    Some(match token_type {
+        "namespace" | "type" | "class" | "struct" | "interface" | "typeParameter" => "type",
+        "enum" => "enum",
+        "enumMember" => "variant",
+        "function" | "method" | "macro" => "function",
+        "parameter" | "variable" => "variable",
+        "property" => "property",
+        "keyword" | "modifier" => "keyword",
+        "comment" => "comment",
+        "string" => "string",
+        "number" => "number",
+        "regexp" => "string.regex",
+        "operator" => "operator",
+        "decorator" => "attribute",
+        _ => return None,
+    })
+}
+
+/// Styles for modifiers that are worth distinguishing even when the theme doesn't style them.
+fn default_modifier_style(modifier: &str) -> Option<HighlightStyle> {
+    match modifier {
+        "mutable" => Some(HighlightStyle {
+            underline: Some(UnderlineStyle {
+                thickness: px(1.),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        "deprecated" => Some(HighlightStyle {
+            strikethrough: Some(StrikethroughStyle {
+                thickness: px(1.),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use gpui::FontStyle;
 
     use super::*;
 
+    #[test]
+    fn test_semantic_token_style() {
+        let syntax_theme = SyntaxTheme::new_test([
+            ("function", gpui::red()),
+            ("semantic.variable", gpui::green()),
+            ("semantic.*.unsafe", gpui::yellow()),
+        ]);
+
+        // Types without a `semantic` key fall back to the matching syntax highlight.
+        assert_eq!(
+            syntax_theme
+                .semantic_token_style("method", &[])
+                .and_then(|style| style.color),
+            Some(gpui::red())
+        );
+        assert_eq!(
+            syntax_theme
+                .semantic_token_style("variable", &[])
+                .and_then(|style| style.color),
+            Some(gpui::green())
+        );
+        assert_eq!(syntax_theme.semantic_token_style("label", &[]), None);
+
+        // Modifiers are layered on top of the token type's style.
+        let style = syntax_theme
+            .semantic_token_style("variable", &["mutable"])
+            .unwrap();
+        assert_eq!(style.color, Some(gpui::green()));
+        assert!(style.underline.is_some());
+        assert_eq!(
+            syntax_theme
+                .semantic_token_style("function", &["unsafe"])
+                .and_then(|style| style.color),
+            Some(gpui::yellow())
+        );
+    }
+
     #[test]
     fn test_syntax_theme_merge() {
         // Merging into an empty `SyntaxTheme` keeps all the user-defined styles.
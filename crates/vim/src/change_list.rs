@@ -32,8 +32,12 @@ impl Vim {
         } else {
             (prev + count).min(self.change_list.len() - 1)
         };
-        self.change_list_position = Some(next);
-        let Some(selections) = self.change_list.get(next).cloned() else {
+        self.go_to_change(next, window, cx);
+    }
+
+    pub(crate) fn go_to_change(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.change_list_position = Some(ix);
+        let Some(selections) = self.change_list.get(ix).cloned() else {
             return;
         };
         self.update_editor(window, cx, |_, editor, window, cx| {
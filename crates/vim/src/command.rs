@@ -5,10 +5,12 @@ use editor::{
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
     display_map::ToDisplayPoint,
     scroll::Autoscroll,
-    Bias, Editor, ToPoint,
+    Anchor, Bias, Editor, ToPoint,
 };
-use gpui::{actions, impl_internal_actions, Action, App, Context, Global, Window};
-use language::Point;
+use gpui::{
+    actions, impl_internal_actions, Action, App, Context, Global, Keystroke, Modifiers, Window,
+};
+use language::{Point, SelectionGoal};
 use multi_buffer::MultiBufferRow;
 use regex::Regex;
 use schemars::JsonSchema;
@@ -30,13 +32,18 @@ use workspace::{notifications::NotifyResultExt, SaveIntent};
 use zed_actions::RevealTarget;
 
 use crate::{
-    motion::{EndOfDocument, Motion, StartOfDocument},
+    insert::NormalBefore,
+    listings::{Listing, ShowListing},
+    motion::{first_non_whitespace, EndOfDocument, Motion, StartOfDocument},
     normal::{
+        repeat::Replayer,
         search::{FindCommand, ReplaceCommand, Replacement},
         JoinLines,
     },
     object::Object,
-    state::Mode,
+    options::SetOptions,
+    state::{Mode, ReplayableAction},
+    vimrc::SourceFile,
     visual::VisualDeleteLine,
     Vim,
 };
@@ -76,6 +83,10 @@ impl_internal_actions!(
         WithRange,
         WithCount,
         OnMatchingLines,
+        NormalCommand,
+        NormalCommandStep,
+        CopyMoveLines,
+        ShiftLines,
         ShellExec
     ]
 );
@@ -239,6 +250,22 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
         action.run(vim, window, cx)
     });
 
+    Vim::action(editor, cx, |vim, action: &NormalCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &NormalCommandStep, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &CopyMoveLines, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &ShiftLines, window, cx| {
+        action.run(vim, window, cx)
+    });
+
     Vim::action(editor, cx, |vim, action: &ShellExec, window, cx| {
         action.run(vim, window, cx)
     })
@@ -743,6 +770,36 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         VimCommand::str(("No", "tifications"), "notification_panel::ToggleFocus"),
         VimCommand::str(("A", "I"), "assistant::ToggleFocus"),
         VimCommand::new(("noh", "lsearch"), search::buffer_search::Dismiss),
+        VimCommand::new(
+            ("reg", "isters"),
+            ShowListing {
+                listing: Listing::Registers,
+            },
+        ),
+        VimCommand::new(
+            ("di", "splay"),
+            ShowListing {
+                listing: Listing::Registers,
+            },
+        ),
+        VimCommand::new(
+            ("marks", ""),
+            ShowListing {
+                listing: Listing::Marks,
+            },
+        ),
+        VimCommand::new(
+            ("ju", "mps"),
+            ShowListing {
+                listing: Listing::Jumps,
+            },
+        ),
+        VimCommand::new(
+            ("changes", ""),
+            ShowListing {
+                listing: Listing::Changes,
+            },
+        ),
         VimCommand::new(("$", ""), EndOfDocument),
         VimCommand::new(("%", ""), EndOfDocument),
         VimCommand::new(("0", ""), StartOfDocument),
@@ -823,6 +880,10 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Option<CommandIntercept
             }
             .boxed_clone(),
         )
+    } else if let Some(action) = SetOptions::parse(query) {
+        Some(action)
+    } else if let Some(action) = SourceFile::parse(query) {
+        Some(action)
     } else if query.starts_with('s') {
         let mut substitute = "substitute".chars().peekable();
         let mut query = query.chars().peekable();
@@ -867,6 +928,12 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Option<CommandIntercept
         } else {
             None
         }
+    } else if query.starts_with("norm") {
+        NormalCommand::parse(query, range.clone())
+    } else if query.starts_with('>') || query.starts_with('<') {
+        ShiftLines::parse(query, range.clone())
+    } else if let Some(action) = CopyMoveLines::parse(query, range.clone()) {
+        Some(action)
     } else if query.contains('!') {
         ShellExec::parse(query, range.clone())
     } else {
@@ -1110,6 +1177,374 @@ impl OnMatchingLines {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct NormalCommand {
+    range: Option<CommandRange>,
+    keys: String,
+}
+
+/// Replayed before each line of a `:normal` command (and once after the last one).
+/// Any unfinished command is completed as if <Esc> had been typed.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct NormalCommandStep {
+    line: Option<Anchor>,
+}
+
+impl NormalCommand {
+    pub fn parse(query: &str, range: Option<CommandRange>) -> Option<Box<dyn Action>> {
+        let mut rest = query.strip_prefix("norm")?;
+        if let Some(suffix) = rest.strip_prefix('a') {
+            rest = suffix.strip_prefix('l').unwrap_or(suffix);
+        }
+        // key bindings are always used, so `:normal!` behaves the same as `:normal`
+        rest = rest.strip_prefix('!').unwrap_or(rest);
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let keys = rest.trim_start();
+        if keys.is_empty() {
+            return None;
+        }
+        Some(
+            Self {
+                range,
+                keys: keys.to_string(),
+            }
+            .boxed_clone(),
+        )
+    }
+
+    fn keystrokes(&self) -> Vec<Keystroke> {
+        self.keys
+            .chars()
+            .map(|c| Keystroke {
+                modifiers: Modifiers {
+                    shift: c.is_ascii_uppercase(),
+                    ..Default::default()
+                },
+                key: match c {
+                    ' ' => "space".to_string(),
+                    '\t' => "tab".to_string(),
+                    c => c.to_ascii_lowercase().to_string(),
+                },
+                key_char: Some(c.to_string()),
+            })
+            .collect()
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let result = vim.update_editor(window, cx, |vim, editor, window, cx| {
+            if let Some(range) = self.range.as_ref() {
+                // lines are anchored up front so that keys which add or remove
+                // lines don't shift the later lines of the range.
+                let range = range.buffer_range(vim, editor, window, cx)?;
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                anyhow::Ok(
+                    (range.start.0..=range.end.0)
+                        .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+                        .collect::<Vec<_>>(),
+                )
+            } else {
+                // without a range the keys are typed at each cursor, which is
+                // how `:g/pattern/normal` visits the matching lines.
+                Ok(editor
+                    .selections
+                    .disjoint_anchors()
+                    .iter()
+                    .map(|selection| selection.head())
+                    .collect())
+            }
+        });
+
+        let lines = match result {
+            None => return,
+            Some(e @ Err(_)) => {
+                let Some(workspace) = vim.workspace(window) else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    e.notify_err(workspace, cx);
+                });
+                return;
+            }
+            Some(Ok(lines)) => lines,
+        };
+
+        let keystrokes = self.keystrokes();
+        let mut actions = Vec::new();
+        for line in lines {
+            actions.push(ReplayableAction::Action(
+                NormalCommandStep { line: Some(line) }.boxed_clone(),
+            ));
+            actions.extend(keystrokes.iter().cloned().map(ReplayableAction::Keystroke));
+        }
+        actions.push(ReplayableAction::Action(
+            NormalCommandStep { line: None }.boxed_clone(),
+        ));
+
+        let mut replayer = Vim::globals(cx)
+            .replayer
+            .get_or_insert_with(Replayer::new)
+            .clone();
+        replayer.replay(actions, window, cx);
+    }
+}
+
+impl NormalCommandStep {
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let leaving_insert = matches!(vim.mode, Mode::Insert | Mode::Replace);
+        if leaving_insert {
+            vim.stop_recording_immediately(NormalBefore.boxed_clone(), cx);
+        }
+        vim.switch_mode(Mode::Normal, false, window, cx);
+        vim.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| match &self.line {
+                Some(anchor) => {
+                    s.select_anchor_ranges([*anchor..*anchor]);
+                }
+                None if leaving_insert => s.move_cursors_with(|map, mut cursor, _| {
+                    *cursor.column_mut() = cursor.column().saturating_sub(1);
+                    (map.clip_point(cursor, Bias::Left), SelectionGoal::None)
+                }),
+                None => {}
+            });
+        });
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CopyMoveLines {
+    range: Option<CommandRange>,
+    address: Position,
+    copy: bool,
+}
+
+impl CopyMoveLines {
+    pub fn parse(query: &str, range: Option<CommandRange>) -> Option<Box<dyn Action>> {
+        let name_len = query
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(query.len());
+        let copy = match &query[..name_len] {
+            "m" | "mo" | "mov" | "move" => false,
+            "t" | "co" | "cop" | "copy" => true,
+            _ => return None,
+        };
+        let mut chars = query[name_len..].trim().chars().peekable();
+        let address = VimCommand::parse_position(&mut chars)?;
+        if chars.next().is_some() {
+            return None;
+        }
+        Some(
+            Self {
+                range,
+                address,
+                copy,
+            }
+            .boxed_clone(),
+        )
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        vim.switch_mode(Mode::Normal, false, window, cx);
+        let result = vim.update_editor(window, cx, |vim, editor, window, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let blocks = if let Some(range) = self.range.as_ref() {
+                let range = range.buffer_range(vim, editor, window, cx)?;
+                vec![
+                    snapshot.anchor_after(Point::new(range.start.0, 0))
+                        ..snapshot.anchor_after(Point::new(range.end.0, 0)),
+                ]
+            } else {
+                // without a range each cursor's line is handled in turn, so that
+                // `:g/pattern/m0` moves every matching line in order.
+                editor
+                    .selections
+                    .all::<Point>(cx)
+                    .into_iter()
+                    .map(|selection| {
+                        let start = snapshot.anchor_after(Point::new(selection.head().row, 0));
+                        start..start
+                    })
+                    .collect()
+            };
+
+            let mut result = Ok(());
+            editor.transact(window, cx, |editor, window, cx| {
+                for block in blocks {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let rows =
+                        block.start.to_point(&snapshot).row..block.end.to_point(&snapshot).row;
+                    if self.range.is_none() {
+                        editor.change_selections(None, window, cx, |s| {
+                            let start = Point::new(rows.start, 0);
+                            s.select_ranges([start..start]);
+                        });
+                    }
+                    result = self
+                        .target_row(vim, editor, window, cx)
+                        .and_then(|target| self.copy_or_move(rows, target, editor, window, cx));
+                    if result.is_err() {
+                        break;
+                    }
+                }
+            });
+            result
+        });
+
+        if let Some(e @ Err(_)) = result {
+            let Some(workspace) = vim.workspace(window) else {
+                return;
+            };
+            workspace.update(cx, |workspace, cx| {
+                e.notify_err(workspace, cx);
+            });
+        }
+    }
+
+    // Returns the row to put the lines below, or None to put them above the first line.
+    fn target_row(
+        &self,
+        vim: &Vim,
+        editor: &mut Editor,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<Option<u32>> {
+        match self.address {
+            Position::Line { row: 0, offset } if offset <= 0 => Ok(None),
+            Position::Line { row: 0, offset } => Position::Line {
+                row: offset as u32,
+                offset: 0,
+            }
+            .buffer_row(vim, editor, window, cx)
+            .map(|row| Some(row.0)),
+            ref address => address
+                .buffer_row(vim, editor, window, cx)
+                .map(|row| Some(row.0)),
+        }
+    }
+
+    fn copy_or_move(
+        &self,
+        rows: Range<u32>,
+        target: Option<u32>,
+        editor: &mut Editor,
+        window: &mut Window,
+        cx: &mut Context<Editor>,
+    ) -> Result<()> {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let line_end = |row: u32| Point::new(row, snapshot.line_len(MultiBufferRow(row)));
+        if !self.copy && target.is_some_and(|target| target >= rows.start && target < rows.end) {
+            return Err(anyhow!("Cannot move a range of lines into itself"));
+        }
+
+        let unchanged = !self.copy
+            && target.map_or(rows.start == 0, |target| {
+                target + 1 == rows.start || target == rows.end
+            });
+        let extra_lines = rows.end - rows.start;
+        let cursor_row = match target {
+            _ if unchanged => rows.end,
+            Some(target) if !self.copy && target > rows.end => target,
+            Some(target) => target + 1 + extra_lines,
+            None => extra_lines,
+        };
+
+        if !unchanged {
+            let text = snapshot
+                .text_for_range(Point::new(rows.start, 0)..line_end(rows.end))
+                .collect::<String>();
+            let mut edits = vec![match target {
+                Some(target) => (line_end(target)..line_end(target), format!("\n{text}")),
+                None => (Point::zero()..Point::zero(), format!("{text}\n")),
+            }];
+            if !self.copy {
+                let deletion = if rows.end < snapshot.max_row().0 {
+                    Point::new(rows.start, 0)..Point::new(rows.end + 1, 0)
+                } else {
+                    line_end(rows.start - 1)..line_end(rows.end)
+                };
+                edits.push((deletion, String::new()));
+            }
+            editor.edit(edits, cx);
+        }
+
+        let column = editor.selections.newest::<Point>(cx).head().column;
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let line_len = snapshot.line_len(MultiBufferRow(cursor_row));
+        let cursor = Point::new(cursor_row, column.min(line_len.saturating_sub(1)));
+        editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+            s.select_ranges([cursor..cursor]);
+        });
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ShiftLines {
+    range: Option<CommandRange>,
+    outdent: bool,
+    times: usize,
+}
+
+impl ShiftLines {
+    pub fn parse(query: &str, range: Option<CommandRange>) -> Option<Box<dyn Action>> {
+        let direction = query.chars().next().filter(|c| *c == '>' || *c == '<')?;
+        if !query.chars().all(|c| c == direction) {
+            return None;
+        }
+        Some(
+            Self {
+                range,
+                outdent: direction == '<',
+                times: query.len(),
+            }
+            .boxed_clone(),
+        )
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        vim.switch_mode(Mode::Normal, false, window, cx);
+        let result = vim.update_editor(window, cx, |vim, editor, window, cx| {
+            let range = self
+                .range
+                .as_ref()
+                .map(|range| range.buffer_range(vim, editor, window, cx))
+                .transpose()?;
+            editor.transact(window, cx, |editor, window, cx| {
+                if let Some(range) = range {
+                    editor.change_selections(None, window, cx, |s| {
+                        let end = Point::new(range.end.0, s.buffer().line_len(range.end));
+                        s.select_ranges([Point::new(range.start.0, 0)..end]);
+                    });
+                }
+                for _ in 0..self.times {
+                    if self.outdent {
+                        editor.outdent(&Default::default(), window, cx);
+                    } else {
+                        editor.indent(&Default::default(), window, cx);
+                    }
+                }
+                editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                    s.move_with(|map, selection| {
+                        let point = first_non_whitespace(map, false, selection.end);
+                        selection.collapse_to(point, SelectionGoal::None)
+                    });
+                });
+            });
+            anyhow::Ok(())
+        });
+
+        if let Some(e @ Err(_)) = result {
+            let Some(workspace) = vim.workspace(window) else {
+                return;
+            };
+            workspace.update(cx, |workspace, cx| {
+                e.notify_err(workspace, cx);
+            });
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ShellExec {
     command: String,
@@ -1304,6 +1739,7 @@ impl ShellExec {
                         shell,
                         show_summary: false,
                         show_command: false,
+                        problem_matchers: Vec::new(),
                     }),
                 });
             });
@@ -1756,4 +2192,82 @@ mod test {
             a
             ˇa"});
     }
+
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            a
+            b"})
+            .await;
+
+        cx.simulate_shared_keystrokes(": % n o r m space shift-a x enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            ax
+            bx
+            ax
+            bˇx"});
+
+        cx.simulate_shared_keystrokes(": g / a / n o r m space d d enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            bx
+            ˇbx"});
+    }
+
+    #[gpui::test]
+    async fn test_command_normal_range_follows_edits(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        // each line of the range is visited once, even as earlier lines are deleted
+        cx.set_state("ˇ1\n2\n3\n4\n5", Mode::Normal);
+        cx.simulate_keystrokes(": 1 , 3 n o r m space d d enter");
+        cx.assert_state("ˇ4\n5", Mode::Normal);
+
+        // ...or added
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(": 1 , 2 n o r m space o x enter");
+        cx.assert_state("a\nx\nb\nˇx\nc", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_move_and_copy(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇ1\n2\n3\n4\n5").await;
+
+        cx.simulate_shared_keystrokes(": m 3 enter").await;
+        cx.shared_state().await.assert_eq("2\n3\nˇ1\n4\n5");
+
+        cx.simulate_shared_keystrokes(": 1 , 2 t $ enter").await;
+        cx.shared_state().await.assert_eq("2\n3\n1\n4\n5\n2\nˇ3");
+
+        cx.simulate_shared_keystrokes(": m 0 enter").await;
+        cx.shared_state().await.assert_eq("ˇ3\n2\n3\n1\n4\n5\n2");
+
+        cx.simulate_shared_keystrokes(": 2 , 3 m $ enter").await;
+        cx.shared_state().await.assert_eq("3\n1\n4\n5\n2\n2\nˇ3");
+
+        cx.simulate_shared_keystrokes(": g / ^ / m 0 enter").await;
+        cx.shared_state().await.assert_eq("ˇ3\n2\n2\n5\n4\n1\n3");
+    }
+
+    #[gpui::test]
+    async fn test_command_shift(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(": 2 , 3 > enter");
+        cx.assert_state("a\n    b\n    ˇc", Mode::Normal);
+
+        cx.simulate_keystrokes(": > > enter");
+        cx.assert_state("a\n    b\n            ˇc", Mode::Normal);
+
+        cx.simulate_keystrokes(": % < enter");
+        cx.assert_state("a\nb\n        ˇc", Mode::Normal);
+    }
 }
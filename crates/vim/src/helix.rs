@@ -1,15 +1,61 @@
-use editor::{movement, scroll::Autoscroll, DisplayPoint, Editor};
+use editor::{
+    display_map::DisplaySnapshot, movement, scroll::Autoscroll, DisplayPoint, Editor, ToOffset,
+};
 use gpui::{actions, Action};
 use gpui::{Context, Window};
-use language::{CharClassifier, CharKind};
-
-use crate::{motion::Motion, state::Mode, Vim};
-
-actions!(vim, [HelixNormalAfter, HelixDelete]);
+use language::{CharClassifier, CharKind, Selection};
+use regex::Regex;
+use search::{buffer_search, BufferSearchBar, SearchOptions};
+use workspace::notifications::NotifyResultExt;
+
+use crate::{
+    motion::Motion,
+    object::Object,
+    state::{HelixSelectRegex, Mode, Operator, SearchState},
+    Vim,
+};
+
+actions!(
+    vim,
+    [
+        HelixNormalAfter,
+        HelixDelete,
+        HelixChange,
+        HelixYank,
+        HelixSelectMatches,
+        HelixSplitSelections,
+        HelixCollapseSelections,
+        HelixFlipSelections,
+        HelixKeepPrimarySelection,
+        HelixRemovePrimarySelection
+    ]
+);
 
 pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     Vim::action(editor, cx, Vim::helix_normal_after);
     Vim::action(editor, cx, Vim::helix_delete);
+    Vim::action(editor, cx, Vim::helix_change);
+    Vim::action(editor, cx, Vim::helix_yank);
+    Vim::action(editor, cx, |vim, _: &HelixSelectMatches, window, cx| {
+        vim.helix_prompt_for_regex(HelixSelectRegex::Matches, window, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixSplitSelections, window, cx| {
+        vim.helix_prompt_for_regex(HelixSelectRegex::Split, window, cx)
+    });
+    Vim::action(editor, cx, Vim::helix_collapse_selections);
+    Vim::action(editor, cx, Vim::helix_flip_selections);
+    Vim::action(editor, cx, Vim::helix_keep_primary_selection);
+    Vim::action(editor, cx, Vim::helix_remove_primary_selection);
+}
+
+/// The character under helix's block cursor. For a forward selection this is
+/// the last selected character, rather than the one after the selection.
+fn helix_cursor(map: &DisplaySnapshot, selection: &Selection<DisplayPoint>) -> DisplayPoint {
+    if selection.is_empty() || selection.reversed {
+        selection.head()
+    } else {
+        movement::left(map, selection.head())
+    }
 }
 
 impl Vim {
@@ -238,33 +284,299 @@ impl Vim {
         }
     }
 
-    pub fn helix_delete(&mut self, _: &HelixDelete, window: &mut Window, cx: &mut Context<Self>) {
+    /// In select mode, motions move the cursor end of each selection and
+    /// leave the other end where it is.
+    pub fn helix_select_motion(
+        &mut self,
+        motion: Motion,
+        times: Option<usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            let text_layout_details = editor.text_layout_details(window);
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = helix_cursor(map, selection);
+                    let anchor = if selection.is_empty() {
+                        cursor
+                    } else if selection.reversed {
+                        movement::left(map, selection.end)
+                    } else {
+                        selection.start
+                    };
+
+                    let Some((point, goal)) =
+                        motion.move_point(map, cursor, selection.goal, times, &text_layout_details)
+                    else {
+                        return;
+                    };
+
+                    // Both ends of the selection are characters, so it always
+                    // covers at least the character under the cursor.
+                    if point >= anchor {
+                        selection.start = anchor;
+                        selection.end = movement::right(map, point);
+                        selection.reversed = false;
+                    } else {
+                        selection.start = point;
+                        selection.end = movement::right(map, anchor);
+                        selection.reversed = true;
+                    }
+                    selection.goal = goal;
+                })
+            });
+        });
+    }
+
+    /// `mi` and `ma` replace each selection with the surrounding text object.
+    pub fn helix_object(&mut self, object: Object, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(Operator::Object { around }) = self.active_operator() else {
+            return;
+        };
+        self.pop_operator(window, cx);
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    let mut cursor = selection.clone();
+                    cursor.collapse_to(helix_cursor(map, selection), selection.goal);
+                    if let Some(range) = object.range(map, cursor, around) {
+                        if !range.is_empty() {
+                            selection.start = range.start;
+                            selection.end = range.end;
+                            selection.reversed = false;
+                        }
+                    }
+                })
+            });
+        });
+    }
+
+    // Fixup selections so they have helix's semantics.
+    // Specifically:
+    //  - Make sure that each cursor acts as a 1 character wide selection
+    fn helix_expand_cursors(editor: &mut Editor, window: &mut Window, cx: &mut Context<Editor>) {
+        editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+            s.move_with(|map, selection| {
+                if selection.is_empty() && !selection.reversed {
+                    selection.end = movement::right(map, selection.end);
+                }
+            });
+        });
+    }
+
+    fn helix_delete_selections(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         self.store_visual_marks(window, cx);
         self.update_editor(window, cx, |vim, editor, window, cx| {
-            // Fixup selections so they have helix's semantics.
-            // Specifically:
-            //  - Make sure that each cursor acts as a 1 character wide selection
             editor.transact(window, cx, |editor, window, cx| {
-                editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
-                    s.move_with(|map, selection| {
-                        if selection.is_empty() && !selection.reversed {
-                            selection.end = movement::right(map, selection.end);
-                        }
-                    });
-                });
+                Self::helix_expand_cursors(editor, window, cx);
             });
 
             vim.copy_selections_content(editor, false, cx);
             editor.insert("", window, cx);
         });
     }
+
+    pub fn helix_delete(&mut self, _: &HelixDelete, window: &mut Window, cx: &mut Context<Self>) {
+        self.helix_delete_selections(window, cx);
+    }
+
+    pub fn helix_change(&mut self, _: &HelixChange, window: &mut Window, cx: &mut Context<Self>) {
+        self.helix_delete_selections(window, cx);
+        self.switch_mode(Mode::Insert, true, window, cx);
+    }
+
+    pub fn helix_collapse_selections(
+        &mut self,
+        _: &HelixCollapseSelections,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = helix_cursor(map, selection);
+                    selection.collapse_to(cursor, selection.goal)
+                })
+            });
+        });
+    }
+
+    pub fn helix_flip_selections(
+        &mut self,
+        _: &HelixFlipSelections,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.move_with(|_, selection| selection.swap_head_tail())
+            });
+        });
+    }
+
+    pub fn helix_keep_primary_selection(
+        &mut self,
+        _: &HelixKeepPrimarySelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                let primary = s.newest_anchor().clone();
+                s.select_anchors(vec![primary]);
+            });
+        });
+    }
+
+    pub fn helix_remove_primary_selection(
+        &mut self,
+        _: &HelixRemovePrimarySelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                let primary_id = s.newest_anchor().id;
+                let rest = s
+                    .disjoint_anchors()
+                    .iter()
+                    .filter(|selection| selection.id != primary_id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !rest.is_empty() {
+                    s.select_anchors(rest);
+                }
+            });
+        });
+    }
+
+    pub fn helix_yank(&mut self, _: &HelixYank, window: &mut Window, cx: &mut Context<Self>) {
+        self.update_editor(window, cx, |vim, editor, window, cx| {
+            let selections = editor.selections.disjoint_anchors();
+            Self::helix_expand_cursors(editor, window, cx);
+            vim.copy_selections_content(editor, false, cx);
+            editor.change_selections(None, window, cx, |s| s.select_anchors(selections.to_vec()));
+        });
+    }
+
+    /// `s` and `S` ask for a regex in the buffer search bar, which previews the
+    /// matches inside the selections until it is submitted.
+    fn helix_prompt_for_regex(
+        &mut self,
+        kind: HelixSelectRegex,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let prior_selections = self.editor_selections(window, cx);
+        pane.update(cx, |pane, cx| {
+            let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() else {
+                return;
+            };
+            search_bar.update(cx, |search_bar, cx| {
+                let deploy = buffer_search::Deploy {
+                    focus: true,
+                    replace_enabled: false,
+                    selection_search_enabled: true,
+                };
+                if !search_bar.deploy(&deploy, window, cx) {
+                    return;
+                }
+                search_bar.set_search_options(SearchOptions::REGEX, cx);
+                self.search = SearchState {
+                    prior_selections,
+                    prior_mode: self.mode,
+                    helix_select: Some(kind),
+                    ..Default::default()
+                };
+            });
+        });
+    }
+
+    pub(crate) fn helix_submit_regex(
+        &mut self,
+        kind: HelixSelectRegex,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let query = pane.update(cx, |pane, cx| {
+            let search_bar = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>()?;
+            Some(search_bar.update(cx, |search_bar, cx| {
+                let query = search_bar.query(cx);
+                search_bar.dismiss(&buffer_search::Dismiss, window, cx);
+                query
+            }))
+        });
+        let prior_selections = std::mem::take(&mut self.search.prior_selections);
+        let Some(query) = query else {
+            return;
+        };
+
+        let regex = Regex::new(&query).map_err(anyhow::Error::from);
+        let regex = match regex {
+            Ok(regex) => regex,
+            Err(error) => {
+                if let Some(workspace) = self.workspace(window) {
+                    workspace.update(cx, |workspace, cx| {
+                        Err::<(), _>(error).notify_err(workspace, cx);
+                    });
+                }
+                return;
+            }
+        };
+
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut ranges = Vec::new();
+            for range in &prior_selections {
+                let (start, end) = (
+                    range.start.to_offset(&snapshot),
+                    range.end.to_offset(&snapshot),
+                );
+                let (start, end) = (start.min(end), start.max(end));
+                let text = snapshot.text_for_range(start..end).collect::<String>();
+                let mut last_end = 0;
+                for found in regex.find_iter(&text) {
+                    match kind {
+                        HelixSelectRegex::Matches if !found.is_empty() => {
+                            ranges.push(start + found.start()..start + found.end())
+                        }
+                        HelixSelectRegex::Split if found.start() > last_end => {
+                            ranges.push(start + last_end..start + found.start())
+                        }
+                        _ => {}
+                    }
+                    last_end = found.end();
+                }
+                if kind == HelixSelectRegex::Split && last_end < text.len() {
+                    ranges.push(start + last_end..end);
+                }
+            }
+
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                // Like helix, keep the original selections if nothing matched.
+                if ranges.is_empty() {
+                    s.select_anchor_ranges(prior_selections);
+                } else {
+                    s.select_ranges(ranges);
+                }
+            });
+        });
+    }
 }
 
 #[cfg(test)]
 mod test {
     use indoc::indoc;
+    use settings::SettingsStore;
 
-    use crate::{state::Mode, test::VimTestContext};
+    use crate::{state::Mode, test::VimTestContext, VimSettings};
 
     #[gpui::test]
     async fn test_next_word_start(cx: &mut gpui::TestAppContext) {
@@ -380,4 +692,80 @@ mod test {
             Mode::HelixNormal,
         );
     }
+
+    #[gpui::test]
+    async fn test_helix_mode_setting(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| s.helix_mode = Some(true));
+        });
+
+        cx.set_state("The quick brownˇ", Mode::Insert);
+        cx.simulate_keystrokes("escape");
+        cx.assert_state("The quick browˇn", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_select_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The quˇick brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("v l l");
+        cx.assert_state("The qu«ickˇ» brown", Mode::HelixSelect);
+
+        cx.simulate_keystrokes("h h h");
+        cx.assert_state("The q«ˇui»ck brown", Mode::HelixSelect);
+
+        cx.simulate_keystrokes("escape");
+        cx.assert_state("The q«ˇui»ck brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes(";");
+        cx.assert_state("The qˇuick brown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_select_and_split_regex(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("«one, two, one threeˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("s o n e enter");
+        cx.assert_state("«oneˇ», two, «oneˇ» three", Mode::HelixNormal);
+
+        cx.set_state("«one, two, one threeˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-s , space enter");
+        cx.assert_state("«oneˇ», «twoˇ», «one threeˇ»", Mode::HelixNormal);
+
+        // nothing matches
+        cx.set_state("«one, twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("s t h r e e enter");
+        cx.assert_state("«one, twoˇ» three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_primary_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("«oneˇ» «twoˇ» «threeˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-,");
+        cx.assert_state("«oneˇ» «twoˇ» three", Mode::HelixNormal);
+
+        cx.simulate_keystrokes(",");
+        cx.assert_state("one «twoˇ» three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_match_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("call(one, tˇwo)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i (");
+        cx.assert_state("call(«one, twoˇ»)", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("m a (");
+        cx.assert_state("call«(one, two)ˇ»", Mode::HelixNormal);
+
+        cx.set_state("call(one, tˇwo)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i w");
+        cx.assert_state("call(one, «twoˇ»)", Mode::HelixNormal);
+    }
 }
@@ -0,0 +1,527 @@
+use std::sync::Arc;
+
+use editor::{Anchor, Editor, MultiBufferSnapshot, NavigationData, ToPoint};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    impl_internal_actions, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle,
+    Focusable, Render, Task, WeakEntity, Window,
+};
+use language::Point;
+use multi_buffer::MultiBufferRow;
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Pane, Workspace};
+
+use crate::{normal::paste::Paste, Vim};
+
+const MAX_PREVIEW_LEN: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Listing {
+    Registers,
+    Marks,
+    Jumps,
+    Changes,
+}
+
+/// Opens a picker over one of vim's lists, like `:registers` or `:marks`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ShowListing {
+    pub(crate) listing: Listing,
+}
+
+impl_internal_actions!(vim, [ShowListing]);
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, action: &ShowListing, window, cx| {
+        vim.show_listing(action.listing, window, cx)
+    });
+}
+
+enum ListingTarget {
+    Register(char),
+    Mark(String),
+    Jump {
+        pane: WeakEntity<Pane>,
+        depth: usize,
+    },
+    Change(usize),
+}
+
+struct ListingEntry {
+    name: SharedString,
+    preview: SharedString,
+    target: ListingTarget,
+}
+
+impl Vim {
+    fn show_listing(&mut self, listing: Listing, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(workspace) = self.workspace(window) else {
+            return;
+        };
+        let entries = match listing {
+            Listing::Registers => self.register_entries(cx),
+            Listing::Marks => self.mark_entries(cx),
+            Listing::Jumps => jump_entries(&workspace, cx),
+            Listing::Changes => self.change_entries(cx),
+        };
+        let vim = cx.entity().downgrade();
+        workspace.update(cx, |workspace, cx| {
+            let workspace_handle = cx.entity().downgrade();
+            workspace.toggle_modal(window, cx, |window, cx| {
+                ListingModal::new(listing, entries, vim, workspace_handle, window, cx)
+            });
+        })
+    }
+
+    fn register_entries(&mut self, cx: &mut Context<Self>) -> Vec<ListingEntry> {
+        let mut registers = Vim::globals(cx)
+            .registers
+            .iter()
+            .filter(|(_, register)| !register.text.is_empty())
+            .map(|(name, register)| (*name, register.text.clone()))
+            .collect::<Vec<_>>();
+        registers.sort_by_key(|(name, _)| (register_order(*name), *name));
+        registers
+            .into_iter()
+            .map(|(name, text)| ListingEntry {
+                name: format!("\"{name}").into(),
+                preview: preview(&text),
+                target: ListingTarget::Register(name),
+            })
+            .collect()
+    }
+
+    fn mark_entries(&mut self, cx: &mut Context<Self>) -> Vec<ListingEntry> {
+        self.load_persisted_marks(cx);
+        let Some(editor) = self.editor() else {
+            return Vec::new();
+        };
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let mut marks = self
+            .marks
+            .iter()
+            .filter_map(|(name, anchors)| Some((name.clone(), *anchors.first()?)))
+            .collect::<Vec<_>>();
+        marks.sort_by_key(|(name, _)| (name.chars().next().map_or(4, mark_order), name.clone()));
+        marks
+            .into_iter()
+            .map(|(name, anchor)| ListingEntry {
+                name: name.clone().into(),
+                preview: location_preview(&snapshot, anchor),
+                target: ListingTarget::Mark(name),
+            })
+            .collect()
+    }
+
+    fn change_entries(&mut self, cx: &mut Context<Self>) -> Vec<ListingEntry> {
+        let Some(editor) = self.editor() else {
+            return Vec::new();
+        };
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let len = self.change_list.len();
+        self.change_list
+            .iter()
+            .enumerate()
+            .rev()
+            .filter_map(|(ix, anchors)| {
+                Some(ListingEntry {
+                    name: (len - ix).to_string().into(),
+                    preview: location_preview(&snapshot, *anchors.first()?),
+                    target: ListingTarget::Change(ix),
+                })
+            })
+            .collect()
+    }
+}
+
+fn jump_entries(workspace: &Entity<Workspace>, cx: &App) -> Vec<ListingEntry> {
+    let pane = workspace.read(cx).active_pane().clone();
+    let mut entries = Vec::new();
+    pane.read(cx)
+        .nav_history()
+        .for_each_backward_entry(cx, |entry, (project_path, _)| {
+            let mut preview = project_path.path.to_string_lossy().to_string();
+            if let Some(data) = entry
+                .data
+                .as_ref()
+                .and_then(|data| data.downcast_ref::<NavigationData>())
+            {
+                let position = data.cursor_position();
+                preview = format!("{}:{}:{}", preview, position.row + 1, position.column + 1);
+            }
+            let depth = entries.len();
+            entries.push(ListingEntry {
+                name: (depth + 1).to_string().into(),
+                preview: preview.into(),
+                target: ListingTarget::Jump {
+                    pane: pane.downgrade(),
+                    depth,
+                },
+            });
+        });
+    entries
+}
+
+/// Lists registers in the order vim's `:registers` does.
+fn register_order(name: char) -> u8 {
+    match name {
+        '"' => 0,
+        '0'..='9' => 1,
+        'a'..='z' => 2,
+        '-' => 3,
+        _ => 4,
+    }
+}
+
+fn mark_order(name: char) -> u8 {
+    match name {
+        'a'..='z' => 0,
+        'A'..='Z' => 1,
+        '0'..='9' => 2,
+        _ => 3,
+    }
+}
+
+fn location_preview(snapshot: &MultiBufferSnapshot, anchor: Anchor) -> SharedString {
+    let point = anchor.to_point(snapshot);
+    let line_end = Point::new(point.row, snapshot.line_len(MultiBufferRow(point.row)));
+    let line = snapshot
+        .text_for_range(Point::new(point.row, 0)..line_end)
+        .collect::<String>();
+    format!(
+        "{}:{} {}",
+        point.row + 1,
+        point.column + 1,
+        preview(line.trim())
+    )
+    .into()
+}
+
+/// Renders text on a single line, showing newlines and tabs the way vim does.
+fn preview(text: &str) -> SharedString {
+    let mut preview = String::new();
+    for c in text.chars() {
+        if preview.len() >= MAX_PREVIEW_LEN {
+            preview.push('…');
+            break;
+        }
+        match c {
+            '\n' => preview.push_str("^J"),
+            '\t' => preview.push_str("^I"),
+            c => preview.push(c),
+        }
+    }
+    preview.into()
+}
+
+pub(crate) struct ListingModal {
+    picker: Entity<Picker<ListingDelegate>>,
+}
+
+impl ListingModal {
+    fn new(
+        listing: Listing,
+        entries: Vec<ListingEntry>,
+        vim: WeakEntity<Vim>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let candidates = entries
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| {
+                StringMatchCandidate::new(ix, &format!("{} {}", entry.name, entry.preview))
+            })
+            .collect();
+        let delegate = ListingDelegate {
+            listing,
+            modal: cx.entity().downgrade(),
+            vim,
+            workspace,
+            entries,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+impl Render for ListingModal {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for ListingModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for ListingModal {}
+impl ModalView for ListingModal {}
+
+pub(crate) struct ListingDelegate {
+    listing: Listing,
+    modal: WeakEntity<ListingModal>,
+    vim: WeakEntity<Vim>,
+    workspace: WeakEntity<Workspace>,
+    entries: Vec<ListingEntry>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for ListingDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.listing {
+            Listing::Registers => "Paste a register…".into(),
+            Listing::Marks => "Jump to a mark…".into(),
+            Listing::Jumps => "Jump back to a location…".into(),
+            Listing::Changes => "Jump to a change…".into(),
+        }
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> SharedString {
+        match self.listing {
+            Listing::Registers => "No registers".into(),
+            Listing::Marks => "No marks".into(),
+            Listing::Jumps => "No jumps".into(),
+            Listing::Changes => "No changes".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                // Keep the listing's own order rather than ordering by score.
+                matches.sort_by_key(|mat| mat.candidate_id);
+                matches
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.entries.get(mat.candidate_id))
+        else {
+            return;
+        };
+
+        match &entry.target {
+            ListingTarget::Register(register) => {
+                let register = *register;
+                self.vim
+                    .update(cx, |vim, cx| {
+                        vim.select_register(register.to_string().into(), window, cx);
+                        vim.paste(&Paste::default(), window, cx);
+                    })
+                    .log_err();
+            }
+            ListingTarget::Mark(name) => {
+                let name: Arc<str> = name.as_str().into();
+                self.vim
+                    .update(cx, |vim, cx| vim.jump_to_mark(name, false, window, cx))
+                    .log_err();
+            }
+            ListingTarget::Change(ix) => {
+                let ix = *ix;
+                self.vim
+                    .update(cx, |vim, cx| vim.go_to_change(ix, window, cx))
+                    .log_err();
+            }
+            ListingTarget::Jump { pane, depth } => {
+                let pane = pane.clone();
+                let depth = *depth;
+                let workspace = self.workspace.clone();
+                cx.spawn_in(window, |_, mut cx| async move {
+                    for _ in 0..=depth {
+                        workspace
+                            .update_in(&mut cx, |workspace, window, cx| {
+                                workspace.go_back(pane.clone(), window, cx)
+                            })?
+                            .await?;
+                    }
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
+            }
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.modal
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+
+        // Candidates are "{name} {preview}", so split the match positions between the two labels.
+        let name_len = entry.name.len();
+        let (name_positions, preview_positions): (Vec<usize>, Vec<usize>) = mat
+            .positions
+            .iter()
+            .partition(|position| **position < name_len);
+        let preview_positions = preview_positions
+            .into_iter()
+            .filter_map(|position| position.checked_sub(name_len + 1))
+            .collect();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            div()
+                                .w(rems(2.5))
+                                .flex_none()
+                                .child(HighlightedLabel::new(entry.name.clone(), name_positions)),
+                        )
+                        .child(
+                            HighlightedLabel::new(entry.preview.clone(), preview_positions)
+                                .color(Color::Muted)
+                                .single_line(),
+                        ),
+                ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use gpui::TestAppContext;
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    use super::ListingModal;
+
+    #[gpui::test]
+    async fn test_registers_listing(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇhello world", Mode::Normal);
+        cx.simulate_keystrokes("\" a y e");
+        cx.simulate_keystrokes(": r e g enter");
+        assert!(
+            cx.workspace(|workspace, _, cx| workspace.active_modal::<ListingModal>(cx).is_some())
+        );
+
+        cx.simulate_keystrokes("\" a enter");
+        assert!(
+            cx.workspace(|workspace, _, cx| workspace.active_modal::<ListingModal>(cx).is_none())
+        );
+        cx.assert_state("hhellˇoello world", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_marks_and_changes_listing(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                one
+                twˇo
+                three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("m a g g");
+        cx.simulate_keystrokes(": m a r k s enter");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+                one
+                twˇo
+                three"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("j x g g");
+        cx.simulate_keystrokes(": c h a n g e s enter");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+                one
+                two
+                ˇhree"},
+            Mode::Normal,
+        );
+    }
+}
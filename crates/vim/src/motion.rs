@@ -540,6 +540,9 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
 
 impl Vim {
     pub(crate) fn search_motion(&mut self, m: Motion, window: &mut Window, cx: &mut Context<Self>) {
+        if Vim::globals(cx).hide_search_highlights {
+            self.hide_search_highlights(window, cx);
+        }
         if let Motion::ZedSearchResult {
             prior_selections, ..
         } = &m
@@ -560,7 +563,7 @@ impl Vim {
                     }
                 }
 
-                Mode::HelixNormal => {}
+                Mode::HelixNormal | Mode::HelixSelect => {}
             }
         }
 
@@ -594,6 +597,7 @@ impl Vim {
             }
 
             Mode::HelixNormal => self.helix_normal_motion(motion.clone(), count, window, cx),
+            Mode::HelixSelect => self.helix_select_motion(motion.clone(), count, window, cx),
         }
         self.clear_operator(window, cx);
         if let Some(operator) = waiting_operator {
@@ -1,16 +1,19 @@
-use std::{ops::Range, sync::Arc};
+use std::{ops::Range, path::PathBuf, sync::Arc};
 
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint,
+    Anchor, Bias, DisplayPoint, Editor, ToPoint,
 };
-use gpui::{Context, Window};
-use language::SelectionGoal;
+use gpui::{App, Context, Window};
+use language::{Point, SelectionGoal};
+use util::ResultExt;
+use workspace::WorkspaceId;
 
 use crate::{
     motion::{self, Motion},
+    persistence::DB,
     state::Mode,
     Vim,
 };
@@ -33,10 +36,82 @@ impl Vim {
         }) else {
             return;
         };
+        if text.chars().all(|c| c.is_ascii_alphabetic()) {
+            self.serialize_mark(&text, &anchors, cx);
+        }
         self.marks.insert(text.to_string(), anchors);
         self.clear_operator(window, cx);
     }
 
+    /// Restores the marks saved for this buffer in the workspace database, in the
+    /// background. This only happens once the editor belongs to a workspace, so it's
+    /// started when the editor is focused.
+    pub(crate) fn load_persisted_marks(&mut self, cx: &mut Context<Self>) {
+        if self.persisted_marks_loaded {
+            return;
+        }
+        let Some(editor) = self.editor() else {
+            return;
+        };
+        let Some((workspace_id, path)) = mark_persistence_key(editor.read(cx), cx) else {
+            return;
+        };
+        self.persisted_marks_loaded = true;
+        cx.spawn(|this, mut cx| async move {
+            let marks = cx
+                .background_executor()
+                .spawn(async move { DB.get_marks(workspace_id, &path) })
+                .await
+                .log_err()?;
+            this.update(&mut cx, |this, cx| {
+                let snapshot = this.editor()?.read(cx).buffer().read(cx).snapshot(cx);
+                for (name, value) in marks {
+                    let Some(points) = serde_json::from_str::<Vec<(u32, u32)>>(&value).log_err()
+                    else {
+                        continue;
+                    };
+                    let anchors = points
+                        .into_iter()
+                        .map(|(row, column)| {
+                            let point = snapshot.clip_point(Point::new(row, column), Bias::Left);
+                            snapshot.anchor_before(point)
+                        })
+                        .collect();
+                    // Marks set before the saved ones were loaded take precedence.
+                    this.marks.entry(name).or_insert(anchors);
+                }
+                cx.notify();
+                Some(())
+            })
+            .ok()
+            .flatten()
+        })
+        .detach();
+    }
+
+    fn serialize_mark(&self, name: &str, anchors: &[Anchor], cx: &mut Context<Self>) {
+        let Some(editor) = self.editor() else {
+            return;
+        };
+        let editor = editor.read(cx);
+        let Some((workspace_id, path)) = mark_persistence_key(editor, cx) else {
+            return;
+        };
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let points = anchors
+            .iter()
+            .map(|anchor| {
+                let point = anchor.to_point(&snapshot);
+                (point.row, point.column)
+            })
+            .collect::<Vec<_>>();
+        let Some(value) = serde_json::to_string(&points).log_err() else {
+            return;
+        };
+        let name = name.to_string();
+        db::write_and_log(cx, move || DB.save_mark(workspace_id, path, name, value));
+    }
+
     // When handling an action, you must create visual marks if you will switch to normal
     // mode without the default selection behavior.
     pub(crate) fn store_visual_marks(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -84,6 +159,17 @@ impl Vim {
         cx: &mut Context<Self>,
     ) {
         self.pop_operator(window, cx);
+        self.jump_to_mark(text, line, window, cx);
+    }
+
+    pub(crate) fn jump_to_mark(
+        &mut self,
+        text: Arc<str>,
+        line: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.load_persisted_marks(cx);
 
         let anchors = match &*text {
             "{" | "}" => self.update_editor(window, cx, |_, editor, _, cx| {
@@ -144,6 +230,13 @@ impl Vim {
     }
 }
 
+fn mark_persistence_key(editor: &Editor, cx: &App) -> Option<(WorkspaceId, PathBuf)> {
+    let workspace_id = editor.workspace()?.read(cx).database_id()?;
+    let buffer = editor.buffer().read(cx).as_singleton()?;
+    let path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+    Some((workspace_id, path))
+}
+
 pub fn jump_motion(
     map: &DisplaySnapshot,
     anchor: Anchor,
@@ -8,7 +8,7 @@ use crate::{
     Vim,
 };
 use editor::Editor;
-use gpui::{actions, Action, App, Context, Window};
+use gpui::{actions, Action, App, Context, Entity, Window};
 use workspace::Workspace;
 
 actions!(vim, [Repeat, EndRepeat, ToggleRecord, ReplayLastRecording]);
@@ -40,7 +40,7 @@ fn repeatable_insert(action: &ReplayableAction) -> Option<Box<dyn Action>> {
                 None
             }
         }
-        ReplayableAction::Insertion { .. } => None,
+        ReplayableAction::Insertion { .. } | ReplayableAction::Keystroke(_) => None,
     }
 }
 
@@ -130,25 +130,41 @@ impl Replayer {
                 text,
                 utf16_range_to_replace,
             } => {
-                let Some(Some(workspace)) = window.root::<Workspace>() else {
-                    return;
-                };
-                let Some(editor) = workspace
-                    .read(cx)
-                    .active_item(cx)
-                    .and_then(|item| item.act_as::<Editor>(cx))
-                else {
+                let Some(editor) = active_editor(window, cx) else {
                     return;
                 };
                 editor.update(cx, |editor, cx| {
                     editor.replay_insert_event(&text, utf16_range_to_replace.clone(), window, cx)
                 })
             }
+            ReplayableAction::Keystroke(keystroke) => {
+                // Keys that nothing handles are typed straight into the editor, in case the
+                // platform input handler has not been handed back from the command palette.
+                if !window.dispatch_keystroke(keystroke.clone(), cx) {
+                    let keystroke = keystroke.with_simulated_ime();
+                    if let Some((text, editor)) = keystroke.key_char.zip(active_editor(window, cx))
+                    {
+                        editor.update(cx, |editor, cx| {
+                            editor.replay_insert_event(&text, None, window, cx)
+                        })
+                    }
+                }
+            }
         }
         window.defer(cx, move |window, cx| self.next(window, cx));
     }
 }
 
+fn active_editor(window: &mut Window, cx: &mut App) -> Option<Entity<Editor>> {
+    let Some(Some(workspace)) = window.root::<Workspace>() else {
+        return None;
+    };
+    workspace
+        .read(cx)
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+}
+
 impl Vim {
     pub(crate) fn record_register(
         &mut self,
@@ -148,7 +148,21 @@ impl Vim {
                     if action.regex {
                         options |= SearchOptions::REGEX;
                     }
+                    let (ignorecase, smartcase) = {
+                        let globals = Vim::globals(cx);
+                        (globals.ignorecase, globals.smartcase)
+                    };
+                    if ignorecase == Some(false) {
+                        options |= SearchOptions::CASE_SENSITIVE;
+                    }
                     search_bar.set_search_options(options, cx);
+                    // Like vim, `smartcase` only applies when `ignorecase` is set.
+                    if ignorecase.is_some() || smartcase.is_some() {
+                        search_bar.set_smartcase_override(match ignorecase {
+                            Some(false) => Some(false),
+                            _ => smartcase,
+                        });
+                    }
                     let prior_mode = if self.temp_mode {
                         Mode::Insert
                     } else {
@@ -175,6 +189,10 @@ impl Vim {
     }
 
     pub fn search_submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(kind) = self.search.helix_select.take() {
+            self.helix_submit_regex(kind, window, cx);
+            return;
+        }
         self.store_visual_marks(window, cx);
         let Some(pane) = self.pane(window, cx) else {
             return;
@@ -238,6 +256,20 @@ impl Vim {
         );
     }
 
+    /// With `:set nohlsearch`, matches are only shown while typing the search.
+    pub(crate) fn hide_search_highlights(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        pane.update(cx, |pane, cx| {
+            if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.dismiss(&buffer_search::Dismiss, window, cx)
+                });
+            }
+        });
+    }
+
     pub fn move_to_match_internal(
         &mut self,
         direction: Direction,
@@ -191,8 +191,11 @@ impl Vim {
         }
 
         let selected_register = self.selected_register.take();
+        let workspace_id = editor
+            .workspace()
+            .and_then(|workspace| workspace.read(cx).database_id());
         Vim::update_globals(cx, |globals, cx| {
-            globals.write_registers(
+            let written = globals.write_registers(
                 Register {
                     text: text.into(),
                     clipboard_selections: Some(clipboard_selections),
@@ -201,7 +204,10 @@ impl Vim {
                 is_yank,
                 linewise,
                 cx,
-            )
+            );
+            if let Some(workspace_id) = workspace_id {
+                globals.serialize_registers(written, workspace_id, cx);
+            }
         });
 
         let highlight_duration = VimSettings::get_global(cx).highlight_on_yank_duration;
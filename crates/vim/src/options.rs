@@ -0,0 +1,367 @@
+use std::num::NonZeroU32;
+
+use anyhow::{anyhow, Result};
+use editor::Editor;
+use gpui::{impl_internal_actions, Action, App, Context, Global, Window};
+use language::language_settings::{LanguageSettingsOverrides, ShowWhitespaceSetting, SoftWrap};
+use workspace::notifications::NotifyResultExt;
+
+use crate::Vim;
+
+/// An option that can be changed with `:set`.
+///
+/// `number`, `relativenumber` and `wrap` are applied to the current editor,
+/// `tabstop`, `shiftwidth`, `expandtab` and `list` to the current buffer, and
+/// `ignorecase`, `smartcase` and `hlsearch` everywhere.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum VimOption {
+    Number(bool),
+    RelativeNumber(bool),
+    Wrap(bool),
+    IgnoreCase(bool),
+    SmartCase(bool),
+    HlSearch(bool),
+    TabStop(u32),
+    ShiftWidth(u32),
+    ExpandTab(bool),
+    List(bool),
+}
+
+impl VimOption {
+    /// Parses the arguments to `:set`, e.g. `nowrap ts=4 sw=4 et`.
+    pub fn parse_all(args: &str) -> Result<Vec<Self>> {
+        let options = args
+            .split_whitespace()
+            .map(Self::parse)
+            .collect::<Result<Vec<_>>>()?;
+        if options.is_empty() {
+            return Err(anyhow!("no options given"));
+        }
+        Ok(options)
+    }
+
+    fn parse(arg: &str) -> Result<Self> {
+        if let Some((name, value)) = arg.split_once(['=', ':']) {
+            let value = value
+                .parse::<u32>()
+                .ok()
+                .filter(|value| *value > 0)
+                .ok_or_else(|| anyhow!("invalid value for {name}: {value}"))?;
+            return match name {
+                "tabstop" | "ts" => Ok(Self::TabStop(value)),
+                "shiftwidth" | "sw" => Ok(Self::ShiftWidth(value)),
+                _ => Err(anyhow!("unknown number option: {name}")),
+            };
+        }
+
+        let (name, enabled) = match arg.strip_prefix("no") {
+            Some(name) if name != "number" && name != "nu" => (name, false),
+            _ => (arg, true),
+        };
+        match name {
+            "number" | "nu" => Ok(Self::Number(enabled)),
+            "relativenumber" | "rnu" => Ok(Self::RelativeNumber(enabled)),
+            "wrap" => Ok(Self::Wrap(enabled)),
+            "ignorecase" | "ic" => Ok(Self::IgnoreCase(enabled)),
+            "smartcase" | "scs" => Ok(Self::SmartCase(enabled)),
+            "hlsearch" | "hls" => Ok(Self::HlSearch(enabled)),
+            "expandtab" | "et" => Ok(Self::ExpandTab(enabled)),
+            "list" => Ok(Self::List(enabled)),
+            _ => Err(anyhow!("unknown option: {arg}")),
+        }
+    }
+
+    pub fn is_global(&self) -> bool {
+        matches!(
+            self,
+            Self::IgnoreCase(_) | Self::SmartCase(_) | Self::HlSearch(_)
+        )
+    }
+
+    /// Applies an option that isn't tied to an editor. These are kept by vim
+    /// for the rest of the session, and don't change any settings.
+    pub fn apply_global(&self, cx: &mut App) {
+        let globals = Vim::globals(cx);
+        match *self {
+            Self::IgnoreCase(enabled) => globals.ignorecase = Some(enabled),
+            Self::SmartCase(enabled) => globals.smartcase = Some(enabled),
+            Self::HlSearch(enabled) => globals.hide_search_highlights = !enabled,
+            _ => {}
+        }
+    }
+
+    fn apply_to_editor(&self, editor: &mut Editor, cx: &mut Context<Editor>) {
+        match *self {
+            Self::Number(enabled) => editor.set_show_line_numbers(enabled, cx),
+            Self::RelativeNumber(enabled) => editor.set_relative_line_number(Some(enabled), cx),
+            Self::Wrap(enabled) => editor.set_soft_wrap_mode(
+                if enabled {
+                    SoftWrap::EditorWidth
+                } else {
+                    SoftWrap::None
+                },
+                cx,
+            ),
+            _ => {}
+        }
+    }
+
+    fn apply_to_buffer(&self, overrides: &mut LanguageSettingsOverrides) {
+        match *self {
+            Self::TabStop(size) => overrides.tab_size = NonZeroU32::new(size),
+            Self::ShiftWidth(size) => overrides.indent_size = NonZeroU32::new(size),
+            Self::ExpandTab(enabled) => overrides.hard_tabs = Some(!enabled),
+            Self::List(enabled) => {
+                overrides.show_whitespaces = Some(if enabled {
+                    ShowWhitespaceSetting::All
+                } else {
+                    ShowWhitespaceSetting::Selection
+                })
+            }
+            _ => {}
+        }
+    }
+
+    fn is_buffer_local(&self) -> bool {
+        matches!(
+            self,
+            Self::TabStop(_) | Self::ShiftWidth(_) | Self::ExpandTab(_) | Self::List(_)
+        )
+    }
+}
+
+/// The options set in the vimrc, which every vim editor starts with.
+#[derive(Default)]
+pub(crate) struct DefaultOptions(Vec<VimOption>);
+
+impl Global for DefaultOptions {}
+
+impl DefaultOptions {
+    /// Replaces the default options, applying the global ones right away.
+    pub fn set(options: &[VimOption], cx: &mut App) {
+        for option in options.iter().filter(|option| option.is_global()) {
+            option.apply_global(cx);
+        }
+        let options = options
+            .iter()
+            .filter(|option| !option.is_global())
+            .cloned()
+            .collect();
+        cx.set_global(Self(options));
+    }
+
+    /// Applies the default options to an editor and its buffers. Buffer options
+    /// that have already been set for a buffer are kept unless `replace` is true.
+    pub fn apply(editor: &mut Editor, replace: bool, cx: &mut Context<Editor>) {
+        let Some(defaults) = cx.try_global::<Self>() else {
+            return;
+        };
+        let options = defaults.0.clone();
+        for option in &options {
+            option.apply_to_editor(editor, cx);
+        }
+
+        let mut defaults = LanguageSettingsOverrides::default();
+        for option in &options {
+            option.apply_to_buffer(&mut defaults);
+        }
+        if defaults == LanguageSettingsOverrides::default() {
+            return;
+        }
+        for buffer in editor.buffer().read(cx).all_buffers() {
+            buffer.update(cx, |buffer, cx| {
+                let current = *buffer.settings_overrides();
+                let overrides = if replace {
+                    defaults.or(current)
+                } else {
+                    current.or(defaults)
+                };
+                buffer.set_settings_overrides(overrides, cx);
+            });
+        }
+    }
+}
+
+/// `:set` and `:setlocal`. Options that vim keeps per window or per buffer only
+/// change the current editor and the buffer under the cursor.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SetOptions {
+    options: Vec<VimOption>,
+}
+
+impl_internal_actions!(vim, [SetOptions]);
+
+impl SetOptions {
+    pub fn parse(query: &str) -> Option<Box<dyn Action>> {
+        let (command, args) = query.split_once(' ').unwrap_or((query, ""));
+        if !matches!(
+            command,
+            "se" | "set" | "setl" | "setlo" | "setloc" | "setloca" | "setlocal"
+        ) {
+            return None;
+        }
+        let options = VimOption::parse_all(args).ok()?;
+        Some(Self { options }.boxed_clone())
+    }
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, action: &SetOptions, window, cx| {
+        vim.set_options_and_notify(&action.options, window, cx)
+    });
+}
+
+impl Vim {
+    pub(crate) fn set_options(
+        &mut self,
+        options: &[VimOption],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        for option in options.iter().filter(|option| option.is_global()) {
+            option.apply_global(cx);
+        }
+
+        self.update_editor(window, cx, |_, editor, _, cx| {
+            for option in options {
+                option.apply_to_editor(editor, cx);
+            }
+            if !options.iter().any(VimOption::is_buffer_local) {
+                return Ok(());
+            }
+
+            let cursor = editor.selections.newest::<usize>(cx).head();
+            let Some((buffer, _)) = editor.buffer().read(cx).point_to_buffer_offset(cursor, cx)
+            else {
+                return Err(anyhow!("no buffer to set options for"));
+            };
+            buffer.update(cx, |buffer, cx| {
+                let mut overrides = *buffer.settings_overrides();
+                for option in options {
+                    option.apply_to_buffer(&mut overrides);
+                }
+                buffer.set_settings_overrides(overrides, cx);
+            });
+            cx.notify();
+            Ok(())
+        })
+        .unwrap_or(Ok(()))
+    }
+
+    pub(crate) fn set_options_and_notify(
+        &mut self,
+        options: &[VimOption],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let result = self.set_options(options, window, cx);
+        if result.is_err() {
+            let Some(workspace) = self.workspace(window) else {
+                return;
+            };
+            workspace.update(cx, |workspace, cx| {
+                result.notify_err(workspace, cx);
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use editor::EditorSettings;
+    use gpui::TestAppContext;
+    use language::language_settings::{language_settings, ShowWhitespaceSetting};
+    use settings::Settings;
+
+    use crate::{
+        options::{DefaultOptions, VimOption},
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+        Vim,
+    };
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(
+            VimOption::parse_all("nu nowrap ts=2 sw:4 noet").unwrap(),
+            vec![
+                VimOption::Number(true),
+                VimOption::Wrap(false),
+                VimOption::TabStop(2),
+                VimOption::ShiftWidth(4),
+                VimOption::ExpandTab(false),
+            ]
+        );
+        assert_eq!(
+            VimOption::parse_all("nonumber").unwrap(),
+            vec![VimOption::Number(false)]
+        );
+        assert!(VimOption::parse_all("ts=0").is_err());
+        assert!(VimOption::parse_all("textwidth=80").is_err());
+        assert!(VimOption::parse_all("").is_err());
+    }
+
+    #[gpui::test]
+    async fn test_set_buffer_options(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa", Mode::Normal);
+        cx.simulate_keystrokes(
+            ": s e t space t s = 2 space s w = 8 space n o e t space l i s t enter",
+        );
+        cx.update_editor(|editor, _, cx| {
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            let buffer = buffer.read(cx);
+            let settings = buffer.settings_at(0, cx);
+            assert_eq!(settings.tab_size.get(), 2);
+            assert_eq!(settings.indent_size().get(), 8);
+            assert!(settings.hard_tabs);
+            assert_eq!(settings.show_whitespaces, ShowWhitespaceSetting::All);
+
+            // The settings themselves are left alone.
+            let settings = language_settings(None, buffer.file(), cx);
+            assert_eq!(settings.tab_size.get(), 4);
+            assert!(!settings.hard_tabs);
+        });
+
+        cx.simulate_keystrokes(": s e t space e t enter");
+        cx.update_editor(|editor, _, cx| {
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            let settings = buffer.read(cx).settings_at(0, cx);
+            assert_eq!(settings.tab_size.get(), 2);
+            assert_eq!(settings.indent_size().get(), 8);
+            assert!(!settings.hard_tabs);
+        });
+
+        cx.set_state("ˇa", Mode::Normal);
+        cx.simulate_keystrokes("> >");
+        cx.assert_state("        ˇa", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_set_search_options(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇone Two two").await;
+        cx.simulate_shared_keystrokes(": s e t space n o i c enter")
+            .await;
+        cx.simulate_shared_keystrokes("/ t w o enter").await;
+        cx.shared_state().await.assert_eq("one Two ˇtwo");
+
+        cx.update(|_, cx| assert!(!EditorSettings::get_global(cx).search.case_sensitive));
+    }
+
+    #[gpui::test]
+    async fn test_default_options(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa", Mode::Normal);
+        cx.update(|_, cx| DefaultOptions::set(&VimOption::parse_all("ts=3 ic").unwrap(), cx));
+        cx.update_editor(|editor, _, cx| {
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            assert_eq!(buffer.read(cx).settings_at(0, cx).tab_size.get(), 3);
+            assert_eq!(Vim::globals(cx).ignorecase, Some(true));
+            assert!(!EditorSettings::get_global(cx).search.case_sensitive);
+        });
+    }
+}
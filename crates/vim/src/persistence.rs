@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // vim_marks(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   mark_name: String,
+    //   value: String, // JSON array of [row, column] points, one per cursor
+    // )
+    //
+    // vim_registers(
+    //   workspace_id: usize,
+    //   register_name: String,
+    //   text: String,
+    //   clipboard_selections: Option<String>, // JSON array of editor::ClipboardSelection
+    // )
+    pub static ref DB: VimDb<WorkspaceDb> = &[
+        sql! (
+            CREATE TABLE vim_marks(
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                mark_name TEXT NOT NULL,
+                value TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, path, mark_name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE vim_registers(
+                workspace_id INTEGER NOT NULL,
+                register_name TEXT NOT NULL,
+                text TEXT NOT NULL,
+                clipboard_selections TEXT,
+                PRIMARY KEY(workspace_id, register_name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        ),
+    ];
+);
+
+impl VimDb {
+    query! {
+        pub fn get_marks(workspace_id: WorkspaceId, path: &Path) -> Result<Vec<(String, String)>> {
+            SELECT mark_name, value FROM vim_marks
+            WHERE workspace_id = ? AND path = ?
+        }
+    }
+
+    query! {
+        pub async fn save_mark(workspace_id: WorkspaceId, path: PathBuf, mark_name: String, value: String) -> Result<()> {
+            INSERT INTO vim_marks
+                (workspace_id, path, mark_name, value)
+            VALUES
+                (?1, ?2, ?3, ?4)
+            ON CONFLICT DO UPDATE SET
+                value = ?4
+        }
+    }
+
+    query! {
+        pub fn get_registers(workspace_id: WorkspaceId) -> Result<Vec<(String, String, Option<String>)>> {
+            SELECT register_name, text, clipboard_selections FROM vim_registers
+            WHERE workspace_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_register(workspace_id: WorkspaceId, register_name: String, text: String, clipboard_selections: Option<String>) -> Result<()> {
+            INSERT INTO vim_registers
+                (workspace_id, register_name, text, clipboard_selections)
+            VALUES
+                (?1, ?2, ?3, ?4)
+            ON CONFLICT DO UPDATE SET
+                text = ?3,
+                clipboard_selections = ?4
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_and_get_marks_and_registers() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let path = PathBuf::from("/root/dir/file.rs");
+
+        DB.save_mark(workspace_id, path.clone(), "a".into(), "[[1,2]]".into())
+            .await
+            .unwrap();
+        DB.save_mark(workspace_id, path.clone(), "a".into(), "[[3,4]]".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_marks(workspace_id, &path).unwrap(),
+            vec![("a".to_string(), "[[3,4]]".to_string())]
+        );
+        assert!(DB
+            .get_marks(workspace_id, Path::new("/root/dir/other.rs"))
+            .unwrap()
+            .is_empty());
+
+        DB.save_register(workspace_id, "q".into(), "hello".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_registers(workspace_id).unwrap(),
+            vec![("q".to_string(), "hello".to_string(), None)]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_load_registers_once() {
+        let first = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let second = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        DB.save_register(first, "a".into(), "first".into(), None)
+            .await
+            .unwrap();
+        DB.save_register(second, "a".into(), "second".into(), None)
+            .await
+            .unwrap();
+        DB.save_register(second, "b".into(), "second".into(), None)
+            .await
+            .unwrap();
+
+        // Opening another workspace keeps the registers of the session.
+        let mut globals = crate::state::VimGlobals::default();
+        globals.load_registers(first);
+        globals.load_registers(second);
+        assert_eq!(globals.registers[&'a'].text.to_string(), "first");
+        assert!(!globals.registers.contains_key(&'b'));
+    }
+}
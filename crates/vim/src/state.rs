@@ -1,5 +1,6 @@
 use crate::command::command_interceptor;
 use crate::normal::repeat::Replayer;
+use crate::persistence::DB;
 use crate::surrounds::SurroundsType;
 use crate::{motion::Motion, object::Object};
 use crate::{UseSystemClipboard, Vim, VimSettings};
@@ -7,7 +8,8 @@ use collections::HashMap;
 use command_palette_hooks::{CommandPaletteFilter, CommandPaletteInterceptor};
 use editor::{Anchor, ClipboardSelection, Editor};
 use gpui::{
-    Action, App, BorrowAppContext, ClipboardEntry, ClipboardItem, Entity, Global, WeakEntity,
+    Action, App, BorrowAppContext, ClipboardEntry, ClipboardItem, Entity, Global, Keystroke,
+    WeakEntity,
 };
 use language::Point;
 use schemars::JsonSchema;
@@ -16,7 +18,8 @@ use settings::{Settings, SettingsStore};
 use std::borrow::BorrowMut;
 use std::{fmt::Display, ops::Range, sync::Arc};
 use ui::{Context, SharedString};
-use workspace::searchable::Direction;
+use util::ResultExt;
+use workspace::{searchable::Direction, WorkspaceId};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, JsonSchema, Serialize)]
 pub enum Mode {
@@ -27,6 +30,7 @@ pub enum Mode {
     VisualLine,
     VisualBlock,
     HelixNormal,
+    HelixSelect,
 }
 
 impl Display for Mode {
@@ -39,6 +43,7 @@ impl Display for Mode {
             Mode::VisualLine => write!(f, "VISUAL LINE"),
             Mode::VisualBlock => write!(f, "VISUAL BLOCK"),
             Mode::HelixNormal => write!(f, "HELIX NORMAL"),
+            Mode::HelixSelect => write!(f, "HELIX SELECT"),
         }
     }
 }
@@ -48,7 +53,7 @@ impl Mode {
         match self {
             Mode::Normal | Mode::Insert | Mode::Replace => false,
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => true,
-            Mode::HelixNormal => false,
+            Mode::HelixNormal | Mode::HelixSelect => false,
         }
     }
 }
@@ -198,9 +203,14 @@ pub struct VimGlobals {
 
     pub last_yank: Option<SharedString>,
     pub registers: HashMap<char, Register>,
+    pub registers_loaded: bool,
     pub recordings: HashMap<char, Vec<ReplayableAction>>,
 
     pub focused_vim: Option<WeakEntity<Vim>>,
+
+    pub hide_search_highlights: bool,
+    pub ignorecase: Option<bool>,
+    pub smartcase: Option<bool>,
 }
 impl Global for VimGlobals {}
 
@@ -237,6 +247,8 @@ impl VimGlobals {
         .detach();
     }
 
+    /// Writes `content` to the given register, or to the unnamed register and the
+    /// registers that follow it, and returns the names of the registers written.
     pub(crate) fn write_registers(
         &mut self,
         content: Register,
@@ -244,7 +256,8 @@ impl VimGlobals {
         is_yank: bool,
         linewise: bool,
         cx: &mut Context<Editor>,
-    ) {
+    ) -> Vec<char> {
+        let mut written = Vec::new();
         if let Some(register) = register {
             let lower = register.to_lowercase().next().unwrap_or(register);
             if lower != register {
@@ -254,15 +267,18 @@ impl VimGlobals {
                 current.clipboard_selections.take();
                 let yanked = current.clone();
                 self.registers.insert('"', yanked);
+                written.extend([lower, '"']);
             } else {
                 match lower {
                     '_' | ':' | '.' | '%' | '#' | '=' | '/' => {}
                     '+' => {
                         self.registers.insert('"', content.clone());
+                        written.push('"');
                         cx.write_to_clipboard(content.into());
                     }
                     '*' => {
                         self.registers.insert('"', content.clone());
+                        written.push('"');
                         #[cfg(any(target_os = "linux", target_os = "freebsd"))]
                         cx.write_to_primary(content.into());
                         #[cfg(not(any(target_os = "linux", target_os = "freebsd")))]
@@ -271,10 +287,12 @@ impl VimGlobals {
                     '"' => {
                         self.registers.insert('"', content.clone());
                         self.registers.insert('0', content);
+                        written.extend(['"', '0']);
                     }
                     _ => {
                         self.registers.insert('"', content.clone());
                         self.registers.insert(lower, content);
+                        written.extend(['"', lower]);
                     }
                 }
             }
@@ -292,16 +310,20 @@ impl VimGlobals {
             }
 
             self.registers.insert('"', content.clone());
+            written.push('"');
             if is_yank {
                 self.registers.insert('0', content);
+                written.push('0');
             } else {
                 let contains_newline = content.text.contains('\n');
                 if !contains_newline {
                     self.registers.insert('-', content.clone());
+                    written.push('-');
                 }
                 if linewise || contains_newline {
                     let mut content = content;
                     for i in '1'..'8' {
+                        written.push(i);
                         if let Some(moved) = self.registers.insert(i, content) {
                             content = moved;
                         } else {
@@ -311,6 +333,7 @@ impl VimGlobals {
                 }
             }
         }
+        written
     }
 
     pub(crate) fn read_register(
@@ -362,6 +385,72 @@ impl VimGlobals {
         }
     }
 
+    /// Registers that are saved to the workspace database, like Neovim's shada.
+    pub(crate) fn is_persisted_register(register: char) -> bool {
+        register == '"'
+            || register == '-'
+            || register.is_ascii_digit()
+            || register.is_ascii_lowercase()
+    }
+
+    /// Loads the registers saved by the first workspace opened in this session.
+    /// Registers are shared by all workspaces, so later workspaces don't load theirs.
+    pub(crate) fn load_registers(&mut self, workspace_id: WorkspaceId) {
+        if self.registers_loaded {
+            return;
+        }
+        self.registers_loaded = true;
+        let Some(registers) = DB.get_registers(workspace_id).log_err() else {
+            return;
+        };
+        for (name, text, clipboard_selections) in registers {
+            let Some(name) = name.chars().next() else {
+                continue;
+            };
+            // Registers written before the workspace was loaded take precedence over saved ones.
+            self.registers.entry(name).or_insert_with(|| Register {
+                text: text.into(),
+                clipboard_selections: clipboard_selections
+                    .and_then(|selections| serde_json::from_str(&selections).log_err()),
+            });
+        }
+    }
+
+    /// Saves the given registers to the workspace database.
+    pub(crate) fn serialize_registers(
+        &self,
+        names: impl IntoIterator<Item = char>,
+        workspace_id: WorkspaceId,
+        cx: &App,
+    ) {
+        let registers = names
+            .into_iter()
+            .filter(|name| Self::is_persisted_register(*name))
+            .filter_map(|name| {
+                let register = self.registers.get(&name)?;
+                let clipboard_selections = register
+                    .clipboard_selections
+                    .as_ref()
+                    .and_then(|selections| serde_json::to_string(selections).log_err());
+                Some((
+                    name.to_string(),
+                    register.text.to_string(),
+                    clipboard_selections,
+                ))
+            })
+            .collect::<Vec<_>>();
+        if registers.is_empty() {
+            return;
+        }
+        db::write_and_log(cx, move || async move {
+            for (name, text, clipboard_selections) in registers {
+                DB.save_register(workspace_id, name, text, clipboard_selections)
+                    .await?;
+            }
+            Ok(())
+        });
+    }
+
     fn system_clipboard_is_newer(&self, cx: &mut Context<Editor>) -> bool {
         cx.read_from_clipboard().is_some_and(|item| {
             if let Some(last_state) = &self.last_yank {
@@ -444,6 +533,7 @@ pub enum ReplayableAction {
         text: Arc<str>,
         utf16_range_to_replace: Option<Range<isize>>,
     },
+    Keystroke(Keystroke),
 }
 
 impl Clone for ReplayableAction {
@@ -457,6 +547,7 @@ impl Clone for ReplayableAction {
                 text: text.clone(),
                 utf16_range_to_replace: utf16_range_to_replace.clone(),
             },
+            Self::Keystroke(keystroke) => Self::Keystroke(keystroke.clone()),
         }
     }
 }
@@ -470,6 +561,16 @@ pub struct SearchState {
     pub prior_selections: Vec<Range<Anchor>>,
     pub prior_operator: Option<Operator>,
     pub prior_mode: Mode,
+    pub helix_select: Option<HelixSelectRegex>,
+}
+
+/// Set while the search bar is used to enter the regex for Helix's `s` or `S`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HelixSelectRegex {
+    /// Select each match inside the selections.
+    Matches,
+    /// Split the selections on each match.
+    Split,
 }
 
 impl Operator {
@@ -9,14 +9,18 @@ mod digraph;
 mod helix;
 mod indent;
 mod insert;
+mod listings;
 mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod options;
+mod persistence;
 mod replace;
 mod rewrap;
 mod state;
 mod surrounds;
+mod vimrc;
 mod visual;
 
 use anyhow::Result;
@@ -44,9 +48,10 @@ use surrounds::SurroundsType;
 use theme::ThemeSettings;
 use ui::{px, IntoElement, SharedString};
 use vim_mode_setting::VimModeSetting;
+pub use vimrc::{handle_vimrc_file_changes, vimrc_key_bindings, VimrcKeyMappings};
 use workspace::{self, Pane, ResizeIntent, Workspace};
 
-use crate::state::ReplayableAction;
+use crate::{options::DefaultOptions, state::ReplayableAction};
 
 /// Used to resize the current pane
 #[derive(Clone, Deserialize, JsonSchema, PartialEq)]
@@ -103,7 +108,13 @@ pub fn init(cx: &mut App) {
 
     cx.observe_new(Vim::register).detach();
 
-    cx.observe_new(|workspace: &mut Workspace, _, _| {
+    cx.observe_new(|workspace: &mut Workspace, _, cx| {
+        if let Some(workspace_id) = workspace.database_id() {
+            if Vim::enabled(cx) {
+                Vim::globals(cx).load_registers(workspace_id);
+            }
+        }
+
         workspace.register_action(|workspace, _: &ToggleVimMode, _, cx| {
             let fs = workspace.app_state().fs.clone();
             let currently_enabled = Vim::enabled(cx);
@@ -211,6 +222,7 @@ pub(crate) struct Vim {
     pub(crate) stored_visual_mode: Option<(Mode, Vec<bool>)>,
     pub(crate) change_list: Vec<Vec<Anchor>>,
     pub(crate) change_list_position: Option<usize>,
+    pub(crate) persisted_marks_loaded: bool,
 
     pub(crate) current_tx: Option<TransactionId>,
     pub(crate) current_anchor: Option<Selection<Anchor>>,
@@ -247,9 +259,10 @@ impl Vim {
     pub fn new(window: &mut Window, cx: &mut Context<Editor>) -> Entity<Self> {
         let editor = cx.entity().clone();
 
+        let mode = Vim::normal_mode(cx);
         cx.new(|cx| Vim {
-            mode: Mode::Normal,
-            last_mode: Mode::Normal,
+            mode,
+            last_mode: mode,
             temp_mode: false,
             exit_temporary_mode: false,
             operator_stack: Vec::new(),
@@ -259,6 +272,7 @@ impl Vim {
             stored_visual_mode: None,
             change_list: Vec::new(),
             change_list_position: None,
+            persisted_marks_loaded: false,
             current_tx: None,
             current_anchor: None,
             undo_modes: HashMap::default(),
@@ -276,6 +290,11 @@ impl Vim {
                 cx.subscribe_in(&editor, window, |this, _, event, window, cx| {
                     this.handle_editor_event(event, window, cx)
                 }),
+                cx.observe_global::<DefaultOptions>(|this, cx| {
+                    if let Some(editor) = this.editor() {
+                        editor.update(cx, |editor, cx| DefaultOptions::apply(editor, true, cx));
+                    }
+                }),
             ],
         })
     }
@@ -325,6 +344,7 @@ impl Vim {
         let vim = Vim::new(window, cx);
 
         editor.register_addon(VimAddon { model: vim.clone() });
+        DefaultOptions::apply(editor, false, cx);
 
         vim.update(cx, |_, cx| {
             Vim::action(editor, cx, |vim, action: &SwitchMode, window, cx| {
@@ -353,12 +373,15 @@ impl Vim {
             helix::register(editor, cx);
             motion::register(editor, cx);
             command::register(editor, cx);
+            options::register(editor, cx);
+            vimrc::register(editor, cx);
             replace::register(editor, cx);
             indent::register(editor, cx);
             rewrap::register(editor, cx);
             object::register(editor, cx);
             visual::register(editor, cx);
             change_list::register(editor, cx);
+            listings::register(editor, cx);
             digraph::register(editor, cx);
 
             cx.defer_in(window, |vim, window, cx| {
@@ -524,6 +547,16 @@ impl Vim {
         self.sync_vim_settings(window, cx);
     }
 
+    /// The mode that editors start in, and that vim returns to after
+    /// leaving insert or visual mode.
+    pub fn normal_mode(cx: &App) -> Mode {
+        if VimSettings::get_global(cx).helix_mode {
+            Mode::HelixNormal
+        } else {
+            Mode::Normal
+        }
+    }
+
     pub fn switch_mode(
         &mut self,
         mode: Mode,
@@ -541,6 +574,11 @@ impl Vim {
         {
             self.temp_mode = false;
         }
+        let mode = if mode == Mode::Normal {
+            Vim::normal_mode(cx)
+        } else {
+            mode
+        };
 
         let last_mode = self.mode;
         let prior_mode = self.last_mode;
@@ -682,9 +720,11 @@ impl Vim {
                 }
             }
             Mode::Replace => CursorShape::Underline,
-            Mode::HelixNormal | Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
-                CursorShape::Block
-            }
+            Mode::HelixNormal
+            | Mode::HelixSelect
+            | Mode::Visual
+            | Mode::VisualLine
+            | Mode::VisualBlock => CursorShape::Block,
             Mode::Insert => CursorShape::Bar,
         }
     }
@@ -700,6 +740,7 @@ impl Vim {
             }
             Mode::Normal
             | Mode::HelixNormal
+            | Mode::HelixSelect
             | Mode::Replace
             | Mode::Visual
             | Mode::VisualLine
@@ -718,7 +759,8 @@ impl Vim {
             | Mode::VisualLine
             | Mode::VisualBlock
             | Mode::Replace
-            | Mode::HelixNormal => false,
+            | Mode::HelixNormal
+            | Mode::HelixSelect => false,
             Mode::Normal => true,
         }
     }
@@ -730,6 +772,7 @@ impl Vim {
             Mode::Insert => "insert",
             Mode::Replace => "replace",
             Mode::HelixNormal => "helix_normal",
+            Mode::HelixSelect => "helix_select",
         }
         .to_string();
 
@@ -794,6 +837,7 @@ impl Vim {
 
         cx.emit(VimEvent::Focused);
         self.sync_vim_settings(window, cx);
+        self.load_persisted_marks(cx);
 
         if VimSettings::get_global(cx).toggle_relative_line_numbers {
             if let Some(old_vim) = Vim::globals(cx).focused_vim() {
@@ -1069,7 +1113,7 @@ impl Vim {
                     })
                 });
             }
-            Mode::Insert | Mode::Replace | Mode::HelixNormal => {}
+            Mode::Insert | Mode::Replace | Mode::HelixNormal | Mode::HelixSelect => {}
         }
     }
 
@@ -1313,6 +1357,7 @@ struct VimSettings {
     pub use_smartcase_find: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
     pub highlight_on_yank_duration: u64,
+    pub helix_mode: bool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -1323,6 +1368,7 @@ struct VimSettingsContent {
     pub use_smartcase_find: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
     pub highlight_on_yank_duration: Option<u64>,
+    pub helix_mode: Option<bool>,
 }
 
 impl Settings for VimSettings {
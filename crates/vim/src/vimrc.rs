@@ -0,0 +1,496 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use editor::Editor;
+use fs::Fs as _;
+use futures::{channel::mpsc, StreamExt};
+use gpui::{impl_internal_actions, Action, App, AsyncApp, Context, Global, KeyBinding, Window};
+use serde_json::{json, Map, Value};
+use settings::{KeymapFile, KeymapFileLoadResult};
+use util::ResultExt;
+use workspace::notifications::NotifyResultExt;
+
+use crate::{
+    command::command_interceptor,
+    options::{DefaultOptions, VimOption},
+    Vim,
+};
+
+/// A file of ex commands in the style of a `.vimrc`.
+///
+/// Only `:set`, `:let mapleader` and the `:map` family are understood when the
+/// file is loaded at startup; `:source` also runs any other ex command.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ExCommandFile {
+    pub options: Vec<VimOption>,
+    pub mappings: Vec<KeyMapping>,
+    pub commands: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// A `:map` translated to a binding for `workspace::SendKeystrokes`.
+/// Zed always resolves the sent keystrokes with the current bindings, so
+/// `:noremap` and `:map` behave the same.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct KeyMapping {
+    context: &'static str,
+    keystrokes: String,
+    /// `None` for a mapping to `<Nop>`, which unbinds the keystrokes.
+    target: Option<String>,
+}
+
+impl ExCommandFile {
+    pub fn parse(content: &str) -> Self {
+        let mut file = Self::default();
+        let mut leader = "\\".to_string();
+
+        for (ix, line) in content.lines().enumerate() {
+            let line = line.trim_start().trim_start_matches(':').trim();
+            if line.is_empty() || line.starts_with('"') {
+                continue;
+            }
+            let (command, args) = line
+                .split_once(char::is_whitespace)
+                .map(|(command, args)| (command, args.trim()))
+                .unwrap_or((line, ""));
+
+            let result = if command == "let" {
+                parse_leader(args).map(|value| leader = value)
+            } else if matches!(command, "se" | "set" | "setl" | "setlocal") {
+                VimOption::parse_all(args).map(|options| file.options.extend(options))
+            } else if let Some(context) = map_context(command) {
+                KeyMapping::parse(context, args, &leader).map(|mapping| file.mappings.push(mapping))
+            } else {
+                file.commands.push(line.to_string());
+                Ok(())
+            };
+            if let Err(error) = result {
+                file.errors.push(format!("line {}: {error}", ix + 1));
+            }
+        }
+        file
+    }
+}
+
+fn parse_leader(args: &str) -> Result<String> {
+    let value = args
+        .strip_prefix("mapleader")
+        .map(|rest| rest.trim_start())
+        .and_then(|rest| rest.strip_prefix('='))
+        .ok_or_else(|| anyhow!("only `let mapleader` is supported"))?
+        .trim();
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .filter(|value| !value.is_empty())
+        .map(|value| value.replace("\\\\", "\\"))
+        .ok_or_else(|| anyhow!("invalid mapleader: {value}"))
+}
+
+fn map_context(command: &str) -> Option<&'static str> {
+    let command = command.strip_suffix('!').unwrap_or(command);
+    Some(match command {
+        "map" | "no" | "noremap" => "VimControl && !menu",
+        "nm" | "nmap" | "nn" | "nnoremap" => "vim_mode == normal && !menu",
+        "vm" | "vmap" | "vn" | "vnoremap" | "xm" | "xmap" | "xn" | "xnoremap" => {
+            "vim_mode == visual && !menu"
+        }
+        "om" | "omap" | "ono" | "onoremap" => "vim_mode == operator && !menu",
+        "im" | "imap" | "ino" | "inoremap" => "vim_mode == insert && !menu",
+        _ => return None,
+    })
+}
+
+impl KeyMapping {
+    fn parse(context: &'static str, mut args: &str, leader: &str) -> Result<Self> {
+        loop {
+            if let Some(rest) = ["<silent>", "<buffer>", "<nowait>", "<unique>"]
+                .iter()
+                .find_map(|arg| args.strip_prefix(arg))
+            {
+                args = rest.trim_start();
+            } else if args.starts_with("<expr>") {
+                return Err(anyhow!("<expr> mappings are not supported"));
+            } else {
+                break;
+            }
+        }
+        let (lhs, rhs) = args
+            .split_once(char::is_whitespace)
+            .map(|(lhs, rhs)| (lhs, rhs.trim()))
+            .filter(|(_, rhs)| !rhs.is_empty())
+            .ok_or_else(|| anyhow!("expected a mapping, got: {args}"))?;
+
+        Ok(Self {
+            context,
+            keystrokes: translate_keys(lhs, leader)?,
+            target: if rhs.eq_ignore_ascii_case("<nop>") {
+                None
+            } else {
+                Some(translate_keys(rhs, leader)?)
+            },
+        })
+    }
+
+    fn keymap_json(mappings: &[Self]) -> String {
+        let mut sections: Vec<(&str, Map<String, Value>)> = Vec::new();
+        for mapping in mappings {
+            let ix = match sections
+                .iter()
+                .position(|(context, _)| *context == mapping.context)
+            {
+                Some(ix) => ix,
+                None => {
+                    sections.push((mapping.context, Map::new()));
+                    sections.len() - 1
+                }
+            };
+            let action = match &mapping.target {
+                Some(target) => json!(["workspace::SendKeystrokes", target]),
+                None => Value::Null,
+            };
+            sections[ix].1.insert(mapping.keystrokes.clone(), action);
+        }
+        Value::Array(
+            sections
+                .into_iter()
+                .map(|(context, bindings)| json!({ "context": context, "bindings": bindings }))
+                .collect(),
+        )
+        .to_string()
+    }
+}
+
+/// Converts vim key notation (e.g. `<C-w>j`, `<leader>ff`) to zed keystrokes
+/// (e.g. `ctrl-w j`, `space f f`).
+fn translate_keys(keys: &str, leader: &str) -> Result<String> {
+    let mut keystrokes = Vec::new();
+    let mut rest = keys;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some((notation, after)) = rest[1..].split_once('>') {
+                if notation.eq_ignore_ascii_case("leader") {
+                    for c in leader.chars() {
+                        keystrokes.push(translate_char(c));
+                    }
+                    rest = after;
+                    continue;
+                }
+                if let Some(keystroke) = translate_notation(notation) {
+                    keystrokes.push(keystroke);
+                    rest = after;
+                    continue;
+                }
+            }
+        }
+        keystrokes.push(translate_char(c));
+        rest = &rest[c.len_utf8()..];
+    }
+    if keystrokes.is_empty() {
+        return Err(anyhow!("no keys given"));
+    }
+    Ok(keystrokes.join(" "))
+}
+
+fn translate_char(c: char) -> String {
+    match c {
+        ' ' => "space".to_string(),
+        '\t' => "tab".to_string(),
+        c if c.is_ascii_uppercase() => format!("shift-{}", c.to_ascii_lowercase()),
+        c => c.to_string(),
+    }
+}
+
+fn translate_notation(notation: &str) -> Option<String> {
+    let mut modifiers = String::new();
+    let mut key = notation;
+    while let Some((modifier, rest)) = key.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+        modifiers.push_str(match modifier.to_ascii_lowercase().as_str() {
+            "c" => "ctrl-",
+            "s" => "shift-",
+            "a" | "m" => "alt-",
+            "d" => "cmd-",
+            _ => return None,
+        });
+        key = rest;
+    }
+
+    let name = match key.to_ascii_lowercase().as_str() {
+        "cr" | "enter" | "return" => "enter".to_string(),
+        "esc" => "escape".to_string(),
+        "space" => "space".to_string(),
+        "tab" => "tab".to_string(),
+        "bs" => "backspace".to_string(),
+        "del" => "delete".to_string(),
+        "lt" => "<".to_string(),
+        "bar" => "|".to_string(),
+        "bslash" => "\\".to_string(),
+        name @ ("up" | "down" | "left" | "right" | "home" | "end" | "pageup" | "pagedown") => {
+            name.to_string()
+        }
+        name if name.len() > 1
+            && name.starts_with('f')
+            && name[1..].parse::<u8>().is_ok_and(|n| (1..=12).contains(&n)) =>
+        {
+            name.to_string()
+        }
+        // vim ignores the case of letters used with ctrl.
+        _ if !modifiers.is_empty() && key.chars().count() == 1 => {
+            let c = key.chars().next()?;
+            if modifiers.contains("ctrl-") {
+                c.to_ascii_lowercase().to_string()
+            } else {
+                return Some(format!("{modifiers}{}", translate_char(c)));
+            }
+        }
+        _ => return None,
+    };
+    Some(format!("{modifiers}{name}"))
+}
+
+/// The key bindings from the startup file and from `:source`, re-bound whenever
+/// the keymap is reloaded.
+#[derive(Default)]
+pub struct VimrcKeyMappings {
+    startup: Vec<KeyMapping>,
+    sourced: Vec<KeyMapping>,
+}
+
+impl Global for VimrcKeyMappings {}
+
+/// Returns the key bindings defined with `:map` in the vimrc file and in files
+/// run with `:source`.
+pub fn vimrc_key_bindings(cx: &App) -> Vec<KeyBinding> {
+    let Some(mappings) = cx.try_global::<VimrcKeyMappings>() else {
+        return Vec::new();
+    };
+    let all = mappings
+        .startup
+        .iter()
+        .chain(mappings.sourced.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+    load_key_bindings(&all, cx)
+}
+
+fn load_key_bindings(mappings: &[KeyMapping], cx: &App) -> Vec<KeyBinding> {
+    match KeymapFile::load(&KeyMapping::keymap_json(mappings), cx) {
+        KeymapFileLoadResult::Success { key_bindings } => key_bindings,
+        KeymapFileLoadResult::SomeFailedToLoad {
+            key_bindings,
+            error_message,
+        } => {
+            log::error!("failed to load vim mappings: {}", error_message.0);
+            key_bindings
+        }
+        KeymapFileLoadResult::JsonParseFailure { error } => {
+            log::error!("failed to load vim mappings: {error}");
+            Vec::new()
+        }
+    }
+}
+
+/// Applies the vimrc file (`~/.config/zed/vimrc`) each time it changes.
+/// Options are applied to every vim editor, and mappings replace those from the
+/// previous version of the file.
+pub fn handle_vimrc_file_changes(mut vimrc_file_rx: mpsc::UnboundedReceiver<String>, cx: &mut App) {
+    cx.spawn(|cx: AsyncApp| async move {
+        while let Some(content) = vimrc_file_rx.next().await {
+            let file = ExCommandFile::parse(&content);
+            cx.update(|cx| {
+                for error in &file.errors {
+                    log::error!("vimrc: {error}");
+                }
+                for command in &file.commands {
+                    log::warn!("vimrc: ignoring unsupported command at startup: {command}");
+                }
+                DefaultOptions::set(&file.options, cx);
+                cx.default_global::<VimrcKeyMappings>().startup = file.mappings;
+            })
+            .log_err();
+        }
+    })
+    .detach();
+}
+
+/// `:so[urce] {file}` runs each line of a file as an ex command.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SourceFile {
+    path: String,
+}
+
+impl_internal_actions!(vim, [SourceFile]);
+
+impl SourceFile {
+    pub fn parse(query: &str) -> Option<Box<dyn Action>> {
+        let (command, path) = query.split_once(char::is_whitespace)?;
+        if !matches!(command, "so" | "sou" | "sour" | "sourc" | "source") {
+            return None;
+        }
+        let path = path.trim();
+        if path.is_empty() {
+            return None;
+        }
+        Some(
+            Self {
+                path: path.to_string(),
+            }
+            .boxed_clone(),
+        )
+    }
+
+    fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let Some(workspace) = vim.workspace(window) else {
+            return;
+        };
+        let (fs, path) = workspace.update(cx, |workspace, cx| {
+            let path = if let Some(rest) = self.path.strip_prefix("~/") {
+                util::paths::home_dir().join(rest)
+            } else {
+                let path = PathBuf::from(&self.path);
+                match workspace.project().read(cx).visible_worktrees(cx).next() {
+                    Some(worktree) if path.is_relative() => worktree.read(cx).abs_path().join(path),
+                    _ => path,
+                }
+            };
+            (workspace.app_state().fs.clone(), path)
+        });
+
+        cx.spawn_in(window, |vim, mut cx| async move {
+            let content = fs.load(&path).await;
+            vim.update_in(&mut cx, |vim, window, cx| {
+                let result = content.and_then(|content| vim.source(&content, window, cx));
+                if result.is_err() {
+                    if let Some(workspace) = vim.workspace(window) {
+                        workspace.update(cx, |workspace, cx| {
+                            result.notify_err(workspace, cx);
+                        });
+                    }
+                }
+            })
+            .log_err();
+        })
+        .detach();
+    }
+}
+
+impl Vim {
+    fn source(&mut self, content: &str, window: &mut Window, cx: &mut Context<Self>) -> Result<()> {
+        let file = ExCommandFile::parse(content);
+        if !file.options.is_empty() {
+            self.set_options(&file.options, window, cx)?;
+        }
+        if !file.mappings.is_empty() {
+            let key_bindings = load_key_bindings(&file.mappings, cx);
+            cx.bind_keys(key_bindings);
+            cx.default_global::<VimrcKeyMappings>()
+                .sourced
+                .extend(file.mappings);
+        }
+        for command in &file.commands {
+            let Some(result) = command_interceptor(command, cx) else {
+                return Err(anyhow!("unknown command: {command}"));
+            };
+            window.dispatch_action(result.action, cx);
+        }
+        match file.errors.first() {
+            Some(error) => Err(anyhow!("{error}")),
+            None => Ok(()),
+        }
+    }
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, action: &SourceFile, window, cx| {
+        action.run(vim, window, cx)
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use gpui::TestAppContext;
+
+    use crate::{
+        options::VimOption,
+        state::Mode,
+        test::VimTestContext,
+        vimrc::{translate_keys, ExCommandFile, KeyMapping},
+        VimAddon,
+    };
+
+    #[test]
+    fn test_translate_keys() {
+        assert_eq!(translate_keys("<C-w>j", "\\").unwrap(), "ctrl-w j");
+        assert_eq!(
+            translate_keys("<leader>fF", " ").unwrap(),
+            "space f shift-f"
+        );
+        assert_eq!(translate_keys(":w<CR>", "\\").unwrap(), ": w enter");
+        assert_eq!(translate_keys("<S-Tab><lt>", "\\").unwrap(), "shift-tab <");
+        assert_eq!(translate_keys("<C-S-K>", "\\").unwrap(), "ctrl-shift-k");
+        assert_eq!(translate_keys("<foo>", "\\").unwrap(), "< f o o >");
+    }
+
+    #[test]
+    fn test_parse_ex_command_file() {
+        let file = ExCommandFile::parse(
+            r#"
+            " comment
+            let mapleader = ","
+            set nu ts=4
+            nnoremap <silent> <leader>w :w<CR>
+            inoremap jk <Esc>
+            map Q <Nop>
+            :echo "hi"
+            set bogus
+            nmap <expr> j x
+            "#,
+        );
+        assert_eq!(
+            file.options,
+            vec![VimOption::Number(true), VimOption::TabStop(4)]
+        );
+        assert_eq!(
+            file.mappings,
+            vec![
+                KeyMapping {
+                    context: "vim_mode == normal && !menu",
+                    keystrokes: ", w".into(),
+                    target: Some(": w enter".into()),
+                },
+                KeyMapping {
+                    context: "vim_mode == insert && !menu",
+                    keystrokes: "j k".into(),
+                    target: Some("escape".into()),
+                },
+                KeyMapping {
+                    context: "VimControl && !menu",
+                    keystrokes: "shift-q".into(),
+                    target: None,
+                },
+            ]
+        );
+        assert_eq!(file.commands, vec!["echo \"hi\"".to_string()]);
+        assert_eq!(file.errors.len(), 2);
+    }
+
+    #[gpui::test]
+    async fn test_source_mappings(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\ntwo\nthree", Mode::Normal);
+        let vim = cx.update_editor(|editor, _, _| editor.addon::<VimAddon>().cloned().unwrap());
+        cx.update(|window, cx| {
+            vim.model.update(cx, |vim, cx| {
+                vim.source("nnoremap Q jj\nset nonu", window, cx).unwrap();
+            });
+        });
+        cx.simulate_keystrokes("shift-q");
+        cx.assert_state("one\ntwo\nˇthree", Mode::Normal);
+    }
+}
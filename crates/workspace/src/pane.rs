@@ -3316,14 +3316,26 @@ impl NavHistory {
             .chain(borrowed_history.backward_stack.iter())
             .chain(borrowed_history.closed_stack.iter())
             .for_each(|entry| {
-                if let Some(project_and_abs_path) =
-                    borrowed_history.paths_by_item.get(&entry.item.id())
-                {
-                    f(entry, project_and_abs_path.clone());
-                } else if let Some(item) = entry.item.upgrade() {
-                    if let Some(path) = item.project_path(cx) {
-                        f(entry, (path, None));
-                    }
+                if let Some(path) = borrowed_history.path_for_entry(entry, cx) {
+                    f(entry, path);
+                }
+            })
+    }
+
+    /// Calls `f` for each entry that going back would visit, most recent first.
+    pub fn for_each_backward_entry(
+        &self,
+        cx: &App,
+        mut f: impl FnMut(&NavigationEntry, (ProjectPath, Option<PathBuf>)),
+    ) {
+        let borrowed_history = self.0.lock();
+        borrowed_history
+            .backward_stack
+            .iter()
+            .rev()
+            .for_each(|entry| {
+                if let Some(path) = borrowed_history.path_for_entry(entry, cx) {
+                    f(entry, path);
                 }
             })
     }
@@ -3440,6 +3452,19 @@ impl NavHistory {
 }
 
 impl NavHistoryState {
+    fn path_for_entry(
+        &self,
+        entry: &NavigationEntry,
+        cx: &App,
+    ) -> Option<(ProjectPath, Option<PathBuf>)> {
+        if let Some(project_and_abs_path) = self.paths_by_item.get(&entry.item.id()) {
+            Some(project_and_abs_path.clone())
+        } else {
+            let path = entry.item.upgrade()?.project_path(cx)?;
+            Some((path, None))
+        }
+    }
+
     pub fn did_update(&self, cx: &mut App) {
         if let Some(pane) = self.pane.upgrade() {
             cx.defer(move |cx| {
@@ -1,5 +1,8 @@
+use std::rc::Rc;
+
+use futures::FutureExt as _;
 use gpui::Context;
-use project::TaskSourceKind;
+use project::{task_store::TaskGraph, TaskSourceKind};
 use remote::ConnectionState;
 use task::{ResolvedTask, TaskContext, TaskTemplate};
 
@@ -42,26 +45,137 @@ pub fn schedule_task(
 pub fn schedule_resolved_task(
     workspace: &mut Workspace,
     task_source_kind: TaskSourceKind,
-    mut resolved_task: ResolvedTask,
+    resolved_task: ResolvedTask,
+    omit_history: bool,
+    cx: &mut Context<Workspace>,
+) {
+    schedule_resolved_task_with_inputs(
+        workspace,
+        task_source_kind,
+        resolved_task,
+        Vec::new(),
+        omit_history,
+        cx,
+    );
+}
+
+/// Schedules the task with the values the user provided for the inputs of the task and of the tasks it depends on.
+/// If any of them still misses a value, the user is asked for all missing values at once instead.
+pub fn schedule_resolved_task_with_inputs(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    resolved_task: ResolvedTask,
+    input_values: Vec<(String, String)>,
     omit_history: bool,
     cx: &mut Context<Workspace>,
 ) {
-    if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
+    let task_graph = if resolved_task.original_task().depends_on.is_empty() {
+        TaskGraph {
+            task_source_kind: task_source_kind.clone(),
+            task: resolved_task,
+            dependencies: Vec::new(),
+        }
+    } else {
+        let Some(task_inventory) = workspace
+            .project()
+            .read(cx)
+            .task_store()
+            .read(cx)
+            .task_inventory()
+            .cloned()
+        else {
+            return;
+        };
+        match task_inventory
+            .read(cx)
+            .task_graph(&task_source_kind, resolved_task)
+        {
+            Ok(task_graph) => task_graph,
+            Err(error) => {
+                workspace.show_error(&error, cx);
+                return;
+            }
+        }
+    };
+    let Some(mut task_graph) = task_graph.with_inputs(&input_values) else {
+        return;
+    };
+
+    let missing_inputs = task_graph.missing_inputs();
+    if !missing_inputs.is_empty() {
+        cx.emit(crate::Event::PromptTaskInputs {
+            task_source_kind,
+            task: Box::new(task_graph.task),
+            inputs: missing_inputs,
+            omit_history,
+        });
+        return;
+    }
+
+    if let Some(spawn_in_terminal) = task_graph.task.resolved.clone() {
         if !omit_history {
-            resolved_task.resolved = Some(spawn_in_terminal.clone());
             workspace.project().update(cx, |project, cx| {
                 if let Some(task_inventory) =
                     project.task_store().read(cx).task_inventory().cloned()
                 {
                     task_inventory.update(cx, |inventory, _| {
-                        inventory.task_scheduled(task_source_kind, resolved_task);
+                        inventory.task_scheduled(task_source_kind.clone(), task_graph.task.clone());
                     })
                 }
             });
         }
 
-        cx.emit(crate::Event::SpawnTask {
-            action: Box::new(spawn_in_terminal),
-        });
+        if task_graph.dependencies.is_empty() {
+            cx.emit(crate::Event::SpawnTask {
+                action: Box::new(spawn_in_terminal),
+            });
+        } else {
+            schedule_task_graph(workspace, task_graph, cx);
+        }
     }
 }
+
+/// Spawns the dependencies of the task first, then the task itself, as long as all of them succeed.
+fn schedule_task_graph(
+    workspace: &mut Workspace,
+    task_graph: TaskGraph,
+    cx: &mut Context<Workspace>,
+) {
+    let task_store = workspace.project().read(cx).task_store().clone();
+    cx.spawn(|workspace, mut cx| async move {
+        let spawn = Rc::new({
+            let workspace = workspace.clone();
+            let cx = cx.clone();
+            move |mut task: ResolvedTask| {
+                let mut cx = cx.clone();
+                let workspace = workspace.clone();
+                let task_store = task_store.clone();
+                async move {
+                    let spawn_in_terminal = task.resolved.take()?;
+                    let completion_rx = task_store
+                        .update(&mut cx, |task_store, _| {
+                            task_store.task_completion(spawn_in_terminal.id.clone())
+                        })
+                        .ok()?;
+                    workspace
+                        .update(&mut cx, |_, cx| {
+                            cx.emit(crate::Event::SpawnTask {
+                                action: Box::new(spawn_in_terminal),
+                            })
+                        })
+                        .ok()?;
+                    completion_rx.await.ok()
+                }
+                .map(|success| success.unwrap_or(false))
+                .boxed_local()
+            }
+        });
+        if let Err(error) = task_graph.run(spawn).await {
+            log::error!("{error:#}");
+            workspace
+                .update(&mut cx, |workspace, cx| workspace.show_error(&error, cx))
+                .ok();
+        }
+    })
+    .detach();
+}
@@ -59,7 +59,8 @@ use persistence::{
 };
 use postage::stream::Stream;
 use project::{
-    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
+    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, TaskSourceKind, Worktree,
+    WorktreeId,
 };
 use remote::{ssh_session::ConnectionIdentifier, SshClientDelegate, SshConnectionOptions};
 use schemars::JsonSchema;
@@ -86,7 +87,7 @@ use std::{
     sync::{atomic::AtomicUsize, Arc, LazyLock, Weak},
     time::Duration,
 };
-use task::SpawnInTerminal;
+use task::{ResolvedTask, SpawnInTerminal, TaskInput};
 use theme::{ActiveTheme, SystemAppearance, ThemeSettings};
 pub use toolbar::{Toolbar, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
 pub use ui;
@@ -747,6 +748,14 @@ pub enum Event {
     SpawnTask {
         action: Box<SpawnInTerminal>,
     },
+    /// The task needs the user to provide values for its inputs, or the inputs of the tasks it depends on,
+    /// before it can be scheduled.
+    PromptTaskInputs {
+        task_source_kind: TaskSourceKind,
+        task: Box<ResolvedTask>,
+        inputs: Vec<TaskInput>,
+        omit_history: bool,
+    },
     OpenBundledFile {
         text: Cow<'static, str>,
         title: &'static str,
@@ -248,6 +248,11 @@ fn main() {
         fs.clone(),
         paths::keymap_file().clone(),
     );
+    let vimrc_file_rx = watch_config_file(
+        &app.background_executor(),
+        fs.clone(),
+        paths::vimrc_file().clone(),
+    );
 
     #[cfg(unix)]
     if !stdout_is_a_pty() {
@@ -488,11 +493,13 @@ fn main() {
         project_panel::init(Assets, cx);
         git_ui::git_panel::init(cx);
         outline_panel::init(Assets, cx);
+        call_hierarchy_panel::init(cx);
         tasks_ui::init(cx);
         snippets_ui::init(cx);
         channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
         search::init(cx);
         vim::init(cx);
+        vim::handle_vimrc_file_changes(vimrc_file_rx, cx);
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
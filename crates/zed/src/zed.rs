@@ -14,6 +14,7 @@ pub use app_menus::*;
 use assets::Assets;
 use assistant_context_editor::AssistantPanelDelegate;
 use breadcrumbs::Breadcrumbs;
+use call_hierarchy_panel::CallHierarchyPanel;
 use client::{zed_urls, ZED_URL_SCHEME};
 use collections::VecDeque;
 use command_palette_hooks::CommandPaletteFilter;
@@ -372,6 +373,7 @@ fn initialize_panels(
     cx.spawn_in(window, |workspace_handle, mut cx| async move {
         let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
         let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
+        let call_hierarchy_panel = CallHierarchyPanel::load(workspace_handle.clone(), cx.clone());
         let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
         let channels_panel =
             collab_ui::collab_panel::CollabPanel::load(workspace_handle.clone(), cx.clone());
@@ -385,6 +387,7 @@ fn initialize_panels(
         let (
             project_panel,
             outline_panel,
+            call_hierarchy_panel,
             terminal_panel,
             channels_panel,
             chat_panel,
@@ -392,6 +395,7 @@ fn initialize_panels(
         ) = futures::try_join!(
             project_panel,
             outline_panel,
+            call_hierarchy_panel,
             terminal_panel,
             channels_panel,
             chat_panel,
@@ -401,6 +405,7 @@ fn initialize_panels(
         workspace_handle.update_in(&mut cx, |workspace, window, cx| {
             workspace.add_panel(project_panel, window, cx);
             workspace.add_panel(outline_panel, window, cx);
+            workspace.add_panel(call_hierarchy_panel, window, cx);
             workspace.add_panel(terminal_panel, window, cx);
             workspace.add_panel(channels_panel, window, cx);
             workspace.add_panel(chat_panel, window, cx);
@@ -1105,6 +1110,7 @@ pub fn handle_keymap_file_changes(
     let (keyboard_layout_tx, mut keyboard_layout_rx) = mpsc::unbounded();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
     let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
+    let vimrc_keymap_tx = base_keymap_tx.clone();
     cx.observe_global::<SettingsStore>(move |cx| {
         let new_base_keymap = *BaseKeymap::get_global(cx);
         let new_vim_enabled = VimModeSetting::get_global(cx).0;
@@ -1117,6 +1123,11 @@ pub fn handle_keymap_file_changes(
     })
     .detach();
 
+    cx.observe_global::<vim::VimrcKeyMappings>(move |_| {
+        vimrc_keymap_tx.unbounded_send(()).ok();
+    })
+    .detach();
+
     let mut current_mapping = settings::get_key_equivalents(cx.keyboard_layout());
     cx.on_keyboard_layout_change(move |cx| {
         let next_mapping = settings::get_key_equivalents(cx.keyboard_layout());
@@ -1259,6 +1270,10 @@ pub fn load_default_keymap(cx: &mut App) {
     if let Some(asset_path) = base_keymap.asset_path() {
         cx.bind_keys(KeymapFile::load_asset(asset_path, cx).unwrap());
     }
+
+    if VimModeSetting::get_global(cx).0 {
+        cx.bind_keys(vim::vimrc_key_bindings(cx));
+    }
 }
 
 pub fn handle_settings_changed(error: Option<anyhow::Error>, cx: &mut App) {
@@ -4009,6 +4024,7 @@ mod tests {
             git_ui::init(cx);
             project_panel::init((), cx);
             outline_panel::init((), cx);
+            call_hierarchy_panel::init(cx);
             terminal_view::init(cx);
             copilot::copilot_chat::init(
                 app_state.fs.clone(),